@@ -1,3 +1,5 @@
 fn main() {
+    // Only the desktop app has a Tauri config to build
+    #[cfg(feature = "gui")]
     tauri_build::build()
-}
\ No newline at end of file
+}
@@ -1,9 +1,21 @@
 use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use futures::stream::{self, StreamExt};
 use reqwest;
+use sha2::{Digest, Sha256};
+use crate::ffmpeg_processor::{AudioAnalysis, VOLUME_WINDOW_SECONDS};
 use crate::speech_recognition::TranscriptSegment;
+use crate::VideoNugget;
+use crate::youtube_api::VideoComment;
+use crate::project_manager::{EventType, ProcessingEvent};
+use crate::error::{http_error_code, status_error_code, AppError, ErrorCode};
+use crate::project_crypto::{read_data, write_data, DataKey};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ContentAnalysis {
     pub summary: String,
     pub key_topics: Vec<String>,
@@ -13,9 +25,77 @@ pub struct ContentAnalysis {
     pub highlight_moments: Vec<HighlightMoment>,
     pub content_categories: Vec<String>,
     pub difficulty_level: String,
+    // Derived from timed transcript segments; sentiment_score stays as the overall aggregate
+    #[serde(default)]
+    pub sentiment_timeline: Vec<SentimentPoint>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    // What viewers ask and want next, from the video's comments, see analyze_comments
+    #[serde(default)]
+    pub audience_insights: Option<AudienceInsights>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl ContentAnalysis {
+    // The category the uploader picked on YouTube goes ahead of the ones read from the content
+    pub fn add_source_category(&mut self, category: &str) {
+        self.content_categories.retain(|existing| !existing.eq_ignore_ascii_case(category));
+        self.content_categories.insert(0, category.to_string());
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AudienceInsights {
+    // Most asked first
+    pub questions: Vec<AudienceRequest>,
+    pub requested_topics: Vec<AudienceRequest>,
+    pub comments_analyzed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AudienceRequest {
+    pub text: String,
+    // Comments asking the same, as far as the model or the heuristics could tell
+    pub mentions: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SeoMetadata {
+    pub keywords: Vec<RankedKeyword>,
+    pub description_draft: String,
+    pub hashtags: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RankedKeyword {
+    pub keyword: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TitleCardSuggestion {
+    pub nugget_id: String,
+    pub thumbnail_variants: Vec<String>,
+    pub title_card: String,
+}
+
+// A question and answer for studying, e.g. in an Anki deck
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Flashcard {
+    pub question: String,
+    pub answer: String,
+    // The nugget the answer comes from, for a link back to the source
+    pub nugget_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SentimentPoint {
+    pub start_time: f64,
+    pub end_time: f64,
+    pub sentiment: f64,
+    pub engagement: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HighlightMoment {
     pub start_time: f64,
     pub end_time: f64,
@@ -24,7 +104,7 @@ pub struct HighlightMoment {
     pub moment_type: MomentType,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum MomentType {
     KeyPoint,
     Question,
@@ -36,7 +116,7 @@ pub enum MomentType {
     Controversy,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AIConfig {
     pub openai_api_key: Option<String>,
     pub claude_api_key: Option<String>,
@@ -45,9 +125,248 @@ pub struct AIConfig {
     pub enable_sentiment_analysis: bool,
     pub enable_topic_extraction: bool,
     pub enable_highlight_detection: bool,
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+    // Any OpenAI-compatible gateway, e.g. https://openrouter.ai/api/v1 or http://localhost:1234/v1
+    #[serde(default)]
+    pub openai_base_url: Option<String>,
+    #[serde(default)]
+    pub openai_model: Option<String>,
+    #[serde(default)]
+    pub claude_model: Option<String>,
+    #[serde(default)]
+    pub gemini_model: Option<String>,
+    #[serde(default = "default_max_chunk_chars")]
+    pub max_chunk_chars: usize,
+    #[serde(default = "default_max_parallel_chunks")]
+    pub max_parallel_chunks: usize,
+    // Language code or name for generated text, e.g. "es" or "German"; None keeps English
+    #[serde(default)]
+    pub output_language: Option<String>,
+}
+
+impl Default for AIConfig {
+    fn default() -> Self {
+        Self {
+            openai_api_key: None,
+            claude_api_key: None,
+            gemini_api_key: None,
+            model_preference: AIModel::Local,
+            enable_sentiment_analysis: true,
+            enable_topic_extraction: true,
+            enable_highlight_detection: true,
+            retry_policy: RetryPolicy::default(),
+            openai_base_url: None,
+            openai_model: None,
+            claude_model: None,
+            gemini_model: None,
+            max_chunk_chars: default_max_chunk_chars(),
+            max_parallel_chunks: default_max_parallel_chunks(),
+            output_language: None,
+        }
+    }
+}
+
+fn default_max_chunk_chars() -> usize {
+    // Roughly 12k tokens, which fits every supported model with room for the prompt
+    48_000
+}
+
+fn default_max_parallel_chunks() -> usize {
+    4
+}
+
+const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_CLAUDE_MODEL: &str = "claude-3-sonnet-20240229";
+const DEFAULT_GEMINI_MODEL: &str = "gemini-pro";
+const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const GEMINI_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub request_timeout_secs: u64,
+    pub circuit_breaker_threshold: u32,
+    pub circuit_breaker_cooldown_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 30_000,
+            request_timeout_secs: 120,
+            circuit_breaker_threshold: 5,
+            circuit_breaker_cooldown_secs: 60,
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn backoff_for_attempt(&self, attempt: u32) -> Duration {
+        let backoff = self.initial_backoff_ms.saturating_mul(2u64.saturating_pow(attempt));
+        Duration::from_millis(backoff.min(self.max_backoff_ms))
+    }
+
+    // Retry-After when the server sent one, the backoff otherwise. None when the server wants
+    // a longer wait than max_backoff_ms, which would hold a batch slot all that time
+    fn delay_for_attempt(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        match retry_after {
+            Some(delay) if delay > Duration::from_millis(self.max_backoff_ms) => None,
+            Some(delay) => Some(delay),
+            None => Some(self.backoff_for_attempt(attempt)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CircuitBreaker {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    fn remaining_cooldown(&self, cooldown: Duration) -> Option<Duration> {
+        self.opened_at
+            .map(|opened| opened.elapsed())
+            .filter(|elapsed| *elapsed < cooldown)
+            .map(|elapsed| cooldown - elapsed)
+    }
+
+    fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+    }
+
+    fn record_failure(&mut self, threshold: u32) {
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= threshold {
+            self.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+// Every provider's breaker, shared by all the analyzers that should trip together: the app's
+// commands and every video of a batch
+#[derive(Clone, Default)]
+pub struct CircuitBreakers(Arc<Mutex<HashMap<String, CircuitBreaker>>>);
+
+impl CircuitBreakers {
+    fn remaining_cooldown(&self, provider: &str, cooldown: Duration) -> Option<Duration> {
+        self.0.lock().unwrap()
+            .get(provider)
+            .and_then(|breaker| breaker.remaining_cooldown(cooldown))
+    }
+
+    fn record_success(&self, provider: &str) {
+        self.0.lock().unwrap()
+            .entry(provider.to_string())
+            .or_default()
+            .record_success();
+    }
+
+    fn record_failure(&self, provider: &str, threshold: u32) {
+        self.0.lock().unwrap()
+            .entry(provider.to_string())
+            .or_default()
+            .record_failure(threshold);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub name: String,
+    pub version: u32,
+    pub system_prompt: String,
+    pub user_prompt: String,
+    #[serde(default)]
+    pub audience: Option<String>,
+}
+
+impl PromptTemplate {
+    pub const DEFAULT_ID: &'static str = "default_analysis";
+
+    pub fn default_analysis() -> Self {
+        Self {
+            id: Self::DEFAULT_ID.to_string(),
+            name: "Default Analysis".to_string(),
+            version: 1,
+            system_prompt: "You are an expert video content analyzer. Analyze the provided video transcript and return structured insights in JSON format.".to_string(),
+            user_prompt: r#"Analyze this video content and provide insights in JSON format with the following structure:
+{
+  "summary": "Brief 2-3 sentence summary of the main content",
+  "key_topics": ["topic1", "topic2", "topic3"],
+  "sentiment_score": 0.5,
+  "engagement_score": 0.8,
+  "suggested_tags": ["tag1", "tag2", "tag3"],
+  "highlight_moments": [
+    {
+      "start_time": 30.0,
+      "end_time": 45.0,
+      "reason": "Key insight or important moment",
+      "confidence": 0.9,
+      "moment_type": "KeyPoint"
+    }
+  ],
+  "content_categories": ["Education", "Technology"],
+  "difficulty_level": "Intermediate"
+}
+
+Video Title: {title}{description}
+Target Audience: {audience}
+Transcript: {transcript}
+
+Provide detailed analysis focusing on:
+1. Main themes and topics discussed
+2. Emotional tone and sentiment
+3. Educational value and difficulty level
+4. Potential highlight moments for social media
+5. Relevant tags for discoverability
+"#.to_string(),
+            audience: None,
+        }
+    }
+
+    // Supported variables: {title}, {description}, {transcript}, {audience}
+    pub fn render(&self, transcript: &str, title: &str, description: Option<&str>) -> String {
+        let desc_part = description.map(|d| format!("\nDescription: {}", d)).unwrap_or_default();
+        let audience = self.audience.as_deref().unwrap_or("General audience");
+
+        let variables = [
+            ("{title}", title),
+            ("{description}", desc_part.as_str()),
+            ("{audience}", audience),
+            ("{transcript}", transcript),
+        ];
+
+        // One pass over the template, so a title or transcript that mentions a variable is left as it is
+        let mut rendered = String::with_capacity(self.user_prompt.len() + transcript.len());
+        let mut rest = self.user_prompt.as_str();
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            let tail = &rest[start..];
+            match variables.iter().find(|(name, _)| tail.starts_with(name)) {
+                Some((name, value)) => {
+                    rendered.push_str(value);
+                    rest = &tail[name.len()..];
+                }
+                None => {
+                    rendered.push('{');
+                    rest = &tail[1..];
+                }
+            }
+        }
+        rendered.push_str(rest);
+        rendered
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum AIModel {
     OpenAIGPT4,
     OpenAIGPT35,
@@ -56,20 +375,441 @@ pub enum AIModel {
     Local,
 }
 
+pub struct AnalysisCache {
+    cache_dir: PathBuf,
+    // Analyses quote the transcript, so they're encrypted along with the projects
+    key: Option<DataKey>,
+}
+
+impl AnalysisCache {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self { cache_dir, key: None }
+    }
+
+    pub fn with_key(mut self, key: Option<DataKey>) -> Self {
+        self.key = key;
+        self
+    }
+
+    // Everything the model is sent. The cache is shared by the workspace's projects, whose
+    // templates can share an id and version while saying different things
+    fn cache_key(model_id: &str, system_prompt: &str, prompt: &str) -> String {
+        let mut hasher = Sha256::new();
+        for part in [model_id, system_prompt, prompt, &content_analysis_schema().to_string()] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn get(&self, key: &str) -> Option<ContentAnalysis> {
+        let content = read_data(&self.cache_dir.join(format!("{}.json", key)), self.key.as_ref()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn put(&self, key: &str, analysis: &ContentAnalysis) -> Result<(), String> {
+        std::fs::create_dir_all(&self.cache_dir)
+            .map_err(|e| format!("Failed to create analysis cache directory: {}", e))?;
+
+        let json_data = serde_json::to_string(analysis)
+            .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+
+        write_data(&self.cache_dir.join(format!("{}.json", key)), &json_data, self.key.as_ref())
+            .map_err(|e| format!("Failed to write analysis cache: {}", e))
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        if self.cache_dir.exists() {
+            std::fs::remove_dir_all(&self.cache_dir)
+                .map_err(|e| format!("Failed to clear analysis cache: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AiError {
+    #[error("{0} API key not provided")]
+    MissingApiKey(&'static str),
+    #[error("{provider} API request failed: {status}")]
+    Rejected { provider: String, status: reqwest::StatusCode },
+    #[error("{context}: {source}")]
+    Http { context: String, source: reqwest::Error },
+    #[error("{0} API is temporarily disabled after repeated failures, retry in {1}s")]
+    CircuitOpen(String, u64),
+    // The last failure of a request that kept failing
+    #[error("{last} (after {retries} retries)")]
+    RetriesExhausted { retries: u32, last: Box<AiError> },
+    #[error("{0}")]
+    InvalidResponse(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl AiError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            AiError::MissingApiKey(_) => ErrorCode::Unauthorized,
+            AiError::Rejected { status, .. } => status_error_code(*status),
+            AiError::Http { source, .. } => http_error_code(source),
+            AiError::CircuitOpen(..) => ErrorCode::Network,
+            AiError::RetriesExhausted { last, .. } => last.code(),
+            AiError::InvalidResponse(_) => ErrorCode::InvalidResponse,
+            AiError::Other(_) => ErrorCode::Unknown,
+        }
+    }
+}
+
+impl From<String> for AiError {
+    fn from(message: String) -> Self {
+        AiError::Other(message)
+    }
+}
+
+impl From<AiError> for String {
+    fn from(error: AiError) -> Self {
+        error.to_string()
+    }
+}
+
+impl From<AiError> for AppError {
+    fn from(error: AiError) -> Self {
+        let code = error.code();
+        let app_error = AppError::new(code, error.to_string());
+        match code {
+            ErrorCode::Unauthorized => app_error.with_hint("Check the provider's API key in Settings"),
+            ErrorCode::RateLimited => app_error.with_hint("The provider is limiting requests, try again in a minute"),
+            ErrorCode::Network | ErrorCode::Timeout => app_error.with_hint("Check your internet connection, or switch to the local model"),
+            _ => app_error,
+        }
+    }
+}
+
 pub struct AIAnalyzer {
     config: AIConfig,
     client: reqwest::Client,
+    circuit_breakers: CircuitBreakers,
+    prompt_template: PromptTemplate,
+    cache: Option<AnalysisCache>,
+    video_duration: Option<f64>,
 }
 
 impl AIAnalyzer {
     pub fn new(config: AIConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.retry_policy.request_timeout_secs))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
         Self {
             config,
-            client: reqwest::Client::new(),
+            client,
+            circuit_breakers: CircuitBreakers::default(),
+            prompt_template: PromptTemplate::default_analysis(),
+            cache: None,
+            video_duration: None,
+        }
+    }
+
+    pub fn with_cache(mut self, cache: AnalysisCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn with_video_duration(mut self, duration: f64) -> Self {
+        self.video_duration = Some(duration);
+        self
+    }
+
+    // Without it the analyzer has breakers of its own, which only trip within its lifetime
+    pub fn with_circuit_breakers(mut self, circuit_breakers: CircuitBreakers) -> Self {
+        self.circuit_breakers = circuit_breakers;
+        self
+    }
+
+    fn openai_base_url(&self) -> &str {
+        self.config.openai_base_url
+            .as_deref()
+            .map(|url| url.trim_end_matches('/'))
+            .filter(|url| !url.is_empty())
+            .unwrap_or(DEFAULT_OPENAI_BASE_URL)
+    }
+
+    fn openai_model(&self) -> &str {
+        match (&self.config.openai_model, &self.config.model_preference) {
+            (Some(model), _) if !model.trim().is_empty() => model.as_str(),
+            (_, AIModel::OpenAIGPT4) => "gpt-4-turbo-preview",
+            _ => "gpt-3.5-turbo",
+        }
+    }
+
+    fn claude_model(&self) -> &str {
+        self.config.claude_model
+            .as_deref()
+            .map(str::trim)
+            .filter(|model| !model.is_empty())
+            .unwrap_or(DEFAULT_CLAUDE_MODEL)
+    }
+
+    fn gemini_model(&self) -> &str {
+        self.config.gemini_model
+            .as_deref()
+            .map(str::trim)
+            .map(|model| model.trim_start_matches("models/"))
+            .filter(|model| !model.is_empty())
+            .unwrap_or(DEFAULT_GEMINI_MODEL)
+    }
+
+    pub async fn list_available_models(&self) -> Result<Vec<String>, AiError> {
+        let mut models = match self.config.model_preference {
+            AIModel::OpenAIGPT4 | AIModel::OpenAIGPT35 => {
+                let mut request = self.client.get(format!("{}/models", self.openai_base_url()));
+                if let Some(api_key) = self.config.openai_api_key.as_deref() {
+                    request = request.header("Authorization", format!("Bearer {}", api_key));
+                }
+                let response = request.send().await
+                    .map_err(|e| AiError::Http { context: "Failed to list OpenAI models".to_string(), source: e })?;
+                let data = read_model_listing(response, "OpenAI").await?;
+                collect_model_ids(&data["data"], "id")
+            }
+            AIModel::Claude3 => {
+                let api_key = self.config.claude_api_key
+                    .as_ref()
+                    .ok_or(AiError::MissingApiKey("Claude"))?;
+                let response = self.client
+                    .get(format!("{}/models", ANTHROPIC_API_URL))
+                    .header("x-api-key", api_key)
+                    .header("anthropic-version", ANTHROPIC_VERSION)
+                    .send()
+                    .await
+                    .map_err(|e| AiError::Http { context: "Failed to list Claude models".to_string(), source: e })?;
+                let data = read_model_listing(response, "Claude").await?;
+                collect_model_ids(&data["data"], "id")
+            }
+            AIModel::Gemini => {
+                let api_key = self.config.gemini_api_key
+                    .as_ref()
+                    .ok_or(AiError::MissingApiKey("Gemini"))?;
+                let response = self.client
+                    .get(format!("{}/models?key={}", GEMINI_API_URL, api_key))
+                    .send()
+                    .await
+                    .map_err(|e| AiError::Http { context: "Failed to list Gemini models".to_string(), source: e })?;
+                let data = read_model_listing(response, "Gemini").await?;
+
+                // Embedding-only models are listed too but can't run an analysis
+                let generative: Vec<serde_json::Value> = data["models"].as_array()
+                    .map(|models| models.iter()
+                        .filter(|m| m["supportedGenerationMethods"].as_array()
+                            .is_some_and(|methods| methods.iter().any(|method| method == "generateContent")))
+                        .cloned()
+                        .collect())
+                    .unwrap_or_default();
+                collect_model_ids(&serde_json::Value::Array(generative), "name")
+                    .into_iter()
+                    .map(|name| name.trim_start_matches("models/").to_string())
+                    .collect()
+            }
+            AIModel::Local => vec!["local".to_string()],
+        };
+
+        models.sort();
+        Ok(models)
+    }
+
+    // Identifies the exact model serving a request, so cached results never cross models
+    fn model_id(&self) -> String {
+        match self.config.model_preference {
+            AIModel::OpenAIGPT4 | AIModel::OpenAIGPT35 => {
+                format!("{}/{}", self.openai_base_url(), self.openai_model())
+            }
+            AIModel::Claude3 => self.claude_model().to_string(),
+            AIModel::Gemini => self.gemini_model().to_string(),
+            AIModel::Local => "local".to_string(),
+        }
+    }
+
+    pub fn with_prompt_template(mut self, template: PromptTemplate) -> Self {
+        self.prompt_template = template;
+        self
+    }
+
+    fn output_language(&self) -> Option<&str> {
+        self.config.output_language
+            .as_deref()
+            .map(str::trim)
+            .filter(|language| !language.is_empty())
+    }
+
+    fn system_prompt(&self) -> String {
+        match self.output_language() {
+            Some(language) => format!(
+                "{}\n\nWrite the summary, key_topics, suggested_tags and highlight reasons in {}, \
+                 even when the transcript is in another language. Keep JSON keys, moment_type and \
+                 difficulty_level values exactly as specified in English.",
+                self.prompt_template.system_prompt,
+                language_name(language)
+            ),
+            None => self.prompt_template.system_prompt.clone(),
+        }
+    }
+
+    async fn send_with_retry<F>(&self, provider: &str, build_request: F) -> Result<reqwest::Response, AiError>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        let policy = &self.config.retry_policy;
+        let cooldown = Duration::from_secs(policy.circuit_breaker_cooldown_secs);
+
+        if let Some(remaining) = self.circuit_breakers.remaining_cooldown(provider, cooldown) {
+            return Err(AiError::CircuitOpen(provider.to_string(), remaining.as_secs() + 1));
+        }
+
+        let mut attempt = 0;
+        loop {
+            let (retry_delay, error) = match build_request().send().await {
+                Ok(response) if response.status().is_success() => {
+                    self.circuit_breakers.record_success(provider);
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let error = AiError::Rejected { provider: provider.to_string(), status };
+                    if status != reqwest::StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+                        return Err(error);
+                    }
+
+                    let retry_after = response.headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(parse_retry_after);
+                    (retry_after, error)
+                }
+                Err(e) => (None, AiError::Http { context: format!("Failed to call {} API", provider), source: e }),
+            };
+
+            if attempt >= policy.max_retries {
+                self.circuit_breakers.record_failure(provider, policy.circuit_breaker_threshold);
+                return Err(AiError::RetriesExhausted { retries: attempt, last: Box::new(error) });
+            }
+
+            let Some(delay) = policy.delay_for_attempt(attempt, retry_delay) else {
+                return Err(error);
+            };
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    pub async fn analyze_content(&self, transcript: &str, title: &str, description: Option<&str>) -> Result<ContentAnalysis, AiError> {
+        // The local heuristics are already instantaneous, so only cloud results are cached
+        let is_local = matches!(self.config.model_preference, AIModel::Local);
+        let cache = self.cache.as_ref().filter(|_| !is_local);
+        let cache_key = AnalysisCache::cache_key(
+            &self.model_id(),
+            &self.system_prompt(),
+            &self.create_analysis_prompt(transcript, title, description),
+        );
+
+        let mut analysis = match cache.and_then(|cache| cache.get(&cache_key)) {
+            Some(cached) => cached,
+            None => {
+                let analysis = self.analyze_uncached(transcript, title, description).await?;
+                if let Some(cache) = cache {
+                    // A failed cache write should never fail the analysis itself
+                    let _ = cache.put(&cache_key, &analysis);
+                }
+                analysis
+            }
+        };
+
+        // Validated after the cache so the same transcript can be reused with a different duration
+        let (highlights, warnings) = validate_highlight_moments(
+            std::mem::take(&mut analysis.highlight_moments),
+            self.video_duration,
+        );
+        analysis.highlight_moments = highlights;
+        analysis.warnings.extend(warnings);
+
+        Ok(analysis)
+    }
+
+    // For the analyzed video's processing history
+    pub fn analysis_event(&self, analysis: &ContentAnalysis) -> ProcessingEvent {
+        let mut parameters = HashMap::new();
+        parameters.insert("model".to_string(), serde_json::json!(self.model_id()));
+        parameters.insert("prompt_template".to_string(), serde_json::json!(self.prompt_template.id));
+        parameters.insert("prompt_template_version".to_string(), serde_json::json!(self.prompt_template.version));
+        parameters.insert("output_language".to_string(), serde_json::json!(self.output_language().unwrap_or("en")));
+        parameters.insert("topic_count".to_string(), serde_json::json!(analysis.key_topics.len()));
+        parameters.insert("highlight_count".to_string(), serde_json::json!(analysis.highlight_moments.len()));
+        parameters.insert("sentiment_score".to_string(), serde_json::json!(analysis.sentiment_score));
+        parameters.insert("warning_count".to_string(), serde_json::json!(analysis.warnings.len()));
+
+        ProcessingEvent::new(
+            EventType::AnalysisCompleted,
+            format!("Analyzed with {}", self.model_id()),
+            parameters,
+        )
+    }
+
+    async fn analyze_uncached(&self, transcript: &str, title: &str, description: Option<&str>) -> Result<ContentAnalysis, AiError> {
+        let is_local = matches!(self.config.model_preference, AIModel::Local);
+        if is_local || transcript.len() <= self.config.max_chunk_chars {
+            return self.analyze_single(transcript, title, description).await;
+        }
+
+        // Map: analyze each chunk independently
+        let chunks = chunk_transcript(transcript, self.config.max_chunk_chars);
+        let chunk_count = chunks.len();
+        // Owned chunks keep this future Send, batch jobs run analysis on spawned tasks
+        let chunk_analyses: Vec<(usize, ContentAnalysis)> = stream::iter(chunks.into_iter().enumerate())
+            .map(|(index, chunk)| async move {
+                let chunk_title = format!("{} (part {} of {})", title, index + 1, chunk_count);
+                self.analyze_single(&chunk, &chunk_title, description).await
+                    .map(|analysis| (chunk.len(), analysis))
+            })
+            .buffered(self.config.max_parallel_chunks.max(1))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+
+        // Reduce: merge the structured fields locally and summarize the summaries
+        let summaries: Vec<String> = chunk_analyses.iter().map(|(_, analysis)| analysis.summary.clone()).collect();
+        let mut merged = merge_chunk_analyses(chunk_analyses);
+        match self.combine_summaries(&summaries, title).await {
+            Ok(summary) => merged.summary = summary,
+            // The joined part summaries still describe the whole video
+            Err(e) => tracing::warn!("Failed to combine the part summaries of {}: {}", title, e),
+        }
+
+        Ok(merged)
+    }
+
+    // One summary of the whole video from the summaries of its parts, in order
+    async fn combine_summaries(&self, summaries: &[String], title: &str) -> Result<String, AiError> {
+        let parts = summaries.iter()
+            .enumerate()
+            .map(|(index, summary)| format!("Part {}: {}", index + 1, summary))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut prompt = format!(
+            "These are summaries of consecutive parts of the video \"{}\". Write a 2-3 sentence \
+             summary of the whole video from them, without mentioning the parts.\n\n{}",
+            title, parts
+        );
+        if let Some(language) = self.output_language() {
+            prompt.push_str(&format!("\n\nWrite the summary in {}.", language_name(language)));
         }
+
+        let value = self.complete_json(SUMMARY_TOOL_NAME, &prompt, summary_schema()).await?;
+        value["summary"].as_str()
+            .map(str::to_string)
+            .ok_or_else(|| AiError::InvalidResponse("Summary response has no summary".to_string()))
     }
 
-    pub async fn analyze_content(&self, transcript: &str, title: &str, description: Option<&str>) -> Result<ContentAnalysis, String> {
+    async fn analyze_single(&self, transcript: &str, title: &str, description: Option<&str>) -> Result<ContentAnalysis, AiError> {
         match self.config.model_preference {
             AIModel::OpenAIGPT4 | AIModel::OpenAIGPT35 => {
                 self.analyze_with_openai(transcript, title, description).await
@@ -86,71 +826,74 @@ impl AIAnalyzer {
         }
     }
 
-    async fn analyze_with_openai(&self, transcript: &str, title: &str, description: Option<&str>) -> Result<ContentAnalysis, String> {
-        let api_key = self.config.openai_api_key
-            .as_ref()
-            .ok_or("OpenAI API key not provided")?;
+    async fn analyze_with_openai(&self, transcript: &str, title: &str, description: Option<&str>) -> Result<ContentAnalysis, AiError> {
+        let base_url = self.openai_base_url();
 
-        let model = match self.config.model_preference {
-            AIModel::OpenAIGPT4 => "gpt-4-turbo-preview",
-            AIModel::OpenAIGPT35 => "gpt-3.5-turbo",
-            _ => "gpt-3.5-turbo",
+        // Local gateways such as LM Studio or vLLM usually run without authentication
+        let api_key = match self.config.openai_api_key.as_deref() {
+            Some(key) => Some(key),
+            None if base_url != DEFAULT_OPENAI_BASE_URL => None,
+            None => return Err(AiError::MissingApiKey("OpenAI")),
         };
 
+        let model = self.openai_model();
+
         let prompt = self.create_analysis_prompt(transcript, title, description);
 
-        let request_body = serde_json::json!({
-            "model": model,
-            "messages": [
-                {
-                    "role": "system",
-                    "content": "You are an expert video content analyzer. Analyze the provided video transcript and return structured insights in JSON format."
-                },
-                {
-                    "role": "user",
-                    "content": prompt
-                }
-            ],
-            "temperature": 0.3,
-            "max_tokens": 2000,
-            "response_format": { "type": "json_object" }
-        });
+        let request_body = openai_tool_request(
+            model,
+            &self.system_prompt(),
+            &prompt,
+            ANALYSIS_TOOL_NAME,
+            "Record the structured analysis of the video content",
+            content_analysis_schema(),
+        );
 
-        let response = self.client
-            .post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to call OpenAI API: {}", e))?;
+        let endpoint = format!("{}/chat/completions", base_url);
+        let response = self.send_with_retry("OpenAI", || {
+            let request = self.client
+                .post(&endpoint)
+                .header("Content-Type", "application/json")
+                .json(&request_body);
 
-        if !response.status().is_success() {
-            return Err(format!("OpenAI API request failed: {}", response.status()));
-        }
+            match api_key {
+                Some(key) => request.header("Authorization", format!("Bearer {}", key)),
+                None => request,
+            }
+        }).await?;
 
         let response_data: serde_json::Value = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
+            .map_err(|e| AiError::InvalidResponse(format!("Failed to parse OpenAI response: {}", e)))?;
 
-        let content = response_data["choices"][0]["message"]["content"]
-            .as_str()
-            .ok_or("Invalid response format from OpenAI")?;
+        if let Some(refusal) = response_data["choices"][0]["message"]["refusal"].as_str() {
+            return Err(AiError::InvalidResponse(format!("OpenAI refused to analyze the content: {}", refusal)));
+        }
 
-        self.parse_analysis_response(content)
+        let arguments = openai_tool_arguments(&response_data, ANALYSIS_TOOL_NAME)?;
+        self.parse_analysis_response(arguments)
     }
 
-    async fn analyze_with_claude(&self, transcript: &str, title: &str, description: Option<&str>) -> Result<ContentAnalysis, String> {
+    async fn analyze_with_claude(&self, transcript: &str, title: &str, description: Option<&str>) -> Result<ContentAnalysis, AiError> {
         let api_key = self.config.claude_api_key
             .as_ref()
-            .ok_or("Claude API key not provided")?;
+            .ok_or(AiError::MissingApiKey("Claude"))?;
 
         let prompt = self.create_analysis_prompt(transcript, title, description);
 
         let request_body = serde_json::json!({
-            "model": "claude-3-sonnet-20240229",
+            "model": self.claude_model(),
             "max_tokens": 2000,
+            "system": self.system_prompt(),
+            "tools": [
+                {
+                    "name": ANALYSIS_TOOL_NAME,
+                    "description": "Record the structured analysis of the video content",
+                    "input_schema": content_analysis_schema()
+                }
+            ],
+            "tool_choice": { "type": "tool", "name": ANALYSIS_TOOL_NAME },
             "messages": [
                 {
                     "role": "user",
@@ -159,40 +902,46 @@ impl AIAnalyzer {
             ]
         });
 
-        let response = self.client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to call Claude API: {}", e))?;
-
-        if !response.status().is_success() {
-            return Err(format!("Claude API request failed: {}", response.status()));
-        }
+        let response = self.send_with_retry("Claude", || {
+            self.client
+                .post(format!("{}/messages", ANTHROPIC_API_URL))
+                .header("x-api-key", api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .header("Content-Type", "application/json")
+                .json(&request_body)
+        }).await?;
 
         let response_data: serde_json::Value = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse Claude response: {}", e))?;
+            .map_err(|e| AiError::InvalidResponse(format!("Failed to parse Claude response: {}", e)))?;
 
-        let content = response_data["content"][0]["text"]
-            .as_str()
-            .ok_or("Invalid response format from Claude")?;
+        let tool_input = response_data["content"]
+            .as_array()
+            .and_then(|blocks| blocks.iter().find(|block| {
+                block["type"] == "tool_use" && block["name"] == ANALYSIS_TOOL_NAME
+            }))
+            .map(|block| &block["input"])
+            .ok_or_else(|| AiError::InvalidResponse("Invalid response format from Claude: no analysis tool call".to_string()))?;
 
-        self.parse_analysis_response(content)
+        validate_content_analysis(tool_input).map_err(AiError::InvalidResponse)
     }
 
-    async fn analyze_with_gemini(&self, transcript: &str, title: &str, description: Option<&str>) -> Result<ContentAnalysis, String> {
+    async fn analyze_with_gemini(&self, transcript: &str, title: &str, description: Option<&str>) -> Result<ContentAnalysis, AiError> {
         let api_key = self.config.gemini_api_key
             .as_ref()
-            .ok_or("Gemini API key not provided")?;
+            .ok_or(AiError::MissingApiKey("Gemini"))?;
 
         let prompt = self.create_analysis_prompt(transcript, title, description);
 
         let request_body = serde_json::json!({
+            "systemInstruction": {
+                "parts": [
+                    {
+                        "text": self.system_prompt()
+                    }
+                ]
+            },
             "contents": [
                 {
                     "parts": [
@@ -204,44 +953,42 @@ impl AIAnalyzer {
             ],
             "generationConfig": {
                 "temperature": 0.3,
-                "maxOutputTokens": 2000
+                "maxOutputTokens": 2000,
+                "responseMimeType": "application/json",
+                "responseSchema": gemini_response_schema(content_analysis_schema())
             }
         });
 
-        let url = format!("https://generativelanguage.googleapis.com/v1beta/models/gemini-pro:generateContent?key={}", api_key);
-
-        let response = self.client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to call Gemini API: {}", e))?;
+        let url = format!("{}/models/{}:generateContent?key={}", GEMINI_API_URL, self.gemini_model(), api_key);
 
-        if !response.status().is_success() {
-            return Err(format!("Gemini API request failed: {}", response.status()));
-        }
+        let response = self.send_with_retry("Gemini", || {
+            self.client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .json(&request_body)
+        }).await?;
 
         let response_data: serde_json::Value = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse Gemini response: {}", e))?;
+            .map_err(|e| AiError::InvalidResponse(format!("Failed to parse Gemini response: {}", e)))?;
 
         let content = response_data["candidates"][0]["content"]["parts"][0]["text"]
             .as_str()
-            .ok_or("Invalid response format from Gemini")?;
+            .ok_or_else(|| AiError::InvalidResponse("Invalid response format from Gemini".to_string()))?;
 
         self.parse_analysis_response(content)
     }
 
-    async fn analyze_with_local_model(&self, transcript: &str, title: &str, _description: Option<&str>) -> Result<ContentAnalysis, String> {
+    async fn analyze_with_local_model(&self, transcript: &str, title: &str, _description: Option<&str>) -> Result<ContentAnalysis, AiError> {
         // Fallback analysis using rule-based methods
         let word_count = transcript.split_whitespace().count();
         let sentences: Vec<&str> = transcript.split('.').collect();
         
         // Simple keyword extraction
         let common_words = vec!["the", "a", "an", "and", "or", "but", "in", "on", "at", "to", "for", "of", "with", "by"];
-        let words: Vec<&str> = transcript.to_lowercase()
+        let lowercase = transcript.to_lowercase();
+        let words: Vec<&str> = lowercase
             .split_whitespace()
             .filter(|word| !common_words.contains(word) && word.len() > 3)
             .collect();
@@ -259,24 +1006,7 @@ impl AIAnalyzer {
         key_topics.truncate(10);
 
         // Simple sentiment analysis
-        let positive_words = vec!["good", "great", "excellent", "amazing", "wonderful", "best", "love", "like"];
-        let negative_words = vec!["bad", "terrible", "awful", "hate", "worst", "dislike", "problem", "issue"];
-        
-        let positive_count = transcript.to_lowercase()
-            .split_whitespace()
-            .filter(|word| positive_words.contains(word))
-            .count();
-            
-        let negative_count = transcript.to_lowercase()
-            .split_whitespace()
-            .filter(|word| negative_words.contains(word))
-            .count();
-        
-        let sentiment_score = if positive_count + negative_count > 0 {
-            (positive_count as f64 - negative_count as f64) / (positive_count + negative_count) as f64
-        } else {
-            0.0
-        };
+        let sentiment_score = lexicon_sentiment(transcript).unwrap_or(0.0);
 
         // Generate summary (first few sentences)
         let summary = sentences.iter()
@@ -295,62 +1025,161 @@ impl AIAnalyzer {
             highlight_moments: vec![],
             content_categories: self.categorize_content(title, transcript),
             difficulty_level: self.assess_difficulty(transcript, word_count),
+            sentiment_timeline: Vec::new(),
+            warnings: Vec::new(),
+            audience_insights: None,
         })
     }
 
-    fn create_analysis_prompt(&self, transcript: &str, title: &str, description: Option<&str>) -> String {
-        let desc_part = description.map(|d| format!("\nDescription: {}", d)).unwrap_or_default();
-        
-        format!(
-            r#"Analyze this video content and provide insights in JSON format with the following structure:
-{{
-  "summary": "Brief 2-3 sentence summary of the main content",
-  "key_topics": ["topic1", "topic2", "topic3"],
-  "sentiment_score": 0.5,
-  "engagement_score": 0.8,
-  "suggested_tags": ["tag1", "tag2", "tag3"],
-  "highlight_moments": [
-    {{
-      "start_time": 30.0,
-      "end_time": 45.0,
-      "reason": "Key insight or important moment",
-      "confidence": 0.9,
-      "moment_type": "KeyPoint"
-    }}
-  ],
-  "content_categories": ["Education", "Technology"],
-  "difficulty_level": "Intermediate"
-}}
+    // analyze_content plus the sentiment timeline, which needs the segments' timings
+    pub async fn analyze_segments(&self, segments: &[TranscriptSegment], title: &str, description: Option<&str>) -> Result<ContentAnalysis, AiError> {
+        let transcript = segments.iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
 
-Video Title: {}{}
-Transcript: {}
-
-Provide detailed analysis focusing on:
-1. Main themes and topics discussed
-2. Emotional tone and sentiment
-3. Educational value and difficulty level
-4. Potential highlight moments for social media
-5. Relevant tags for discoverability
-"#, title, desc_part, transcript)
+        let mut analysis = self.analyze_content(&transcript, title, description).await?;
+        analysis.sentiment_timeline = build_sentiment_timeline(segments, TIMELINE_BUCKET_SECONDS);
+        Ok(analysis)
     }
 
-    fn parse_analysis_response(&self, content: &str) -> Result<ContentAnalysis, String> {
-        // Try to parse as JSON first
-        if let Ok(analysis) = serde_json::from_str::<ContentAnalysis>(content) {
-            return Ok(analysis);
+    // Surfaces the questions viewers ask and the topics they want covered. The most liked comments
+    // go to the model; the local model, or a cloud one that fails, falls back to heuristics
+    pub async fn analyze_comments(&self, comments: &[VideoComment], title: &str) -> Result<AudienceInsights, AiError> {
+        let mut top: Vec<&VideoComment> = comments.iter()
+            .filter(|comment| !comment.text.trim().is_empty())
+            .collect();
+        top.sort_by_key(|comment| Reverse(comment.like_count));
+        top.truncate(MAX_ANALYZED_COMMENTS);
+
+        if matches!(self.config.model_preference, AIModel::Local) || top.is_empty() {
+            return Ok(audience_insights_from_comments(&top));
         }
 
-        // If JSON parsing fails, try to extract JSON from the response
-        if let Some(json_start) = content.find('{') {
-            if let Some(json_end) = content.rfind('}') {
-                let json_content = &content[json_start..=json_end];
-                if let Ok(analysis) = serde_json::from_str::<ContentAnalysis>(json_content) {
-                    return Ok(analysis);
-                }
+        let listing = top.iter()
+            .map(|comment| {
+                let text: String = comment.text.split_whitespace().collect::<Vec<_>>().join(" ");
+                format!("- [{} likes] {}", comment.like_count, text.chars().take(MAX_COMMENT_CHARS).collect::<String>())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut prompt = format!(
+            "These are the most liked comments on the YouTube video \"{}\". List the questions viewers \
+             ask, merging ones that ask the same thing, and the topics they ask to see covered next. \
+             Count in mentions how many comments ask each, and put the most asked first.\n\n{}",
+            title, listing
+        );
+        if let Some(language) = self.output_language() {
+            prompt.push_str(&format!("\n\nWrite the questions and topics in {}.", language_name(language)));
+        }
+
+        let insights = match self.complete_json(AUDIENCE_TOOL_NAME, &prompt, audience_insights_schema()).await {
+            Ok(value) => serde_json::from_value::<AudienceInsightsResponse>(value)
+                .map_err(|e| AiError::InvalidResponse(format!("Failed to parse audience insights: {}", e))),
+            Err(e) => Err(e),
+        };
+        match insights {
+            Ok(response) => Ok(AudienceInsights {
+                questions: response.questions,
+                requested_topics: response.requested_topics,
+                comments_analyzed: top.len(),
+            }),
+            Err(e) => {
+                tracing::warn!("Falling back to comment heuristics: {}", e);
+                Ok(audience_insights_from_comments(&top))
+            }
+        }
+    }
+
+    // A JSON object matching schema from the configured cloud model, each asked the way it
+    // follows a schema best, like the analysis itself
+    async fn complete_json(&self, name: &str, prompt: &str, schema: serde_json::Value) -> Result<serde_json::Value, AiError> {
+        let system = "You help video creators understand their audience. Answer only with the requested JSON.";
+        match self.config.model_preference {
+            AIModel::OpenAIGPT4 | AIModel::OpenAIGPT35 => {
+                let base_url = self.openai_base_url();
+                let api_key = match self.config.openai_api_key.as_deref() {
+                    Some(key) => Some(key),
+                    None if base_url != DEFAULT_OPENAI_BASE_URL => None,
+                    None => return Err(AiError::MissingApiKey("OpenAI")),
+                };
+                let request_body = openai_tool_request(self.openai_model(), system, prompt, name, "Record the result", schema);
+                let endpoint = format!("{}/chat/completions", base_url);
+                let response = self.send_with_retry("OpenAI", || {
+                    let request = self.client.post(&endpoint).json(&request_body);
+                    match api_key {
+                        Some(key) => request.header("Authorization", format!("Bearer {}", key)),
+                        None => request,
+                    }
+                }).await?;
+                let data: serde_json::Value = response.json().await
+                    .map_err(|e| AiError::InvalidResponse(format!("Failed to parse OpenAI response: {}", e)))?;
+                let arguments = openai_tool_arguments(&data, name)?;
+                serde_json::from_str(arguments.trim())
+                    .map_err(|e| AiError::InvalidResponse(format!("OpenAI response is not valid JSON: {}", e)))
+            }
+            AIModel::Claude3 => {
+                let api_key = self.config.claude_api_key
+                    .as_ref()
+                    .ok_or(AiError::MissingApiKey("Claude"))?;
+                let request_body = serde_json::json!({
+                    "model": self.claude_model(),
+                    "max_tokens": 2000,
+                    "system": system,
+                    "tools": [{ "name": name, "description": "Record the result", "input_schema": schema }],
+                    "tool_choice": { "type": "tool", "name": name },
+                    "messages": [{ "role": "user", "content": prompt }]
+                });
+                let response = self.send_with_retry("Claude", || {
+                    self.client
+                        .post(format!("{}/messages", ANTHROPIC_API_URL))
+                        .header("x-api-key", api_key)
+                        .header("anthropic-version", ANTHROPIC_VERSION)
+                        .json(&request_body)
+                }).await?;
+                let data: serde_json::Value = response.json().await
+                    .map_err(|e| AiError::InvalidResponse(format!("Failed to parse Claude response: {}", e)))?;
+                data["content"].as_array()
+                    .and_then(|blocks| blocks.iter().find(|block| block["type"] == "tool_use" && block["name"] == name))
+                    .map(|block| block["input"].clone())
+                    .ok_or_else(|| AiError::InvalidResponse("Invalid response format from Claude: no tool call".to_string()))
+            }
+            AIModel::Gemini => {
+                let api_key = self.config.gemini_api_key
+                    .as_ref()
+                    .ok_or(AiError::MissingApiKey("Gemini"))?;
+                let request_body = serde_json::json!({
+                    "systemInstruction": { "parts": [{ "text": system }] },
+                    "contents": [{ "parts": [{ "text": prompt }] }],
+                    "generationConfig": {
+                        "temperature": 0.3,
+                        "maxOutputTokens": 2000,
+                        "responseMimeType": "application/json",
+                        "responseSchema": gemini_response_schema(schema)
+                    }
+                });
+                let url = format!("{}/models/{}:generateContent?key={}", GEMINI_API_URL, self.gemini_model(), api_key);
+                let response = self.send_with_retry("Gemini", || self.client.post(&url).json(&request_body)).await?;
+                let data: serde_json::Value = response.json().await
+                    .map_err(|e| AiError::InvalidResponse(format!("Failed to parse Gemini response: {}", e)))?;
+                let content = data["candidates"][0]["content"]["parts"][0]["text"].as_str()
+                    .ok_or_else(|| AiError::InvalidResponse("Invalid response format from Gemini".to_string()))?;
+                serde_json::from_str(content.trim())
+                    .map_err(|e| AiError::InvalidResponse(format!("Gemini response is not valid JSON: {}", e)))
             }
+            AIModel::Local => Err(AiError::Other("The local model can't answer free-form requests".to_string())),
         }
+    }
+
+    fn create_analysis_prompt(&self, transcript: &str, title: &str, description: Option<&str>) -> String {
+        self.prompt_template.render(transcript, title, description)
+    }
+
+    fn parse_analysis_response(&self, content: &str) -> Result<ContentAnalysis, AiError> {
+        let value: serde_json::Value = serde_json::from_str(content.trim())
+            .map_err(|e| AiError::InvalidResponse(format!("AI analysis response is not valid JSON: {}", e)))?;
 
-        Err("Failed to parse AI analysis response".to_string())
+        validate_content_analysis(&value).map_err(AiError::InvalidResponse)
     }
 
     fn categorize_content(&self, title: &str, transcript: &str) -> Vec<String> {
@@ -400,7 +1229,7 @@ Provide detailed analysis focusing on:
         }
     }
 
-    pub async fn detect_highlights_from_segments(&self, segments: &[TranscriptSegment]) -> Result<Vec<HighlightMoment>, String> {
+    pub async fn detect_highlights_from_segments(&self, segments: &[TranscriptSegment], audio: Option<&AudioAnalysis>) -> Result<Vec<HighlightMoment>, String> {
         let mut highlights = Vec::new();
         
         for segment in segments {
@@ -418,7 +1247,7 @@ Provide detailed analysis focusing on:
             }
             
             // Detect key insights
-            let insight_keywords = vec!["important", "key", "crucial", "essential", "remember", "note"];
+            let insight_keywords = ["important", "key", "crucial", "essential", "remember", "note"];
             if insight_keywords.iter().any(|keyword| text.contains(keyword)) {
                 highlights.push(HighlightMoment {
                     start_time: segment.start_time,
@@ -430,7 +1259,7 @@ Provide detailed analysis focusing on:
             }
             
             // Detect conclusions
-            let conclusion_keywords = vec!["conclusion", "summary", "in conclusion", "to summarize", "finally"];
+            let conclusion_keywords = ["conclusion", "summary", "in conclusion", "to summarize", "finally"];
             if conclusion_keywords.iter().any(|keyword| text.contains(keyword)) {
                 highlights.push(HighlightMoment {
                     start_time: segment.start_time,
@@ -441,12 +1270,19 @@ Provide detailed analysis focusing on:
                 });
             }
         }
-        
+
+        highlights.extend(highlights_from_timeline(&build_sentiment_timeline(segments, 30.0)));
+
+        if let Some(audio) = audio {
+            highlights = fuse_audio_highlights(highlights, segments, &audio.volume_levels, VOLUME_WINDOW_SECONDS);
+        }
+
         Ok(highlights)
     }
 
     pub async fn generate_social_media_captions(&self, analysis: &ContentAnalysis) -> Result<HashMap<String, String>, String> {
         let mut captions = HashMap::new();
+        let strings = caption_strings(self.output_language().unwrap_or("en"));
         
         // TikTok caption (hashtag heavy, engaging)
         let tiktok_caption = format!(
@@ -461,15 +1297,16 @@ Provide detailed analysis focusing on:
         let instagram_caption = format!(
             "{}\n\n{}\n\n#{}",
             analysis.summary,
-            "What do you think about this? Let me know in the comments! 👇",
+            strings.comment_prompt,
             analysis.suggested_tags.join(" #")
         );
         captions.insert("instagram".to_string(), instagram_caption);
         
         // YouTube Short caption (informative, searchable)
         let youtube_caption = format!(
-            "{}\n\nTopics covered: {}\n\n{}",
+            "{}\n\n{}: {}\n\n{}",
             analysis.summary,
+            strings.topics_covered,
             analysis.key_topics.join(", "),
             analysis.suggested_tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" ")
         );
@@ -477,4 +1314,1520 @@ Provide detailed analysis focusing on:
         
         Ok(captions)
     }
-}
\ No newline at end of file
+}
+
+// Keywords, hashtags and a description draft from an analysis; worked out locally, no model is asked
+pub fn generate_seo_metadata(analysis: &ContentAnalysis, title: &str, nuggets: &[VideoNugget]) -> SeoMetadata {
+    let keywords = rank_seo_keywords(analysis, title, nuggets);
+
+    let hashtag = |keyword: &str| -> String {
+        let tag: String = keyword.split_whitespace()
+            .map(|word| {
+                let mut chars = word.chars().filter(|c| c.is_alphanumeric());
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                    None => String::new(),
+                }
+            })
+            .collect();
+        format!("#{}", tag)
+    };
+    let top_hashtags = |count: usize| -> Vec<String> {
+        keywords.iter()
+            .map(|k| hashtag(&k.keyword))
+            .filter(|tag| tag.len() > 1)
+            .take(count)
+            .collect()
+    };
+
+    // Platform conventions: YouTube shows the first three above the title, TikTok and Instagram reward more
+    let mut hashtags = HashMap::new();
+    hashtags.insert("youtube".to_string(), top_hashtags(3));
+    hashtags.insert("tiktok".to_string(), top_hashtags(5));
+    hashtags.insert("instagram".to_string(), top_hashtags(10));
+    hashtags.insert("linkedin".to_string(), top_hashtags(3));
+
+    let mut description_draft = analysis.summary.clone();
+
+    // YouTube only renders chapters when they start at 0:00 and are in ascending order
+    let mut chapter_starts: Vec<&VideoNugget> = nuggets.iter().collect();
+    chapter_starts.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap_or(std::cmp::Ordering::Equal));
+    if chapter_starts.len() >= 3 {
+        description_draft.push_str("\n\nChapters:\n");
+        for (index, nugget) in chapter_starts.iter().enumerate() {
+            let start = if index == 0 { 0.0 } else { nugget.start_time };
+            description_draft.push_str(&format!("{} {}\n", format_chapter_timestamp(start), nugget.title));
+        }
+    }
+
+    if !analysis.key_topics.is_empty() {
+        description_draft.push_str(&format!("\nTopics covered: {}\n", analysis.key_topics.join(", ")));
+    }
+    description_draft.push_str(&format!("\n{}", hashtags["youtube"].join(" ")));
+
+    SeoMetadata {
+        keywords,
+        description_draft: description_draft.trim().to_string(),
+        hashtags,
+    }
+}
+
+const SUPPORTED_LANGUAGES: [(&str, &str); 10] = [
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fr", "French"),
+    ("de", "German"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("nl", "Dutch"),
+    ("ja", "Japanese"),
+    ("zh", "Chinese"),
+    ("sr", "Serbian"),
+];
+
+// Accepts either a code ("pt-BR") or a name ("Portuguese") and returns the primary code
+fn language_code(language: &str) -> String {
+    let language = language.trim().to_lowercase();
+    SUPPORTED_LANGUAGES.iter()
+        .find(|(_, name)| name.to_lowercase() == language)
+        .map(|(code, _)| code.to_string())
+        .unwrap_or_else(|| language.split(['-', '_']).next().unwrap_or("").to_string())
+}
+
+// Models follow "Spanish" more reliably than "es", unknown values are passed through as-is
+fn language_name(language: &str) -> String {
+    let code = language_code(language);
+    SUPPORTED_LANGUAGES.iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| language.trim().to_string())
+}
+
+struct CaptionStrings {
+    comment_prompt: &'static str,
+    topics_covered: &'static str,
+}
+
+fn caption_strings(language: &str) -> CaptionStrings {
+    let (comment_prompt, topics_covered) = match language_code(language).as_str() {
+        "es" => ("¿Qué opinas de esto? ¡Cuéntamelo en los comentarios! 👇", "Temas tratados"),
+        "fr" => ("Qu'en pensez-vous ? Dites-le-moi en commentaire ! 👇", "Sujets abordés"),
+        "de" => ("Was denkst du darüber? Schreib es in die Kommentare! 👇", "Behandelte Themen"),
+        "it" => ("Cosa ne pensi? Fammelo sapere nei commenti! 👇", "Argomenti trattati"),
+        "pt" => ("O que você acha disso? Conta nos comentários! 👇", "Tópicos abordados"),
+        "nl" => ("Wat vind jij hiervan? Laat het weten in de reacties! 👇", "Behandelde onderwerpen"),
+        "ja" => ("あなたはどう思いますか？コメントで教えてください！👇", "取り上げたトピック"),
+        "zh" => ("你怎么看？在评论区告诉我吧！👇", "涵盖的主题"),
+        "sr" => ("Šta mislite o ovome? Javite mi u komentarima! 👇", "Obrađene teme"),
+        _ => ("What do you think about this? Let me know in the comments! 👇", "Topics covered"),
+    };
+
+    CaptionStrings { comment_prompt, topics_covered }
+}
+
+fn rank_seo_keywords(analysis: &ContentAnalysis, title: &str, nuggets: &[VideoNugget]) -> Vec<RankedKeyword> {
+    let mut scores: Vec<(String, f64)> = Vec::new();
+    let mut add = |keyword: &str, weight: f64| {
+        let keyword = keyword.trim().to_lowercase();
+        if keyword.len() < 3 {
+            return;
+        }
+        match scores.iter_mut().find(|(k, _)| *k == keyword) {
+            Some((_, score)) => *score += weight,
+            None => scores.push((keyword, weight)),
+        }
+    };
+
+    // Earlier topics are the more central ones, so they get a higher weight
+    for (index, topic) in analysis.key_topics.iter().enumerate() {
+        add(topic, 3.0 - (index as f64 * 0.2).min(2.0));
+    }
+    for tag in &analysis.suggested_tags {
+        add(tag, 1.5);
+    }
+    for category in &analysis.content_categories {
+        add(category, 0.5);
+    }
+
+    let title_lower = title.to_lowercase();
+    let nugget_titles = nuggets.iter()
+        .map(|n| n.title.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ");
+    for (keyword, score) in scores.iter_mut() {
+        if title_lower.contains(keyword.as_str()) {
+            *score += 2.0;
+        }
+        if nugget_titles.contains(keyword.as_str()) {
+            *score += 0.5;
+        }
+    }
+
+    scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let max_score = scores.first().map(|(_, s)| *s).unwrap_or(1.0).max(f64::EPSILON);
+    scores.into_iter()
+        .take(15)
+        .map(|(keyword, score)| RankedKeyword { keyword, score: score / max_score })
+        .collect()
+}
+
+fn format_chapter_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, secs) = (total / 3600, (total % 3600) / 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+// Splits on sentence boundaries where possible, falling back to word boundaries
+fn chunk_transcript(transcript: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for sentence in transcript.split_inclusive(['.', '?', '!']) {
+        if current.len() + sentence.len() > max_chars && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+
+        if sentence.len() > max_chars {
+            for word in sentence.split_inclusive(char::is_whitespace) {
+                if current.len() + word.len() > max_chars && !current.is_empty() {
+                    chunks.push(std::mem::take(&mut current));
+                }
+                current.push_str(word);
+            }
+        } else {
+            current.push_str(sentence);
+        }
+    }
+
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+fn merge_chunk_analyses(chunk_analyses: Vec<(usize, ContentAnalysis)>) -> ContentAnalysis {
+    let total_len: usize = chunk_analyses.iter().map(|(len, _)| *len).sum::<usize>().max(1);
+
+    let mut summaries = Vec::new();
+    let mut topic_counts: Vec<(String, usize)> = Vec::new();
+    let mut suggested_tags: Vec<String> = Vec::new();
+    let mut content_categories: Vec<String> = Vec::new();
+    let mut highlight_moments = Vec::new();
+    let mut difficulty_counts: HashMap<String, usize> = HashMap::new();
+    let mut sentiment_score = 0.0;
+    let mut engagement_score = 0.0;
+
+    fn push_unique(list: &mut Vec<String>, value: String) {
+        if !list.iter().any(|existing| existing.eq_ignore_ascii_case(&value)) {
+            list.push(value);
+        }
+    }
+
+    for (len, analysis) in chunk_analyses {
+        let weight = len as f64 / total_len as f64;
+        sentiment_score += analysis.sentiment_score * weight;
+        engagement_score += analysis.engagement_score * weight;
+        summaries.push(analysis.summary);
+
+        for topic in analysis.key_topics {
+            match topic_counts.iter_mut().find(|(t, _)| t.eq_ignore_ascii_case(&topic)) {
+                Some((_, count)) => *count += 1,
+                None => topic_counts.push((topic, 1)),
+            }
+        }
+        for tag in analysis.suggested_tags {
+            push_unique(&mut suggested_tags, tag);
+        }
+        for category in analysis.content_categories {
+            push_unique(&mut content_categories, category);
+        }
+        *difficulty_counts.entry(analysis.difficulty_level).or_insert(0) += 1;
+        highlight_moments.extend(analysis.highlight_moments);
+    }
+
+    // Stable sort keeps first-seen order for topics with equal counts
+    topic_counts.sort_by_key(|(_, count)| Reverse(*count));
+    highlight_moments.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap_or(std::cmp::Ordering::Equal));
+
+    let difficulty_level = difficulty_counts.into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(level, _)| level)
+        .unwrap_or_else(|| "Intermediate".to_string());
+
+    ContentAnalysis {
+        summary: summaries.join(" "),
+        key_topics: topic_counts.into_iter().take(10).map(|(topic, _)| topic).collect(),
+        sentiment_score,
+        engagement_score,
+        suggested_tags,
+        highlight_moments,
+        content_categories,
+        difficulty_level,
+        sentiment_timeline: Vec::new(),
+        warnings: Vec::new(),
+        audience_insights: None,
+    }
+}
+
+const POSITIVE_WORDS: [&str; 8] = ["good", "great", "excellent", "amazing", "wonderful", "best", "love", "like"];
+const NEGATIVE_WORDS: [&str; 8] = ["bad", "terrible", "awful", "hate", "worst", "dislike", "problem", "issue"];
+const TIMELINE_BUCKET_SECONDS: f64 = 60.0;
+
+// Returns None when the text contains no sentiment-bearing words at all
+fn lexicon_sentiment(text: &str) -> Option<f64> {
+    let lowercase = text.to_lowercase();
+    let words = lowercase
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()));
+
+    let (positive, negative) = words.fold((0usize, 0usize), |(pos, neg), word| {
+        if POSITIVE_WORDS.contains(&word) {
+            (pos + 1, neg)
+        } else if NEGATIVE_WORDS.contains(&word) {
+            (pos, neg + 1)
+        } else {
+            (pos, neg)
+        }
+    });
+
+    if positive + negative == 0 {
+        return None;
+    }
+    Some((positive as f64 - negative as f64) / (positive + negative) as f64)
+}
+
+pub fn build_sentiment_timeline(segments: &[TranscriptSegment], bucket_seconds: f64) -> Vec<SentimentPoint> {
+    if segments.is_empty() || bucket_seconds <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut buckets: Vec<(f64, f64, String)> = Vec::new();
+    for segment in segments {
+        let bucket_start = (segment.start_time / bucket_seconds).floor() * bucket_seconds;
+        match buckets.last_mut() {
+            Some((start, end, text)) if *start == bucket_start => {
+                *end = end.max(segment.end_time);
+                text.push(' ');
+                text.push_str(&segment.text);
+            }
+            _ => buckets.push((bucket_start, segment.end_time, segment.text.clone())),
+        }
+    }
+
+    buckets.into_iter()
+        .map(|(start_time, end_time, text)| {
+            let duration = (end_time - start_time).max(1.0);
+            let sentiment = lexicon_sentiment(&text).unwrap_or(0.0);
+
+            // Fast speech, questions and exclamations all tend to hold attention
+            let words_per_second = text.split_whitespace().count() as f64 / duration;
+            let pace = (words_per_second / 3.0).min(1.0);
+            let punctuation = text.chars().filter(|c| *c == '?' || *c == '!').count() as f64;
+            let emphasis = (punctuation / (duration / 10.0)).min(1.0);
+            let engagement = (0.5 * pace + 0.3 * emphasis + 0.2 * sentiment.abs()).clamp(0.0, 1.0);
+
+            SentimentPoint { start_time, end_time, sentiment, engagement }
+        })
+        .collect()
+}
+
+// Peaks in engagement and strong sentiment swings make good clip candidates
+fn highlights_from_timeline(timeline: &[SentimentPoint]) -> Vec<HighlightMoment> {
+    if timeline.len() < 3 {
+        return Vec::new();
+    }
+
+    let mean = timeline.iter().map(|p| p.engagement).sum::<f64>() / timeline.len() as f64;
+    let mut highlights = Vec::new();
+
+    for window in timeline.windows(3) {
+        let (prev, point, next) = (&window[0], &window[1], &window[2]);
+
+        if point.engagement > prev.engagement && point.engagement >= next.engagement && point.engagement > mean * 1.2 {
+            highlights.push(HighlightMoment {
+                start_time: point.start_time,
+                end_time: point.end_time,
+                reason: "Engagement peak".to_string(),
+                confidence: point.engagement.min(0.95),
+                moment_type: MomentType::Insight,
+            });
+        }
+
+        if point.sentiment <= -0.5 && point.sentiment < prev.sentiment && point.sentiment <= next.sentiment {
+            highlights.push(HighlightMoment {
+                start_time: point.start_time,
+                end_time: point.end_time,
+                reason: "Sentiment valley".to_string(),
+                confidence: point.sentiment.abs().min(0.9),
+                moment_type: MomentType::Controversy,
+            });
+        }
+    }
+
+    highlights
+}
+
+const THUMBNAIL_FILLER_WORDS: [&str; 24] = [
+    "a", "an", "the", "and", "or", "of", "to", "in", "on", "for", "with", "at",
+    "is", "are", "was", "this", "that", "it", "so", "we", "i", "part", "clip", "nugget",
+];
+const THUMBNAIL_MAX_WORDS: usize = 4;
+const TITLE_CARD_MAX_CHARS: usize = 40;
+// Shorter ones are usually rhetorical ("Why?", "Right?")
+const FLASHCARD_MIN_QUESTION_WORDS: usize = 3;
+const FLASHCARD_MAX_ANSWER_SENTENCES: usize = 2;
+
+// Thumbnail text has to be readable at phone size, so every variant stays within a few words
+pub fn suggest_title_cards(nuggets: &[VideoNugget], analysis: Option<&ContentAnalysis>) -> Vec<TitleCardSuggestion> {
+    nuggets.iter()
+        .map(|nugget| {
+            let text = format!("{} {}", nugget.title, nugget.transcript.as_deref().unwrap_or("")).to_lowercase();
+            let core_words: Vec<String> = nugget.title.split_whitespace()
+                .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()).to_string())
+                .filter(|w| !w.is_empty() && !THUMBNAIL_FILLER_WORDS.contains(&w.to_lowercase().as_str()))
+                .take(THUMBNAIL_MAX_WORDS)
+                .collect();
+
+            let topic = analysis
+                .and_then(|a| a.key_topics.iter().find(|topic| text.contains(&topic.to_lowercase())))
+                .cloned()
+                .or_else(|| core_words.first().cloned())
+                .unwrap_or_else(|| nugget.title.clone());
+
+            let mut variants = Vec::new();
+            if !core_words.is_empty() {
+                variants.push(core_words.join(" ").to_uppercase());
+            }
+
+            // Reuse an opening question from the clip itself, it is the strongest curiosity hook
+            let opening_question = nugget.transcript.as_deref()
+                .and_then(|t| t.split_inclusive(['.', '!', '?']).next())
+                .map(str::trim)
+                .filter(|sentence| sentence.ends_with('?') && sentence.split_whitespace().count() <= 6);
+            variants.push(match opening_question {
+                Some(question) => question.to_string(),
+                None => format!("Why {}?", topic),
+            });
+
+            let number = nugget.title.split_whitespace()
+                .find(|w| w.chars().all(|c| c.is_ascii_digit()));
+            variants.push(match number {
+                Some(n) => format!("{} {} Tips", n, title_case(&topic)),
+                None => format!("The Truth About {}", title_case(&topic)),
+            });
+
+            variants.dedup();
+
+            TitleCardSuggestion {
+                nugget_id: nugget.id.clone(),
+                thumbnail_variants: variants,
+                title_card: truncate_at_word(&nugget.title, TITLE_CARD_MAX_CHARS),
+            }
+        })
+        .collect()
+}
+
+// Questions asked in a nugget's transcript, answered by the sentences that follow them up to
+// the next question. Lectures tend to pose a question and then answer it
+pub fn suggest_flashcards(nuggets: &[VideoNugget]) -> Vec<Flashcard> {
+    let mut flashcards = Vec::new();
+    for nugget in nuggets {
+        let Some(transcript) = nugget.transcript.as_deref() else {
+            continue;
+        };
+        let sentences: Vec<&str> = transcript.split_inclusive(['.', '!', '?'])
+            .map(str::trim)
+            .filter(|sentence| !sentence.is_empty())
+            .collect();
+
+        for (index, sentence) in sentences.iter().enumerate() {
+            if !sentence.ends_with('?') || sentence.split_whitespace().count() < FLASHCARD_MIN_QUESTION_WORDS {
+                continue;
+            }
+            let answer: Vec<&str> = sentences[index + 1..].iter()
+                .take_while(|next| !next.ends_with('?'))
+                .take(FLASHCARD_MAX_ANSWER_SENTENCES)
+                .copied()
+                .collect();
+            if answer.is_empty() {
+                continue;
+            }
+            flashcards.push(Flashcard {
+                question: sentence.to_string(),
+                answer: answer.join(" "),
+                nugget_id: Some(nugget.id.clone()),
+            });
+        }
+    }
+    flashcards
+}
+
+fn title_case(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn truncate_at_word(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let mut truncated = String::new();
+    for word in text.split_whitespace() {
+        let next_len = truncated.chars().count() + word.chars().count() + usize::from(!truncated.is_empty());
+        if next_len > max_chars.saturating_sub(1) {
+            break;
+        }
+        if !truncated.is_empty() {
+            truncated.push(' ');
+        }
+        truncated.push_str(word);
+    }
+    format!("{}…", truncated)
+}
+
+const AUDIO_SPIKE_Z_SCORE: f64 = 2.0;
+const AUDIO_BOOST: f64 = 0.15;
+const LAUGHTER_MAX_SECONDS: f64 = 4.0;
+
+// (start, end, peak z-score) for each run of windows well above the average loudness
+fn audio_energy_bursts(volume_levels: &[f64], window_seconds: f64) -> Vec<(f64, f64, f64)> {
+    if volume_levels.len() < 3 {
+        return Vec::new();
+    }
+
+    let mean = volume_levels.iter().sum::<f64>() / volume_levels.len() as f64;
+    let variance = volume_levels.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / volume_levels.len() as f64;
+    let std_dev = variance.sqrt();
+    if std_dev <= f64::EPSILON {
+        return Vec::new();
+    }
+
+    let mut bursts = Vec::new();
+    let mut current: Option<(usize, f64)> = None;
+
+    for (index, level) in volume_levels.iter().enumerate() {
+        let z_score = (level - mean) / std_dev;
+        match (z_score >= AUDIO_SPIKE_Z_SCORE, current) {
+            (true, Some((start, peak))) => current = Some((start, peak.max(z_score))),
+            (true, None) => current = Some((index, z_score)),
+            (false, Some((start, peak))) => {
+                bursts.push((start as f64 * window_seconds, index as f64 * window_seconds, peak));
+                current = None;
+            }
+            (false, None) => {}
+        }
+    }
+    if let Some((start, peak)) = current {
+        bursts.push((start as f64 * window_seconds, volume_levels.len() as f64 * window_seconds, peak));
+    }
+
+    bursts
+}
+
+// Transcript cues that coincide with a loudness spike are boosted; loud stretches nobody is
+// talking over are most likely laughter (short) or applause (long) and become highlights of their own
+fn fuse_audio_highlights(
+    mut highlights: Vec<HighlightMoment>,
+    segments: &[TranscriptSegment],
+    volume_levels: &[f64],
+    window_seconds: f64,
+) -> Vec<HighlightMoment> {
+    let overlaps = |a_start: f64, a_end: f64, b_start: f64, b_end: f64| a_start < b_end && a_end > b_start;
+
+    for (start, end, peak) in audio_energy_bursts(volume_levels, window_seconds) {
+        let mut matched = false;
+        for highlight in highlights.iter_mut().filter(|h| overlaps(h.start_time, h.end_time, start, end)) {
+            highlight.confidence = (highlight.confidence + AUDIO_BOOST).min(1.0);
+            matched = true;
+        }
+
+        let has_speech = segments.iter().any(|s| overlaps(s.start_time, s.end_time, start, end));
+        if matched || has_speech {
+            continue;
+        }
+
+        let confidence = (0.5 + (peak - AUDIO_SPIKE_Z_SCORE) * 0.1).clamp(0.5, 0.9);
+        let (reason, moment_type) = if end - start <= LAUGHTER_MAX_SECONDS {
+            ("Laughter-like audio burst", MomentType::Humor)
+        } else {
+            ("Applause-like sustained audio burst", MomentType::KeyPoint)
+        };
+
+        // Include a little lead-in so the clip shows what the audience reacted to
+        highlights.push(HighlightMoment {
+            start_time: (start - 5.0).max(0.0),
+            end_time: end,
+            reason: reason.to_string(),
+            confidence,
+            moment_type,
+        });
+    }
+
+    highlights.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal));
+    highlights
+}
+
+const MIN_HIGHLIGHT_SECONDS: f64 = 1.0;
+
+// Models sometimes report times past the end of the video or swap start and end
+pub fn validate_highlight_moments(highlights: Vec<HighlightMoment>, video_duration: Option<f64>) -> (Vec<HighlightMoment>, Vec<String>) {
+    let duration = video_duration.filter(|d| d.is_finite() && *d > 0.0);
+    let mut valid = Vec::new();
+    let mut warnings = Vec::new();
+
+    for mut highlight in highlights {
+        let range = format!("{:.1}s-{:.1}s", highlight.start_time, highlight.end_time);
+
+        if !highlight.start_time.is_finite() || !highlight.end_time.is_finite() {
+            warnings.push(format!("Dropped highlight {}: timestamps are not numbers", range));
+            continue;
+        }
+
+        if highlight.end_time < highlight.start_time {
+            std::mem::swap(&mut highlight.start_time, &mut highlight.end_time);
+            warnings.push(format!("Swapped inverted highlight range {}", range));
+        }
+
+        if highlight.start_time < 0.0 {
+            highlight.start_time = 0.0;
+            warnings.push(format!("Clamped highlight {} to start at 0.0s", range));
+        }
+
+        if let Some(duration) = duration {
+            if highlight.start_time >= duration {
+                warnings.push(format!("Dropped highlight {}: starts after the video ends ({:.1}s)", range, duration));
+                continue;
+            }
+            if highlight.end_time > duration {
+                highlight.end_time = duration;
+                warnings.push(format!("Clamped highlight {} to the video end ({:.1}s)", range, duration));
+            }
+        }
+
+        if highlight.end_time - highlight.start_time < MIN_HIGHLIGHT_SECONDS {
+            warnings.push(format!("Dropped highlight {}: shorter than {:.0}s", range, MIN_HIGHLIGHT_SECONDS));
+            continue;
+        }
+
+        highlight.confidence = if highlight.confidence.is_finite() {
+            highlight.confidence.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        valid.push(highlight);
+    }
+
+    (valid, warnings)
+}
+
+// A chat completion that has to answer through the one function. Function calling works on every
+// OpenAI chat model and most compatible gateways, where a json_schema response format doesn't
+fn openai_tool_request(model: &str, system: &str, prompt: &str, name: &str, description: &str, schema: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "model": model,
+        "messages": [
+            { "role": "system", "content": system },
+            { "role": "user", "content": prompt }
+        ],
+        "temperature": 0.3,
+        "max_tokens": 2000,
+        "tools": [{
+            "type": "function",
+            "function": { "name": name, "description": description, "parameters": schema }
+        }],
+        "tool_choice": { "type": "function", "function": { "name": name } }
+    })
+}
+
+// The JSON arguments the model called the function with
+fn openai_tool_arguments<'a>(response: &'a serde_json::Value, name: &str) -> Result<&'a str, AiError> {
+    response["choices"][0]["message"]["tool_calls"]
+        .as_array()
+        .and_then(|calls| calls.iter().find(|call| call["function"]["name"] == name))
+        .and_then(|call| call["function"]["arguments"].as_str())
+        .ok_or_else(|| AiError::InvalidResponse("Invalid response format from OpenAI: no function call".to_string()))
+}
+
+const ANALYSIS_TOOL_NAME: &str = "record_content_analysis";
+const AUDIENCE_TOOL_NAME: &str = "record_audience_insights";
+const SUMMARY_TOOL_NAME: &str = "record_summary";
+// The most liked comments that go into the prompt, and how much of each
+const MAX_ANALYZED_COMMENTS: usize = 100;
+const MAX_COMMENT_CHARS: usize = 500;
+// Phrases viewers use to ask for a video; the topic is what follows
+const REQUEST_PHRASES: [&str; 8] = [
+    "video on ", "video about ", "tutorial on ", "please cover ", "can you cover ",
+    "could you cover ", "would love to see ", "next video ",
+];
+
+#[derive(Deserialize)]
+struct AudienceInsightsResponse {
+    questions: Vec<AudienceRequest>,
+    requested_topics: Vec<AudienceRequest>,
+}
+
+fn summary_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": { "summary": { "type": "string" } },
+        "required": ["summary"],
+        "additionalProperties": false
+    })
+}
+
+fn audience_insights_schema() -> serde_json::Value {
+    let requests = serde_json::json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": {
+                "text": { "type": "string" },
+                "mentions": { "type": "integer", "minimum": 1 }
+            },
+            "required": ["text", "mentions"],
+            "additionalProperties": false
+        }
+    });
+    serde_json::json!({
+        "type": "object",
+        "properties": { "questions": requests, "requested_topics": requests },
+        "required": ["questions", "requested_topics"],
+        "additionalProperties": false
+    })
+}
+
+// Questions are the sentences ending in '?', topics what follows a REQUEST_PHRASES phrase. Repeats
+// count as mentions; likes break ties
+fn audience_insights_from_comments(comments: &[&VideoComment]) -> AudienceInsights {
+    fn tally(counts: &mut Vec<(String, u32, u64)>, text: String, likes: u64) {
+        match counts.iter_mut().find(|(existing, _, _)| existing.eq_ignore_ascii_case(&text)) {
+            Some((_, mentions, total_likes)) => {
+                *mentions += 1;
+                *total_likes += likes;
+            }
+            None => counts.push((text, 1, likes)),
+        }
+    }
+    fn ranked(mut counts: Vec<(String, u32, u64)>) -> Vec<AudienceRequest> {
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)));
+        counts.into_iter()
+            .take(10)
+            .map(|(text, mentions, _)| AudienceRequest { text, mentions })
+            .collect()
+    }
+
+    let mut questions = Vec::new();
+    let mut topics = Vec::new();
+    for comment in comments {
+        let text = comment.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        for sentence in text.split_inclusive(['?', '.', '!']) {
+            let sentence = sentence.trim();
+            if sentence.ends_with('?') && sentence.split_whitespace().count() >= 3 {
+                tally(&mut questions, sentence.to_string(), comment.like_count);
+            }
+        }
+
+        let lowercase = text.to_lowercase();
+        for phrase in REQUEST_PHRASES {
+            let Some(start) = lowercase.find(phrase) else { continue };
+            let topic: String = lowercase[start + phrase.len()..]
+                .split(['.', '!', '?', ',', '\n'])
+                .next()
+                .unwrap_or_default()
+                .split_whitespace()
+                .take(6)
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !topic.is_empty() {
+                tally(&mut topics, topic, comment.like_count);
+            }
+        }
+    }
+
+    AudienceInsights {
+        questions: ranked(questions),
+        requested_topics: ranked(topics),
+        comments_analyzed: comments.len(),
+    }
+}
+
+const MOMENT_TYPES: [&str; 8] = [
+    "KeyPoint", "Question", "Demonstration", "Conclusion",
+    "CallToAction", "Humor", "Insight", "Controversy",
+];
+
+pub fn content_analysis_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "summary": { "type": "string" },
+            "key_topics": { "type": "array", "items": { "type": "string" } },
+            "sentiment_score": { "type": "number", "minimum": -1.0, "maximum": 1.0 },
+            "engagement_score": { "type": "number", "minimum": 0.0, "maximum": 1.0 },
+            "suggested_tags": { "type": "array", "items": { "type": "string" } },
+            "highlight_moments": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "start_time": { "type": "number" },
+                        "end_time": { "type": "number" },
+                        "reason": { "type": "string" },
+                        "confidence": { "type": "number", "minimum": 0.0, "maximum": 1.0 },
+                        "moment_type": { "type": "string", "enum": MOMENT_TYPES }
+                    },
+                    "required": ["start_time", "end_time", "reason", "confidence", "moment_type"],
+                    "additionalProperties": false
+                }
+            },
+            "content_categories": { "type": "array", "items": { "type": "string" } },
+            "difficulty_level": { "type": "string", "enum": ["Beginner", "Intermediate", "Advanced"] }
+        },
+        "required": [
+            "summary", "key_topics", "sentiment_score", "engagement_score", "suggested_tags",
+            "highlight_moments", "content_categories", "difficulty_level"
+        ],
+        "additionalProperties": false
+    })
+}
+
+// Gemini accepts an OpenAPI subset that rejects additionalProperties and numeric bounds
+fn gemini_response_schema(mut schema: serde_json::Value) -> serde_json::Value {
+    if let Some(object) = schema.as_object_mut() {
+        object.remove("additionalProperties");
+        object.remove("minimum");
+        object.remove("maximum");
+        for value in object.values_mut() {
+            *value = gemini_response_schema(value.take());
+        }
+    }
+    schema
+}
+
+pub fn validate_content_analysis(value: &serde_json::Value) -> Result<ContentAnalysis, String> {
+    let object = value.as_object()
+        .ok_or("AI analysis response must be a JSON object")?;
+
+    let mut errors = Vec::new();
+
+    let check_string = |field: &str, errors: &mut Vec<String>| {
+        match object.get(field) {
+            Some(v) if v.is_string() => {}
+            Some(_) => errors.push(format!("{}: expected a string", field)),
+            None => errors.push(format!("{}: missing required field", field)),
+        }
+    };
+    let check_string_array = |field: &str, errors: &mut Vec<String>| {
+        match object.get(field).map(|v| v.as_array()) {
+            Some(Some(items)) => {
+                for (index, item) in items.iter().enumerate() {
+                    if !item.is_string() {
+                        errors.push(format!("{}[{}]: expected a string", field, index));
+                    }
+                }
+            }
+            Some(None) => errors.push(format!("{}: expected an array of strings", field)),
+            None => errors.push(format!("{}: missing required field", field)),
+        }
+    };
+    let check_number = |field: &str, min: f64, max: f64, errors: &mut Vec<String>| {
+        match object.get(field).map(|v| v.as_f64()) {
+            Some(Some(n)) if n < min || n > max => {
+                errors.push(format!("{}: {} is outside the range {} to {}", field, n, min, max));
+            }
+            Some(Some(_)) => {}
+            Some(None) => errors.push(format!("{}: expected a number", field)),
+            None => errors.push(format!("{}: missing required field", field)),
+        }
+    };
+
+    check_string("summary", &mut errors);
+    check_string("difficulty_level", &mut errors);
+    check_string_array("key_topics", &mut errors);
+    check_string_array("suggested_tags", &mut errors);
+    check_string_array("content_categories", &mut errors);
+    check_number("sentiment_score", -1.0, 1.0, &mut errors);
+    check_number("engagement_score", 0.0, 1.0, &mut errors);
+
+    match object.get("highlight_moments").map(|v| v.as_array()) {
+        Some(Some(moments)) => {
+            for (index, moment) in moments.iter().enumerate() {
+                let prefix = format!("highlight_moments[{}]", index);
+                let start = moment["start_time"].as_f64();
+                let end = moment["end_time"].as_f64();
+
+                if start.is_none() {
+                    errors.push(format!("{}.start_time: expected a number", prefix));
+                }
+                if end.is_none() {
+                    errors.push(format!("{}.end_time: expected a number", prefix));
+                }
+                if !moment["reason"].is_string() {
+                    errors.push(format!("{}.reason: expected a string", prefix));
+                }
+                match moment["confidence"].as_f64() {
+                    Some(c) if (0.0..=1.0).contains(&c) => {}
+                    Some(c) => errors.push(format!("{}.confidence: {} is outside the range 0 to 1", prefix, c)),
+                    None => errors.push(format!("{}.confidence: expected a number", prefix)),
+                }
+                match moment["moment_type"].as_str() {
+                    Some(t) if MOMENT_TYPES.contains(&t) => {}
+                    Some(t) => errors.push(format!("{}.moment_type: unknown value '{}'", prefix, t)),
+                    None => errors.push(format!("{}.moment_type: expected a string", prefix)),
+                }
+            }
+        }
+        Some(None) => errors.push("highlight_moments: expected an array".to_string()),
+        None => errors.push("highlight_moments: missing required field".to_string()),
+    }
+
+    if !errors.is_empty() {
+        return Err(format!("AI analysis response failed validation: {}", errors.join("; ")));
+    }
+
+    serde_json::from_value(value.clone())
+        .map_err(|e| format!("Failed to parse AI analysis response: {}", e))
+}
+
+async fn read_model_listing(response: reqwest::Response, provider: &str) -> Result<serde_json::Value, AiError> {
+    if !response.status().is_success() {
+        return Err(AiError::Rejected { provider: provider.to_string(), status: response.status() });
+    }
+
+    response.json()
+        .await
+        .map_err(|e| AiError::InvalidResponse(format!("Failed to parse {} model listing: {}", provider, e)))
+}
+
+fn collect_model_ids(models: &serde_json::Value, id_field: &str) -> Vec<String> {
+    models.as_array()
+        .map(|models| models.iter()
+            .filter_map(|m| m[id_field].as_str().map(str::to_string))
+            .collect())
+        .unwrap_or_default()
+}
+
+// Retry-After is either a number of seconds or an HTTP date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let retry_at = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    let delay = retry_at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_template_render_substitutes_variables() {
+        let template = PromptTemplate {
+            user_prompt: "T={title}{description} A={audience} X={transcript}".to_string(),
+            audience: Some("Developers".to_string()),
+            ..PromptTemplate::default_analysis()
+        };
+
+        let prompt = template.render("some {title} text", "My Video", Some("About Rust"));
+        assert_eq!(prompt, "T=My Video\nDescription: About Rust A=Developers X=some {title} text");
+
+        let prompt = template.render("words", "Using {transcript} in {language}", None);
+        assert_eq!(prompt, "T=Using {transcript} in {language} A=Developers X=words");
+    }
+
+    #[test]
+    fn test_default_prompt_template_contains_all_variables() {
+        let template = PromptTemplate::default_analysis();
+        let prompt = template.render("hello world", "Title", None);
+
+        assert!(prompt.contains("Video Title: Title"));
+        assert!(prompt.contains("Target Audience: General audience"));
+        assert!(prompt.contains("Transcript: hello world"));
+        assert!(!prompt.contains("{transcript}"));
+    }
+
+    #[test]
+    fn test_default_openai_model_is_asked_through_a_function_call() {
+        let analyzer = AIAnalyzer::new(AIConfig { model_preference: AIModel::OpenAIGPT35, ..AIConfig::default() });
+        let body = openai_tool_request(analyzer.openai_model(), "system", "prompt", ANALYSIS_TOOL_NAME, "Record", content_analysis_schema());
+
+        assert_eq!(body["model"], "gpt-3.5-turbo");
+        assert!(body.get("response_format").is_none());
+        assert_eq!(body["tools"][0]["type"], "function");
+        assert_eq!(body["tools"][0]["function"]["name"], ANALYSIS_TOOL_NAME);
+        assert_eq!(body["tools"][0]["function"]["parameters"], content_analysis_schema());
+        assert_eq!(body["tool_choice"]["function"]["name"], ANALYSIS_TOOL_NAME);
+
+        let response = serde_json::json!({ "choices": [{ "message": { "content": null, "tool_calls": [{
+            "type": "function",
+            "function": { "name": ANALYSIS_TOOL_NAME, "arguments": "{\"summary\":\"s\"}" }
+        }] } }] });
+        assert_eq!(openai_tool_arguments(&response, ANALYSIS_TOOL_NAME).unwrap(), "{\"summary\":\"s\"}");
+        assert!(openai_tool_arguments(&response, AUDIENCE_TOOL_NAME).is_err());
+    }
+
+    fn valid_analysis_json() -> serde_json::Value {
+        serde_json::json!({
+            "summary": "A summary",
+            "key_topics": ["rust"],
+            "sentiment_score": 0.4,
+            "engagement_score": 0.8,
+            "suggested_tags": ["programming"],
+            "highlight_moments": [{
+                "start_time": 10.0,
+                "end_time": 20.0,
+                "reason": "Key point",
+                "confidence": 0.9,
+                "moment_type": "KeyPoint"
+            }],
+            "content_categories": ["Technology"],
+            "difficulty_level": "Beginner"
+        })
+    }
+
+    #[test]
+    fn test_validate_content_analysis_accepts_valid_response() {
+        let analysis = validate_content_analysis(&valid_analysis_json()).unwrap();
+        assert_eq!(analysis.summary, "A summary");
+        assert_eq!(analysis.highlight_moments.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_content_analysis_reports_field_errors() {
+        let mut value = valid_analysis_json();
+        value["sentiment_score"] = serde_json::json!(3.0);
+        value["key_topics"] = serde_json::json!("rust");
+        value["highlight_moments"][0]["moment_type"] = serde_json::json!("Drama");
+        value.as_object_mut().unwrap().remove("summary");
+
+        let error = validate_content_analysis(&value).unwrap_err();
+        assert!(error.contains("summary: missing required field"));
+        assert!(error.contains("sentiment_score: 3 is outside the range -1 to 1"));
+        assert!(error.contains("key_topics: expected an array of strings"));
+        assert!(error.contains("highlight_moments[0].moment_type: unknown value 'Drama'"));
+    }
+
+    #[test]
+    fn test_gemini_schema_strips_unsupported_keywords() {
+        let schema = gemini_response_schema(content_analysis_schema());
+        let serialized = schema.to_string();
+
+        assert!(!serialized.contains("additionalProperties"));
+        assert!(!serialized.contains("minimum"));
+        assert!(serialized.contains("highlight_moments"));
+    }
+
+    #[test]
+    fn test_chunk_transcript_respects_limit_and_keeps_text() {
+        let transcript = "First sentence here. Second one follows? Third is last!";
+        let chunks = chunk_transcript(transcript, 25);
+
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|c| c.len() <= 25));
+        assert_eq!(chunks.concat(), transcript);
+    }
+
+    #[test]
+    fn test_chunk_transcript_splits_long_sentences_on_words() {
+        let transcript = "word ".repeat(20);
+        let chunks = chunk_transcript(&transcript, 12);
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.len() <= 12));
+        assert_eq!(chunks.concat(), transcript);
+    }
+
+    #[test]
+    fn test_merge_chunk_analyses_combines_fields() {
+        let first: ContentAnalysis = serde_json::from_value(valid_analysis_json()).unwrap();
+        let mut second: ContentAnalysis = serde_json::from_value(valid_analysis_json()).unwrap();
+        second.summary = "Another summary".to_string();
+        second.sentiment_score = -0.2;
+        second.key_topics = vec!["Rust".to_string(), "tokio".to_string()];
+        second.highlight_moments[0].start_time = 5.0;
+
+        let merged = merge_chunk_analyses(vec![(100, first), (300, second)]);
+
+        assert_eq!(merged.summary, "A summary Another summary");
+        assert_eq!(merged.key_topics, vec!["rust".to_string(), "tokio".to_string()]);
+        assert!((merged.sentiment_score - (-0.05)).abs() < 1e-9);
+        assert_eq!(merged.suggested_tags, vec!["programming".to_string()]);
+        assert_eq!(merged.highlight_moments.len(), 2);
+        assert_eq!(merged.highlight_moments[0].start_time, 5.0);
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_everything_the_model_is_sent() {
+        let key_for = |config: AIConfig, template: PromptTemplate, transcript: &str, title: &str| {
+            let analyzer = AIAnalyzer::new(config).with_prompt_template(template);
+            AnalysisCache::cache_key(
+                &analyzer.model_id(),
+                &analyzer.system_prompt(),
+                &analyzer.create_analysis_prompt(transcript, title, None),
+            )
+        };
+        let config = AIConfig { model_preference: AIModel::OpenAIGPT4, ..AIConfig::default() };
+        let template = PromptTemplate::default_analysis();
+        // Another project's edit of the same template, at the same version
+        let edited = PromptTemplate { user_prompt: format!("Be brief. {}", template.user_prompt), ..template.clone() };
+
+        let key = key_for(config.clone(), template.clone(), "transcript", "Title");
+        assert_eq!(key, key_for(config.clone(), template.clone(), "transcript", "Title"));
+        assert_ne!(key, key_for(config.clone(), template.clone(), "transcript!", "Title"));
+        assert_ne!(key, key_for(config.clone(), template.clone(), "transcript", "Other title"));
+        assert_ne!(key, key_for(config.clone(), edited, "transcript", "Title"));
+        assert_ne!(key, key_for(AIConfig { model_preference: AIModel::Claude3, ..config.clone() }, template.clone(), "transcript", "Title"));
+        assert_ne!(key, key_for(AIConfig { output_language: Some("es".to_string()), ..config }, template, "transcript", "Title"));
+    }
+
+    #[test]
+    fn test_analysis_cache_roundtrip() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let cache = AnalysisCache::new(temp_dir.path().join("analysis"));
+        let analysis: ContentAnalysis = serde_json::from_value(valid_analysis_json()).unwrap();
+
+        assert!(cache.get("missing").is_none());
+        cache.put("key", &analysis).unwrap();
+        assert_eq!(cache.get("key").unwrap().summary, "A summary");
+
+        cache.clear().unwrap();
+        assert!(cache.get("key").is_none());
+    }
+
+    fn segment(start_time: f64, end_time: f64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_time,
+            end_time,
+            text: text.to_string(),
+            confidence: 0.9,
+            speaker_id: None,
+        }
+    }
+
+    #[test]
+    fn test_lexicon_sentiment() {
+        assert_eq!(lexicon_sentiment("This is great, I love it!"), Some(1.0));
+        assert_eq!(lexicon_sentiment("Good idea but a bad result"), Some(0.0));
+        assert_eq!(lexicon_sentiment("Nothing to see here"), None);
+    }
+
+    #[test]
+    fn test_build_sentiment_timeline_buckets_segments() {
+        let segments = vec![
+            segment(0.0, 20.0, "This is great"),
+            segment(20.0, 50.0, "really amazing stuff"),
+            segment(65.0, 90.0, "but this part is terrible"),
+        ];
+
+        let timeline = build_sentiment_timeline(&segments, 60.0);
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].start_time, 0.0);
+        assert_eq!(timeline[0].end_time, 50.0);
+        assert!(timeline[0].sentiment > 0.0);
+        assert_eq!(timeline[1].start_time, 60.0);
+        assert!(timeline[1].sentiment < 0.0);
+        assert!(timeline.iter().all(|p| (0.0..=1.0).contains(&p.engagement)));
+    }
+
+    #[tokio::test]
+    async fn test_analyze_segments_keeps_the_timeline() {
+        let segments = vec![
+            segment(0.0, 20.0, "This is great"),
+            segment(65.0, 90.0, "but this part is terrible"),
+        ];
+
+        let analysis = AIAnalyzer::new(local_config(None)).analyze_segments(&segments, "Talk", None).await.unwrap();
+        assert_eq!(analysis.sentiment_timeline.len(), 2);
+    }
+
+    #[test]
+    fn test_highlights_from_timeline_finds_peaks_and_valleys() {
+        let point = |start: f64, sentiment: f64, engagement: f64| SentimentPoint {
+            start_time: start,
+            end_time: start + 30.0,
+            sentiment,
+            engagement,
+        };
+        let timeline = vec![
+            point(0.0, 0.0, 0.2),
+            point(30.0, 0.5, 0.9),
+            point(60.0, 0.0, 0.2),
+            point(90.0, -1.0, 0.2),
+            point(120.0, 0.0, 0.2),
+        ];
+
+        let highlights = highlights_from_timeline(&timeline);
+        assert_eq!(highlights.len(), 2);
+        assert_eq!(highlights[0].start_time, 30.0);
+        assert_eq!(highlights[1].start_time, 90.0);
+    }
+
+    #[test]
+    fn test_format_chapter_timestamp() {
+        assert_eq!(format_chapter_timestamp(0.0), "0:00");
+        assert_eq!(format_chapter_timestamp(75.4), "1:15");
+        assert_eq!(format_chapter_timestamp(3725.0), "1:02:05");
+    }
+
+    fn local_config(output_language: Option<&str>) -> AIConfig {
+        AIConfig {
+            output_language: output_language.map(str::to_string),
+            ..AIConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_model_ids_default_and_override() {
+        let mut config = local_config(None);
+        config.model_preference = AIModel::Claude3;
+        assert_eq!(AIAnalyzer::new(config).model_id(), DEFAULT_CLAUDE_MODEL);
+
+        let mut config = local_config(None);
+        config.model_preference = AIModel::Gemini;
+        config.gemini_model = Some("models/gemini-1.5-pro".to_string());
+        assert_eq!(AIAnalyzer::new(config).model_id(), "gemini-1.5-pro");
+
+        let mut config = local_config(None);
+        config.model_preference = AIModel::Claude3;
+        config.claude_model = Some("  ".to_string());
+        assert_eq!(AIAnalyzer::new(config).model_id(), DEFAULT_CLAUDE_MODEL);
+    }
+
+    #[tokio::test]
+    async fn test_openai_compatible_gateway_needs_no_key() {
+        let mut config = local_config(None);
+        config.model_preference = AIModel::OpenAIGPT35;
+        config.retry_policy.max_retries = 0;
+        let result = AIAnalyzer::new(config.clone()).analyze_with_openai("transcript", "Title", None).await;
+        assert!(matches!(result, Err(AiError::MissingApiKey("OpenAI"))));
+
+        config.openai_base_url = Some("http://127.0.0.1:1/v1/".to_string());
+        config.openai_model = Some("local-model".to_string());
+        let analyzer = AIAnalyzer::new(config);
+        assert_eq!(analyzer.openai_base_url(), "http://127.0.0.1:1/v1");
+        assert_eq!(analyzer.model_id(), "http://127.0.0.1:1/v1/local-model");
+        // Nothing listens there, but the request goes out without a key
+        let result = analyzer.analyze_with_openai("transcript", "Title", None).await;
+        assert!(matches!(result, Err(AiError::RetriesExhausted { .. })));
+    }
+
+    #[test]
+    fn test_collect_model_ids() {
+        let listing = serde_json::json!([{ "id": "gpt-4o" }, { "object": "model" }, { "id": "gpt-4o-mini" }]);
+        assert_eq!(collect_model_ids(&listing, "id"), vec!["gpt-4o", "gpt-4o-mini"]);
+        assert!(collect_model_ids(&serde_json::Value::Null, "id").is_empty());
+    }
+
+    #[test]
+    fn test_language_name_accepts_codes_and_names() {
+        assert_eq!(language_name("es"), "Spanish");
+        assert_eq!(language_name("pt-BR"), "Portuguese");
+        assert_eq!(language_name("german"), "German");
+        assert_eq!(language_name("Klingon"), "Klingon");
+    }
+
+    #[test]
+    fn test_system_prompt_includes_output_language() {
+        let english = AIAnalyzer::new(local_config(None));
+        assert_eq!(english.system_prompt(), PromptTemplate::default_analysis().system_prompt);
+
+        let spanish = AIAnalyzer::new(local_config(Some("es")));
+        assert!(spanish.system_prompt().contains("in Spanish"));
+    }
+
+    #[tokio::test]
+    async fn test_social_media_captions_are_localized() {
+        let analysis: ContentAnalysis = serde_json::from_value(valid_analysis_json()).unwrap();
+
+        let captions = AIAnalyzer::new(local_config(Some("de"))).generate_social_media_captions(&analysis).await.unwrap();
+        assert!(captions["youtube"].contains("Behandelte Themen"));
+        assert!(captions["instagram"].contains("Kommentare"));
+
+        let captions = AIAnalyzer::new(local_config(None)).generate_social_media_captions(&analysis).await.unwrap();
+        assert!(captions["youtube"].contains("Topics covered"));
+    }
+
+    #[test]
+    fn test_suggest_title_cards_builds_short_variants() {
+        let nuggets = vec![
+            VideoNugget {
+                id: "a".to_string(),
+                title: "The 5 biggest mistakes in async Rust code reviews nobody talks about".to_string(),
+                start_time: 0.0,
+                end_time: 30.0,
+                transcript: Some("Ever seen a deadlock? Here is why it happens.".to_string()),
+                tags: vec![],
+                notes: None,
+                youtube_video_id: None,
+                created_at: chrono::Utc::now().to_rfc3339(),
+            },
+        ];
+        let analysis: ContentAnalysis = serde_json::from_value(valid_analysis_json()).unwrap();
+
+        let suggestions = suggest_title_cards(&nuggets, Some(&analysis));
+        let suggestion = &suggestions[0];
+
+        assert_eq!(suggestion.nugget_id, "a");
+        assert_eq!(suggestion.thumbnail_variants[0], "5 BIGGEST MISTAKES ASYNC");
+        assert_eq!(suggestion.thumbnail_variants[1], "Ever seen a deadlock?");
+        assert!(suggestion.thumbnail_variants[2].starts_with("5 "));
+        assert!(suggestion.title_card.chars().count() <= TITLE_CARD_MAX_CHARS);
+        assert!(suggestion.title_card.ends_with('…'));
+    }
+
+    #[test]
+    fn test_suggest_flashcards() {
+        let nugget = VideoNugget {
+            id: "n1".to_string(),
+            title: "Cells".to_string(),
+            start_time: 0.0,
+            end_time: 30.0,
+            transcript: Some("Welcome back. So what does the mitochondrion do? It makes ATP. That powers the cell. It also signals. Why? Good question. Any questions about that?".to_string()),
+            tags: Vec::new(),
+            notes: None,
+            youtube_video_id: None,
+            created_at: String::new(),
+        };
+
+        let flashcards = suggest_flashcards(&[nugget]);
+        assert_eq!(flashcards.len(), 1);
+        assert_eq!(flashcards[0].question, "So what does the mitochondrion do?");
+        assert_eq!(flashcards[0].answer, "It makes ATP. That powers the cell.");
+        assert_eq!(flashcards[0].nugget_id.as_deref(), Some("n1"));
+    }
+
+    #[test]
+    fn test_truncate_at_word() {
+        assert_eq!(truncate_at_word("Short title", 40), "Short title");
+        assert_eq!(truncate_at_word("one two three four", 10), "one two…");
+    }
+
+    fn highlight(start_time: f64, end_time: f64) -> HighlightMoment {
+        HighlightMoment {
+            start_time,
+            end_time,
+            reason: "test".to_string(),
+            confidence: 0.9,
+            moment_type: MomentType::KeyPoint,
+        }
+    }
+
+    #[test]
+    fn test_audio_energy_bursts_finds_loud_runs() {
+        let mut levels = vec![0.1; 30];
+        levels[10] = 0.9;
+        levels[11] = 0.8;
+        levels[25] = 0.9;
+
+        let bursts = audio_energy_bursts(&levels, 1.0);
+        assert_eq!(bursts.len(), 2);
+        assert_eq!((bursts[0].0, bursts[0].1), (10.0, 12.0));
+        assert_eq!((bursts[1].0, bursts[1].1), (25.0, 26.0));
+
+        assert!(audio_energy_bursts(&[0.4; 10], 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_fuse_audio_highlights_boosts_and_adds_reactions() {
+        let mut levels = vec![0.1; 60];
+        levels[12] = 0.9;
+        levels[40] = 0.9;
+        levels[41] = 0.9;
+        let segments = vec![segment(10.0, 15.0, "This is the key point")];
+
+        let mut transcript_highlight = highlight(10.0, 15.0);
+        transcript_highlight.confidence = 0.6;
+
+        let fused = fuse_audio_highlights(vec![transcript_highlight], &segments, &levels, 1.0);
+        assert_eq!(fused.len(), 2);
+
+        let boosted = fused.iter().find(|h| h.start_time == 10.0).unwrap();
+        assert!((boosted.confidence - 0.75).abs() < 1e-9);
+
+        let reaction = fused.iter().find(|h| h.end_time == 42.0).unwrap();
+        assert!(matches!(reaction.moment_type, MomentType::Humor));
+        assert_eq!(reaction.start_time, 35.0);
+    }
+
+    #[test]
+    fn test_validate_highlight_moments_fixes_and_drops() {
+        let (valid, warnings) = validate_highlight_moments(vec![
+            highlight(10.0, 20.0),
+            highlight(40.0, 30.0),
+            highlight(-5.0, 5.0),
+            highlight(90.0, 120.0),
+            highlight(150.0, 160.0),
+            highlight(f64::NAN, 10.0),
+            highlight(50.0, 50.2),
+        ], Some(100.0));
+
+        let ranges: Vec<(f64, f64)> = valid.iter().map(|h| (h.start_time, h.end_time)).collect();
+        assert_eq!(ranges, vec![(10.0, 20.0), (30.0, 40.0), (0.0, 5.0), (90.0, 100.0)]);
+        assert_eq!(warnings.len(), 6);
+    }
+
+    #[test]
+    fn test_validate_highlight_moments_without_duration() {
+        let mut over_confident = highlight(500.0, 520.0);
+        over_confident.confidence = 1.7;
+
+        let (valid, warnings) = validate_highlight_moments(vec![over_confident], None);
+        assert_eq!(valid.len(), 1);
+        assert_eq!(valid[0].confidence, 1.0);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_generate_seo_metadata() {
+        let analysis: ContentAnalysis = serde_json::from_value(valid_analysis_json()).unwrap();
+        let nuggets: Vec<VideoNugget> = (0..3).map(|i| VideoNugget {
+            id: i.to_string(),
+            title: format!("Chapter {}", i + 1),
+            start_time: 5.0 + i as f64 * 60.0,
+            end_time: 65.0 + i as f64 * 60.0,
+            transcript: None,
+            tags: vec![],
+            notes: None,
+            youtube_video_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }).collect();
+
+        let seo = generate_seo_metadata(&analysis, "Learning Rust fast", &nuggets);
+
+        assert_eq!(seo.keywords[0].keyword, "rust");
+        assert_eq!(seo.keywords[0].score, 1.0);
+        assert!(seo.description_draft.starts_with("A summary"));
+        assert!(seo.description_draft.contains("0:00 Chapter 1\n1:05 Chapter 2"));
+        assert_eq!(seo.hashtags["youtube"][0], "#Rust");
+        assert!(seo.hashtags["instagram"].len() <= 10);
+    }
+
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        assert_eq!(parse_retry_after("30"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after(" 5 "), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_parse_retry_after_http_date_in_past() {
+        let result = parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT");
+        assert_eq!(result, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_parse_retry_after_invalid() {
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            initial_backoff_ms: 500,
+            max_backoff_ms: 3000,
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.backoff_for_attempt(0), Duration::from_millis(500));
+        assert_eq!(policy.backoff_for_attempt(1), Duration::from_millis(1000));
+        assert_eq!(policy.backoff_for_attempt(2), Duration::from_millis(2000));
+        assert_eq!(policy.backoff_for_attempt(3), Duration::from_millis(3000));
+        assert_eq!(policy.backoff_for_attempt(40), Duration::from_millis(3000));
+    }
+
+    #[test]
+    fn test_retry_after_beyond_the_backoff_cap_is_not_waited_for() {
+        let policy = RetryPolicy {
+            initial_backoff_ms: 500,
+            max_backoff_ms: 3000,
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.delay_for_attempt(1, None), Some(Duration::from_millis(1000)));
+        assert_eq!(policy.delay_for_attempt(1, Some(Duration::from_secs(2))), Some(Duration::from_secs(2)));
+        assert_eq!(policy.delay_for_attempt(1, Some(Duration::from_secs(3600))), None);
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_after_threshold() {
+        let mut breaker = CircuitBreaker::default();
+        let cooldown = Duration::from_secs(60);
+
+        breaker.record_failure(2);
+        assert!(breaker.remaining_cooldown(cooldown).is_none());
+
+        breaker.record_failure(2);
+        assert!(breaker.remaining_cooldown(cooldown).is_some());
+
+        breaker.record_success();
+        assert!(breaker.remaining_cooldown(cooldown).is_none());
+    }
+
+    #[test]
+    fn test_circuit_breakers_are_shared_between_analyzers() {
+        let breakers = CircuitBreakers::default();
+        let first = AIAnalyzer::new(local_config(None)).with_circuit_breakers(breakers.clone());
+        let second = AIAnalyzer::new(local_config(None)).with_circuit_breakers(breakers);
+        let cooldown = Duration::from_secs(60);
+
+        first.circuit_breakers.record_failure("Claude", 2);
+        second.circuit_breakers.record_failure("Claude", 2);
+        assert!(first.circuit_breakers.remaining_cooldown("Claude", cooldown).is_some());
+        assert!(second.circuit_breakers.remaining_cooldown("OpenAI", cooldown).is_none());
+    }
+
+    #[test]
+    fn test_ai_error_codes() {
+        let error = AiError::RetriesExhausted {
+            retries: 3,
+            last: Box::new(AiError::Rejected { provider: "Claude".to_string(), status: reqwest::StatusCode::TOO_MANY_REQUESTS }),
+        };
+        assert_eq!(error.to_string(), "Claude API request failed: 429 Too Many Requests (after 3 retries)");
+
+        let app_error = AppError::from(error);
+        assert_eq!(app_error.code, ErrorCode::RateLimited);
+        assert!(app_error.hint.is_some());
+        assert_eq!(AppError::from(AiError::MissingApiKey("Gemini")).code, ErrorCode::Unauthorized);
+    }
+
+    #[tokio::test]
+    async fn test_local_audience_insights_from_comments() {
+        let comment = |text: &str, like_count: u64| VideoComment {
+            author: "@viewer".to_string(),
+            text: text.to_string(),
+            like_count,
+            reply_count: 0,
+            published_at: String::new(),
+        };
+        let comments = vec![
+            comment("Great video! Could you do a video on async traits?", 10),
+            comment("Please make a video on async traits. How does pinning work?", 50),
+            comment("How does pinning work?", 5),
+            comment("First", 0),
+        ];
+
+        let analyzer = AIAnalyzer::new(AIConfig { model_preference: AIModel::Local, ..AIConfig::default() });
+        let insights = analyzer.analyze_comments(&comments, "Rust").await.unwrap();
+        assert_eq!(insights.comments_analyzed, 4);
+        assert_eq!(insights.questions[0], AudienceRequest { text: "How does pinning work?".to_string(), mentions: 2 });
+        assert_eq!(insights.requested_topics[0], AudienceRequest { text: "async traits".to_string(), mentions: 2 });
+    }
+}
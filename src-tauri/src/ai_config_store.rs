@@ -0,0 +1,324 @@
+use crate::ai_analyzer::{AIConfig, AIModel};
+use crate::atomic_file::write_atomic;
+use serde::{Serialize, Deserialize};
+use std::path::PathBuf;
+
+#[cfg(feature = "keychain")]
+const KEYRING_SERVICE: &str = "video-nugget";
+// Builds without the keychain keep the AI keys in ai_config.json, as versions before it did
+const KEYCHAIN: bool = cfg!(feature = "keychain");
+const OPENAI_KEY_ENTRY: &str = "openai_api_key";
+const CLAUDE_KEY_ENTRY: &str = "claude_api_key";
+const GEMINI_KEY_ENTRY: &str = "gemini_api_key";
+const YOUTUBE_KEY_ENTRY: &str = "youtube_api_key";
+
+// Every key the app uses, each stored as its own OS keychain entry
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ApiKeyProvider {
+    OpenAI,
+    Claude,
+    Gemini,
+    YouTube,
+}
+
+impl ApiKeyProvider {
+    fn entry(self) -> &'static str {
+        match self {
+            ApiKeyProvider::OpenAI => OPENAI_KEY_ENTRY,
+            ApiKeyProvider::Claude => CLAUDE_KEY_ENTRY,
+            ApiKeyProvider::Gemini => GEMINI_KEY_ENTRY,
+            ApiKeyProvider::YouTube => YOUTUBE_KEY_ENTRY,
+        }
+    }
+}
+
+pub fn api_key(provider: ApiKeyProvider) -> Result<Option<String>, String> {
+    read_secret(provider.entry())
+}
+
+pub fn set_api_key(provider: ApiKeyProvider, key: &str) -> Result<(), String> {
+    if key.trim().is_empty() {
+        return Err("API key is empty".to_string());
+    }
+    write_secret(provider.entry(), Some(key))
+}
+
+pub fn clear_api_key(provider: ApiKeyProvider) -> Result<(), String> {
+    write_secret(provider.entry(), Some(""))
+}
+
+// What the UI gets back: the settings, plus whether each key exists, never the keys themselves
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AIConfigView {
+    #[serde(flatten)]
+    pub config: AIConfig,
+    pub openai_key_set: bool,
+    pub claude_key_set: bool,
+    pub gemini_key_set: bool,
+}
+
+pub struct AIConfigStore {
+    config_path: PathBuf,
+}
+
+impl AIConfigStore {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self {
+            config_path: workspace_root.join("ai_config.json"),
+        }
+    }
+
+    // Full config with keys pulled from the OS keychain, for constructing an AIAnalyzer. A
+    // keychain that can't be read only fails the load when the selected provider needs its key
+    pub fn load(&self) -> Result<AIConfig, String> {
+        let mut config = self.load_settings()?;
+        if KEYCHAIN {
+            if let Err(e) = self.move_keys_to_keychain(&config) {
+                // The keys stay in the file until the keychain takes them
+                tracing::warn!("{}", e);
+            }
+        }
+        let needed = required_key_entry(&config.model_preference);
+        config.openai_api_key = read_key(OPENAI_KEY_ENTRY, needed)?.or(config.openai_api_key);
+        config.claude_api_key = read_key(CLAUDE_KEY_ENTRY, needed)?.or(config.claude_api_key);
+        config.gemini_api_key = read_key(GEMINI_KEY_ENTRY, needed)?.or(config.gemini_api_key);
+        Ok(config)
+    }
+
+    pub fn view(&self) -> Result<AIConfigView, String> {
+        let config = self.load()?;
+
+        Ok(AIConfigView {
+            openai_key_set: config.openai_api_key.is_some(),
+            claude_key_set: config.claude_api_key.is_some(),
+            gemini_key_set: config.gemini_api_key.is_some(),
+            config: without_secrets(config),
+        })
+    }
+
+    // A key of None leaves the stored key untouched, an empty string removes it
+    pub fn save(&self, config: AIConfig) -> Result<(), String> {
+        if let Some(parent) = self.config_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let stored = if KEYCHAIN {
+            without_secrets(config.clone())
+        } else {
+            let previous = self.load_settings()?;
+            AIConfig {
+                openai_api_key: updated_key(config.openai_api_key.clone(), previous.openai_api_key),
+                claude_api_key: updated_key(config.claude_api_key.clone(), previous.claude_api_key),
+                gemini_api_key: updated_key(config.gemini_api_key.clone(), previous.gemini_api_key),
+                ..config.clone()
+            }
+        };
+        let content = serde_json::to_string_pretty(&stored)
+            .map_err(|e| format!("Failed to serialize AI config: {}", e))?;
+
+        write_atomic(&self.config_path, content.as_bytes())
+            .map_err(|e| format!("Failed to save AI config: {}", e))?;
+        if !KEYCHAIN {
+            return Ok(());
+        }
+
+        write_secret(OPENAI_KEY_ENTRY, config.openai_api_key.as_deref())?;
+        write_secret(CLAUDE_KEY_ENTRY, config.claude_api_key.as_deref())?;
+        write_secret(GEMINI_KEY_ENTRY, config.gemini_api_key.as_deref())
+    }
+
+    // Versions before the keychain kept keys in the config file. They are moved over unless the
+    // keychain already has a newer key, and the file is written again without them
+    fn move_keys_to_keychain(&self, settings: &AIConfig) -> Result<(), String> {
+        let keys = [
+            (OPENAI_KEY_ENTRY, &settings.openai_api_key),
+            (CLAUDE_KEY_ENTRY, &settings.claude_api_key),
+            (GEMINI_KEY_ENTRY, &settings.gemini_api_key),
+        ];
+        if keys.iter().all(|(_, key)| key.is_none()) {
+            return Ok(());
+        }
+        for (entry, key) in keys {
+            if key.is_some() && read_secret(entry)?.is_none() {
+                write_secret(entry, key.as_deref())?;
+            }
+        }
+
+        let content = serde_json::to_string_pretty(&without_secrets(settings.clone()))
+            .map_err(|e| format!("Failed to serialize AI config: {}", e))?;
+        write_atomic(&self.config_path, content.as_bytes())
+            .map_err(|e| format!("Failed to save AI config: {}", e))
+    }
+
+    fn load_settings(&self) -> Result<AIConfig, String> {
+        if !self.config_path.exists() {
+            return Ok(AIConfig::default());
+        }
+
+        let content = std::fs::read_to_string(&self.config_path)
+            .map_err(|e| format!("Failed to read AI config: {}", e))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse AI config: {}", e))
+    }
+}
+
+fn without_secrets(mut config: AIConfig) -> AIConfig {
+    config.openai_api_key = None;
+    config.claude_api_key = None;
+    config.gemini_api_key = None;
+    config
+}
+
+// The key save keeps in the file: None keeps the old one, an empty string removes it
+fn updated_key(key: Option<String>, previous: Option<String>) -> Option<String> {
+    match key {
+        None => previous,
+        Some(key) if key.trim().is_empty() => None,
+        Some(key) => Some(key.trim().to_string()),
+    }
+}
+
+fn required_key_entry(model: &AIModel) -> Option<&'static str> {
+    match model {
+        AIModel::OpenAIGPT4 | AIModel::OpenAIGPT35 => Some(OPENAI_KEY_ENTRY),
+        AIModel::Claude3 => Some(CLAUDE_KEY_ENTRY),
+        AIModel::Gemini => Some(GEMINI_KEY_ENTRY),
+        AIModel::Local => None,
+    }
+}
+
+// A key the keychain won't give up counts as not set, unless it's the one that's needed
+fn read_key(entry: &str, needed: Option<&str>) -> Result<Option<String>, String> {
+    match read_secret(entry) {
+        Ok(key) => Ok(key),
+        Err(e) if needed == Some(entry) => Err(e),
+        Err(e) => {
+            tracing::warn!("{}", e);
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(feature = "keychain")]
+fn keyring_entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, name)
+        .map_err(|e| format!("Failed to access OS keychain: {}", e))
+}
+
+#[cfg(feature = "keychain")]
+pub fn read_secret(name: &str) -> Result<Option<String>, String> {
+    match keyring_entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read {} from OS keychain: {}", name, e)),
+    }
+}
+
+#[cfg(feature = "keychain")]
+pub fn write_secret(name: &str, secret: Option<&str>) -> Result<(), String> {
+    let entry = keyring_entry(name)?;
+
+    match secret.map(str::trim) {
+        None => Ok(()),
+        Some("") => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to remove {} from OS keychain: {}", name, e)),
+        },
+        Some(secret) => entry.set_password(secret)
+            .map_err(|e| format!("Failed to store {} in OS keychain: {}", name, e)),
+    }
+}
+
+// Without the keychain, e.g. the CLI on a headless server, secrets are read from the
+// environment: youtube_api_key from VIDEO_NUGGET_YOUTUBE_API_KEY
+#[cfg(not(feature = "keychain"))]
+pub fn read_secret(name: &str) -> Result<Option<String>, String> {
+    Ok(std::env::var(secret_env_var(name)).ok().filter(|secret| !secret.trim().is_empty()))
+}
+
+#[cfg(not(feature = "keychain"))]
+pub fn write_secret(name: &str, secret: Option<&str>) -> Result<(), String> {
+    match secret.map(str::trim) {
+        None | Some("") => Ok(()),
+        Some(_) => Err(format!("This build has no OS keychain, set {} instead", secret_env_var(name))),
+    }
+}
+
+#[cfg(not(feature = "keychain"))]
+fn secret_env_var(name: &str) -> String {
+    let name: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("VIDEO_NUGGET_{}", name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persisted_settings_never_contain_keys() {
+        let config = AIConfig {
+            openai_api_key: Some("sk-secret".to_string()),
+            output_language: Some("es".to_string()),
+            ..AIConfig::default()
+        };
+
+        let content = serde_json::to_string(&without_secrets(config)).unwrap();
+        assert!(!content.contains("sk-secret"));
+        assert!(content.contains("\"es\""));
+    }
+
+    #[test]
+    fn test_missing_config_file_falls_back_to_defaults() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let store = AIConfigStore::new(temp_dir.path().to_path_buf());
+
+        let config = store.load_settings().unwrap();
+        assert!(matches!(config.model_preference, crate::ai_analyzer::AIModel::Local));
+        assert!(config.openai_api_key.is_none());
+    }
+
+    #[test]
+    fn test_keys_get_an_entry_each_and_empty_ones_are_refused() {
+        let providers = [ApiKeyProvider::OpenAI, ApiKeyProvider::Claude, ApiKeyProvider::Gemini, ApiKeyProvider::YouTube];
+        let entries: std::collections::HashSet<&str> = providers.iter().map(|provider| provider.entry()).collect();
+        assert_eq!(entries.len(), providers.len());
+
+        assert!(set_api_key(ApiKeyProvider::OpenAI, "  ").is_err());
+    }
+
+    #[test]
+    fn test_settings_without_keys_are_left_alone() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let store = AIConfigStore::new(temp_dir.path().to_path_buf());
+
+        store.move_keys_to_keychain(&AIConfig::default()).unwrap();
+        assert!(!temp_dir.path().join("ai_config.json").exists());
+    }
+
+    #[test]
+    fn test_keys_are_updated_in_the_file_without_a_keychain() {
+        assert_eq!(updated_key(None, Some("sk-old".to_string())), Some("sk-old".to_string()));
+        assert_eq!(updated_key(Some(" sk-new ".to_string()), Some("sk-old".to_string())), Some("sk-new".to_string()));
+        assert_eq!(updated_key(Some(String::new()), Some("sk-old".to_string())), None);
+    }
+
+    #[cfg(not(feature = "keychain"))]
+    #[test]
+    fn test_secrets_come_from_the_environment_without_a_keychain() {
+        assert_eq!(secret_env_var(YOUTUBE_KEY_ENTRY), "VIDEO_NUGGET_YOUTUBE_API_KEY");
+        assert_eq!(secret_env_var("tiktok_account_abc-1"), "VIDEO_NUGGET_TIKTOK_ACCOUNT_ABC_1");
+        assert!(write_secret(YOUTUBE_KEY_ENTRY, Some("")).is_ok());
+        assert!(write_secret(YOUTUBE_KEY_ENTRY, Some("key")).is_err());
+    }
+
+    #[test]
+    fn test_only_a_cloud_provider_needs_a_key() {
+        assert_eq!(required_key_entry(&AIModel::Local), None);
+        assert_eq!(required_key_entry(&AIModel::OpenAIGPT35), Some(OPENAI_KEY_ENTRY));
+        assert_eq!(required_key_entry(&AIModel::Claude3), Some(CLAUDE_KEY_ENTRY));
+    }
+}
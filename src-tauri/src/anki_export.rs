@@ -0,0 +1,450 @@
+use crate::VideoNugget;
+use crate::ai_analyzer::Flashcard;
+use crate::atomic_file::write_atomic;
+use ring::digest::{digest, SHA1_FOR_LEGACY_USE_ONLY};
+use rusqlite::{params, Connection};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::io::{Cursor, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+// Fixed, so importing a second deck reuses the note types instead of adding copies
+const NUGGET_MODEL_ID: i64 = 1_718_000_000_001;
+const FLASHCARD_MODEL_ID: i64 = 1_718_000_000_002;
+const DEFAULT_DECK_ID: i64 = 1;
+// The legacy collection format, which every Anki version since 2.1 still imports
+const SCHEMA_VERSION: i64 = 11;
+const FIELD_SEPARATOR: &str = "\x1f";
+
+const SCHEMA: &str = "
+CREATE TABLE col (id integer primary key, crt integer not null, mod integer not null, scm integer not null, ver integer not null, dty integer not null, usn integer not null, ls integer not null, conf text not null, models text not null, decks text not null, dconf text not null, tags text not null);
+CREATE TABLE notes (id integer primary key, guid text not null, mid integer not null, mod integer not null, usn integer not null, tags text not null, flds text not null, sfld integer not null, csum integer not null, flags integer not null, data text not null);
+CREATE TABLE cards (id integer primary key, nid integer not null, did integer not null, ord integer not null, mod integer not null, usn integer not null, type integer not null, queue integer not null, due integer not null, ivl integer not null, factor integer not null, reps integer not null, lapses integer not null, left integer not null, odue integer not null, odid integer not null, flags integer not null, data text not null);
+CREATE TABLE revlog (id integer primary key, cid integer not null, usn integer not null, ease integer not null, ivl integer not null, lastIvl integer not null, factor integer not null, time integer not null, type integer not null);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+CREATE INDEX ix_notes_usn on notes (usn);
+CREATE INDEX ix_cards_usn on cards (usn);
+CREATE INDEX ix_revlog_usn on revlog (usn);
+CREATE INDEX ix_cards_nid on cards (nid);
+CREATE INDEX ix_cards_sched on cards (did, queue, due);
+CREATE INDEX ix_revlog_cid on revlog (cid);
+CREATE INDEX ix_notes_csum on notes (csum);
+";
+
+const CARD_CSS: &str = ".card { font-family: arial; font-size: 20px; text-align: left; color: black; background-color: white; }\n.source { font-size: 14px; color: #666; }";
+
+struct Note {
+    guid: String,
+    model_id: i64,
+    fields: Vec<String>,
+    tags: Vec<String>,
+}
+
+// One card per nugget (title on the front, transcript and source link on the back) and one per
+// flashcard. source_url is the video's URL, the links jump to where each nugget starts.
+// Returns how many cards the deck has.
+pub fn write_apkg(deck_name: &str, source_url: Option<&str>, nuggets: &[VideoNugget], flashcards: &[Flashcard], path: &Path) -> Result<usize, String> {
+    let notes = build_notes(source_url, nuggets, flashcards);
+    if notes.is_empty() {
+        return Err("Nothing to export".to_string());
+    }
+
+    let temp_dir = tempfile::tempdir()
+        .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let collection_path = temp_dir.path().join("collection.anki2");
+    write_collection(&collection_path, deck_name, &notes)?;
+    let collection = std::fs::read(&collection_path)
+        .map_err(|e| format!("Failed to read Anki collection: {}", e))?;
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    // The deck has no media, but Anki expects the media map to be there
+    for (name, contents) in [("collection.anki2", collection.as_slice()), ("media", b"{}".as_slice())] {
+        writer.start_file(name, options)
+            .and_then(|_| writer.write_all(contents).map_err(Into::into))
+            .map_err(|e| format!("Failed to write Anki deck: {}", e))?;
+    }
+    let deck = writer.finish()
+        .map_err(|e| format!("Failed to finish Anki deck: {}", e))?;
+    write_atomic(path, deck.get_ref())
+        .map_err(|e| format!("Failed to write Anki deck: {}", e))?;
+
+    Ok(notes.len())
+}
+
+// YouTube takes the start time as a query parameter, anything else gets a media fragment
+pub fn timestamp_link(url: &str, seconds: f64) -> String {
+    let seconds = seconds.max(0.0).floor() as u64;
+    match url::Url::parse(url) {
+        Ok(mut parsed) if parsed.host_str().is_some_and(|host| host.contains("youtube.com") || host.contains("youtu.be")) => {
+            let pairs: Vec<(String, String)> = parsed.query_pairs()
+                .filter(|(key, _)| key != "t")
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            parsed.query_pairs_mut()
+                .clear()
+                .extend_pairs(pairs)
+                .append_pair("t", &format!("{}s", seconds));
+            parsed.to_string()
+        }
+        Ok(mut parsed) => {
+            parsed.set_fragment(Some(&format!("t={}", seconds)));
+            parsed.to_string()
+        }
+        Err(_) => format!("{}#t={}", url, seconds),
+    }
+}
+
+fn build_notes(source_url: Option<&str>, nuggets: &[VideoNugget], flashcards: &[Flashcard]) -> Vec<Note> {
+    let source_html = |nugget: &VideoNugget| -> String {
+        let label = format!("{} @ {}", escape_html(&nugget.title), format_time(nugget.start_time));
+        match source_url {
+            Some(url) => format!("<a class=\"source\" href=\"{}\">{}</a>", escape_html(&timestamp_link(url, nugget.start_time)), label),
+            None => format!("<span class=\"source\">{}</span>", label),
+        }
+    };
+
+    let mut notes: Vec<Note> = nuggets.iter()
+        .map(|nugget| {
+            let mut back = escape_html(nugget.transcript.as_deref().unwrap_or(""));
+            if let Some(notes) = nugget.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+                back.push_str(&format!("<br><br><i>{}</i>", escape_html(notes.trim())));
+            }
+            Note {
+                guid: guid(&format!("nugget:{}", nugget.id)),
+                model_id: NUGGET_MODEL_ID,
+                fields: vec![escape_html(&nugget.title), back, source_html(nugget)],
+                tags: nugget.tags.clone(),
+            }
+        })
+        .collect();
+
+    notes.extend(flashcards.iter().map(|card| {
+        let nugget = card.nugget_id.as_ref()
+            .and_then(|id| nuggets.iter().find(|n| &n.id == id));
+        Note {
+            guid: guid(&format!("flashcard:{}:{}", card.nugget_id.as_deref().unwrap_or(""), card.question)),
+            model_id: FLASHCARD_MODEL_ID,
+            fields: vec![
+                escape_html(&card.question),
+                escape_html(&card.answer),
+                nugget.map(source_html).unwrap_or_default(),
+            ],
+            tags: nugget.map(|n| n.tags.clone()).unwrap_or_default(),
+        }
+    }));
+
+    notes
+}
+
+fn write_collection(path: &Path, deck_name: &str, notes: &[Note]) -> Result<(), String> {
+    let now = chrono::Utc::now();
+    let now_ms = now.timestamp_millis();
+    let now_secs = now.timestamp();
+    let deck_id = deck_id(deck_name);
+
+    let mut connection = Connection::open(path)
+        .map_err(|e| format!("Failed to create Anki collection: {}", e))?;
+    connection.execute_batch(SCHEMA)
+        .map_err(|e| format!("Failed to create Anki collection: {}", e))?;
+
+    let transaction = connection.transaction()
+        .map_err(|e| format!("Failed to write Anki collection: {}", e))?;
+    transaction.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?2, ?3, 0, 0, 0, ?4, ?5, ?6, ?7, '{}')",
+        params![
+            now_secs - now_secs % 86_400,
+            now_ms,
+            SCHEMA_VERSION,
+            collection_conf(deck_id).to_string(),
+            models(deck_id, now_secs).to_string(),
+            decks(deck_id, deck_name, now_secs).to_string(),
+            deck_options().to_string(),
+        ],
+    ).map_err(|e| format!("Failed to write Anki collection: {}", e))?;
+
+    for (position, note) in notes.iter().enumerate() {
+        // Millisecond IDs like Anki's own, offset so they stay unique within the deck
+        let note_id = now_ms + position as i64;
+        let tags = note.tags.iter()
+            .map(|tag| tag.split_whitespace().collect::<Vec<_>>().join("_"))
+            .filter(|tag| !tag.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let sort_field = strip_html(&note.fields[0]);
+
+        transaction.execute(
+            "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')",
+            params![
+                note_id,
+                note.guid,
+                note.model_id,
+                now_secs,
+                if tags.is_empty() { String::new() } else { format!(" {} ", tags) },
+                note.fields.join(FIELD_SEPARATOR),
+                sort_field,
+                checksum(&sort_field),
+            ],
+        ).map_err(|e| format!("Failed to write Anki note: {}", e))?;
+
+        // New cards, due in the order the nuggets come in
+        transaction.execute(
+            "INSERT INTO cards VALUES (?1, ?2, ?3, 0, ?4, -1, 0, 0, ?5, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+            params![note_id, note_id, deck_id, now_secs, position as i64 + 1],
+        ).map_err(|e| format!("Failed to write Anki card: {}", e))?;
+    }
+
+    transaction.commit()
+        .map_err(|e| format!("Failed to write Anki collection: {}", e))
+}
+
+fn collection_conf(deck_id: i64) -> serde_json::Value {
+    json!({
+        "activeDecks": [deck_id],
+        "curDeck": deck_id,
+        "newSpread": 0,
+        "collapseTime": 1200,
+        "timeLim": 0,
+        "estTimes": true,
+        "dueCounts": true,
+        "curModel": NUGGET_MODEL_ID.to_string(),
+        "nextPos": 1,
+        "sortType": "noteFld",
+        "sortBackwards": false,
+        "addToCur": true
+    })
+}
+
+fn models(deck_id: i64, now_secs: i64) -> serde_json::Value {
+    let model = |id: i64, name: &str, fields: [&str; 3], front: &str, back: &str| json!({
+        "id": id,
+        "name": name,
+        "type": 0,
+        "mod": now_secs,
+        "usn": -1,
+        "sortf": 0,
+        "did": deck_id,
+        "tmpls": [{
+            "name": "Card 1",
+            "ord": 0,
+            "qfmt": front,
+            "afmt": back,
+            "did": null,
+            "bqfmt": "",
+            "bafmt": ""
+        }],
+        "flds": fields.iter().enumerate().map(|(ord, name)| json!({
+            "name": name,
+            "ord": ord,
+            "sticky": false,
+            "rtl": false,
+            "font": "Arial",
+            "size": 20,
+            "media": []
+        })).collect::<Vec<_>>(),
+        "css": CARD_CSS,
+        "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+        "latexPost": "\\end{document}",
+        "tags": [],
+        "vers": [],
+        "req": [[0, "any", [0]]]
+    });
+
+    json!({
+        NUGGET_MODEL_ID.to_string(): model(
+            NUGGET_MODEL_ID,
+            "Video Nugget",
+            ["Title", "Transcript", "Source"],
+            "{{Title}}",
+            "{{FrontSide}}<hr id=answer>{{Transcript}}<br><br>{{Source}}",
+        ),
+        FLASHCARD_MODEL_ID.to_string(): model(
+            FLASHCARD_MODEL_ID,
+            "Video Nugget Q&A",
+            ["Question", "Answer", "Source"],
+            "{{Question}}",
+            "{{FrontSide}}<hr id=answer>{{Answer}}<br><br>{{Source}}",
+        ),
+    })
+}
+
+fn decks(deck_id: i64, deck_name: &str, now_secs: i64) -> serde_json::Value {
+    let deck = |id: i64, name: &str| json!({
+        "id": id,
+        "name": name,
+        "mod": now_secs,
+        "usn": -1,
+        "desc": "",
+        "dyn": 0,
+        "conf": 1,
+        "collapsed": false,
+        "extendNew": 0,
+        "extendRev": 50,
+        "newToday": [0, 0],
+        "revToday": [0, 0],
+        "lrnToday": [0, 0],
+        "timeToday": [0, 0]
+    });
+
+    json!({
+        DEFAULT_DECK_ID.to_string(): deck(DEFAULT_DECK_ID, "Default"),
+        deck_id.to_string(): deck(deck_id, deck_name),
+    })
+}
+
+fn deck_options() -> serde_json::Value {
+    json!({
+        "1": {
+            "id": 1,
+            "name": "Default",
+            "mod": 0,
+            "usn": 0,
+            "maxTaken": 60,
+            "autoplay": true,
+            "timer": 0,
+            "replayq": true,
+            "dyn": false,
+            "new": {
+                "bury": true,
+                "delays": [1, 10],
+                "initialFactor": 2500,
+                "ints": [1, 4, 7],
+                "order": 1,
+                "perDay": 20,
+                "separate": true
+            },
+            "rev": {
+                "bury": true,
+                "ease4": 1.3,
+                "fuzz": 0.05,
+                "ivlFct": 1,
+                "maxIvl": 36500,
+                "minSpace": 1,
+                "perDay": 100
+            },
+            "lapse": {
+                "delays": [10],
+                "leechAction": 0,
+                "leechFails": 8,
+                "minInt": 1,
+                "mult": 0
+            }
+        }
+    })
+}
+
+// Stable per deck name, so exporting the same deck again updates it in Anki
+fn deck_id(deck_name: &str) -> i64 {
+    let hash = Sha256::digest(deck_name.as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes[2..].copy_from_slice(&hash[..6]);
+    // Kept above the default deck's ID
+    i64::from_be_bytes(bytes) + 2
+}
+
+fn guid(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))[..16].to_string()
+}
+
+// Anki finds duplicate notes by the first 8 hex digits of the SHA-1 of the sort field
+fn checksum(sort_field: &str) -> i64 {
+    let hash = digest(&SHA1_FOR_LEGACY_USE_ONLY, sort_field.as_bytes());
+    let bytes = hash.as_ref();
+    i64::from(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "<br>")
+}
+
+fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+pub fn format_time(seconds: f64) -> String {
+    let total = seconds.max(0.0).floor() as u64;
+    if total >= 3600 {
+        format!("{}:{:02}:{:02}", total / 3600, total % 3600 / 60, total % 60)
+    } else {
+        format!("{}:{:02}", total / 60, total % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nugget(id: &str, title: &str, start_time: f64) -> VideoNugget {
+        VideoNugget {
+            id: id.to_string(),
+            title: title.to_string(),
+            start_time,
+            end_time: start_time + 30.0,
+            transcript: Some("Mitochondria <b>make</b> ATP".to_string()),
+            tags: vec!["cell biology".to_string()],
+            notes: None,
+            youtube_video_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn test_timestamp_link() {
+        assert_eq!(timestamp_link("https://www.youtube.com/watch?v=abc&t=5s", 75.6), "https://www.youtube.com/watch?v=abc&t=75s");
+        assert_eq!(timestamp_link("https://youtu.be/abc", 12.0), "https://youtu.be/abc?t=12s");
+        assert_eq!(timestamp_link("https://example.com/lecture.mp4", 90.0), "https://example.com/lecture.mp4#t=90");
+    }
+
+    #[test]
+    fn test_build_notes() {
+        let nuggets = vec![nugget("n1", "Powerhouse", 125.0)];
+        let flashcards = vec![Flashcard {
+            question: "What do mitochondria make?".to_string(),
+            answer: "ATP".to_string(),
+            nugget_id: Some("n1".to_string()),
+        }];
+        let notes = build_notes(Some("https://youtu.be/abc"), &nuggets, &flashcards);
+
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].fields[1], "Mitochondria &lt;b&gt;make&lt;/b&gt; ATP");
+        assert!(notes[0].fields[2].contains("https://youtu.be/abc?t=125s"));
+        assert!(notes[0].fields[2].contains("Powerhouse @ 2:05"));
+        assert_eq!(notes[1].model_id, FLASHCARD_MODEL_ID);
+        assert_eq!(notes[1].tags, vec!["cell biology".to_string()]);
+        // Exporting again gives the same notes, so Anki updates rather than duplicates them
+        assert_eq!(build_notes(None, &nuggets, &flashcards)[0].guid, notes[0].guid);
+        assert_eq!(strip_html(&notes[0].fields[1]), "Mitochondria <b>make</b> ATP");
+    }
+
+    #[test]
+    fn test_write_apkg() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("lecture.apkg");
+        let cards = write_apkg("Biology::Lecture 1", Some("https://youtu.be/abc"), &[nugget("n1", "Powerhouse", 0.0)], &[], &path).unwrap();
+        assert_eq!(cards, 1);
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert!(archive.by_name("collection.anki2").is_ok());
+        assert!(archive.by_name("media").is_ok());
+        assert!(write_apkg("Empty", None, &[], &[], &path).is_err());
+    }
+}
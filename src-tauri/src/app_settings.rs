@@ -0,0 +1,215 @@
+use crate::atomic_file::{self, write_atomic};
+use crate::export_format::ExportFormatting;
+use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
+
+const DOWNLOAD_QUALITIES: [&str; 4] = ["best", "worst", "720p", "480p"];
+
+// Settings that have to be known before the workspace can be opened, so they live in the
+// app config dir rather than in the workspace. AI keys aren't here, they are per workspace
+// in ai_config_store and the OS keychain.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AppSettings {
+    #[serde(default)]
+    pub workspace_path: Option<PathBuf>,
+    // Read every saved file back and compare it, see atomic_file
+    #[serde(default)]
+    pub verify_writes: bool,
+    #[serde(default)]
+    pub tools: ToolPaths,
+    #[serde(default)]
+    pub defaults: DefaultSettings,
+    #[serde(default)]
+    pub progress_stream: ProgressStreamSettings,
+}
+
+// Unset tools are looked up on the PATH and in the usual install locations
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ToolPaths {
+    pub ffmpeg: Option<PathBuf>,
+    pub yt_dlp: Option<PathBuf>,
+    pub whisper: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct DefaultSettings {
+    // yt-dlp quality for downloads that don't ask for one: "best", "worst", "720p" or "480p"
+    pub download_quality: String,
+    // For exports that don't bring their own formatting
+    pub export_formatting: Option<ExportFormatting>,
+    // Re-encode with the GPU's H.264 encoder when detect_capabilities finds one that works
+    pub hardware_encoding: bool,
+    // Units a day the YouTube API key may spend; 10,000 unless Google granted more
+    pub youtube_daily_quota: u64,
+    // Two-letter country code YouTube names categories for, "US" and so on
+    pub youtube_region: String,
+}
+
+impl Default for DefaultSettings {
+    fn default() -> Self {
+        Self {
+            download_quality: "best".to_string(),
+            export_formatting: None,
+            hardware_encoding: true,
+            youtube_daily_quota: 10_000,
+            youtube_region: "US".to_string(),
+        }
+    }
+}
+
+// The localhost event stream of operation progress, see progress_stream.rs
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ProgressStreamSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for ProgressStreamSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7788,
+        }
+    }
+}
+
+impl AppSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        let tools = [("FFmpeg", &self.tools.ffmpeg), ("yt-dlp", &self.tools.yt_dlp), ("Whisper", &self.tools.whisper)];
+        for (name, path) in tools {
+            if let Some(path) = path.as_ref().filter(|path| !path.is_file()) {
+                return Err(format!("{} not found at {}", name, path.display()));
+            }
+        }
+        if !DOWNLOAD_QUALITIES.contains(&self.defaults.download_quality.as_str()) {
+            return Err(format!("Unknown download quality: {}", self.defaults.download_quality));
+        }
+        if let Some(formatting) = &self.defaults.export_formatting {
+            formatting.validate()?;
+        }
+        if self.defaults.youtube_daily_quota == 0 {
+            return Err("YouTube daily quota must be above 0".to_string());
+        }
+        let region = &self.defaults.youtube_region;
+        if region.len() != 2 || !region.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(format!("Invalid YouTube region: {}", region));
+        }
+        if self.progress_stream.port < 1024 {
+            return Err("Progress stream port must be 1024 or higher".to_string());
+        }
+        Ok(())
+    }
+}
+
+// The settings in effect, for modules that run without access to the store. Saving doesn't
+// change them, apply does
+static CURRENT: LazyLock<RwLock<AppSettings>> = LazyLock::new(Default::default);
+
+pub fn current() -> AppSettings {
+    CURRENT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub fn apply(settings: AppSettings) {
+    atomic_file::set_verify_writes(settings.verify_writes);
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = settings;
+}
+
+// The configured tools, else whichever are on the PATH
+pub fn ffmpeg() -> PathBuf {
+    current().tools.ffmpeg.unwrap_or_else(|| PathBuf::from("ffmpeg"))
+}
+
+pub fn yt_dlp() -> PathBuf {
+    current().tools.yt_dlp.unwrap_or_else(|| PathBuf::from("yt-dlp"))
+}
+
+pub struct AppSettingsStore {
+    settings_path: PathBuf,
+}
+
+impl AppSettingsStore {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            settings_path: config_dir.join("settings.json"),
+        }
+    }
+
+    pub fn load(&self) -> Result<AppSettings, String> {
+        if !self.settings_path.exists() {
+            return Ok(AppSettings::default());
+        }
+
+        let content = std::fs::read_to_string(&self.settings_path)
+            .map_err(|e| format!("Failed to read app settings: {}", e))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse app settings: {}", e))
+    }
+
+    pub fn save(&self, settings: &AppSettings) -> Result<(), String> {
+        if let Some(parent) = self.settings_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let content = serde_json::to_string_pretty(settings)
+            .map_err(|e| format!("Failed to serialize app settings: {}", e))?;
+
+        write_atomic(&self.settings_path, content.as_bytes())
+            .map_err(|e| format!("Failed to save app settings: {}", e))
+    }
+
+    // Everything but the workspace path, which only move_workspace changes
+    pub fn update(&self, mut settings: AppSettings) -> Result<AppSettings, String> {
+        settings.validate()?;
+        settings.workspace_path = self.load()?.workspace_path;
+        self.save(&settings)?;
+        Ok(settings)
+    }
+
+    // The configured workspace, else ./workspace if an older version created one there, else
+    // a folder in the app data dir
+    pub fn workspace_path(&self, app_data_dir: &Path) -> PathBuf {
+        let settings = self.load().unwrap_or_else(|e| {
+            tracing::warn!("Failed to load app settings: {}", e);
+            AppSettings::default()
+        });
+        if let Some(workspace_path) = settings.workspace_path {
+            return workspace_path;
+        }
+
+        match std::env::current_dir() {
+            Ok(dir) if dir.join("workspace").is_dir() => dir.join("workspace"),
+            _ => app_data_dir.join("workspace"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_keeps_workspace_path() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let store = AppSettingsStore::new(temp_dir.path().to_path_buf());
+        // Written by a version without tools or defaults
+        std::fs::write(temp_dir.path().join("settings.json"), r#"{"workspace_path": "/data/nuggets"}"#).unwrap();
+        assert_eq!(store.load().unwrap().defaults.download_quality, "best");
+
+        let mut settings = AppSettings::default();
+        settings.defaults.download_quality = "720p".to_string();
+        let updated = store.update(settings.clone()).unwrap();
+        assert_eq!(updated.workspace_path, Some(PathBuf::from("/data/nuggets")));
+        assert_eq!(store.load().unwrap().defaults.download_quality, "720p");
+
+        settings.defaults.download_quality = "4k".to_string();
+        assert!(store.update(settings.clone()).is_err());
+        settings.defaults.download_quality = "best".to_string();
+        settings.tools.ffmpeg = Some(temp_dir.path().join("missing-ffmpeg"));
+        assert!(store.update(settings).is_err());
+    }
+}
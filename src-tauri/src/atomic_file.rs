@@ -0,0 +1,119 @@
+use std::fs::File;
+use std::io::{Error, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use uuid::Uuid;
+
+// Every save checks it, so it's one switch for the whole app rather than an option threaded
+// through each FileManager and ProjectManager call
+static VERIFY_WRITES: AtomicBool = AtomicBool::new(false);
+
+// With verification on, each write is read back and compared once it's in place
+pub fn set_verify_writes(enabled: bool) {
+    VERIFY_WRITES.store(enabled, Ordering::Relaxed);
+}
+
+pub fn verify_writes() -> bool {
+    VERIFY_WRITES.load(Ordering::Relaxed)
+}
+
+// Writes a temporary file next to `path`, syncs it and renames it over the original, so readers
+// and a crash mid-write only ever see the old or the new contents. The directory is synced too,
+// so the rename itself survives a power loss
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let temp_path = temp_path(path);
+    File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| persist(&temp_path, path))
+        .inspect_err(|_| {
+            let _ = std::fs::remove_file(&temp_path);
+        })?;
+
+    if verify_writes() && std::fs::read(path)? != contents {
+        return Err(Error::new(ErrorKind::InvalidData, format!("{} doesn't match what was written", path.display())));
+    }
+    Ok(())
+}
+
+pub async fn write_atomic_async(path: impl AsRef<Path>, contents: impl Into<Vec<u8>>) -> std::io::Result<()> {
+    let path = path.as_ref().to_path_buf();
+    let contents = contents.into();
+    tokio::task::spawn_blocking(move || write_atomic(&path, &contents))
+        .await
+        .map_err(Error::other)?
+}
+
+// For files too big to build in memory first: write and sync temp_path(path), then persist it
+pub fn temp_path(path: &Path) -> PathBuf {
+    // Unique, so two saves of the same file can't write into each other's temp file
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(path.file_name().unwrap_or_default());
+    temp_name.push(format!(".{}.tmp", Uuid::new_v4().simple()));
+    path.with_file_name(temp_name)
+}
+
+// Renames a synced temp file over path, then syncs the directory
+pub fn persist(temp_path: &Path, path: &Path) -> std::io::Result<()> {
+    std::fs::rename(temp_path, path)?;
+    sync_dir(path)
+}
+
+// Copies into a temp file first, so an interrupted copy never leaves a truncated file at `to`
+pub fn copy_atomic(from: &Path, to: &Path) -> std::io::Result<u64> {
+    let temp_path = temp_path(to);
+    let result = std::fs::copy(from, &temp_path)
+        .and_then(|size| File::open(&temp_path)?.sync_all().map(|_| size))
+        .and_then(|size| persist(&temp_path, to).map(|_| size));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    let size = result?;
+
+    if verify_writes() && std::fs::read(from)? != std::fs::read(to)? {
+        return Err(Error::new(ErrorKind::InvalidData, format!("{} doesn't match {}", to.display(), from.display())));
+    }
+    Ok(size)
+}
+
+// Windows has no way to sync a directory; NTFS journals the rename instead
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> std::io::Result<()> {
+    let dir = path.parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_and_copy_atomic() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let file = temp_dir.path().join("nuggets.json");
+        write_atomic(&file, b"[1]").unwrap();
+        write_atomic(&file, b"[2]").unwrap();
+        assert_eq!(std::fs::read(&file).unwrap(), b"[2]");
+
+        let copy = temp_dir.path().join("backup.json");
+        assert_eq!(copy_atomic(&file, &copy).unwrap(), 3);
+        assert_eq!(std::fs::read(&copy).unwrap(), b"[2]");
+
+        // Nothing left behind
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+
+        // The temp file goes in the same directory, or the rename could cross file systems
+        let missing_dir = temp_dir.path().join("missing").join("file.json");
+        assert!(write_atomic(&missing_dir, b"{}").is_err());
+        assert_eq!(temp_path(&file).parent(), Some(temp_dir.path()));
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+// A backup is kept if any rule keeps it; with every count at 0 nothing is ever pruned
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BackupRetention {
+    // The most recent backups, whatever their age
+    pub keep_last: usize,
+    // The newest backup of each of the last N days that have one
+    pub keep_daily: usize,
+    // The newest backup of each of the last N weeks that have one
+    pub keep_weekly: usize,
+}
+
+impl Default for BackupRetention {
+    fn default() -> Self {
+        Self {
+            keep_last: 5,
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+impl BackupRetention {
+    fn keeps_everything(&self) -> bool {
+        self.keep_last == 0 && self.keep_daily == 0 && self.keep_weekly == 0
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+// Backups the policy doesn't keep, for the caller to delete
+pub fn backups_to_prune(backups: &[BackupInfo], retention: &BackupRetention) -> Vec<PathBuf> {
+    if retention.keeps_everything() {
+        return Vec::new();
+    }
+
+    let mut newest_first: Vec<&BackupInfo> = backups.iter().collect();
+    newest_first.sort_by_key(|backup| Reverse(backup.created_at));
+
+    let mut kept: HashSet<usize> = (0..retention.keep_last.min(newest_first.len())).collect();
+
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    for (index, backup) in newest_first.iter().enumerate() {
+        let day = backup.created_at.date_naive();
+        if days.len() < retention.keep_daily && days.insert(day) {
+            kept.insert(index);
+        }
+
+        let week = backup.created_at.iso_week();
+        if weeks.len() < retention.keep_weekly && weeks.insert((week.year(), week.week())) {
+            kept.insert(index);
+        }
+    }
+
+    newest_first.iter()
+        .enumerate()
+        .filter(|(index, _)| !kept.contains(index))
+        .map(|(_, backup)| backup.path.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn backup(day: u32, hour: u32) -> BackupInfo {
+        BackupInfo {
+            path: PathBuf::from(format!("backup_{}_{}.json", day, hour)),
+            created_at: Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap(),
+            size_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_backups_to_prune() {
+        // 2024-03-04 is a Monday; two backups a day for two weeks
+        let backups: Vec<BackupInfo> = (4..18)
+            .flat_map(|day| [backup(day, 6), backup(day, 18)])
+            .collect();
+
+        let retention = BackupRetention { keep_last: 3, keep_daily: 2, keep_weekly: 2 };
+        let mut pruned = backups_to_prune(&backups, &retention);
+        pruned.sort();
+        let mut kept: Vec<PathBuf> = backups.iter()
+            .map(|b| b.path.clone())
+            .filter(|path| !pruned.contains(path))
+            .collect();
+        kept.sort();
+
+        // Last 3, the newest of the 17th and 16th, and the newest of both ISO weeks
+        let expected: Vec<PathBuf> = ["backup_10_18.json", "backup_16_18.json", "backup_17_18.json", "backup_17_6.json"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(kept, expected);
+
+        assert!(backups_to_prune(&backups, &BackupRetention { keep_last: 0, keep_daily: 0, keep_weekly: 0 }).is_empty());
+    }
+}
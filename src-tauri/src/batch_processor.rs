@@ -1,14 +1,39 @@
-use crate::{VideoNugget, ProcessingResult, VideoInfo};
+use crate::{VideoNugget, VideoInfo};
 use crate::video_processor::VideoProcessor;
-use crate::ffmpeg_processor::FFmpegProcessor;
-use crate::speech_recognition::SpeechRecognizer;
-use crate::ai_analyzer::{AIAnalyzer, ContentAnalysis};
+use crate::ffmpeg_processor::{run_cancellable, DownloadProgress};
+use crate::speech_recognition::SpeechAnalysis;
+use crate::processors::Processors;
+use crate::ai_analyzer::{AIAnalyzer, AIConfig, AnalysisCache, CircuitBreakers, ContentAnalysis, PromptTemplate};
+use crate::ai_config_store::{self, AIConfigStore, ApiKeyProvider};
+use crate::app_settings;
+use crate::file_manager::{CsvOptions, FileManager};
+use crate::export_format::ExportFormatting;
+use crate::export_filter::ExportFilter;
+use crate::job_scheduler::{JobPriority, JobScheduler};
+use crate::output_template::{self, TemplateContext, COMBINED_EXPORT_NAME};
+use crate::processed_index::{self, ProcessedIndex, PROCESSED_INDEX_FILE};
+use crate::project_crypto::{self, DataKey};
+use crate::resource_governor::{ResourceGovernor, ResourceKind, ResourcePolicy};
+use crate::download_throttle::{DownloadPacing, DownloadThrottle};
+use crate::disk_space::{self, DiskSpaceEstimate, DiskSpacePolicy, EstimateInput};
+use crate::batch_report::BatchReport;
+use crate::export_manifest;
+use crate::pipeline_artifacts::ArtifactStore;
+use crate::project_manager::{ProcessingEvent, ProjectManager};
+use crate::youtube_api::YouTubeAPI;
+use crate::youtube_cache::ResponseCache;
+use crate::youtube_quota::ApiQuota;
+use futures::future::BoxFuture;
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
-use tokio::sync::mpsc;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BatchJob {
     pub id: String,
     pub name: String,
@@ -20,9 +45,34 @@ pub struct BatchJob {
     pub completed_at: Option<String>,
     pub progress: BatchProgress,
     pub results: Vec<BatchResult>,
+    // Set when the app quit while the job was running; start_batch_job picks up the remaining URLs
+    #[serde(default)]
+    pub interrupted: bool,
+    #[serde(default)]
+    pub priority: JobPriority,
+    // Failed results replaced by a retry_failed_videos run, kept for the job's history
+    #[serde(default)]
+    pub retried_results: Vec<BatchResult>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    // Jobs that must complete before this one starts; it then starts on its own
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    // Jobs of one pipeline share downloads, transcripts and nuggets
+    #[serde(default)]
+    pub pipeline_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl BatchJob {
+    pub fn remaining_urls(&self) -> Vec<String> {
+        self.urls.iter()
+            .filter(|url| !self.results.iter().any(|r| &r.url == *url))
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BatchConfig {
     pub video_config: HashMap<String, serde_json::Value>,
     pub output_directory: String,
@@ -33,9 +83,113 @@ pub struct BatchConfig {
     pub concurrent_jobs: usize,
     pub retry_failed: bool,
     pub max_retries: u32,
+    // Where each video's clips and exports go inside output_directory, see output_template.rs
+    #[serde(default = "default_output_template")]
+    pub output_template: String,
+    // Skip videos already in the processed-video index of project_id (or of the batch
+    // workspace when there is no project)
+    #[serde(default)]
+    pub skip_duplicates: bool,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    // What to do when the pre-flight estimate says the batch won't fit on disk
+    #[serde(default)]
+    pub disk_space_policy: DiskSpacePolicy,
+    // Keyed by URL, for playlists where a few videos need different settings
+    #[serde(default)]
+    pub url_overrides: HashMap<String, VideoOverride>,
+    // None runs everything the flags above ask for in one go
+    #[serde(default)]
+    pub stage: Option<PipelineStage>,
+    // Delay, jitter and per-host limit between this job's downloads
+    #[serde(default)]
+    pub download_pacing: DownloadPacing,
+    // For the csv export format
+    #[serde(default)]
+    pub csv_options: CsvOptions,
+    // Timestamps and dates in the exports; None uses the app's default
+    #[serde(default)]
+    pub formatting: Option<ExportFormatting>,
+    // Which of each video's nuggets go into the exports; clips are still cut for all of them
+    #[serde(default)]
+    pub export_filter: Option<ExportFilter>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum PipelineStage {
+    // Download and transcript only
+    Transcribe,
+    // Nuggets, AI analysis and exports, from the transcript of an earlier stage
+    Analyze,
+    // Clips and social formats for the nuggets of an earlier stage
+    RenderClips,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct VideoOverride {
+    pub nugget_duration: Option<f64>,
+    // Also turns off AI analysis for the video, it works on the transcript
+    pub skip_transcription: Option<bool>,
+    // yt-dlp quality: "best", "worst", "720p" or "480p"
+    pub quality: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+impl BatchConfig {
+    // The job's config with the URL's overrides applied
+    pub fn for_url(&self, url: &str) -> BatchConfig {
+        let mut config = self.clone();
+        config.url_overrides.clear();
+
+        let Some(video_override) = self.url_overrides.get(url) else {
+            return config;
+        };
+
+        if let Some(nugget_duration) = video_override.nugget_duration {
+            config.video_config.insert("nugget_duration".to_string(), serde_json::json!(nugget_duration));
+        }
+        if let Some(skip_transcription) = video_override.skip_transcription {
+            config.enable_transcript = !skip_transcription;
+            config.video_config.insert("extract_transcript".to_string(), serde_json::json!(!skip_transcription));
+            if skip_transcription {
+                config.enable_ai_analysis = false;
+            }
+        }
+        if let Some(quality) = &video_override.quality {
+            config.video_config.insert("quality".to_string(), serde_json::json!(quality));
+        }
+
+        config
+    }
+
+    pub fn download_quality(&self) -> &str {
+        self.video_config.get("quality")
+            .and_then(|quality| quality.as_str())
+            .unwrap_or(BATCH_DOWNLOAD_QUALITY)
+    }
+}
+
+// Videos without a quality of their own download at the app's default
+pub fn parse_batch_config(config: serde_json::Value) -> Result<BatchConfig, String> {
+    let mut batch_config: BatchConfig = serde_json::from_value(config)
+        .map_err(|e| format!("Invalid batch config: {}", e))?;
+    output_template::validate_template(&batch_config.output_template)?;
+    batch_config.download_pacing.validate()?;
+    if let Some(formatting) = &batch_config.formatting {
+        formatting.validate()?;
+    }
+    if let Some(filter) = &batch_config.export_filter {
+        filter.validate()?;
+    }
+    batch_config.video_config.entry("quality".to_string())
+        .or_insert_with(|| serde_json::json!(app_settings::current().defaults.download_quality));
+    Ok(batch_config)
+}
+
+fn default_output_template() -> String {
+    output_template::DEFAULT_OUTPUT_TEMPLATE.to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum BatchStatus {
     Pending,
     Running,
@@ -45,7 +199,7 @@ pub enum BatchStatus {
     Paused,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BatchProgress {
     pub total_videos: usize,
     pub processed_videos: usize,
@@ -54,21 +208,49 @@ pub struct BatchProgress {
     pub percentage: f64,
     pub eta_minutes: Option<f64>,
     pub start_time: Option<i64>,
+    // Videos being worked on right now, in job order
+    #[serde(default)]
+    pub active_videos: Vec<VideoProgress>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoProgress {
+    pub url: String,
+    // 1-based position in the job
+    pub index: usize,
+    pub stage: VideoStage,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum VideoStage {
+    // percent is None until yt-dlp reports any, and for direct downloads
+    Downloading { percent: Option<f64> },
+    // Waiting for a download, FFmpeg or transcription slot
+    WaitingForResources,
+    FindingNuggets,
+    RenderingClip { current: usize, total: usize },
+    Transcribing,
+    Analyzing,
+    Exporting,
+    Retrying { attempt: u32 },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BatchResult {
     pub url: String,
     pub video_info: Option<VideoInfo>,
     pub nuggets: Vec<VideoNugget>,
     pub analysis: Option<ContentAnalysis>,
     pub output_files: Vec<String>,
+    // Recorded in the video's processing history when the job adds it to a project
+    #[serde(default)]
+    pub events: Vec<ProcessingEvent>,
     pub status: ProcessingStatus,
     pub error_message: Option<String>,
     pub processing_time_seconds: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum ProcessingStatus {
     Success,
     Failed,
@@ -78,22 +260,281 @@ pub enum ProcessingStatus {
 
 pub struct BatchProcessor {
     jobs: HashMap<String, BatchJob>,
-    ffmpeg_processor: FFmpegProcessor,
-    speech_recognizer: SpeechRecognizer,
-    ai_analyzer: Option<AIAnalyzer>,
+    processors: Arc<Processors>,
+    storage_dir: Option<PathBuf>,
+    projects_root: Option<PathBuf>,
+    cancel_tokens: HashMap<String, CancellationToken>,
+    // Jobs load_jobs found still running, which only a crash or forced quit leaves behind
+    recovered_jobs: HashSet<String>,
+    scheduler: Arc<JobScheduler>,
+    resource_governor: Arc<ResourceGovernor>,
+    project_manager: Option<Arc<Mutex<ProjectManager>>>,
+    ai_config_store: Option<Arc<Mutex<AIConfigStore>>>,
+    youtube_quota: Option<Arc<ApiQuota>>,
+    youtube_cache: Option<ResponseCache>,
+    circuit_breakers: CircuitBreakers,
+    on_job_finished: Option<CompletionHook>,
+}
+
+type SharedProcessedIndex = Arc<std::sync::Mutex<ProcessedIndex>>;
+type UpdateCallback = Arc<dyn Fn(&BatchJob) + Send + Sync>;
+pub type CompletionHook = Arc<dyn Fn(&BatchJob) + Send + Sync>;
+
+enum RunEvent {
+    Stage(VideoProgress),
+    Finished(Box<BatchResult>),
+}
+
+// Lets a video tell the run's collector which stage it's in
+#[derive(Clone)]
+struct StageReporter {
+    url: String,
+    index: usize,
+    events: mpsc::UnboundedSender<RunEvent>,
 }
 
+impl StageReporter {
+    fn report(&self, stage: VideoStage) {
+        let _ = self.events.send(RunEvent::Stage(VideoProgress {
+            url: self.url.clone(),
+            index: self.index,
+            stage,
+        }));
+    }
+
+    // yt-dlp prints several progress lines a second, only whole-percent changes are passed on
+    fn download_progress(&self) -> DownloadProgress {
+        let reporter = self.clone();
+        let last_percent = AtomicU32::new(u32::MAX);
+        Arc::new(move |percent| {
+            if last_percent.swap(percent as u32, Ordering::Relaxed) != percent as u32 {
+                reporter.report(VideoStage::Downloading { percent: Some(percent) });
+            }
+        })
+    }
+}
+
+// Everything a run needs once it leaves the processor lock
+struct JobRun {
+    // Paired with the 1-based position in the whole job, so {index} stays stable across resumes
+    urls: Vec<(usize, String)>,
+    context: RunContext,
+}
+
+// Shared by all videos of one run
+#[derive(Clone)]
+struct RunContext {
+    job_id: String,
+    config: BatchConfig,
+    cancel_token: CancellationToken,
+    processors: Arc<Processors>,
+    processed_index: Option<SharedProcessedIndex>,
+    resource_governor: Arc<ResourceGovernor>,
+    artifacts: Option<Arc<ArtifactStore>>,
+    download_throttle: Arc<DownloadThrottle>,
+    analysis: Option<Arc<AnalysisSetup>>,
+}
+
+// What each video's AIAnalyzer is built from; the analyzer itself needs the video's duration
+struct AnalysisSetup {
+    config: AIConfig,
+    prompt_template: PromptTemplate,
+    cache_dir: Option<PathBuf>,
+    data_key: Option<DataKey>,
+    // Adds YouTube's category to the analysis when there's a key
+    youtube: Option<YouTubeAPI>,
+    circuit_breakers: CircuitBreakers,
+}
+
+// A video that made it through every stage of one attempt
+struct ProcessedVideo {
+    video_info: VideoInfo,
+    nuggets: Vec<VideoNugget>,
+    analysis: Option<ContentAnalysis>,
+    output_files: Vec<String>,
+    events: Vec<ProcessingEvent>,
+}
+
+const RESOURCE_POLICY_FILE: &str = "resource_policy.json";
+
+// Videos processed at once across all jobs; each one is a download plus FFmpeg transcodes
+const GLOBAL_VIDEO_SLOTS: usize = 2;
+
+const BATCH_DOWNLOAD_QUALITY: &str = "best";
+
 impl BatchProcessor {
-    pub fn new(ai_analyzer: Option<AIAnalyzer>) -> Result<Self, String> {
+    pub fn new() -> Result<Self, String> {
         Ok(Self {
             jobs: HashMap::new(),
-            ffmpeg_processor: FFmpegProcessor::new()?,
-            speech_recognizer: SpeechRecognizer::new()?,
-            ai_analyzer,
+            processors: Arc::new(Processors::new()),
+            storage_dir: None,
+            projects_root: None,
+            cancel_tokens: HashMap::new(),
+            recovered_jobs: HashSet::new(),
+            scheduler: Arc::new(JobScheduler::new(GLOBAL_VIDEO_SLOTS)),
+            resource_governor: Arc::new(ResourceGovernor::new(ResourcePolicy::default())),
+            project_manager: None,
+            ai_config_store: None,
+            youtube_quota: None,
+            youtube_cache: None,
+            circuit_breakers: CircuitBreakers::default(),
+            on_job_finished: None,
         })
     }
 
-    pub fn create_batch_job(&mut self, name: String, urls: Vec<String>, config: BatchConfig) -> String {
+    pub fn with_storage(mut self, storage_dir: PathBuf) -> Result<Self, String> {
+        std::fs::create_dir_all(&storage_dir)
+            .map_err(|e| format!("Failed to create batch job directory: {}", e))?;
+
+        let policy = ResourcePolicy::load(&storage_dir.join(RESOURCE_POLICY_FILE))?;
+        self.resource_governor = Arc::new(ResourceGovernor::new(policy));
+        self.storage_dir = Some(storage_dir);
+        Ok(self)
+    }
+
+    pub fn resource_policy(&self) -> ResourcePolicy {
+        self.resource_governor.policy().clone()
+    }
+
+    // Runs that are already going keep the limits they started with
+    pub fn set_resource_policy(&mut self, policy: ResourcePolicy) -> Result<(), String> {
+        policy.validate()?;
+        if let Some(storage_dir) = &self.storage_dir {
+            policy.save(&storage_dir.join(RESOURCE_POLICY_FILE))?;
+        }
+
+        self.resource_governor = Arc::new(ResourceGovernor::new(policy));
+        Ok(())
+    }
+
+    // Project folders live directly under this root, see ProjectManager::create_project
+    pub fn with_projects_root(mut self, projects_root: PathBuf) -> Self {
+        self.projects_root = Some(projects_root);
+        self
+    }
+
+    // Batch analysis uses the AI settings and keys saved from the settings screen
+    pub fn with_ai_config_store(mut self, ai_config_store: Arc<Mutex<AIConfigStore>>) -> Self {
+        self.ai_config_store = Some(ai_config_store);
+        self
+    }
+
+    // Playlists are listed through the YouTube Data API, counted against the app's quota, when
+    // a YouTube key is set
+    pub fn with_youtube(mut self, quota: Arc<ApiQuota>, cache: ResponseCache) -> Self {
+        self.youtube_quota = Some(quota);
+        self.youtube_cache = Some(cache);
+        self
+    }
+
+    fn youtube_api(&self) -> Option<YouTubeAPI> {
+        let api_key = ai_config_store::api_key(ApiKeyProvider::YouTube).ok().flatten()?;
+        let mut youtube = YouTubeAPI::new(Some(api_key));
+        if let Some(quota) = &self.youtube_quota {
+            youtube = youtube.with_quota(quota.clone());
+        }
+        if let Some(cache) = &self.youtube_cache {
+            youtube = youtube.with_cache(cache.clone());
+        }
+        Some(youtube)
+    }
+
+    // The app's breakers, so a provider that keeps failing is left alone by every job and command
+    pub fn with_circuit_breakers(mut self, circuit_breakers: CircuitBreakers) -> Self {
+        self.circuit_breakers = circuit_breakers;
+        self
+    }
+
+    // Called once when a run completes or fails, not on cancel or pause
+    pub fn with_completion_hook(mut self, hook: CompletionHook) -> Self {
+        self.on_job_finished = Some(hook);
+        self
+    }
+
+    pub fn has_running_jobs(&self) -> bool {
+        self.jobs.values().any(|job| job.status == BatchStatus::Running)
+    }
+
+    // After the workspace has been moved; the files must already be in their new place
+    pub fn relocate(&mut self, storage_dir: PathBuf, projects_root: PathBuf) {
+        self.storage_dir = Some(storage_dir);
+        self.projects_root = Some(projects_root);
+    }
+
+    // The app's shared processors, instead of ones only batch runs use
+    pub fn with_processors(mut self, processors: Arc<Processors>) -> Self {
+        self.processors = processors;
+        self
+    }
+
+    // Lets jobs with a project_id add their finished videos to that project
+    pub fn with_project_manager(mut self, project_manager: Arc<Mutex<ProjectManager>>) -> Self {
+        self.project_manager = Some(project_manager);
+        self
+    }
+
+    fn processed_index_path(&self, config: &BatchConfig) -> Option<PathBuf> {
+        match (&config.project_id, &self.projects_root) {
+            (Some(project_id), Some(projects_root)) => Some(projects_root.join(project_id).join(PROCESSED_INDEX_FILE)),
+            // A subfolder, load_jobs treats every JSON file in storage_dir as a job
+            _ => self.storage_dir.as_ref().map(|dir| dir.join("unassigned").join(PROCESSED_INDEX_FILE)),
+        }
+    }
+
+    fn save_job(&self, job_id: &str) -> Result<(), String> {
+        let (Some(storage_dir), Some(job)) = (&self.storage_dir, self.jobs.get(job_id)) else {
+            return Ok(());
+        };
+
+        let json_data = serde_json::to_string_pretty(job)
+            .map_err(|e| format!("Failed to serialize batch job: {}", e))?;
+
+        std::fs::write(storage_dir.join(format!("{}.json", job_id)), json_data)
+            .map_err(|e| format!("Failed to save batch job: {}", e))
+    }
+
+    pub fn load_jobs(&mut self) -> Result<(), String> {
+        let Some(storage_dir) = self.storage_dir.clone() else {
+            return Ok(());
+        };
+
+        for entry in std::fs::read_dir(&storage_dir)
+            .map_err(|e| format!("Failed to read batch job directory: {}", e))? {
+
+            let path = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?.path();
+            // Job files are named by job ID, anything else in here is processor settings
+            let is_job_file = path.extension().and_then(|ext| ext.to_str()) == Some("json")
+                && path.file_stem().and_then(|stem| stem.to_str()).is_some_and(|stem| Uuid::parse_str(stem).is_ok());
+            if !is_job_file {
+                continue;
+            }
+
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read batch job file: {}", e))?;
+
+            let mut job: BatchJob = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse batch job file: {}", e))?;
+
+            // Nothing is running right after startup, so whatever was in flight gets resumed later
+            if job.status == BatchStatus::Running {
+                job.status = BatchStatus::Paused;
+                job.interrupted = true;
+                job.progress.current_video = None;
+                job.progress.eta_minutes = None;
+                job.progress.active_videos.clear();
+                self.recovered_jobs.insert(job.id.clone());
+            }
+
+            let job_id = job.id.clone();
+            self.scheduler.set_priority(&job_id, job.priority);
+            self.jobs.insert(job_id.clone(), job);
+            self.save_job(&job_id)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn create_batch_job(&mut self, name: String, urls: Vec<String>, config: BatchConfig, priority: JobPriority) -> String {
         let job_id = Uuid::new_v4().to_string();
         
         let job = BatchJob {
@@ -110,100 +551,572 @@ impl BatchProcessor {
                 processed_videos: 0,
                 failed_videos: 0,
                 current_video: None,
+                active_videos: Vec::new(),
                 percentage: 0.0,
                 eta_minutes: None,
                 start_time: None,
             },
             results: Vec::new(),
+            interrupted: false,
+            priority,
+            retried_results: Vec::new(),
+            warnings: Vec::new(),
+            depends_on: Vec::new(),
+            pipeline_id: None,
         };
 
+        self.scheduler.set_priority(&job_id, priority);
         self.jobs.insert(job_id.clone(), job);
+        // Persisting is best effort here, the job still exists in memory for this session
+        let _ = self.save_job(&job_id);
         job_id
     }
 
-    pub async fn start_batch_job(&mut self, job_id: &str) -> Result<(), String> {
-        let job = self.jobs.get_mut(job_id)
+    // Returns as soon as the job is running; progress is reported through on_update and
+    // get_batch_job, so the processor lock is only held briefly per finished video
+    pub async fn start_batch_job<F>(processor: Arc<Mutex<BatchProcessor>>, job_id: &str, on_update: F) -> Result<(), String>
+    where
+        F: Fn(&BatchJob) + Send + Sync + 'static,
+    {
+        Self::run_job(processor, job_id.to_string(), Arc::new(on_update)).await
+    }
+
+    // Boxed because a finished run starts its dependents through this again
+    fn run_job(processor: Arc<Mutex<BatchProcessor>>, job_id: String, on_update: UpdateCallback) -> BoxFuture<'static, Result<(), String>> {
+        Box::pin(async move {
+            let job_id = job_id.as_str();
+            let data_key = Self::load_data_key(&processor).await;
+            let analysis = Self::load_analysis_setup(&processor, job_id, data_key.clone()).await?.map(Arc::new);
+
+            let (run, scheduler, project_manager) = {
+                let mut processor = processor.lock().await;
+                let run = processor.begin_run(job_id, analysis, data_key)?;
+                if let Some(job) = processor.jobs.get(job_id) {
+                    on_update(job);
+                }
+                (run, processor.scheduler.clone(), processor.project_manager.clone())
+            };
+            let JobRun { urls, context } = run;
+            let cancel_token = context.cancel_token.clone();
+            let project_target = project_manager.zip(context.config.project_id.clone());
+
+            let job_id = job_id.to_string();
+            tokio::spawn(async move {
+                // Checked once the job is running, looking up a large batch's durations takes a while
+                let disk_space_policy = context.config.disk_space_policy;
+                if disk_space_policy != DiskSpacePolicy::Skip {
+                    let remaining: Vec<String> = urls.iter().map(|(_, url)| url.clone()).collect();
+                    let estimate = Self::disk_space_estimate(&remaining, &context.config, Some(&cancel_token)).await;
+                    if !estimate.sufficient {
+                        let mut processor = processor.lock().await;
+                        if disk_space_policy == DiskSpacePolicy::Refuse {
+                            processor.refuse_run(&job_id, &cancel_token, format!("Not enough disk space. {}", estimate.summary()));
+                        } else if let Some(job) = processor.jobs.get_mut(&job_id) {
+                            job.warnings.push(format!("Disk space may run out. {}", estimate.summary()));
+                            let _ = processor.save_job(&job_id);
+                        }
+                        if let Some(job) = processor.jobs.get(&job_id) {
+                            on_update(job);
+                        }
+                        if disk_space_policy == DiskSpacePolicy::Refuse {
+                            return;
+                        }
+                    }
+                }
+
+                // Process videos concurrently
+                let concurrent_jobs = context.config.concurrent_jobs.min(urls.len()).max(1);
+                // Unbounded so stage updates never hold up a video; the semaphore bounds the work
+                let (tx, mut rx) = mpsc::unbounded_channel::<RunEvent>();
+
+                let worker_token = cancel_token.clone();
+                tokio::spawn(async move {
+                    let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrent_jobs));
+                    let mut tasks = Vec::new();
+
+                    for (index, url) in urls {
+                        let permit = tokio::select! {
+                            _ = worker_token.cancelled() => break,
+                            permit = semaphore.clone().acquire_owned() => permit.unwrap(),
+                        };
+                        let tx = tx.clone();
+                        let scheduler = scheduler.clone();
+                        let context = context.clone();
+                    
+                        let task = tokio::spawn(async move {
+                            let _permit = permit; // Keep permit alive
+                            // The per-job permit caps this job, the scheduler slot is shared by all jobs
+                            let _slot = tokio::select! {
+                                _ = context.cancel_token.cancelled() => return,
+                                slot = scheduler.acquire(&context.job_id) => slot,
+                            };
+                            let reporter = StageReporter { url: url.clone(), index, events: tx.clone() };
+                            // Cancelled videos send nothing, so they stay in remaining_urls for a resume
+                            if let Some(result) = Self::process_single_video(&context, &url, index, &reporter).await {
+                                let _ = tx.send(RunEvent::Finished(Box::new(result)));
+                            }
+                        });
+                    
+                        tasks.push(task);
+                    }
+
+                    drop(tx); // Close the channel when all tasks are spawned
+                
+                    for task in tasks {
+                        let _ = task.await;
+                    }
+                });
+
+                // Collect results
+                while let Some(event) = rx.recv().await {
+                    // Done before taking the processor lock, so the two locks are never held together
+                    let project_error = match (&event, &project_target) {
+                        (RunEvent::Finished(result), Some((project_manager, project_id))) if result.status == ProcessingStatus::Success => {
+                            project_manager.lock().await.add_batch_result(project_id, &job_id, result).err()
+                                .map(|e| format!("Failed to add {} to project: {}", result.url, e))
+                        }
+                        _ => None,
+                    };
+
+                    let mut processor = processor.lock().await;
+                    if let Some(warning) = project_error {
+                        if let Some(job) = processor.jobs.get_mut(&job_id) {
+                            job.warnings.push(warning);
+                        }
+                    }
+                    match event {
+                        // A killed yt-dlp can still print a line after a pause, which must not
+                        // show up in a resumed run's progress
+                        RunEvent::Stage(_) if cancel_token.is_cancelled() => continue,
+                        RunEvent::Stage(progress) => processor.record_stage(&job_id, progress),
+                        RunEvent::Finished(result) => processor.record_result(&job_id, *result),
+                    }
+                    if let Some(job) = processor.jobs.get(&job_id) {
+                        on_update(job);
+                    }
+                }
+
+                let (ready, manifest) = {
+                    let mut processor = processor.lock().await;
+                    let finished = processor.finish_run(&job_id, &cancel_token);
+                    let mut manifest = None;
+                    if let Some(job) = processor.jobs.get(&job_id) {
+                        on_update(job);
+                        if let Some(hook) = processor.on_job_finished.as_ref().filter(|_| finished) {
+                            hook(job);
+                        }
+                        if finished {
+                            let files: Vec<PathBuf> = job.results.iter()
+                                .flat_map(|result| result.output_files.iter().map(PathBuf::from))
+                                .collect();
+                            let path = export_manifest::batch_manifest_path(Path::new(&job.config.output_directory), &job.id);
+                            manifest = Some((path, files));
+                        }
+                    }
+                    (processor.ready_dependents(&job_id), manifest)
+                };
+
+                // Hashing every clip takes a while, so it happens without the lock
+                if let Some((path, files)) = manifest {
+                    let written = tokio::task::spawn_blocking(move || export_manifest::write_manifest(&path, &files))
+                        .await
+                        .map_err(|e| e.to_string())
+                        .and_then(|written| written);
+                    if let Err(e) = written {
+                        tracing::warn!("Failed to write manifest for batch job {}: {}", job_id, e);
+                    }
+                }
+
+                for dependent_id in ready {
+                    if let Err(e) = Self::run_job(processor.clone(), dependent_id.clone(), on_update.clone()).await {
+                        tracing::warn!("Failed to start dependent batch job {}: {}", dependent_id, e);
+                    }
+                }
+            });
+
+            Ok(())
+        })
+    }
+
+    // A run stopped before any video started goes back to pending, with the reason in its warnings
+    fn refuse_run(&mut self, job_id: &str, cancel_token: &CancellationToken, reason: String) {
+        if cancel_token.is_cancelled() {
+            return;
+        }
+        self.cancel_tokens.remove(job_id);
+        if let Some(job) = self.jobs.get_mut(job_id).filter(|job| job.status == BatchStatus::Running) {
+            job.status = BatchStatus::Pending;
+            job.progress.start_time = None;
+            job.warnings.push(reason);
+        }
+        let _ = self.save_job(job_id);
+    }
+
+    // Pending jobs waiting on job_id whose prerequisites have now all completed
+    fn ready_dependents(&self, job_id: &str) -> Vec<String> {
+        if self.jobs.get(job_id).map(|job| &job.status) != Some(&BatchStatus::Completed) {
+            return Vec::new();
+        }
+
+        self.jobs.values()
+            .filter(|job| job.status == BatchStatus::Pending && job.depends_on.iter().any(|id| id == job_id))
+            .filter(|job| self.unmet_dependency(job).is_none())
+            .map(|job| job.id.clone())
+            .collect()
+    }
+
+    // A deleted prerequisite can never complete, so it no longer blocks
+    fn unmet_dependency<'a>(&self, job: &'a BatchJob) -> Option<&'a str> {
+        job.depends_on.iter()
+            .find(|id| self.jobs.get(*id).is_some_and(|dependency| dependency.status != BatchStatus::Completed))
+            .map(|id| id.as_str())
+    }
+
+    pub fn add_job_dependency(&mut self, job_id: &str, depends_on: &str) -> Result<(), String> {
+        if job_id == depends_on {
+            return Err("A job can't depend on itself".to_string());
+        }
+
+        let dependency = self.jobs.get(depends_on)
+            .ok_or("Prerequisite batch job not found")?;
+        let job = self.jobs.get(job_id)
             .ok_or("Batch job not found")?;
 
         if job.status != BatchStatus::Pending {
+            return Err("Can only add prerequisites to pending jobs".to_string());
+        }
+        if self.depends_transitively(depends_on, job_id) {
+            return Err("Dependency would create a cycle".to_string());
+        }
+
+        // Joining the prerequisite's pipeline is what lets the two share artifacts
+        let pipeline_id = dependency.pipeline_id.clone()
+            .or_else(|| job.pipeline_id.clone())
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        for id in [depends_on, job_id] {
+            if let Some(job) = self.jobs.get_mut(id) {
+                job.pipeline_id.get_or_insert_with(|| pipeline_id.clone());
+            }
+        }
+        if let Some(job) = self.jobs.get_mut(job_id) {
+            if !job.depends_on.iter().any(|id| id == depends_on) {
+                job.depends_on.push(depends_on.to_string());
+            }
+        }
+
+        self.save_job(depends_on)?;
+        self.save_job(job_id)
+    }
+
+    fn depends_transitively(&self, job_id: &str, target: &str) -> bool {
+        let mut stack = vec![job_id.to_string()];
+        let mut seen = std::collections::HashSet::new();
+
+        while let Some(id) = stack.pop() {
+            if id == target {
+                return true;
+            }
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            if let Some(job) = self.jobs.get(&id) {
+                stack.extend(job.depends_on.iter().cloned());
+            }
+        }
+
+        false
+    }
+
+    // One job per stage over the same URLs, each waiting for the one before it
+    pub fn create_pipeline(&mut self, name: String, urls: Vec<String>, config: BatchConfig, stages: Vec<PipelineStage>, priority: JobPriority) -> Result<Vec<String>, String> {
+        if stages.is_empty() {
+            return Err("A pipeline needs at least one stage".to_string());
+        }
+
+        let pipeline_id = Uuid::new_v4().to_string();
+        let mut job_ids: Vec<String> = Vec::new();
+
+        for stage in stages {
+            let stage_config = BatchConfig {
+                stage: Some(stage),
+                ..config.clone()
+            };
+            let job_id = self.create_batch_job(format!("{} - {:?}", name, stage), urls.clone(), stage_config, priority);
+
+            if let Some(job) = self.jobs.get_mut(&job_id) {
+                job.pipeline_id = Some(pipeline_id.clone());
+                job.depends_on = job_ids.last().cloned().into_iter().collect();
+            }
+            self.save_job(&job_id)?;
+            job_ids.push(job_id);
+        }
+
+        Ok(job_ids)
+    }
+
+    fn artifact_store(&self, pipeline_id: &str) -> Option<ArtifactStore> {
+        self.storage_dir.as_ref()
+            .map(|dir| ArtifactStore::new(dir.join("artifacts").join(pipeline_id)))
+    }
+
+    // Looks up the remaining videos' durations with yt-dlp, which can take a few seconds per
+    // video, so the processor is only locked to read the job
+    pub async fn estimate_disk_space(processor: Arc<Mutex<BatchProcessor>>, job_id: &str) -> Result<DiskSpaceEstimate, String> {
+        let (urls, config) = {
+            let processor = processor.lock().await;
+            let job = processor.jobs.get(job_id)
+                .ok_or("Batch job not found")?;
+            (job.remaining_urls(), job.config.clone())
+        };
+
+        Ok(Self::disk_space_estimate(&urls, &config, None).await)
+    }
+
+    async fn disk_space_estimate(urls: &[String], config: &BatchConfig, cancel_token: Option<&CancellationToken>) -> DiskSpaceEstimate {
+        let durations = Self::fetch_durations(urls, cancel_token).await;
+        let (output_bytes, temp_bytes) = disk_space::estimate_required_bytes(&EstimateInput {
+            durations: &durations,
+            quality: config.download_quality(),
+            export_formats: config.export_formats.len(),
+            social_formats: config.enable_social_formats,
+            transcribe: config.enable_ai_analysis,
+            concurrent_videos: config.concurrent_jobs.clamp(1, GLOBAL_VIDEO_SLOTS),
+        });
+        let unknown = durations.iter().filter(|d| d.is_none()).count();
+
+        disk_space::check_disk_space(Path::new(&config.output_directory), output_bytes, temp_bytes, unknown)
+    }
+
+    // One yt-dlp call for all URLs; videos it can't resolve come back as None
+    async fn fetch_durations(urls: &[String], cancel_token: Option<&CancellationToken>) -> Vec<Option<f64>> {
+        if urls.is_empty() {
+            return Vec::new();
+        }
+
+        let output = run_cancellable(tokio::process::Command::new(app_settings::yt_dlp())
+            .args([
+                "--skip-download",
+                "--ignore-errors",
+                "--print", "%(original_url)s\t%(duration)s",
+            ])
+            .args(urls), cancel_token).await;
+
+        let Ok(output) = output else {
+            return vec![None; urls.len()];
+        };
+
+        let durations: HashMap<String, f64> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (url, duration) = line.rsplit_once('\t')?;
+                Some((url.trim().to_string(), duration.trim().parse().ok()?))
+            })
+            .collect();
+
+        urls.iter().map(|url| durations.get(url).copied()).collect()
+    }
+
+    // None when the job doesn't analyze anything. The project, if any, supplies the prompt
+    // template and output language, same as for analyze_content
+    async fn load_analysis_setup(processor: &Arc<Mutex<BatchProcessor>>, job_id: &str, data_key: Option<DataKey>) -> Result<Option<AnalysisSetup>, String> {
+        let (config, ai_config_store, project_manager, cache_dir, youtube, circuit_breakers) = {
+            let processor = processor.lock().await;
+            let job = processor.jobs.get(job_id)
+                .ok_or("Batch job not found")?;
+            (
+                job.config.clone(),
+                processor.ai_config_store.clone(),
+                processor.project_manager.clone(),
+                // Nothing is cached while the workspace is locked, it would be written unencrypted
+                processor.projects_root.as_ref()
+                    .filter(|root| data_key.is_some() || !project_crypto::is_enabled(root))
+                    .map(|root| root.join("cache").join("analysis")),
+                processor.youtube_api(),
+                processor.circuit_breakers.clone(),
+            )
+        };
+
+        if !config.enable_ai_analysis && config.stage != Some(PipelineStage::Analyze) {
+            return Ok(None);
+        }
+
+        let mut ai_config = match ai_config_store {
+            Some(store) => store.lock().await.load()?,
+            None => AIConfig::default(),
+        };
+        let mut prompt_template = PromptTemplate::default_analysis();
+
+        if let (Some(project_id), Some(project_manager)) = (&config.project_id, project_manager) {
+            let manager = project_manager.lock().await;
+            prompt_template = manager.active_prompt_template(project_id)?;
+            if let Some(language) = manager.project_settings(project_id).and_then(|settings| settings.output_language.clone()) {
+                ai_config.output_language = Some(language);
+            }
+        }
+
+        Ok(Some(AnalysisSetup { config: ai_config, prompt_template, cache_dir, data_key, youtube, circuit_breakers }))
+    }
+
+    // The workspace key from the project manager, for the artifacts and analyses a run stores
+    async fn load_data_key(processor: &Arc<Mutex<BatchProcessor>>) -> Option<DataKey> {
+        let project_manager = processor.lock().await.project_manager.clone()?;
+        let data_key = project_manager.lock().await.data_key();
+        data_key
+    }
+
+    fn begin_run(&mut self, job_id: &str, analysis: Option<Arc<AnalysisSetup>>, data_key: Option<DataKey>) -> Result<JobRun, String> {
+        let job = self.jobs.get(job_id)
+            .ok_or("Batch job not found")?;
+        if let Some(dependency) = self.unmet_dependency(job) {
+            return Err(format!("Waiting for prerequisite batch job {}", dependency));
+        }
+        let artifacts = match job.pipeline_id.as_deref().and_then(|pipeline_id| self.artifact_store(pipeline_id)) {
+            // A locked workspace would get its transcripts written unencrypted
+            Some(_) if data_key.is_none() && self.projects_root.as_deref().is_some_and(project_crypto::is_enabled) => {
+                return Err(project_crypto::WORKSPACE_LOCKED.to_string());
+            }
+            artifacts => artifacts.map(|artifacts| Arc::new(artifacts.with_key(data_key))),
+        };
+
+        let processed_index = match self.jobs.get(job_id) {
+            Some(job) if job.config.skip_duplicates => self.processed_index_path(&job.config)
+                .map(ProcessedIndex::load)
+                .transpose()?,
+            _ => None,
+        };
+
+        let job = self.jobs.get_mut(job_id)
+            .ok_or("Batch job not found")?;
+
+        let resuming = job.status == BatchStatus::Paused && job.interrupted;
+        if job.status != BatchStatus::Pending && !resuming {
             return Err("Job is not in pending state".to_string());
         }
 
         job.status = BatchStatus::Running;
-        job.started_at = Some(chrono::Utc::now().to_rfc3339());
+        job.interrupted = false;
+        self.recovered_jobs.remove(job_id);
+        if !resuming {
+            job.started_at = Some(chrono::Utc::now().to_rfc3339());
+        }
         job.progress.start_time = Some(chrono::Utc::now().timestamp());
+        let (skipped, urls): (Vec<_>, Vec<_>) = job.remaining_urls().into_iter()
+            .map(|url| (job.urls.iter().position(|u| *u == url).unwrap_or(0) + 1, url))
+            .partition(|(_, url)| processed_index.as_ref().is_some_and(|index| index.contains_url(url)));
+        let config = job.config.clone();
 
-        // Create a copy of the job for processing
-        let mut job_copy = job.clone();
-        
-        // Process videos concurrently
-        let concurrent_jobs = job_copy.config.concurrent_jobs.min(job_copy.urls.len());
-        let (tx, mut rx) = mpsc::channel::<BatchResult>(concurrent_jobs);
+        // A fresh token per run, a cancelled token can't be reused after a pause
+        let cancel_token = CancellationToken::new();
+        self.cancel_tokens.insert(job_id.to_string(), cancel_token.clone());
 
-        // Spawn processing tasks
-        let urls = job_copy.urls.clone();
-        let config = job_copy.config.clone();
-        
-        tokio::spawn(async move {
-            let semaphore = tokio::sync::Semaphore::new(concurrent_jobs);
-            let mut tasks = Vec::new();
-
-            for url in urls {
-                let permit = semaphore.acquire().await.unwrap();
-                let tx = tx.clone();
-                let config = config.clone();
-                
-                let task = tokio::spawn(async move {
-                    let _permit = permit; // Keep permit alive
-                    let result = Self::process_single_video(&url, &config).await;
-                    let _ = tx.send(result).await;
-                });
-                
-                tasks.push(task);
+        for (_, url) in skipped {
+            self.record_result(job_id, Self::skipped_result(url, "Already processed in an earlier batch"));
+        }
+
+        self.save_job(job_id)?;
+        Ok(JobRun {
+            urls,
+            context: RunContext {
+                job_id: job_id.to_string(),
+                cancel_token,
+                processors: self.processors.clone(),
+                processed_index: processed_index.map(|index| Arc::new(std::sync::Mutex::new(index))),
+                resource_governor: self.resource_governor.clone(),
+                artifacts,
+                download_throttle: Arc::new(DownloadThrottle::new(config.download_pacing.clone())),
+                analysis,
+                config,
+            },
+        })
+    }
+
+    fn skipped_result(url: String, reason: &str) -> BatchResult {
+        BatchResult {
+            url,
+            video_info: None,
+            nuggets: Vec::new(),
+            analysis: None,
+            output_files: Vec::new(),
+            events: Vec::new(),
+            status: ProcessingStatus::Skipped,
+            error_message: Some(reason.to_string()),
+            processing_time_seconds: 0.0,
+        }
+    }
+
+    // Stage changes only go out through on_update, writing the job file for each one isn't worth it
+    fn record_stage(&mut self, job_id: &str, progress: VideoProgress) {
+        if let Some(job) = self.jobs.get_mut(job_id) {
+            job.progress.current_video = Some(progress.url.clone());
+            let active = &mut job.progress.active_videos;
+            match active.iter_mut().find(|video| video.index == progress.index) {
+                Some(video) => *video = progress,
+                None => {
+                    active.push(progress);
+                    active.sort_by_key(|video| video.index);
+                }
             }
+        }
+    }
 
-            drop(tx); // Close the channel when all tasks are spawned
-            
-            for task in tasks {
-                let _ = task.await;
+    fn record_result(&mut self, job_id: &str, result: BatchResult) {
+        if let Some(job) = self.jobs.get_mut(job_id) {
+            if result.status == ProcessingStatus::Failed {
+                job.progress.failed_videos += 1;
             }
-        });
+            job.progress.active_videos.retain(|video| video.url != result.url);
 
-        // Collect results
-        while let Some(result) = rx.recv().await {
-            if let Some(job) = self.jobs.get_mut(job_id) {
-                job.results.push(result.clone());
-                job.progress.processed_videos = job.results.len();
-                job.progress.percentage = (job.progress.processed_videos as f64 / job.progress.total_videos as f64) * 100.0;
-                
-                if result.status == ProcessingStatus::Failed {
-                    job.progress.failed_videos += 1;
-                }
+            job.results.push(result);
+            job.progress.processed_videos = job.results.len();
+            job.progress.percentage = (job.progress.processed_videos as f64 / job.progress.total_videos as f64) * 100.0;
 
-                // Calculate ETA
-                if let Some(start_time) = job.progress.start_time {
-                    let elapsed_minutes = (chrono::Utc::now().timestamp() - start_time) as f64 / 60.0;
-                    if job.progress.processed_videos > 0 {
-                        let avg_time_per_video = elapsed_minutes / job.progress.processed_videos as f64;
-                        let remaining_videos = job.progress.total_videos - job.progress.processed_videos;
-                        job.progress.eta_minutes = Some(avg_time_per_video * remaining_videos as f64);
-                    }
+            // Calculate ETA
+            if let Some(start_time) = job.progress.start_time {
+                let elapsed_minutes = (chrono::Utc::now().timestamp() - start_time) as f64 / 60.0;
+                if job.progress.processed_videos > 0 {
+                    let avg_time_per_video = elapsed_minutes / job.progress.processed_videos as f64;
+                    let remaining_videos = job.progress.total_videos - job.progress.processed_videos;
+                    job.progress.eta_minutes = Some(avg_time_per_video * remaining_videos as f64);
                 }
             }
         }
+        // The result is already in memory, a failed write only loses it on a crash
+        let _ = self.save_job(job_id);
+    }
 
-        // Mark job as completed
-        if let Some(job) = self.jobs.get_mut(job_id) {
-            job.status = BatchStatus::Completed;
+    // True when the run ended the job, as opposed to a cancel or pause having stopped it
+    fn finish_run(&mut self, job_id: &str, cancel_token: &CancellationToken) -> bool {
+        // Cancel and pause already dropped this run's token, and a resumed run may own the
+        // current one, so a cancelled run must leave both the token and the status alone
+        if cancel_token.is_cancelled() {
+            return false;
+        }
+        self.cancel_tokens.remove(job_id);
+
+        let mut finished = false;
+        if let Some(job) = self.jobs.get_mut(job_id).filter(|job| job.status == BatchStatus::Running) {
+            // A job where every video failed doesn't count as done, its dependents stay pending
+            job.status = if job.progress.total_videos > 0 && job.progress.failed_videos == job.progress.total_videos {
+                BatchStatus::Failed
+            } else {
+                BatchStatus::Completed
+            };
+            finished = true;
             job.completed_at = Some(chrono::Utc::now().to_rfc3339());
             job.progress.percentage = 100.0;
             job.progress.eta_minutes = Some(0.0);
+            job.progress.current_video = None;
+            job.progress.active_videos.clear();
         }
 
-        Ok(())
+        let _ = self.save_job(job_id);
+        finished
     }
 
-    async fn process_single_video(url: &str, config: &BatchConfig) -> BatchResult {
+    async fn process_single_video(context: &RunContext, url: &str, index: usize, reporter: &StageReporter) -> Option<BatchResult> {
+        let cancel_token = &context.cancel_token;
+        let config = &context.config.for_url(url);
         let start_time = std::time::Instant::now();
         
         let mut result = BatchResult {
@@ -212,6 +1125,7 @@ impl BatchProcessor {
             nuggets: Vec::new(),
             analysis: None,
             output_files: Vec::new(),
+            events: Vec::new(),
             status: ProcessingStatus::Success,
             error_message: None,
             processing_time_seconds: 0.0,
@@ -222,12 +1136,23 @@ impl BatchProcessor {
         let max_retries = if config.retry_failed { config.max_retries } else { 0 };
 
         while retries <= max_retries {
-            match Self::attempt_video_processing(url, config).await {
-                Ok((video_info, nuggets, analysis, output_files)) => {
-                    result.video_info = Some(video_info);
-                    result.nuggets = nuggets;
-                    result.analysis = analysis;
-                    result.output_files = output_files;
+            let attempt = tokio::select! {
+                _ = cancel_token.cancelled() => return None,
+                attempt = Self::attempt_video_processing(context, config, url, index, reporter) => attempt,
+            };
+
+            match attempt {
+                Ok(None) => {
+                    result.status = ProcessingStatus::Skipped;
+                    result.error_message = Some("Same video content was already processed".to_string());
+                    break;
+                }
+                Ok(Some(processed)) => {
+                    result.video_info = Some(processed.video_info);
+                    result.nuggets = processed.nuggets;
+                    result.analysis = processed.analysis;
+                    result.output_files = processed.output_files;
+                    result.events = processed.events;
                     result.status = ProcessingStatus::Success;
                     break;
                 }
@@ -235,8 +1160,12 @@ impl BatchProcessor {
                     if retries < max_retries {
                         retries += 1;
                         result.status = ProcessingStatus::Retrying;
+                        reporter.report(VideoStage::Retrying { attempt: retries });
                         // Wait before retry (exponential backoff)
-                        tokio::time::sleep(tokio::time::Duration::from_secs(2u64.pow(retries))).await;
+                        tokio::select! {
+                            _ = cancel_token.cancelled() => return None,
+                            _ = tokio::time::sleep(tokio::time::Duration::from_secs(2u64.pow(retries))) => {}
+                        }
                     } else {
                         result.status = ProcessingStatus::Failed;
                         result.error_message = Some(error);
@@ -247,36 +1176,118 @@ impl BatchProcessor {
         }
 
         result.processing_time_seconds = start_time.elapsed().as_secs_f64();
-        result
+        Some(result)
     }
 
-    async fn attempt_video_processing(url: &str, config: &BatchConfig) -> Result<(VideoInfo, Vec<VideoNugget>, Option<ContentAnalysis>, Vec<String>), String> {
-        let video_processor = VideoProcessor::new();
-        let ffmpeg_processor = FFmpegProcessor::new()?;
+    // Ok(None) when the downloaded file matches one already in the processed-video index
+    async fn attempt_video_processing(context: &RunContext, config: &BatchConfig, url: &str, index: usize, reporter: &StageReporter) -> Result<Option<ProcessedVideo>, String> {
+        let RunContext { job_id, cancel_token, processors, processed_index, resource_governor, artifacts, download_throttle, analysis: analysis_setup, .. } = context;
+        let artifacts = artifacts.as_deref();
+        let video_processor = VideoProcessor::with_extractor(processors.youtube().clone());
+        let ffmpeg_processor = processors.ffmpeg()?.scratch()?.with_cancellation(cancel_token.clone());
+
+        let stage = config.stage;
+        let transcribe = match stage {
+            None => config.enable_ai_analysis,
+            Some(PipelineStage::Transcribe) | Some(PipelineStage::Analyze) => true,
+            Some(PipelineStage::RenderClips) => false,
+        };
+        let render_clips = match stage {
+            None => config.enable_social_formats,
+            Some(stage) => stage == PipelineStage::RenderClips,
+        };
+        let make_nuggets = stage != Some(PipelineStage::Transcribe);
+        let export = matches!(stage, None | Some(PipelineStage::Analyze));
         
-        // Download and get video info
-        let video_path = ffmpeg_processor.download_video(url, "best").await?;
-        let video_info = ffmpeg_processor.get_video_info(&video_path)?;
+        // Download and get video info; a pipeline downloads each video once for all stages
+        let download = || async {
+            reporter.report(VideoStage::WaitingForResources);
+            let _download_slot = download_throttle.acquire(url, cancel_token).await
+                .ok_or("Cancelled")?;
+            reporter.report(VideoStage::Downloading { percent: None });
+            ffmpeg_processor.download_video_with_progress(url, config.download_quality(), Some(reporter.download_progress())).await
+        };
+        let video_path = match artifacts {
+            Some(artifacts) => match artifacts.video_path(url) {
+                Some(path) => path.to_string_lossy().to_string(),
+                None => {
+                    let downloaded = download().await?;
+                    artifacts.store_video(url, Path::new(&downloaded))?.to_string_lossy().to_string()
+                }
+            },
+            None => download().await?,
+        };
+        let content_hash = match processed_index.as_ref() {
+            Some(processed_index) => {
+                let content_hash = processed_index::hash_file(Path::new(&video_path))?;
+                if processed_index.lock().unwrap().contains_hash(&content_hash) {
+                    return Ok(None);
+                }
+                Some(content_hash)
+            }
+            None => None,
+        };
+        // Cancellation while waiting for a slot surfaces as an error, the caller drops it anyway
+        reporter.report(VideoStage::WaitingForResources);
+        let ffmpeg_slot = resource_governor.acquire(ResourceKind::Ffmpeg, cancel_token).await
+            .ok_or("Cancelled")?;
+        let mut video_info = ffmpeg_processor.get_video_info(&video_path).await?;
+        let source = ffmpeg_processor.get_source_metadata(url).await;
+        if let Some(source) = &source {
+            video_info.title = source.title.clone();
+        }
         
-        // Process video to create nuggets
-        let processing_result = video_processor.process_video(url, config.video_config.clone()).await?;
+        let mut events = Vec::new();
+
+        // Process video to create nuggets, or pick up the ones an earlier stage made
+        let nuggets: Vec<VideoNugget> = match artifacts.and_then(|a| a.load_nuggets(url)) {
+            Some(nuggets) if make_nuggets => nuggets,
+            _ if make_nuggets => {
+                reporter.report(VideoStage::FindingNuggets);
+                let processed = video_processor.process_video(url, config.video_config.clone()).await?;
+                if let Some(artifacts) = artifacts {
+                    artifacts.store_nuggets(url, &processed.nuggets)?;
+                }
+                events.extend(processed.events);
+                processed.nuggets
+            }
+            _ => Vec::new(),
+        };
         
         let mut output_files = Vec::new();
         let mut analysis = None;
 
+        let template_context = TemplateContext {
+            video_title: video_info.title.clone(),
+            channel: source.and_then(|s| s.channel).unwrap_or_else(|| "unknown_channel".to_string()),
+            date: chrono::Utc::now().format("%Y-%m-%d").to_string(),
+            index,
+            nugget_title: COMBINED_EXPORT_NAME.to_string(),
+        };
+
         // Generate video clips if requested
-        if config.enable_social_formats {
-            let clips = ffmpeg_processor.create_video_clips(&video_path, &processing_result.nuggets, &config.output_directory)?;
+        if render_clips {
+            let nugget_titles: Vec<String> = nuggets.iter()
+                .map(|n| n.title.clone())
+                .collect();
+            let clip_stems: Vec<String> = output_template::render_clip_paths(&config.output_template, &template_context, &nugget_titles)?
+                .into_iter()
+                .map(|path| format!("{}/{}", config.output_directory, path))
+                .collect();
             
-            for clip in clips {
-                output_files.push(clip.output_path);
-                if let Some(thumb) = clip.thumbnail_path {
-                    output_files.push(thumb);
-                }
-                
-                // Create social media formats
-                if config.enable_social_formats {
-                    let social_formats = ffmpeg_processor.create_social_media_formats(&clip.output_path)?;
+            // One clip at a time, so progress can name the clip being rendered
+            for (position, (nugget, stem)) in nuggets.iter().zip(&clip_stems).enumerate() {
+                reporter.report(VideoStage::RenderingClip { current: position + 1, total: nuggets.len() });
+                let clips = ffmpeg_processor.create_video_clips_at(&video_path, std::slice::from_ref(nugget), std::slice::from_ref(stem)).await?;
+
+                for clip in clips {
+                    output_files.push(clip.output_path.clone());
+                    if let Some(thumb) = clip.thumbnail_path {
+                        output_files.push(thumb);
+                    }
+                    
+                    // Create social media formats
+                    let social_formats = ffmpeg_processor.create_social_media_formats(&clip.output_path).await?;
                     output_files.push(social_formats.tiktok);
                     output_files.push(social_formats.instagram);
                     output_files.push(social_formats.youtube_short);
@@ -284,54 +1295,158 @@ impl BatchProcessor {
             }
         }
 
-        // AI Analysis if enabled
-        if config.enable_ai_analysis {
-            // Extract transcript for analysis
-            let audio_path = ffmpeg_processor.extract_audio(&video_path)?;
-            let speech_recognizer = SpeechRecognizer::new()?;
-            let transcript_analysis = speech_recognizer.transcribe_audio(&audio_path).await?;
-            let full_transcript = transcript_analysis.segments
-                .iter()
-                .map(|s| s.text.as_str())
-                .collect::<Vec<_>>()
-                .join(" ");
+        let cached_transcript: Option<SpeechAnalysis> = if transcribe {
+            artifacts.and_then(|a| a.load_transcript(url))
+        } else {
+            None
+        };
+
+        // The audio is extracted under the FFmpeg slot, transcription has a limit of its own
+        let audio_path = if transcribe && cached_transcript.is_none() {
+            Some(ffmpeg_processor.extract_audio(&video_path).await?)
+        } else {
+            None
+        };
+        drop(ffmpeg_slot);
 
-            // Create AI analyzer (would need configuration)
-            // analysis = Some(ai_analyzer.analyze_content(&full_transcript, &video_info.title, None).await?);
+        let transcript_analysis = match (cached_transcript, audio_path) {
+            (Some(transcript), _) => Some(transcript),
+            (None, Some(audio_path)) => {
+                reporter.report(VideoStage::WaitingForResources);
+                let _transcription_slot = resource_governor.acquire(ResourceKind::Transcription, cancel_token).await
+                    .ok_or("Cancelled")?;
+                reporter.report(VideoStage::Transcribing);
+                let speech_recognizer = processors.speech()?.scratch()?.with_cancellation(cancel_token.clone());
+                let transcript = speech_recognizer.transcribe_audio(&audio_path).await?;
+                if let Some(artifacts) = artifacts {
+                    let transcript_path = artifacts.store_transcript(url, &transcript)?;
+                    if stage == Some(PipelineStage::Transcribe) {
+                        output_files.push(transcript_path.to_string_lossy().to_string());
+                    }
+                }
+                Some(transcript)
+            }
+            (None, None) => None,
+        };
+
+        // AI Analysis if enabled
+        let analyze = match stage {
+            None => config.enable_ai_analysis,
+            Some(stage) => stage == PipelineStage::Analyze,
+        };
+        if let (Some(transcript_analysis), Some(setup), true) = (transcript_analysis, analysis_setup, analyze) {
+            reporter.report(VideoStage::Analyzing);
+            let mut ai_analyzer = AIAnalyzer::new(setup.config.clone())
+                .with_prompt_template(setup.prompt_template.clone())
+                .with_video_duration(video_info.duration)
+                .with_circuit_breakers(setup.circuit_breakers.clone());
+            if let Some(cache_dir) = &setup.cache_dir {
+                ai_analyzer = ai_analyzer.with_cache(AnalysisCache::new(cache_dir.clone()).with_key(setup.data_key.clone()));
+            }
+            let mut result = ai_analyzer.analyze_segments(&transcript_analysis.segments, &video_info.title, None).await?;
+            if let (Some(youtube), Some(video_id)) = (&setup.youtube, processed_index::youtube_video_id(url)) {
+                let region = app_settings::current().defaults.youtube_region;
+                match youtube.get_video_category(&video_id, &region).await {
+                    Ok(Some(category)) => result.add_source_category(&category),
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to get YouTube category for {}: {}", url, e),
+                }
+            }
+            events.push(ai_analyzer.analysis_event(&result));
+            analysis = Some(result);
         }
 
         // Export in requested formats
-        for format in &config.export_formats {
-            let export_path = format!("{}/nuggets_{}.{}", config.output_directory, 
-                chrono::Utc::now().timestamp(), format);
+        let export_formats: &[String] = if export { &config.export_formats } else { &[] };
+        let export_stem = format!("{}/{}", config.output_directory,
+            output_template::render_output_path(&config.output_template, &template_context)?);
+        if let Some(parent) = std::path::Path::new(&export_stem).parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create output directory: {}", e))?;
+        }
+
+        if !export_formats.is_empty() {
+            reporter.report(VideoStage::Exporting);
+        }
+        let exported = match &config.export_filter {
+            Some(filter) => filter.apply_with_analysis(nuggets.clone(), analysis.as_ref()),
+            None => nuggets.clone(),
+        };
+        for format in export_formats {
+            let export_path = format!("{}.{}", export_stem, format);
+            let file_manager = FileManager::new().with_formatting(config.formatting.clone())?;
             
             match format.as_str() {
                 "json" => {
-                    let file_manager = crate::file_manager::FileManager::new();
-                    file_manager.save_nuggets(processing_result.nuggets.clone(), &export_path).await?;
-                    output_files.push(export_path);
+                    file_manager.save_nuggets(exported.clone(), &export_path).await?;
+                }
+                "jsonl" | "ndjson" => {
+                    file_manager.export_as_jsonl(exported.clone(), &export_path).await?;
                 }
                 "csv" => {
-                    let file_manager = crate::file_manager::FileManager::new();
-                    file_manager.export_as_csv(processing_result.nuggets.clone(), &export_path).await?;
-                    output_files.push(export_path);
+                    file_manager.export_as_csv_with_options(exported.clone(), &config.csv_options, &export_path).await?;
                 }
                 "markdown" => {
-                    let file_manager = crate::file_manager::FileManager::new();
-                    file_manager.export_as_markdown(processing_result.nuggets.clone(), &export_path).await?;
-                    output_files.push(export_path);
+                    file_manager.export_as_markdown(exported.clone(), &export_path).await?;
                 }
-                _ => {} // Ignore unknown formats
+                _ => continue, // Ignore unknown formats
+            }
+            events.push(FileManager::export_event(format, &export_path, exported.len()));
+            output_files.push(export_path);
+        }
+
+        if let Some(processed_index) = processed_index {
+            let mut processed_index = processed_index.lock().unwrap();
+            processed_index.record(url, content_hash, job_id);
+            // Only costs a reprocess next time, not worth failing a finished video over
+            if let Err(e) = processed_index.save() {
+                tracing::warn!("Failed to update processed video index: {}", e);
             }
         }
 
-        Ok((video_info, processing_result.nuggets, analysis, output_files))
+        Ok(Some(ProcessedVideo { video_info, nuggets, analysis, output_files, events }))
+    }
+
+    pub fn set_batch_job_priority(&mut self, job_id: &str, priority: JobPriority) -> Result<(), String> {
+        let job = self.jobs.get_mut(job_id)
+            .ok_or("Batch job not found")?;
+
+        job.priority = priority;
+        self.scheduler.set_priority(job_id, priority);
+        self.save_job(job_id)
+    }
+
+    // None removes the URL's override
+    pub fn set_url_override(&mut self, job_id: &str, url: &str, video_override: Option<VideoOverride>) -> Result<(), String> {
+        let job = self.jobs.get_mut(job_id)
+            .ok_or("Batch job not found")?;
+
+        if job.status == BatchStatus::Running {
+            return Err("Cannot change a running job. Pause it first.".to_string());
+        }
+        if !job.urls.iter().any(|u| u == url) {
+            return Err("URL is not part of this batch job".to_string());
+        }
+
+        match video_override {
+            Some(video_override) => job.config.url_overrides.insert(url.to_string(), video_override),
+            None => job.config.url_overrides.remove(url),
+        };
+        self.save_job(job_id)
     }
 
     pub fn get_batch_job(&self, job_id: &str) -> Option<&BatchJob> {
         self.jobs.get(job_id)
     }
 
+    // Jobs a crash cut short that haven't been resumed yet, unlike ones paused on purpose
+    pub fn recovered_jobs(&self) -> Vec<&BatchJob> {
+        self.recovered_jobs.iter()
+            .filter_map(|job_id| self.jobs.get(job_id))
+            .filter(|job| job.interrupted)
+            .collect()
+    }
+
     pub fn list_batch_jobs(&self) -> Vec<&BatchJob> {
         self.jobs.values().collect()
     }
@@ -342,7 +1457,12 @@ impl BatchProcessor {
 
         if job.status == BatchStatus::Running {
             job.status = BatchStatus::Cancelled;
-            Ok(())
+            job.progress.current_video = None;
+            job.progress.active_videos.clear();
+            if let Some(token) = self.cancel_tokens.remove(job_id) {
+                token.cancel();
+            }
+            self.save_job(job_id)
         } else {
             Err("Can only cancel running jobs".to_string())
         }
@@ -353,25 +1473,67 @@ impl BatchProcessor {
             .ok_or("Batch job not found")?;
 
         if job.status == BatchStatus::Running {
+            // In-flight videos are aborted and picked up again from scratch on resume
             job.status = BatchStatus::Paused;
-            Ok(())
+            job.interrupted = true;
+            job.progress.current_video = None;
+            job.progress.active_videos.clear();
+            if let Some(token) = self.cancel_tokens.remove(job_id) {
+                token.cancel();
+            }
+            self.save_job(job_id)
         } else {
             Err("Can only pause running jobs".to_string())
         }
     }
 
+    // Only marks the job resumable, the caller restarts it with start_batch_job
     pub fn resume_batch_job(&mut self, job_id: &str) -> Result<(), String> {
         let job = self.jobs.get_mut(job_id)
             .ok_or("Batch job not found")?;
 
         if job.status == BatchStatus::Paused {
-            job.status = BatchStatus::Running;
-            Ok(())
+            job.interrupted = true;
+            self.save_job(job_id)
         } else {
             Err("Can only resume paused jobs".to_string())
         }
     }
 
+    // Moves the failed results out of the way so their URLs count as remaining again, then
+    // marks the job resumable; the caller restarts it with start_batch_job. Returns the count.
+    pub fn prepare_failed_retry(&mut self, job_id: &str) -> Result<usize, String> {
+        let job = self.jobs.get_mut(job_id)
+            .ok_or("Batch job not found")?;
+
+        if !matches!(job.status, BatchStatus::Completed | BatchStatus::Failed | BatchStatus::Paused) {
+            return Err("Can only retry failed videos of completed, failed or paused jobs".to_string());
+        }
+
+        let (failed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut job.results)
+            .into_iter()
+            .partition(|result| result.status == ProcessingStatus::Failed);
+        job.results = kept;
+
+        if failed.is_empty() {
+            return Err("Job has no failed videos".to_string());
+        }
+
+        let retry_count = failed.len();
+        job.retried_results.extend(failed);
+        job.status = BatchStatus::Paused;
+        job.interrupted = true;
+        job.completed_at = None;
+        job.progress.failed_videos = job.progress.failed_videos.saturating_sub(retry_count);
+        job.progress.processed_videos = job.results.len();
+        job.progress.percentage = (job.progress.processed_videos as f64 / job.progress.total_videos.max(1) as f64) * 100.0;
+        job.progress.current_video = None;
+        job.progress.eta_minutes = None;
+
+        self.save_job(job_id)?;
+        Ok(retry_count)
+    }
+
     pub fn delete_batch_job(&mut self, job_id: &str) -> Result<(), String> {
         let job = self.jobs.get(job_id)
             .ok_or("Batch job not found")?;
@@ -380,25 +1542,186 @@ impl BatchProcessor {
             return Err("Cannot delete running job. Cancel it first.".to_string());
         }
 
+        let pipeline_id = job.pipeline_id.clone();
         self.jobs.remove(job_id);
+        self.scheduler.remove_job(job_id);
+
+        // The shared downloads and transcripts go with the pipeline's last job
+        if let Some(pipeline_id) = pipeline_id {
+            let still_used = self.jobs.values().any(|j| j.pipeline_id.as_deref() == Some(pipeline_id.as_str()));
+            if !still_used {
+                if let Some(artifacts) = self.artifact_store(&pipeline_id) {
+                    artifacts.remove_all()?;
+                }
+            }
+        }
+
+        if let Some(storage_dir) = &self.storage_dir {
+            let job_file = storage_dir.join(format!("{}.json", job_id));
+            if job_file.exists() {
+                std::fs::remove_file(job_file)
+                    .map_err(|e| format!("Failed to delete batch job file: {}", e))?;
+            }
+        }
         Ok(())
     }
 
-    pub async fn create_batch_from_playlist(&mut self, playlist_url: &str, name: String, config: BatchConfig) -> Result<String, String> {
-        // Extract video URLs from playlist
-        let urls = self.extract_playlist_urls(playlist_url).await?;
-        Ok(self.create_batch_job(name, urls, config))
+    // Like create_batch_from_channel, the processor is only locked to enqueue the job
+    pub async fn create_batch_from_playlist(processor: Arc<Mutex<BatchProcessor>>, playlist_url: &str, name: String, config: BatchConfig) -> Result<String, String> {
+        let youtube = processor.lock().await.youtube_api();
+        let urls = Self::extract_playlist_urls(youtube, playlist_url).await?;
+
+        if urls.is_empty() {
+            return Err("The playlist has no videos that can be processed".to_string());
+        }
+
+        let mut processor = processor.lock().await;
+        Ok(processor.create_batch_job(name, urls, config, JobPriority::default()))
+    }
+
+    // The top count videos trending in the region, in one category or all of them, most popular
+    // first. Without a name the batch is named after the category and region
+    pub async fn create_batch_from_trending(processor: Arc<Mutex<BatchProcessor>>, region_code: &str, category_id: Option<&str>, count: usize, name: Option<String>, config: BatchConfig) -> Result<String, String> {
+        let youtube = processor.lock().await.youtube_api()
+            .ok_or("Trending videos need a YouTube API key")?;
+        let videos = youtube.get_top_trending(region_code, category_id, count).await?;
+        if videos.is_empty() {
+            return Err("Nothing is trending there right now".to_string());
+        }
+
+        let name = match name.filter(|name| !name.trim().is_empty()) {
+            Some(name) => name,
+            None => {
+                let category = match category_id {
+                    Some(category_id) => youtube.get_video_categories(region_code).await
+                        .ok()
+                        .and_then(|categories| categories.get(category_id).cloned())
+                        .unwrap_or_else(|| format!("category {}", category_id)),
+                    None => "all categories".to_string(),
+                };
+                format!("Trending in {}, {} ({})", category, region_code.to_uppercase(), chrono::Utc::now().format("%Y-%m-%d"))
+            }
+        };
+        let urls = videos.iter()
+            .map(|video| format!("https://www.youtube.com/watch?v={}", video.video_id))
+            .collect();
+
+        let mut processor = processor.lock().await;
+        Ok(processor.create_batch_job(name, urls, config, JobPriority::default()))
+    }
+
+    // newest_n and since_date (YYYY-MM-DD) both apply when given, whichever cuts the list shorter wins.
+    // Listing a channel can take a while, so the processor is only locked to enqueue the job.
+    pub async fn create_batch_from_channel(processor: Arc<Mutex<BatchProcessor>>, channel_url: &str, newest_n: Option<usize>, since_date: Option<&str>, name: String, config: BatchConfig) -> Result<String, String> {
+        let uploads_url = Self::resolve_uploads_playlist(channel_url).await?;
+        let urls = Self::extract_channel_urls(&uploads_url, newest_n, since_date).await?;
+
+        if urls.is_empty() {
+            return Err("No channel videos match the given filters".to_string());
+        }
+
+        let mut processor = processor.lock().await;
+        Ok(processor.create_batch_job(name, urls, config, JobPriority::default()))
+    }
+
+    // The uploads playlist lists every public video newest first, unlike the channel's
+    // tabs, which split videos, shorts and streams
+    async fn resolve_uploads_playlist(channel_url: &str) -> Result<String, String> {
+        let channel_id = Self::resolve_channel_id(channel_url).await?;
+        let playlist_id = uploads_playlist_id(&channel_id)
+            .ok_or_else(|| format!("Not a YouTube channel ID: {}", channel_id))?;
+
+        Ok(format!("https://www.youtube.com/playlist?list={}", playlist_id))
+    }
+
+    // Handle and custom URLs only name the channel, yt-dlp looks up its ID
+    pub async fn resolve_channel_id(channel_url: &str) -> Result<String, String> {
+        let channel_id = match channel_url.split("/channel/").nth(1) {
+            Some(rest) => rest.split(['/', '?']).next().unwrap_or("").to_string(),
+            None => {
+                let output = run_cancellable(tokio::process::Command::new(app_settings::yt_dlp())
+                    .args([
+                        "--skip-download",
+                        "--playlist-items", "1",
+                        "--print", "channel_id",
+                        channel_url,
+                    ]), None).await
+                    .map_err(|e| format!("Failed to resolve channel: {}", e))?;
+
+                if !output.status.success() {
+                    return Err(format!("Failed to resolve channel: {}",
+                        String::from_utf8_lossy(&output.stderr)));
+                }
+
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .to_string()
+            }
+        };
+        Ok(channel_id)
+    }
+
+    async fn extract_channel_urls(uploads_url: &str, newest_n: Option<usize>, since_date: Option<&str>) -> Result<Vec<String>, String> {
+        let mut args: Vec<String> = vec!["--skip-download".to_string()];
+
+        match since_date {
+            // Upload dates need full extraction; the playlist is newest first, so the
+            // first video that's too old ends the listing
+            Some(since_date) => {
+                args.push("--dateafter".to_string());
+                args.push(ytdlp_date(since_date)?);
+                args.push("--break-on-reject".to_string());
+            }
+            None => args.push("--flat-playlist".to_string()),
+        }
+
+        if let Some(newest_n) = newest_n {
+            args.push("--playlist-end".to_string());
+            args.push(newest_n.max(1).to_string());
+        }
+
+        args.push("--print".to_string());
+        args.push("url".to_string());
+        args.push(uploads_url.to_string());
+
+        let output = run_cancellable(tokio::process::Command::new(app_settings::yt_dlp())
+            .args(&args), None).await
+            .map_err(|e| format!("Failed to extract channel videos: {}", e))?;
+
+        let urls: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        // --break-on-reject stops with a non-zero exit but no ERROR line, that one is expected
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() && (since_date.is_none() || stderr.contains("ERROR:")) {
+            return Err(format!("Failed to extract channel videos: {}", stderr));
+        }
+
+        Ok(urls)
     }
 
-    async fn extract_playlist_urls(&self, playlist_url: &str) -> Result<Vec<String>, String> {
-        // Use yt-dlp or similar to extract video URLs from playlist
-        let output = std::process::Command::new("yt-dlp")
-            .args(&[
+    // The Data API pages through the playlist and skips private and deleted videos; yt-dlp is
+    // the fallback without a key, or when the API fails
+    async fn extract_playlist_urls(youtube: Option<YouTubeAPI>, playlist_url: &str) -> Result<Vec<String>, String> {
+        if let (Some(youtube), Some(playlist_id)) = (youtube, youtube_playlist_id(playlist_url)) {
+            match youtube.get_playlist_items(&playlist_id).await {
+                Ok(items) => return Ok(items.iter().map(|item| item.url()).collect()),
+                Err(e) => tracing::warn!("Listing the playlist with yt-dlp instead: {}", e),
+            }
+        }
+
+        let output = run_cancellable(tokio::process::Command::new(app_settings::yt_dlp())
+            .args([
                 "--get-url",
                 "--flat-playlist",
                 playlist_url,
-            ])
-            .output()
+            ]), None).await
             .map_err(|e| format!("Failed to extract playlist URLs: {}", e))?;
 
         if output.status.success() {
@@ -417,51 +1740,294 @@ impl BatchProcessor {
     pub async fn generate_batch_report(&self, job_id: &str) -> Result<String, String> {
         let job = self.jobs.get(job_id)
             .ok_or("Batch job not found")?;
+        Ok(markdown_report(job))
+    }
+
+    // Every file a job left behind: its videos' outputs plus the reports and manifest written for it
+    pub fn job_files(&self, job_id: &str) -> Result<Vec<PathBuf>, String> {
+        let job = self.jobs.get(job_id)
+            .ok_or("Batch job not found")?;
+
+        let output_dir = Path::new(&job.config.output_directory);
+        let mut files: Vec<PathBuf> = job.results.iter()
+            .flat_map(|result| result.output_files.iter().map(PathBuf::from))
+            .collect();
+        files.extend(["html", "json", "md"].iter().map(|extension| output_dir.join(format!("batch_report_{}.{}", job.id, extension))));
+        files.push(export_manifest::batch_manifest_path(output_dir, &job.id));
+        files.retain(|file| file.is_file());
+        Ok(files)
+    }
+
+    // Writes batch_report_<job id>.<ext> into the job's output directory for each of
+    // "html", "json" and "markdown", returning the written paths. The processor is only locked
+    // to copy the job
+    pub async fn export_batch_report(processor: Arc<Mutex<BatchProcessor>>, job_id: &str, formats: &[String]) -> Result<Vec<String>, String> {
+        let job = processor.lock().await.jobs.get(job_id)
+            .cloned()
+            .ok_or("Batch job not found")?;
+
+        let report_dir = PathBuf::from(&job.config.output_directory);
+        std::fs::create_dir_all(&report_dir)
+            .map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+        let report = BatchReport::from_job(&job);
+        let mut written = Vec::new();
 
-        let mut report = String::new();
-        report.push_str(&format!("# Batch Processing Report\n\n"));
-        report.push_str(&format!("**Job Name:** {}\n", job.name));
-        report.push_str(&format!("**Job ID:** {}\n", job.id));
-        report.push_str(&format!("**Status:** {:?}\n", job.status));
-        report.push_str(&format!("**Created:** {}\n", job.created_at));
+        for format in formats {
+            let (content, extension) = match format.as_str() {
+                "html" => (report.to_html(&report_dir), "html"),
+                "json" => (report.to_json()?, "json"),
+                "markdown" => (markdown_report(&job), "md"),
+                _ => return Err(format!("Unsupported report format: {}", format)),
+            };
+
+            let report_path = report_dir.join(format!("batch_report_{}.{}", job.id, extension));
+            std::fs::write(&report_path, content)
+                .map_err(|e| format!("Failed to write batch report: {}", e))?;
+            written.push(report_path.to_string_lossy().to_string());
+        }
+
+        Ok(written)
+    }
+}
+
+fn markdown_report(job: &BatchJob) -> String {
+    let mut report = String::new();
+    report.push_str("# Batch Processing Report\n\n");
+    report.push_str(&format!("**Job Name:** {}\n", job.name));
+    report.push_str(&format!("**Job ID:** {}\n", job.id));
+    report.push_str(&format!("**Status:** {:?}\n", job.status));
+    report.push_str(&format!("**Created:** {}\n", job.created_at));
+    
+    if let Some(started) = &job.started_at {
+        report.push_str(&format!("**Started:** {}\n", started));
+    }
+    
+    if let Some(completed) = &job.completed_at {
+        report.push_str(&format!("**Completed:** {}\n", completed));
+    }
+
+    report.push_str("\n## Statistics\n\n");
+    report.push_str(&format!("- **Total Videos:** {}\n", job.progress.total_videos));
+    report.push_str(&format!("- **Processed:** {}\n", job.progress.processed_videos));
+    report.push_str(&format!("- **Failed:** {}\n", job.progress.failed_videos));
+    report.push_str(&format!("- **Success Rate:** {:.1}%\n", 
+        job.progress.processed_videos.saturating_sub(job.progress.failed_videos) as f64 / job.progress.total_videos.max(1) as f64 * 100.0));
+
+    report.push_str("\n## Results\n\n");
+    
+    for (index, result) in job.results.iter().enumerate() {
+        report.push_str(&format!("### Video {} - {:?}\n", index + 1, result.status));
+        report.push_str(&format!("**URL:** {}\n", result.url));
         
-        if let Some(started) = &job.started_at {
-            report.push_str(&format!("**Started:** {}\n", started));
+        if let Some(info) = &result.video_info {
+            report.push_str(&format!("**Title:** {}\n", info.title));
+            report.push_str(&format!("**Duration:** {:.1}s\n", info.duration));
         }
         
-        if let Some(completed) = &job.completed_at {
-            report.push_str(&format!("**Completed:** {}\n", completed));
+        report.push_str(&format!("**Nuggets Generated:** {}\n", result.nuggets.len()));
+        report.push_str(&format!("**Processing Time:** {:.1}s\n", result.processing_time_seconds));
+        report.push_str(&format!("**Output Files:** {}\n", result.output_files.len()));
+        
+        if let Some(error) = &result.error_message {
+            report.push_str(&format!("**Error:** {}\n", error));
         }
+        
+        report.push('\n');
+    }
 
-        report.push_str(&format!("\n## Statistics\n\n"));
-        report.push_str(&format!("- **Total Videos:** {}\n", job.progress.total_videos));
-        report.push_str(&format!("- **Processed:** {}\n", job.progress.processed_videos));
-        report.push_str(&format!("- **Failed:** {}\n", job.progress.failed_videos));
-        report.push_str(&format!("- **Success Rate:** {:.1}%\n", 
-            (job.progress.processed_videos - job.progress.failed_videos) as f64 / job.progress.total_videos as f64 * 100.0));
+    report
+}
 
-        report.push_str(&format!("\n## Results\n\n"));
-        
-        for (index, result) in job.results.iter().enumerate() {
-            report.push_str(&format!("### Video {} - {:?}\n", index + 1, result.status));
-            report.push_str(&format!("**URL:** {}\n", result.url));
-            
-            if let Some(info) = &result.video_info {
-                report.push_str(&format!("**Title:** {}\n", info.title));
-                report.push_str(&format!("**Duration:** {:.1}s\n", info.duration));
-            }
-            
-            report.push_str(&format!("**Nuggets Generated:** {}\n", result.nuggets.len()));
-            report.push_str(&format!("**Processing Time:** {:.1}s\n", result.processing_time_seconds));
-            report.push_str(&format!("**Output Files:** {}\n", result.output_files.len()));
-            
-            if let Some(error) = &result.error_message {
-                report.push_str(&format!("**Error:** {}\n", error));
-            }
-            
-            report.push_str("\n");
+pub fn uploads_playlist_id(channel_id: &str) -> Option<String> {
+    channel_id.strip_prefix("UC")
+        .filter(|rest| !rest.is_empty())
+        .map(|rest| format!("UU{}", rest))
+}
+
+// The list parameter of a playlist or watch URL, or a bare playlist ID
+fn youtube_playlist_id(playlist_url: &str) -> Option<String> {
+    let candidate = match playlist_url.split(['?', '&']).find_map(|part| part.strip_prefix("list=")) {
+        Some(list) => list,
+        None if !playlist_url.contains('/') => playlist_url.trim(),
+        None => return None,
+    };
+    let playlist_id: String = candidate.chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    (!playlist_id.is_empty()).then_some(playlist_id)
+}
+
+fn ytdlp_date(date: &str) -> Result<String, String> {
+    chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map(|date| date.format("%Y%m%d").to_string())
+        .map_err(|_| format!("Invalid date, expected YYYY-MM-DD: {}", date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uploads_playlist_id() {
+        assert_eq!(uploads_playlist_id("UC_x5XG1OV2P6uZZ5FSM9Ttw").as_deref(), Some("UU_x5XG1OV2P6uZZ5FSM9Ttw"));
+        assert_eq!(uploads_playlist_id("@GoogleDevelopers"), None);
+        assert_eq!(uploads_playlist_id("UC"), None);
+    }
+
+    #[test]
+    fn test_youtube_playlist_id() {
+        assert_eq!(youtube_playlist_id("https://www.youtube.com/playlist?list=PLrAXtmRdnEQy8VsC").as_deref(), Some("PLrAXtmRdnEQy8VsC"));
+        assert_eq!(youtube_playlist_id("https://www.youtube.com/watch?v=abc&list=PL123&index=2").as_deref(), Some("PL123"));
+        assert_eq!(youtube_playlist_id("PL123").as_deref(), Some("PL123"));
+        assert_eq!(youtube_playlist_id("https://vimeo.com/showcase/123"), None);
+    }
+
+    #[test]
+    fn test_for_url_applies_overrides() {
+        let mut config: BatchConfig = serde_json::from_value(serde_json::json!({
+            "video_config": { "nugget_duration": 30.0 },
+            "output_directory": "/tmp/out",
+            "export_formats": ["json"],
+            "enable_ai_analysis": true,
+            "enable_transcript": true,
+            "enable_social_formats": false,
+            "concurrent_jobs": 2,
+            "retry_failed": false,
+            "max_retries": 0
+        })).unwrap();
+        config.url_overrides.insert("https://youtu.be/short".to_string(), VideoOverride {
+            nugget_duration: Some(10.0),
+            skip_transcription: Some(true),
+            quality: Some("480p".to_string()),
+        });
+
+        let overridden = config.for_url("https://youtu.be/short");
+        assert_eq!(overridden.video_config["nugget_duration"], serde_json::json!(10.0));
+        assert!(!overridden.enable_transcript && !overridden.enable_ai_analysis);
+        assert_eq!(overridden.download_quality(), "480p");
+
+        let untouched = config.for_url("https://youtu.be/other");
+        assert_eq!(untouched.video_config["nugget_duration"], serde_json::json!(30.0));
+        assert!(untouched.enable_ai_analysis);
+        assert_eq!(untouched.download_quality(), "best");
+    }
+
+    fn config(output_directory: &str) -> BatchConfig {
+        serde_json::from_value(serde_json::json!({
+            "video_config": {},
+            "output_directory": output_directory,
+            "export_formats": [],
+            "enable_ai_analysis": false,
+            "enable_transcript": false,
+            "enable_social_formats": false,
+            "concurrent_jobs": 1,
+            "retry_failed": false,
+            "max_retries": 0
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_refused_run_goes_back_to_pending() {
+        let mut processor = BatchProcessor::new().unwrap();
+        let job_id = processor.create_batch_job("Talks".to_string(), vec!["https://youtu.be/a".to_string()], config("/tmp/out"), JobPriority::Normal);
+        processor.begin_run(&job_id, None, None).unwrap();
+        let cancel_token = processor.cancel_tokens[&job_id].clone();
+
+        processor.refuse_run(&job_id, &cancel_token, "Not enough disk space".to_string());
+        let job = &processor.jobs[&job_id];
+        assert_eq!(job.status, BatchStatus::Pending);
+        assert_eq!(job.warnings, vec!["Not enough disk space"]);
+        assert!(processor.begin_run(&job_id, None, None).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_start_batch_job_returns_while_the_run_goes_on() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let processor = Arc::new(Mutex::new(BatchProcessor::new().unwrap()));
+        let job_id = processor.lock().await.create_batch_job(
+            "Talks".to_string(),
+            vec!["https://youtu.be/a".to_string()],
+            config(output_dir.path().to_str().unwrap()),
+            JobPriority::Normal,
+        );
+
+        BatchProcessor::start_batch_job(processor.clone(), &job_id, |_| {}).await.unwrap();
+
+        // The test runtime is single-threaded, so the spawned run hasn't taken a step yet
+        let processor = processor.try_lock().expect("start_batch_job returned holding the processor lock");
+        assert_eq!(processor.jobs[&job_id].status, BatchStatus::Running);
+        assert!(processor.jobs[&job_id].results.is_empty());
+    }
+
+    #[test]
+    fn test_interrupted_run_is_restored_paused() {
+        let storage_dir = tempfile::tempdir().unwrap();
+        let mut processor = BatchProcessor::new().unwrap().with_storage(storage_dir.path().to_path_buf()).unwrap();
+        let urls = vec!["https://youtu.be/a".to_string(), "https://youtu.be/b".to_string()];
+        let job_id = processor.create_batch_job("Talks".to_string(), urls.clone(), config("/tmp/out"), JobPriority::Normal);
+        processor.begin_run(&job_id, None, None).unwrap();
+
+        let mut restored = BatchProcessor::new().unwrap().with_storage(storage_dir.path().to_path_buf()).unwrap();
+        restored.load_jobs().unwrap();
+        let job = &restored.jobs[&job_id];
+        assert_eq!(job.status, BatchStatus::Paused);
+        assert!(job.interrupted);
+        assert!(restored.recovered_jobs.contains(&job_id));
+        assert_eq!(job.remaining_urls(), urls);
+        assert!(restored.begin_run(&job_id, None, None).is_ok());
+    }
+
+    #[test]
+    fn test_prepare_failed_retry_requeues_only_failed_videos() {
+        let mut processor = BatchProcessor::new().unwrap();
+        let urls = vec!["https://youtu.be/a".to_string(), "https://youtu.be/b".to_string(), "https://youtu.be/c".to_string()];
+        let job_id = processor.create_batch_job("Talks".to_string(), urls.clone(), config("/tmp/out"), JobPriority::Normal);
+        for (url, status) in urls.iter().zip([ProcessingStatus::Success, ProcessingStatus::Failed, ProcessingStatus::Failed]) {
+            processor.record_result(&job_id, BatchResult { status, ..BatchProcessor::skipped_result(url.clone(), "") });
         }
+        processor.jobs.get_mut(&job_id).unwrap().status = BatchStatus::Completed;
+
+        assert_eq!(processor.prepare_failed_retry(&job_id).unwrap(), 2);
+        let job = &processor.jobs[&job_id];
+        assert_eq!(job.status, BatchStatus::Paused);
+        assert_eq!(job.results.len(), 1);
+        assert_eq!(job.retried_results.len(), 2);
+        assert_eq!((job.progress.processed_videos, job.progress.failed_videos), (1, 0));
+        assert!((job.progress.percentage - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(job.remaining_urls(), urls[1..]);
 
-        Ok(report)
+        assert!(processor.prepare_failed_retry(&job_id).is_err());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_analysis_setup_uses_the_stored_ai_config() {
+        let workspace = tempfile::tempdir().unwrap();
+        let stored = AIConfig { max_chunk_chars: 1234, ..AIConfig::default() };
+        std::fs::write(workspace.path().join("ai_config.json"), serde_json::to_string(&stored).unwrap()).unwrap();
+        let mut manager = ProjectManager::new(workspace.path().join("projects")).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        manager.get_project_mut(&project_id).unwrap().unwrap().settings.output_language = Some("de".to_string());
+
+        let processor = BatchProcessor::new().unwrap()
+            .with_ai_config_store(Arc::new(Mutex::new(AIConfigStore::new(workspace.path().to_path_buf()))))
+            .with_project_manager(Arc::new(Mutex::new(manager)));
+        let processor = Arc::new(Mutex::new(processor));
+        let mut job_config = config("/tmp/out");
+        let without_analysis = processor.lock().await.create_batch_job("Plain".to_string(), vec![], job_config.clone(), JobPriority::Normal);
+        job_config.enable_ai_analysis = true;
+        job_config.project_id = Some(project_id);
+        let with_analysis = processor.lock().await.create_batch_job("Analyzed".to_string(), vec![], job_config, JobPriority::Normal);
+
+        assert!(BatchProcessor::load_analysis_setup(&processor, &without_analysis, None).await.unwrap().is_none());
+        let setup = BatchProcessor::load_analysis_setup(&processor, &with_analysis, None).await.unwrap().unwrap();
+        assert_eq!(setup.config.max_chunk_chars, 1234);
+        assert_eq!(setup.config.output_language.as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn test_ytdlp_date() {
+        assert_eq!(ytdlp_date("2024-03-01").unwrap(), "20240301");
+        assert!(ytdlp_date("03/01/2024").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+use crate::batch_processor::{BatchJob, BatchStatus, ProcessingStatus};
+use serde::{Serialize, Deserialize};
+use std::path::Path;
+
+// Machine-readable counterpart of generate_batch_report, also the input for the HTML report
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchReport {
+    pub job_id: String,
+    pub job_name: String,
+    pub status: BatchStatus,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub generated_at: String,
+    pub total_videos: usize,
+    pub processed_videos: usize,
+    pub failed_videos: usize,
+    pub skipped_videos: usize,
+    pub success_rate: f64,
+    pub total_nuggets: usize,
+    pub warnings: Vec<String>,
+    pub videos: Vec<VideoReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoReport {
+    pub url: String,
+    pub title: Option<String>,
+    pub duration_seconds: Option<f64>,
+    pub status: ProcessingStatus,
+    pub nugget_count: usize,
+    pub processing_time_seconds: f64,
+    pub thumbnails: Vec<String>,
+    pub output_files: Vec<String>,
+    pub error_message: Option<String>,
+}
+
+impl BatchReport {
+    pub fn from_job(job: &BatchJob) -> Self {
+        let skipped_videos = job.results.iter()
+            .filter(|r| r.status == ProcessingStatus::Skipped)
+            .count();
+        let succeeded = job.results.iter()
+            .filter(|r| r.status == ProcessingStatus::Success)
+            .count();
+
+        let videos = job.results.iter()
+            .map(|result| {
+                let (thumbnails, output_files) = result.output_files.iter()
+                    .cloned()
+                    .partition(|file| is_thumbnail(file));
+
+                VideoReport {
+                    url: result.url.clone(),
+                    title: result.video_info.as_ref().map(|info| info.title.clone()),
+                    duration_seconds: result.video_info.as_ref().map(|info| info.duration),
+                    status: result.status.clone(),
+                    nugget_count: result.nuggets.len(),
+                    processing_time_seconds: result.processing_time_seconds,
+                    thumbnails,
+                    output_files,
+                    error_message: result.error_message.clone(),
+                }
+            })
+            .collect();
+
+        Self {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            status: job.status.clone(),
+            created_at: job.created_at.clone(),
+            started_at: job.started_at.clone(),
+            completed_at: job.completed_at.clone(),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            total_videos: job.progress.total_videos,
+            processed_videos: job.progress.processed_videos,
+            failed_videos: job.progress.failed_videos,
+            skipped_videos,
+            success_rate: if job.progress.total_videos == 0 {
+                0.0
+            } else {
+                succeeded as f64 / job.progress.total_videos as f64 * 100.0
+            },
+            total_nuggets: job.results.iter().map(|r| r.nuggets.len()).sum(),
+            warnings: job.warnings.clone(),
+            videos,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize batch report: {}", e))
+    }
+
+    // Self-contained page; thumbnails and output files are linked relative to report_dir
+    // when they're inside it, so the folder can be moved or zipped as a whole
+    pub fn to_html(&self, report_dir: &Path) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>Batch Report - {}</title>\n", escape_html(&self.job_name)));
+        html.push_str(REPORT_STYLE);
+        html.push_str("</head>\n<body>\n");
+
+        html.push_str(&format!("<h1>{}</h1>\n", escape_html(&self.job_name)));
+        html.push_str(&format!("<p class=\"meta\">Job {} &middot; {:?} &middot; created {}",
+            escape_html(&self.job_id), self.status, escape_html(&self.created_at)));
+        if let Some(completed) = &self.completed_at {
+            html.push_str(&format!(" &middot; completed {}", escape_html(completed)));
+        }
+        html.push_str("</p>\n");
+
+        html.push_str("<table class=\"stats\">\n");
+        for (label, value) in [
+            ("Total videos", self.total_videos.to_string()),
+            ("Processed", self.processed_videos.to_string()),
+            ("Failed", self.failed_videos.to_string()),
+            ("Skipped", self.skipped_videos.to_string()),
+            ("Nuggets", self.total_nuggets.to_string()),
+            ("Success rate", format!("{:.1}%", self.success_rate)),
+        ] {
+            html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", label, value));
+        }
+        html.push_str("</table>\n");
+
+        if !self.warnings.is_empty() {
+            html.push_str("<ul class=\"warnings\">\n");
+            for warning in &self.warnings {
+                html.push_str(&format!("<li>{}</li>\n", escape_html(warning)));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        for (index, video) in self.videos.iter().enumerate() {
+            let status_class = format!("{:?}", video.status).to_lowercase();
+            html.push_str(&format!("<section class=\"video {}\">\n", status_class));
+            html.push_str(&format!("<h2>{}. {}</h2>\n", index + 1,
+                escape_html(video.title.as_deref().unwrap_or(&video.url))));
+            html.push_str(&format!("<p class=\"meta\"><a href=\"{}\">{}</a> &middot; {:?} &middot; {} nuggets &middot; {:.1}s",
+                escape_html(&video.url), escape_html(&video.url), video.status, video.nugget_count, video.processing_time_seconds));
+            if let Some(duration) = video.duration_seconds {
+                html.push_str(&format!(" &middot; {:.0}s long", duration));
+            }
+            html.push_str("</p>\n");
+
+            if let Some(error) = &video.error_message {
+                html.push_str(&format!("<p class=\"error\">{}</p>\n", escape_html(error)));
+            }
+
+            if !video.thumbnails.is_empty() {
+                html.push_str("<div class=\"thumbnails\">\n");
+                for thumbnail in &video.thumbnails {
+                    html.push_str(&format!("<img src=\"{}\" alt=\"\" loading=\"lazy\">\n",
+                        escape_html(&file_link(thumbnail, report_dir))));
+                }
+                html.push_str("</div>\n");
+            }
+
+            if !video.output_files.is_empty() {
+                html.push_str("<ul class=\"files\">\n");
+                for file in &video.output_files {
+                    let name = Path::new(file).file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| file.clone());
+                    html.push_str(&format!("<li><a href=\"{}\">{}</a></li>\n",
+                        escape_html(&file_link(file, report_dir)), escape_html(&name)));
+                }
+                html.push_str("</ul>\n");
+            }
+
+            html.push_str("</section>\n");
+        }
+
+        html.push_str(&format!("<footer>Generated {}</footer>\n", escape_html(&self.generated_at)));
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+const REPORT_STYLE: &str = "<style>
+body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif; max-width: 960px; margin: 2rem auto; padding: 0 1rem; color: #1f2933; }
+.meta, footer { color: #616e7c; font-size: 0.9rem; }
+table.stats th { text-align: left; padding-right: 1.5rem; font-weight: 500; }
+.warnings li, .error { color: #b44d12; }
+section.video { border-top: 1px solid #e4e7eb; padding: 1rem 0; }
+section.failed h2 { color: #ab091e; }
+.thumbnails img { height: 96px; margin: 0 0.5rem 0.5rem 0; border-radius: 4px; }
+</style>\n";
+
+fn is_thumbnail(file: &str) -> bool {
+    file.ends_with("_thumb.jpg") || file.ends_with("_thumb.png")
+}
+
+pub fn file_link(file: &str, report_dir: &Path) -> String {
+    let path = Path::new(file);
+    if let Ok(relative) = path.strip_prefix(report_dir) {
+        return relative.to_string_lossy().replace('\\', "/");
+    }
+
+    let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    format!("file://{}", absolute.to_string_lossy().replace('\\', "/"))
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("<b>\"Tom\" & 'Jerry'</b>"), "&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;");
+    }
+
+    #[test]
+    fn test_file_link_is_relative_inside_report_dir() {
+        let report_dir = Path::new("/exports/batch");
+        assert_eq!(file_link("/exports/batch/channel/clip_thumb.jpg", report_dir), "channel/clip_thumb.jpg");
+        assert!(file_link("/elsewhere/clip.mp4", report_dir).starts_with("file:///elsewhere"));
+    }
+}
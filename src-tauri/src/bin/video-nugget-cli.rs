@@ -0,0 +1,312 @@
+// Runs the desktop app's pipeline without its window, for servers and scripts. Results go to
+// stdout or the --output file, progress and logs to stderr.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use video_nugget_lib::batch_processor::{parse_batch_config, BatchJob, BatchProcessor, BatchStatus};
+use video_nugget_lib::file_manager::{write_export, FileManager};
+use video_nugget_lib::job_scheduler::JobPriority;
+use video_nugget_lib::operations::{OperationKind, OperationProgress, OperationRegistry};
+use video_nugget_lib::processors::Processors;
+use video_nugget_lib::video_processor::process_video_with_progress;
+
+const USAGE: &str = "\
+Usage:
+  video-nugget-cli process <url> [--nugget-duration <seconds>] [--overlap <seconds>]
+                   [--no-transcript] [--output <file>] [--format <format>]
+  video-nugget-cli batch <config.yaml> [--workspace <dir>]
+  video-nugget-cli export <nuggets.json> --output <file> [--format <format>]
+
+Formats: json, jsonl, csv, markdown, srt, vtt, anki, pdf, html. Without --format it's taken
+from the output file's extension. process prints its result as JSON when there's no --output.
+Add --verbose to any command for the full log.";
+
+// Options that are flags rather than taking a value
+const FLAGS: &[&str] = &["--no-transcript", "--verbose", "--help"];
+
+struct Args {
+    command: String,
+    target: String,
+    options: HashMap<String, String>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut flags = Vec::new();
+        while let Some(arg) = args.next() {
+            if FLAGS.contains(&arg.as_str()) {
+                flags.push(arg);
+            } else if arg.starts_with("--") {
+                let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+                options.insert(arg, value);
+            } else {
+                positional.push(arg);
+            }
+        }
+        if flags.iter().any(|flag| flag == "--help") {
+            return Err(String::new());
+        }
+
+        let mut positional = positional.into_iter();
+        match (positional.next(), positional.next(), positional.next()) {
+            (Some(command), Some(target), None) => Ok(Self { command, target, options, flags }),
+            _ => Err(String::new()),
+        }
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(|value| value.as_str())
+    }
+
+    fn seconds(&self, name: &str) -> Result<Option<f64>, String> {
+        self.option(name)
+            .map(|value| value.parse::<f64>().map_err(|_| format!("{} must be a number of seconds", name)))
+            .transpose()
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    // --format, or the output file's extension
+    fn format(&self, output: &str) -> Result<String, String> {
+        if let Some(format) = self.option("--format") {
+            return Ok(format.to_string());
+        }
+        let extension = Path::new(output).extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .ok_or("Pass --format, the output file has no extension to go by")?;
+        Ok(match extension.as_str() {
+            "md" => "markdown".to_string(),
+            "apkg" => "anki".to_string(),
+            "htm" => "html".to_string(),
+            _ => extension,
+        })
+    }
+}
+
+// A batch config file: the job's name and URLs next to the fields of BatchConfig
+#[derive(Deserialize)]
+struct BatchFile {
+    name: Option<String>,
+    urls: Vec<String>,
+    #[serde(default)]
+    priority: JobPriority,
+    #[serde(flatten)]
+    config: serde_json::Value,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("error: {}\n", e);
+            }
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let level = if args.flag("--verbose") { tracing::Level::INFO } else { tracing::Level::WARN };
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(level)
+        .init();
+
+    let result = match args.command.as_str() {
+        "process" => process(&args).await,
+        "batch" => batch(&args).await,
+        "export" => export(&args).await,
+        command => Err(format!("Unknown command {}\n\n{}", command, USAGE)),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn process(args: &Args) -> Result<(), String> {
+    let mut config = HashMap::new();
+    if let Some(nugget_duration) = args.seconds("--nugget-duration")? {
+        config.insert("nugget_duration".to_string(), serde_json::json!(nugget_duration));
+    }
+    if let Some(overlap) = args.seconds("--overlap")? {
+        config.insert("overlap_duration".to_string(), serde_json::json!(overlap));
+    }
+    config.insert("enable_transcript".to_string(), serde_json::json!(!args.flag("--no-transcript")));
+    let output = args.option("--output");
+    let format = output.map(|output| args.format(output)).transpose()?;
+
+    let operations = OperationRegistry::new(print_progress);
+    let processors = Processors::new();
+    let operation = operations.start(None, OperationKind::Transcription, &args.target)?;
+    let result = {
+        let run = process_video_with_progress(&args.target, config, &processors, &operation);
+        tokio::pin!(run);
+        tokio::select! {
+            result = &mut run => result,
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("Cancelling");
+                operations.cancel(operation.id())?;
+                run.await
+            }
+        }
+    };
+    operation.finish(&result);
+    let result = result?;
+
+    match (output, format) {
+        (Some(output), Some(format)) => {
+            let message = write_export(result.nuggets, &format, output, None, None, None, None).await?;
+            eprintln!("{}", message);
+        }
+        _ => {
+            let json = serde_json::to_string_pretty(&result)
+                .map_err(|e| format!("Failed to serialize result: {}", e))?;
+            println!("{}", json);
+        }
+    }
+    Ok(())
+}
+
+async fn batch(args: &Args) -> Result<(), String> {
+    let contents = std::fs::read_to_string(&args.target)
+        .map_err(|e| format!("Failed to read {}: {}", args.target, e))?;
+    let batch_file: BatchFile = serde_yaml::from_str(&contents)
+        .map_err(|e| format!("Invalid batch config {}: {}", args.target, e))?;
+    let config = parse_batch_config(batch_file.config)?;
+    let name = batch_file.name.unwrap_or_else(|| {
+        Path::new(&args.target).file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Batch".to_string())
+    });
+
+    // Job state lives here like it does in the app's workspace, so a second run can be compared
+    // against the processed-video index of the first
+    let workspace = args.option("--workspace")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(".video-nugget"));
+    let processor = BatchProcessor::new()?
+        .with_storage(workspace.join("batch_jobs"))?
+        .with_projects_root(workspace.clone())
+        .with_processors(Arc::new(Processors::new()));
+    let processor = Arc::new(Mutex::new(processor));
+    let job_id = processor.lock().await.create_batch_job(name, batch_file.urls, config, batch_file.priority);
+
+    // start_batch_job returns once the run has started; updates come in until it's over
+    let (updates, mut update_rx) = tokio::sync::mpsc::unbounded_channel();
+    let on_update = move |job: &BatchJob| {
+        let _ = updates.send(format!(
+            "{:?}: {} of {} videos ({:.0}%){}",
+            job.status,
+            job.progress.processed_videos,
+            job.progress.total_videos,
+            job.progress.percentage,
+            job.progress.current_video.as_ref().map(|url| format!(", {}", url)).unwrap_or_default(),
+        ));
+    };
+    BatchProcessor::start_batch_job(processor.clone(), &job_id, on_update).await?;
+
+    let mut last_line = String::new();
+    let mut cancelled = false;
+    loop {
+        tokio::select! {
+            line = update_rx.recv() => match line {
+                Some(line) if line != last_line => {
+                    eprintln!("{}", line);
+                    last_line = line;
+                }
+                Some(_) => {}
+                None => break,
+            },
+            _ = tokio::signal::ctrl_c(), if !cancelled => {
+                eprintln!("Cancelling");
+                cancelled = true;
+                processor.lock().await.cancel_batch_job(&job_id)?;
+            }
+        }
+    }
+
+    let processor = processor.lock().await;
+    let job = processor.get_batch_job(&job_id).ok_or("Batch job not found")?;
+    for result in &job.results {
+        if let Some(error) = &result.error_message {
+            eprintln!("failed: {}: {}", result.url, error);
+        }
+    }
+    match job.status {
+        BatchStatus::Completed if job.progress.failed_videos == 0 => {
+            eprintln!("Done, output is in {}", job.config.output_directory);
+            Ok(())
+        }
+        _ => Err(format!("{} of {} videos failed", job.progress.failed_videos, job.progress.total_videos)),
+    }
+}
+
+async fn export(args: &Args) -> Result<(), String> {
+    let output = args.option("--output").ok_or("export needs --output")?;
+    let format = args.format(output)?;
+    let nuggets = FileManager::new().load_nuggets(&args.target).await?;
+    let message = write_export(nuggets, &format, output, None, None, None, None).await?;
+    eprintln!("{}", message);
+    Ok(())
+}
+
+fn print_progress(progress: &OperationProgress) {
+    match (progress.percent, &progress.message) {
+        (Some(percent), Some(message)) => eprintln!("{}: {:.0}% {}", progress.stage, percent, message),
+        (Some(percent), None) => eprintln!("{}: {:.0}%", progress.stage, percent),
+        (None, _) => eprintln!("{}", progress.stage),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Result<Args, String> {
+        Args::parse(line.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = args("process https://youtu.be/a --overlap 2 --no-transcript --output out.md").unwrap();
+        assert_eq!((parsed.command.as_str(), parsed.target.as_str()), ("process", "https://youtu.be/a"));
+        assert_eq!(parsed.seconds("--overlap").unwrap(), Some(2.0));
+        assert!(parsed.flag("--no-transcript"));
+        assert_eq!(parsed.format("out.md").unwrap(), "markdown");
+
+        assert_eq!(args("export nuggets.json --output notes.txt --format csv").unwrap().format("notes.txt").unwrap(), "csv");
+        assert!(args("process https://youtu.be/a --overlap soon").unwrap().seconds("--overlap").is_err());
+        assert!(args("process https://youtu.be/a --output").is_err());
+        assert!(args("process").is_err());
+        assert!(args("batch jobs.yaml --help").is_err());
+    }
+
+    #[test]
+    fn test_batch_file_keeps_config_fields() {
+        let batch_file: BatchFile = serde_yaml::from_str("\
+urls: [https://youtu.be/a]
+priority: High
+output_directory: out
+concurrent_jobs: 2
+").unwrap();
+        assert_eq!(batch_file.name, None);
+        assert_eq!(batch_file.urls, vec!["https://youtu.be/a"]);
+        assert_eq!(batch_file.priority, JobPriority::High);
+        assert_eq!(batch_file.config["concurrent_jobs"], serde_json::json!(2));
+        assert!(batch_file.config.get("urls").is_none());
+    }
+}
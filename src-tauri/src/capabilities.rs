@@ -0,0 +1,211 @@
+use serde::{Serialize, Deserialize};
+use std::path::Path;
+use std::process::Command;
+use sysinfo::System;
+use crate::disk_space;
+
+// Below this much free temp space, downloads default to 720p so a long video still fits
+const LOW_DISK_BYTES: u64 = 10 * 1024 * 1024 * 1024;
+
+// Hardware H.264 encoders, in order of preference. VAAPI isn't here: it needs a device and an
+// upload filter the FFmpeg commands don't set up
+const HARDWARE_ENCODERS: [(&str, &str); 4] = [
+    ("h264_videotoolbox", "VideoToolbox"),
+    ("h264_nvenc", "NVENC"),
+    ("h264_qsv", "Quick Sync"),
+    ("h264_amf", "AMF"),
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HardwareEncoder {
+    // FFmpeg's name for it, what goes after -c:v
+    pub name: String,
+    pub api: String,
+}
+
+impl HardwareEncoder {
+    // Roughly the quality of libx264 at CRF 23; each encoder has its own way of asking
+    pub fn quality_args(&self) -> Vec<&'static str> {
+        match self.name.as_str() {
+            "h264_videotoolbox" => vec!["-q:v", "65"],
+            "h264_nvenc" => vec!["-preset", "p5", "-cq", "23"],
+            "h264_qsv" => vec!["-global_quality", "23"],
+            _ => vec!["-rc", "cqp", "-qp_i", "23", "-qp_p", "23"],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum WhisperAcceleration {
+    Cuda,
+    // Apple Silicon, which whisper.cpp uses on its own
+    Metal,
+    Cpu,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WhisperCapability {
+    pub path: String,
+    pub acceleration: WhisperAcceleration,
+}
+
+// What the pipeline picks when the user hasn't said otherwise
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecommendedDefaults {
+    pub video_encoder: String,
+    pub max_ffmpeg_processes: usize,
+    pub max_transcriptions: usize,
+    pub download_quality: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CapabilityReport {
+    pub ffmpeg_path: Option<String>,
+    // Only the ones that managed a test encode; FFmpeg lists every encoder it was built with
+    pub hardware_encoders: Vec<HardwareEncoder>,
+    pub whisper: Option<WhisperCapability>,
+    pub cpu_cores: usize,
+    pub physical_cpu_cores: Option<usize>,
+    pub total_memory_mb: u64,
+    pub workspace_free_bytes: Option<u64>,
+    pub temp_free_bytes: Option<u64>,
+    pub recommended: RecommendedDefaults,
+}
+
+// Runs FFmpeg and the GPU tools, so it takes a second or two; keep it off the async runtime
+pub fn detect(ffmpeg_path: Option<&str>, whisper_path: Option<&str>, workspace: &Path) -> CapabilityReport {
+    let hardware_encoders = ffmpeg_path.map(hardware_encoders).unwrap_or_default();
+    let whisper = whisper_path.map(|path| WhisperCapability {
+        path: path.to_string(),
+        acceleration: whisper_acceleration(path),
+    });
+
+    let cpu_cores = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let mut system = System::new();
+    system.refresh_memory();
+    let temp_free_bytes = disk_space::available_space(&std::env::temp_dir());
+
+    let recommended = recommend(
+        cpu_cores,
+        hardware_encoders.first(),
+        whisper.as_ref().map(|whisper| whisper.acceleration),
+        temp_free_bytes,
+    );
+    CapabilityReport {
+        ffmpeg_path: ffmpeg_path.map(str::to_string),
+        hardware_encoders,
+        whisper,
+        cpu_cores,
+        physical_cpu_cores: system.physical_core_count(),
+        total_memory_mb: system.total_memory() / (1024 * 1024),
+        workspace_free_bytes: disk_space::available_space(workspace),
+        temp_free_bytes,
+        recommended,
+    }
+}
+
+// The encoders FFmpeg was built with that also work on this machine. NVENC, for one, is in
+// most builds whether or not there's an NVIDIA card
+pub fn hardware_encoders(ffmpeg_path: &str) -> Vec<HardwareEncoder> {
+    let Ok(output) = Command::new(ffmpeg_path).args(["-hide_banner", "-encoders"]).output() else {
+        return Vec::new();
+    };
+    let listed = String::from_utf8_lossy(&output.stdout);
+    let built_with: Vec<&str> = listed.lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .collect();
+
+    HARDWARE_ENCODERS.iter()
+        .filter(|(name, _)| built_with.contains(name))
+        .filter(|(name, _)| encodes(ffmpeg_path, name))
+        .map(|(name, api)| HardwareEncoder {
+            name: name.to_string(),
+            api: api.to_string(),
+        })
+        .collect()
+}
+
+// A tenth of a second of black frames, thrown away
+fn encodes(ffmpeg_path: &str, encoder: &str) -> bool {
+    Command::new(ffmpeg_path)
+        .args([
+            "-hide_banner", "-loglevel", "error",
+            "-f", "lavfi", "-i", "color=c=black:s=256x256:d=0.1",
+            "-c:v", encoder,
+            "-f", "null", "-",
+        ])
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+fn whisper_acceleration(whisper_path: &str) -> WhisperAcceleration {
+    let has_nvidia_gpu = Command::new("nvidia-smi")
+        .arg("-L")
+        .output()
+        .is_ok_and(|output| output.status.success() && String::from_utf8_lossy(&output.stdout).contains("GPU"));
+    if has_nvidia_gpu {
+        return WhisperAcceleration::Cuda;
+    }
+
+    // The Python openai-whisper runs on the CPU on a Mac, only whisper.cpp uses Metal
+    let whisper_cpp = Path::new(whisper_path).file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .is_some_and(|name| name.contains("cpp") || name.starts_with("whisper-cli"));
+    if cfg!(all(target_os = "macos", target_arch = "aarch64")) && whisper_cpp {
+        WhisperAcceleration::Metal
+    } else {
+        WhisperAcceleration::Cpu
+    }
+}
+
+fn recommend(
+    cpu_cores: usize,
+    encoder: Option<&HardwareEncoder>,
+    whisper: Option<WhisperAcceleration>,
+    temp_free_bytes: Option<u64>,
+) -> RecommendedDefaults {
+    // A hardware encoder leaves the CPU free for more FFmpeg processes than software encoding does
+    let max_ffmpeg_processes = match encoder {
+        Some(_) => cpu_cores.clamp(1, 4),
+        None => (cpu_cores / 2).max(1),
+    };
+    // Whisper on the CPU already uses every core; a GPU can take a second one
+    let max_transcriptions = match whisper {
+        Some(WhisperAcceleration::Cuda) | Some(WhisperAcceleration::Metal) => 2,
+        _ => 1,
+    };
+    let download_quality = match temp_free_bytes {
+        Some(free) if free < LOW_DISK_BYTES => "720p",
+        _ => "best",
+    };
+
+    RecommendedDefaults {
+        video_encoder: encoder.map(|encoder| encoder.name.clone()).unwrap_or_else(|| "libx264".to_string()),
+        max_ffmpeg_processes,
+        max_transcriptions,
+        download_quality: download_quality.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommend() {
+        let software = recommend(8, None, Some(WhisperAcceleration::Cpu), Some(100 * 1024 * 1024 * 1024));
+        assert_eq!(software.video_encoder, "libx264");
+        assert_eq!(software.max_ffmpeg_processes, 4);
+        assert_eq!(software.max_transcriptions, 1);
+        assert_eq!(software.download_quality, "best");
+
+        let nvenc = HardwareEncoder { name: "h264_nvenc".to_string(), api: "NVENC".to_string() };
+        let gpu = recommend(2, Some(&nvenc), Some(WhisperAcceleration::Cuda), Some(1024));
+        assert_eq!(gpu.video_encoder, "h264_nvenc");
+        assert_eq!(gpu.max_ffmpeg_processes, 2);
+        assert_eq!(gpu.max_transcriptions, 2);
+        assert_eq!(gpu.download_quality, "720p");
+    }
+}
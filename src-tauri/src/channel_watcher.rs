@@ -0,0 +1,207 @@
+use crate::atomic_file::write_atomic;
+use crate::youtube_api::{PlaylistItem, UploadsCheck, YouTubeAPI, YouTubeApiError};
+use serde::{Serialize, Deserialize};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+pub const NEW_UPLOADS_EVENT: &str = "youtube://new-uploads";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WatchedChannel {
+    pub channel_id: String,
+    pub uploads_playlist_id: String,
+    // Of the first page of uploads at the last check, sent back as If-None-Match
+    #[serde(default)]
+    pub etag: Option<String>,
+    // Publish time of the newest upload seen; only later ones are new. None until the first check
+    #[serde(default)]
+    pub last_published_at: Option<String>,
+    pub added_at: String,
+    #[serde(default)]
+    pub last_checked_at: Option<String>,
+}
+
+// Payload of NEW_UPLOADS_EVENT, newest first
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewUploads {
+    pub channel_id: String,
+    pub uploads: Vec<PlaylistItem>,
+}
+
+// Channels whose new uploads the app looks out for, kept in the workspace. A check costs one
+// quota unit a channel whether or not anything was uploaded
+pub struct ChannelWatcher {
+    path: Mutex<PathBuf>,
+    channels: Mutex<Vec<WatchedChannel>>,
+}
+
+impl ChannelWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        let channels = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: Mutex::new(path),
+            channels: Mutex::new(channels),
+        }
+    }
+
+    // After the workspace moved, with the file in it
+    pub fn relocate(&self, path: PathBuf) {
+        *self.path.lock().unwrap_or_else(|e| e.into_inner()) = path;
+    }
+
+    pub fn list(&self) -> Vec<WatchedChannel> {
+        self.lock().clone()
+    }
+
+    // Uploads from before the first check aren't reported, only what comes after
+    pub fn watch(&self, channel_id: &str, uploads_playlist_id: String) -> Result<WatchedChannel, String> {
+        let mut channels = self.lock();
+        if let Some(channel) = channels.iter().find(|channel| channel.channel_id == channel_id) {
+            return Ok(channel.clone());
+        }
+        let channel = WatchedChannel {
+            channel_id: channel_id.to_string(),
+            uploads_playlist_id,
+            etag: None,
+            last_published_at: None,
+            added_at: chrono::Utc::now().to_rfc3339(),
+            last_checked_at: None,
+        };
+        channels.push(channel.clone());
+        self.save(&channels)?;
+        Ok(channel)
+    }
+
+    pub fn unwatch(&self, channel_id: &str) -> Result<(), String> {
+        let mut channels = self.lock();
+        let count = channels.len();
+        channels.retain(|channel| channel.channel_id != channel_id);
+        if channels.len() == count {
+            return Err(format!("Channel {} isn't watched", channel_id));
+        }
+        self.save(&channels)
+    }
+
+    // Channels with new uploads only. A channel that fails is skipped until the next check, but
+    // a missing or unusable key or an exhausted quota would fail every one, so those end the check
+    pub async fn check(&self, youtube: &YouTubeAPI) -> Result<Vec<NewUploads>, YouTubeApiError> {
+        let mut found = Vec::new();
+        for channel in self.list() {
+            let check = match youtube.get_new_uploads(&channel.uploads_playlist_id, channel.etag.as_deref(), channel.last_published_at.as_deref()).await {
+                Ok(check) => check,
+                Err(e @ (YouTubeApiError::MissingApiKey(_) | YouTubeApiError::InvalidApiKey | YouTubeApiError::KeyRestricted(_) | YouTubeApiError::QuotaExceeded)) => return Err(e),
+                Err(e) => {
+                    tracing::warn!("Failed to check channel {} for uploads: {}", channel.channel_id, e);
+                    continue;
+                }
+            };
+
+            let mut channels = self.lock();
+            // Unwatched while the request was out
+            let Some(watched) = channels.iter_mut().find(|watched| watched.channel_id == channel.channel_id) else {
+                continue;
+            };
+            let uploads = record_check(watched, check);
+            if let Err(e) = self.save(&channels) {
+                tracing::warn!("{}", e);
+            }
+            if !uploads.is_empty() {
+                found.push(NewUploads { channel_id: channel.channel_id, uploads });
+            }
+        }
+        Ok(found)
+    }
+
+    fn save(&self, channels: &[WatchedChannel]) -> Result<(), String> {
+        let path = self.path.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create watched channels directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(channels)
+            .map_err(|e| format!("Failed to serialize watched channels: {}", e))?;
+        write_atomic(&path, content.as_bytes())
+            .map_err(|e| format!("Failed to write watched channels: {}", e))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<WatchedChannel>> {
+        self.channels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// Moves the channel's ETag and cutoff on and returns the uploads to report. The first check
+// only sets the cutoff, everything it sees was there before the channel was watched
+fn record_check(channel: &mut WatchedChannel, check: UploadsCheck) -> Vec<PlaylistItem> {
+    channel.last_checked_at = Some(chrono::Utc::now().to_rfc3339());
+    let UploadsCheck::Changed { etag, uploads } = check else {
+        return Vec::new();
+    };
+
+    let first_check = channel.last_published_at.is_none();
+    channel.etag = etag;
+    let newest = uploads.iter()
+        .filter_map(|upload| upload.published_at.as_deref())
+        .max();
+    if let Some(newest) = newest {
+        channel.last_published_at = Some(newest.to_string());
+    } else if first_check {
+        // A channel with no uploads yet; anything from now on is new
+        channel.last_published_at = Some(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+    }
+
+    if first_check {
+        Vec::new()
+    } else {
+        uploads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload(video_id: &str, published_at: &str) -> PlaylistItem {
+        PlaylistItem {
+            video_id: video_id.to_string(),
+            title: video_id.to_string(),
+            position: 0,
+            published_at: Some(published_at.to_string()),
+            channel: None,
+        }
+    }
+
+    #[test]
+    fn test_first_check_sets_the_cutoff_and_later_ones_report_uploads() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("watched_channels.json");
+        let watcher = ChannelWatcher::new(path.clone());
+        let mut channel = watcher.watch("UCabc", "UUabc".to_string()).unwrap();
+        assert_eq!(watcher.watch("UCabc", "UUabc".to_string()).unwrap().added_at, channel.added_at);
+
+        let existing = record_check(&mut channel, UploadsCheck::Changed {
+            etag: Some("\"etag-1\"".to_string()),
+            uploads: vec![upload("old", "2025-05-01T10:00:00Z")],
+        });
+        assert!(existing.is_empty());
+        assert_eq!(channel.last_published_at.as_deref(), Some("2025-05-01T10:00:00Z"));
+
+        assert!(record_check(&mut channel, UploadsCheck::Unchanged).is_empty());
+        assert_eq!(channel.etag.as_deref(), Some("\"etag-1\""));
+
+        let new = record_check(&mut channel, UploadsCheck::Changed {
+            etag: Some("\"etag-2\"".to_string()),
+            uploads: vec![upload("newer", "2025-05-03T10:00:00Z"), upload("new", "2025-05-02T10:00:00Z")],
+        });
+        assert_eq!(new.len(), 2);
+        assert_eq!(channel.etag.as_deref(), Some("\"etag-2\""));
+        assert_eq!(channel.last_published_at.as_deref(), Some("2025-05-03T10:00:00Z"));
+
+        assert_eq!(ChannelWatcher::new(path.clone()).list().len(), 1);
+        watcher.unwatch("UCabc").unwrap();
+        assert!(watcher.unwatch("UCabc").is_err());
+        assert!(ChannelWatcher::new(path).list().is_empty());
+    }
+}
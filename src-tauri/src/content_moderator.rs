@@ -0,0 +1,200 @@
+use crate::VideoNugget;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationCategory {
+    Profanity,
+    Violence,
+    MedicalClaim,
+    FinancialClaim,
+    CopyrightMusic,
+    Custom,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ModerationSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModerationFlag {
+    pub category: ModerationCategory,
+    pub severity: ModerationSeverity,
+    pub matched_text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NuggetModeration {
+    pub nugget_id: String,
+    pub flags: Vec<ModerationFlag>,
+    pub safe_to_publish: bool,
+}
+
+const PROFANITY_TERMS: [&str; 10] = [
+    "fuck", "fucking", "shit", "bullshit", "bitch", "asshole", "bastard", "damn", "crap", "dick",
+];
+
+const VIOLENCE_TERMS: [&str; 10] = [
+    "kill", "killed", "murder", "shoot", "shooting", "stab", "gun", "blood", "bomb", "assault",
+];
+
+// Claims are phrases rather than words, "cure" alone is fine but "cures cancer" is not
+const MEDICAL_CLAIM_PATTERNS: [&str; 5] = [
+    r"\bcures?\s+\w+",
+    r"\b(heals?|reverses?|prevents?)\s+(cancer|diabetes|depression|anxiety|covid|disease)",
+    r"\bno\s+side\s+effects\b",
+    r"\b(doctors?|fda)\s+(hate|don'?t want)",
+    r"\blose\s+\d+\s*(pounds|lbs|kg|kilos)\b",
+];
+
+const FINANCIAL_CLAIM_PATTERNS: [&str; 5] = [
+    r"\bguaranteed\s+(returns?|profits?|income)\b",
+    r"\brisk[- ]free\b",
+    r"\b(double|triple)\s+your\s+money\b",
+    r"\bget\s+rich\s+quick\b",
+    r"\b\d+\s*%\s+(returns?|profit|gains?)\s+(a|per|every)\s+(day|week|month)\b",
+];
+
+const COPYRIGHT_MUSIC_PATTERNS: [&str; 4] = [
+    r"\b(song|track|music)\s+(by|from)\s+\w+",
+    r"\bplaying\s+(in\s+the\s+background|on\s+the\s+radio)\b",
+    r"\b(official\s+)?music\s+video\b",
+    r"\b(feat\.|ft\.|featuring)\s+\w+",
+];
+
+pub struct ContentModerator {
+    rules: Vec<(ModerationCategory, ModerationSeverity, Regex)>,
+}
+
+impl ContentModerator {
+    pub fn new(custom_blocklist: Vec<String>) -> Result<Self, String> {
+        let mut rules = Vec::new();
+
+        let word_rule = |terms: &[&str]| -> Result<Regex, String> {
+            Regex::new(&format!(r"(?i)\b({})\b", terms.iter().map(|t| regex::escape(t)).collect::<Vec<_>>().join("|")))
+                .map_err(|e| format!("Failed to compile moderation rule: {}", e))
+        };
+        let pattern_rule = |pattern: &str| -> Result<Regex, String> {
+            Regex::new(&format!("(?i){}", pattern))
+                .map_err(|e| format!("Failed to compile moderation rule: {}", e))
+        };
+
+        rules.push((ModerationCategory::Profanity, ModerationSeverity::Medium, word_rule(&PROFANITY_TERMS)?));
+        rules.push((ModerationCategory::Violence, ModerationSeverity::Medium, word_rule(&VIOLENCE_TERMS)?));
+        for pattern in MEDICAL_CLAIM_PATTERNS {
+            rules.push((ModerationCategory::MedicalClaim, ModerationSeverity::High, pattern_rule(pattern)?));
+        }
+        for pattern in FINANCIAL_CLAIM_PATTERNS {
+            rules.push((ModerationCategory::FinancialClaim, ModerationSeverity::High, pattern_rule(pattern)?));
+        }
+        for pattern in COPYRIGHT_MUSIC_PATTERNS {
+            rules.push((ModerationCategory::CopyrightMusic, ModerationSeverity::Low, pattern_rule(pattern)?));
+        }
+
+        let custom_terms: Vec<&str> = custom_blocklist.iter()
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .collect();
+        if !custom_terms.is_empty() {
+            rules.push((ModerationCategory::Custom, ModerationSeverity::High, word_rule(&custom_terms)?));
+        }
+
+        Ok(Self { rules })
+    }
+
+    // Clips with only low-severity flags stay publishable, they just need a manual look
+    pub fn moderate_nuggets(&self, nuggets: &[VideoNugget]) -> Vec<NuggetModeration> {
+        nuggets.iter()
+            .map(|nugget| {
+                let flags = self.check_text(&format!("{}\n{}", nugget.title, nugget.transcript.as_deref().unwrap_or("")));
+                let safe_to_publish = flags.iter().all(|f| f.severity == ModerationSeverity::Low);
+
+                NuggetModeration {
+                    nugget_id: nugget.id.clone(),
+                    flags,
+                    safe_to_publish,
+                }
+            })
+            .collect()
+    }
+
+    pub fn check_text(&self, text: &str) -> Vec<ModerationFlag> {
+        let mut flags: Vec<ModerationFlag> = Vec::new();
+
+        for (category, severity, rule) in &self.rules {
+            for found in rule.find_iter(text) {
+                let matched_text = found.as_str().to_lowercase();
+                let duplicate = flags.iter()
+                    .any(|f| f.category == *category && f.matched_text == matched_text);
+                if !duplicate {
+                    flags.push(ModerationFlag {
+                        category: *category,
+                        severity: *severity,
+                        matched_text,
+                    });
+                }
+            }
+        }
+
+        flags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nugget(id: &str, transcript: &str) -> VideoNugget {
+        VideoNugget {
+            id: id.to_string(),
+            title: "Clip".to_string(),
+            start_time: 0.0,
+            end_time: 30.0,
+            transcript: Some(transcript.to_string()),
+            tags: vec![],
+            notes: None,
+            youtube_video_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn test_check_text_flags_each_category() {
+        let moderator = ContentModerator::new(vec![]).unwrap();
+
+        let categories = |text: &str| -> Vec<ModerationCategory> {
+            moderator.check_text(text).into_iter().map(|f| f.category).collect()
+        };
+
+        assert_eq!(categories("What the hell, this is Shit"), vec![ModerationCategory::Profanity]);
+        assert_eq!(categories("He tried to stab me"), vec![ModerationCategory::Violence]);
+        assert_eq!(categories("This tea cures arthritis"), vec![ModerationCategory::MedicalClaim]);
+        assert_eq!(categories("Guaranteed returns, totally risk-free"), vec![ModerationCategory::FinancialClaim, ModerationCategory::FinancialClaim]);
+        assert_eq!(categories("That song by Adele was playing in the background"), vec![ModerationCategory::CopyrightMusic, ModerationCategory::CopyrightMusic]);
+        assert!(categories("A calm walkthrough of Rust lifetimes").is_empty());
+    }
+
+    #[test]
+    fn test_words_only_match_on_boundaries() {
+        let moderator = ContentModerator::new(vec![]).unwrap();
+        assert!(moderator.check_text("Click the skill tree, then the scrapbook").is_empty());
+    }
+
+    #[test]
+    fn test_moderate_nuggets_marks_safe_clips() {
+        let moderator = ContentModerator::new(vec!["competitor".to_string()]).unwrap();
+        let results = moderator.moderate_nuggets(&[
+            nugget("clean", "Today we learn about ownership"),
+            nugget("music", "Great track from Daft Punk here"),
+            nugget("brand", "Our Competitor does it worse"),
+        ]);
+
+        assert!(results[0].safe_to_publish && results[0].flags.is_empty());
+        assert!(results[1].safe_to_publish && !results[1].flags.is_empty());
+        assert!(!results[2].safe_to_publish);
+        assert_eq!(results[2].flags[0].category, ModerationCategory::Custom);
+    }
+}
@@ -0,0 +1,93 @@
+use serde::{Serialize, Deserialize};
+use std::sync::Mutex;
+use url::Url;
+
+pub const SCHEME: &str = "video-nugget";
+pub const DEEP_LINK_EVENT: &str = "deep-link-received";
+
+// What a video-nugget://process?url=...&project=... link asks for. Any web page can open one,
+// so a link only puts a request in front of the user, it never starts processing by itself
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeepLinkRequest {
+    pub url: String,
+    pub project_id: Option<String>,
+    pub received_at: String,
+}
+
+pub fn parse(link: &str) -> Result<DeepLinkRequest, String> {
+    let link = Url::parse(link)
+        .map_err(|e| format!("Invalid link: {}", e))?;
+    if link.scheme() != SCHEME {
+        return Err(format!("Not a {} link", SCHEME));
+    }
+    // video-nugget://process has the action as its host; a trailing slash is fine too
+    let action = link.host_str().unwrap_or_default();
+    if action != "process" || !matches!(link.path(), "" | "/") {
+        return Err(format!("Unknown action: {}{}", action, link.path()));
+    }
+
+    let mut url = None;
+    let mut project_id = None;
+    for (key, value) in link.query_pairs() {
+        match key.as_ref() {
+            "url" => url = Some(value.to_string()),
+            "project" if !value.trim().is_empty() => project_id = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    let url = url.ok_or("The link has no url to process")?;
+    let video_url = Url::parse(&url)
+        .map_err(|e| format!("Invalid video URL: {}", e))?;
+    // Anything else, file:// especially, would hand local paths to yt-dlp and FFmpeg
+    if !matches!(video_url.scheme(), "http" | "https") {
+        return Err("Only http and https videos can be opened from a link".to_string());
+    }
+
+    Ok(DeepLinkRequest {
+        url,
+        project_id,
+        received_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+// Requests the UI hasn't picked up yet. A link that launches the app arrives before the UI is
+// listening for DEEP_LINK_EVENT, so the UI takes what's pending when it starts and again on
+// every event
+#[derive(Default)]
+pub struct PendingDeepLinks {
+    requests: Mutex<Vec<DeepLinkRequest>>,
+}
+
+impl PendingDeepLinks {
+    pub fn push(&self, request: DeepLinkRequest) {
+        self.requests.lock().unwrap_or_else(|e| e.into_inner()).push(request);
+    }
+
+    // Oldest first
+    pub fn take(&self) -> Vec<DeepLinkRequest> {
+        std::mem::take(&mut *self.requests.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_process_link() {
+        let request = parse("video-nugget://process?url=https%3A%2F%2Fyoutube.com%2Fwatch%3Fv%3Dabc%26t%3D5&project=p-1").unwrap();
+        assert_eq!(request.url, "https://youtube.com/watch?v=abc&t=5");
+        assert_eq!(request.project_id.as_deref(), Some("p-1"));
+        assert!(parse("video-nugget://process/?url=https://youtu.be/abc").unwrap().project_id.is_none());
+
+        assert!(parse("video-nugget://process").is_err());
+        assert!(parse("video-nugget://delete?url=https://youtu.be/abc").is_err());
+        assert!(parse("video-nugget://process?url=file:///etc/passwd").is_err());
+        assert!(parse("https://example.com/process?url=https://youtu.be/abc").is_err());
+
+        let pending = PendingDeepLinks::default();
+        pending.push(request);
+        assert_eq!(pending.take().len(), 1);
+        assert!(pending.take().is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
+use sysinfo::Disks;
+
+// Used for videos whose duration couldn't be looked up before the batch starts
+pub const ASSUMED_VIDEO_SECONDS: f64 = 600.0;
+
+// 16-bit stereo PCM at 44.1kHz, what FFmpegProcessor::extract_audio writes
+const WAV_BYTES_PER_SECOND: f64 = 176_400.0;
+const EXPORT_BYTES_PER_FORMAT: u64 = 1024 * 1024;
+// Estimates are rough, so ask for some headroom on top
+const SAFETY_MARGIN: f64 = 1.1;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum DiskSpacePolicy {
+    // Stop a batch that won't fit before any video starts; it goes back to pending
+    #[default]
+    Refuse,
+    // Start anyway and record a warning on the job
+    Warn,
+    Skip,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DiskSpaceEstimate {
+    pub output_bytes_required: u64,
+    pub output_bytes_available: Option<u64>,
+    // Downloads and extracted audio live in the temp dir until each video finishes
+    pub temp_bytes_required: u64,
+    pub temp_bytes_available: Option<u64>,
+    pub videos_with_unknown_duration: usize,
+    pub sufficient: bool,
+}
+
+impl DiskSpaceEstimate {
+    pub fn summary(&self) -> String {
+        format!(
+            "Batch needs about {} in the output directory ({} free) and {} of temporary space ({} free)",
+            format_bytes(self.output_bytes_required),
+            self.output_bytes_available.map(format_bytes).unwrap_or_else(|| "unknown".to_string()),
+            format_bytes(self.temp_bytes_required),
+            self.temp_bytes_available.map(format_bytes).unwrap_or_else(|| "unknown".to_string()),
+        )
+    }
+}
+
+pub struct EstimateInput<'a> {
+    pub durations: &'a [Option<f64>],
+    pub quality: &'a str,
+    pub export_formats: usize,
+    pub social_formats: bool,
+    pub transcribe: bool,
+    // Videos in flight at once; each holds its download until it's done
+    pub concurrent_videos: usize,
+}
+
+// Returns (output bytes, temp bytes)
+pub fn estimate_required_bytes(input: &EstimateInput) -> (u64, u64) {
+    let video_rate = download_bytes_per_second(input.quality);
+    let mut output_bytes = 0.0;
+    let mut temp_per_video: Vec<f64> = Vec::with_capacity(input.durations.len());
+
+    for duration in input.durations {
+        let seconds = duration.unwrap_or(ASSUMED_VIDEO_SECONDS);
+        let download = seconds * video_rate;
+
+        if input.social_formats {
+            // Stream-copied clips can add up to the whole video, plus three re-encoded formats
+            output_bytes += download * 4.0;
+        }
+        output_bytes += (input.export_formats as u64 * EXPORT_BYTES_PER_FORMAT) as f64;
+
+        let audio = if input.transcribe { seconds * WAV_BYTES_PER_SECOND } else { 0.0 };
+        temp_per_video.push(download + audio);
+    }
+
+    temp_per_video.sort_by(|a, b| b.total_cmp(a));
+    let temp_bytes: f64 = temp_per_video.iter()
+        .take(input.concurrent_videos.max(1))
+        .sum();
+
+    ((output_bytes * SAFETY_MARGIN) as u64, (temp_bytes * SAFETY_MARGIN) as u64)
+}
+
+pub fn check_disk_space(output_dir: &Path, output_bytes: u64, temp_bytes: u64, videos_with_unknown_duration: usize) -> DiskSpaceEstimate {
+    let disks = Disks::new_with_refreshed_list();
+    let output_disk = disk_for_path(&disks, output_dir);
+    let temp_disk = disk_for_path(&disks, &std::env::temp_dir());
+
+    let available = |disk: Option<usize>| disk.map(|index| disks.list()[index].available_space());
+    let output_bytes_available = available(output_disk);
+    let temp_bytes_available = available(temp_disk);
+
+    // On a shared disk the two requirements add up
+    let sufficient = if output_disk.is_some() && output_disk == temp_disk {
+        output_bytes_available.is_none_or(|free| output_bytes + temp_bytes <= free)
+    } else {
+        output_bytes_available.is_none_or(|free| output_bytes <= free)
+            && temp_bytes_available.is_none_or(|free| temp_bytes <= free)
+    };
+
+    DiskSpaceEstimate {
+        output_bytes_required: output_bytes,
+        output_bytes_available,
+        temp_bytes_required: temp_bytes,
+        temp_bytes_available,
+        videos_with_unknown_duration,
+        sufficient,
+    }
+}
+
+// Free space on the disk holding path, None when it's on no disk sysinfo knows about
+pub fn available_space(path: &Path) -> Option<u64> {
+    let disks = Disks::new_with_refreshed_list();
+    disk_for_path(&disks, path).map(|index| disks.list()[index].available_space())
+}
+
+// Roughly what yt-dlp's mp4 formats come in at for each quality setting
+fn download_bytes_per_second(quality: &str) -> f64 {
+    let megabits = match quality {
+        "worst" => 0.5,
+        "480p" => 1.5,
+        "720p" => 3.0,
+        _ => 6.0,
+    };
+    megabits * 1_000_000.0 / 8.0
+}
+
+// Index of the disk with the longest mount point containing the path
+fn disk_for_path(disks: &Disks, path: &Path) -> Option<usize> {
+    let path = existing_ancestor(path)?;
+
+    disks.list().iter()
+        .enumerate()
+        .filter(|(_, disk)| path.starts_with(disk.mount_point()))
+        .max_by_key(|(_, disk)| disk.mount_point().as_os_str().len())
+        .map(|(index, _)| index)
+}
+
+// The output directory usually doesn't exist yet, its closest existing parent is on the same disk
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().ok()?.join(path)
+    };
+
+    absolute.ancestors()
+        .find(|ancestor| ancestor.exists())
+        .and_then(|ancestor| ancestor.canonicalize().ok())
+}
+
+fn format_bytes(bytes: u64) -> String {
+    let gigabytes = bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+    if gigabytes >= 1.0 {
+        format!("{:.1} GB", gigabytes)
+    } else {
+        format!("{:.0} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_scales_with_duration_and_options() {
+        let durations = [Some(60.0), Some(120.0), None];
+        let input = EstimateInput {
+            durations: &durations,
+            quality: "best",
+            export_formats: 0,
+            social_formats: false,
+            transcribe: false,
+            concurrent_videos: 1,
+        };
+
+        // Without clips nothing large is written to the output directory, and only the largest
+        // download (the assumed 10 minutes) sits in temp at a time
+        let (output, temp) = estimate_required_bytes(&input);
+        assert_eq!(output, 0);
+        assert_eq!(temp, (600.0 * 750_000.0 * SAFETY_MARGIN) as u64);
+
+        let (output, temp_with_audio) = estimate_required_bytes(&EstimateInput {
+            social_formats: true,
+            transcribe: true,
+            concurrent_videos: 2,
+            ..input
+        });
+        assert_eq!(output, ((60.0 + 120.0 + 600.0) * 750_000.0 * 4.0 * SAFETY_MARGIN) as u64);
+        assert!(temp_with_audio > temp);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512 * 1024 * 1024), "512 MB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024 / 2), "1.5 GB");
+    }
+}
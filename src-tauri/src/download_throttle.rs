@@ -0,0 +1,155 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+// Politeness settings for a batch's downloads; the defaults download as fast as
+// concurrent_jobs allows
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct DownloadPacing {
+    // Minimum gap between two downloads from the same host starting
+    pub delay_seconds: f64,
+    // Up to this much extra, picked at random for each gap
+    pub jitter_seconds: f64,
+    // Downloads from one host at once, 0 for no limit
+    pub max_per_host: usize,
+}
+
+impl DownloadPacing {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.delay_seconds >= 0.0 && self.jitter_seconds >= 0.0) {
+            return Err("Download delay and jitter can't be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+struct HostState {
+    slots: Option<Arc<Semaphore>>,
+    next_start: Instant,
+}
+
+pub struct DownloadPermit {
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+// One per batch run; hammering YouTube with back-to-back yt-dlp downloads gets the IP
+// rate-limited halfway through a playlist
+pub struct DownloadThrottle {
+    pacing: DownloadPacing,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+impl DownloadThrottle {
+    pub fn new(pacing: DownloadPacing) -> Self {
+        Self {
+            pacing,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // None when the token is cancelled while waiting; hold the permit for the whole download
+    pub async fn acquire(&self, url: &str, cancel_token: &CancellationToken) -> Option<DownloadPermit> {
+        let host = host_key(url);
+        let slots = self.host_state(&host, |state| state.slots.clone());
+
+        let slot = match slots {
+            Some(slots) => tokio::select! {
+                _ = cancel_token.cancelled() => return None,
+                permit = slots.acquire_owned() => Some(permit.ok()?),
+            },
+            None => None,
+        };
+
+        // Each download reserves its start time right after the previous one's
+        let start = self.host_state(&host, |state| {
+            let start = state.next_start.max(Instant::now());
+            state.next_start = start + self.gap();
+            start
+        });
+
+        tokio::select! {
+            _ = cancel_token.cancelled() => return None,
+            _ = tokio::time::sleep_until(start) => {}
+        }
+
+        Some(DownloadPermit { _slot: slot })
+    }
+
+    fn host_state<T>(&self, host: &str, f: impl FnOnce(&mut HostState) -> T) -> T {
+        let mut hosts = self.hosts.lock().unwrap();
+        let state = hosts.entry(host.to_string()).or_insert_with(|| HostState {
+            slots: (self.pacing.max_per_host > 0).then(|| Arc::new(Semaphore::new(self.pacing.max_per_host))),
+            next_start: Instant::now(),
+        });
+        f(state)
+    }
+
+    fn gap(&self) -> Duration {
+        let jitter = if self.pacing.jitter_seconds > 0.0 {
+            self.pacing.jitter_seconds * random_fraction()
+        } else {
+            0.0
+        };
+        Duration::from_secs_f64((self.pacing.delay_seconds + jitter).max(0.0))
+    }
+}
+
+// youtu.be, m.youtube.com and www.youtube.com all end up at the same servers
+fn host_key(url: &str) -> String {
+    let host = url::Url::parse(url).ok()
+        .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+        .unwrap_or_default();
+
+    if host == "youtu.be" || host == "youtube.com" || host.ends_with(".youtube.com") {
+        return "youtube.com".to_string();
+    }
+    host.trim_start_matches("www.").to_string()
+}
+
+// uuid's v4 generator is random enough for jitter, no need for another dependency
+fn random_fraction() -> f64 {
+    (Uuid::new_v4().as_u128() % 1_000_000) as f64 / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_key() {
+        assert_eq!(host_key("https://www.youtube.com/watch?v=dQw4w9WgXcQ"), "youtube.com");
+        assert_eq!(host_key("https://youtu.be/dQw4w9WgXcQ"), "youtube.com");
+        assert_eq!(host_key("https://m.youtube.com/watch?v=dQw4w9WgXcQ"), "youtube.com");
+        assert_eq!(host_key("https://www.Example.com/talk.mp4"), "example.com");
+    }
+
+    #[tokio::test]
+    async fn test_acquire_spaces_downloads_per_host() {
+        let throttle = DownloadThrottle::new(DownloadPacing {
+            delay_seconds: 0.05,
+            jitter_seconds: 0.0,
+            max_per_host: 1,
+        });
+        let token = CancellationToken::new();
+
+        let started = std::time::Instant::now();
+        let first = throttle.acquire("https://youtu.be/a", &token).await;
+        assert!(first.is_some());
+
+        // Another host isn't held up by the first download
+        assert!(throttle.acquire("https://example.com/b.mp4", &token).await.is_some());
+        assert!(started.elapsed() < Duration::from_millis(50));
+
+        let blocked = tokio::time::timeout(Duration::from_millis(100), throttle.acquire("https://www.youtube.com/watch?v=c", &token)).await;
+        assert!(blocked.is_err());
+
+        drop(first);
+        assert!(throttle.acquire("https://www.youtube.com/watch?v=c", &token).await.is_some());
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+}
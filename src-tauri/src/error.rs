@@ -0,0 +1,112 @@
+use serde::{Serialize, Deserialize};
+
+// What the UI tells apart, to show the right remedy or retry on its own
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ErrorCode {
+    // FFmpeg, yt-dlp or Whisper isn't installed or configured
+    ToolMissing,
+    Network,
+    Timeout,
+    QuotaExceeded,
+    RateLimited,
+    // A missing, wrong or revoked API key
+    Unauthorized,
+    NotFound,
+    InvalidInput,
+    // The service answered with something that can't be used
+    InvalidResponse,
+    Cancelled,
+    // An external tool ran and failed
+    ProcessFailed,
+    Unknown,
+}
+
+// The error commands hand to the frontend. Modules with an error type of their own convert it
+// into one of these with a code and, when there's something the user can do, a hint. Plain
+// String errors from modules that don't have one yet come through as Unknown.
+#[derive(Debug, thiserror::Error, Serialize, Deserialize, Clone, PartialEq)]
+#[error("{message}")]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    pub fn cancelled() -> Self {
+        Self::new(ErrorCode::Cancelled, "Cancelled")
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Unknown, message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        Self::new(ErrorCode::Unknown, message)
+    }
+}
+
+// For callers that still pass errors on as Strings
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.message
+    }
+}
+
+pub fn http_error_code(error: &reqwest::Error) -> ErrorCode {
+    if error.is_timeout() {
+        return ErrorCode::Timeout;
+    }
+    match error.status() {
+        Some(status) => status_error_code(status),
+        None if error.is_decode() => ErrorCode::InvalidResponse,
+        None => ErrorCode::Network,
+    }
+}
+
+pub fn status_error_code(status: reqwest::StatusCode) -> ErrorCode {
+    match status.as_u16() {
+        401 | 403 => ErrorCode::Unauthorized,
+        404 => ErrorCode::NotFound,
+        408 | 504 => ErrorCode::Timeout,
+        429 => ErrorCode::RateLimited,
+        400..=499 => ErrorCode::InvalidInput,
+        _ => ErrorCode::Network,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_error_serializes_code_and_hint() {
+        let error = AppError::new(ErrorCode::ToolMissing, "FFmpeg not found").with_hint("Install FFmpeg");
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "ToolMissing");
+        assert_eq!(json["message"], "FFmpeg not found");
+        assert_eq!(json["hint"], "Install FFmpeg");
+
+        let plain: AppError = "Project not found".into();
+        assert_eq!(plain.code, ErrorCode::Unknown);
+        assert_eq!(String::from(plain), "Project not found");
+        assert_eq!(status_error_code(reqwest::StatusCode::TOO_MANY_REQUESTS), ErrorCode::RateLimited);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::atomic_file;
+use crate::project_archive::{is_compressed_media, unique_name};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum BundleFormat {
+    Zip,
+    TarGz,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum BundleLayout {
+    // Every file at the top of the bundle
+    Flat,
+    // The folders below the directory the files have in common
+    KeepFolders,
+    // clips/, thumbnails/, subtitles/, exports/ and other/
+    ByType,
+}
+
+// Packs the files into one zip or tar.gz at bundle_path, laid out as chosen. Files with the
+// same name get a numeric suffix. Returns how many files went in.
+pub fn write_bundle(files: &[PathBuf], bundle_path: &Path, format: BundleFormat, layout: BundleLayout) -> Result<usize, String> {
+    if files.is_empty() {
+        return Err("No files to bundle".to_string());
+    }
+    if let Some(missing) = files.iter().find(|file| !file.is_file()) {
+        return Err(format!("File not found: {}", missing.display()));
+    }
+    let entries = bundle_entries(files, layout);
+
+    // Built next to the destination and moved into place, so a failed bundle never replaces a good one
+    let temp_path = atomic_file::temp_path(bundle_path);
+    let written = match format {
+        BundleFormat::Zip => write_zip(&entries, &temp_path),
+        BundleFormat::TarGz => write_tar_gz(&entries, &temp_path),
+    }
+    .and_then(|file| file.sync_all().map_err(|e| format!("Failed to write bundle: {}", e)))
+    .and_then(|_| atomic_file::persist(&temp_path, bundle_path).map_err(|e| format!("Failed to write bundle: {}", e)));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    written?;
+
+    Ok(entries.len())
+}
+
+// Source files and their names inside the bundle, in the order given
+fn bundle_entries(files: &[PathBuf], layout: BundleLayout) -> Vec<(PathBuf, String)> {
+    let base_dir = common_dir(files);
+    let mut names: HashMap<PathBuf, String> = HashMap::new();
+    let mut entries = Vec::new();
+    for file in files {
+        if names.contains_key(file) {
+            continue;
+        }
+        let file_name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let name = match layout {
+            BundleLayout::Flat => file_name,
+            // Files on different drives have nothing in common to keep
+            BundleLayout::KeepFolders => match file.strip_prefix(&base_dir) {
+                Ok(relative) if !base_dir.as_os_str().is_empty() => relative.to_string_lossy().replace('\\', "/"),
+                _ => file_name,
+            },
+            BundleLayout::ByType => format!("{}/{}", type_folder(file), file_name),
+        };
+        let name = unique_name(&names, name);
+        names.insert(file.clone(), name.clone());
+        entries.push((file.clone(), name));
+    }
+    entries
+}
+
+fn common_dir(files: &[PathBuf]) -> PathBuf {
+    let mut common = files.first()
+        .and_then(|file| file.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    for file in files.iter().skip(1) {
+        while !file.starts_with(&common) {
+            if !common.pop() {
+                return PathBuf::new();
+            }
+        }
+    }
+    common
+}
+
+fn type_folder(file: &Path) -> &'static str {
+    let extension = file.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "mp4" | "mov" | "mkv" | "webm" | "mp3" | "m4a" => "clips",
+        "jpg" | "jpeg" | "png" | "webp" => "thumbnails",
+        "srt" | "vtt" | "ass" | "txt" => "subtitles",
+        "json" | "jsonl" | "ndjson" | "csv" | "md" | "html" | "pdf" | "xml" | "apkg" => "exports",
+        _ => "other",
+    }
+}
+
+fn write_zip(entries: &[(PathBuf, String)], path: &Path) -> Result<File, String> {
+    let file = File::create(path)
+        .map_err(|e| format!("Failed to create bundle: {}", e))?;
+    let mut writer = ZipWriter::new(file);
+    // Video and images are already compressed, deflating them only costs time
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored).large_file(true);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated).large_file(true);
+
+    for (source, name) in entries {
+        let options = if is_compressed_media(source) { stored } else { deflated };
+        writer.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to write bundle: {}", e))?;
+        let mut input = File::open(source)
+            .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        std::io::copy(&mut input, &mut writer)
+            .map_err(|e| format!("Failed to write bundle: {}", e))?;
+    }
+    writer.finish()
+        .map_err(|e| format!("Failed to finish bundle: {}", e))
+}
+
+fn write_tar_gz(entries: &[(PathBuf, String)], path: &Path) -> Result<File, String> {
+    let file = File::create(path)
+        .map_err(|e| format!("Failed to create bundle: {}", e))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for (source, name) in entries {
+        builder.append_path_with_name(source, name)
+            .map_err(|e| format!("Failed to add {} to bundle: {}", source.display(), e))?;
+    }
+    builder.into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|e| format!("Failed to finish bundle: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn export_files(dir: &Path) -> Vec<PathBuf> {
+        let files = vec![
+            dir.join("video_a").join("nugget_001.mp4"),
+            dir.join("video_a").join("nugget_001_thumb.jpg"),
+            dir.join("video_b").join("nugget_001.mp4"),
+            dir.join("nuggets.srt"),
+        ];
+        for file in &files {
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, file.file_name().unwrap().as_encoded_bytes()).unwrap();
+        }
+        files
+    }
+
+    fn names(entries: &[(PathBuf, String)]) -> Vec<&str> {
+        entries.iter().map(|(_, name)| name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_bundle_layouts() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let files = export_files(temp_dir.path());
+
+        assert_eq!(names(&bundle_entries(&files, BundleLayout::Flat)),
+            vec!["nugget_001.mp4", "nugget_001_thumb.jpg", "nugget_001_2.mp4", "nuggets.srt"]);
+        assert_eq!(names(&bundle_entries(&files, BundleLayout::KeepFolders)),
+            vec!["video_a/nugget_001.mp4", "video_a/nugget_001_thumb.jpg", "video_b/nugget_001.mp4", "nuggets.srt"]);
+        assert_eq!(names(&bundle_entries(&files, BundleLayout::ByType)),
+            vec!["clips/nugget_001.mp4", "thumbnails/nugget_001_thumb.jpg", "clips/nugget_001_2.mp4", "subtitles/nuggets.srt"]);
+    }
+
+    #[test]
+    fn test_write_bundle() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let files = export_files(temp_dir.path());
+
+        let zip_path = temp_dir.path().join("export.zip");
+        assert_eq!(write_bundle(&files, &zip_path, BundleFormat::Zip, BundleLayout::ByType).unwrap(), 4);
+        let mut archive = zip::ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        let mut srt = String::new();
+        archive.by_name("subtitles/nuggets.srt").unwrap().read_to_string(&mut srt).unwrap();
+        assert_eq!(srt, "nuggets.srt");
+
+        let tar_path = temp_dir.path().join("export.tar.gz");
+        assert_eq!(write_bundle(&files, &tar_path, BundleFormat::TarGz, BundleLayout::KeepFolders).unwrap(), 4);
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(File::open(&tar_path).unwrap()));
+        let entries: Vec<String> = archive.entries().unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(entries, vec!["video_a/nugget_001.mp4", "video_a/nugget_001_thumb.jpg", "video_b/nugget_001.mp4", "nuggets.srt"]);
+
+        let missing = vec![temp_dir.path().join("missing.csv")];
+        assert!(write_bundle(&missing, &zip_path, BundleFormat::Zip, BundleLayout::Flat).is_err());
+        // The failed bundle left the earlier one alone
+        assert!(zip::ZipArchive::new(File::open(&zip_path).unwrap()).is_ok());
+    }
+}
@@ -0,0 +1,312 @@
+use crate::VideoNugget;
+use crate::ai_analyzer::ContentAnalysis;
+use crate::project_manager::{VideoProject, VideoStatus};
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
+
+// Which nuggets an export writes. Every set field narrows the selection and bounds are inclusive;
+// best then keeps the highest scoring of what's left, ranked by engagement and then confidence.
+// Nuggets keep their order either way.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ExportFilter {
+    // A nugget needs at least one of them
+    pub include_tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+    // Seconds into the video; a nugget has to lie inside the window
+    pub from_time: Option<f64>,
+    pub to_time: Option<f64>,
+    // Both from the video's analysis, so unanalyzed videos never pass them
+    pub min_engagement: Option<f64>,
+    pub min_confidence: Option<f64>,
+    // Nuggets exported without their video have no status and aren't held back by it
+    pub statuses: Vec<VideoStatus>,
+    pub best: Option<usize>,
+}
+
+struct Candidate<'a> {
+    nugget: &'a VideoNugget,
+    analysis: Option<&'a ContentAnalysis>,
+    status: Option<&'a VideoStatus>,
+}
+
+impl ExportFilter {
+    pub fn validate(&self) -> Result<(), String> {
+        if let (Some(from_time), Some(to_time)) = (self.from_time, self.to_time) {
+            if from_time > to_time {
+                return Err("The time window ends before it starts".to_string());
+            }
+        }
+        let scores = [("Engagement", self.min_engagement), ("Confidence", self.min_confidence)];
+        for (name, score) in scores {
+            if score.is_some_and(|score| !(0.0..=1.0).contains(&score)) {
+                return Err(format!("{} must be between 0 and 1", name));
+            }
+        }
+        if self.best == Some(0) {
+            return Err("Best has to keep at least one nugget".to_string());
+        }
+        Ok(())
+    }
+
+    // Each nugget is scored with the analysis and status of whichever of the videos has it;
+    // nuggets in none of them have neither
+    pub fn apply(&self, nuggets: Vec<VideoNugget>, videos: &[VideoProject]) -> Vec<VideoNugget> {
+        let owners: HashMap<&str, &VideoProject> = videos.iter()
+            .flat_map(|video| video.nuggets.iter().map(move |nugget| (nugget.id.as_str(), video)))
+            .collect();
+        let selected = self.select(nuggets.iter()
+            .map(|nugget| {
+                let video = owners.get(nugget.id.as_str());
+                Candidate {
+                    nugget,
+                    analysis: video.and_then(|video| video.analysis.as_ref()),
+                    status: video.map(|video| &video.status),
+                }
+            })
+            .collect());
+        nuggets.into_iter().filter(|nugget| selected.contains(&nugget.id)).collect()
+    }
+
+    // For nuggets of a video that isn't in a project yet, as in a batch
+    pub fn apply_with_analysis(&self, nuggets: Vec<VideoNugget>, analysis: Option<&ContentAnalysis>) -> Vec<VideoNugget> {
+        let selected = self.select(nuggets.iter()
+            .map(|nugget| Candidate { nugget, analysis, status: None })
+            .collect());
+        nuggets.into_iter().filter(|nugget| selected.contains(&nugget.id)).collect()
+    }
+
+    // Ids of the nuggets to export from all the videos, which best ranks together
+    pub fn selected_ids(&self, videos: &[VideoProject]) -> HashSet<String> {
+        self.select(videos.iter()
+            .flat_map(|video| video.nuggets.iter().map(move |nugget| Candidate {
+                nugget,
+                analysis: video.analysis.as_ref(),
+                status: Some(&video.status),
+            }))
+            .collect())
+    }
+
+    // Keeps the selected nuggets in each video; videos left without any are dropped
+    pub fn apply_to_videos(&self, videos: &mut Vec<VideoProject>) {
+        let selected = self.selected_ids(videos);
+        for video in videos.iter_mut() {
+            video.nuggets.retain(|nugget| selected.contains(&nugget.id));
+        }
+        videos.retain(|video| !video.nuggets.is_empty());
+    }
+
+    fn select(&self, candidates: Vec<Candidate>) -> HashSet<String> {
+        let mut passed: Vec<(&VideoNugget, f64, f64)> = candidates.into_iter()
+            .filter(|candidate| self.matches(candidate))
+            .map(|candidate| {
+                let engagement = candidate.analysis.map_or(0.0, |analysis| nugget_engagement(candidate.nugget, analysis));
+                let confidence = candidate.analysis.map_or(0.0, |analysis| nugget_confidence(candidate.nugget, analysis));
+                (candidate.nugget, engagement, confidence)
+            })
+            .collect();
+        if let Some(best) = self.best {
+            passed.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.2.total_cmp(&a.2)));
+            passed.truncate(best);
+        }
+        passed.into_iter().map(|(nugget, _, _)| nugget.id.clone()).collect()
+    }
+
+    fn matches(&self, candidate: &Candidate) -> bool {
+        let nugget = candidate.nugget;
+        let has_tag = |tags: &[String]| tags.iter().any(|tag| nugget.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)));
+        if !self.include_tags.is_empty() && !has_tag(&self.include_tags) {
+            return false;
+        }
+        if has_tag(&self.exclude_tags) {
+            return false;
+        }
+
+        if self.from_time.is_some_and(|from_time| nugget.start_time < from_time)
+            || self.to_time.is_some_and(|to_time| nugget.end_time > to_time) {
+            return false;
+        }
+
+        if !self.statuses.is_empty() && candidate.status.is_some_and(|status| !self.statuses.contains(status)) {
+            return false;
+        }
+
+        if self.min_engagement.is_some() || self.min_confidence.is_some() {
+            let Some(analysis) = candidate.analysis else {
+                return false;
+            };
+            if self.min_engagement.is_some_and(|min| nugget_engagement(nugget, analysis) < min)
+                || self.min_confidence.is_some_and(|min| nugget_confidence(nugget, analysis) < min) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+// Same as nugget_sentiment: the timeline points the nugget overlaps, or the whole video's score
+pub fn nugget_engagement(nugget: &VideoNugget, analysis: &ContentAnalysis) -> f64 {
+    let overlapping: Vec<f64> = analysis.sentiment_timeline.iter()
+        .filter(|point| point.start_time < nugget.end_time && point.end_time > nugget.start_time)
+        .map(|point| point.engagement)
+        .collect();
+    if overlapping.is_empty() {
+        analysis.engagement_score
+    } else {
+        overlapping.iter().sum::<f64>() / overlapping.len() as f64
+    }
+}
+
+// The most confident highlight moment the nugget covers, 0 when it covers none
+pub fn nugget_confidence(nugget: &VideoNugget, analysis: &ContentAnalysis) -> f64 {
+    analysis.highlight_moments.iter()
+        .filter(|moment| moment.start_time < nugget.end_time && moment.end_time > nugget.start_time)
+        .map(|moment| moment.confidence)
+        .fold(0.0, f64::max)
+}
+
+// The selected nuggets along with what goes with each of them by position, like their clips
+pub fn select_paired<T>(nuggets: &[VideoNugget], paired: Vec<T>, selected: &HashSet<String>) -> (Vec<VideoNugget>, Vec<T>) {
+    nuggets.iter().cloned()
+        .zip(paired)
+        .filter(|(nugget, _)| selected.contains(&nugget.id))
+        .unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_analyzer::{HighlightMoment, MomentType, SentimentPoint};
+    use crate::VideoInfo;
+
+    fn nugget(id: &str, start_time: f64, tags: &[&str]) -> VideoNugget {
+        VideoNugget {
+            id: id.to_string(),
+            title: id.to_string(),
+            start_time,
+            end_time: start_time + 10.0,
+            transcript: None,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            notes: None,
+            youtube_video_id: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn analysis() -> ContentAnalysis {
+        let point = |start_time: f64, engagement: f64| SentimentPoint {
+            start_time,
+            end_time: start_time + 10.0,
+            sentiment: 0.0,
+            engagement,
+        };
+        ContentAnalysis {
+            summary: String::new(),
+            key_topics: Vec::new(),
+            sentiment_score: 0.0,
+            engagement_score: 0.5,
+            suggested_tags: Vec::new(),
+            highlight_moments: vec![HighlightMoment {
+                start_time: 12.0,
+                end_time: 15.0,
+                reason: "Punchline".to_string(),
+                confidence: 0.8,
+                moment_type: MomentType::Humor,
+            }],
+            content_categories: Vec::new(),
+            difficulty_level: "Beginner".to_string(),
+            sentiment_timeline: vec![point(0.0, 0.2), point(10.0, 0.9), point(20.0, 0.6)],
+            warnings: Vec::new(),
+            audience_insights: None,
+        }
+    }
+
+    fn video(id: &str, status: VideoStatus, nuggets: Vec<VideoNugget>) -> VideoProject {
+        VideoProject {
+            id: id.to_string(),
+            video_info: VideoInfo {
+                title: id.to_string(),
+                duration: 600.0,
+                url: String::new(),
+                thumbnail: None,
+            },
+            nuggets,
+            analysis: Some(analysis()),
+            seo: None,
+            output_files: Vec::new(),
+            processing_history: Vec::new(),
+            custom_tags: Vec::new(),
+            notes: String::new(),
+            status,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn ids(nuggets: &[VideoNugget]) -> Vec<&str> {
+        nuggets.iter().map(|nugget| nugget.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_tags_and_time_window() {
+        let nuggets = vec![
+            nugget("a", 0.0, &["Intro"]),
+            nugget("b", 10.0, &["demo", "draft"]),
+            nugget("c", 20.0, &["demo"]),
+            nugget("d", 40.0, &["demo"]),
+        ];
+        let filter = ExportFilter {
+            include_tags: vec!["DEMO".to_string()],
+            exclude_tags: vec!["draft".to_string()],
+            to_time: Some(30.0),
+            ..ExportFilter::default()
+        };
+        assert_eq!(ids(&filter.apply(nuggets.clone(), &[])), vec!["c"]);
+        // Without analysis there is nothing to score
+        let min_engagement = ExportFilter { min_engagement: Some(0.1), ..ExportFilter::default() };
+        assert!(min_engagement.apply(nuggets, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_scores_and_best() {
+        let nuggets = vec![nugget("a", 0.0, &[]), nugget("b", 10.0, &[]), nugget("c", 20.0, &[])];
+        let analysis = analysis();
+        assert_eq!(nugget_engagement(&nuggets[1], &analysis), 0.9);
+        assert_eq!(nugget_confidence(&nuggets[1], &analysis), 0.8);
+        assert_eq!(nugget_confidence(&nuggets[0], &analysis), 0.0);
+
+        let videos = vec![video("v1", VideoStatus::Completed, nuggets.clone())];
+        let confident = ExportFilter { min_confidence: Some(0.5), ..ExportFilter::default() };
+        assert_eq!(ids(&confident.apply(nuggets.clone(), &videos)), vec!["b"]);
+        // The best two in their original order
+        let best = ExportFilter { best: Some(2), ..ExportFilter::default() };
+        assert_eq!(ids(&best.apply(nuggets.clone(), &videos)), vec!["b", "c"]);
+        assert_eq!(ids(&best.apply_with_analysis(nuggets.clone(), Some(&analysis))), vec!["b", "c"]);
+
+        let selected = best.selected_ids(&videos);
+        let (kept, clips) = select_paired(&videos[0].nuggets, vec!["a.mp4", "b.mp4", "c.mp4"], &selected);
+        assert_eq!(ids(&kept), vec!["b", "c"]);
+        assert_eq!(clips, vec!["b.mp4", "c.mp4"]);
+    }
+
+    #[test]
+    fn test_apply_to_videos() {
+        let mut videos = vec![
+            video("v1", VideoStatus::Completed, vec![nugget("a", 0.0, &[]), nugget("b", 10.0, &[])]),
+            video("v2", VideoStatus::Failed, vec![nugget("c", 10.0, &[])]),
+            video("v3", VideoStatus::Completed, vec![nugget("d", 20.0, &[])]),
+        ];
+        let filter = ExportFilter {
+            statuses: vec![VideoStatus::Completed],
+            best: Some(2),
+            ..ExportFilter::default()
+        };
+        filter.apply_to_videos(&mut videos);
+        let kept: Vec<(&str, Vec<&str>)> = videos.iter().map(|video| (video.id.as_str(), ids(&video.nuggets))).collect();
+        assert_eq!(kept, vec![("v1", vec!["b"]), ("v3", vec!["d"])]);
+
+        assert!(ExportFilter { from_time: Some(20.0), to_time: Some(10.0), ..ExportFilter::default() }.validate().is_err());
+        assert!(ExportFilter { min_confidence: Some(1.5), ..ExportFilter::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum TimeFormat {
+    // 1234.50
+    #[default]
+    Seconds,
+    // 00:20:34
+    Clock,
+    // 00:20:34.500
+    ClockMillis,
+    // 00:20:34:15, non-drop-frame at fps
+    Timecode,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum DateFormat {
+    // As stored, 2024-05-01T09:30:00+00:00
+    #[default]
+    Rfc3339,
+    // 2024-05-01
+    Date,
+    // 2024-05-01 11:30
+    DateTime,
+    // The date order and clock of `locale`, e.g. 05/01/2024 11:30 AM for en-US
+    Locale,
+}
+
+// How exports write timestamps and dates. Exporters given none keep their own formats; with one,
+// CSV, Markdown, PDF and plain text all use it, and subtitle cues are snapped to frames when
+// it's a timecode. Dates other than RFC 3339 are in local time.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ExportFormatting {
+    pub time_format: TimeFormat,
+    pub date_format: DateFormat,
+    pub fps: f64,
+    // A BCP 47 tag such as "en-US" or "de"
+    pub locale: String,
+}
+
+impl Default for ExportFormatting {
+    fn default() -> Self {
+        Self {
+            time_format: TimeFormat::default(),
+            date_format: DateFormat::default(),
+            fps: 30.0,
+            locale: "en-US".to_string(),
+        }
+    }
+}
+
+impl ExportFormatting {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.fps.is_finite() || self.fps < 1.0 || self.fps > 240.0 {
+            return Err("Frame rate must be between 1 and 240".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn format_time(&self, seconds: f64) -> String {
+        let seconds = seconds.max(0.0);
+        match self.time_format {
+            TimeFormat::Seconds => format!("{:.2}", seconds),
+            TimeFormat::Clock => {
+                let total = seconds.floor() as u64;
+                format!("{:02}:{:02}:{:02}", total / 3600, total % 3600 / 60, total % 60)
+            }
+            TimeFormat::ClockMillis => {
+                let millis = (seconds * 1000.0).round() as u64;
+                let total = millis / 1000;
+                format!("{:02}:{:02}:{:02}.{:03}", total / 3600, total % 3600 / 60, total % 60, millis % 1000)
+            }
+            TimeFormat::Timecode => {
+                // Frames are counted at the nominal rate, so 29.97 counts 30 a second
+                let nominal = self.fps.round().max(1.0) as u64;
+                let frames = (seconds * self.fps).round() as u64;
+                let total = frames / nominal;
+                format!("{:02}:{:02}:{:02}:{:02}", total / 3600, total % 3600 / 60, total % 60, frames % nominal)
+            }
+        }
+    }
+
+    // Anything that isn't RFC 3339 comes back unchanged
+    pub fn format_date(&self, rfc3339: &str) -> String {
+        if self.date_format == DateFormat::Rfc3339 {
+            return rfc3339.to_string();
+        }
+        let Ok(date) = chrono::DateTime::parse_from_rfc3339(rfc3339) else {
+            return rfc3339.to_string();
+        };
+        let local = date.with_timezone(&chrono::Local);
+        let pattern = match self.date_format {
+            DateFormat::Date => "%Y-%m-%d".to_string(),
+            DateFormat::DateTime => "%Y-%m-%d %H:%M".to_string(),
+            _ => locale_pattern(&self.locale),
+        };
+        local.format(&pattern).to_string()
+    }
+
+    // Subtitle cues can't carry a formatted time, but with a timecode they land on frame boundaries
+    pub fn snap_to_frame(&self, seconds: f64) -> f64 {
+        if self.time_format != TimeFormat::Timecode {
+            return seconds;
+        }
+        (seconds * self.fps).round() / self.fps
+    }
+}
+
+// Date order and clock for the common locales; anything unknown gets ISO dates
+fn locale_pattern(locale: &str) -> String {
+    let locale = locale.trim().replace('_', "-").to_lowercase();
+    let language = locale.split('-').next().unwrap_or_default();
+    let pattern = match (locale.as_str(), language) {
+        ("en-us" | "en-ph" | "en", _) => "%m/%d/%Y %I:%M %p",
+        ("en-ca" | "fr-ca", _) => "%Y-%m-%d %H:%M",
+        (_, "en" | "fr" | "es" | "it" | "pt" | "el" | "vi") => "%d/%m/%Y %H:%M",
+        (_, "de" | "ru" | "pl" | "tr" | "fi" | "nb" | "no" | "da" | "cs" | "uk" | "ro") => "%d.%m.%Y %H:%M",
+        (_, "nl") => "%d-%m-%Y %H:%M",
+        (_, "ja" | "zh" | "ko" | "hu") => "%Y/%m/%d %H:%M",
+        _ => "%Y-%m-%d %H:%M",
+    };
+    pattern.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formatting(time_format: TimeFormat, date_format: DateFormat) -> ExportFormatting {
+        ExportFormatting { time_format, date_format, ..ExportFormatting::default() }
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(formatting(TimeFormat::Seconds, DateFormat::Rfc3339).format_time(1234.5), "1234.50");
+        assert_eq!(formatting(TimeFormat::Clock, DateFormat::Rfc3339).format_time(3723.9), "01:02:03");
+        assert_eq!(formatting(TimeFormat::ClockMillis, DateFormat::Rfc3339).format_time(61.2506), "00:01:01.251");
+
+        let mut timecode = formatting(TimeFormat::Timecode, DateFormat::Rfc3339);
+        timecode.fps = 25.0;
+        assert_eq!(timecode.format_time(61.48), "00:01:01:12");
+        assert_eq!(timecode.snap_to_frame(1.013), 1.0);
+        assert!(ExportFormatting { fps: 0.0, ..ExportFormatting::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = "2024-05-01T12:00:00+00:00";
+        let local = chrono::DateTime::parse_from_rfc3339(date).unwrap().with_timezone(&chrono::Local);
+        assert_eq!(formatting(TimeFormat::Seconds, DateFormat::Rfc3339).format_date(date), date);
+        assert_eq!(formatting(TimeFormat::Seconds, DateFormat::Date).format_date(date), local.format("%Y-%m-%d").to_string());
+        assert_eq!(formatting(TimeFormat::Seconds, DateFormat::Date).format_date("yesterday"), "yesterday");
+
+        let mut german = formatting(TimeFormat::Seconds, DateFormat::Locale);
+        german.locale = "de-DE".to_string();
+        assert_eq!(german.format_date(date), local.format("%d.%m.%Y %H:%M").to_string());
+        assert_eq!(locale_pattern("en_US"), "%m/%d/%Y %I:%M %p");
+        assert_eq!(locale_pattern("en-GB"), "%d/%m/%Y %H:%M");
+        assert_eq!(locale_pattern("xx"), "%Y-%m-%d %H:%M");
+    }
+}
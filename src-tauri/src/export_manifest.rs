@@ -0,0 +1,101 @@
+use crate::atomic_file::write_atomic;
+use crate::processed_index::hash_file;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ManifestEntry {
+    // Relative to the manifest's folder when the file is inside it, absolute otherwise
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportManifest {
+    pub created_at: String,
+    pub files: Vec<ManifestEntry>,
+}
+
+// project.zip gets project.manifest.json next to it
+pub fn manifest_path(export_path: &Path) -> PathBuf {
+    export_path.with_extension("manifest.json")
+}
+
+pub fn batch_manifest_path(output_directory: &Path, job_id: &str) -> PathBuf {
+    output_directory.join(format!("batch_manifest_{}.json", job_id))
+}
+
+// Lists each file with its size and SHA-256 so a copy can be checked against it. Files that
+// no longer exist are left out, a manifest only vouches for what's actually there
+pub fn write_manifest(path: &Path, files: &[PathBuf]) -> Result<ExportManifest, String> {
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    for file in files {
+        if !file.is_file() || !seen.insert(file.clone()) {
+            continue;
+        }
+        let size = std::fs::metadata(file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?
+            .len();
+        let entry_path = match file.strip_prefix(base_dir) {
+            Ok(relative) if !base_dir.as_os_str().is_empty() => relative.to_path_buf(),
+            _ => file.clone(),
+        };
+        entries.push(ManifestEntry {
+            // Forward slashes, so tools on any platform can resolve the relative paths
+            path: entry_path.to_string_lossy().replace('\\', "/"),
+            size,
+            sha256: hash_file(file)?,
+        });
+    }
+
+    let manifest = ExportManifest {
+        created_at: chrono::Utc::now().to_rfc3339(),
+        files: entries,
+    };
+    let json_data = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    write_atomic(path, json_data.as_bytes())
+        .map_err(|e| format!("Failed to write manifest: {}", e))?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_manifest() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let clips = temp_dir.path().join("clips");
+        std::fs::create_dir_all(&clips).unwrap();
+        let clip = clips.join("nugget_001.mp4");
+        std::fs::write(&clip, b"hello").unwrap();
+        let outside = tempfile::tempdir().expect("Failed to create temp dir");
+        let transcript = outside.path().join("transcript.srt");
+        std::fs::write(&transcript, b"").unwrap();
+
+        let path = batch_manifest_path(temp_dir.path(), "job-1");
+        let files = vec![clip.clone(), clip, transcript.clone(), temp_dir.path().join("missing.mp4")];
+        let manifest = write_manifest(&path, &files).unwrap();
+
+        assert_eq!(manifest.files, vec![
+            ManifestEntry {
+                path: "clips/nugget_001.mp4".to_string(),
+                size: 5,
+                sha256: "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".to_string(),
+            },
+            ManifestEntry {
+                path: transcript.to_string_lossy().replace('\\', "/"),
+                size: 0,
+                sha256: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+            },
+        ]);
+        let saved: ExportManifest = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.files, manifest.files);
+        assert_eq!(manifest_path(Path::new("/exports/project.zip")), PathBuf::from("/exports/project.manifest.json"));
+    }
+}
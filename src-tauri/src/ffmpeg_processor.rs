@@ -1,8 +1,18 @@
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Output, Stdio};
+use std::sync::Arc;
+use std::time::Instant;
 use tempfile::TempDir;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
 use serde::{Serialize, Deserialize};
 use crate::VideoNugget;
+use crate::app_settings;
+use crate::capabilities::HardwareEncoder;
+use crate::error::{http_error_code, AppError, ErrorCode};
+use crate::logging;
+use crate::youtube_extractor::VideoChapter;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VideoClip {
@@ -14,34 +24,167 @@ pub struct VideoClip {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AudioAnalysis {
+    // Linear RMS level (0.0-1.0) for each VOLUME_WINDOW_SECONDS window, starting at 0s
     pub volume_levels: Vec<f64>,
     pub silence_segments: Vec<(f64, f64)>,
     pub speech_segments: Vec<(f64, f64)>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SourceMetadata {
+    pub title: String,
+    pub channel: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionKind {
+    // Written or uploaded by the creator
+    Uploaded,
+    // YouTube's speech recognition, in the language spoken
+    AutoGenerated,
+    // The auto-generated track machine-translated to another language
+    AutoTranslated,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CaptionTrack {
+    // As the site names it: "en", "pt-BR", "en-orig"
+    pub language: String,
+    pub name: String,
+    pub kind: CaptionKind,
+}
+
+// Which track extract_transcript reads instead of running Whisper
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CaptionSelection {
+    pub language: String,
+    pub kind: CaptionKind,
+}
+
+pub const VOLUME_WINDOW_SECONDS: f64 = 1.0;
+
+// Called with the download percentage (0-100) as yt-dlp reports it
+pub type DownloadProgress = Arc<dyn Fn(f64) + Send + Sync>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FfmpegError {
+    #[error("FFmpeg not found. Please install FFmpeg and ensure it's in your PATH.")]
+    FfmpegMissing,
+    #[error("Cancelled")]
+    Cancelled,
+    // FFmpeg or yt-dlp ran and exited with an error, the message carries its output
+    #[error("{0}")]
+    Failed(String),
+    #[error("Failed to download: {0}")]
+    Download(#[from] reqwest::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<String> for FfmpegError {
+    fn from(message: String) -> Self {
+        FfmpegError::Other(message)
+    }
+}
+
+impl From<&str> for FfmpegError {
+    fn from(message: &str) -> Self {
+        FfmpegError::Other(message.to_string())
+    }
+}
+
+impl From<FfmpegError> for String {
+    fn from(error: FfmpegError) -> Self {
+        error.to_string()
+    }
+}
+
+impl From<FfmpegError> for AppError {
+    fn from(error: FfmpegError) -> Self {
+        let message = error.to_string();
+        match error {
+            FfmpegError::FfmpegMissing => AppError::new(ErrorCode::ToolMissing, message)
+                .with_hint("Install FFmpeg, or set its location in Settings"),
+            FfmpegError::Cancelled => AppError::cancelled(),
+            FfmpegError::Failed(_) => AppError::new(ErrorCode::ProcessFailed, message),
+            FfmpegError::Download(e) => AppError::new(http_error_code(&e), message)
+                .with_hint("Check your internet connection and that the URL is reachable"),
+            FfmpegError::Other(_) => AppError::new(ErrorCode::Unknown, message),
+        }
+    }
+}
+
+// Cheap to clone, clones share the probed FFmpeg path and the temp directory
+#[derive(Clone)]
 pub struct FFmpegProcessor {
-    temp_dir: TempDir,
+    temp_dir: Arc<TempDir>,
     ffmpeg_path: String,
+    cancel_token: Option<CancellationToken>,
+    // Used for re-encoding instead of libx264, see capabilities::hardware_encoders
+    video_encoder: Option<HardwareEncoder>,
 }
 
 impl FFmpegProcessor {
-    pub fn new() -> Result<Self, String> {
+    pub fn new() -> Result<Self, FfmpegError> {
         let temp_dir = TempDir::new()
             .map_err(|e| format!("Failed to create temp directory: {}", e))?;
         
         // Try to find FFmpeg in common locations
         let ffmpeg_path = Self::find_ffmpeg()
-            .ok_or("FFmpeg not found. Please install FFmpeg and ensure it's in your PATH.")?;
+            .ok_or(FfmpegError::FfmpegMissing)?;
 
         Ok(Self {
-            temp_dir,
+            temp_dir: Arc::new(temp_dir),
             ffmpeg_path,
+            cancel_token: None,
+            video_encoder: None,
         })
     }
 
+    // A copy working in a temp directory of its own, removed when the copy is dropped.
+    // Operations that download or extract files take one, so their fixed file names don't
+    // clash and the files don't outlive them
+    pub fn scratch(&self) -> Result<Self, FfmpegError> {
+        let temp_dir = TempDir::new()
+            .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+        Ok(Self {
+            temp_dir: Arc::new(temp_dir),
+            ..self.clone()
+        })
+    }
+
+    // Where this copy writes its files
+    pub fn work_dir(&self) -> &Path {
+        self.temp_dir.path()
+    }
+
+    pub fn ffmpeg_path(&self) -> &str {
+        &self.ffmpeg_path
+    }
+
+    pub fn with_video_encoder(mut self, encoder: Option<HardwareEncoder>) -> Self {
+        self.video_encoder = encoder;
+        self
+    }
+
+    // Cancelling the token kills whichever FFmpeg or yt-dlp process is currently running
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    async fn run(&self, command: &mut Command) -> std::io::Result<Output> {
+        run_cancellable(command, self.cancel_token.as_ref()).await
+    }
+
     fn find_ffmpeg() -> Option<String> {
+        if let Some(path) = app_settings::current().tools.ffmpeg {
+            return Some(path.to_string_lossy().to_string());
+        }
+
         // Check if ffmpeg is in PATH
-        if Command::new("ffmpeg").arg("-version").output().is_ok() {
+        if std::process::Command::new("ffmpeg").arg("-version").output().is_ok() {
             return Some("ffmpeg".to_string());
         }
 
@@ -61,12 +204,17 @@ impl FFmpegProcessor {
         None
     }
 
-    pub async fn download_video(&self, url: &str, quality: &str) -> Result<String, String> {
+    pub async fn download_video(&self, url: &str, quality: &str) -> Result<String, FfmpegError> {
+        self.download_video_with_progress(url, quality, None).await
+    }
+
+    // Direct downloads report no progress, only yt-dlp prints it
+    pub async fn download_video_with_progress(&self, url: &str, quality: &str, on_progress: Option<DownloadProgress>) -> Result<String, FfmpegError> {
         let output_path = self.temp_dir.path().join("downloaded_video.mp4");
         
         // Use yt-dlp if available, otherwise fall back to basic download
-        let success = if let Ok(_) = Command::new("yt-dlp").arg("--version").output() {
-            self.download_with_ytdlp(url, &output_path, quality).await
+        let success = if Command::new(app_settings::yt_dlp()).arg("--version").output().await.is_ok() {
+            self.download_with_ytdlp(url, &output_path, quality, on_progress).await
         } else {
             // Fallback to direct URL download (for non-YouTube URLs)
             self.download_direct(url, &output_path).await
@@ -75,11 +223,106 @@ impl FFmpegProcessor {
         if success? {
             Ok(output_path.to_string_lossy().to_string())
         } else {
-            Err("Failed to download video".to_string())
+            Err(FfmpegError::Failed("Failed to download video".to_string()))
+        }
+    }
+
+    // Title and channel as the site reports them; the downloaded file name carries neither
+    pub async fn get_source_metadata(&self, url: &str) -> Option<SourceMetadata> {
+        let output = self.run(Command::new(app_settings::yt_dlp())
+            .args([
+                "--skip-download",
+                "--print", "title",
+                "--print", "channel",
+                url,
+            ])).await
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines().map(str::trim);
+        let title = lines.next().filter(|t| !t.is_empty())?.to_string();
+        // yt-dlp prints "NA" for fields the extractor doesn't provide
+        let channel = lines.next()
+            .filter(|c| !c.is_empty() && *c != "NA")
+            .map(str::to_string);
+
+        Some(SourceMetadata { title, channel })
+    }
+
+    // The chapters the uploader marked, empty for a video without any
+    pub async fn get_video_chapters(&self, url: &str) -> Result<Vec<VideoChapter>, FfmpegError> {
+        let output = self.run(Command::new(app_settings::yt_dlp())
+            .args([
+                "--skip-download",
+                "--print", "%(chapters)j",
+                url,
+            ])).await
+            .map_err(|e| command_error("Failed to execute yt-dlp", e))?;
+
+        if !output.status.success() {
+            return Err(FfmpegError::Failed(format!("Failed to read chapters: {}", String::from_utf8_lossy(&output.stderr).trim())));
+        }
+        Ok(parse_chapters(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    // Uploaded tracks first, then the auto-generated one and its translations
+    pub async fn list_caption_tracks(&self, url: &str) -> Result<Vec<CaptionTrack>, FfmpegError> {
+        let output = self.run(Command::new(app_settings::yt_dlp())
+            .args([
+                "--skip-download",
+                "--print", "%(.{language,subtitles,automatic_captions})j",
+                url,
+            ])).await
+            .map_err(|e| command_error("Failed to execute yt-dlp", e))?;
+
+        if !output.status.success() {
+            return Err(FfmpegError::Failed(format!("Failed to list captions: {}", String::from_utf8_lossy(&output.stderr).trim())));
+        }
+        Ok(parse_caption_tracks(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    // The track as WebVTT, in the work directory
+    pub async fn download_captions(&self, url: &str, selection: &CaptionSelection) -> Result<PathBuf, FfmpegError> {
+        // yt-dlp reads --sub-langs as regular expressions
+        let language = &selection.language;
+        if language.is_empty() || !language.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(FfmpegError::Failed(format!("Invalid caption language: {}", language)));
         }
+        let write_flag = match selection.kind {
+            CaptionKind::Uploaded => "--write-subs",
+            CaptionKind::AutoGenerated | CaptionKind::AutoTranslated => "--write-auto-subs",
+        };
+        let output_template = self.work_dir().join("captions");
+        let output = self.run(Command::new(app_settings::yt_dlp())
+            .args([
+                "--skip-download",
+                write_flag,
+                "--sub-langs", language,
+                "--sub-format", "vtt",
+                "-o", &output_template.to_string_lossy(),
+                url,
+            ])).await
+            .map_err(|e| command_error("Failed to execute yt-dlp", e))?;
+
+        if !output.status.success() {
+            return Err(FfmpegError::Failed(format!("Failed to download captions: {}", String::from_utf8_lossy(&output.stderr).trim())));
+        }
+        let captions_path = self.work_dir().join(format!("captions.{}.vtt", language));
+        if !captions_path.exists() {
+            return Err(FfmpegError::Failed(format!("The video has no {} captions in {}", match selection.kind {
+                CaptionKind::Uploaded => "uploaded",
+                CaptionKind::AutoGenerated => "auto-generated",
+                CaptionKind::AutoTranslated => "auto-translated",
+            }, language)));
+        }
+        Ok(captions_path)
     }
 
-    async fn download_with_ytdlp(&self, url: &str, output_path: &Path, quality: &str) -> Result<bool, String> {
+    async fn download_with_ytdlp(&self, url: &str, output_path: &Path, quality: &str, on_progress: Option<DownloadProgress>) -> Result<bool, FfmpegError> {
         let format_string = match quality {
             "best" => "best[ext=mp4]",
             "worst" => "worst[ext=mp4]",
@@ -88,24 +331,32 @@ impl FFmpegProcessor {
             _ => "best[ext=mp4]",
         };
 
-        let output = Command::new("yt-dlp")
-            .args(&[
+        let on_line = on_progress.map(|on_progress| -> LineCallback {
+            Box::new(move |line| {
+                if let Some(percent) = parse_download_percent(line) {
+                    on_progress(percent);
+                }
+            })
+        });
+
+        // --newline puts each progress update on its own line instead of rewriting one
+        let output = run_with_stdout_lines(Command::new(app_settings::yt_dlp())
+            .args([
+                "--newline",
                 "-f", format_string,
                 "-o", &output_path.to_string_lossy(),
                 url,
-            ])
-            .output()
-            .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+            ]), self.cancel_token.as_ref(), on_line).await
+            .map_err(|e| command_error("Failed to execute yt-dlp", e))?;
 
         Ok(output.status.success())
     }
 
-    async fn download_direct(&self, url: &str, output_path: &Path) -> Result<bool, String> {
-        let response = reqwest::get(url).await
-            .map_err(|e| format!("Failed to download: {}", e))?;
+    async fn download_direct(&self, url: &str, output_path: &Path) -> Result<bool, FfmpegError> {
+        let response = reqwest::get(url).await?
+            .error_for_status()?;
 
-        let content = response.bytes().await
-            .map_err(|e| format!("Failed to read response: {}", e))?;
+        let content = response.bytes().await?;
 
         tokio::fs::write(output_path, content).await
             .map_err(|e| format!("Failed to write file: {}", e))?;
@@ -113,14 +364,13 @@ impl FFmpegProcessor {
         Ok(true)
     }
 
-    pub fn get_video_info(&self, video_path: &str) -> Result<VideoInfo, String> {
-        let output = Command::new(&self.ffmpeg_path)
-            .args(&[
+    pub async fn get_video_info(&self, video_path: &str) -> Result<VideoInfo, FfmpegError> {
+        let output = self.run(Command::new(&self.ffmpeg_path)
+            .args([
                 "-i", video_path,
                 "-f", "null", "-",
-            ])
-            .output()
-            .map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
+            ])).await
+            .map_err(|e| command_error("Failed to execute ffmpeg", e))?;
 
         let stderr = String::from_utf8_lossy(&output.stderr);
         
@@ -140,7 +390,7 @@ impl FFmpegProcessor {
         })
     }
 
-    fn parse_duration(&self, ffmpeg_output: &str) -> Result<f64, String> {
+    fn parse_duration(&self, ffmpeg_output: &str) -> Result<f64, FfmpegError> {
         use regex::Regex;
         
         let duration_regex = Regex::new(r"Duration: (\d{2}):(\d{2}):(\d{2})\.(\d{2})")
@@ -154,49 +404,62 @@ impl FFmpegProcessor {
 
             Ok(hours * 3600.0 + minutes * 60.0 + seconds + centiseconds / 100.0)
         } else {
-            Err("Could not parse video duration".to_string())
+            Err("Could not parse video duration".into())
         }
     }
 
-    pub fn extract_audio(&self, video_path: &str) -> Result<String, String> {
+    pub async fn extract_audio(&self, video_path: &str) -> Result<String, FfmpegError> {
         let audio_path = self.temp_dir.path().join("audio.wav");
         
-        let output = Command::new(&self.ffmpeg_path)
-            .args(&[
+        let output = self.run(Command::new(&self.ffmpeg_path)
+            .args([
                 "-i", video_path,
                 "-vn", // No video
                 "-acodec", "pcm_s16le",
                 "-ar", "44100",
                 "-ac", "2",
                 &audio_path.to_string_lossy(),
-            ])
-            .output()
-            .map_err(|e| format!("Failed to extract audio: {}", e))?;
+            ])).await
+            .map_err(|e| command_error("Failed to extract audio", e))?;
 
         if output.status.success() {
             Ok(audio_path.to_string_lossy().to_string())
         } else {
-            Err(format!("FFmpeg audio extraction failed: {}", 
-                String::from_utf8_lossy(&output.stderr)))
+            Err(FfmpegError::Failed(format!("FFmpeg audio extraction failed: {}", 
+                String::from_utf8_lossy(&output.stderr))))
         }
     }
 
-    pub fn create_video_clips(&self, video_path: &str, nuggets: &[VideoNugget], output_dir: &str) -> Result<Vec<VideoClip>, String> {
-        std::fs::create_dir_all(output_dir)
-            .map_err(|e| format!("Failed to create output directory: {}", e))?;
+    pub async fn create_video_clips(&self, video_path: &str, nuggets: &[VideoNugget], output_dir: &str) -> Result<Vec<VideoClip>, FfmpegError> {
+        let output_stems: Vec<String> = (1..=nuggets.len())
+            .map(|index| format!("{}/nugget_{:03}", output_dir, index))
+            .collect();
+
+        self.create_video_clips_at(video_path, nuggets, &output_stems).await
+    }
+
+    // Same as create_video_clips, but each clip goes to its own path (without extension)
+    pub async fn create_video_clips_at(&self, video_path: &str, nuggets: &[VideoNugget], output_stems: &[String]) -> Result<Vec<VideoClip>, FfmpegError> {
+        if output_stems.len() != nuggets.len() {
+            return Err("Expected one output path per nugget".into());
+        }
 
         let mut clips = Vec::new();
 
-        for (index, nugget) in nuggets.iter().enumerate() {
-            let output_path = format!("{}/nugget_{:03}.mp4", output_dir, index + 1);
-            let thumbnail_path = format!("{}/nugget_{:03}_thumb.jpg", output_dir, index + 1);
+        for (nugget, stem) in nuggets.iter().zip(output_stems) {
+            let output_path = format!("{}.mp4", stem);
+            let thumbnail_path = format!("{}_thumb.jpg", stem);
+            if let Some(parent) = Path::new(&output_path).parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create output directory: {}", e))?;
+            }
             
             // Create video clip
-            self.extract_clip(video_path, nugget.start_time, nugget.end_time, &output_path)?;
+            self.extract_clip(video_path, nugget.start_time, nugget.end_time, &output_path).await?;
             
             // Create thumbnail
             let thumb_time = nugget.start_time + (nugget.end_time - nugget.start_time) / 2.0;
-            self.create_thumbnail(video_path, thumb_time, &thumbnail_path)?;
+            self.create_thumbnail(video_path, thumb_time, &thumbnail_path).await?;
 
             clips.push(VideoClip {
                 start_time: nugget.start_time,
@@ -209,58 +472,90 @@ impl FFmpegProcessor {
         Ok(clips)
     }
 
-    fn extract_clip(&self, video_path: &str, start_time: f64, end_time: f64, output_path: &str) -> Result<(), String> {
+    async fn extract_clip(&self, video_path: &str, start_time: f64, end_time: f64, output_path: &str) -> Result<(), FfmpegError> {
         let duration = end_time - start_time;
         
-        let output = Command::new(&self.ffmpeg_path)
-            .args(&[
+        let output = self.run(Command::new(&self.ffmpeg_path)
+            .args([
                 "-i", video_path,
                 "-ss", &start_time.to_string(),
                 "-t", &duration.to_string(),
                 "-c", "copy",
                 "-avoid_negative_ts", "make_zero",
                 output_path,
-            ])
-            .output()
-            .map_err(|e| format!("Failed to extract clip: {}", e))?;
+            ])).await
+            .map_err(|e| command_error("Failed to extract clip", e))?;
 
         if output.status.success() {
             Ok(())
         } else {
-            Err(format!("FFmpeg clip extraction failed: {}", 
-                String::from_utf8_lossy(&output.stderr)))
+            Err(FfmpegError::Failed(format!("FFmpeg clip extraction failed: {}", 
+                String::from_utf8_lossy(&output.stderr))))
         }
     }
 
-    fn create_thumbnail(&self, video_path: &str, time: f64, output_path: &str) -> Result<(), String> {
-        let output = Command::new(&self.ffmpeg_path)
-            .args(&[
+    async fn create_thumbnail(&self, video_path: &str, time: f64, output_path: &str) -> Result<(), FfmpegError> {
+        let output = self.run(Command::new(&self.ffmpeg_path)
+            .args([
                 "-i", video_path,
                 "-ss", &time.to_string(),
                 "-vframes", "1",
                 "-q:v", "2",
                 output_path,
-            ])
-            .output()
-            .map_err(|e| format!("Failed to create thumbnail: {}", e))?;
+            ])).await
+            .map_err(|e| command_error("Failed to create thumbnail", e))?;
 
         if output.status.success() {
             Ok(())
         } else {
-            Err(format!("FFmpeg thumbnail creation failed: {}", 
-                String::from_utf8_lossy(&output.stderr)))
+            Err(FfmpegError::Failed(format!("FFmpeg thumbnail creation failed: {}", 
+                String::from_utf8_lossy(&output.stderr))))
         }
     }
 
-    pub fn analyze_audio(&self, audio_path: &str) -> Result<AudioAnalysis, String> {
+    pub async fn render_title_card(&self, video_path: &str, time: f64, text: &str, output_path: &str) -> Result<(), FfmpegError> {
+        // Going through a text file avoids drawtext's quoting rules for arbitrary user text
+        let text_path = self.temp_dir.path().join(format!("title_card_{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&text_path, text)
+            .map_err(|e| format!("Failed to write title card text: {}", e))?;
+
+        let filter = format!(
+            "drawtext=textfile={}:fontcolor=white:fontsize=h/12:box=1:boxcolor=black@0.6:boxborderw=24:x=(w-text_w)/2:y=h-text_h-h/10",
+            escape_filter_path(&text_path.to_string_lossy())
+        );
+
+        let output = self.run(Command::new(&self.ffmpeg_path)
+            .args([
+                "-y",
+                "-ss", &time.to_string(),
+                "-i", video_path,
+                "-vf", &filter,
+                "-vframes", "1",
+                "-q:v", "2",
+                output_path,
+            ])).await
+            .map_err(|e| command_error("Failed to render title card", e));
+
+        let _ = std::fs::remove_file(&text_path);
+        let output = output?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(FfmpegError::Failed(format!("FFmpeg title card rendering failed: {}", 
+                String::from_utf8_lossy(&output.stderr))))
+        }
+    }
+
+    pub async fn analyze_audio(&self, audio_path: &str) -> Result<AudioAnalysis, FfmpegError> {
         // Extract volume levels
-        let volume_levels = self.get_volume_levels(audio_path)?;
+        let volume_levels = self.get_volume_levels(audio_path).await?;
         
         // Detect silence segments
-        let silence_segments = self.detect_silence(audio_path)?;
+        let silence_segments = self.detect_silence(audio_path).await?;
         
         // Infer speech segments (inverse of silence)
-        let speech_segments = self.infer_speech_segments(&silence_segments, self.get_audio_duration(audio_path)?);
+        let speech_segments = self.infer_speech_segments(&silence_segments, self.get_audio_duration(audio_path).await?);
 
         Ok(AudioAnalysis {
             volume_levels,
@@ -269,32 +564,36 @@ impl FFmpegProcessor {
         })
     }
 
-    fn get_volume_levels(&self, audio_path: &str) -> Result<Vec<f64>, String> {
-        let output = Command::new(&self.ffmpeg_path)
-            .args(&[
+    async fn get_volume_levels(&self, audio_path: &str) -> Result<Vec<f64>, FfmpegError> {
+        // extract_audio always writes 44.1kHz, so one frame of 44100 samples is one window
+        let samples_per_window = (44_100.0 * VOLUME_WINDOW_SECONDS) as u32;
+        let output = self.run(Command::new(&self.ffmpeg_path)
+            .args([
                 "-i", audio_path,
-                "-af", "volumedetect",
+                "-af", &format!(
+                    "asetnsamples=n={},astats=metadata=1:reset=1,ametadata=print:key=lavfi.astats.Overall.RMS_level:file=-",
+                    samples_per_window
+                ),
                 "-f", "null", "-",
-            ])
-            .output()
-            .map_err(|e| format!("Failed to analyze volume: {}", e))?;
+            ])).await
+            .map_err(|e| command_error("Failed to analyze volume", e))?;
 
-        // Parse volume information from stderr
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        
-        // This is a simplified implementation - in reality, you'd want more detailed analysis
-        Ok(vec![0.5, 0.7, 0.3, 0.8, 0.6]) // Placeholder data
+        if !output.status.success() {
+            return Err(FfmpegError::Failed(format!("FFmpeg volume analysis failed: {}", 
+                String::from_utf8_lossy(&output.stderr))));
+        }
+
+        Ok(parse_rms_levels(&String::from_utf8_lossy(&output.stdout)))
     }
 
-    fn detect_silence(&self, audio_path: &str) -> Result<Vec<(f64, f64)>, String> {
-        let output = Command::new(&self.ffmpeg_path)
-            .args(&[
+    async fn detect_silence(&self, audio_path: &str) -> Result<Vec<(f64, f64)>, FfmpegError> {
+        let output = self.run(Command::new(&self.ffmpeg_path)
+            .args([
                 "-i", audio_path,
                 "-af", "silencedetect=noise=-50dB:duration=0.5",
                 "-f", "null", "-",
-            ])
-            .output()
-            .map_err(|e| format!("Failed to detect silence: {}", e))?;
+            ])).await
+            .map_err(|e| command_error("Failed to detect silence", e))?;
 
         let stderr = String::from_utf8_lossy(&output.stderr);
         
@@ -332,20 +631,19 @@ impl FFmpegProcessor {
         speech_segments
     }
 
-    fn get_audio_duration(&self, audio_path: &str) -> Result<f64, String> {
-        let output = Command::new(&self.ffmpeg_path)
-            .args(&[
+    async fn get_audio_duration(&self, audio_path: &str) -> Result<f64, FfmpegError> {
+        let output = self.run(Command::new(&self.ffmpeg_path)
+            .args([
                 "-i", audio_path,
                 "-f", "null", "-",
-            ])
-            .output()
-            .map_err(|e| format!("Failed to get audio duration: {}", e))?;
+            ])).await
+            .map_err(|e| command_error("Failed to get audio duration", e))?;
 
         let stderr = String::from_utf8_lossy(&output.stderr);
         self.parse_duration(&stderr)
     }
 
-    pub fn create_social_media_formats(&self, clip_path: &str) -> Result<SocialMediaFormats, String> {
+    pub async fn create_social_media_formats(&self, clip_path: &str) -> Result<SocialMediaFormats, FfmpegError> {
         let base_name = Path::new(clip_path).file_stem().unwrap().to_string_lossy();
         let output_dir = Path::new(clip_path).parent().unwrap();
 
@@ -354,13 +652,13 @@ impl FFmpegProcessor {
         let youtube_short_path = output_dir.join(format!("{}_youtube_short.mp4", base_name));
 
         // TikTok format (9:16, max 60s)
-        self.convert_to_format(clip_path, &tiktok_path.to_string_lossy(), "720", "1280", 60.0)?;
+        self.convert_to_format(clip_path, &tiktok_path.to_string_lossy(), "720", "1280", 60.0).await?;
         
         // Instagram Reel format (9:16, max 90s)
-        self.convert_to_format(clip_path, &instagram_path.to_string_lossy(), "720", "1280", 90.0)?;
+        self.convert_to_format(clip_path, &instagram_path.to_string_lossy(), "720", "1280", 90.0).await?;
         
         // YouTube Short format (9:16, max 60s)
-        self.convert_to_format(clip_path, &youtube_short_path.to_string_lossy(), "1080", "1920", 60.0)?;
+        self.convert_to_format(clip_path, &youtube_short_path.to_string_lossy(), "1080", "1920", 60.0).await?;
 
         Ok(SocialMediaFormats {
             tiktok: tiktok_path.to_string_lossy().to_string(),
@@ -369,29 +667,246 @@ impl FFmpegProcessor {
         })
     }
 
-    fn convert_to_format(&self, input: &str, output: &str, width: &str, height: &str, max_duration: f64) -> Result<(), String> {
-        let output = Command::new(&self.ffmpeg_path)
-            .args(&[
+    async fn convert_to_format(&self, input: &str, output: &str, width: &str, height: &str, max_duration: f64) -> Result<(), FfmpegError> {
+        let video_args = match &self.video_encoder {
+            Some(encoder) => [vec!["-c:v", encoder.name.as_str()], encoder.quality_args()].concat(),
+            None => vec!["-c:v", "libx264", "-preset", "medium", "-crf", "23"],
+        };
+        let output = self.run(Command::new(&self.ffmpeg_path)
+            .args([
                 "-i", input,
                 "-vf", &format!("scale={}:{},setsar=1", width, height),
                 "-t", &max_duration.to_string(),
-                "-c:v", "libx264",
-                "-preset", "medium",
-                "-crf", "23",
+            ])
+            .args(&video_args)
+            .args([
                 "-c:a", "aac",
                 "-b:a", "128k",
                 output,
-            ])
-            .output()
-            .map_err(|e| format!("Failed to convert format: {}", e))?;
+            ])).await
+            .map_err(|e| command_error("Failed to convert format", e))?;
 
         if output.status.success() {
             Ok(())
         } else {
-            Err(format!("FFmpeg format conversion failed: {}", 
-                String::from_utf8_lossy(&output.stderr)))
+            Err(FfmpegError::Failed(format!("FFmpeg format conversion failed: {}", 
+                String::from_utf8_lossy(&output.stderr))))
+        }
+    }
+}
+
+// run_cancellable reports a cancel as Interrupted
+fn command_error(context: &str, error: std::io::Error) -> FfmpegError {
+    match error.kind() {
+        std::io::ErrorKind::Interrupted => FfmpegError::Cancelled,
+        _ => FfmpegError::Other(format!("{}: {}", context, error)),
+    }
+}
+
+pub type LineCallback = Box<dyn FnMut(&str) + Send>;
+
+pub async fn run_cancellable(command: &mut Command, cancel_token: Option<&CancellationToken>) -> std::io::Result<Output> {
+    run_with_stdout_lines(command, cancel_token, None).await
+}
+
+// Same as run_cancellable, but also hands each stdout line to on_line while the process runs.
+// Every external tool goes through here, so this is where the exact command lines are logged
+pub async fn run_with_stdout_lines(command: &mut Command, cancel_token: Option<&CancellationToken>, on_line: Option<LineCallback>) -> std::io::Result<Output> {
+    let command_line = logging::command_line(command.as_std());
+    tracing::info!(command = %command_line, "Running external command");
+    let started = Instant::now();
+
+    let result = spawn_and_wait(command, cancel_token, on_line).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    match &result {
+        Ok(output) if output.status.success() => {
+            tracing::info!(command = %command_line, elapsed_ms, "External command finished");
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let tail: Vec<&str> = stderr.lines().rev().take(20).collect();
+            tracing::warn!(
+                command = %command_line,
+                status = %output.status,
+                elapsed_ms,
+                stderr = %tail.into_iter().rev().collect::<Vec<_>>().join("\n"),
+                "External command failed"
+            );
         }
+        Err(e) => tracing::warn!(command = %command_line, elapsed_ms, error = %e, "External command did not run to completion"),
     }
+    result
+}
+
+async fn spawn_and_wait(command: &mut Command, cancel_token: Option<&CancellationToken>, on_line: Option<LineCallback>) -> std::io::Result<Output> {
+    if cancel_token.is_some_and(|token| token.is_cancelled()) {
+        return Err(std::io::Error::new(std::io::ErrorKind::Interrupted, "cancelled"));
+    }
+
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    // Both pipes are read while waiting, FFmpeg blocks once a pipe buffer fills up
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let run = async {
+        let (status, stdout, stderr) = tokio::try_join!(
+            child.wait(),
+            read_lines(stdout, on_line),
+            read_pipe(stderr),
+        )?;
+        Ok(Output { status, stdout, stderr })
+    };
+    let cancelled = async {
+        match cancel_token {
+            Some(token) => token.cancelled().await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let output = tokio::select! {
+        output = run => Some(output),
+        _ = cancelled => None,
+    };
+    match output {
+        Some(output) => output,
+        None => {
+            let _ = child.kill().await;
+            Err(std::io::Error::new(std::io::ErrorKind::Interrupted, "cancelled"))
+        }
+    }
+}
+
+async fn read_pipe<R: AsyncRead + Unpin>(pipe: Option<R>) -> std::io::Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    if let Some(mut pipe) = pipe {
+        pipe.read_to_end(&mut buffer).await?;
+    }
+    Ok(buffer)
+}
+
+// Without a callback the output is only collected
+async fn read_lines<R: AsyncRead + Unpin>(pipe: Option<R>, on_line: Option<LineCallback>) -> std::io::Result<Vec<u8>> {
+    let Some(mut on_line) = on_line else {
+        return read_pipe(pipe).await;
+    };
+    let mut buffer = Vec::new();
+    if let Some(pipe) = pipe {
+        let mut reader = tokio::io::BufReader::new(pipe);
+        let mut line = Vec::new();
+        while reader.read_until(b'\n', &mut line).await? > 0 {
+            on_line(String::from_utf8_lossy(&line).trim_end());
+            buffer.append(&mut line);
+        }
+    }
+    Ok(buffer)
+}
+
+// "[download]  42.3% of 10.00MiB at 1.00MiB/s ETA 00:05" -> 42.3
+fn parse_download_percent(line: &str) -> Option<f64> {
+    line.strip_prefix("[download]")?
+        .split_whitespace()
+        .next()?
+        .strip_suffix('%')?
+        .parse()
+        .ok()
+}
+
+// yt-dlp's chapters as JSON; "null" or "NA" when the video has none
+fn parse_chapters(stdout: &str) -> Vec<VideoChapter> {
+    #[derive(Deserialize)]
+    struct Chapter {
+        title: Option<String>,
+        start_time: f64,
+        end_time: f64,
+    }
+
+    let chapters: Vec<Chapter> = serde_json::from_str::<Option<Vec<Chapter>>>(stdout.trim())
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    chapters.into_iter()
+        .enumerate()
+        .map(|(index, chapter)| VideoChapter {
+            title: chapter.title
+                .filter(|title| !title.trim().is_empty())
+                .unwrap_or_else(|| format!("Chapter {}", index + 1)),
+            start_time: chapter.start_time,
+            end_time: chapter.end_time,
+        })
+        .collect()
+}
+
+// The automatic captions hold the spoken language, marked -orig or else the video's language,
+// and everything YouTube can translate it to
+fn parse_caption_tracks(stdout: &str) -> Vec<CaptionTrack> {
+    #[derive(Deserialize)]
+    struct CaptionFormat {
+        name: Option<String>,
+    }
+    #[derive(Deserialize)]
+    struct Captions {
+        language: Option<String>,
+        subtitles: Option<std::collections::BTreeMap<String, Vec<CaptionFormat>>>,
+        automatic_captions: Option<std::collections::BTreeMap<String, Vec<CaptionFormat>>>,
+    }
+
+    let Ok(captions) = serde_json::from_str::<Captions>(stdout.trim()) else {
+        return Vec::new();
+    };
+    let track = |language: &str, formats: &[CaptionFormat], kind: CaptionKind| CaptionTrack {
+        language: language.to_string(),
+        name: formats.iter()
+            .find_map(|format| format.name.clone())
+            .unwrap_or_else(|| language.to_string()),
+        kind,
+    };
+
+    let mut tracks: Vec<CaptionTrack> = captions.subtitles.unwrap_or_default().iter()
+        // Live chat replays show up as a subtitle track
+        .filter(|(language, _)| language.as_str() != "live_chat")
+        .map(|(language, formats)| track(language, formats, CaptionKind::Uploaded))
+        .collect();
+
+    let automatic = captions.automatic_captions.unwrap_or_default();
+    let has_original = automatic.keys().any(|language| language.ends_with("-orig"));
+    let spoken = captions.language.unwrap_or_default();
+    let mut translated = Vec::new();
+    for (language, formats) in &automatic {
+        let original = if has_original {
+            language.ends_with("-orig")
+        } else {
+            !spoken.is_empty() && (*language == spoken || language.split('-').next() == Some(spoken.as_str()))
+        };
+        if original {
+            tracks.push(track(language, formats, CaptionKind::AutoGenerated));
+        } else {
+            translated.push(track(language, formats, CaptionKind::AutoTranslated));
+        }
+    }
+    tracks.extend(translated);
+    tracks
+}
+
+// Turns ametadata lines like "lavfi.astats.Overall.RMS_level=-23.5" into linear levels
+fn parse_rms_levels(metadata: &str) -> Vec<f64> {
+    metadata.lines()
+        .filter_map(|line| line.trim().strip_prefix("lavfi.astats.Overall.RMS_level="))
+        .map(|value| match value.parse::<f64>() {
+            Ok(db) if db.is_finite() => 10f64.powf(db / 20.0).clamp(0.0, 1.0),
+            // Digital silence is reported as -inf
+            _ => 0.0,
+        })
+        .collect()
+}
+
+// ':' separates filter options, so it has to be escaped in paths (Windows drive letters, mostly)
+fn escape_filter_path(path: &str) -> String {
+    path.replace('\\', "/")
+        .replace(':', "\\:")
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -402,4 +917,93 @@ pub struct SocialMediaFormats {
 }
 
 // Re-export VideoInfo from the parent module
-use crate::VideoInfo;
\ No newline at end of file
+use crate::VideoInfo;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_cancellable_refuses_when_already_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let result = run_cancellable(Command::new("ffmpeg").arg("-version"), Some(&token)).await;
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::Interrupted);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_cancellable_kills_on_cancel() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            canceller.cancel();
+        });
+
+        let started = Instant::now();
+        let result = run_cancellable(Command::new("sleep").arg("10"), Some(&token)).await;
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::Interrupted);
+        assert!(started.elapsed().as_secs() < 5);
+    }
+
+    #[test]
+    fn test_parse_rms_levels() {
+        let metadata = "frame:0    pts:0       pts_time:0\n\
+                        lavfi.astats.Overall.RMS_level=-20.000000\n\
+                        frame:1    pts:44100   pts_time:1\n\
+                        lavfi.astats.Overall.RMS_level=-inf\n\
+                        frame:2    pts:88200   pts_time:2\n\
+                        lavfi.astats.Overall.RMS_level=0.000000\n";
+
+        let levels = parse_rms_levels(metadata);
+        assert_eq!(levels.len(), 3);
+        assert!((levels[0] - 0.1).abs() < 1e-9);
+        assert_eq!(levels[1], 0.0);
+        assert_eq!(levels[2], 1.0);
+    }
+
+    #[test]
+    fn test_parse_download_percent() {
+        assert_eq!(parse_download_percent("[download]  42.3% of  10.00MiB at  1.00MiB/s ETA 00:05"), Some(42.3));
+        assert_eq!(parse_download_percent("[download] 100% of 10.00MiB in 00:00:05"), Some(100.0));
+        assert_eq!(parse_download_percent("[download] Destination: video.mp4"), None);
+        assert_eq!(parse_download_percent("[youtube] dQw4w9WgXcQ: Downloading webpage"), None);
+    }
+
+    #[test]
+    fn test_parse_chapters() {
+        let chapters = parse_chapters(r#"[{"start_time": 0.0, "end_time": 62.5, "title": "Intro"}, {"start_time": 62.5, "end_time": 300.0, "title": ""}]"#);
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].title, "Intro");
+        assert_eq!(chapters[1].title, "Chapter 2");
+        assert_eq!(chapters[1].end_time, 300.0);
+
+        assert!(parse_chapters("NA\n").is_empty());
+        assert!(parse_chapters("null").is_empty());
+    }
+
+    #[test]
+    fn test_parse_caption_tracks() {
+        let tracks = parse_caption_tracks(r#"{
+            "language": "en",
+            "subtitles": { "de": [{ "ext": "vtt", "name": "German" }], "live_chat": [{ "ext": "json" }] },
+            "automatic_captions": {
+                "en-orig": [{ "ext": "vtt", "name": "English (Original)" }],
+                "en": [{ "ext": "vtt", "name": "English" }],
+                "ja": [{ "ext": "vtt", "name": "Japanese" }]
+            }
+        }"#);
+        assert_eq!(tracks.len(), 4);
+        assert_eq!(tracks[0], CaptionTrack { language: "de".to_string(), name: "German".to_string(), kind: CaptionKind::Uploaded });
+        assert_eq!(tracks[1].language, "en-orig");
+        assert_eq!(tracks[1].kind, CaptionKind::AutoGenerated);
+        assert!(tracks[2..].iter().all(|track| track.kind == CaptionKind::AutoTranslated));
+
+        let older = parse_caption_tracks(r#"{"language": "en", "subtitles": {}, "automatic_captions": {"en": [{}], "fr": [{}]}}"#);
+        assert_eq!(older[0].kind, CaptionKind::AutoGenerated);
+        assert_eq!(older[1].kind, CaptionKind::AutoTranslated);
+        assert!(parse_caption_tracks("NA").is_empty());
+    }
+}
@@ -1,22 +1,84 @@
 use crate::VideoNugget;
-use std::path::Path;
+use crate::ai_analyzer::{Flashcard, SeoMetadata};
+use crate::anki_export;
+use crate::app_settings;
+use crate::atomic_file::{self, write_atomic_async};
+use crate::export_format::ExportFormatting;
+use crate::html_export::{self, HtmlVideo};
+use crate::obsidian_export;
+use crate::static_site_export::{self, SiteGenerator};
+use crate::pdf_export::{self, PdfReport};
+use crate::project_manager::{EventType, ProcessingEvent, Project};
+use crate::speech_recognition::SpeechRecognizer;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 use serde_json;
 
 pub struct FileManager {
-    // Add any state needed for file management
+    // Timestamps and dates in exports; without it each format keeps its own
+    formatting: Option<ExportFormatting>,
+}
+
+impl Default for FileManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl FileManager {
+    // Starts from the app's default export formatting, if one is set
     pub fn new() -> Self {
-        Self {}
+        Self { formatting: app_settings::current().defaults.export_formatting }
+    }
+
+    // None keeps the default
+    pub fn with_formatting(mut self, formatting: Option<ExportFormatting>) -> Result<Self, String> {
+        if let Some(formatting) = formatting {
+            formatting.validate()?;
+            self.formatting = Some(formatting);
+        }
+        Ok(self)
+    }
+
+    fn format_time(&self, seconds: f64, default: impl FnOnce(f64) -> String) -> String {
+        match &self.formatting {
+            Some(formatting) => formatting.format_time(seconds),
+            None => default(seconds),
+        }
+    }
+
+    fn format_date(&self, date: &str) -> String {
+        match &self.formatting {
+            Some(formatting) => formatting.format_date(date),
+            None => date.to_string(),
+        }
+    }
+
+    // For the processing history of the video (or project) the nuggets came from
+    pub fn export_event(format: &str, filepath: &str, nugget_count: usize) -> ProcessingEvent {
+        let mut parameters = HashMap::new();
+        parameters.insert("format".to_string(), serde_json::json!(format));
+        parameters.insert("path".to_string(), serde_json::json!(filepath));
+        parameters.insert("nugget_count".to_string(), serde_json::json!(nugget_count));
+
+        ProcessingEvent::new(
+            EventType::ExportCreated,
+            format!("Exported {} nuggets as {}", nugget_count, format),
+            parameters,
+        )
+    }
+
+    pub fn nuggets_json(nuggets: &[VideoNugget]) -> Result<String, String> {
+        serde_json::to_string_pretty(nuggets)
+            .map_err(|e| format!("Failed to serialize nuggets: {}", e))
     }
 
     pub async fn save_nuggets(&self, nuggets: Vec<VideoNugget>, filepath: &str) -> Result<String, String> {
-        let json_data = serde_json::to_string_pretty(&nuggets)
-            .map_err(|e| format!("Failed to serialize nuggets: {}", e))?;
+        let json_data = Self::nuggets_json(&nuggets)?;
 
-        fs::write(filepath, json_data)
+        write_atomic_async(filepath, json_data)
             .await
             .map_err(|e| format!("Failed to write file: {}", e))?;
 
@@ -42,38 +104,209 @@ impl FileManager {
         self.save_nuggets(nuggets, filepath).await
     }
 
-    pub async fn export_as_csv(&self, nuggets: Vec<VideoNugget>, filepath: &str) -> Result<String, String> {
-        let mut csv_content = String::from("ID,Title,Start Time,End Time,Tags,Created At,Transcript\n");
-        
+    // One nugget per line (JSON Lines / NDJSON), written as it goes, so tools like jq can stream
+    // it. A JSON Schema for the lines goes next to it as <name>.schema.json
+    pub async fn export_as_jsonl(&self, nuggets: Vec<VideoNugget>, filepath: &str) -> Result<String, String> {
+        // Streamed into a temp file rather than built in memory, then moved into place
+        let temp_path = atomic_file::temp_path(Path::new(filepath));
+        let result = match Self::write_jsonl(&nuggets, &temp_path).await {
+            Ok(()) => atomic_file::persist(&temp_path, Path::new(filepath))
+                .map_err(|e| format!("Failed to write JSONL file: {}", e)),
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path).await;
+        }
+        result?;
+
+        let schema = serde_json::to_string_pretty(&Self::nugget_schema())
+            .map_err(|e| format!("Failed to serialize schema: {}", e))?;
+        write_atomic_async(Self::schema_path(filepath), schema)
+            .await
+            .map_err(|e| format!("Failed to write schema file: {}", e))?;
+
+        Ok(format!("Successfully exported {} nuggets to JSONL: {}", nuggets.len(), filepath))
+    }
+
+    async fn write_jsonl(nuggets: &[VideoNugget], path: &Path) -> Result<(), String> {
+        let file = fs::File::create(path)
+            .await
+            .map_err(|e| format!("Failed to create JSONL file: {}", e))?;
+        let mut writer = tokio::io::BufWriter::new(file);
         for nugget in nuggets {
-            let tags = nugget.tags.join(";");
-            let transcript = nugget.transcript.unwrap_or_else(|| "".to_string());
-            let line = format!(
-                "{},{},{},{},{},{},\"{}\"\n",
-                nugget.id,
-                nugget.title.replace(",", ";"),
-                nugget.start_time,
-                nugget.end_time,
-                tags,
-                nugget.created_at,
-                transcript.replace("\"", "\"\"")
-            );
-            csv_content.push_str(&line);
+            let mut line = serde_json::to_vec(nugget)
+                .map_err(|e| format!("Failed to serialize nugget: {}", e))?;
+            line.push(b'\n');
+            writer.write_all(&line)
+                .await
+                .map_err(|e| format!("Failed to write JSONL file: {}", e))?;
         }
+        writer.flush()
+            .await
+            .map_err(|e| format!("Failed to write JSONL file: {}", e))?;
+        writer.get_ref().sync_all()
+            .await
+            .map_err(|e| format!("Failed to write JSONL file: {}", e))
+    }
+
+    pub fn schema_path(filepath: &str) -> PathBuf {
+        Path::new(filepath).with_extension("schema.json")
+    }
+
+    fn nugget_schema() -> serde_json::Value {
+        serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "VideoNugget",
+            "description": "One line of a video-nugget JSONL export",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "title": { "type": "string" },
+                "start_time": { "type": "number", "description": "Seconds from the start of the video" },
+                "end_time": { "type": "number", "description": "Seconds from the start of the video" },
+                "transcript": { "type": ["string", "null"] },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "notes": { "type": ["string", "null"], "description": "Markdown" },
+                "created_at": { "type": "string", "format": "date-time" }
+            },
+            "required": ["id", "title", "start_time", "end_time", "transcript", "tags", "created_at"]
+        })
+    }
+
+    pub async fn export_as_csv(&self, nuggets: Vec<VideoNugget>, filepath: &str) -> Result<String, String> {
+        self.export_as_csv_with_options(nuggets, &CsvOptions::default(), filepath).await
+    }
+
+    // Fields are quoted when they need to be (RFC 4180), so titles and transcripts keep their
+    // commas, quotes and line breaks
+    pub async fn export_as_csv_with_options(&self, nuggets: Vec<VideoNugget>, options: &CsvOptions, filepath: &str) -> Result<String, String> {
+        let csv_content = self.render_csv(nuggets, options)?;
 
-        fs::write(filepath, csv_content)
+        write_atomic_async(filepath, csv_content)
             .await
             .map_err(|e| format!("Failed to write CSV file: {}", e))?;
 
         Ok(format!("Successfully exported to CSV: {}", filepath))
     }
 
+    pub fn render_csv(&self, nuggets: Vec<VideoNugget>, options: &CsvOptions) -> Result<Vec<u8>, String> {
+        options.validate()?;
+
+        let mut buffer = Vec::new();
+        if options.include_bom {
+            buffer.extend_from_slice("\u{feff}".as_bytes());
+        }
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(options.delimiter as u8)
+            .from_writer(buffer);
+
+        writer.write_record(["ID", "Title", "Start Time", "End Time", "Tags", "Created At", "Transcript"])
+            .map_err(|e| format!("Failed to write CSV: {}", e))?;
+        for nugget in nuggets {
+            writer.write_record([
+                nugget.id,
+                nugget.title,
+                self.format_time(nugget.start_time, |seconds| seconds.to_string()),
+                self.format_time(nugget.end_time, |seconds| seconds.to_string()),
+                nugget.tags.join(";"),
+                self.format_date(&nugget.created_at),
+                nugget.transcript.unwrap_or_default(),
+            ]).map_err(|e| format!("Failed to write CSV: {}", e))?;
+        }
+        writer.into_inner()
+            .map_err(|e| format!("Failed to write CSV: {}", e))
+    }
+
+    // A cue per nugget over its time range with the title as the text, for loading the nuggets as
+    // markers in an editor. Unlike generated subtitles there's no transcript in it
+    pub async fn export_as_markers(&self, nuggets: Vec<VideoNugget>, format: &str, filepath: &str) -> Result<String, String> {
+        let mut nuggets = nuggets;
+        nuggets.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+        let mut content = match format {
+            "srt" => String::new(),
+            "vtt" => String::from("WEBVTT\n\n"),
+            _ => return Err("Unsupported marker format".to_string()),
+        };
+        for (index, nugget) in nuggets.iter().enumerate() {
+            let (start_time, end_time) = match &self.formatting {
+                Some(formatting) => (formatting.snap_to_frame(nugget.start_time), formatting.snap_to_frame(nugget.end_time)),
+                None => (nugget.start_time, nugget.end_time),
+            };
+            // A blank line would end the cue early
+            let title = nugget.title.split_whitespace().collect::<Vec<_>>().join(" ").replace("-->", "->");
+            let (title, with_comma) = match format {
+                "vtt" => (title.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"), false),
+                _ => (title, true),
+            };
+            content.push_str(&format!(
+                "{}\n{} --> {}\n{}\n\n",
+                index + 1,
+                SpeechRecognizer::format_timestamp(start_time.max(0.0), with_comma),
+                SpeechRecognizer::format_timestamp(end_time.max(start_time).max(0.0), with_comma),
+                title
+            ));
+        }
+
+        write_atomic_async(filepath, content)
+            .await
+            .map_err(|e| format!("Failed to write {} file: {}", format.to_uppercase(), e))?;
+
+        Ok(format!("Successfully exported {} nugget markers to {}: {}", nuggets.len(), format.to_uppercase(), filepath))
+    }
+
     pub async fn export_as_markdown(&self, nuggets: Vec<VideoNugget>, filepath: &str) -> Result<String, String> {
+        self.export_as_markdown_with_seo(nuggets, None, filepath).await
+    }
+
+    pub async fn export_as_markdown_with_seo(&self, nuggets: Vec<VideoNugget>, seo: Option<&SeoMetadata>, filepath: &str) -> Result<String, String> {
+        self.export_as_markdown_with_notes(nuggets, seo, None, filepath).await
+    }
+
+    // Notes are markdown already and are written as-is
+    pub async fn export_as_markdown_with_notes(&self, nuggets: Vec<VideoNugget>, seo: Option<&SeoMetadata>, notes: Option<&str>, filepath: &str) -> Result<String, String> {
+        let md_content = self.render_markdown(&nuggets, seo, notes);
+
+        write_atomic_async(filepath, md_content)
+            .await
+            .map_err(|e| format!("Failed to write Markdown file: {}", e))?;
+
+        Ok(format!("Successfully exported to Markdown: {}", filepath))
+    }
+
+    pub fn render_markdown(&self, nuggets: &[VideoNugget], seo: Option<&SeoMetadata>, notes: Option<&str>) -> String {
         let mut md_content = String::from("# Video Nuggets\n\n");
+
+        if let Some(notes) = notes.filter(|n| !n.trim().is_empty()) {
+            md_content.push_str(&format!("## Notes\n\n{}\n\n---\n\n", notes.trim_end()));
+        }
+
+        if let Some(seo) = seo {
+            md_content.push_str("## SEO\n\n");
+            md_content.push_str(&format!("**Keywords:** {}\n\n", seo.keywords.iter()
+                .map(|k| k.keyword.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")));
+
+            let mut platforms: Vec<&String> = seo.hashtags.keys().collect();
+            platforms.sort();
+            for platform in platforms {
+                md_content.push_str(&format!("**Hashtags ({}):** {}\n\n", platform, seo.hashtags[platform].join(" ")));
+            }
+
+            md_content.push_str(&format!("**Description Draft:**\n\n```\n{}\n```\n\n---\n\n", seo.description_draft));
+        }
         
         for (index, nugget) in nuggets.iter().enumerate() {
             md_content.push_str(&format!("## {} - {}\n\n", index + 1, nugget.title));
-            md_content.push_str(&format!("**Time:** {:.2}s - {:.2}s\n\n", nugget.start_time, nugget.end_time));
+            md_content.push_str(&format!(
+                "**Time:** {} - {}\n\n",
+                self.format_time(nugget.start_time, |seconds| format!("{:.2}s", seconds)),
+                self.format_time(nugget.end_time, |seconds| format!("{:.2}s", seconds)),
+            ));
+            if self.formatting.is_some() {
+                md_content.push_str(&format!("**Created:** {}\n\n", self.format_date(&nugget.created_at)));
+            }
             
             if !nugget.tags.is_empty() {
                 md_content.push_str(&format!("**Tags:** {}\n\n", nugget.tags.join(", ")));
@@ -82,15 +315,98 @@ impl FileManager {
             if let Some(transcript) = &nugget.transcript {
                 md_content.push_str(&format!("**Transcript:**\n{}\n\n", transcript));
             }
+
+            if let Some(notes) = &nugget.notes {
+                md_content.push_str(&format!("**Notes:**\n\n{}\n\n", notes.trim_end()));
+            }
             
             md_content.push_str("---\n\n");
         }
+        md_content
+    }
+
+    // For pasting into chats and documents that don't render markdown
+    pub fn render_plain_text(&self, nuggets: &[VideoNugget]) -> String {
+        nuggets.iter()
+            .map(|nugget| {
+                let mut text = format!(
+                    "{} ({} - {})",
+                    nugget.title,
+                    self.format_time(nugget.start_time, anki_export::format_time),
+                    self.format_time(nugget.end_time, anki_export::format_time),
+                );
+                if !nugget.tags.is_empty() {
+                    text.push_str(&format!("\nTags: {}", nugget.tags.join(", ")));
+                }
+                if let Some(transcript) = nugget.transcript.as_deref().filter(|t| !t.trim().is_empty()) {
+                    text.push_str(&format!("\n{}", transcript.trim()));
+                }
+                if let Some(notes) = nugget.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+                    text.push_str(&format!("\nNotes: {}", notes.trim()));
+                }
+                text
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    // An .apkg deck with a card per nugget and per flashcard. With the video's URL each card
+    // links back to where its nugget starts
+    pub async fn export_as_anki(&self, deck_name: &str, source_url: Option<&str>, nuggets: Vec<VideoNugget>, flashcards: Vec<Flashcard>, filepath: &str) -> Result<String, String> {
+        let deck_name = deck_name.to_string();
+        let source_url = source_url.map(str::to_string);
+        let path = PathBuf::from(filepath);
 
-        fs::write(filepath, md_content)
+        // SQLite and zip writes are blocking
+        let cards = tokio::task::spawn_blocking(move || {
+            anki_export::write_apkg(&deck_name, source_url.as_deref(), &nuggets, &flashcards, &path)
+        })
+        .await
+        .map_err(|e| format!("Failed to export Anki deck: {}", e))??;
+
+        Ok(format!("Successfully exported {} cards to Anki deck: {}", cards, filepath))
+    }
+
+    // A formatted report with a cover page and a section per nugget, for people who won't open JSON
+    pub async fn export_as_pdf(&self, mut report: PdfReport, filepath: &str) -> Result<String, String> {
+        if report.formatting.is_none() {
+            report.formatting = self.formatting.clone();
+        }
+        let path = PathBuf::from(filepath);
+        let pages = tokio::task::spawn_blocking(move || pdf_export::write_pdf(&report, &path))
             .await
-            .map_err(|e| format!("Failed to write Markdown file: {}", e))?;
+            .map_err(|e| format!("Failed to export PDF: {}", e))??;
 
-        Ok(format!("Successfully exported to Markdown: {}", filepath))
+        Ok(format!("Successfully exported {} pages to PDF: {}", pages, filepath))
+    }
+
+    pub async fn export_as_html(&self, title: &str, videos: Vec<HtmlVideo>, filepath: &str) -> Result<String, String> {
+        let page_dir = Path::new(filepath).parent().unwrap_or(Path::new(""));
+        let html = html_export::to_html(title, &videos, page_dir);
+
+        write_atomic_async(filepath, html)
+            .await
+            .map_err(|e| format!("Failed to write HTML file: {}", e))?;
+
+        Ok(format!("Successfully exported to HTML: {}", filepath))
+    }
+
+    pub async fn export_as_obsidian(&self, project: Project, directory: &str) -> Result<String, String> {
+        let path = PathBuf::from(directory);
+        let notes = tokio::task::spawn_blocking(move || obsidian_export::write_vault(&project, &path))
+            .await
+            .map_err(|e| format!("Failed to export Obsidian vault: {}", e))??;
+
+        Ok(format!("Successfully exported {} notes to Obsidian vault: {}", notes, directory))
+    }
+
+    pub async fn export_as_static_site(&self, project: Project, generator: SiteGenerator, directory: &str) -> Result<String, String> {
+        let path = PathBuf::from(directory);
+        let pages = tokio::task::spawn_blocking(move || static_site_export::write_site(&project, &path, generator))
+            .await
+            .map_err(|e| format!("Failed to export static site: {}", e))??;
+
+        Ok(format!("Successfully exported {} nugget pages to: {}", pages, directory))
     }
 
     pub async fn create_backup(&self, filepath: &str) -> Result<String, String> {
@@ -100,8 +416,10 @@ impl FileManager {
 
         let backup_filepath = format!("{}.backup.{}", filepath, chrono::Utc::now().timestamp());
         
-        fs::copy(filepath, &backup_filepath)
+        let (from, to) = (PathBuf::from(filepath), PathBuf::from(&backup_filepath));
+        tokio::task::spawn_blocking(move || atomic_file::copy_atomic(&from, &to))
             .await
+            .map_err(|e| format!("Failed to create backup: {}", e))?
             .map_err(|e| format!("Failed to create backup: {}", e))?;
 
         Ok(format!("Backup created: {}", backup_filepath))
@@ -152,6 +470,120 @@ impl FileManager {
     }
 }
 
+// Writes nuggets in one of the export formats by name, as export_nuggets and the CLI take it
+pub async fn write_export(nuggets: Vec<VideoNugget>, format: &str, filepath: &str, seo: Option<&SeoMetadata>, notes: Option<&str>, csv_options: Option<&CsvOptions>, formatting: Option<ExportFormatting>) -> Result<String, String> {
+    let file_manager = FileManager::new().with_formatting(formatting)?;
+    match format {
+        "json" => file_manager.export_as_json(nuggets, filepath).await,
+        "jsonl" | "ndjson" => file_manager.export_as_jsonl(nuggets, filepath).await,
+        "csv" => file_manager.export_as_csv_with_options(nuggets, csv_options.unwrap_or(&CsvOptions::default()), filepath).await,
+        "markdown" => file_manager.export_as_markdown_with_notes(nuggets, seo, notes, filepath).await,
+        "srt" | "vtt" => file_manager.export_as_markers(nuggets, format, filepath).await,
+        "anki" => {
+            let deck_name = Path::new(filepath).file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Video Nuggets".to_string());
+            file_manager.export_as_anki(&deck_name, None, nuggets, Vec::new(), filepath).await
+        }
+        "pdf" => {
+            let title = Path::new(filepath).file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Video Nuggets".to_string());
+            file_manager.export_as_pdf(PdfReport::from_nuggets(&title, nuggets), filepath).await
+        }
+        "html" => {
+            let title = Path::new(filepath).file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Video Nuggets".to_string());
+            let video = HtmlVideo {
+                title: title.clone(),
+                url: String::new(),
+                clips: vec![None; nuggets.len()],
+                nuggets,
+            };
+            file_manager.export_as_html(&title, vec![video], filepath).await
+        }
+        _ => Err("Unsupported export format".to_string()),
+    }
+}
+
+// Shows the file selected in Finder, Explorer or the desktop's file manager. File managers
+// without the FileManager1 D-Bus interface just open the folder it's in
+pub fn reveal_in_file_manager(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    // Not canonicalize, Explorer doesn't take the \\?\ paths it returns on Windows
+    let path = std::path::absolute(path)
+        .map_err(|e| format!("Failed to resolve {}: {}", path.display(), e))?;
+
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg("-R").arg(&path).spawn().map(|_| ());
+
+    #[cfg(windows)]
+    let result = {
+        use std::os::windows::process::CommandExt;
+        // Explorer wants /select and the path as one argument, quoted its own way
+        std::process::Command::new("explorer")
+            .raw_arg(format!("/select,\"{}\"", path.display()))
+            .spawn()
+            .map(|_| ())
+    };
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let result = {
+        let uri = url::Url::from_file_path(&path)
+            .map_err(|_| format!("Failed to resolve {}", path.display()))?;
+        let shown = std::process::Command::new("dbus-send")
+            .args([
+                "--session",
+                "--dest=org.freedesktop.FileManager1",
+                "--type=method_call",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+            ])
+            .arg(format!("array:string:{}", uri))
+            .arg("string:")
+            .status()
+            .is_ok_and(|status| status.success());
+        if shown {
+            Ok(())
+        } else {
+            let folder = if path.is_dir() { path.as_path() } else { path.parent().unwrap_or(&path) };
+            std::process::Command::new("xdg-open").arg(folder).spawn().map(|_| ())
+        }
+    };
+
+    result.map_err(|e| format!("Failed to open file manager: {}", e))
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct CsvOptions {
+    // A single ASCII character; ';' suits locales where the comma is the decimal separator
+    pub delimiter: char,
+    // Excel only reads the file as UTF-8 when it starts with a byte order mark
+    pub include_bom: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            include_bom: false,
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.delimiter.is_ascii() || matches!(self.delimiter, '"' | '\n' | '\r') {
+            return Err("CSV delimiter must be a single ASCII character other than a quote or line break".to_string());
+        }
+        Ok(())
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct ProjectInfo {
     pub filepath: String,
@@ -175,6 +607,8 @@ mod tests {
             end_time: 30.0,
             transcript: Some("Test transcript".to_string()),
             tags: vec!["test".to_string(), "video-nugget".to_string()],
+            notes: None,
+            youtube_video_id: None,
             created_at: chrono::Utc::now().to_rfc3339(),
         }
     }
@@ -183,7 +617,15 @@ mod tests {
     fn test_new_file_manager() {
         let _manager = FileManager::new();
         // Just verify it can be created
-        assert!(true);
+    }
+
+    #[test]
+    fn test_reveal_missing_file_is_refused() {
+        let temp_dir = tempdir().unwrap();
+        let missing = temp_dir.path().join("gone.mp4");
+
+        let error = reveal_in_file_manager(&missing).unwrap_err();
+        assert!(error.starts_with("File not found"));
     }
 
     #[tokio::test]
@@ -241,6 +683,51 @@ mod tests {
         assert!(content.contains("test;video-nugget"));
     }
 
+    #[tokio::test]
+    async fn test_export_as_jsonl() {
+        let manager = FileManager::new();
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let file_path = temp_dir.path().join("nuggets.jsonl");
+        let file_path_str = file_path.to_str().unwrap();
+
+        let nuggets = vec![create_test_nugget("First"), create_test_nugget("Second\nline")];
+        manager.export_as_jsonl(nuggets, file_path_str).await.unwrap();
+
+        let content = fs::read_to_string(file_path_str).await.unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let second: VideoNugget = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second.title, "Second\nline");
+
+        let schema_path = FileManager::schema_path(file_path_str);
+        assert_eq!(schema_path, temp_dir.path().join("nuggets.schema.json"));
+        let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(schema_path).await.unwrap()).unwrap();
+        assert_eq!(schema["title"], "VideoNugget");
+    }
+
+    #[tokio::test]
+    async fn test_export_as_markers() {
+        let manager = FileManager::new();
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let mut late = create_test_nugget("Closing <thoughts>");
+        late.start_time = 3725.5;
+        late.end_time = 3740.0;
+        let nuggets = vec![late, create_test_nugget("Opening\n\nremarks")];
+
+        let srt_path = temp_dir.path().join("markers.srt");
+        manager.export_as_markers(nuggets.clone(), "srt", srt_path.to_str().unwrap()).await.unwrap();
+        let srt = fs::read_to_string(&srt_path).await.unwrap();
+        assert_eq!(srt, "1\n00:00:00,000 --> 00:00:30,000\nOpening remarks\n\n2\n01:02:05,500 --> 01:02:20,000\nClosing <thoughts>\n\n");
+
+        let vtt_path = temp_dir.path().join("markers.vtt");
+        manager.export_as_markers(nuggets.clone(), "vtt", vtt_path.to_str().unwrap()).await.unwrap();
+        let vtt = fs::read_to_string(&vtt_path).await.unwrap();
+        assert!(vtt.starts_with("WEBVTT\n\n1\n00:00:00.000 --> 00:00:30.000\nOpening remarks\n"));
+        assert!(vtt.contains("Closing &lt;thoughts&gt;"));
+
+        assert!(manager.export_as_markers(nuggets, "ass", vtt_path.to_str().unwrap()).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_export_as_markdown() {
         let manager = FileManager::new();
@@ -263,6 +750,49 @@ mod tests {
         assert!(content.contains("**Transcript:**\nTest transcript"));
     }
 
+    #[tokio::test]
+    async fn test_export_as_markdown_with_seo() {
+        let manager = FileManager::new();
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let file_path = temp_dir.path().join("test_export_seo.md");
+        let file_path_str = file_path.to_str().unwrap();
+
+        let seo = SeoMetadata {
+            keywords: vec![crate::ai_analyzer::RankedKeyword { keyword: "rust".to_string(), score: 1.0 }],
+            description_draft: "Learn Rust\n\nChapters:\n0:00 Intro".to_string(),
+            hashtags: std::collections::HashMap::from([
+                ("youtube".to_string(), vec!["#Rust".to_string()]),
+            ]),
+        };
+
+        let nuggets = vec![create_test_nugget("SEO Test Nugget")];
+        let result = manager.export_as_markdown_with_seo(nuggets, Some(&seo), file_path_str).await;
+        assert!(result.is_ok());
+
+        let content = fs::read_to_string(file_path_str).await.unwrap();
+        assert!(content.contains("**Keywords:** rust"));
+        assert!(content.contains("**Hashtags (youtube):** #Rust"));
+        assert!(content.contains("0:00 Intro"));
+        assert!(content.contains("## 1 - SEO Test Nugget"));
+    }
+
+    #[tokio::test]
+    async fn test_export_as_markdown_with_notes() {
+        let manager = FileManager::new();
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let file_path = temp_dir.path().join("test_export_notes.md");
+        let file_path_str = file_path.to_str().unwrap();
+
+        let mut nugget = create_test_nugget("Notes Test Nugget");
+        nugget.notes = Some("- [ ] Use as the **intro**".to_string());
+        let result = manager.export_as_markdown_with_notes(vec![nugget], None, Some("Recorded at *RustConf*\n"), file_path_str).await;
+        assert!(result.is_ok());
+
+        let content = fs::read_to_string(file_path_str).await.unwrap();
+        assert!(content.contains("## Notes\n\nRecorded at *RustConf*\n\n"));
+        assert!(content.contains("**Notes:**\n\n- [ ] Use as the **intro**"));
+    }
+
     #[tokio::test]
     async fn test_export_as_json() {
         let manager = FileManager::new();
@@ -372,7 +902,66 @@ mod tests {
         assert!(result.is_ok());
 
         let content = fs::read_to_string(file_path_str).await.unwrap();
-        assert!(content.contains("Title; with; commas")); // Commas replaced with semicolons
+        assert!(content.contains("\"Title, with, commas\"")); // Commas kept, field quoted
         assert!(content.contains("\"Transcript with \"\"quotes\"\" and, commas\"")); // Quotes escaped
     }
+
+    #[tokio::test]
+    async fn test_csv_export_with_delimiter_and_bom() {
+        let manager = FileManager::new();
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let file_path = temp_dir.path().join("excel.csv");
+        let file_path_str = file_path.to_str().unwrap();
+
+        let mut nugget = create_test_nugget("Price; 1,5 EUR");
+        nugget.transcript = Some("Line one\nline two".to_string());
+        let options = CsvOptions { delimiter: ';', include_bom: true };
+        manager.export_as_csv_with_options(vec![nugget], &options, file_path_str).await.unwrap();
+
+        let content = fs::read_to_string(file_path_str).await.unwrap();
+        assert!(content.starts_with("\u{feff}ID;Title;Start Time;End Time;Tags;Created At;Transcript\n"));
+        assert!(content.contains(";\"Price; 1,5 EUR\";0;30;\"test;video-nugget\";"));
+        assert!(content.contains("\"Line one\nline two\""));
+
+        let invalid = CsvOptions { delimiter: '"', include_bom: false };
+        assert!(manager.export_as_csv_with_options(Vec::new(), &invalid, file_path_str).await.is_err());
+    }
+
+    #[test]
+    fn test_render_plain_text() {
+        let mut first = create_test_nugget("Intro");
+        first.start_time = 65.0;
+        first.end_time = 80.0;
+        first.notes = Some("Check the slides".to_string());
+        let mut second = create_test_nugget("Outro");
+        second.tags = Vec::new();
+        second.transcript = None;
+
+        let text = FileManager::new().render_plain_text(&[first, second]);
+        assert!(text.starts_with("Intro (1:05 - 1:20)\nTags: test, video-nugget\n"));
+        assert!(text.ends_with("\nNotes: Check the slides\n\nOutro (0:00 - 0:30)"));
+    }
+
+    #[test]
+    fn test_formatted_export() {
+        use crate::export_format::{DateFormat, TimeFormat};
+
+        let mut nugget = create_test_nugget("Intro");
+        nugget.start_time = 3725.5;
+        nugget.end_time = 3730.0;
+        nugget.created_at = "not a date".to_string();
+        let formatting = ExportFormatting {
+            time_format: TimeFormat::Clock,
+            date_format: DateFormat::Date,
+            ..ExportFormatting::default()
+        };
+        let manager = FileManager::new().with_formatting(Some(formatting)).unwrap();
+
+        let markdown = manager.render_markdown(std::slice::from_ref(&nugget), None, None);
+        assert!(markdown.contains("**Time:** 01:02:05 - 01:02:10\n\n**Created:** not a date\n"));
+        assert!(manager.render_plain_text(&[nugget]).starts_with("Intro (01:02:05 - 01:02:10)"));
+
+        let bad_fps = ExportFormatting { fps: 0.0, ..ExportFormatting::default() };
+        assert!(FileManager::new().with_formatting(Some(bad_fps)).is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,233 @@
+use crate::VideoNugget;
+use crate::anki_export::{format_time, timestamp_link};
+use crate::batch_report::{escape_html, file_link};
+use std::path::Path;
+
+pub struct HtmlVideo {
+    pub title: String,
+    // Empty when the nuggets aren't tied to a video, the timestamps are then plain text
+    pub url: String,
+    pub nuggets: Vec<VideoNugget>,
+    // Rendered clips in the same order as the nuggets
+    pub clips: Vec<Option<String>>,
+}
+
+// Batch jobs write <stem>.mp4 and <stem>_thumb.jpg for each nugget, in nugget order, next to
+// the social media versions. Like the PDF thumbnails, nothing is matched when the counts differ
+pub fn nugget_clips(output_files: &[String], nugget_count: usize) -> Vec<Option<String>> {
+    let clips: Vec<String> = output_files.iter()
+        .filter_map(|file| file.strip_suffix("_thumb.jpg"))
+        .map(|stem| format!("{}.mp4", stem))
+        .collect();
+    if clips.len() != nugget_count {
+        return vec![None; nugget_count];
+    }
+    clips.into_iter()
+        .map(|clip| Path::new(&clip).exists().then_some(clip))
+        .collect()
+}
+
+// One page with everything inlined, so it can be emailed or opened straight from disk. YouTube
+// videos get an embedded player that the timestamps seek; elsewhere they open the source at
+// that time. Clips are linked relative to page_dir when they're inside it
+pub fn to_html(title: &str, videos: &[HtmlVideo], page_dir: &Path) -> String {
+    let nugget_count: usize = videos.iter().map(|video| video.nuggets.len()).sum();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape_html(title)));
+    html.push_str(PAGE_STYLE);
+    html.push_str("</head>\n<body>\n");
+
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(title)));
+    html.push_str(&format!("<p class=\"meta\">{} videos &middot; {} nuggets</p>\n", videos.len(), nugget_count));
+    html.push_str("<input type=\"search\" id=\"search\" placeholder=\"Search nuggets\" autofocus>\n");
+    html.push_str(&format!("<p class=\"meta\" id=\"count\">{} nuggets</p>\n", nugget_count));
+
+    for (video_index, video) in videos.iter().enumerate() {
+        let youtube = youtube_id(&video.url);
+        html.push_str("<section class=\"video\">\n");
+        html.push_str(&format!("<h2>{}</h2>\n", escape_html(&video.title)));
+        if !video.url.is_empty() {
+            html.push_str(&format!("<p class=\"meta\"><a href=\"{}\" target=\"_blank\" rel=\"noopener\">{}</a></p>\n",
+                escape_html(&video.url), escape_html(&video.url)));
+        }
+        if let Some(id) = &youtube {
+            html.push_str(&format!(
+                "<div class=\"player\"><iframe id=\"player-{}\" src=\"https://www.youtube.com/embed/{}\" title=\"{}\" allow=\"autoplay; encrypted-media; picture-in-picture\" allowfullscreen></iframe></div>\n",
+                video_index, escape_html(id), escape_html(&video.title)));
+        }
+
+        for (nugget_index, nugget) in video.nuggets.iter().enumerate() {
+            let clip = video.clips.get(nugget_index).and_then(|clip| clip.as_deref());
+            write_nugget(&mut html, video_index, video, youtube.as_deref(), nugget, clip, page_dir);
+        }
+        html.push_str("</section>\n");
+    }
+
+    html.push_str(&format!("<footer>Generated {}</footer>\n", chrono::Utc::now().format("%Y-%m-%d %H:%M UTC")));
+    html.push_str(PAGE_SCRIPT);
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn write_nugget(html: &mut String, video_index: usize, video: &HtmlVideo, youtube: Option<&str>, nugget: &VideoNugget, clip: Option<&str>, page_dir: &Path) {
+    // Everything the search box matches on
+    let search = [Some(nugget.title.as_str()), nugget.transcript.as_deref(), nugget.notes.as_deref()]
+        .into_iter()
+        .flatten()
+        .chain(nugget.tags.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    html.push_str(&format!("<article class=\"nugget\" data-search=\"{}\">\n", escape_html(&search)));
+    html.push_str(&format!("<h3>{}</h3>\n", escape_html(&nugget.title)));
+
+    let range = format!("{} - {}", format_time(nugget.start_time), format_time(nugget.end_time));
+    html.push_str("<p class=\"meta\">");
+    match youtube {
+        Some(id) => html.push_str(&format!(
+            "<a class=\"time\" href=\"{}\" target=\"_blank\" rel=\"noopener\" data-player=\"player-{}\" data-video=\"{}\" data-start=\"{}\">{}</a>",
+            escape_html(&timestamp_link(&video.url, nugget.start_time)), video_index, escape_html(id),
+            nugget.start_time.max(0.0).floor() as u64, range)),
+        None if !video.url.is_empty() => html.push_str(&format!(
+            "<a class=\"time\" href=\"{}\" target=\"_blank\" rel=\"noopener\">{}</a>",
+            escape_html(&timestamp_link(&video.url, nugget.start_time)), range)),
+        None => html.push_str(&range),
+    }
+    for tag in &nugget.tags {
+        html.push_str(&format!(" <span class=\"tag\">{}</span>", escape_html(tag)));
+    }
+    html.push_str("</p>\n");
+
+    if let Some(clip) = clip {
+        html.push_str(&format!("<video controls preload=\"none\" src=\"{}\"></video>\n",
+            escape_html(&file_link(clip, page_dir))));
+    }
+    if let Some(transcript) = nugget.transcript.as_deref().filter(|t| !t.trim().is_empty()) {
+        html.push_str(&format!("<details><summary>Transcript</summary><p>{}</p></details>\n", escape_html(transcript)));
+    }
+    if let Some(notes) = nugget.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+        html.push_str(&format!("<p class=\"notes\">{}</p>\n", escape_html(notes)));
+    }
+    html.push_str("</article>\n");
+}
+
+fn youtube_id(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    let id = if host.ends_with("youtu.be") {
+        parsed.path_segments()?.next().map(str::to_string)
+    } else if host.ends_with("youtube.com") {
+        match parsed.path_segments()?.collect::<Vec<_>>().as_slice() {
+            ["shorts" | "embed" | "live", id, ..] => Some(id.to_string()),
+            _ => parsed.query_pairs().find(|(key, _)| key == "v").map(|(_, value)| value.to_string()),
+        }
+    } else {
+        None
+    };
+    id.filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+}
+
+const PAGE_STYLE: &str = "<style>
+body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif; max-width: 960px; margin: 2rem auto; padding: 0 1rem; color: #1f2933; }
+.meta, footer { color: #616e7c; font-size: 0.9rem; }
+#search { width: 100%; padding: 0.6rem; font-size: 1rem; border: 1px solid #cbd2d9; border-radius: 6px; box-sizing: border-box; }
+section.video { border-top: 1px solid #e4e7eb; padding: 1rem 0; }
+.player { position: relative; padding-bottom: 56.25%; height: 0; margin-bottom: 1rem; }
+.player iframe { position: absolute; inset: 0; width: 100%; height: 100%; border: 0; border-radius: 6px; }
+article.nugget { padding: 0.5rem 0 0.75rem; border-bottom: 1px dashed #e4e7eb; }
+article.nugget h3 { margin: 0.25rem 0; font-size: 1.05rem; }
+a.time { font-variant-numeric: tabular-nums; }
+.tag { background: #e4e7eb; border-radius: 4px; padding: 0 0.35rem; margin-left: 0.25rem; font-size: 0.8rem; }
+video { max-width: 100%; max-height: 320px; border-radius: 6px; }
+.notes { white-space: pre-wrap; border-left: 3px solid #cbd2d9; padding-left: 0.75rem; }
+.hidden { display: none; }
+</style>\n";
+
+const PAGE_SCRIPT: &str = "<script>
+const search = document.getElementById('search');
+const count = document.getElementById('count');
+search.addEventListener('input', () => {
+  const terms = search.value.toLowerCase().split(/\\s+/).filter(Boolean);
+  let visible = 0;
+  document.querySelectorAll('section.video').forEach(section => {
+    let shown = 0;
+    section.querySelectorAll('article.nugget').forEach(nugget => {
+      const match = terms.every(term => nugget.dataset.search.includes(term));
+      nugget.classList.toggle('hidden', !match);
+      if (match) shown++;
+    });
+    section.classList.toggle('hidden', shown === 0 && terms.length > 0);
+    visible += shown;
+  });
+  count.textContent = visible + ' nuggets';
+});
+document.querySelectorAll('a.time[data-player]').forEach(link => {
+  link.addEventListener('click', event => {
+    const player = document.getElementById(link.dataset.player);
+    if (!player) return;
+    event.preventDefault();
+    player.src = 'https://www.youtube.com/embed/' + link.dataset.video + '?start=' + link.dataset.start + '&autoplay=1';
+    player.scrollIntoView({ behavior: 'smooth', block: 'center' });
+  });
+});
+</script>\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nugget(title: &str, start_time: f64) -> VideoNugget {
+        VideoNugget {
+            id: title.to_string(),
+            title: title.to_string(),
+            start_time,
+            end_time: start_time + 15.0,
+            transcript: Some("Borrowing <rules>".to_string()),
+            tags: vec!["Rust".to_string()],
+            notes: None,
+            youtube_video_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn test_youtube_id() {
+        assert_eq!(youtube_id("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=10s").as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(youtube_id("https://youtu.be/dQw4w9WgXcQ?t=3").as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(youtube_id("https://www.youtube.com/shorts/abc_123").as_deref(), Some("abc_123"));
+        assert_eq!(youtube_id("https://vimeo.com/12345"), None);
+    }
+
+    #[test]
+    fn test_to_html() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let clip = temp_dir.path().join("clips").join("nugget_001.mp4");
+        let videos = vec![
+            HtmlVideo {
+                title: "Ownership & borrowing".to_string(),
+                url: "https://www.youtube.com/watch?v=abc123".to_string(),
+                nuggets: vec![nugget("Moves", 75.0)],
+                clips: vec![Some(clip.to_string_lossy().to_string())],
+            },
+            HtmlVideo {
+                title: "Meetup".to_string(),
+                url: "https://example.com/meetup.mp4".to_string(),
+                nuggets: vec![nugget("Lifetimes", 5.0)],
+                clips: vec![None],
+            },
+        ];
+
+        let html = to_html("Rust <talks>", &videos, temp_dir.path());
+        assert!(html.contains("<title>Rust &lt;talks&gt;</title>"));
+        assert!(html.contains("<h2>Ownership &amp; borrowing</h2>"));
+        assert!(html.contains("src=\"https://www.youtube.com/embed/abc123\""));
+        assert!(html.contains("data-player=\"player-0\" data-video=\"abc123\" data-start=\"75\">1:15 - 1:30</a>"));
+        assert!(html.contains("href=\"https://example.com/meetup.mp4#t=5\""));
+        assert!(html.contains("<video controls preload=\"none\" src=\"clips/nugget_001.mp4\">"));
+        assert!(html.contains("data-search=\"lifetimes borrowing &lt;rules&gt; rust\""));
+        assert!(!html.contains("player-1"));
+    }
+}
@@ -0,0 +1,214 @@
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum JobPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+// Ordered so the first ticket is the highest priority, oldest request
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Ticket {
+    priority: Reverse<JobPriority>,
+    sequence: u64,
+    job_id: String,
+}
+
+struct SchedulerState {
+    available_slots: usize,
+    next_sequence: u64,
+    waiting: BTreeSet<Ticket>,
+    priorities: HashMap<String, JobPriority>,
+}
+
+// A semaphore shared by every batch job that hands free slots to the highest-priority
+// waiter instead of the first one; videos that are already running are never interrupted
+pub struct JobScheduler {
+    state: Mutex<SchedulerState>,
+    notify: Notify,
+}
+
+pub struct SchedulerPermit {
+    scheduler: Arc<JobScheduler>,
+}
+
+impl Drop for SchedulerPermit {
+    fn drop(&mut self) {
+        self.scheduler.state.lock().unwrap().available_slots += 1;
+        self.scheduler.notify.notify_waiters();
+    }
+}
+
+// Removes the ticket again if the waiting future is dropped, e.g. when the job is cancelled
+struct TicketGuard<'a> {
+    scheduler: &'a JobScheduler,
+    ticket: Option<u64>,
+}
+
+impl Drop for TicketGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(sequence) = self.ticket {
+            self.scheduler.state.lock().unwrap().waiting.retain(|t| t.sequence != sequence);
+            self.scheduler.notify.notify_waiters();
+        }
+    }
+}
+
+impl JobScheduler {
+    pub fn new(slots: usize) -> Self {
+        Self {
+            state: Mutex::new(SchedulerState {
+                available_slots: slots.max(1),
+                next_sequence: 0,
+                waiting: BTreeSet::new(),
+                priorities: HashMap::new(),
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    pub async fn acquire(self: &Arc<Self>, job_id: &str) -> SchedulerPermit {
+        let sequence = {
+            let mut state = self.state.lock().unwrap();
+            let sequence = state.next_sequence;
+            let priority = state.priorities.get(job_id).copied().unwrap_or_default();
+            state.next_sequence += 1;
+            state.waiting.insert(Ticket {
+                priority: Reverse(priority),
+                sequence,
+                job_id: job_id.to_string(),
+            });
+            sequence
+        };
+        let mut guard = TicketGuard { scheduler: self, ticket: Some(sequence) };
+
+        loop {
+            // Register for wakeups before checking, so a release in between isn't missed
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            {
+                let mut state = self.state.lock().unwrap();
+                let is_next = state.waiting.first().is_some_and(|t| t.sequence == sequence);
+                if is_next && state.available_slots > 0 {
+                    state.available_slots -= 1;
+                    state.waiting.pop_first();
+                    guard.ticket = None;
+
+                    // The next waiter in line may have given up while this one held the front
+                    if state.available_slots > 0 && !state.waiting.is_empty() {
+                        self.notify.notify_waiters();
+                    }
+                    return SchedulerPermit { scheduler: self.clone() };
+                }
+            }
+
+            notified.await;
+        }
+    }
+
+    // Also moves the job's already waiting videos, running ones are left alone
+    pub fn set_priority(&self, job_id: &str, priority: JobPriority) {
+        let mut state = self.state.lock().unwrap();
+        state.priorities.insert(job_id.to_string(), priority);
+        let tickets: Vec<Ticket> = state.waiting.iter()
+            .filter(|t| t.job_id == job_id)
+            .cloned()
+            .collect();
+
+        for mut ticket in tickets {
+            state.waiting.remove(&ticket);
+            ticket.priority = Reverse(priority);
+            state.waiting.insert(ticket);
+        }
+        drop(state);
+
+        self.notify.notify_waiters();
+    }
+
+    pub fn remove_job(&self, job_id: &str) {
+        self.state.lock().unwrap().priorities.remove(job_id);
+    }
+
+    pub fn queued_count(&self) -> usize {
+        self.state.lock().unwrap().waiting.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_higher_priority_waiter_gets_the_next_slot() {
+        let scheduler = Arc::new(JobScheduler::new(1));
+        scheduler.set_priority("backfill", JobPriority::Low);
+        scheduler.set_priority("urgent", JobPriority::Urgent);
+        let running = scheduler.acquire("backfill").await;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for job_id in ["backfill", "urgent"] {
+            let scheduler = scheduler.clone();
+            let order = order.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = scheduler.acquire(job_id).await;
+                order.lock().unwrap().push(job_id);
+            }));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(scheduler.queued_count(), 2);
+        drop(running);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(*order.lock().unwrap(), vec!["urgent", "backfill"]);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_waiter_leaves_the_queue() {
+        let scheduler = Arc::new(JobScheduler::new(1));
+        let _running = scheduler.acquire("a").await;
+
+        let waiting = tokio::time::timeout(Duration::from_millis(20), scheduler.acquire("b")).await;
+        assert!(waiting.is_err());
+        assert_eq!(scheduler.queued_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_set_priority_moves_waiting_tickets() {
+        let scheduler = Arc::new(JobScheduler::new(1));
+        let running = scheduler.acquire("a").await;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for job_id in ["first", "second"] {
+            let scheduler = scheduler.clone();
+            let order = order.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = scheduler.acquire(job_id).await;
+                order.lock().unwrap().push(job_id);
+            }));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        scheduler.set_priority("second", JobPriority::High);
+        drop(running);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(*order.lock().unwrap(), vec!["second", "first"]);
+    }
+}
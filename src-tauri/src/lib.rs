@@ -0,0 +1,100 @@
+// The processing core shared by the desktop app and video-nugget-cli. Nothing in here
+// depends on Tauri, the app's commands in main.rs are thin wrappers around it.
+
+use serde::{Deserialize, Serialize};
+use project_manager::ProcessingEvent;
+
+pub mod video_processor;
+pub mod youtube_extractor;
+pub mod youtube_api;
+pub mod file_manager;
+pub mod export_format;
+pub mod export_filter;
+pub mod anki_export;
+pub mod obsidian_export;
+pub mod readwise_export;
+pub mod pdf_export;
+pub mod html_export;
+pub mod static_site_export;
+pub mod nugget_import;
+pub mod nugget_watcher;
+pub mod ffmpeg_processor;
+pub mod speech_recognition;
+pub mod ai_analyzer;
+pub mod ai_config_store;
+pub mod app_settings;
+pub mod atomic_file;
+pub mod notifications;
+pub mod batch_processor;
+pub mod job_scheduler;
+pub mod output_template;
+pub mod processed_index;
+pub mod resource_governor;
+pub mod disk_space;
+pub mod download_throttle;
+pub mod batch_report;
+pub mod export_manifest;
+pub mod export_bundle;
+pub mod pipeline_artifacts;
+pub mod backup_retention;
+pub mod project_manager;
+pub mod project_archive;
+pub mod project_lock;
+pub mod project_crypto;
+pub mod project_versions;
+pub mod project_stats;
+pub mod trash;
+pub mod project_collections;
+pub mod project_activity;
+pub mod project_storage;
+pub mod virality_scorer;
+pub mod content_moderator;
+pub mod operations;
+pub mod logging;
+pub mod error;
+pub mod processors;
+pub mod progress_stream;
+pub mod deep_link;
+pub mod operation_journal;
+pub mod capabilities;
+pub mod workflow;
+pub mod youtube_quota;
+pub mod youtube_cache;
+pub mod channel_watcher;
+pub mod oauth_loopback;
+pub mod youtube_publisher;
+pub mod tiktok_publisher;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoNugget {
+    pub id: String,
+    pub title: String,
+    pub start_time: f64,
+    pub end_time: f64,
+    pub transcript: Option<String>,
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    // Set once the nugget's clip is uploaded, see youtube_publisher
+    #[serde(default)]
+    pub youtube_video_id: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessingResult {
+    pub success: bool,
+    pub message: String,
+    pub nuggets: Vec<VideoNugget>,
+    // Pass these to add_video_to_project so the video's history shows how it was processed
+    #[serde(default)]
+    pub events: Vec<ProcessingEvent>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoInfo {
+    pub title: String,
+    pub duration: f64,
+    pub url: String,
+    pub thumbnail: Option<String>,
+}
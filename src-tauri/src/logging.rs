@@ -0,0 +1,156 @@
+use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, Registry};
+
+const LOG_FILE_PREFIX: &str = "video-nugget";
+const LOG_FILE_SUFFIX: &str = "log";
+// A week of daily files
+const MAX_LOG_FILES: usize = 7;
+pub const MAX_RECENT_LINES: usize = 5000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+struct Logger {
+    dir: PathBuf,
+    level: reload::Handle<LevelFilter, Registry>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+// Logs go to a file per day in dir, of which the last MAX_LOG_FILES are kept, and to stderr.
+// Writes are synchronous so the lines before a crash make it to the file.
+pub fn init(dir: &Path) -> Result<(), String> {
+    if LOGGER.get().is_some() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create log directory {}: {}", dir.display(), e))?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| format!("Failed to open log directory {}: {}", dir.display(), e))?;
+
+    let (level, handle) = reload::Layer::new(LogLevel::default().filter());
+    tracing_subscriber::registry()
+        .with(level)
+        .with(fmt::layer().with_ansi(false).with_writer(appender))
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .try_init()
+        .map_err(|e| format!("Failed to start logging: {}", e))?;
+
+    let _ = LOGGER.set(Logger { dir: dir.to_path_buf(), level: handle });
+    Ok(())
+}
+
+pub fn set_level(level: LogLevel) -> Result<(), String> {
+    let logger = LOGGER.get().ok_or("Logging is not initialized")?;
+    logger.level.modify(|filter| *filter = level.filter())
+        .map_err(|e| format!("Failed to change log level: {}", e))?;
+    tracing::info!("Log level set to {:?}", level);
+    Ok(())
+}
+
+// The newest max_lines lines across the log files, oldest first
+pub fn recent_logs(max_lines: usize) -> Result<Vec<String>, String> {
+    let logger = LOGGER.get().ok_or("Logging is not initialized")?;
+    read_recent_lines(&logger.dir, max_lines.min(MAX_RECENT_LINES))
+}
+
+fn read_recent_lines(dir: &Path, max_lines: usize) -> Result<Vec<String>, String> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read log directory: {}", e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX) && name.ends_with(LOG_FILE_SUFFIX)))
+        .collect();
+    // The date in the name sorts them oldest first
+    files.sort();
+
+    let mut lines: Vec<String> = Vec::new();
+    for file in files.iter().rev() {
+        if lines.len() >= max_lines {
+            break;
+        }
+        let contents = std::fs::read(file)
+            .map_err(|e| format!("Failed to read log file {}: {}", file.display(), e))?;
+        let wanted = max_lines - lines.len();
+        let mut newest: Vec<String> = String::from_utf8_lossy(&contents)
+            .lines()
+            .rev()
+            .take(wanted)
+            .map(|line| line.to_string())
+            .collect();
+        newest.reverse();
+        newest.append(&mut lines);
+        lines = newest;
+    }
+    Ok(lines)
+}
+
+// The command as it could be pasted into a shell, for logging exactly what was run
+pub fn command_line(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|part| {
+            let part = part.to_string_lossy();
+            if part.is_empty() || part.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+                format!("\"{}\"", part.replace('"', "\\\""))
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_recent_lines() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        std::fs::write(temp_dir.path().join("video-nugget.2024-05-01.log"), "one\ntwo\nthree\n").unwrap();
+        std::fs::write(temp_dir.path().join("video-nugget.2024-05-02.log"), "four\nfive\n").unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "not a log\n").unwrap();
+
+        assert_eq!(read_recent_lines(temp_dir.path(), 3).unwrap(), vec!["three", "four", "five"]);
+        assert_eq!(read_recent_lines(temp_dir.path(), 10).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_command_line() {
+        let mut command = Command::new("ffmpeg");
+        command.args(["-i", "My Video.mp4", "-vf", "drawtext=text='Hi'", ""]);
+        assert_eq!(command_line(&command), "ffmpeg -i \"My Video.mp4\" -vf \"drawtext=text='Hi'\" \"\"");
+    }
+}
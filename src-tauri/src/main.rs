@@ -1,397 +1,2786 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use tauri::Manager;
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_shell::ShellExt;
 use std::collections::HashMap;
-use serde::{Deserialize, Serialize};
-
-mod video_processor;
-mod youtube_extractor;
-mod youtube_api;
-mod file_manager;
-mod ffmpeg_processor;
-mod speech_recognition;
-mod ai_analyzer;
-mod batch_processor;
-mod project_manager;
-
-use video_processor::VideoProcessor;
-use youtube_extractor::YouTubeExtractor;
-use youtube_api::YouTubeAPI;
-use file_manager::FileManager;
-use ffmpeg_processor::FFmpegProcessor;
-use speech_recognition::{SpeechRecognizer, SpeechAnalysis, SubtitleFormat};
-use ai_analyzer::{AIAnalyzer, AIConfig, ContentAnalysis};
-use batch_processor::{BatchProcessor, BatchJob, BatchConfig};
-use project_manager::{ProjectManager, Project, VideoProject};
+use std::path::{Path, PathBuf};
+
+use video_nugget_lib::{
+    video_processor,
+    youtube_api,
+    file_manager,
+    export_format,
+    export_filter,
+    readwise_export,
+    pdf_export,
+    html_export,
+    static_site_export,
+    nugget_watcher,
+    ffmpeg_processor,
+    speech_recognition,
+    ai_analyzer,
+    ai_config_store,
+    app_settings,
+    atomic_file,
+    notifications,
+    batch_processor,
+    job_scheduler,
+    resource_governor,
+    disk_space,
+    export_manifest,
+    export_bundle,
+    backup_retention,
+    project_manager,
+    project_archive,
+    project_crypto,
+    project_versions,
+    project_stats,
+    trash,
+    project_collections,
+    project_activity,
+    project_storage,
+    virality_scorer,
+    content_moderator,
+    operations,
+    logging,
+    error,
+    processors,
+    progress_stream,
+    deep_link,
+    operation_journal,
+    capabilities,
+    workflow,
+    youtube_extractor,
+    youtube_quota,
+    youtube_cache,
+    channel_watcher,
+    youtube_publisher,
+    tiktok_publisher,
+    processed_index,
+};
+use video_nugget_lib::{VideoNugget, ProcessingResult, VideoInfo};
+
+use video_processor::{process_video_with_progress, VideoProcessor};
+use youtube_api::{ApiKeyStatus, PlaylistItem, SearchFilters, SearchPage, VideoComment, YouTubeAPI, YouTubeApiError};
+use youtube_extractor::{chapter_nuggets, VideoChapter};
+use ffmpeg_processor::{CaptionSelection, CaptionTrack};
+use youtube_quota::{ApiQuota, QuotaUsage};
+use youtube_cache::ResponseCache;
+use channel_watcher::{ChannelWatcher, NewUploads, WatchedChannel, NEW_UPLOADS_EVENT};
+use youtube_publisher::{PublisherStatus, UploadMetadata, YouTubePublisher};
+use tiktok_publisher::{TikTokAccount, TikTokPost, TikTokPublisher};
+use file_manager::{write_export, CsvOptions, FileManager};
+use export_format::ExportFormatting;
+use export_filter::ExportFilter;
+use pdf_export::PdfReport;
+use html_export::HtmlVideo;
+use static_site_export::SiteGenerator;
+use export_bundle::{BundleFormat, BundleLayout};
+use nugget_watcher::NuggetWatcher;
+use speech_recognition::{captions_analysis, SpeechAnalysis, SubtitleFormat, TranscriptSegment};
+use processors::Processors;
+use progress_stream::ProgressStream;
+use deep_link::{DeepLinkRequest, PendingDeepLinks};
+use operation_journal::{JournalEntry, OperationJournal, ResumableCommand};
+use ai_analyzer::{AIAnalyzer, AIConfig, AnalysisCache, AudienceInsights, CircuitBreakers, ContentAnalysis, Flashcard, PromptTemplate, SentimentPoint, SeoMetadata, TitleCardSuggestion};
+use ai_config_store::{AIConfigStore, AIConfigView, ApiKeyProvider};
+use app_settings::{AppSettings, AppSettingsStore};
+use notifications::{BatchSummary, NotificationSettings, NotificationSettingsView, NotificationStore};
+use batch_processor::{parse_batch_config, uploads_playlist_id, BatchProcessor, BatchJob, BatchStatus, CompletionHook, PipelineStage, VideoOverride};
+use job_scheduler::JobPriority;
+use resource_governor::ResourcePolicy;
+use disk_space::DiskSpaceEstimate;
+use capabilities::CapabilityReport;
+use workflow::{WorkflowRunResult, WorkflowRunner};
+use project_manager::{ProcessingEvent, ProjectManager, Project, ProjectSummary, VideoProject, MergeStrategy, MergeSummary, NuggetUpdate, ProjectTemplate, DuplicateVideo};
+use project_versions::{VersionDiff, VersionInfo};
+use project_stats::{project_stats, ProjectStats};
+use project_collections::{search_nuggets, CollectionNugget, NuggetCollection, NuggetQuery, NuggetRef, SmartCollection};
+use project_activity::{query_activity, ActivityFilter, ActivityPage};
+use project_storage::{storage_breakdown, StorageBreakdown};
+use backup_retention::BackupInfo;
+use trash::TrashEntry;
+use project_crypto::EncryptionStatus;
+use virality_scorer::{ViralityScorer, PlatformRanking};
+use content_moderator::{ContentModerator, NuggetModeration};
+use operations::{OperationHandle, OperationKind, OperationProgress, OperationRegistry};
+use logging::LogLevel;
+use error::{AppError, ErrorCode};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct VideoNugget {
-    pub id: String,
-    pub title: String,
-    pub start_time: f64,
-    pub end_time: f64,
-    pub transcript: Option<String>,
-    pub tags: Vec<String>,
-    pub created_at: String,
+// What this machine can do: hardware encoders, Whisper acceleration, cores, memory and free disk,
+// with the defaults that suit it. Probing FFmpeg and the GPU takes a second or two
+#[tauri::command]
+async fn detect_capabilities(
+    processors: tauri::State<'_, Arc<Processors>>,
+    project_state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<CapabilityReport, AppError> {
+    let workspace = project_state.lock().await.workspace_root().to_path_buf();
+    let processors = processors.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let ffmpeg_path = processors.ffmpeg().ok().map(|ffmpeg| ffmpeg.ffmpeg_path().to_string());
+        let whisper_path = processors.speech().ok().and_then(|speech| speech.whisper_path().map(str::to_string));
+        capabilities::detect(ffmpeg_path.as_deref(), whisper_path.as_deref(), &workspace)
+    })
+    .await
+    .map_err(|e| AppError::from(format!("Capability detection failed: {}", e)))
+}
+
+// Command to extract video information
+#[tauri::command]
+async fn get_video_info(url: String, processors: tauri::State<'_, Arc<Processors>>) -> Result<VideoInfo, AppError> {
+    Ok(processors.youtube().get_video_info(&url).await?)
+}
+
+// Command to process video and extract nuggets
+#[tauri::command]
+async fn process_video(
+    url: String,
+    config: HashMap<String, serde_json::Value>,
+    processors: tauri::State<'_, Arc<Processors>>
+) -> Result<ProcessingResult, AppError> {
+    let processor = VideoProcessor::with_extractor(processors.youtube().clone());
+    Ok(processor.process_video(&url, config).await?)
+}
+
+// Command to save nuggets to file
+#[tauri::command]
+async fn save_nuggets(
+    nuggets: Vec<VideoNugget>,
+    filepath: String,
+    watcher_state: tauri::State<'_, Arc<Mutex<NuggetWatcher>>>
+) -> Result<String, AppError> {
+    let json_data = FileManager::nuggets_json(&nuggets)?;
+    watcher_state.lock().await.expect_contents(Path::new(&filepath), json_data.as_bytes());
+    let file_manager = FileManager::new();
+    Ok(file_manager.save_nuggets(nuggets, &filepath).await?)
+}
+
+// Command to load nuggets from file. The file is watched from then on, and edits made outside
+// the app are announced with a "nugget-file-changed" event carrying its path
+#[tauri::command]
+async fn load_nuggets(
+    filepath: String,
+    watcher_state: tauri::State<'_, Arc<Mutex<NuggetWatcher>>>
+) -> Result<Vec<VideoNugget>, AppError> {
+    let file_manager = FileManager::new();
+    let nuggets = file_manager.load_nuggets(&filepath).await?;
+    if let Err(e) = watcher_state.lock().await.watch(Path::new(&filepath)) {
+        tracing::warn!("{}", e);
+    }
+    Ok(nuggets)
+}
+
+// For when the UI closes a file it loaded
+#[tauri::command]
+async fn unwatch_nugget_file(
+    filepath: String,
+    watcher_state: tauri::State<'_, Arc<Mutex<NuggetWatcher>>>
+) -> Result<(), AppError> {
+    Ok(watcher_state.lock().await.unwatch(Path::new(&filepath))?)
+}
+
+// Command to export nuggets in different formats. With a project_id the export is recorded in
+// the project's activity, or in the video's history when video_id is given too
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn export_nuggets(
+    nuggets: Vec<VideoNugget>,
+    format: String,
+    filepath: String,
+    seo: Option<SeoMetadata>,
+    notes: Option<String>,
+    project_id: Option<String>,
+    video_id: Option<String>,
+    csv_options: Option<CsvOptions>,
+    formatting: Option<ExportFormatting>,
+    filter: Option<ExportFilter>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let nuggets = filter_nuggets(&state, project_id.as_deref(), nuggets, filter.as_ref()).await?;
+    let event = FileManager::export_event(&format, &filepath, nuggets.len());
+    let message = write_export(nuggets, &format, &filepath, seo.as_ref(), notes.as_deref(), csv_options.as_ref(), formatting).await?;
+    if let Some(project_id) = project_id {
+        record_export(&state, &project_id, video_id.as_deref(), event).await;
+    }
+    Ok(message)
+}
+
+// format is "markdown", "text" or "csv". Returns how many nuggets were copied
+#[tauri::command]
+async fn copy_export_to_clipboard(
+    project_id: String,
+    nugget_ids: Vec<String>,
+    format: String,
+    formatting: Option<ExportFormatting>,
+    filter: Option<ExportFilter>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<usize, AppError> {
+    let file_manager = FileManager::new().with_formatting(formatting)?;
+    let nuggets = state.lock().await.find_nuggets(&project_id, &nugget_ids)?;
+    let nuggets = filter_nuggets(&state, Some(&project_id), nuggets, filter.as_ref()).await?;
+    let count = nuggets.len();
+    let text = match format.as_str() {
+        "markdown" => file_manager.render_markdown(&nuggets, None, None),
+        "text" => file_manager.render_plain_text(&nuggets),
+        "csv" => String::from_utf8(file_manager.render_csv(nuggets, &CsvOptions::default())?)
+            .map_err(|e| format!("Failed to render CSV: {}", e))?,
+        _ => return Err(AppError::new(ErrorCode::InvalidInput, "Unsupported clipboard format")),
+    };
+
+    app.clipboard().write_text(text)
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
+    Ok(count)
+}
+
+// The nuggets the export filter keeps, scored with the analysis of their video in the project
+async fn filter_nuggets(
+    manager: &Mutex<ProjectManager>,
+    project_id: Option<&str>,
+    nuggets: Vec<VideoNugget>,
+    filter: Option<&ExportFilter>,
+) -> Result<Vec<VideoNugget>, String> {
+    let Some(filter) = filter else {
+        return Ok(nuggets);
+    };
+    filter.validate()?;
+    let videos = match project_id {
+        Some(project_id) => manager.lock().await.get_project(project_id)?
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?
+            .videos.clone(),
+        None => Vec::new(),
+    };
+    Ok(filter.apply(nuggets, &videos))
+}
+
+// The export is already written by now, so failing to record it is only logged
+async fn record_export(manager: &Mutex<ProjectManager>, project_id: &str, video_id: Option<&str>, event: ProcessingEvent) {
+    let mut manager = manager.lock().await;
+    let result = match video_id {
+        Some(video_id) => manager.add_video_events(project_id, video_id, vec![event]),
+        None => manager.add_processing_event(project_id, event.event_type, event.details, event.parameters),
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to record export: {}", e);
+    }
+}
+
+// Command to get application version
+#[tauri::command]
+fn get_app_version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+// Command to open file in default application
+#[tauri::command]
+async fn open_file(app: tauri::AppHandle, filepath: String) -> Result<(), AppError> {
+    if !Path::new(&filepath).exists() {
+        return Err(AppError::new(ErrorCode::NotFound, format!("File not found: {}", filepath)));
+    }
+    // Shell::open is deprecated in favour of the opener plugin, which the app doesn't use yet
+    #[allow(deprecated)]
+    app.shell().open(&filepath, None)
+        .map_err(|e| AppError::from(format!("Failed to open file: {}", e)))
+}
+
+// Shows the file selected in Finder or Explorer rather than opening it
+#[tauri::command]
+async fn reveal_in_file_manager(path: String) -> Result<(), AppError> {
+    Ok(file_manager::reveal_in_file_manager(Path::new(&path))?)
+}
+
+// Advanced processing commands. Progress is reported under operation_id, see list_operations
+#[tauri::command]
+async fn process_video_advanced(
+    url: String,
+    config: HashMap<String, serde_json::Value>,
+    operation_id: Option<String>,
+    operations: tauri::State<'_, OperationRegistry>,
+    processors: tauri::State<'_, Arc<Processors>>,
+    journal: tauri::State<'_, OperationJournal>
+) -> Result<ProcessingResult, AppError> {
+    let operation = operations.start(operation_id, OperationKind::Transcription, &url)?;
+    let params = serde_json::json!({ "url": url, "config": config });
+    let operation = journaled(operation, &journal, OperationKind::Transcription, &url, ResumableCommand::ProcessVideo, params);
+    let result = process_video_with_progress(&url, config, &processors, &operation).await;
+    operation.finish(&result);
+    result
+}
+
+// With captions, the transcript is that caption track rather than Whisper's, see list_caption_tracks
+#[tauri::command]
+async fn extract_transcript(
+    url: String,
+    captions: Option<CaptionSelection>,
+    operation_id: Option<String>,
+    operations: tauri::State<'_, OperationRegistry>,
+    processors: tauri::State<'_, Arc<Processors>>,
+    journal: tauri::State<'_, OperationJournal>
+) -> Result<SpeechAnalysis, AppError> {
+    let operation = operations.start(operation_id, OperationKind::Transcription, &url)?;
+    let params = serde_json::json!({ "url": url, "captions": captions });
+    let operation = journaled(operation, &journal, OperationKind::Transcription, &url, ResumableCommand::ExtractTranscript, params);
+    let result: Result<SpeechAnalysis, AppError> = async {
+        let ffmpeg_processor = processors.ffmpeg()?.scratch()?.with_cancellation(operation.cancel_token());
+        operation.work_dir(ffmpeg_processor.work_dir());
+        if let Some(captions) = &captions {
+            operation.stage("Downloading captions");
+            let captions_path = ffmpeg_processor.download_captions(&url, captions).await?;
+            let content = std::fs::read_to_string(&captions_path)
+                .map_err(|e| format!("Failed to read captions: {}", e))?;
+            return Ok(captions_analysis(&content, captions.language.trim_end_matches("-orig")));
+        }
+        let speech_recognizer = processors.speech()?.scratch()?.with_cancellation(operation.cancel_token());
+        operation.work_dir(speech_recognizer.work_dir());
+
+        // Whatever an interrupted run already finished is picked up rather than done again
+        let audio_path = match operation.reusable_artifact("audio") {
+            Some(audio_path) => audio_path,
+            None => {
+                let video_path = match operation.reusable_artifact("video") {
+                    Some(video_path) => video_path,
+                    None => {
+                        operation.stage("Downloading");
+                        let quality = app_settings::current().defaults.download_quality;
+                        let video_path = ffmpeg_processor.download_video_with_progress(&url, &quality, Some(Arc::new(operation.reporter()))).await?;
+                        operation.artifact("video", &video_path);
+                        video_path
+                    }
+                };
+                operation.stage("Extracting audio");
+                let audio_path = ffmpeg_processor.extract_audio(&video_path).await?;
+                operation.artifact("audio", &audio_path);
+                audio_path
+            }
+        };
+
+        operation.stage("Transcribing");
+        Ok(speech_recognizer.transcribe_audio(&audio_path).await?)
+    }.await;
+    operation.finish(&result);
+    result
+}
+
+// Downloads a video to output_path as an operation, so it reports progress and cancel_operation
+// stops it. quality defaults to the one in settings
+#[tauri::command]
+async fn download_video(
+    url: String,
+    output_path: String,
+    quality: Option<String>,
+    operation_id: Option<String>,
+    operations: tauri::State<'_, OperationRegistry>,
+    processors: tauri::State<'_, Arc<Processors>>
+) -> Result<String, AppError> {
+    let operation = operations.start(operation_id, OperationKind::Download, &url)?;
+    let result: Result<String, AppError> = async {
+        let ffmpeg_processor = processors.ffmpeg()?.scratch()?.with_cancellation(operation.cancel_token());
+        let quality = quality.unwrap_or_else(|| app_settings::current().defaults.download_quality);
+
+        operation.stage("Downloading");
+        let video_path = ffmpeg_processor.download_video_with_progress(&url, &quality, Some(Arc::new(operation.reporter()))).await?;
+        operation.stage("Saving");
+        let (from, to) = (PathBuf::from(video_path), PathBuf::from(&output_path));
+        tauri::async_runtime::spawn_blocking(move || atomic_file::copy_atomic(&from, &to))
+            .await
+            .map_err(|e| format!("Failed to save video: {}", e))?
+            .map_err(|e| format!("Failed to save video: {}", e))?;
+        Ok(output_path)
+    }.await;
+    operation.finish(&result);
+    result
+}
+
+// Chapters come from yt-dlp, so they work without a YouTube API key
+#[tauri::command]
+async fn get_video_chapters(url: String, processors: tauri::State<'_, Arc<Processors>>) -> Result<Vec<VideoChapter>, AppError> {
+    Ok(processors.ffmpeg()?.get_video_chapters(&url).await?)
+}
+
+// Uploaded, auto-generated and auto-translated tracks, from yt-dlp like the chapters
+#[tauri::command]
+async fn list_caption_tracks(video_id: String, processors: tauri::State<'_, Arc<Processors>>) -> Result<Vec<CaptionTrack>, AppError> {
+    // A watch URL works as well as the bare ID
+    let video_id = processed_index::youtube_video_id(&video_id).unwrap_or(video_id);
+    if video_id.is_empty() || !video_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!("Not a YouTube video ID: {}", video_id)));
+    }
+    let url = format!("https://www.youtube.com/watch?v={}", video_id);
+    Ok(processors.ffmpeg()?.list_caption_tracks(&url).await?)
+}
+
+// A nugget per chapter, for processing a video chapter by chapter
+#[tauri::command]
+async fn get_chapter_nuggets(url: String, processors: tauri::State<'_, Arc<Processors>>) -> Result<Vec<VideoNugget>, AppError> {
+    let chapters = get_video_chapters(url, processors).await?;
+    if chapters.is_empty() {
+        return Err(AppError::new(ErrorCode::NotFound, "This video has no chapters"));
+    }
+    Ok(chapter_nuggets(&chapters))
+}
+
+// Runs a template's workflow on a video and, with a project_id, adds the result to the project
+// with that project's settings. Every step is announced as a workflow://step event; outputs go
+// to output_directory, by default a workflow folder in the project or workspace
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn run_workflow(
+    app: tauri::AppHandle,
+    template_id: String,
+    url: String,
+    project_id: Option<String>,
+    output_directory: Option<String>,
+    operation_id: Option<String>,
+    operations: tauri::State<'_, OperationRegistry>,
+    processors: tauri::State<'_, Arc<Processors>>,
+    project_state: tauri::State<'_, Arc<Mutex<ProjectManager>>>,
+    ai_config: tauri::State<'_, Arc<Mutex<AIConfigStore>>>,
+    circuit_breakers: tauri::State<'_, CircuitBreakers>
+) -> Result<WorkflowRunResult, AppError> {
+    let (steps, settings, prompt_template, output_directory) = {
+        let mut manager = project_state.lock().await;
+        let template = manager.get_templates().iter()
+            .find(|template| template.id == template_id)
+            .cloned()
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Template not found"))?;
+        let (settings, prompt_template, project_path) = match &project_id {
+            Some(project_id) => {
+                let prompt_template = manager.active_prompt_template(project_id)?;
+                let project = manager.get_project(project_id)?.ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?;
+                (project.settings.clone(), prompt_template, Some(project.workspace_path.clone()))
+            }
+            None => (template.settings.clone(), PromptTemplate::default_analysis(), None),
+        };
+        let output_directory = output_directory.map(PathBuf::from)
+            .unwrap_or_else(|| workflow::default_output_directory(manager.workspace_root(), project_path.as_deref()));
+        (template.workflow, settings, prompt_template, output_directory)
+    };
+
+    let mut ai_config = ai_config.lock().await.load()?;
+    if let Some(language) = settings.output_language.clone() {
+        ai_config.output_language = Some(language);
+    }
+    let runner = WorkflowRunner::new(processors.inner().clone(), settings, output_directory)
+        .with_ai(ai_config, prompt_template)
+        .with_circuit_breakers(circuit_breakers.inner().clone());
+
+    let operation = operations.start(operation_id, OperationKind::Workflow, &url)?;
+    let result = runner.run(&steps, &url, &operation, |event| {
+        let _ = app.emit(workflow::WORKFLOW_STEP_EVENT, event);
+    }).await;
+    operation.finish(&result);
+    let result = result?;
+
+    if let (Some(project_id), Some(video_info)) = (&project_id, &result.video_info) {
+        let mut manager = project_state.lock().await;
+        let video_id = manager.add_video_to_project(project_id, video_info.clone(), result.nuggets.clone(), result.analysis.clone())?;
+        manager.add_video_events(project_id, &video_id, result.events.clone())?;
+        if !result.output_files.is_empty() {
+            manager.set_video_output_files(project_id, &video_id, result.output_files.clone())?;
+        }
+    }
+    Ok(result)
+}
+
+// Search, channel and trending use the YouTube Data API with the key saved in settings
+fn youtube_api(quota: &Arc<ApiQuota>, cache: &ResponseCache) -> Result<YouTubeAPI, AppError> {
+    Ok(YouTubeAPI::new(ai_config_store::api_key(ApiKeyProvider::YouTube)?)
+        .with_quota(quota.clone())
+        .with_cache(cache.clone()))
+}
+
+// Comments read for audience insights; two quota units
+const ANALYZED_COMMENTS: u32 = 200;
+
+// The Data API returns at most 50 results a page
+fn youtube_page_size(max_results: Option<u32>) -> u32 {
+    max_results.unwrap_or(25).clamp(1, 50)
+}
+
+// Each returns one page; page_token is the previous page's next_page_token
+#[tauri::command]
+async fn search_youtube_videos(
+    query: String,
+    filters: Option<SearchFilters>,
+    max_results: Option<u32>,
+    page_token: Option<String>,
+    quota: tauri::State<'_, Arc<ApiQuota>>,
+    cache: tauri::State<'_, ResponseCache>
+) -> Result<SearchPage, AppError> {
+    if query.trim().is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Search query is empty"));
+    }
+    Ok(youtube_api(&quota, &cache)?.search_videos(query.trim(), &filters.unwrap_or_default(), youtube_page_size(max_results), page_token.as_deref()).await?)
+}
+
+#[tauri::command]
+async fn get_youtube_channel_videos(
+    channel_id: String,
+    max_results: Option<u32>,
+    page_token: Option<String>,
+    quota: tauri::State<'_, Arc<ApiQuota>>,
+    cache: tauri::State<'_, ResponseCache>
+) -> Result<SearchPage, AppError> {
+    Ok(youtube_api(&quota, &cache)?.get_channel_videos(&channel_id, youtube_page_size(max_results), page_token.as_deref()).await?)
+}
+
+// The region defaults to the one in settings; category_id is from get_video_categories
+#[tauri::command]
+async fn get_trending_youtube_videos(
+    region_code: Option<String>,
+    category_id: Option<String>,
+    max_results: Option<u32>,
+    page_token: Option<String>,
+    quota: tauri::State<'_, Arc<ApiQuota>>,
+    cache: tauri::State<'_, ResponseCache>
+) -> Result<SearchPage, AppError> {
+    let region_code = region_code.unwrap_or_else(|| app_settings::current().defaults.youtube_region);
+    Ok(youtube_api(&quota, &cache)?.get_trending_videos(&region_code, category_id.as_deref(), youtube_page_size(max_results), page_token.as_deref()).await?)
+}
+
+// Names the region's categories for picking one to pass to get_trending_youtube_videos and
+// create_batch_from_trending
+#[tauri::command]
+async fn get_youtube_categories(
+    region_code: Option<String>,
+    quota: tauri::State<'_, Arc<ApiQuota>>,
+    cache: tauri::State<'_, ResponseCache>
+) -> Result<HashMap<String, String>, AppError> {
+    let region_code = region_code.unwrap_or_else(|| app_settings::current().defaults.youtube_region);
+    Ok(youtube_api(&quota, &cache)?.get_video_categories(&region_code).await?)
+}
+
+// Every public and unlisted video in the playlist, in order; one quota unit per 50
+#[tauri::command]
+async fn get_playlist_items(
+    playlist_id: String,
+    quota: tauri::State<'_, Arc<ApiQuota>>,
+    cache: tauri::State<'_, ResponseCache>
+) -> Result<Vec<PlaylistItem>, AppError> {
+    Ok(youtube_api(&quota, &cache)?.get_playlist_items(&playlist_id).await?)
+}
+
+// Most relevant first; one quota unit per 100 comments
+#[tauri::command]
+async fn get_video_comments(
+    video_id: String,
+    max_results: Option<u32>,
+    quota: tauri::State<'_, Arc<ApiQuota>>,
+    cache: tauri::State<'_, ResponseCache>
+) -> Result<Vec<VideoComment>, AppError> {
+    let max_results = max_results.unwrap_or(ANALYZED_COMMENTS).clamp(1, 1000);
+    Ok(youtube_api(&quota, &cache)?.get_video_comments(&video_id, max_results).await?)
+}
+
+// Watched channels are checked every CHANNEL_CHECK_MINUTES, a quota unit each, and new uploads
+// arrive as youtube://new-uploads events
+const CHANNEL_CHECK_MINUTES: u64 = 30;
+
+// Uploads from before the channel was watched aren't reported
+#[tauri::command]
+async fn watch_channel(channel_url: String, watcher: tauri::State<'_, Arc<ChannelWatcher>>) -> Result<WatchedChannel, AppError> {
+    let channel_id = BatchProcessor::resolve_channel_id(&channel_url).await?;
+    let playlist_id = uploads_playlist_id(&channel_id)
+        .ok_or_else(|| AppError::new(ErrorCode::InvalidInput, format!("Not a YouTube channel ID: {}", channel_id)))?;
+    Ok(watcher.watch(&channel_id, playlist_id)?)
+}
+
+#[tauri::command]
+fn unwatch_channel(channel_id: String, watcher: tauri::State<'_, Arc<ChannelWatcher>>) -> Result<(), AppError> {
+    Ok(watcher.unwatch(&channel_id)?)
+}
+
+#[tauri::command]
+fn list_watched_channels(watcher: tauri::State<'_, Arc<ChannelWatcher>>) -> Vec<WatchedChannel> {
+    watcher.list()
+}
+
+// Without waiting for the next scheduled check; the uploads found are also sent as events
+#[tauri::command]
+async fn check_watched_channels(
+    app: tauri::AppHandle,
+    watcher: tauri::State<'_, Arc<ChannelWatcher>>,
+    quota: tauri::State<'_, Arc<ApiQuota>>,
+    cache: tauri::State<'_, ResponseCache>
+) -> Result<Vec<NewUploads>, AppError> {
+    Ok(check_channels(&app, &watcher, &youtube_api(&quota, &cache)?).await?)
+}
+
+async fn check_channels(app: &tauri::AppHandle, watcher: &ChannelWatcher, youtube: &YouTubeAPI) -> Result<Vec<NewUploads>, YouTubeApiError> {
+    let found = watcher.check(youtube).await?;
+    for new_uploads in &found {
+        let _ = app.emit(NEW_UPLOADS_EVENT, new_uploads);
+    }
+    Ok(found)
+}
+
+// Runs for the life of the app, the first check right at startup
+async fn check_channels_periodically(app: tauri::AppHandle, watcher: Arc<ChannelWatcher>, quota: Arc<ApiQuota>, cache: ResponseCache) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(CHANNEL_CHECK_MINUTES * 60));
+    loop {
+        interval.tick().await;
+        if watcher.list().is_empty() {
+            continue;
+        }
+        let youtube = match youtube_api(&quota, &cache) {
+            Ok(youtube) => youtube,
+            Err(e) => {
+                tracing::warn!("Failed to check watched channels: {}", e.message);
+                continue;
+            }
+        };
+        match check_channels(&app, &watcher, &youtube).await {
+            Ok(_) | Err(YouTubeApiError::MissingApiKey(_)) => {}
+            Err(e) => tracing::warn!("Failed to check watched channels: {}", e),
+        }
+    }
+}
+
+// Today's YouTube API usage as the app counted it. youtube://quota-warning goes out once a day
+// when it passes 80% of the limit
+#[tauri::command]
+fn get_api_quota_usage(quota: tauri::State<'_, Arc<ApiQuota>>) -> QuotaUsage {
+    quota.usage()
+}
+
+// Operations still running, for a UI that opens after they started. Progress after that arrives
+// as operation://progress events
+#[tauri::command]
+fn list_operations(operations: tauri::State<'_, OperationRegistry>) -> Vec<OperationProgress> {
+    operations.list()
+}
+
+// Journals the operation so a crash can be recovered from. Without a journal it still runs,
+// it just can't be resumed
+fn journaled(
+    operation: OperationHandle,
+    journal: &OperationJournal,
+    kind: OperationKind,
+    label: &str,
+    command: ResumableCommand,
+    params: serde_json::Value,
+) -> OperationHandle {
+    match journal.begin(operation.id(), kind, label, command, params) {
+        Ok(record) => operation.with_journal(record),
+        Err(e) => {
+            tracing::warn!("Failed to journal operation {}: {}", operation.id(), e);
+            operation
+        }
+    }
+}
+
+// Operations a crash or forced quit cut short, oldest first. Resuming one is running its command
+// again with the entry's params and operation_id, which picks up the files it had finished;
+// batch jobs are resumed with start_batch_job
+#[tauri::command]
+async fn list_interrupted_operations(
+    operations: tauri::State<'_, OperationRegistry>,
+    journal: tauri::State<'_, OperationJournal>,
+    batch_state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<Vec<JournalEntry>, AppError> {
+    let running: Vec<String> = operations.list().into_iter().map(|progress| progress.operation_id).collect();
+    let mut interrupted: Vec<JournalEntry> = journal.list().into_iter()
+        .filter(|entry| !running.contains(&entry.operation_id))
+        .collect();
+
+    let processor = batch_state.lock().await;
+    for job in processor.recovered_jobs() {
+        let started_at = job.started_at.clone().unwrap_or_else(|| job.created_at.clone());
+        interrupted.push(JournalEntry {
+            operation_id: job.id.clone(),
+            kind: OperationKind::Batch,
+            label: job.name.clone(),
+            command: ResumableCommand::Batch,
+            params: serde_json::json!({ "job_id": job.id }),
+            stage: format!("{} of {} videos processed", job.progress.processed_videos, job.progress.total_videos),
+            artifacts: HashMap::new(),
+            work_dirs: Vec::new(),
+            updated_at: started_at.clone(),
+            started_at,
+        });
+    }
+    interrupted.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    Ok(interrupted)
+}
+
+// Gives up on an interrupted operation and removes the files it left behind. Batch jobs keep
+// their progress in the job itself and stay paused until they're started again
+#[tauri::command]
+fn discard_interrupted_operation(operation_id: String, journal: tauri::State<'_, OperationJournal>) -> Result<(), AppError> {
+    Ok(journal.discard(&operation_id)?)
+}
+
+#[tauri::command]
+fn cancel_operation(operation_id: String, operations: tauri::State<'_, OperationRegistry>) -> Result<(), AppError> {
+    Ok(operations.cancel(&operation_id)?)
+}
+
+// Where external dashboards can follow progress, None unless it's turned on in the settings
+#[tauri::command]
+fn get_progress_stream_url(progress_stream: tauri::State<'_, Arc<ProgressStream>>) -> Option<String> {
+    progress_stream.url()
+}
+
+// video-nugget:// links opened since the last call. The UI asks for them when it starts and on
+// every deep-link-received event, and confirms each with the user before processing it
+#[tauri::command]
+fn take_pending_deep_links(pending: tauri::State<'_, PendingDeepLinks>) -> Vec<DeepLinkRequest> {
+    pending.take()
+}
+
+fn receive_deep_link(app: &tauri::AppHandle, link: &str) {
+    match deep_link::parse(link) {
+        Ok(request) => {
+            app.state::<PendingDeepLinks>().push(request.clone());
+            let _ = app.emit(deep_link::DEEP_LINK_EVENT, request);
+            focus_main_window(app);
+        }
+        Err(e) => tracing::warn!("Ignoring deep link {}: {}", link, e),
+    }
+}
+
+fn focus_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+// The newest lines of the log files, including every external command run, for bug reports
+#[tauri::command]
+fn get_recent_logs(max_lines: Option<usize>) -> Result<Vec<String>, AppError> {
+    Ok(logging::recent_logs(max_lines.unwrap_or(500))?)
+}
+
+// Until the app restarts, which goes back to Info
+#[tauri::command]
+fn set_log_level(level: LogLevel) -> Result<(), AppError> {
+    Ok(logging::set_level(level)?)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn analyze_content(
+    transcript: String,
+    title: String,
+    description: Option<String>,
+    project_id: Option<String>,
+    // When a video already in the project is analyzed, the run goes into its history
+    video_id: Option<String>,
+    output_language: Option<String>,
+    video_duration: Option<f64>,
+    // A YouTube URL adds the video's YouTube category and audience_insights from its comments,
+    // when a YouTube key is set
+    source_url: Option<String>,
+    operation_id: Option<String>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>,
+    ai_config: tauri::State<'_, Arc<Mutex<AIConfigStore>>>,
+    operations: tauri::State<'_, OperationRegistry>,
+    quota: tauri::State<'_, Arc<ApiQuota>>,
+    cache: tauri::State<'_, ResponseCache>,
+    circuit_breakers: tauri::State<'_, CircuitBreakers>
+) -> Result<ContentAnalysis, AppError> {
+    let (prompt_template, project_language, analysis_cache) = {
+        let manager = state.lock().await;
+        let (template, language) = match &project_id {
+            Some(project_id) => (
+                manager.active_prompt_template(project_id)?,
+                manager.project_settings(project_id).and_then(|settings| settings.output_language.clone()),
+            ),
+            None => (PromptTemplate::default_analysis(), None),
+        };
+        // Nothing is cached while the workspace is locked, it would be written unencrypted
+        let status = manager.encryption_status();
+        let analysis_cache = (!status.enabled || status.unlocked)
+            .then(|| AnalysisCache::new(manager.workspace_root().join("cache").join("analysis")).with_key(manager.data_key()));
+        (template, language, analysis_cache)
+    };
+
+    let mut ai_config = ai_config.lock().await.load()?;
+    if let Some(language) = output_language.or(project_language) {
+        ai_config.output_language = Some(language);
+    }
+    
+    let mut analyzer = AIAnalyzer::new(ai_config)
+        .with_prompt_template(prompt_template)
+        .with_circuit_breakers(circuit_breakers.inner().clone());
+    if let Some(analysis_cache) = analysis_cache {
+        analyzer = analyzer.with_cache(analysis_cache);
+    }
+    if let Some(duration) = video_duration {
+        analyzer = analyzer.with_video_duration(duration);
+    }
+    let operation = operations.start(operation_id, OperationKind::Analysis, &title)?;
+    operation.stage("Analyzing");
+    let youtube_video_id = source_url.as_deref().and_then(processed_index::youtube_video_id);
+    let result = operations::cancellable(&operation, async {
+        let mut analysis = analyzer.analyze_content(&transcript, &title, description.as_deref()).await?;
+        if let Some(youtube_video_id) = youtube_video_id {
+            match youtube_category(&youtube_video_id, &quota, &cache).await {
+                Ok(Some(category)) => analysis.add_source_category(&category),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to get YouTube category: {}", e.message),
+            }
+            operation.stage("Reading comments");
+            // The analysis stands without them; comments can be disabled or the key missing
+            match audience_insights(&analyzer, &youtube_video_id, &title, &quota, &cache).await {
+                Ok(insights) => analysis.audience_insights = Some(insights),
+                Err(e) => analysis.warnings.push(format!("Audience insights unavailable: {}", e)),
+            }
+        }
+        Ok::<_, AppError>(analysis)
+    }).await;
+    operation.finish(&result);
+    let analysis = result?;
+
+    if let (Some(project_id), Some(video_id)) = (project_id, video_id) {
+        let mut manager = state.lock().await;
+        manager.add_video_events(&project_id, &video_id, vec![analyzer.analysis_event(&analysis)])?;
+    }
+    Ok(analysis)
+}
+
+async fn youtube_category(youtube_video_id: &str, quota: &Arc<ApiQuota>, cache: &ResponseCache) -> Result<Option<String>, AppError> {
+    let region = app_settings::current().defaults.youtube_region;
+    Ok(youtube_api(quota, cache)?.get_video_category(youtube_video_id, &region).await?)
+}
+
+async fn audience_insights(
+    analyzer: &AIAnalyzer,
+    youtube_video_id: &str,
+    title: &str,
+    quota: &Arc<ApiQuota>,
+    cache: &ResponseCache
+) -> Result<AudienceInsights, AppError> {
+    let comments = youtube_api(quota, cache)?.get_video_comments(youtube_video_id, ANALYZED_COMMENTS).await?;
+    Ok(analyzer.analyze_comments(&comments, title).await?)
+}
+
+#[tauri::command]
+async fn get_ai_config(state: tauri::State<'_, Arc<Mutex<AIConfigStore>>>) -> Result<AIConfigView, AppError> {
+    let store = state.lock().await;
+    Ok(store.view()?)
+}
+
+#[tauri::command]
+async fn set_ai_config(
+    config: AIConfig,
+    state: tauri::State<'_, Arc<Mutex<AIConfigStore>>>
+) -> Result<(), AppError> {
+    let store = state.lock().await;
+    Ok(store.save(config)?)
+}
+
+#[tauri::command]
+fn get_api_key_set(provider: ApiKeyProvider) -> Result<bool, AppError> {
+    Ok(ai_config_store::api_key(provider)?.is_some())
+}
+
+#[tauri::command]
+fn set_api_key(provider: ApiKeyProvider, key: String) -> Result<(), AppError> {
+    Ok(ai_config_store::set_api_key(provider, &key)?)
+}
+
+#[tauri::command]
+fn clear_api_key(provider: ApiKeyProvider) -> Result<(), AppError> {
+    Ok(ai_config_store::clear_api_key(provider)?)
+}
+
+// Whether the YouTube key typed into the settings form, or the stored one, is valid, restricted
+// or out of quota. Unlike test_api_key a bad key is an answer, not an error
+#[tauri::command]
+async fn validate_youtube_api_key(key: Option<String>, quota: tauri::State<'_, Arc<ApiQuota>>) -> Result<ApiKeyStatus, AppError> {
+    let key = match key.filter(|key| !key.trim().is_empty()) {
+        Some(key) => Some(key.trim().to_string()),
+        None => ai_config_store::api_key(ApiKeyProvider::YouTube)?,
+    };
+    Ok(YouTubeAPI::new(key).with_quota(quota.inner().clone()).validate_api_key().await)
+}
+
+// Tries the key typed into the settings form, or the stored one, against the provider
+#[tauri::command]
+async fn test_api_key(
+    provider: ApiKeyProvider,
+    key: Option<String>,
+    state: tauri::State<'_, Arc<Mutex<AIConfigStore>>>,
+    quota: tauri::State<'_, Arc<ApiQuota>>
+) -> Result<(), AppError> {
+    let key = match key.filter(|key| !key.trim().is_empty()) {
+        Some(key) => key.trim().to_string(),
+        None => ai_config_store::api_key(provider)?.ok_or("No API key set")?,
+    };
+
+    // The stored config still matters for OpenAI, whose base URL may point at another gateway
+    let mut config = state.lock().await.load()?;
+    match provider {
+        ApiKeyProvider::YouTube => return Ok(YouTubeAPI::new(Some(key)).with_quota(quota.inner().clone()).check_api_key().await?),
+        ApiKeyProvider::OpenAI => {
+            config.openai_api_key = Some(key);
+            config.model_preference = ai_analyzer::AIModel::OpenAIGPT4;
+        }
+        ApiKeyProvider::Claude => {
+            config.claude_api_key = Some(key);
+            config.model_preference = ai_analyzer::AIModel::Claude3;
+        }
+        ApiKeyProvider::Gemini => {
+            config.gemini_api_key = Some(key);
+            config.model_preference = ai_analyzer::AIModel::Gemini;
+        }
+    }
+    AIAnalyzer::new(config).list_available_models().await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_notification_settings(state: tauri::State<'_, Arc<Mutex<NotificationStore>>>) -> Result<NotificationSettingsView, AppError> {
+    let store = state.lock().await;
+    Ok(store.view()?)
+}
+
+#[tauri::command]
+async fn set_notification_settings(
+    settings: NotificationSettings,
+    smtp_password: Option<String>,
+    state: tauri::State<'_, Arc<Mutex<NotificationStore>>>
+) -> Result<(), AppError> {
+    let store = state.lock().await;
+    Ok(store.save(settings, smtp_password)?)
+}
+
+#[tauri::command]
+async fn send_test_email(state: tauri::State<'_, Arc<Mutex<NotificationStore>>>) -> Result<(), AppError> {
+    let store = state.lock().await;
+    Ok(store.send_email("Video Nugget test email", "Batch notifications will be sent to this address.").await?)
+}
+
+#[tauri::command]
+async fn list_available_models(
+    provider: ai_analyzer::AIModel,
+    api_key: Option<String>,
+    base_url: Option<String>,
+    state: tauri::State<'_, Arc<Mutex<AIConfigStore>>>
+) -> Result<Vec<String>, AppError> {
+    // Keys typed into the settings form take precedence over the stored ones
+    let mut config = state.lock().await.load()?;
+    match provider {
+        ai_analyzer::AIModel::OpenAIGPT4 | ai_analyzer::AIModel::OpenAIGPT35 => {
+            config.openai_api_key = api_key.or(config.openai_api_key);
+            config.openai_base_url = base_url.or(config.openai_base_url);
+        }
+        ai_analyzer::AIModel::Claude3 => config.claude_api_key = api_key.or(config.claude_api_key),
+        ai_analyzer::AIModel::Gemini => config.gemini_api_key = api_key.or(config.gemini_api_key),
+        ai_analyzer::AIModel::Local => {}
+    }
+    config.model_preference = provider;
+
+    Ok(AIAnalyzer::new(config).list_available_models().await?)
+}
+
+#[tauri::command]
+async fn detect_highlights(
+    transcript_segments: Vec<TranscriptSegment>,
+    audio_path: Option<String>,
+    processors: tauri::State<'_, Arc<Processors>>
+) -> Result<Vec<ai_analyzer::HighlightMoment>, AppError> {
+    // Audio cues are optional, transcript heuristics alone still produce highlights
+    let audio = match audio_path {
+        Some(audio_path) => Some(processors.ffmpeg()?.analyze_audio(&audio_path).await?),
+        None => None,
+    };
+
+    let analyzer = AIAnalyzer::new(AIConfig::default());
+    Ok(analyzer.detect_highlights_from_segments(&transcript_segments, audio.as_ref()).await?)
+}
+
+#[tauri::command]
+fn get_sentiment_timeline(transcript_segments: Vec<TranscriptSegment>, bucket_seconds: Option<f64>) -> Vec<SentimentPoint> {
+    ai_analyzer::build_sentiment_timeline(&transcript_segments, bucket_seconds.unwrap_or(60.0))
+}
+
+#[tauri::command]
+fn score_nugget_virality(
+    nuggets: Vec<VideoNugget>,
+    analysis: Option<ContentAnalysis>,
+    trending_topics: Option<Vec<String>>,
+    top_n: Option<usize>
+) -> Vec<PlatformRanking> {
+    let scorer = ViralityScorer::new(trending_topics.unwrap_or_default());
+    scorer.rank_nuggets(&nuggets, analysis.as_ref(), top_n.unwrap_or(nuggets.len()))
+}
+
+#[tauri::command]
+async fn generate_subtitles(
+    transcript_segments: Vec<serde_json::Value>,
+    format: String,
+    processors: tauri::State<'_, Arc<Processors>>
+) -> Result<String, AppError> {
+    // Convert JSON to TranscriptSegment objects
+    let segments: Result<Vec<_>, _> = transcript_segments.iter()
+        .map(|v| serde_json::from_value(v.clone()))
+        .collect();
+    
+    let segments = segments.map_err(|e| format!("Failed to parse transcript segments: {}", e))?;
+    
+    let speech_analysis = SpeechAnalysis {
+        segments,
+        language: "en".to_string(),
+        total_speech_time: 0.0,
+        word_count: 0,
+        average_confidence: 0.0,
+    };
+    
+    let subtitle_format = match format.as_str() {
+        "srt" => SubtitleFormat::SRT,
+        "vtt" => SubtitleFormat::VTT,
+        "ass" => SubtitleFormat::ASS,
+        _ => return Err(AppError::new(ErrorCode::InvalidInput, "Unsupported subtitle format")),
+    };
+    
+    let speech_recognizer = processors.speech()?;
+    Ok(speech_recognizer.generate_subtitles(&speech_analysis, subtitle_format).await?)
+}
+
+#[tauri::command]
+fn moderate_nuggets(nuggets: Vec<VideoNugget>, blocklist: Option<Vec<String>>) -> Result<Vec<NuggetModeration>, AppError> {
+    let moderator = ContentModerator::new(blocklist.unwrap_or_default())?;
+    Ok(moderator.moderate_nuggets(&nuggets))
+}
+
+#[tauri::command]
+fn suggest_flashcards(nuggets: Vec<VideoNugget>) -> Vec<Flashcard> {
+    ai_analyzer::suggest_flashcards(&nuggets)
+}
+
+#[tauri::command]
+fn suggest_title_cards(nuggets: Vec<VideoNugget>, analysis: Option<ContentAnalysis>) -> Vec<TitleCardSuggestion> {
+    ai_analyzer::suggest_title_cards(&nuggets, analysis.as_ref())
+}
+
+#[tauri::command]
+async fn render_title_card(
+    video_path: String,
+    time: f64,
+    text: String,
+    output_path: String,
+    operation_id: Option<String>,
+    operations: tauri::State<'_, OperationRegistry>,
+    processors: tauri::State<'_, Arc<Processors>>
+) -> Result<String, AppError> {
+    let operation = operations.start(operation_id, OperationKind::Rendering, &output_path)?;
+    operation.stage("Rendering title card");
+    let result = async {
+        processors.ffmpeg()?
+            .with_cancellation(operation.cancel_token())
+            .render_title_card(&video_path, time, &text, &output_path).await
+    }.await;
+    operation.finish(&result);
+    result?;
+    Ok(output_path)
+}
+
+#[tauri::command]
+async fn create_social_formats(
+    video_path: String,
+    operation_id: Option<String>,
+    operations: tauri::State<'_, OperationRegistry>,
+    processors: tauri::State<'_, Arc<Processors>>
+) -> Result<serde_json::Value, AppError> {
+    let operation = operations.start(operation_id, OperationKind::Rendering, &video_path)?;
+    operation.stage("Rendering social formats");
+    let result = async {
+        processors.ffmpeg()?
+            .with_cancellation(operation.cancel_token())
+            .create_social_media_formats(&video_path).await
+    }.await;
+    operation.finish(&result);
+    
+    Ok(serde_json::to_value(result?)
+        .map_err(|e| format!("Failed to serialize formats: {}", e))?)
+}
+
+// Batch processing commands
+#[tauri::command]
+async fn create_batch_job(
+    name: String,
+    urls: Vec<String>,
+    config: serde_json::Value,
+    priority: Option<JobPriority>,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<String, AppError> {
+    let batch_config = parse_batch_config(config)?;
+    
+    let mut processor = state.lock().await;
+    Ok(processor.create_batch_job(name, urls, batch_config, priority.unwrap_or_default()))
+}
+
+#[tauri::command]
+async fn create_batch_pipeline(
+    name: String,
+    urls: Vec<String>,
+    config: serde_json::Value,
+    stages: Vec<PipelineStage>,
+    priority: Option<JobPriority>,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<Vec<String>, AppError> {
+    let batch_config = parse_batch_config(config)?;
+
+    let mut processor = state.lock().await;
+    Ok(processor.create_pipeline(name, urls, batch_config, stages, priority.unwrap_or_default())?)
+}
+
+#[tauri::command]
+async fn add_batch_job_dependency(
+    job_id: String,
+    depends_on: String,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<(), AppError> {
+    let mut processor = state.lock().await;
+    Ok(processor.add_job_dependency(&job_id, &depends_on)?)
+}
+
+#[tauri::command]
+async fn set_batch_job_priority(
+    job_id: String,
+    priority: JobPriority,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<(), AppError> {
+    let mut processor = state.lock().await;
+    Ok(processor.set_batch_job_priority(&job_id, priority)?)
+}
+
+#[tauri::command]
+async fn set_batch_url_override(
+    job_id: String,
+    url: String,
+    video_override: Option<VideoOverride>,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<(), AppError> {
+    let mut processor = state.lock().await;
+    Ok(processor.set_url_override(&job_id, &url, video_override)?)
+}
+
+#[tauri::command]
+async fn create_batch_from_playlist(
+    playlist_url: String,
+    name: String,
+    config: serde_json::Value,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<String, AppError> {
+    let batch_config = parse_batch_config(config)?;
+    Ok(BatchProcessor::create_batch_from_playlist(state.inner().clone(), &playlist_url, name, batch_config).await?)
+}
+
+// A batch of the top count trending videos (25 by default, 200 at most), for reaction and
+// commentary channels
+#[tauri::command]
+async fn create_batch_from_trending(
+    region_code: Option<String>,
+    category_id: Option<String>,
+    count: Option<usize>,
+    name: Option<String>,
+    config: serde_json::Value,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<String, AppError> {
+    let batch_config = parse_batch_config(config)?;
+    let region_code = region_code.unwrap_or_else(|| app_settings::current().defaults.youtube_region);
+    Ok(BatchProcessor::create_batch_from_trending(state.inner().clone(), &region_code, category_id.as_deref(), count.unwrap_or(25), name, batch_config).await?)
+}
+
+#[tauri::command]
+async fn create_batch_from_channel(
+    channel_url: String,
+    newest_n: Option<usize>,
+    since_date: Option<String>,
+    name: String,
+    config: serde_json::Value,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<String, AppError> {
+    let batch_config = parse_batch_config(config)?;
+
+    Ok(BatchProcessor::create_batch_from_channel(state.inner().clone(), &channel_url, newest_n, since_date.as_deref(), name, batch_config).await?)
+}
+
+#[tauri::command]
+async fn start_batch_job(
+    job_id: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>,
+    operations: tauri::State<'_, OperationRegistry>
+) -> Result<(), AppError> {
+    let on_update = batch_update_emitter(app, state.inner().clone(), operations.inner().clone());
+    Ok(BatchProcessor::start_batch_job(state.inner().clone(), &job_id, on_update).await?)
+}
+
+// Running jobs are mirrored into the operation registry, so they are listed and cancelled like
+// any other long-running operation
+fn batch_update_emitter(
+    app: tauri::AppHandle,
+    processor: Arc<Mutex<BatchProcessor>>,
+    operations: OperationRegistry
+) -> impl Fn(&BatchJob) + Send + Sync + 'static {
+    let running: std::sync::Mutex<HashMap<String, OperationHandle>> = std::sync::Mutex::new(HashMap::new());
+    move |job: &BatchJob| {
+        let _ = app.emit("batch-job-updated", job);
+
+        let mut running = running.lock().unwrap_or_else(|e| e.into_inner());
+        match job.status {
+            BatchStatus::Completed | BatchStatus::Failed | BatchStatus::Cancelled => {
+                let Some(operation) = running.remove(&job.id) else { return };
+                let result = match job.status {
+                    BatchStatus::Completed => Ok(()),
+                    BatchStatus::Cancelled => {
+                        // Cancelled through cancel_batch_job rather than the registry
+                        operation.cancel_token().cancel();
+                        Err("Batch job cancelled".to_string())
+                    }
+                    _ => Err(format!("{} of {} videos failed", job.progress.failed_videos, job.progress.total_videos)),
+                };
+                operation.finish(&result);
+            }
+            _ => {
+                if !running.contains_key(&job.id) {
+                    let processor = processor.clone();
+                    let job_id = job.id.clone();
+                    let on_cancel = move || {
+                        let processor = processor.clone();
+                        let job_id = job_id.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = processor.lock().await.cancel_batch_job(&job_id) {
+                                tracing::warn!("Failed to cancel batch job {}: {}", job_id, e);
+                            }
+                        });
+                    };
+                    match operations.start_with_cancel(Some(job.id.clone()), OperationKind::Batch, &job.name, on_cancel) {
+                        Ok(operation) => { running.insert(job.id.clone(), operation); }
+                        Err(e) => tracing::warn!("Failed to register batch job {}: {}", job.id, e),
+                    }
+                }
+                if let Some(operation) = running.get(&job.id) {
+                    let stage = format!("{:?}", job.status);
+                    operation.report(&stage, Some(job.progress.percentage), job.progress.current_video.clone());
+                }
+            }
+        }
+    }
+}
+
+// Runs inside the batch processor lock, so the actual sending happens on its own task
+fn batch_completion_notifier(app: tauri::AppHandle, store: Arc<Mutex<NotificationStore>>) -> CompletionHook {
+    Arc::new(move |job: &BatchJob| {
+        let app = app.clone();
+        let store = store.clone();
+        let job = job.clone();
+
+        tauri::async_runtime::spawn(async move {
+            let store = store.lock().await;
+            let settings = match store.load() {
+                Ok(settings) => settings,
+                Err(e) => {
+                    tracing::warn!("Failed to load notification settings: {}", e);
+                    return;
+                }
+            };
+            if !settings.should_notify(&job) {
+                return;
+            }
+
+            let summary = BatchSummary::from_job(&job);
+            if settings.desktop {
+                if let Err(e) = app.notification().builder().title(&summary.title).body(&summary.short).show() {
+                    tracing::warn!("Failed to show batch notification: {}", e);
+                }
+            }
+            if settings.email.is_some() {
+                if let Err(e) = store.send_email(&summary.title, &summary.details).await {
+                    tracing::warn!("Failed to send batch notification email: {}", e);
+                }
+            }
+        });
+    })
+}
+
+#[tauri::command]
+async fn get_batch_job_status(
+    job_id: String,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<Option<BatchJob>, AppError> {
+    let processor = state.lock().await;
+    Ok(processor.get_batch_job(&job_id).cloned())
+}
+
+#[tauri::command]
+async fn cancel_batch_job(
+    job_id: String,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<(), AppError> {
+    let mut processor = state.lock().await;
+    Ok(processor.cancel_batch_job(&job_id)?)
+}
+
+#[tauri::command]
+async fn pause_batch_job(
+    job_id: String,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<(), AppError> {
+    let mut processor = state.lock().await;
+    Ok(processor.pause_batch_job(&job_id)?)
+}
+
+#[tauri::command]
+async fn resume_batch_job(
+    job_id: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>,
+    operations: tauri::State<'_, OperationRegistry>
+) -> Result<(), AppError> {
+    state.lock().await.resume_batch_job(&job_id)?;
+    let on_update = batch_update_emitter(app, state.inner().clone(), operations.inner().clone());
+    Ok(BatchProcessor::start_batch_job(state.inner().clone(), &job_id, on_update).await?)
+}
+
+#[tauri::command]
+async fn retry_failed_videos(
+    job_id: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>,
+    operations: tauri::State<'_, OperationRegistry>
+) -> Result<usize, AppError> {
+    let retry_count = state.lock().await.prepare_failed_retry(&job_id)?;
+    let on_update = batch_update_emitter(app, state.inner().clone(), operations.inner().clone());
+    BatchProcessor::start_batch_job(state.inner().clone(), &job_id, on_update).await?;
+    Ok(retry_count)
+}
+
+#[tauri::command]
+async fn export_batch_report(
+    job_id: String,
+    formats: Vec<String>,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<Vec<String>, AppError> {
+    Ok(BatchProcessor::export_batch_report(state.inner().clone(), &job_id, &formats).await?)
+}
+
+// Returns how many files went into the bundle
+#[tauri::command]
+async fn bundle_batch_output(
+    job_id: String,
+    bundle_path: String,
+    format: BundleFormat,
+    layout: BundleLayout,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<usize, AppError> {
+    let files = state.lock().await.job_files(&job_id)?;
+    let count = tauri::async_runtime::spawn_blocking(move || {
+        export_bundle::write_bundle(&files, Path::new(&bundle_path), format, layout)
+    })
+    .await
+    .map_err(|e| format!("Bundle task failed: {}", e))??;
+    Ok(count)
+}
+
+#[tauri::command]
+async fn bundle_export_files(
+    files: Vec<String>,
+    bundle_path: String,
+    format: BundleFormat,
+    layout: BundleLayout
+) -> Result<usize, AppError> {
+    let files: Vec<PathBuf> = files.into_iter().map(PathBuf::from).collect();
+    let count = tauri::async_runtime::spawn_blocking(move || {
+        export_bundle::write_bundle(&files, Path::new(&bundle_path), format, layout)
+    })
+    .await
+    .map_err(|e| format!("Bundle task failed: {}", e))??;
+    Ok(count)
+}
+
+#[tauri::command]
+async fn estimate_batch_disk_space(
+    job_id: String,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<DiskSpaceEstimate, AppError> {
+    Ok(BatchProcessor::estimate_disk_space(state.inner().clone(), &job_id).await?)
+}
+
+#[tauri::command]
+async fn get_resource_policy(
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<ResourcePolicy, AppError> {
+    Ok(state.lock().await.resource_policy())
+}
+
+#[tauri::command]
+async fn set_resource_policy(
+    policy: ResourcePolicy,
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<(), AppError> {
+    Ok(state.lock().await.set_resource_policy(policy)?)
+}
+
+#[tauri::command]
+async fn list_batch_jobs(
+    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
+) -> Result<Vec<BatchJob>, AppError> {
+    let processor = state.lock().await;
+    Ok(processor.list_batch_jobs().into_iter().cloned().collect())
+}
+
+// Project management commands
+#[tauri::command]
+async fn create_project(
+    name: String,
+    description: Option<String>,
+    template_id: Option<String>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.create_project(name, description, template_id)?)
+}
+
+#[tauri::command]
+async fn add_video_to_project(
+    project_id: String,
+    video_info: VideoInfo,
+    nuggets: Vec<VideoNugget>,
+    analysis: Option<ContentAnalysis>,
+    // From ProcessingResult.events, and any other pipeline steps run before adding the video
+    events: Option<Vec<ProcessingEvent>>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    let video_id = manager.add_video_to_project(&project_id, video_info, nuggets, analysis)?;
+    manager.add_video_events(&project_id, &video_id, events.unwrap_or_default())?;
+    Ok(video_id)
+}
+
+#[tauri::command]
+async fn get_project(
+    project_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Option<Project>, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.get_project(&project_id)?.cloned())
+}
+
+#[tauri::command]
+async fn list_projects(
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Vec<ProjectSummary>, AppError> {
+    let manager = state.lock().await;
+    Ok(manager.list_projects())
+}
+
+#[tauri::command]
+async fn update_project_settings(
+    project_id: String,
+    settings: serde_json::Value,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let settings = serde_json::from_value(settings)
+        .map_err(|e| format!("Invalid project settings: {}", e))?;
+    
+    let mut manager = state.lock().await;
+    Ok(manager.update_project_settings(&project_id, settings)?)
+}
+
+#[tauri::command]
+async fn generate_seo_metadata(
+    project_id: String,
+    video_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<SeoMetadata, AppError> {
+    let mut manager = state.lock().await;
+    let video = manager.get_video(&project_id, &video_id)?;
+    let analysis = video.analysis.as_ref()
+        .ok_or("Video has no content analysis yet")?;
+
+    let seo = ai_analyzer::generate_seo_metadata(analysis, &video.video_info.title, &video.nuggets);
+
+    manager.set_video_seo(&project_id, &video_id, seo.clone())?;
+    Ok(seo)
+}
+
+#[tauri::command]
+async fn list_prompt_templates(
+    project_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Vec<PromptTemplate>, AppError> {
+    let manager = state.lock().await;
+    Ok(manager.list_prompt_templates(&project_id)?)
+}
+
+#[tauri::command]
+async fn save_prompt_template(
+    project_id: String,
+    template: PromptTemplate,
+    make_active: bool,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.save_prompt_template(&project_id, template, make_active)?)
+}
+
+#[tauri::command]
+async fn reset_prompt_templates(
+    project_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.reset_prompt_templates(&project_id)?)
+}
+
+#[tauri::command]
+async fn delete_project(
+    project_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.delete_project(&project_id)?)
+}
+
+#[tauri::command]
+async fn clone_project(
+    project_id: String,
+    new_name: String,
+    include_media: bool,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.clone_project(&project_id, new_name, include_media)?)
+}
+
+#[tauri::command]
+async fn list_trash(state: tauri::State<'_, Arc<Mutex<ProjectManager>>>) -> Result<Vec<TrashEntry>, AppError> {
+    let manager = state.lock().await;
+    Ok(manager.list_trash()?)
+}
+
+#[tauri::command]
+async fn restore_from_trash(
+    entry_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<TrashEntry, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.restore_from_trash(&entry_id)?)
+}
+
+// Without an entry ID the whole trash is emptied
+#[tauri::command]
+async fn purge_trash(
+    entry_id: Option<String>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<usize, AppError> {
+    let manager = state.lock().await;
+    Ok(manager.purge_trash(entry_id.as_deref())?)
+}
+
+#[tauri::command]
+async fn get_encryption_status(
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<EncryptionStatus, AppError> {
+    let manager = state.lock().await;
+    Ok(manager.encryption_status())
+}
+
+#[tauri::command]
+async fn enable_encryption(
+    passphrase: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.enable_encryption(&passphrase)?)
+}
+
+#[tauri::command]
+async fn unlock_workspace(
+    passphrase: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.unlock_workspace(&passphrase)?)
+}
+
+#[tauri::command]
+async fn disable_encryption(
+    passphrase: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.disable_encryption(&passphrase)?)
+}
+
+#[tauri::command]
+async fn export_project(
+    project_id: String,
+    export_path: String,
+    include_files: bool,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    // Each export gets <name>.manifest.json next to it, for checking copies of it
+    let export_file = PathBuf::from(&export_path);
+    let manifest_path = export_manifest::manifest_path(&export_file);
+
+    let mut manager = state.lock().await;
+    if !include_files {
+        manager.export_project(&project_id, &export_path, false)?;
+        drop(manager);
+        export_manifest::write_manifest(&manifest_path, &[export_file])?;
+        return Ok(());
+    }
+
+    // Copying the media can take a while, don't block other project commands meanwhile
+    let project = manager.get_project(&project_id)?
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?
+        .clone();
+    drop(manager);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        project_archive::export_archive(&project, &export_file, |progress| {
+            let _ = app.emit("project-archive-progress", progress);
+        })?;
+        export_manifest::write_manifest(&manifest_path, &[export_file]).map(|_| ())
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))??;
+    Ok(())
+}
+
+#[tauri::command]
+async fn import_project(
+    import_path: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    if !project_archive::is_archive(Path::new(&import_path)) {
+        return Ok(manager.import_project(&import_path)?);
+    }
+
+    let (project_id, project_dir) = manager.prepare_import();
+    drop(manager);
+
+    let extract_dir = project_dir.clone();
+    let project = tauri::async_runtime::spawn_blocking(move || {
+        project_archive::import_archive(Path::new(&import_path), &extract_dir, |progress| {
+            let _ = app.emit("project-archive-progress", progress);
+        })
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {}", e))?
+    .inspect_err(|_| {
+        let _ = std::fs::remove_dir_all(&project_dir);
+    })?;
+
+    Ok(state.lock().await.finish_import(project_id, project)?)
+}
+
+#[tauri::command]
+async fn merge_import_project(
+    project_id: String,
+    import_path: String,
+    strategy: MergeStrategy,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<MergeSummary, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.merge_import(&project_id, &import_path, strategy)?)
+}
+
+#[tauri::command]
+async fn get_video_notes(
+    project_id: String,
+    video_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.get_video(&project_id, &video_id)?.notes.clone())
+}
+
+#[tauri::command]
+async fn set_video_notes(
+    project_id: String,
+    video_id: String,
+    notes: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.set_video_notes(&project_id, &video_id, notes)?)
+}
+
+#[tauri::command]
+async fn get_nugget_notes(
+    project_id: String,
+    video_id: String,
+    nugget_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Option<String>, AppError> {
+    let mut manager = state.lock().await;
+    let video = manager.get_video(&project_id, &video_id)?;
+    let nugget = video.nuggets.iter()
+        .find(|n| n.id == nugget_id)
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Nugget not found"))?;
+    Ok(nugget.notes.clone())
+}
+
+// An empty string clears the nugget's notes
+#[tauri::command]
+async fn set_nugget_notes(
+    project_id: String,
+    video_id: String,
+    nugget_id: String,
+    notes: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    let update = NuggetUpdate { notes: Some(notes), ..NuggetUpdate::default() };
+    Ok(manager.update_nugget(&project_id, &video_id, &nugget_id, update)?)
+}
+
+// Markdown export of one project video with its SEO metadata and notes
+#[tauri::command]
+async fn export_video_markdown(
+    project_id: String,
+    video_id: String,
+    filepath: String,
+    formatting: Option<ExportFormatting>,
+    filter: Option<ExportFilter>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let file_manager = FileManager::new().with_formatting(formatting)?;
+    let video = {
+        let mut manager = state.lock().await;
+        manager.get_video(&project_id, &video_id)?.clone()
+    };
+    let nuggets = filter_nuggets(&state, Some(&project_id), video.nuggets.clone(), filter.as_ref()).await?;
+
+    let event = FileManager::export_event("markdown", &filepath, nuggets.len());
+    let message = file_manager.export_as_markdown_with_notes(nuggets, video.seo.as_ref(), Some(&video.notes), &filepath).await?;
+    record_export(&state, &project_id, Some(&video_id), event).await;
+    Ok(message)
+}
+
+#[tauri::command]
+async fn get_collections(
+    project_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Vec<NuggetCollection>, AppError> {
+    let mut manager = state.lock().await;
+    let project = manager.get_project(&project_id)?
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?;
+    Ok(project.collections.clone())
+}
+
+#[tauri::command]
+async fn create_collection(
+    project_id: String,
+    name: String,
+    description: Option<String>,
+    items: Option<Vec<NuggetRef>>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.create_collection(&project_id, name, description, items.unwrap_or_default())?)
+}
+
+#[tauri::command]
+async fn update_collection(
+    project_id: String,
+    collection_id: String,
+    name: Option<String>,
+    description: Option<String>,
+    items: Option<Vec<NuggetRef>>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.update_collection(&project_id, &collection_id, name, description, items)?)
+}
+
+#[tauri::command]
+async fn add_to_collection(
+    project_id: String,
+    collection_id: String,
+    items: Vec<NuggetRef>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.add_to_collection(&project_id, &collection_id, items)?)
+}
+
+#[tauri::command]
+async fn delete_collection(
+    project_id: String,
+    collection_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.delete_collection(&project_id, &collection_id)?)
+}
+
+#[tauri::command]
+async fn get_collection_nuggets(
+    project_id: String,
+    collection_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Vec<CollectionNugget>, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.collection_nuggets(&project_id, &collection_id)?)
+}
+
+#[tauri::command]
+async fn search_project_nuggets(
+    project_id: String,
+    query: NuggetQuery,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Vec<CollectionNugget>, AppError> {
+    query.validate()?;
+    let mut manager = state.lock().await;
+    let project = manager.get_project(&project_id)?
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?;
+    Ok(search_nuggets(&project.videos, &query))
+}
+
+#[tauri::command]
+async fn get_smart_collections(
+    project_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Vec<SmartCollection>, AppError> {
+    let mut manager = state.lock().await;
+    let project = manager.get_project(&project_id)?
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?;
+    Ok(project.smart_collections.clone())
+}
+
+#[tauri::command]
+async fn create_smart_collection(
+    project_id: String,
+    name: String,
+    query: NuggetQuery,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.create_smart_collection(&project_id, name, query)?)
+}
+
+#[tauri::command]
+async fn update_smart_collection(
+    project_id: String,
+    collection_id: String,
+    name: Option<String>,
+    query: Option<NuggetQuery>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.update_smart_collection(&project_id, &collection_id, name, query)?)
+}
+
+#[tauri::command]
+async fn delete_smart_collection(
+    project_id: String,
+    collection_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.delete_smart_collection(&project_id, &collection_id)?)
+}
+
+#[tauri::command]
+async fn get_smart_collection_nuggets(
+    project_id: String,
+    collection_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Vec<CollectionNugget>, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.smart_collection_nuggets(&project_id, &collection_id)?)
+}
+
+// A deck named after the project and video, whose cards link back into the video
+#[tauri::command]
+async fn export_video_anki(
+    project_id: String,
+    video_id: String,
+    filepath: String,
+    flashcards: Option<Vec<Flashcard>>,
+    filter: Option<ExportFilter>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let (deck_name, video) = {
+        let mut manager = state.lock().await;
+        let video = manager.get_video(&project_id, &video_id)?.clone();
+        let project = manager.get_project(&project_id)?
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?;
+        (format!("{}::{}", project.name, video.video_info.title), video)
+    };
+
+    let nuggets = filter_nuggets(&state, Some(&project_id), video.nuggets, filter.as_ref()).await?;
+
+    let event = FileManager::export_event("anki", &filepath, nuggets.len());
+    let file_manager = FileManager::new();
+    let message = file_manager.export_as_anki(&deck_name, Some(&video.video_info.url), nuggets, flashcards.unwrap_or_default(), &filepath).await?;
+    record_export(&state, &project_id, Some(&video_id), event).await;
+    Ok(message)
+}
+
+// A single searchable page for the whole project, or for just one of its videos
+#[tauri::command]
+async fn export_project_html(
+    project_id: String,
+    video_id: Option<String>,
+    filepath: String,
+    filter: Option<ExportFilter>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    if let Some(filter) = &filter {
+        filter.validate()?;
+    }
+    let (title, videos) = {
+        let mut manager = state.lock().await;
+        let project = manager.get_project(&project_id)?
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?;
+        let videos: Vec<&VideoProject> = match &video_id {
+            Some(video_id) => vec![project.videos.iter()
+                .find(|v| &v.id == video_id)
+                .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Video not found in project"))?],
+            None => project.videos.iter().collect(),
+        };
+        let title = match (&video_id, videos.first()) {
+            (Some(_), Some(video)) => video.video_info.title.clone(),
+            _ => project.name.clone(),
+        };
+        let selected = filter.as_ref()
+            .map(|filter| filter.selected_ids(&videos.iter().map(|&video| video.clone()).collect::<Vec<_>>()));
+        let videos: Vec<HtmlVideo> = videos.into_iter()
+            .filter_map(|video| {
+                let clips = html_export::nugget_clips(&video.output_files, video.nuggets.len());
+                let (nuggets, clips) = match &selected {
+                    Some(selected) => export_filter::select_paired(&video.nuggets, clips, selected),
+                    None => (video.nuggets.clone(), clips),
+                };
+                if selected.is_some() && nuggets.is_empty() {
+                    return None;
+                }
+                Some(HtmlVideo {
+                    title: video.video_info.title.clone(),
+                    url: video.video_info.url.clone(),
+                    nuggets,
+                    clips,
+                })
+            })
+            .collect();
+        (title, videos)
+    };
+
+    let nugget_count = videos.iter().map(|video| video.nuggets.len()).sum();
+    let event = FileManager::export_event("html", &filepath, nugget_count);
+    let file_manager = FileManager::new();
+    let message = file_manager.export_as_html(&title, videos, &filepath).await?;
+    record_export(&state, &project_id, video_id.as_deref(), event).await;
+    Ok(message)
+}
+
+// With the video's analysis on the cover and its clip thumbnails next to each nugget
+#[tauri::command]
+async fn export_video_pdf(
+    project_id: String,
+    video_id: String,
+    filepath: String,
+    formatting: Option<ExportFormatting>,
+    filter: Option<ExportFilter>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let file_manager = FileManager::new().with_formatting(formatting)?;
+    if let Some(filter) = &filter {
+        filter.validate()?;
+    }
+    let report = {
+        let mut manager = state.lock().await;
+        let video = manager.get_video(&project_id, &video_id)?.clone();
+        let project = manager.get_project(&project_id)?
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?;
+        let thumbnails = pdf_export::nugget_thumbnails(&video.output_files, video.nuggets.len());
+        let (nuggets, thumbnails) = match &filter {
+            Some(filter) => export_filter::select_paired(&video.nuggets, thumbnails, &filter.selected_ids(std::slice::from_ref(&video))),
+            None => (video.nuggets.clone(), thumbnails),
+        };
+        PdfReport {
+            title: video.video_info.title.clone(),
+            subtitle: Some(project.name.clone()),
+            source_url: Some(video.video_info.url.clone()),
+            analysis: video.analysis.clone(),
+            thumbnails,
+            nuggets,
+            formatting: None,
+        }
+    };
+
+    let event = FileManager::export_event("pdf", &filepath, report.nuggets.len());
+    let message = file_manager.export_as_pdf(report, &filepath).await?;
+    record_export(&state, &project_id, Some(&video_id), event).await;
+    Ok(message)
+}
+
+#[tauri::command]
+async fn export_project_obsidian(
+    project_id: String,
+    directory: String,
+    filter: Option<ExportFilter>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut project = {
+        let mut manager = state.lock().await;
+        manager.get_project(&project_id)?
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?
+            .clone()
+    };
+    if let Some(filter) = &filter {
+        filter.validate()?;
+        filter.apply_to_videos(&mut project.videos);
+    }
+
+    let nugget_count = project.videos.iter().map(|video| video.nuggets.len()).sum();
+    let event = FileManager::export_event("obsidian", &directory, nugget_count);
+    let file_manager = FileManager::new();
+    let message = file_manager.export_as_obsidian(project, &directory).await?;
+    record_export(&state, &project_id, None, event).await;
+    Ok(message)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProcessingResult {
-    pub success: bool,
-    pub message: String,
-    pub nuggets: Vec<VideoNugget>,
+#[tauri::command]
+fn get_readwise_token_set() -> Result<bool, AppError> {
+    Ok(readwise_export::token_set()?)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct VideoInfo {
-    pub title: String,
-    pub duration: f64,
-    pub url: String,
-    pub thumbnail: Option<String>,
+// An empty token removes the stored one
+#[tauri::command]
+async fn set_readwise_token(token: String) -> Result<(), AppError> {
+    Ok(readwise_export::save_token(&token).await?)
 }
 
-// Command to extract video information
+// Returns how many highlights were sent
 #[tauri::command]
-async fn get_video_info(url: String) -> Result<VideoInfo, String> {
-    let extractor = YouTubeExtractor::new();
-    extractor.get_video_info(&url).await
+async fn export_video_readwise(
+    project_id: String,
+    video_id: String,
+    filter: Option<ExportFilter>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<usize, AppError> {
+    let mut video = state.lock().await.get_video(&project_id, &video_id)?.clone();
+    if let Some(filter) = &filter {
+        filter.validate()?;
+        video.nuggets = filter.apply(video.nuggets.clone(), std::slice::from_ref(&video));
+    }
+    let highlights = readwise_export::video_highlights(&video);
+
+    let sent = readwise_export::send_highlights(&highlights).await?;
+    let event = FileManager::export_event("readwise", "https://readwise.io", sent);
+    record_export(&state, &project_id, Some(&video_id), event).await;
+    Ok(sent)
 }
 
-// Command to process video and extract nuggets
 #[tauri::command]
-async fn process_video(url: String, config: HashMap<String, serde_json::Value>) -> Result<ProcessingResult, String> {
-    let processor = VideoProcessor::new();
-    processor.process_video(&url, config).await
+fn get_youtube_publisher_status() -> Result<PublisherStatus, AppError> {
+    Ok(youtube_publisher::status()?)
 }
 
-// Command to save nuggets to file
+// Setting a client signs out of the account connected with the previous one
 #[tauri::command]
-async fn save_nuggets(nuggets: Vec<VideoNugget>, filepath: String) -> Result<String, String> {
-    let file_manager = FileManager::new();
-    file_manager.save_nuggets(nuggets, &filepath).await
+fn set_youtube_oauth_client(client_id: String, client_secret: String) -> Result<(), AppError> {
+    Ok(youtube_publisher::set_oauth_client(&client_id, &client_secret)?)
 }
 
-// Command to load nuggets from file
+// Opens Google's sign-in in the browser and resolves once the user is back, or after five minutes
 #[tauri::command]
-async fn load_nuggets(filepath: String) -> Result<Vec<VideoNugget>, String> {
-    let file_manager = FileManager::new();
-    file_manager.load_nuggets(&filepath).await
+async fn connect_youtube_account(app: tauri::AppHandle) -> Result<(), AppError> {
+    let authorization = youtube_publisher::begin_authorization().await?;
+    #[allow(deprecated)]
+    app.shell().open(&authorization.url, None)
+        .map_err(|e| format!("Failed to open the browser: {}", e))?;
+    Ok(authorization.finish().await?)
 }
 
-// Command to export nuggets in different formats
 #[tauri::command]
-async fn export_nuggets(nuggets: Vec<VideoNugget>, format: String, filepath: String) -> Result<String, String> {
-    let file_manager = FileManager::new();
-    match format.as_str() {
-        "json" => file_manager.export_as_json(nuggets, &filepath).await,
-        "csv" => file_manager.export_as_csv(nuggets, &filepath).await,
-        "markdown" => file_manager.export_as_markdown(nuggets, &filepath).await,
-        _ => Err("Unsupported export format".to_string()),
+fn disconnect_youtube_account() -> Result<(), AppError> {
+    Ok(youtube_publisher::sign_out()?)
+}
+
+// Title, description and tags for uploading the nugget, from its title and tags and the video's
+// analysis; the UI shows them for editing before publish_nugget_to_youtube
+#[tauri::command]
+async fn draft_youtube_upload(
+    project_id: String,
+    video_id: String,
+    nugget_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>,
+    ai_state: tauri::State<'_, Arc<Mutex<AIConfigStore>>>
+) -> Result<UploadMetadata, AppError> {
+    let video = state.lock().await.get_video(&project_id, &video_id)?.clone();
+    let nugget = video.nuggets.iter()
+        .find(|nugget| nugget.id == nugget_id)
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Nugget not found"))?;
+    let (caption, suggested_tags) = match &video.analysis {
+        Some(analysis) => {
+            let analyzer = AIAnalyzer::new(ai_state.lock().await.load()?);
+            let mut captions = analyzer.generate_social_media_captions(analysis).await?;
+            (captions.remove("youtube"), analysis.suggested_tags.clone())
+        }
+        None => (None, Vec::new()),
+    };
+    Ok(youtube_publisher::nugget_metadata(nugget, &video.video_info.url, caption.as_deref(), &suggested_tags))
+}
+
+// Uploads a rendered clip of the nugget, a Short from create_social_formats say, and records the
+// new video's ID on the nugget. Without metadata the draft from draft_youtube_upload is used
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn publish_nugget_to_youtube(
+    project_id: String,
+    video_id: String,
+    nugget_id: String,
+    clip_path: String,
+    metadata: Option<UploadMetadata>,
+    operation_id: Option<String>,
+    operations: tauri::State<'_, OperationRegistry>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>,
+    ai_state: tauri::State<'_, Arc<Mutex<AIConfigStore>>>,
+    quota: tauri::State<'_, Arc<ApiQuota>>
+) -> Result<String, AppError> {
+    if !Path::new(&clip_path).exists() {
+        return Err(AppError::new(ErrorCode::NotFound, format!("Clip not found: {}", clip_path)));
     }
+    let metadata = match metadata {
+        Some(metadata) => metadata,
+        None => draft_youtube_upload(project_id.clone(), video_id.clone(), nugget_id.clone(), state.clone(), ai_state).await?,
+    };
+    let publisher = YouTubePublisher::from_keychain()
+        .map_err(|e| AppError::new(ErrorCode::Unauthorized, e).with_hint("Connect a YouTube account in Settings"))?
+        .with_quota(quota.inner().clone());
+
+    let operation = operations.start(operation_id, OperationKind::Upload, &metadata.title)?;
+    operation.stage("Uploading to YouTube");
+    let result = publisher.upload(&clip_path, &metadata).await;
+    operation.finish(&result);
+    let youtube_video_id = result?;
+
+    state.lock().await.set_nugget_youtube_video_id(&project_id, &video_id, &nugget_id, &youtube_video_id)?;
+    Ok(youtube_video_id)
 }
 
-// Command to get application version
 #[tauri::command]
-fn get_app_version() -> String {
-    env!("CARGO_PKG_VERSION").to_string()
+fn get_tiktok_client_set() -> Result<bool, AppError> {
+    Ok(tiktok_publisher::client_set()?)
 }
 
-// Command to open file in default application
+// redirect_port is the one in the redirect URI registered with the TikTok app
 #[tauri::command]
-async fn open_file(filepath: String) -> Result<(), String> {
-    tauri_plugin_shell::ShellExt::open(&tauri_plugin_shell::Shell::default(), &filepath, None)
-        .map_err(|e| format!("Failed to open file: {}", e))
+fn set_tiktok_client(client_key: String, client_secret: String, redirect_port: u16) -> Result<(), AppError> {
+    Ok(tiktok_publisher::set_client(&client_key, &client_secret, redirect_port)?)
 }
 
-// Advanced processing commands
 #[tauri::command]
-async fn process_video_advanced(url: String, config: HashMap<String, serde_json::Value>) -> Result<ProcessingResult, String> {
-    let ffmpeg_processor = FFmpegProcessor::new()?;
-    let speech_recognizer = SpeechRecognizer::new()?;
-    
-    // Download video
-    let video_path = ffmpeg_processor.download_video(&url, "best").await?;
-    let video_info = ffmpeg_processor.get_video_info(&video_path)?;
-    
-    // Extract audio for transcription
-    let audio_path = ffmpeg_processor.extract_audio(&video_path)?;
-    
-    // Get configuration
-    let nugget_duration = config.get("nugget_duration")
-        .and_then(|v| v.as_f64())
-        .unwrap_or(30.0);
-    
-    let overlap_duration = config.get("overlap_duration")
-        .and_then(|v| v.as_f64())
-        .unwrap_or(5.0);
-    
-    let enable_transcript = config.get("enable_transcript")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(true);
-    
-    // Generate nuggets with transcription
-    let mut nuggets = Vec::new();
-    let mut current_time = 0.0;
-    let mut nugget_index = 1;
-
-    while current_time < video_info.duration {
-        let end_time = (current_time + nugget_duration).min(video_info.duration);
-        
-        let transcript = if enable_transcript {
-            speech_recognizer.transcribe_segment(&audio_path, current_time, end_time).await.ok()
-        } else {
-            None
-        };
+fn list_tiktok_accounts(publisher: tauri::State<'_, Arc<TikTokPublisher>>) -> Vec<TikTokAccount> {
+    publisher.list()
+}
 
-        let nugget = VideoNugget {
-            id: uuid::Uuid::new_v4().to_string(),
-            title: format!("{} - Part {}", video_info.title, nugget_index),
-            start_time: current_time,
-            end_time,
-            transcript,
-            tags: vec!["video-nugget".to_string()],
-            created_at: chrono::Utc::now().to_rfc3339(),
-        };
+// Opens TikTok's sign-in in the browser and adds the account the user signs in with
+#[tauri::command]
+async fn connect_tiktok_account(app: tauri::AppHandle, publisher: tauri::State<'_, Arc<TikTokPublisher>>) -> Result<TikTokAccount, AppError> {
+    let authorization = tiktok_publisher::begin_authorization().await?;
+    #[allow(deprecated)]
+    app.shell().open(&authorization.url, None)
+        .map_err(|e| format!("Failed to open the browser: {}", e))?;
+    Ok(publisher.connect(authorization).await?)
+}
 
-        nuggets.push(nugget);
-        current_time = end_time - overlap_duration;
-        
-        if current_time >= video_info.duration - 1.0 {
-            break;
-        }
-        
-        nugget_index += 1;
+#[tauri::command]
+fn remove_tiktok_account(open_id: String, publisher: tauri::State<'_, Arc<TikTokPublisher>>) -> Result<(), AppError> {
+    Ok(publisher.remove(&open_id)?)
+}
+
+// The TikTok caption generate_social_media_captions writes from the video's analysis
+#[tauri::command]
+async fn draft_tiktok_caption(
+    project_id: String,
+    video_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>,
+    ai_state: tauri::State<'_, Arc<Mutex<AIConfigStore>>>
+) -> Result<String, AppError> {
+    let video = state.lock().await.get_video(&project_id, &video_id)?.clone();
+    let analysis = video.analysis.as_ref()
+        .ok_or("Video has no content analysis yet")?;
+    let analyzer = AIAnalyzer::new(ai_state.lock().await.load()?);
+    analyzer.generate_social_media_captions(analysis).await?
+        .remove("tiktok")
+        .ok_or_else(|| AppError::new(ErrorCode::InvalidResponse, "No TikTok caption was generated"))
+}
+
+// Uploads a rendered vertical clip of the video to the account, as a draft in its TikTok inbox
+// unless post.mode says direct. Without a caption the one from draft_tiktok_caption is used.
+// Returns the publish ID for get_tiktok_post_status
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn publish_to_tiktok(
+    open_id: String,
+    project_id: String,
+    video_id: String,
+    clip_path: String,
+    post: Option<TikTokPost>,
+    operation_id: Option<String>,
+    operations: tauri::State<'_, OperationRegistry>,
+    publisher: tauri::State<'_, Arc<TikTokPublisher>>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>,
+    ai_state: tauri::State<'_, Arc<Mutex<AIConfigStore>>>
+) -> Result<String, AppError> {
+    if !Path::new(&clip_path).exists() {
+        return Err(AppError::new(ErrorCode::NotFound, format!("Clip not found: {}", clip_path)));
+    }
+    let mut post = post.unwrap_or_default();
+    if post.caption.trim().is_empty() {
+        post.caption = draft_tiktok_caption(project_id.clone(), video_id.clone(), state.clone(), ai_state).await?;
     }
 
-    Ok(ProcessingResult {
-        success: true,
-        message: format!("Successfully processed video into {} nuggets", nuggets.len()),
-        nuggets,
-    })
+    let operation = operations.start(operation_id, OperationKind::Upload, &clip_path)?;
+    operation.stage("Uploading to TikTok");
+    let result = publisher.post(&open_id, &clip_path, &post).await;
+    operation.finish(&result);
+    let publish_id = result?;
+
+    let event = FileManager::export_event("tiktok", "https://www.tiktok.com", 1);
+    record_export(&state, &project_id, Some(&video_id), event).await;
+    Ok(publish_id)
 }
 
 #[tauri::command]
-async fn extract_transcript(url: String) -> Result<SpeechAnalysis, String> {
-    let ffmpeg_processor = FFmpegProcessor::new()?;
-    let speech_recognizer = SpeechRecognizer::new()?;
-    
-    let video_path = ffmpeg_processor.download_video(&url, "best").await?;
-    let audio_path = ffmpeg_processor.extract_audio(&video_path)?;
-    
-    speech_recognizer.transcribe_audio(&audio_path).await
+async fn get_tiktok_post_status(open_id: String, publish_id: String, publisher: tauri::State<'_, Arc<TikTokPublisher>>) -> Result<String, AppError> {
+    Ok(publisher.post_status(&open_id, &publish_id).await?)
 }
 
+// generator is "hugo" or "jekyll"; directory is the content folder the pages go in
 #[tauri::command]
-async fn analyze_content(transcript: String, title: String, description: Option<String>) -> Result<ContentAnalysis, String> {
-    let ai_config = AIConfig {
-        openai_api_key: None, // Would be configured by user
-        claude_api_key: None,
-        gemini_api_key: None,
-        model_preference: ai_analyzer::AIModel::Local,
-        enable_sentiment_analysis: true,
-        enable_topic_extraction: true,
-        enable_highlight_detection: true,
+async fn export_project_static_site(
+    project_id: String,
+    generator: String,
+    directory: String,
+    filter: Option<ExportFilter>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let generator = SiteGenerator::parse(&generator)?;
+    let mut project = {
+        let mut manager = state.lock().await;
+        manager.get_project(&project_id)?
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?
+            .clone()
     };
-    
-    let analyzer = AIAnalyzer::new(ai_config);
-    analyzer.analyze_content(&transcript, &title, description.as_deref()).await
+    if let Some(filter) = &filter {
+        filter.validate()?;
+        filter.apply_to_videos(&mut project.videos);
+    }
+
+    let nugget_count = project.videos.iter().map(|video| video.nuggets.len()).sum();
+    let format = if generator == SiteGenerator::Hugo { "hugo" } else { "jekyll" };
+    let event = FileManager::export_event(format, &directory, nugget_count);
+    let file_manager = FileManager::new();
+    let message = file_manager.export_as_static_site(project, generator, &directory).await?;
+    record_export(&state, &project_id, None, event).await;
+    Ok(message)
 }
 
+// Same formats as export_nuggets, with the nuggets in collection order
 #[tauri::command]
-async fn generate_subtitles(transcript_segments: Vec<serde_json::Value>, format: String) -> Result<String, String> {
-    // Convert JSON to TranscriptSegment objects
-    let segments: Result<Vec<_>, _> = transcript_segments.iter()
-        .map(|v| serde_json::from_value(v.clone()))
-        .collect();
-    
-    let segments = segments.map_err(|e| format!("Failed to parse transcript segments: {}", e))?;
-    
-    let speech_analysis = SpeechAnalysis {
-        segments,
-        language: "en".to_string(),
-        total_speech_time: 0.0,
-        word_count: 0,
-        average_confidence: 0.0,
-    };
-    
-    let subtitle_format = match format.as_str() {
-        "srt" => SubtitleFormat::SRT,
-        "vtt" => SubtitleFormat::VTT,
-        "ass" => SubtitleFormat::ASS,
-        _ => return Err("Unsupported subtitle format".to_string()),
+async fn export_collection(
+    project_id: String,
+    collection_id: String,
+    format: String,
+    filepath: String,
+    formatting: Option<ExportFormatting>,
+    filter: Option<ExportFilter>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let (nuggets, description) = {
+        let mut manager = state.lock().await;
+        let nuggets = manager.collection_nuggets(&project_id, &collection_id)?;
+        let project = manager.get_project(&project_id)?
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?;
+        let description = project.collections.iter()
+            .find(|c| c.id == collection_id)
+            .and_then(|c| c.description.clone());
+        (nuggets.into_iter().map(|item| item.nugget).collect::<Vec<_>>(), description)
     };
-    
-    let speech_recognizer = SpeechRecognizer::new()?;
-    speech_recognizer.generate_subtitles(&speech_analysis, subtitle_format).await
+    let nuggets = filter_nuggets(&state, Some(&project_id), nuggets, filter.as_ref()).await?;
+
+    let mut event = FileManager::export_event(&format, &filepath, nuggets.len());
+    event.parameters.insert("collection_id".to_string(), serde_json::json!(collection_id));
+    let message = write_export(nuggets, &format, &filepath, None, description.as_deref(), None, formatting).await?;
+    record_export(&state, &project_id, None, event).await;
+    Ok(message)
 }
 
 #[tauri::command]
-async fn create_social_formats(video_path: String) -> Result<serde_json::Value, String> {
-    let ffmpeg_processor = FFmpegProcessor::new()?;
-    let formats = ffmpeg_processor.create_social_media_formats(&video_path)?;
-    
-    Ok(serde_json::to_value(formats)
-        .map_err(|e| format!("Failed to serialize formats: {}", e))?)
+async fn get_project_stats(
+    project_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<ProjectStats, AppError> {
+    let mut manager = state.lock().await;
+    let project = manager.get_project(&project_id)?
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?;
+    Ok(project_stats(project))
 }
 
-// Batch processing commands
 #[tauri::command]
-async fn create_batch_job(
-    name: String,
-    urls: Vec<String>,
-    config: serde_json::Value,
-    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
-) -> Result<String, String> {
-    let batch_config: BatchConfig = serde_json::from_value(config)
-        .map_err(|e| format!("Invalid batch config: {}", e))?;
-    
-    let mut processor = state.lock().await;
-    Ok(processor.create_batch_job(name, urls, batch_config))
+async fn get_activity(
+    project_id: String,
+    filter: Option<ActivityFilter>,
+    page: Option<usize>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<ActivityPage, AppError> {
+    let mut manager = state.lock().await;
+    let project = manager.get_project(&project_id)?
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?;
+    Ok(query_activity(project, &filter.unwrap_or_default(), page.unwrap_or(0))?)
 }
 
 #[tauri::command]
-async fn start_batch_job(
-    job_id: String,
-    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
-) -> Result<(), String> {
-    let mut processor = state.lock().await;
-    processor.start_batch_job(&job_id).await
+async fn get_storage_breakdown(
+    project_id: String,
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<StorageBreakdown, AppError> {
+    let mut manager = state.lock().await;
+    let project = manager.get_project(&project_id)?
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "Project not found"))?;
+    Ok(storage_breakdown(project, limit.unwrap_or(20)))
 }
 
 #[tauri::command]
-async fn get_batch_job_status(
-    job_id: String,
-    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
-) -> Result<Option<BatchJob>, String> {
-    let processor = state.lock().await;
-    Ok(processor.get_batch_job(&job_id).cloned())
+async fn get_workspace_path(state: tauri::State<'_, Arc<Mutex<ProjectManager>>>) -> Result<String, AppError> {
+    let manager = state.lock().await;
+    Ok(manager.workspace_root().to_string_lossy().to_string())
 }
 
 #[tauri::command]
-async fn cancel_batch_job(
-    job_id: String,
-    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
-) -> Result<(), String> {
-    let mut processor = state.lock().await;
-    processor.cancel_batch_job(&job_id)
+#[allow(clippy::too_many_arguments)]
+async fn move_workspace(
+    new_path: String,
+    project_state: tauri::State<'_, Arc<Mutex<ProjectManager>>>,
+    batch_state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>,
+    ai_config_state: tauri::State<'_, Arc<Mutex<AIConfigStore>>>,
+    notification_state: tauri::State<'_, Arc<Mutex<NotificationStore>>>,
+    settings_state: tauri::State<'_, Arc<Mutex<AppSettingsStore>>>,
+    journal: tauri::State<'_, OperationJournal>,
+    youtube_quota: tauri::State<'_, Arc<ApiQuota>>,
+    youtube_cache: tauri::State<'_, ResponseCache>,
+    channel_watcher: tauri::State<'_, Arc<ChannelWatcher>>,
+    tiktok_publisher: tauri::State<'_, Arc<TikTokPublisher>>
+) -> Result<(), AppError> {
+    // Held for the whole move so no job starts writing into the old workspace
+    let mut processor = batch_state.lock().await;
+    if processor.has_running_jobs() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Cannot move the workspace while a batch job is running"));
+    }
+
+    let new_root = PathBuf::from(new_path);
+    let mut manager = project_state.lock().await;
+    manager.move_workspace(&new_root)?;
+
+    processor.relocate(new_root.join("batch_jobs"), new_root.clone());
+    *ai_config_state.lock().await = AIConfigStore::new(new_root.clone());
+    *notification_state.lock().await = NotificationStore::new(new_root.clone());
+    journal.relocate(new_root.join("operations"));
+    // The batch processor and the channel check share these
+    youtube_quota.relocate(new_root.join("youtube_quota.json"));
+    youtube_cache.relocate(new_root.join("cache").join("youtube"));
+    channel_watcher.relocate(new_root.join("watched_channels.json"));
+    tiktok_publisher.relocate(new_root.join("tiktok_accounts.json"));
+
+    let settings_store = settings_state.lock().await;
+    let mut settings = settings_store.load()?;
+    settings.workspace_path = Some(new_root);
+    settings_store.save(&settings)?;
+    app_settings::apply(settings);
+    Ok(())
 }
 
 #[tauri::command]
-async fn list_batch_jobs(
-    state: tauri::State<'_, Arc<Mutex<BatchProcessor>>>
-) -> Result<Vec<BatchJob>, String> {
-    let processor = state.lock().await;
-    Ok(processor.list_batch_jobs().into_iter().cloned().collect())
+async fn get_settings(settings_state: tauri::State<'_, Arc<Mutex<AppSettingsStore>>>) -> Result<AppSettings, AppError> {
+    Ok(settings_state.lock().await.load()?)
 }
 
-// Project management commands
+// The workspace path is left as it is, move_workspace changes it. Returns the saved settings
 #[tauri::command]
-async fn create_project(
-    name: String,
-    description: Option<String>,
-    template_id: Option<String>,
+async fn update_settings(
+    settings: AppSettings,
+    settings_state: tauri::State<'_, Arc<Mutex<AppSettingsStore>>>,
+    processors: tauri::State<'_, Arc<Processors>>,
+    progress_stream: tauri::State<'_, Arc<ProgressStream>>,
+    operations: tauri::State<'_, OperationRegistry>
+) -> Result<AppSettings, AppError> {
+    let settings = settings_state.lock().await.update(settings)?;
+    app_settings::apply(settings.clone());
+    // The tool paths may have changed
+    processors.reset();
+    progress_stream.apply(settings.progress_stream.enabled, settings.progress_stream.port, operations.inner().clone()).await?;
+    Ok(settings)
+}
+
+#[tauri::command]
+fn get_verify_writes() -> bool {
+    atomic_file::verify_writes()
+}
+
+#[tauri::command]
+async fn set_verify_writes(
+    enabled: bool,
+    settings_state: tauri::State<'_, Arc<Mutex<AppSettingsStore>>>
+) -> Result<(), AppError> {
+    let settings_store = settings_state.lock().await;
+    let mut settings = settings_store.load()?;
+    settings.verify_writes = enabled;
+    settings_store.save(&settings)?;
+    app_settings::apply(settings);
+    Ok(())
+}
+
+#[tauri::command]
+async fn move_project(
+    project_id: String,
+    destination: String,
     state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut manager = state.lock().await;
-    manager.create_project(name, description, template_id)
+    let project_dir = manager.move_project(&project_id, Path::new(&destination))?;
+    Ok(project_dir.to_string_lossy().to_string())
 }
 
 #[tauri::command]
-async fn add_video_to_project(
+async fn find_duplicate_videos(
+    url: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Vec<DuplicateVideo>, AppError> {
+    let manager = state.lock().await;
+    Ok(manager.find_duplicate_videos(&url)?)
+}
+
+#[tauri::command]
+async fn link_existing_video(
     project_id: String,
-    video_info: VideoInfo,
-    nuggets: Vec<VideoNugget>,
-    analysis: Option<ContentAnalysis>,
+    source_project_id: String,
+    video_id: String,
     state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut manager = state.lock().await;
-    manager.add_video_to_project(&project_id, video_info, nuggets, analysis)
+    Ok(manager.link_existing_video(&project_id, &source_project_id, &video_id)?)
 }
 
 #[tauri::command]
-async fn get_project(
+async fn remove_video_from_project(
     project_id: String,
+    video_id: String,
+    delete_files: bool,
     state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
-) -> Result<Option<Project>, String> {
-    let manager = state.lock().await;
-    Ok(manager.get_project(&project_id).cloned())
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.delete_video(&project_id, &video_id, delete_files)?)
 }
 
 #[tauri::command]
-async fn list_projects(
+async fn archive_video(
+    project_id: String,
+    video_id: String,
+    archived: bool,
+    delete_files: bool,
     state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
-) -> Result<Vec<Project>, String> {
-    let manager = state.lock().await;
-    Ok(manager.list_projects().into_iter().cloned().collect())
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.archive_video(&project_id, &video_id, archived, delete_files)?)
 }
 
 #[tauri::command]
-async fn update_project_settings(
+async fn delete_project_nuggets(
     project_id: String,
-    settings: serde_json::Value,
+    video_id: String,
+    nugget_ids: Vec<String>,
     state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
-) -> Result<(), String> {
-    let settings = serde_json::from_value(settings)
-        .map_err(|e| format!("Invalid project settings: {}", e))?;
-    
+) -> Result<usize, AppError> {
     let mut manager = state.lock().await;
-    manager.update_project_settings(&project_id, settings)
+    Ok(manager.delete_nuggets(&project_id, &video_id, &nugget_ids)?)
 }
 
 #[tauri::command]
-async fn delete_project(
+async fn update_nugget(
     project_id: String,
+    video_id: String,
+    nugget_id: String,
+    update: NuggetUpdate,
     state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut manager = state.lock().await;
-    manager.delete_project(&project_id)
+    Ok(manager.update_nugget(&project_id, &video_id, &nugget_id, update)?)
 }
 
 #[tauri::command]
-async fn export_project(
+async fn split_nugget(
+    project_id: String,
+    video_id: String,
+    nugget_id: String,
+    at: f64,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.split_nugget(&project_id, &video_id, &nugget_id, at)?)
+}
+
+#[tauri::command]
+async fn merge_nuggets(
+    project_id: String,
+    video_id: String,
+    first_id: String,
+    second_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.merge_nuggets(&project_id, &video_id, &first_id, &second_id)?)
+}
+
+#[tauri::command]
+async fn reorder_nuggets(
+    project_id: String,
+    video_id: String,
+    nugget_ids: Vec<String>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.reorder_nuggets(&project_id, &video_id, &nugget_ids)?)
+}
+
+// Reads an SRT/VTT file or a chapter list, e.g. copied from a YouTube description into a text file
+#[tauri::command]
+async fn import_nuggets(
     project_id: String,
+    video_id: String,
+    filepath: String,
+    replace: Option<bool>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<usize, AppError> {
+    let content = tokio::fs::read_to_string(&filepath)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", filepath, e))?;
+    let mut manager = state.lock().await;
+    Ok(manager.import_nuggets(&project_id, &video_id, content.trim_start_matches('\u{feff}'), replace.unwrap_or(false))?)
+}
+
+#[tauri::command]
+async fn undo_last_change(
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Option<String>, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.undo_last_change()?)
+}
+
+#[tauri::command]
+async fn redo(
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Option<String>, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.redo()?)
+}
+
+#[tauri::command]
+async fn get_templates(
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Vec<ProjectTemplate>, AppError> {
+    let manager = state.lock().await;
+    Ok(manager.get_templates().to_vec())
+}
+
+#[tauri::command]
+async fn save_project_template(
+    template: ProjectTemplate,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.save_template(template)?)
+}
+
+#[tauri::command]
+async fn delete_project_template(
+    template_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.delete_template(&template_id)?)
+}
+
+#[tauri::command]
+async fn export_project_template(
+    template_id: String,
     export_path: String,
-    include_files: bool,
     state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let manager = state.lock().await;
-    manager.export_project(&project_id, &export_path, include_files)
+    Ok(manager.export_template(&template_id, &export_path)?)
 }
 
 #[tauri::command]
-async fn import_project(
+async fn import_project_template(
     import_path: String,
     state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
-) -> Result<String, String> {
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.import_template(&import_path)?)
+}
+
+#[tauri::command]
+async fn create_project_backup(
+    project_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.create_backup(&project_id)?)
+}
+
+#[tauri::command]
+async fn list_project_backups(
+    project_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Vec<BackupInfo>, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.list_backups(&project_id)?)
+}
+
+#[tauri::command]
+async fn restore_from_backup(
+    project_id: String,
+    backup_path: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<String, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.restore_from_backup(&project_id, &backup_path)?)
+}
+
+#[tauri::command]
+async fn list_project_versions(
+    project_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<Vec<VersionInfo>, AppError> {
     let mut manager = state.lock().await;
-    manager.import_project(&import_path)
+    Ok(manager.list_versions(&project_id)?)
+}
+
+#[tauri::command]
+async fn diff_project_versions(
+    project_id: String,
+    from_version: String,
+    to_version: Option<String>,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<VersionDiff, AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.diff_versions(&project_id, &from_version, to_version.as_deref())?)
+}
+
+#[tauri::command]
+async fn rollback_project(
+    project_id: String,
+    version_id: String,
+    state: tauri::State<'_, Arc<Mutex<ProjectManager>>>
+) -> Result<(), AppError> {
+    let mut manager = state.lock().await;
+    Ok(manager.rollback_to_version(&project_id, &version_id)?)
 }
 
 fn main() {
     tauri::Builder::default()
+        // First, so a second launch hands its link to the running app and exits. With the
+        // deep-link feature the link reaches on_open_url below, this only brings the window up
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| focus_main_window(app)))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .invoke_handler(tauri::generate_handler![
             get_video_info,
             process_video,
             save_nuggets,
             load_nuggets,
+            copy_export_to_clipboard,
+            unwatch_nugget_file,
             export_nuggets,
             get_app_version,
             open_file,
+            reveal_in_file_manager,
             // Advanced processing commands
             process_video_advanced,
             extract_transcript,
             analyze_content,
+            get_ai_config,
+            set_ai_config,
+            get_api_key_set,
+            set_api_key,
+            clear_api_key,
+            test_api_key,
+            validate_youtube_api_key,
+            get_notification_settings,
+            set_notification_settings,
+            send_test_email,
+            list_available_models,
+            detect_highlights,
+            get_sentiment_timeline,
+            score_nugget_virality,
+            moderate_nuggets,
+            suggest_title_cards,
+            suggest_flashcards,
+            render_title_card,
             generate_subtitles,
             create_social_formats,
             // Batch processing commands
             create_batch_job,
+            create_batch_pipeline,
+            add_batch_job_dependency,
+            set_batch_job_priority,
+            create_batch_from_playlist,
+            create_batch_from_trending,
+            create_batch_from_channel,
+            set_batch_url_override,
             start_batch_job,
+            list_operations,
+            cancel_operation,
+            list_interrupted_operations,
+            detect_capabilities,
+            run_workflow,
+            download_video,
+            get_video_chapters,
+            list_caption_tracks,
+            get_chapter_nuggets,
+            search_youtube_videos,
+            get_youtube_channel_videos,
+            get_trending_youtube_videos,
+            get_youtube_categories,
+            get_api_quota_usage,
+            get_video_comments,
+            get_playlist_items,
+            watch_channel,
+            unwatch_channel,
+            list_watched_channels,
+            check_watched_channels,
+            discard_interrupted_operation,
+            get_progress_stream_url,
+            take_pending_deep_links,
+            get_recent_logs,
+            set_log_level,
             get_batch_job_status,
             cancel_batch_job,
+            pause_batch_job,
+            resume_batch_job,
+            retry_failed_videos,
+            estimate_batch_disk_space,
+            export_batch_report,
+            bundle_batch_output,
+            bundle_export_files,
+            get_resource_policy,
+            set_resource_policy,
             list_batch_jobs,
             // Project management commands
             create_project,
@@ -399,24 +2788,204 @@ fn main() {
             get_project,
             list_projects,
             update_project_settings,
+            generate_seo_metadata,
+            list_prompt_templates,
+            save_prompt_template,
+            reset_prompt_templates,
             delete_project,
+            clone_project,
+            list_trash,
+            restore_from_trash,
+            purge_trash,
+            get_encryption_status,
+            enable_encryption,
+            unlock_workspace,
+            disable_encryption,
             export_project,
-            import_project
+            import_project,
+            merge_import_project,
+            get_templates,
+            save_project_template,
+            delete_project_template,
+            export_project_template,
+            import_project_template,
+            create_project_backup,
+            list_project_backups,
+            restore_from_backup,
+            list_project_versions,
+            diff_project_versions,
+            rollback_project,
+            get_project_stats,
+            get_activity,
+            get_collections,
+            create_collection,
+            update_collection,
+            add_to_collection,
+            delete_collection,
+            get_collection_nuggets,
+            export_collection,
+            search_project_nuggets,
+            get_smart_collections,
+            create_smart_collection,
+            update_smart_collection,
+            delete_smart_collection,
+            get_smart_collection_nuggets,
+            get_storage_breakdown,
+            get_workspace_path,
+            move_workspace,
+            move_project,
+            get_video_notes,
+            set_video_notes,
+            get_nugget_notes,
+            set_nugget_notes,
+            export_video_markdown,
+            export_video_anki,
+            export_project_obsidian,
+            export_project_static_site,
+            get_readwise_token_set,
+            set_readwise_token,
+            export_video_readwise,
+            get_youtube_publisher_status,
+            set_youtube_oauth_client,
+            connect_youtube_account,
+            disconnect_youtube_account,
+            draft_youtube_upload,
+            publish_nugget_to_youtube,
+            get_tiktok_client_set,
+            set_tiktok_client,
+            list_tiktok_accounts,
+            connect_tiktok_account,
+            remove_tiktok_account,
+            draft_tiktok_caption,
+            publish_to_tiktok,
+            get_tiktok_post_status,
+            export_video_pdf,
+            export_project_html,
+            find_duplicate_videos,
+            link_existing_video,
+            remove_video_from_project,
+            archive_video,
+            delete_project_nuggets,
+            update_nugget,
+            split_nugget,
+            merge_nuggets,
+            reorder_nuggets,
+            import_nuggets,
+            get_verify_writes,
+            set_verify_writes,
+            get_settings,
+            update_settings,
+            undo_last_change,
+            redo
         ])
         .setup(|app| {
+            // Before anything that might log
+            if let Err(e) = logging::init(&app.path().app_data_dir()?.join("logs")) {
+                eprintln!("{}", e);
+            }
+
             // Initialize application state
-            let workspace_path = std::env::current_dir()
-                .unwrap_or_else(|_| std::path::PathBuf::from("."))
-                .join("workspace");
+            let settings_store = AppSettingsStore::new(app.path().app_config_dir()?);
+            let workspace_path = settings_store.workspace_path(&app.path().app_data_dir()?);
+            match settings_store.load() {
+                Ok(settings) => app_settings::apply(settings),
+                Err(e) => tracing::warn!("Failed to load app settings: {}", e),
+            }
             
-            let project_manager = ProjectManager::new(workspace_path)
+            let ai_config_store = Arc::new(Mutex::new(AIConfigStore::new(workspace_path.clone())));
+            let notification_store = Arc::new(Mutex::new(NotificationStore::new(workspace_path.clone())));
+            let batch_jobs_path = workspace_path.join("batch_jobs");
+            let operations_path = workspace_path.join("operations");
+            let watched_channels_path = workspace_path.join("watched_channels.json");
+            let tiktok_accounts_path = workspace_path.join("tiktok_accounts.json");
+            let app_handle = app.handle().clone();
+            let youtube_quota = Arc::new(ApiQuota::new(workspace_path.join("youtube_quota.json")).with_warning(move |usage| {
+                let _ = app_handle.emit(youtube_quota::QUOTA_WARNING_EVENT, usage);
+            }));
+            let youtube_cache = ResponseCache::new(workspace_path.join("cache").join("youtube"));
+            let projects_root = workspace_path.clone();
+            let mut project_manager = ProjectManager::new(workspace_path)
                 .expect("Failed to initialize project manager");
+            if let Err(e) = project_manager.load_projects() {
+                tracing::error!("Failed to load projects: {}", e);
+            }
+            if let Err(e) = project_manager.load_templates() {
+                tracing::warn!("Failed to load project templates: {}", e);
+            }
+            let project_manager = Arc::new(Mutex::new(project_manager));
+            let processors = Arc::new(Processors::new());
+            let circuit_breakers = CircuitBreakers::default();
             
-            let batch_processor = BatchProcessor::new(None)
+            let mut batch_processor = BatchProcessor::new()
+                .and_then(|processor| processor.with_storage(batch_jobs_path))
+                .map(|processor| processor.with_projects_root(projects_root))
+                .map(|processor| processor.with_project_manager(project_manager.clone()))
+                .map(|processor| processor.with_processors(processors.clone()))
+                .map(|processor| processor.with_circuit_breakers(circuit_breakers.clone()))
+                .map(|processor| processor.with_ai_config_store(ai_config_store.clone()))
+                .map(|processor| processor.with_youtube(youtube_quota.clone(), youtube_cache.clone()))
+                .map(|processor| processor.with_completion_hook(batch_completion_notifier(app.handle().clone(), notification_store.clone())))
                 .expect("Failed to initialize batch processor");
+            if let Err(e) = batch_processor.load_jobs() {
+                tracing::warn!("Failed to restore batch jobs: {}", e);
+            }
             
-            app.manage(Arc::new(Mutex::new(project_manager)));
+            app.manage(project_manager);
             app.manage(Arc::new(Mutex::new(batch_processor)));
+            app.manage(ai_config_store);
+            app.manage(notification_store);
+            app.manage(Arc::new(Mutex::new(settings_store)));
+            app.manage(processors);
+            app.manage(circuit_breakers);
+
+            let app_handle = app.handle().clone();
+            let nugget_watcher = NuggetWatcher::new(move |path| {
+                let _ = app_handle.emit("nugget-file-changed", path.to_string_lossy().to_string());
+            })
+            .expect("Failed to initialize nugget file watcher");
+            app.manage(Arc::new(Mutex::new(nugget_watcher)));
+
+            let app_handle = app.handle().clone();
+            let progress_stream = Arc::new(ProgressStream::new());
+            let publisher = progress_stream.clone();
+            let operation_registry = OperationRegistry::new(move |progress| {
+                let _ = app_handle.emit(operations::PROGRESS_EVENT, progress);
+                publisher.publish(progress);
+            });
+            let stream_settings = app_settings::current().progress_stream;
+            if let Err(e) = tauri::async_runtime::block_on(progress_stream.apply(stream_settings.enabled, stream_settings.port, operation_registry.clone())) {
+                tracing::warn!("Failed to start progress stream: {}", e);
+            }
+            app.manage(operation_registry);
+            app.manage(progress_stream);
+            app.manage(OperationJournal::new(operations_path));
+            let channel_watcher = Arc::new(ChannelWatcher::new(watched_channels_path));
+            tauri::async_runtime::spawn(check_channels_periodically(app.handle().clone(), channel_watcher.clone(), youtube_quota.clone(), youtube_cache.clone()));
+            app.manage(channel_watcher);
+            app.manage(Arc::new(TikTokPublisher::new(tiktok_accounts_path)));
+            app.manage(youtube_quota);
+            app.manage(youtube_cache);
+
+            app.manage(PendingDeepLinks::default());
+            // Installers register the scheme; Linux and Windows dev builds have to do it themselves
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            {
+                if let Err(e) = app.deep_link().register_all() {
+                    tracing::warn!("Failed to register the {} link scheme: {}", deep_link::SCHEME, e);
+                }
+            }
+            let app_handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    receive_deep_link(&app_handle, url.as_str());
+                }
+            });
+            // The link the app was launched with, if any
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    receive_deep_link(app.handle(), url.as_str());
+                }
+            }
             
             Ok(())
         })
@@ -0,0 +1,275 @@
+use crate::ai_config_store::{read_secret, write_secret};
+use crate::batch_processor::{BatchJob, BatchStatus, ProcessingStatus};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Serialize, Deserialize};
+use std::path::PathBuf;
+
+const SMTP_PASSWORD_ENTRY: &str = "smtp_password";
+// Enough to see what went wrong without turning the email into the full report
+const MAX_LISTED_FAILURES: usize = 10;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotificationSettings {
+    pub desktop: bool,
+    // Batches with failures always notify, clean runs only when this is set
+    pub notify_on_success: bool,
+    pub email: Option<EmailSettings>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            desktop: true,
+            notify_on_success: true,
+            email: None,
+        }
+    }
+}
+
+impl NotificationSettings {
+    pub fn should_notify(&self, job: &BatchJob) -> bool {
+        (self.desktop || self.email.is_some())
+            && (self.notify_on_success || job.status == BatchStatus::Failed || job.progress.failed_videos > 0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EmailSettings {
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub security: SmtpSecurity,
+    // The password lives in the OS keychain, see NotificationStore::save
+    pub username: Option<String>,
+    pub from_address: String,
+    pub to_address: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum SmtpSecurity {
+    // Usually port 587
+    StartTls,
+    // Usually port 465
+    Tls,
+    // Plain text, only for a relay on localhost
+    None,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationSettingsView {
+    #[serde(flatten)]
+    pub settings: NotificationSettings,
+    pub smtp_password_set: bool,
+}
+
+pub struct BatchSummary {
+    pub title: String,
+    // One line for the desktop notification
+    pub short: String,
+    // The email body
+    pub details: String,
+}
+
+impl BatchSummary {
+    pub fn from_job(job: &BatchJob) -> Self {
+        let progress = &job.progress;
+        let skipped = job.results.iter()
+            .filter(|r| r.status == ProcessingStatus::Skipped)
+            .count();
+        let nuggets: usize = job.results.iter().map(|r| r.nuggets.len()).sum();
+
+        let title = if job.status == BatchStatus::Failed {
+            format!("Batch \"{}\" failed", job.name)
+        } else if progress.failed_videos > 0 {
+            format!("Batch \"{}\" finished with {} failed videos", job.name, progress.failed_videos)
+        } else {
+            format!("Batch \"{}\" completed", job.name)
+        };
+        let short = format!("{} of {} videos processed, {} failed, {} skipped, {} nuggets",
+            progress.processed_videos, progress.total_videos, progress.failed_videos, skipped, nuggets);
+
+        let mut details = format!("{}\n\n{}\n", title, short);
+        if let (Some(started), Some(completed)) = (&job.started_at, &job.completed_at) {
+            details.push_str(&format!("Started {}, finished {}\n", started, completed));
+        }
+
+        let failures: Vec<_> = job.results.iter()
+            .filter(|r| r.status == ProcessingStatus::Failed)
+            .collect();
+        if !failures.is_empty() {
+            details.push_str("\nFailed videos:\n");
+            for failure in failures.iter().take(MAX_LISTED_FAILURES) {
+                details.push_str(&format!("- {}: {}\n", failure.url,
+                    failure.error_message.as_deref().unwrap_or("Unknown error")));
+            }
+            if failures.len() > MAX_LISTED_FAILURES {
+                details.push_str(&format!("- and {} more\n", failures.len() - MAX_LISTED_FAILURES));
+            }
+        }
+
+        if !job.warnings.is_empty() {
+            details.push_str("\nWarnings:\n");
+            for warning in &job.warnings {
+                details.push_str(&format!("- {}\n", warning));
+            }
+        }
+
+        Self { title, short, details }
+    }
+}
+
+pub struct NotificationStore {
+    settings_path: PathBuf,
+}
+
+impl NotificationStore {
+    pub fn new(workspace_root: PathBuf) -> Self {
+        Self {
+            settings_path: workspace_root.join("notification_settings.json"),
+        }
+    }
+
+    pub fn load(&self) -> Result<NotificationSettings, String> {
+        if !self.settings_path.exists() {
+            return Ok(NotificationSettings::default());
+        }
+
+        let content = std::fs::read_to_string(&self.settings_path)
+            .map_err(|e| format!("Failed to read notification settings: {}", e))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse notification settings: {}", e))
+    }
+
+    pub fn view(&self) -> Result<NotificationSettingsView, String> {
+        Ok(NotificationSettingsView {
+            settings: self.load()?,
+            smtp_password_set: read_secret(SMTP_PASSWORD_ENTRY)?.is_some(),
+        })
+    }
+
+    // A password of None leaves the stored one untouched, an empty string removes it
+    pub fn save(&self, settings: NotificationSettings, smtp_password: Option<String>) -> Result<(), String> {
+        if let Some(email) = &settings.email {
+            email.from_address.parse::<lettre::Address>()
+                .map_err(|e| format!("Invalid sender address: {}", e))?;
+            email.to_address.parse::<lettre::Address>()
+                .map_err(|e| format!("Invalid recipient address: {}", e))?;
+        }
+
+        if let Some(parent) = self.settings_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let content = serde_json::to_string_pretty(&settings)
+            .map_err(|e| format!("Failed to serialize notification settings: {}", e))?;
+
+        std::fs::write(&self.settings_path, content)
+            .map_err(|e| format!("Failed to save notification settings: {}", e))?;
+
+        write_secret(SMTP_PASSWORD_ENTRY, smtp_password.as_deref())
+    }
+
+    pub async fn send_email(&self, subject: &str, body: &str) -> Result<(), String> {
+        let email = self.load()?.email
+            .ok_or("Email notifications are not configured")?;
+        let password = read_secret(SMTP_PASSWORD_ENTRY)?;
+
+        let message = Message::builder()
+            .from(email.from_address.parse().map_err(|e| format!("Invalid sender address: {}", e))?)
+            .to(email.to_address.parse().map_err(|e| format!("Invalid recipient address: {}", e))?)
+            .subject(subject)
+            .body(body.to_string())
+            .map_err(|e| format!("Failed to build email: {}", e))?;
+
+        let mut transport = match email.security {
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&email.smtp_host),
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&email.smtp_host),
+            SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&email.smtp_host)),
+        }
+        .map_err(|e| format!("Failed to set up SMTP connection: {}", e))?
+        .port(email.smtp_port);
+
+        if let Some(username) = email.username {
+            transport = transport.credentials(Credentials::new(username, password.unwrap_or_default()));
+        }
+
+        transport.build()
+            .send(message)
+            .await
+            .map_err(|e| format!("Failed to send email: {}", e))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch_processor::{BatchConfig, BatchResult};
+
+    fn finished_job(statuses: &[ProcessingStatus]) -> BatchJob {
+        let config: BatchConfig = serde_json::from_value(serde_json::json!({
+            "video_config": {},
+            "output_directory": "/tmp/out",
+            "export_formats": [],
+            "enable_ai_analysis": false,
+            "enable_transcript": false,
+            "enable_social_formats": false,
+            "concurrent_jobs": 1,
+            "retry_failed": false,
+            "max_retries": 0
+        })).unwrap();
+
+        let mut job: BatchJob = serde_json::from_value(serde_json::json!({
+            "id": "job-1",
+            "name": "Weekly uploads",
+            "urls": [],
+            "config": config,
+            "status": "Completed",
+            "progress": {
+                "total_videos": statuses.len(),
+                "processed_videos": statuses.len(),
+                "failed_videos": statuses.iter().filter(|s| **s == ProcessingStatus::Failed).count(),
+                "current_video": null,
+                "percentage": 100.0,
+                "eta_minutes": null,
+                "start_time": null
+            },
+            "results": [],
+            "created_at": "2024-01-01T00:00:00Z",
+            "started_at": null,
+            "completed_at": null
+        })).unwrap();
+
+        for (index, status) in statuses.iter().enumerate() {
+            job.results.push(BatchResult {
+                url: format!("https://youtu.be/video{}", index),
+                video_info: None,
+                nuggets: Vec::new(),
+                analysis: None,
+                output_files: Vec::new(),
+                events: Vec::new(),
+                status: status.clone(),
+                error_message: (*status == ProcessingStatus::Failed).then(|| "Download failed".to_string()),
+                processing_time_seconds: 1.0,
+            });
+        }
+        job
+    }
+
+    #[test]
+    fn test_summary_lists_failures() {
+        let job = finished_job(&[ProcessingStatus::Success, ProcessingStatus::Failed, ProcessingStatus::Skipped]);
+        let summary = BatchSummary::from_job(&job);
+
+        assert_eq!(summary.title, "Batch \"Weekly uploads\" finished with 1 failed videos");
+        assert_eq!(summary.short, "3 of 3 videos processed, 1 failed, 1 skipped, 0 nuggets");
+        assert!(summary.details.contains("- https://youtu.be/video1: Download failed"));
+
+        let quiet = NotificationSettings { notify_on_success: false, ..NotificationSettings::default() };
+        assert!(quiet.should_notify(&job));
+        assert!(!quiet.should_notify(&finished_job(&[ProcessingStatus::Success])));
+    }
+}
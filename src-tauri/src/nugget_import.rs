@@ -0,0 +1,161 @@
+use crate::VideoNugget;
+use regex::Regex;
+use uuid::Uuid;
+
+// When the video's duration isn't known there's nothing to end the last chapter at
+const LAST_CHAPTER_FALLBACK_SECONDS: f64 = 60.0;
+
+// SRT and WebVTT files become a nugget per cue, titled with the cue's text. Anything else is
+// read as a chapter list, a timestamp and a title per line, the way YouTube descriptions have
+// them; each chapter runs until the next one starts and the last one until the end of the video.
+pub fn parse_nuggets(content: &str, video_duration: f64) -> Result<Vec<VideoNugget>, String> {
+    let nuggets = if content.contains("-->") {
+        parse_subtitles(content)
+    } else {
+        parse_chapters(content, video_duration)
+    };
+
+    if nuggets.is_empty() {
+        return Err("No chapters or subtitle cues found".to_string());
+    }
+    Ok(nuggets)
+}
+
+fn parse_subtitles(content: &str) -> Vec<VideoNugget> {
+    parse_cues(content).into_iter()
+        .map(|cue| new_nugget(cue.lines.join(" "), cue.start_time, cue.end_time))
+        .collect()
+}
+
+// An SRT or WebVTT cue, its text line by line with the markup taken out
+pub struct SubtitleCue {
+    pub start_time: f64,
+    pub end_time: f64,
+    pub lines: Vec<String>,
+}
+
+// Cues without text or with an end before their start are skipped
+pub fn parse_cues(content: &str) -> Vec<SubtitleCue> {
+    let markup = Regex::new(r"<[^>]*>|\{[^}]*\}").unwrap();
+    let content = content.replace("\r\n", "\n");
+
+    content.split("\n\n")
+        .filter_map(|block| {
+            let mut lines = block.lines().map(str::trim).skip_while(|line| !line.contains("-->"));
+            let (start, end) = lines.next()?.split_once("-->")?;
+            // WebVTT cue settings follow the end time
+            let start_time = parse_timestamp(start.trim())?;
+            let end_time = parse_timestamp(end.split_whitespace().next()?)?;
+
+            let lines: Vec<String> = lines
+                .map(|line| markup.replace_all(line, "").trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect();
+            if lines.is_empty() || end_time <= start_time {
+                return None;
+            }
+            Some(SubtitleCue { start_time, end_time, lines })
+        })
+        .collect()
+}
+
+fn parse_chapters(content: &str, video_duration: f64) -> Vec<VideoNugget> {
+    // "0:00 Intro", "(1:02:03) - Deep dive" or "Wrap up 12:30"
+    let leading = Regex::new(r"^[\(\[]?((?:\d+:)?\d{1,2}:\d{2})[\)\]]?\s*[-–—:|.]?\s*(.+)$").unwrap();
+    let trailing = Regex::new(r"^(.+?)\s*[-–—:|]?\s*[\(\[]?((?:\d+:)?\d{1,2}:\d{2})[\)\]]?$").unwrap();
+
+    let mut chapters: Vec<(f64, String)> = content.lines()
+        .map(|line| line.trim().trim_start_matches(['-', '*', '•']).trim())
+        .filter_map(|line| {
+            let (time, title) = if let Some(captures) = leading.captures(line) {
+                (captures.get(1)?.as_str(), captures.get(2)?.as_str())
+            } else {
+                let captures = trailing.captures(line)?;
+                (captures.get(2)?.as_str(), captures.get(1)?.as_str())
+            };
+            Some((parse_timestamp(time)?, title.trim().to_string()))
+        })
+        .filter(|(_, title)| !title.is_empty())
+        .collect();
+    chapters.sort_by(|a, b| a.0.total_cmp(&b.0));
+    chapters.dedup_by(|a, b| a.0 == b.0);
+
+    let starts: Vec<f64> = chapters.iter().map(|(start, _)| *start).collect();
+    chapters.into_iter()
+        .enumerate()
+        .map(|(index, (start_time, title))| {
+            let end_time = match starts.get(index + 1) {
+                Some(next) => *next,
+                None if video_duration > start_time => video_duration,
+                None => start_time + LAST_CHAPTER_FALLBACK_SECONDS,
+            };
+            new_nugget(title, start_time, end_time)
+        })
+        .collect()
+}
+
+// H:MM:SS or MM:SS, with optional milliseconds after a comma (SRT) or a dot (WebVTT)
+fn parse_timestamp(value: &str) -> Option<f64> {
+    let (clock, fraction) = match value.split_once([',', '.']) {
+        Some((clock, fraction)) => (clock, fraction),
+        None => (value, ""),
+    };
+    let mut seconds = 0.0;
+    for part in clock.split(':') {
+        seconds = seconds * 60.0 + part.parse::<u32>().ok()? as f64;
+    }
+    if !fraction.is_empty() {
+        seconds += format!("0.{}", fraction).parse::<f64>().ok()?;
+    }
+    Some(seconds)
+}
+
+fn new_nugget(title: String, start_time: f64, end_time: f64) -> VideoNugget {
+    VideoNugget {
+        id: Uuid::new_v4().to_string(),
+        title,
+        start_time,
+        end_time,
+        transcript: None,
+        tags: Vec::new(),
+        notes: None,
+        youtube_video_id: None,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(nuggets: &[VideoNugget]) -> Vec<(String, f64, f64)> {
+        nuggets.iter().map(|n| (n.title.clone(), n.start_time, n.end_time)).collect()
+    }
+
+    #[test]
+    fn test_parse_srt_and_vtt() {
+        let srt = "1\r\n00:00:01,500 --> 00:00:04,000\r\nHello <i>there</i>\r\nfriend\r\n\r\n2\r\n01:00:00,000 --> 01:00:02,250\r\nBye\r\n";
+        assert_eq!(summary(&parse_nuggets(srt, 0.0).unwrap()), vec![
+            ("Hello there friend".to_string(), 1.5, 4.0),
+            ("Bye".to_string(), 3600.0, 3602.25),
+        ]);
+
+        let vtt = "WEBVTT\n\nNOTE exported\n\nintro\n00:05.000 --> 00:09.000 align:start\nIntro\n";
+        assert_eq!(summary(&parse_nuggets(vtt, 0.0).unwrap()), vec![("Intro".to_string(), 5.0, 9.0)]);
+    }
+
+    #[test]
+    fn test_parse_chapters() {
+        let description = "Chapters:\n0:00 Intro\n(1:30) - Setup\nhttps://example.com\n- 1:02:03 | Deep dive\nWrap up 1:10:00\n";
+        assert_eq!(summary(&parse_nuggets(description, 4500.0).unwrap()), vec![
+            ("Intro".to_string(), 0.0, 90.0),
+            ("Setup".to_string(), 90.0, 3723.0),
+            ("Deep dive".to_string(), 3723.0, 4200.0),
+            ("Wrap up".to_string(), 4200.0, 4500.0),
+        ]);
+
+        let nuggets = parse_nuggets("0:00 Only chapter", 0.0).unwrap();
+        assert_eq!(nuggets[0].end_time, LAST_CHAPTER_FALLBACK_SECONDS);
+        assert!(parse_nuggets("no timestamps here", 100.0).is_err());
+    }
+}
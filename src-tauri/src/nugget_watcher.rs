@@ -0,0 +1,121 @@
+use crate::processed_index::hash_file;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Watches nugget files the app has loaded and calls on_change when one is edited by something
+// else, so the UI can offer to reload before a save overwrites the edit. Each file's hash is
+// kept from when the app last read or wrote it; events that leave the contents as the app
+// knows them, like its own saves, are ignored.
+pub struct NuggetWatcher {
+    watcher: RecommendedWatcher,
+    files: Arc<Mutex<HashMap<PathBuf, String>>>,
+}
+
+impl NuggetWatcher {
+    pub fn new(on_change: impl Fn(&Path) + Send + 'static) -> Result<Self, String> {
+        let files: Arc<Mutex<HashMap<PathBuf, String>>> = Arc::new(Mutex::new(HashMap::new()));
+        let watched = files.clone();
+        let watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let Ok(event) = result else { return };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                return;
+            }
+            let mut files = watched.lock().unwrap_or_else(|e| e.into_inner());
+            for path in &event.paths {
+                let Some(known) = files.get_mut(path) else { continue };
+                // Removed again since the event, there is nothing to compare
+                let Ok(hash) = hash_file(path) else { continue };
+                if hash != *known {
+                    *known = hash;
+                    on_change(path);
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+
+        Ok(Self { watcher, files })
+    }
+
+    // Saves replace the file with a rename, which a watch on the file itself wouldn't survive,
+    // so it's the folder that's watched
+    pub fn watch(&mut self, path: &Path) -> Result<(), String> {
+        let path = path.canonicalize()
+            .map_err(|e| format!("Failed to watch {}: {}", path.display(), e))?;
+        let hash = hash_file(&path)?;
+        let dir = path.parent().ok_or("File has no parent folder")?.to_path_buf();
+
+        let first_in_dir = !self.lock().keys().any(|file| file.parent() == Some(dir.as_path()));
+        if first_in_dir {
+            self.watcher.watch(&dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+        }
+        self.lock().insert(path, hash);
+        Ok(())
+    }
+
+    pub fn unwatch(&mut self, path: &Path) -> Result<(), String> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if self.lock().remove(&path).is_none() {
+            return Ok(());
+        }
+        let Some(dir) = path.parent() else { return Ok(()) };
+        if !self.lock().keys().any(|file| file.parent() == Some(dir)) {
+            self.watcher.unwatch(dir)
+                .map_err(|e| format!("Failed to stop watching {}: {}", dir.display(), e))?;
+        }
+        Ok(())
+    }
+
+    // Called before the app writes a watched file, so its own save isn't reported as an edit
+    pub fn expect_contents(&self, path: &Path, contents: &[u8]) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if let Some(known) = self.lock().get_mut(&path) {
+            *known = format!("{:x}", Sha256::digest(contents));
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, String>> {
+        self.files.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atomic_file::write_atomic;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_reports_external_edits_only() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let file = temp_dir.path().join("nuggets.json");
+        std::fs::write(&file, b"[]").unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = NuggetWatcher::new(move |path| {
+            let _ = sender.send(path.to_path_buf());
+        }).unwrap();
+        watcher.watch(&file).unwrap();
+
+        // The app's own save
+        let saved = br#"[{"id":"1"}]"#;
+        watcher.expect_contents(&file, saved);
+        write_atomic(&file, saved).unwrap();
+        assert!(receiver.recv_timeout(Duration::from_millis(500)).is_err());
+
+        std::fs::write(&file, br#"[{"id":"2"}]"#).unwrap();
+        let changed = receiver.recv_timeout(Duration::from_secs(5)).expect("No change reported");
+        assert_eq!(changed, file.canonicalize().unwrap());
+        // A plain write can be seen half done too, which is reported as well
+        std::thread::sleep(Duration::from_millis(200));
+        while receiver.try_recv().is_ok() {}
+
+        watcher.unwatch(&file).unwrap();
+        std::fs::write(&file, b"[]").unwrap();
+        assert!(receiver.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use url::Url;
+
+// How long the user has to sign in once the browser is open
+const SIGN_IN_TIMEOUT: Duration = Duration::from_secs(300);
+
+// Where the browser is sent back to after an OAuth sign-in, for services that let a desktop app
+// listen on loopback. state travels with the request so a redirect meant for another one is refused
+pub struct LoopbackRedirect {
+    pub redirect_uri: String,
+    pub state: String,
+    service: &'static str,
+    path: String,
+    listener: TcpListener,
+}
+
+impl LoopbackRedirect {
+    // Port 0 takes any free port, for services that accept whichever one the redirect comes to
+    pub async fn bind(service: &'static str, port: u16, path: &str) -> Result<Self, String> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|e| format!("Failed to listen for the {} sign-in redirect: {}", service, e))?;
+        let port = listener.local_addr()
+            .map_err(|e| format!("Failed to listen for the {} sign-in redirect: {}", service, e))?
+            .port();
+        Ok(Self {
+            redirect_uri: format!("http://127.0.0.1:{}{}", port, path),
+            state: uuid::Uuid::new_v4().simple().to_string(),
+            service,
+            path: path.to_string(),
+            listener,
+        })
+    }
+
+    // The authorization code, once the browser comes back. It may ask for other things first,
+    // a favicon say; only the redirect counts
+    pub async fn receive_code(&self) -> Result<String, String> {
+        tokio::time::timeout(SIGN_IN_TIMEOUT, async {
+            loop {
+                let (mut stream, _) = self.listener.accept()
+                    .await
+                    .map_err(|e| format!("Failed to receive the {} sign-in redirect: {}", self.service, e))?;
+                let mut buffer = vec![0u8; 8192];
+                let read = stream.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]);
+                let Some(result) = redirect_code(request.lines().next().unwrap_or_default(), &self.path, &self.state) else {
+                    let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+                    continue;
+                };
+
+                let page = match &result {
+                    Ok(_) => format!("Signed in to {}. You can close this window and go back to Video Nugget.", self.service),
+                    Err(_) => format!("{} sign-in didn't complete. You can close this window and try again from Video Nugget.", self.service),
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    page.len(),
+                    page
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+                return result.map_err(|e| format!("{} sign-in failed: {}", self.service, e));
+            }
+        })
+        .await
+        .map_err(|_| format!("Timed out waiting for {} sign-in", self.service))?
+    }
+}
+
+// A PKCE code verifier, 64 characters from the allowed set
+pub fn code_verifier() -> String {
+    format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple())
+}
+
+// The code from the redirect's request line, "GET /?state=...&code=... HTTP/1.1". None for a
+// request that isn't the redirect at all
+fn redirect_code(request_line: &str, path: &str, expected_state: &str) -> Option<Result<String, String>> {
+    let target = request_line.strip_prefix("GET ")?.split(' ').next()?;
+    let url = Url::parse(&format!("http://127.0.0.1{}", target)).ok()?;
+    if url.path() != path {
+        return None;
+    }
+    let param = |name: &str| url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.to_string());
+
+    if param("state").as_deref() != Some(expected_state) {
+        return Some(Err("the redirect doesn't belong to this request".to_string()));
+    }
+    if let Some(error) = param("error") {
+        return Some(Err(param("error_description").unwrap_or(error)));
+    }
+    Some(param("code").ok_or_else(|| "the redirect has no code".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redirect_code() {
+        assert_eq!(redirect_code("GET /?state=s1&code=4%2Fabc&scope=x HTTP/1.1", "/", "s1"), Some(Ok("4/abc".to_string())));
+        assert!(matches!(redirect_code("GET /?state=other&code=abc HTTP/1.1", "/", "s1"), Some(Err(_))));
+        assert!(matches!(redirect_code("GET /?state=s1&error=access_denied HTTP/1.1", "/", "s1"), Some(Err(_))));
+        assert_eq!(redirect_code("GET /favicon.ico HTTP/1.1", "/", "s1"), None);
+        assert_eq!(redirect_code("GET /callback/?state=s1&code=abc HTTP/1.1", "/callback/", "s1"), Some(Ok("abc".to_string())));
+    }
+}
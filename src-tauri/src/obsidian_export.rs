@@ -0,0 +1,316 @@
+use crate::VideoNugget;
+use crate::anki_export::{format_time, timestamp_link};
+use crate::atomic_file::write_atomic;
+use crate::output_template::sanitize_segment;
+use crate::project_manager::{Project, VideoProject};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::path::Path;
+
+pub const VIDEOS_FOLDER: &str = "Videos";
+pub const NUGGETS_FOLDER: &str = "Nuggets";
+// Nuggets sharing the most tags come first
+const MAX_RELATED_NUGGETS: usize = 10;
+
+struct NuggetNote<'a> {
+    name: String,
+    video_index: usize,
+    nugget: &'a VideoNugget,
+    tags: Vec<String>,
+}
+
+// An index note for the project, a note per video under Videos/ and a note per nugget under
+// Nuggets/, linked with wikilinks so Obsidian's graph and backlinks pick them up. Existing notes
+// with the same names are overwritten, so exporting again refreshes the vault.
+// Returns how many notes were written.
+pub fn write_vault(project: &Project, directory: &Path) -> Result<usize, String> {
+    let mut used_names = HashSet::new();
+    let project_note = unique_name(&project.name, &mut used_names);
+    let video_notes: Vec<String> = project.videos.iter()
+        .map(|video| unique_name(&video.video_info.title, &mut used_names))
+        .collect();
+    let nugget_notes: Vec<NuggetNote> = project.videos.iter()
+        .enumerate()
+        .flat_map(|(video_index, video)| video.nuggets.iter().map(move |nugget| (video_index, video, nugget)))
+        .map(|(video_index, video, nugget)| NuggetNote {
+            name: unique_name(&format!("{} - {}", video.video_info.title, nugget.title), &mut used_names),
+            video_index,
+            nugget,
+            tags: nugget.tags.iter().map(|tag| tag_name(tag)).filter(|tag| !tag.is_empty()).collect(),
+        })
+        .collect();
+
+    for folder in [VIDEOS_FOLDER, NUGGETS_FOLDER] {
+        std::fs::create_dir_all(directory.join(folder))
+            .map_err(|e| format!("Failed to create vault folder: {}", e))?;
+    }
+
+    write_note(&directory.join(format!("{}.md", project_note)), &project_markdown(project, &video_notes))?;
+    for (video_index, video) in project.videos.iter().enumerate() {
+        let nuggets: Vec<&NuggetNote> = nugget_notes.iter()
+            .filter(|note| note.video_index == video_index)
+            .collect();
+        let markdown = video_markdown(project, &project_note, video, &nuggets);
+        write_note(&directory.join(VIDEOS_FOLDER).join(format!("{}.md", video_notes[video_index])), &markdown)?;
+    }
+    for note in &nugget_notes {
+        let video = &project.videos[note.video_index];
+        let markdown = nugget_markdown(video, &video_notes[note.video_index], note, &related_nuggets(note, &nugget_notes));
+        write_note(&directory.join(NUGGETS_FOLDER).join(format!("{}.md", note.name)), &markdown)?;
+    }
+
+    Ok(1 + video_notes.len() + nugget_notes.len())
+}
+
+fn project_markdown(project: &Project, video_notes: &[String]) -> String {
+    let mut markdown = frontmatter(&[
+        ("project", yaml_string(&project.name)),
+        ("created", yaml_string(&project.created_at)),
+        ("tags", yaml_list(&project.tags.iter().map(|tag| tag_name(tag)).collect::<Vec<_>>())),
+    ]);
+    markdown.push_str(&format!("# {}\n\n", project.name));
+    if let Some(description) = project.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        markdown.push_str(&format!("{}\n\n", description.trim_end()));
+    }
+
+    markdown.push_str("## Videos\n\n");
+    for (video, note) in project.videos.iter().zip(video_notes) {
+        markdown.push_str(&format!("- {} ({} nuggets)\n", wikilink(note, &video.video_info.title), video.nuggets.len()));
+    }
+    markdown
+}
+
+fn video_markdown(project: &Project, project_note: &str, video: &VideoProject, nuggets: &[&NuggetNote]) -> String {
+    let mut tags: Vec<String> = video.custom_tags.iter().map(|tag| tag_name(tag)).collect();
+    if let Some(analysis) = &video.analysis {
+        tags.extend(analysis.suggested_tags.iter().map(|tag| tag_name(tag)));
+    }
+    dedup_tags(&mut tags);
+    let categories = video.analysis.as_ref()
+        .map(|analysis| analysis.content_categories.clone())
+        .unwrap_or_default();
+
+    let mut markdown = frontmatter(&[
+        ("title", yaml_string(&video.video_info.title)),
+        ("source", yaml_string(&video.video_info.url)),
+        ("duration", format_time(video.video_info.duration)),
+        ("status", format!("{:?}", video.status)),
+        ("project", yaml_string(&project.name)),
+        ("created", yaml_string(&video.created_at)),
+        ("tags", yaml_list(&tags)),
+        ("categories", yaml_list(&categories)),
+    ]);
+    markdown.push_str(&format!("# {}\n\n", video.video_info.title));
+    markdown.push_str(&format!("Project: {}\nSource: <{}>\n\n", wikilink(project_note, &project.name), video.video_info.url));
+
+    if let Some(analysis) = video.analysis.as_ref().filter(|a| !a.summary.trim().is_empty()) {
+        markdown.push_str(&format!("## Summary\n\n{}\n\n", analysis.summary.trim_end()));
+    }
+    if !video.notes.trim().is_empty() {
+        markdown.push_str(&format!("## Notes\n\n{}\n\n", video.notes.trim_end()));
+    }
+
+    markdown.push_str("## Nuggets\n\n");
+    for note in nuggets {
+        markdown.push_str(&format!(
+            "- {} [{}]({})\n",
+            wikilink(&note.name, &note.nugget.title),
+            format_time(note.nugget.start_time),
+            timestamp_link(&video.video_info.url, note.nugget.start_time),
+        ));
+    }
+    markdown
+}
+
+fn nugget_markdown(video: &VideoProject, video_note: &str, note: &NuggetNote, related: &[&NuggetNote]) -> String {
+    let nugget = note.nugget;
+    let link = timestamp_link(&video.video_info.url, nugget.start_time);
+
+    let mut markdown = frontmatter(&[
+        ("title", yaml_string(&nugget.title)),
+        ("video", yaml_string(&format!("[[{}]]", video_note))),
+        ("source", yaml_string(&link)),
+        ("start", format!("{}", nugget.start_time)),
+        ("end", format!("{}", nugget.end_time)),
+        ("tags", yaml_list(&note.tags)),
+    ]);
+    markdown.push_str(&format!("# {}\n\n", nugget.title));
+    markdown.push_str(&format!(
+        "From {} at [{} - {}]({})\n\n",
+        wikilink(video_note, &video.video_info.title),
+        format_time(nugget.start_time),
+        format_time(nugget.end_time),
+        link,
+    ));
+
+    if let Some(transcript) = nugget.transcript.as_deref().filter(|t| !t.trim().is_empty()) {
+        markdown.push_str(&format!("## Transcript\n\n{}\n\n", transcript.trim_end()));
+    }
+    if let Some(notes) = nugget.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+        markdown.push_str(&format!("## Notes\n\n{}\n\n", notes.trim_end()));
+    }
+
+    if !related.is_empty() {
+        markdown.push_str("## Related\n\n");
+        for other in related {
+            markdown.push_str(&format!("- {}\n", wikilink(&other.name, &other.nugget.title)));
+        }
+    }
+    markdown
+}
+
+// Other nuggets, from any video, that share a tag with this one. The relation is symmetric, so
+// each side links the other and both show up in the backlinks
+fn related_nuggets<'a, 'b>(note: &NuggetNote, notes: &'a [NuggetNote<'b>]) -> Vec<&'a NuggetNote<'b>> {
+    let mut related: Vec<(usize, &NuggetNote)> = notes.iter()
+        .filter(|other| other.name != note.name)
+        .map(|other| (other.tags.iter().filter(|tag| note.tags.contains(tag)).count(), other))
+        .filter(|(shared, _)| *shared > 0)
+        .collect();
+    // Stable, so ties keep the project's order
+    related.sort_by_key(|(shared, _)| Reverse(*shared));
+    related.into_iter()
+        .take(MAX_RELATED_NUGGETS)
+        .map(|(_, other)| other)
+        .collect()
+}
+
+// Note names have to be unique across the vault for wikilinks to resolve without a folder
+fn unique_name(title: &str, used: &mut HashSet<String>) -> String {
+    // Characters that break wikilinks, on top of the ones file systems reject
+    let cleaned: String = title.chars()
+        .map(|c| if matches!(c, '#' | '^' | '[' | ']' | '|') { ' ' } else { c })
+        .collect();
+    let base = sanitize_segment(&cleaned);
+
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while !used.insert(name.to_lowercase()) {
+        name = format!("{} {}", base, suffix);
+        suffix += 1;
+    }
+    name
+}
+
+// Obsidian tags can't contain spaces and can't be only digits
+fn tag_name(tag: &str) -> String {
+    let tag: String = tag.trim().trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'))
+        .collect();
+    if !tag.is_empty() && tag.chars().all(|c| c.is_ascii_digit()) {
+        format!("_{}", tag)
+    } else {
+        tag
+    }
+}
+
+fn dedup_tags(tags: &mut Vec<String>) {
+    let mut seen = HashSet::new();
+    tags.retain(|tag| !tag.is_empty() && seen.insert(tag.to_lowercase()));
+}
+
+fn wikilink(name: &str, label: &str) -> String {
+    let label = label.replace(['[', ']', '|'], " ");
+    if label.trim() == name {
+        format!("[[{}]]", name)
+    } else {
+        format!("[[{}|{}]]", name, label.trim())
+    }
+}
+
+pub fn frontmatter(fields: &[(&str, String)]) -> String {
+    let mut yaml = String::from("---\n");
+    for (key, value) in fields {
+        yaml.push_str(&format!("{}: {}\n", key, value));
+    }
+    yaml.push_str("---\n\n");
+    yaml
+}
+
+// JSON strings are valid double-quoted YAML scalars, which saves escaping by hand
+pub fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+pub fn yaml_list(values: &[String]) -> String {
+    format!("[{}]", values.iter().map(|value| yaml_string(value)).collect::<Vec<_>>().join(", "))
+}
+
+fn write_note(path: &Path, markdown: &str) -> Result<(), String> {
+    write_atomic(path, markdown.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_manager::ProjectManager;
+    use crate::VideoInfo;
+
+    fn nugget(title: &str, start_time: f64, tags: &[&str]) -> VideoNugget {
+        VideoNugget {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: title.to_string(),
+            start_time,
+            end_time: start_time + 20.0,
+            transcript: Some(format!("{} transcript", title)),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            notes: None,
+            youtube_video_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    fn video_info(title: &str) -> VideoInfo {
+        VideoInfo {
+            title: title.to_string(),
+            duration: 600.0,
+            url: "https://www.youtube.com/watch?v=abc123".to_string(),
+            thumbnail: None,
+        }
+    }
+
+    #[test]
+    fn test_unique_name_and_tags() {
+        let mut used = HashSet::new();
+        assert_eq!(unique_name("Intro: Part #1", &mut used), "Intro_ Part 1");
+        assert_eq!(unique_name("intro_ part 1", &mut used), "intro_ part 1 2");
+        assert_eq!(tag_name("machine learning"), "machine-learning");
+        assert_eq!(tag_name("#2024"), "_2024");
+    }
+
+    #[test]
+    fn test_write_vault() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().join("workspace")).unwrap();
+        let project_id = manager.create_project("Biology".to_string(), None, None).unwrap();
+        manager.add_video_to_project(&project_id, video_info("Cells"), vec![
+            nugget("Mitochondria", 65.0, &["energy"]),
+            nugget("Ribosomes", 120.0, &["proteins"]),
+        ], None).unwrap();
+        manager.add_video_to_project(&project_id, video_info("Metabolism"), vec![
+            nugget("ATP", 30.0, &["energy"]),
+        ], None).unwrap();
+        let project = manager.get_project(&project_id).unwrap().unwrap();
+
+        let vault = temp_dir.path().join("vault");
+        assert_eq!(write_vault(project, &vault).unwrap(), 6);
+        assert!(vault.join("Biology.md").exists());
+        assert!(vault.join(VIDEOS_FOLDER).join("Cells.md").exists());
+
+        let video = std::fs::read_to_string(vault.join(VIDEOS_FOLDER).join("Cells.md")).unwrap();
+        assert!(video.starts_with("---\ntitle: \"Cells\"\n"));
+        assert!(video.contains("[[Cells - Mitochondria|Mitochondria]] [1:05](https://www.youtube.com/watch?v=abc123&t=65s)"));
+
+        let mitochondria = std::fs::read_to_string(vault.join(NUGGETS_FOLDER).join("Cells - Mitochondria.md")).unwrap();
+        assert!(mitochondria.contains("tags: [\"energy\"]"));
+        assert!(mitochondria.contains("From [[Cells]] at [1:05 - 1:25]"));
+        assert!(mitochondria.contains("## Related\n\n- [[Metabolism - ATP|ATP]]"));
+        let ribosomes = std::fs::read_to_string(vault.join(NUGGETS_FOLDER).join("Cells - Ribosomes.md")).unwrap();
+        assert!(!ribosomes.contains("## Related"));
+    }
+}
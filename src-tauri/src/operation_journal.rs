@@ -0,0 +1,286 @@
+use crate::atomic_file::write_atomic;
+use crate::operations::OperationKind;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// What to run again to resume an operation
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ResumableCommand {
+    ProcessVideo,
+    ExtractTranscript,
+    // Batch jobs keep their own state, see BatchJob::interrupted
+    Batch,
+}
+
+// A running operation as the journal last saw it. Entries still there when the app starts
+// belong to operations a crash cut short
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JournalEntry {
+    pub operation_id: String,
+    pub kind: OperationKind,
+    pub label: String,
+    pub command: ResumableCommand,
+    // The command's arguments, to run it again with
+    pub params: serde_json::Value,
+    pub stage: String,
+    // Files finished stages produced, by name ("video", "audio")
+    #[serde(default)]
+    pub artifacts: HashMap<String, PathBuf>,
+    // Scratch directories of every run, removed once the operation finishes
+    #[serde(default)]
+    pub work_dirs: Vec<PathBuf>,
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+// One file per running operation, written when it starts, changes stage or finishes a file,
+// and removed when it finishes whichever way.
+#[derive(Clone)]
+pub struct OperationJournal {
+    // Shared with the records of running operations, so they follow a relocate
+    dir: Arc<Mutex<PathBuf>>,
+}
+
+impl OperationJournal {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir: Arc::new(Mutex::new(dir)) }
+    }
+
+    // After the workspace moved, with the entries in it
+    pub fn relocate(&self, dir: PathBuf) {
+        *self.dir.lock().unwrap_or_else(|e| e.into_inner()) = dir;
+    }
+
+    // Starts journaling operation_id. When the journal already has it from an interrupted run
+    // of the same command, its artifacts that are still on disk are carried over for reuse
+    pub fn begin(
+        &self,
+        operation_id: &str,
+        kind: OperationKind,
+        label: &str,
+        command: ResumableCommand,
+        params: serde_json::Value,
+    ) -> Result<JournalRecord, String> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let previous = self.get(operation_id)?.filter(|entry| entry.command == command);
+        let (mut artifacts, work_dirs, started_at) = match previous {
+            Some(entry) => (entry.artifacts, entry.work_dirs, entry.started_at),
+            None => (HashMap::new(), Vec::new(), now.clone()),
+        };
+        artifacts.retain(|_, path| path.is_file());
+
+        let entry = JournalEntry {
+            operation_id: operation_id.to_string(),
+            kind,
+            label: label.to_string(),
+            command,
+            params,
+            stage: "Starting".to_string(),
+            artifacts,
+            work_dirs,
+            started_at,
+            updated_at: now,
+        };
+        self.save(&entry)?;
+        Ok(JournalRecord {
+            journal: self.clone(),
+            entry: Mutex::new(entry),
+        })
+    }
+
+    pub fn get(&self, operation_id: &str) -> Result<Option<JournalEntry>, String> {
+        let path = self.entry_path(operation_id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read operation journal: {}", e))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse operation journal: {}", e))
+    }
+
+    // Oldest first. Unreadable entries are skipped, they can't be resumed anyway
+    pub fn list(&self) -> Vec<JournalEntry> {
+        let Ok(files) = std::fs::read_dir(self.dir()) else {
+            return Vec::new();
+        };
+        let mut entries: Vec<JournalEntry> = files
+            .filter_map(|file| file.ok())
+            .filter(|file| file.path().extension().is_some_and(|extension| extension == "json"))
+            .filter_map(|file| {
+                let content = std::fs::read_to_string(file.path()).ok()?;
+                match serde_json::from_str(&content) {
+                    Ok(entry) => Some(entry),
+                    Err(e) => {
+                        tracing::warn!("Skipping unreadable journal entry {}: {}", file.path().display(), e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        entries
+    }
+
+    // Forgets an interrupted operation and deletes what it left behind
+    pub fn discard(&self, operation_id: &str) -> Result<(), String> {
+        let entry = self.get(operation_id)?.ok_or("Interrupted operation not found")?;
+        self.close(&entry);
+        Ok(())
+    }
+
+    fn save(&self, entry: &JournalEntry) -> Result<(), String> {
+        std::fs::create_dir_all(self.dir())
+            .map_err(|e| format!("Failed to create operation journal: {}", e))?;
+        let content = serde_json::to_string_pretty(entry)
+            .map_err(|e| format!("Failed to serialize journal entry: {}", e))?;
+        write_atomic(&self.entry_path(&entry.operation_id)?, content.as_bytes())
+            .map_err(|e| format!("Failed to write operation journal: {}", e))
+    }
+
+    fn close(&self, entry: &JournalEntry) {
+        for work_dir in &entry.work_dirs {
+            // Only scratch directories are ever recorded, anything else in an edited journal is left alone
+            if work_dir.starts_with(std::env::temp_dir()) && work_dir.is_dir() {
+                if let Err(e) = std::fs::remove_dir_all(work_dir) {
+                    tracing::warn!("Failed to remove {}: {}", work_dir.display(), e);
+                }
+            }
+        }
+        if let Ok(path) = self.entry_path(&entry.operation_id) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    // Operation ids come from the frontend
+    fn entry_path(&self, operation_id: &str) -> Result<PathBuf, String> {
+        if operation_id.is_empty() || !operation_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("Invalid operation id: {}", operation_id));
+        }
+        Ok(self.dir().join(format!("{}.json", operation_id)))
+    }
+
+    fn dir(&self) -> PathBuf {
+        self.dir.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+// An operation's entry while it runs, see OperationHandle::with_journal
+pub struct JournalRecord {
+    journal: OperationJournal,
+    entry: Mutex<JournalEntry>,
+}
+
+impl JournalRecord {
+    pub fn stage(&self, stage: &str) {
+        self.update(|entry| {
+            if entry.stage == stage {
+                return false;
+            }
+            entry.stage = stage.to_string();
+            true
+        });
+    }
+
+    pub fn artifact(&self, name: &str, path: &Path) {
+        self.update(|entry| {
+            entry.artifacts.insert(name.to_string(), path.to_path_buf());
+            true
+        });
+    }
+
+    pub fn work_dir(&self, dir: &Path) {
+        self.update(|entry| {
+            if entry.work_dirs.iter().any(|work_dir| work_dir == dir) {
+                return false;
+            }
+            entry.work_dirs.push(dir.to_path_buf());
+            true
+        });
+    }
+
+    // The file an earlier run finished for this stage, if it's still there
+    pub fn reusable(&self, name: &str) -> Option<PathBuf> {
+        self.lock().artifacts.get(name)
+            .filter(|path| path.is_file())
+            .cloned()
+    }
+
+    pub fn close(&self) {
+        self.journal.close(&self.lock());
+    }
+
+    // A failed write only costs the chance to resume, the operation itself carries on
+    fn update(&self, change: impl FnOnce(&mut JournalEntry) -> bool) {
+        let mut entry = self.lock();
+        if !change(&mut entry) {
+            return;
+        }
+        entry.updated_at = chrono::Utc::now().to_rfc3339();
+        if let Err(e) = self.journal.save(&entry) {
+            tracing::warn!("{}", e);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, JournalEntry> {
+        self.entry.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interrupted_operation_resumes_with_its_artifacts() {
+        let journal_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        // Left behind like a crashed run's scratch directory would be
+        let work_dir = std::env::temp_dir().join(format!("journal-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&work_dir).unwrap();
+        let journal = OperationJournal::new(journal_dir.path().to_path_buf());
+        let params = serde_json::json!({ "url": "https://youtu.be/abc" });
+
+        let record = journal.begin("op-1", OperationKind::Transcription, "video", ResumableCommand::ExtractTranscript, params.clone()).unwrap();
+        let video = work_dir.join("video.mp4");
+        std::fs::write(&video, b"video").unwrap();
+        record.work_dir(&work_dir);
+        record.stage("Downloading");
+        record.artifact("video", &video);
+        record.artifact("audio", &work_dir.join("missing.wav"));
+        // The app crashes here, the record is never closed
+        drop(record);
+
+        let interrupted = journal.list();
+        assert_eq!(interrupted.len(), 1);
+        assert_eq!(interrupted[0].stage, "Downloading");
+
+        let resumed = journal.begin("op-1", OperationKind::Transcription, "video", ResumableCommand::ExtractTranscript, params).unwrap();
+        assert_eq!(resumed.reusable("video"), Some(video));
+        assert!(resumed.reusable("audio").is_none());
+        resumed.close();
+        assert!(journal.list().is_empty());
+        assert!(!work_dir.exists());
+
+        assert!(journal.begin("../escape", OperationKind::Transcription, "video", ResumableCommand::ProcessVideo, serde_json::Value::Null).is_err());
+    }
+
+    #[test]
+    fn test_relocated_journal_keeps_running_operations() {
+        let workspace = tempfile::tempdir().expect("Failed to create temp dir");
+        let old_dir = workspace.path().join("old").join("operations");
+        let new_dir = workspace.path().join("new").join("operations");
+        let journal = OperationJournal::new(old_dir.clone());
+        let record = journal.begin("op-1", OperationKind::Transcription, "video", ResumableCommand::ProcessVideo, serde_json::Value::Null).unwrap();
+
+        std::fs::create_dir_all(new_dir.parent().unwrap()).unwrap();
+        std::fs::rename(&old_dir, &new_dir).unwrap();
+        journal.relocate(new_dir);
+        record.stage("Transcribing");
+
+        assert!(!old_dir.exists());
+        assert_eq!(journal.list()[0].stage, "Transcribing");
+    }
+}
@@ -0,0 +1,370 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+use tokio_util::sync::CancellationToken;
+use crate::error::AppError;
+use crate::operation_journal::JournalRecord;
+
+pub const PROGRESS_EVENT: &str = "operation://progress";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum OperationKind {
+    // Downloading and transcribing a video
+    Transcription,
+    Analysis,
+    Rendering,
+    Batch,
+    // A project template's workflow, see workflow.rs
+    Workflow,
+    Download,
+    // Publishing a clip to YouTube or elsewhere
+    Upload,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum OperationState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+// What every progress event carries. Percent and ETA are for the current stage, both None while
+// the stage can't tell how far along it is
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OperationProgress {
+    pub operation_id: String,
+    pub kind: OperationKind,
+    pub label: String,
+    pub state: OperationState,
+    pub stage: String,
+    pub percent: Option<f64>,
+    pub message: Option<String>,
+    pub eta_seconds: Option<f64>,
+    pub started_at: String,
+}
+
+type Emit = Arc<dyn Fn(&OperationProgress) + Send + Sync>;
+type OnCancel = Box<dyn Fn() + Send>;
+
+struct Operation {
+    progress: OperationProgress,
+    stage_started: Instant,
+    cancel_token: CancellationToken,
+    on_cancel: Option<OnCancel>,
+}
+
+// Every long-running command registers here for the time it runs, and reports its stages and
+// progress through the handle it gets back. Each change is emitted as PROGRESS_EVENT; finished
+// operations are emitted once more with their final state and then forgotten.
+// Progress comes from blocking threads as well as tasks, so it's locked internally and cloned
+// rather than kept behind the async Mutex the other stores use.
+#[derive(Clone)]
+pub struct OperationRegistry {
+    operations: Arc<Mutex<HashMap<String, Operation>>>,
+    emit: Emit,
+}
+
+impl OperationRegistry {
+    pub fn new(emit: impl Fn(&OperationProgress) + Send + Sync + 'static) -> Self {
+        Self {
+            operations: Arc::new(Mutex::new(HashMap::new())),
+            emit: Arc::new(emit),
+        }
+    }
+
+    // The caller may pick the id, so it can tell its own operation's events apart before the
+    // command returns. Cancelling cancels the handle's token, which the operation has to honor
+    pub fn start(&self, operation_id: Option<String>, kind: OperationKind, label: &str) -> Result<OperationHandle, String> {
+        self.register(operation_id, kind, label, None)
+    }
+
+    // For operations that are stopped some other way than through the handle's token
+    pub fn start_with_cancel(
+        &self,
+        operation_id: Option<String>,
+        kind: OperationKind,
+        label: &str,
+        on_cancel: impl Fn() + Send + 'static,
+    ) -> Result<OperationHandle, String> {
+        self.register(operation_id, kind, label, Some(Box::new(on_cancel)))
+    }
+
+    fn register(&self, operation_id: Option<String>, kind: OperationKind, label: &str, on_cancel: Option<OnCancel>) -> Result<OperationHandle, String> {
+        let operation_id = operation_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let cancel_token = CancellationToken::new();
+        let progress = OperationProgress {
+            operation_id: operation_id.clone(),
+            kind,
+            label: label.to_string(),
+            state: OperationState::Running,
+            stage: "Starting".to_string(),
+            percent: None,
+            message: None,
+            eta_seconds: None,
+            started_at: chrono::Utc::now().to_rfc3339(),
+        };
+        {
+            let mut operations = self.lock();
+            if operations.contains_key(&operation_id) {
+                return Err(format!("Operation {} is already running", operation_id));
+            }
+            operations.insert(operation_id.clone(), Operation {
+                progress: progress.clone(),
+                stage_started: Instant::now(),
+                cancel_token: cancel_token.clone(),
+                on_cancel,
+            });
+        }
+        (self.emit)(&progress);
+
+        Ok(OperationHandle {
+            operation_id,
+            cancel_token,
+            registry: self.clone(),
+            finished: false,
+            journal: None,
+        })
+    }
+
+    // Oldest first
+    pub fn list(&self) -> Vec<OperationProgress> {
+        let mut operations: Vec<OperationProgress> = self.lock().values()
+            .map(|operation| operation.progress.clone())
+            .collect();
+        operations.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        operations
+    }
+
+    // The operation reports itself as cancelled once it has actually stopped
+    pub fn cancel(&self, operation_id: &str) -> Result<(), String> {
+        let operations = self.lock();
+        let operation = operations.get(operation_id)
+            .ok_or("Operation not found")?;
+        operation.cancel_token.cancel();
+        if let Some(on_cancel) = &operation.on_cancel {
+            on_cancel();
+        }
+        Ok(())
+    }
+
+    fn update(&self, operation_id: &str, update: impl FnOnce(&mut OperationProgress)) {
+        let progress = {
+            let mut operations = self.lock();
+            let Some(operation) = operations.get_mut(operation_id) else { return };
+            let before = operation.progress.clone();
+            update(&mut operation.progress);
+
+            if operation.progress.stage != before.stage {
+                operation.stage_started = Instant::now();
+            }
+            operation.progress.eta_seconds = operation.progress.percent
+                .filter(|percent| *percent > 0.0 && *percent < 100.0)
+                .map(|percent| operation.stage_started.elapsed().as_secs_f64() * (100.0 - percent) / percent);
+
+            // Downloads report every fraction of a percent, whole steps are enough for a progress bar
+            let moved = match (before.percent, operation.progress.percent) {
+                (Some(before), Some(now)) => (now - before).abs() >= 1.0 || now >= 100.0,
+                (before, now) => before.is_some() != now.is_some(),
+            };
+            if !moved && operation.progress.stage == before.stage && operation.progress.message == before.message {
+                operation.progress.percent = before.percent;
+                return;
+            }
+            operation.progress.clone()
+        };
+        (self.emit)(&progress);
+    }
+
+    fn finish(&self, operation_id: &str, state: OperationState, message: Option<String>) {
+        let Some(operation) = self.lock().remove(operation_id) else { return };
+        let mut progress = operation.progress;
+        progress.state = state;
+        progress.eta_seconds = None;
+        if state == OperationState::Completed {
+            progress.percent = Some(100.0);
+        }
+        if message.is_some() {
+            progress.message = message;
+        }
+        (self.emit)(&progress);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Operation>> {
+        self.operations.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// Dropping a handle without finishing it, as an early return does, reports the operation failed
+pub struct OperationHandle {
+    operation_id: String,
+    cancel_token: CancellationToken,
+    registry: OperationRegistry,
+    finished: bool,
+    journal: Option<JournalRecord>,
+}
+
+impl OperationHandle {
+    pub fn id(&self) -> &str {
+        &self.operation_id
+    }
+
+    pub fn cancel_token(&self) -> CancellationToken {
+        self.cancel_token.clone()
+    }
+
+    // Keeps the operation's stage and files in the journal until it finishes, so a crash
+    // leaves behind what's needed to resume it
+    pub fn with_journal(mut self, record: JournalRecord) -> Self {
+        self.journal = Some(record);
+        self
+    }
+
+    // A file a finished stage produced, for a resumed run to pick up instead of redoing the stage
+    pub fn artifact(&self, name: &str, path: &str) {
+        if let Some(journal) = &self.journal {
+            journal.artifact(name, Path::new(path));
+        }
+    }
+
+    // The artifact an interrupted run of this operation left behind, if there is one
+    pub fn reusable_artifact(&self, name: &str) -> Option<String> {
+        self.journal.as_ref()?
+            .reusable(name)
+            .map(|path| path.to_string_lossy().to_string())
+    }
+
+    // A scratch directory to remove with the journal entry, crash or not
+    pub fn work_dir(&self, dir: &Path) {
+        if let Some(journal) = &self.journal {
+            journal.work_dir(dir);
+        }
+    }
+
+    pub fn stage(&self, stage: &str) {
+        if let Some(journal) = &self.journal {
+            journal.stage(stage);
+        }
+        self.registry.update(&self.operation_id, |progress| {
+            progress.stage = stage.to_string();
+            progress.percent = None;
+            progress.message = None;
+        });
+    }
+
+    pub fn progress(&self, percent: f64, message: Option<String>) {
+        self.registry.update(&self.operation_id, |progress| {
+            progress.percent = Some(percent.clamp(0.0, 100.0));
+            progress.message = message;
+        });
+    }
+
+    // Stage and progress as one event, for callers that learn both at once
+    pub fn report(&self, stage: &str, percent: Option<f64>, message: Option<String>) {
+        if let Some(journal) = &self.journal {
+            journal.stage(stage);
+        }
+        self.registry.update(&self.operation_id, |progress| {
+            progress.stage = stage.to_string();
+            progress.percent = percent.map(|percent| percent.clamp(0.0, 100.0));
+            progress.message = message;
+        });
+    }
+
+    // For callbacks that outlive a borrow of the handle, like download progress
+    pub fn reporter(&self) -> impl Fn(f64) + Send + Sync + 'static {
+        let registry = self.registry.clone();
+        let operation_id = self.operation_id.clone();
+        move |percent| registry.update(&operation_id, |progress| progress.percent = Some(percent.clamp(0.0, 100.0)))
+    }
+
+    // Failures after a cancel count as the cancel
+    pub fn finish<T, E: std::fmt::Display>(mut self, result: &Result<T, E>) {
+        self.finished = true;
+        let (state, message) = match result {
+            Ok(_) => (OperationState::Completed, None),
+            Err(_) if self.cancel_token.is_cancelled() => (OperationState::Cancelled, None),
+            Err(e) => (OperationState::Failed, Some(e.to_string())),
+        };
+        self.registry.finish(&self.operation_id, state, message);
+    }
+}
+
+impl Drop for OperationHandle {
+    fn drop(&mut self) {
+        if !self.finished {
+            let state = if self.cancel_token.is_cancelled() { OperationState::Cancelled } else { OperationState::Failed };
+            self.registry.finish(&self.operation_id, state, None);
+        }
+        // Only a crash skips this, which is what leaves the entry behind
+        if let Some(journal) = &self.journal {
+            journal.close();
+        }
+    }
+}
+
+// Runs future until it's done or the operation is cancelled, for work that has no way to take
+// a cancellation token itself
+pub async fn cancellable<T, E: From<AppError>>(handle: &OperationHandle, future: impl std::future::Future<Output = Result<T, E>>) -> Result<T, E> {
+    tokio::select! {
+        result = future => result,
+        _ = handle.cancel_token.cancelled() => Err(AppError::cancelled().into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> (OperationRegistry, Arc<Mutex<Vec<OperationProgress>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let registry = OperationRegistry::new(move |progress| sink.lock().unwrap().push(progress.clone()));
+        (registry, events)
+    }
+
+    #[test]
+    fn test_progress_events() {
+        let (registry, events) = registry();
+        let operation = registry.start(Some("op-1".to_string()), OperationKind::Transcription, "video").unwrap();
+        assert!(registry.start(Some("op-1".to_string()), OperationKind::Transcription, "video").is_err());
+
+        operation.stage("Downloading");
+        let report = operation.reporter();
+        report(10.0);
+        report(10.4);
+        report(50.0);
+        assert_eq!(registry.list().len(), 1);
+        operation.finish(&Ok::<(), String>(()));
+
+        let events = events.lock().unwrap();
+        let percents: Vec<Option<f64>> = events.iter().map(|event| event.percent).collect();
+        // The 10.4 was too small a step to send
+        assert_eq!(percents, vec![None, None, Some(10.0), Some(50.0), Some(100.0)]);
+        assert!(events[3].eta_seconds.is_some());
+        assert_eq!(events.last().unwrap().state, OperationState::Completed);
+        assert!(registry.list().is_empty());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let (registry, events) = registry();
+        let cancelled = Arc::new(Mutex::new(false));
+        let flag = cancelled.clone();
+        let operation = registry.start_with_cancel(None, OperationKind::Batch, "job", move || *flag.lock().unwrap() = true).unwrap();
+        let operation_id = operation.id().to_string();
+
+        registry.cancel(&operation_id).unwrap();
+        assert!(*cancelled.lock().unwrap());
+        assert!(operation.cancel_token().is_cancelled());
+        operation.finish(&Err::<(), String>("Cancelled".to_string()));
+        assert_eq!(events.lock().unwrap().last().unwrap().state, OperationState::Cancelled);
+        assert!(registry.cancel(&operation_id).is_err());
+
+        // An early return reports the operation failed
+        drop(registry.start(None, OperationKind::Rendering, "clip").unwrap());
+        assert_eq!(events.lock().unwrap().last().unwrap().state, OperationState::Failed);
+    }
+}
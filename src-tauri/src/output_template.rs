@@ -0,0 +1,158 @@
+// Output paths for batch results, e.g. "{channel}/{date}_{video_title}/{nugget_title}".
+// Slashes in the template create folders, slashes inside a variable's value never do.
+
+pub const DEFAULT_OUTPUT_TEMPLATE: &str = "{channel}/{date}_{video_title}/{nugget_title}";
+
+pub const TEMPLATE_VARIABLES: [&str; 5] = ["video_title", "channel", "date", "index", "nugget_title"];
+
+// The combined per-video exports (JSON, CSV, Markdown) render {nugget_title} as this
+pub const COMBINED_EXPORT_NAME: &str = "nuggets";
+
+const MAX_SEGMENT_CHARS: usize = 80;
+
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    pub video_title: String,
+    pub channel: String,
+    pub date: String,
+    // 1-based position of the video in its batch
+    pub index: usize,
+    pub nugget_title: String,
+}
+
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("Output template is empty".to_string());
+    }
+    if template.starts_with('/') || template.starts_with('\\') {
+        return Err("Output template must be a relative path".to_string());
+    }
+    if template.split(['/', '\\']).any(|segment| segment.trim() == "..") {
+        return Err("Output template can't contain '..'".to_string());
+    }
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}')
+            .ok_or("Output template has an unclosed '{'")?;
+        let name = &rest[start + 1..start + end];
+        if !TEMPLATE_VARIABLES.contains(&name) {
+            return Err(format!("Unknown output template variable: {{{}}}", name));
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    if rest.contains('}') {
+        return Err("Output template has an unmatched '}'".to_string());
+    }
+
+    Ok(())
+}
+
+// Relative path without an extension, to be joined onto the batch output directory
+pub fn render_output_path(template: &str, context: &TemplateContext) -> Result<String, String> {
+    validate_template(template)?;
+
+    let rendered = template
+        .replace("{video_title}", &sanitize_segment(&context.video_title))
+        .replace("{channel}", &sanitize_segment(&context.channel))
+        .replace("{date}", &sanitize_segment(&context.date))
+        .replace("{index}", &format!("{:03}", context.index))
+        .replace("{nugget_title}", &sanitize_segment(&context.nugget_title));
+
+    let segments: Vec<String> = rendered.split(['/', '\\'])
+        .map(|segment| segment.trim().trim_end_matches('.').to_string())
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    if segments.is_empty() {
+        return Err("Output template rendered to an empty path".to_string());
+    }
+
+    Ok(segments.join("/"))
+}
+
+// One path per clip; clips with the same title get a numeric suffix instead of overwriting each other
+pub fn render_clip_paths(template: &str, context: &TemplateContext, nugget_titles: &[String]) -> Result<Vec<String>, String> {
+    let mut paths: Vec<String> = Vec::with_capacity(nugget_titles.len());
+
+    for title in nugget_titles {
+        let clip_context = TemplateContext {
+            nugget_title: title.clone(),
+            ..context.clone()
+        };
+        let base = render_output_path(template, &clip_context)?;
+
+        let mut path = base.clone();
+        let mut suffix = 2;
+        while paths.contains(&path) {
+            path = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+// Makes a value safe as a single file or folder name on Windows, macOS and Linux
+pub fn sanitize_segment(value: &str) -> String {
+    let cleaned: String = value.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    let collapsed = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    let truncated: String = collapsed.chars().take(MAX_SEGMENT_CHARS).collect();
+    let trimmed = truncated.trim().trim_matches('.').trim();
+
+    if trimmed.is_empty() {
+        "untitled".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            video_title: "Rust: Ownership / Borrowing?".to_string(),
+            channel: "Let's Get Rusty".to_string(),
+            date: "2024-03-01".to_string(),
+            index: 7,
+            nugget_title: "Why the borrow checker exists".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_output_path_sanitizes_values() {
+        let path = render_output_path(DEFAULT_OUTPUT_TEMPLATE, &context()).unwrap();
+        assert_eq!(path, "Let's Get Rusty/2024-03-01_Rust_ Ownership _ Borrowing_/Why the borrow checker exists");
+
+        let path = render_output_path("{index}-{video_title}", &TemplateContext { video_title: "...".to_string(), ..context() }).unwrap();
+        assert_eq!(path, "007-untitled");
+    }
+
+    #[test]
+    fn test_validate_template_rejects_bad_templates() {
+        assert!(validate_template("{channel}/{index}").is_ok());
+        assert!(validate_template("{channel}/{views}").is_err());
+        assert!(validate_template("{channel").is_err());
+        assert!(validate_template("channel}").is_err());
+        assert!(validate_template("/abs/{index}").is_err());
+        assert!(validate_template("{channel}/../{index}").is_err());
+    }
+
+    #[test]
+    fn test_render_clip_paths_dedupes_titles() {
+        let titles = vec!["Intro".to_string(), "Intro".to_string(), "Outro".to_string()];
+        let paths = render_clip_paths("{index}/{nugget_title}", &context(), &titles).unwrap();
+        assert_eq!(paths, vec!["007/Intro", "007/Intro_2", "007/Outro"]);
+    }
+}
@@ -0,0 +1,399 @@
+use crate::VideoNugget;
+use crate::ai_analyzer::ContentAnalysis;
+use crate::anki_export::{format_time, timestamp_link};
+use crate::atomic_file::write_atomic;
+use crate::export_format::ExportFormatting;
+use crate::project_collections::nugget_sentiment;
+use printpdf::{
+    image_crate, BuiltinFont, Color, Image, ImageTransform, IndirectFontRef, Mm, PdfDocument,
+    PdfDocumentReference, PdfLayerReference, Rgb,
+};
+use std::path::{Path, PathBuf};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const THUMBNAIL_MAX_WIDTH: f32 = 90.0;
+const THUMBNAIL_MAX_HEIGHT: f32 = 60.0;
+// Images are placed at this resolution before scaling them to fit
+const IMAGE_DPI: f32 = 300.0;
+// A nugget's heading isn't left alone at the bottom of a page
+const SECTION_MIN_SPACE: f32 = 40.0;
+const POINT_IN_MM: f32 = 0.3528;
+
+// Everything that goes into the report. thumbnails are in the same order as the nuggets; a
+// missing or unreadable one is left out rather than failing the export
+pub struct PdfReport {
+    pub title: String,
+    // The project the video belongs to, when there is one
+    pub subtitle: Option<String>,
+    pub source_url: Option<String>,
+    pub analysis: Option<ContentAnalysis>,
+    pub nuggets: Vec<VideoNugget>,
+    pub thumbnails: Vec<Option<PathBuf>>,
+    pub formatting: Option<ExportFormatting>,
+}
+
+impl PdfReport {
+    pub fn from_nuggets(title: &str, nuggets: Vec<VideoNugget>) -> Self {
+        Self {
+            title: title.to_string(),
+            subtitle: None,
+            source_url: None,
+            analysis: None,
+            thumbnails: vec![None; nuggets.len()],
+            nuggets,
+            formatting: None,
+        }
+    }
+
+    fn time(&self, seconds: f64) -> String {
+        match &self.formatting {
+            Some(formatting) => formatting.format_time(seconds),
+            None => format_time(seconds),
+        }
+    }
+}
+
+// Batch jobs write a <clip>_thumb.jpg per nugget, in nugget order. When the counts don't line
+// up (some clips were deleted, say) there's no telling which is which, so none are used
+pub fn nugget_thumbnails(output_files: &[String], nugget_count: usize) -> Vec<Option<PathBuf>> {
+    let thumbnails: Vec<PathBuf> = output_files.iter()
+        .filter(|file| file.ends_with("_thumb.jpg"))
+        .map(PathBuf::from)
+        .collect();
+    if thumbnails.len() != nugget_count {
+        return vec![None; nugget_count];
+    }
+    thumbnails.into_iter()
+        .map(|path| path.exists().then_some(path))
+        .collect()
+}
+
+struct Fonts {
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    italic: IndirectFontRef,
+}
+
+// Writes top to bottom, starting a new page when the next block doesn't fit
+struct Layout {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    fonts: Fonts,
+    y: f32,
+    pages: usize,
+}
+
+// A cover page with the video's analysis, then a section per nugget. Returns how many pages
+// the document has.
+pub fn write_pdf(report: &PdfReport, path: &Path) -> Result<usize, String> {
+    let mut layout = Layout::new(&report.title)?;
+
+    write_cover(&mut layout, report);
+    for (index, nugget) in report.nuggets.iter().enumerate() {
+        if index == 0 {
+            layout.new_page();
+        }
+        let thumbnail = report.thumbnails.get(index).and_then(|t| t.as_deref());
+        write_nugget(&mut layout, report, index, nugget, thumbnail);
+    }
+
+    let pages = layout.pages;
+    let bytes = layout.doc.save_to_bytes()
+        .map_err(|e| format!("Failed to build PDF: {}", e))?;
+    write_atomic(path, &bytes)
+        .map_err(|e| format!("Failed to write PDF file: {}", e))?;
+    Ok(pages)
+}
+
+fn write_cover(layout: &mut Layout, report: &PdfReport) {
+    layout.space(40.0);
+    layout.paragraph(&report.title, 26.0, FontStyle::Bold);
+    if let Some(subtitle) = &report.subtitle {
+        layout.paragraph(subtitle, 14.0, FontStyle::Regular);
+    }
+    layout.space(6.0);
+
+    let total: f64 = report.nuggets.iter().map(|n| n.end_time - n.start_time).sum();
+    layout.muted(&format!("{} nuggets, {} in total", report.nuggets.len(), report.time(total)), 11.0);
+    if let Some(url) = &report.source_url {
+        layout.muted(&format!("Source: {}", url), 11.0);
+    }
+    let generated = match &report.formatting {
+        Some(formatting) => formatting.format_date(&chrono::Utc::now().to_rfc3339()),
+        None => chrono::Utc::now().format("%Y-%m-%d").to_string(),
+    };
+    layout.muted(&format!("Generated {}", generated), 11.0);
+
+    let Some(analysis) = &report.analysis else {
+        return;
+    };
+    layout.space(12.0);
+    if !analysis.summary.trim().is_empty() {
+        layout.paragraph("Summary", 14.0, FontStyle::Bold);
+        layout.paragraph(&analysis.summary, 11.0, FontStyle::Regular);
+        layout.space(4.0);
+    }
+    if !analysis.key_topics.is_empty() {
+        layout.paragraph(&format!("Key topics: {}", analysis.key_topics.join(", ")), 11.0, FontStyle::Regular);
+    }
+    if !analysis.content_categories.is_empty() {
+        layout.paragraph(&format!("Categories: {}", analysis.content_categories.join(", ")), 11.0, FontStyle::Regular);
+    }
+    layout.paragraph(&format!(
+        "Difficulty: {}    Sentiment: {:.2}    Engagement: {:.2}",
+        analysis.difficulty_level, analysis.sentiment_score, analysis.engagement_score
+    ), 11.0, FontStyle::Regular);
+}
+
+fn write_nugget(layout: &mut Layout, report: &PdfReport, index: usize, nugget: &VideoNugget, thumbnail: Option<&Path>) {
+    layout.ensure_space(SECTION_MIN_SPACE);
+    layout.paragraph(&format!("{}. {}", index + 1, nugget.title), 16.0, FontStyle::Bold);
+    layout.muted(&format!(
+        "{} - {} ({:.0}s)",
+        report.time(nugget.start_time),
+        report.time(nugget.end_time),
+        nugget.end_time - nugget.start_time
+    ), 10.0);
+    if let Some(url) = &report.source_url {
+        layout.muted(&format!("Watch: {}", timestamp_link(url, nugget.start_time)), 10.0);
+    }
+    if !nugget.tags.is_empty() {
+        layout.muted(&format!("Tags: {}", nugget.tags.join(", ")), 10.0);
+    }
+    layout.space(3.0);
+
+    if let Some(thumbnail) = thumbnail {
+        layout.image(thumbnail);
+    }
+
+    if let Some(analysis) = &report.analysis {
+        layout.paragraph("Analysis", 12.0, FontStyle::Bold);
+        layout.paragraph(&format!("Sentiment: {:.2}", nugget_sentiment(nugget, analysis)), 10.0, FontStyle::Regular);
+        for moment in analysis.highlight_moments.iter()
+            .filter(|m| m.start_time < nugget.end_time && m.end_time > nugget.start_time)
+        {
+            layout.paragraph(&format!("- {:?} at {}: {}", moment.moment_type, report.time(moment.start_time), moment.reason), 10.0, FontStyle::Regular);
+        }
+        layout.space(2.0);
+    }
+
+    if let Some(transcript) = nugget.transcript.as_deref().filter(|t| !t.trim().is_empty()) {
+        layout.paragraph("Transcript", 12.0, FontStyle::Bold);
+        layout.paragraph(transcript, 10.0, FontStyle::Italic);
+        layout.space(2.0);
+    }
+    if let Some(notes) = nugget.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+        layout.paragraph("Notes", 12.0, FontStyle::Bold);
+        layout.paragraph(notes, 10.0, FontStyle::Regular);
+    }
+    layout.space(8.0);
+}
+
+#[derive(Clone, Copy)]
+enum FontStyle {
+    Regular,
+    Bold,
+    Italic,
+}
+
+impl Layout {
+    fn new(title: &str) -> Result<Self, String> {
+        let (doc, page, layer) = PdfDocument::new(pdf_text(title), Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1".to_string());
+        let font = |font| doc.add_builtin_font(font)
+            .map_err(|e| format!("Failed to load PDF font: {}", e));
+        let fonts = Fonts {
+            regular: font(BuiltinFont::Helvetica)?,
+            bold: font(BuiltinFont::HelveticaBold)?,
+            italic: font(BuiltinFont::HelveticaOblique)?,
+        };
+        let layer = doc.get_page(page).get_layer(layer);
+
+        Ok(Self {
+            doc,
+            layer,
+            fonts,
+            y: PAGE_HEIGHT - MARGIN,
+            pages: 1,
+        })
+    }
+
+    fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1".to_string());
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.pages += 1;
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn paragraph(&mut self, text: &str, size: f32, style: FontStyle) {
+        let line_height = size * POINT_IN_MM * 1.4;
+        let font = match style {
+            FontStyle::Regular => &self.fonts.regular,
+            FontStyle::Bold => &self.fonts.bold,
+            FontStyle::Italic => &self.fonts.italic,
+        }.clone();
+
+        for line in wrap_text(&pdf_text(text), max_chars(PAGE_WIDTH - 2.0 * MARGIN, size)) {
+            self.ensure_space(line_height);
+            self.y -= line_height;
+            self.layer.use_text(line, size, Mm(MARGIN), Mm(self.y), &font);
+        }
+    }
+
+    fn muted(&mut self, text: &str, size: f32) {
+        self.layer.set_fill_color(Color::Rgb(Rgb::new(0.4, 0.4, 0.4, None)));
+        self.paragraph(text, size, FontStyle::Regular);
+        self.layer.set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+    }
+
+    // Scaled down to fit THUMBNAIL_MAX_WIDTH x THUMBNAIL_MAX_HEIGHT, never up
+    fn image(&mut self, path: &Path) {
+        let Ok(decoded) = image_crate::open(path) else {
+            return;
+        };
+        let image = Image::from_dynamic_image(&decoded);
+        let natural_width = image.image.width.0 as f32 / IMAGE_DPI * 25.4;
+        let natural_height = image.image.height.0 as f32 / IMAGE_DPI * 25.4;
+        if natural_width <= 0.0 || natural_height <= 0.0 {
+            return;
+        }
+        let scale = (THUMBNAIL_MAX_WIDTH / natural_width)
+            .min(THUMBNAIL_MAX_HEIGHT / natural_height)
+            .min(1.0);
+        let height = natural_height * scale;
+
+        self.ensure_space(height + 4.0);
+        self.y -= height;
+        image.add_to_layer(self.layer.clone(), ImageTransform {
+            translate_x: Some(Mm(MARGIN)),
+            translate_y: Some(Mm(self.y)),
+            scale_x: Some(scale),
+            scale_y: Some(scale),
+            dpi: Some(IMAGE_DPI),
+            ..Default::default()
+        });
+        self.y -= 4.0;
+    }
+}
+
+// Helvetica averages about half an em per character, close enough to wrap on
+fn max_chars(width: f32, size: f32) -> usize {
+    ((width / (size * POINT_IN_MM * 0.5)) as usize).max(10)
+}
+
+// Greedy word wrap that keeps the text's own line breaks; words longer than a line are split
+fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            while word.len() > max_chars {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                lines.push(word.drain(..max_chars).collect());
+            }
+            let word: String = word.into_iter().collect();
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+    // Blank lines between paragraphs stay, trailing ones don't
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+// The built-in fonts only cover Latin-1, so typographic punctuation is swapped for its ASCII
+// version and anything else outside it becomes '?'
+fn pdf_text(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| -> Vec<char> {
+            match c {
+                '\u{2018}' | '\u{2019}' => vec!['\''],
+                '\u{201C}' | '\u{201D}' => vec!['"'],
+                '\u{2013}' | '\u{2014}' => vec!['-'],
+                '\u{2026}' => vec!['.', '.', '.'],
+                '\t' => vec![' '],
+                c if c.is_control() && c != '\n' => Vec::new(),
+                c if (c as u32) < 0x80 || (0xA0..=0xFF).contains(&(c as u32)) => vec![c],
+                _ => vec!['?'],
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_text() {
+        assert_eq!(wrap_text("one two three four", 9), vec!["one two", "three", "four"]);
+        assert_eq!(wrap_text("first\n\nsecond\n", 20), vec!["first", "", "second"]);
+        assert_eq!(wrap_text("abcdefghijkl", 5), vec!["abcde", "fghij", "kl"]);
+    }
+
+    #[test]
+    fn test_pdf_text() {
+        assert_eq!(pdf_text("\u{201C}Caf\u{e9}\u{201D} \u{2014} done\u{2026}"), "\"Caf\u{e9}\" - done...");
+        assert_eq!(pdf_text("\u{65e5}\u{672c}"), "??");
+    }
+
+    #[test]
+    fn test_nugget_thumbnails() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let thumb = temp_dir.path().join("nugget_001_thumb.jpg");
+        std::fs::write(&thumb, b"jpg").unwrap();
+        let files = vec![
+            temp_dir.path().join("nugget_001.mp4").to_string_lossy().to_string(),
+            thumb.to_string_lossy().to_string(),
+            temp_dir.path().join("nugget_002_thumb.jpg").to_string_lossy().to_string(),
+        ];
+
+        assert_eq!(nugget_thumbnails(&files, 2), vec![Some(thumb), None]);
+        assert_eq!(nugget_thumbnails(&files, 3), vec![None, None, None]);
+    }
+
+    #[test]
+    fn test_write_pdf() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let nugget = VideoNugget {
+            id: "n1".to_string(),
+            title: "Why ownership matters".to_string(),
+            start_time: 12.0,
+            end_time: 48.0,
+            transcript: Some("Ownership lets the compiler free memory for you. ".repeat(40)),
+            tags: vec!["rust".to_string()],
+            notes: Some("Use in the intro".to_string()),
+            youtube_video_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let mut report = PdfReport::from_nuggets("Rust talk", vec![nugget]);
+        report.source_url = Some("https://www.youtube.com/watch?v=abc".to_string());
+        report.thumbnails = vec![Some(temp_dir.path().join("missing_thumb.jpg"))];
+
+        let path = temp_dir.path().join("report.pdf");
+        assert_eq!(write_pdf(&report, &path).unwrap(), 2);
+        assert!(std::fs::read(&path).unwrap().starts_with(b"%PDF"));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::processed_index::video_key;
+use crate::project_crypto::{read_data, write_data, DataKey};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+const VIDEO_FILE: &str = "video.mp4";
+const TRANSCRIPT_FILE: &str = "transcript.json";
+const NUGGETS_FILE: &str = "nuggets.json";
+
+// Intermediate results shared by the jobs of one pipeline, so the analysis stage reuses the
+// transcription stage's download and transcript instead of starting over. One folder per video.
+pub struct ArtifactStore {
+    root: PathBuf,
+    // For the transcripts and nuggets, when the workspace is encrypted
+    key: Option<DataKey>,
+}
+
+impl ArtifactStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root, key: None }
+    }
+
+    pub fn with_key(mut self, key: Option<DataKey>) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn video_path(&self, url: &str) -> Option<PathBuf> {
+        let path = self.video_dir(url).join(VIDEO_FILE);
+        path.exists().then_some(path)
+    }
+
+    // Moves the download out of its temp dir, falling back to a copy across filesystems
+    pub fn store_video(&self, url: &str, downloaded: &Path) -> Result<PathBuf, String> {
+        let path = self.video_dir(url).join(VIDEO_FILE);
+        self.ensure_dir(url)?;
+
+        if std::fs::rename(downloaded, &path).is_err() {
+            std::fs::copy(downloaded, &path)
+                .map_err(|e| format!("Failed to store pipeline video: {}", e))?;
+        }
+
+        Ok(path)
+    }
+
+    pub fn load_transcript<T: DeserializeOwned>(&self, url: &str) -> Option<T> {
+        self.load_json(url, TRANSCRIPT_FILE)
+    }
+
+    pub fn store_transcript<T: Serialize>(&self, url: &str, transcript: &T) -> Result<PathBuf, String> {
+        self.store_json(url, TRANSCRIPT_FILE, transcript)
+    }
+
+    pub fn load_nuggets<T: DeserializeOwned>(&self, url: &str) -> Option<T> {
+        self.load_json(url, NUGGETS_FILE)
+    }
+
+    pub fn store_nuggets<T: Serialize>(&self, url: &str, nuggets: &T) -> Result<PathBuf, String> {
+        self.store_json(url, NUGGETS_FILE, nuggets)
+    }
+
+    pub fn remove_all(&self) -> Result<(), String> {
+        if self.root.exists() {
+            std::fs::remove_dir_all(&self.root)
+                .map_err(|e| format!("Failed to remove pipeline artifacts: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn video_dir(&self, url: &str) -> PathBuf {
+        let hash = format!("{:x}", Sha256::digest(video_key(url).as_bytes()));
+        self.root.join(&hash[..16])
+    }
+
+    fn ensure_dir(&self, url: &str) -> Result<(), String> {
+        std::fs::create_dir_all(self.video_dir(url))
+            .map_err(|e| format!("Failed to create pipeline artifact directory: {}", e))
+    }
+
+    // A missing or unreadable artifact just means the stage redoes the work
+    fn load_json<T: DeserializeOwned>(&self, url: &str, name: &str) -> Option<T> {
+        let content = read_data(&self.video_dir(url).join(name), self.key.as_ref()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn store_json<T: Serialize>(&self, url: &str, name: &str, value: &T) -> Result<PathBuf, String> {
+        self.ensure_dir(url)?;
+        let path = self.video_dir(url).join(name);
+
+        let json_data = serde_json::to_string_pretty(value)
+            .map_err(|e| format!("Failed to serialize pipeline artifact: {}", e))?;
+
+        write_data(&path, &json_data, self.key.as_ref())
+            .map_err(|e| format!("Failed to save pipeline artifact: {}", e))?;
+
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifacts_are_shared_across_url_variants() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let store = ArtifactStore::new(temp_dir.path().join("pipeline"));
+
+        store.store_nuggets("https://www.youtube.com/watch?v=dQw4w9WgXcQ", &vec!["intro", "outro"]).unwrap();
+        let nuggets: Option<Vec<String>> = store.load_nuggets("https://youtu.be/dQw4w9WgXcQ");
+        assert_eq!(nuggets, Some(vec!["intro".to_string(), "outro".to_string()]));
+
+        let missing: Option<Vec<String>> = store.load_transcript("https://youtu.be/dQw4w9WgXcQ");
+        assert!(missing.is_none());
+
+        let downloaded = temp_dir.path().join("download.mp4");
+        std::fs::write(&downloaded, b"video").unwrap();
+        let stored = store.store_video("https://youtu.be/dQw4w9WgXcQ", &downloaded).unwrap();
+        assert_eq!(store.video_path("https://youtu.be/dQw4w9WgXcQ"), Some(stored));
+
+        store.remove_all().unwrap();
+        assert!(store.video_path("https://youtu.be/dQw4w9WgXcQ").is_none());
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub const PROCESSED_INDEX_FILE: &str = "processed_videos.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessedVideo {
+    pub video_key: String,
+    pub url: String,
+    // SHA-256 of the downloaded file, catches the same video re-uploaded under another URL
+    pub content_hash: Option<String>,
+    pub batch_job_id: String,
+    pub processed_at: String,
+}
+
+// Videos a project has already been through, so re-running a playlist only picks up new ones
+pub struct ProcessedIndex {
+    path: PathBuf,
+    entries: HashMap<String, ProcessedVideo>,
+}
+
+impl ProcessedIndex {
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        let entries = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read processed video index: {}", e))?;
+
+            let videos: Vec<ProcessedVideo> = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse processed video index: {}", e))?;
+
+            videos.into_iter()
+                .map(|video| (video.video_key.clone(), video))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self { path, entries })
+    }
+
+    pub fn contains_url(&self, url: &str) -> bool {
+        self.entries.contains_key(&video_key(url))
+    }
+
+    pub fn contains_hash(&self, content_hash: &str) -> bool {
+        self.entries.values()
+            .any(|video| video.content_hash.as_deref() == Some(content_hash))
+    }
+
+    pub fn record(&mut self, url: &str, content_hash: Option<String>, batch_job_id: &str) {
+        let video_key = video_key(url);
+        self.entries.insert(video_key.clone(), ProcessedVideo {
+            video_key,
+            url: url.to_string(),
+            content_hash,
+            batch_job_id: batch_job_id.to_string(),
+            processed_at: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create processed video index directory: {}", e))?;
+        }
+
+        let mut videos: Vec<&ProcessedVideo> = self.entries.values().collect();
+        videos.sort_by(|a, b| a.processed_at.cmp(&b.processed_at));
+
+        let json_data = serde_json::to_string_pretty(&videos)
+            .map_err(|e| format!("Failed to serialize processed video index: {}", e))?;
+
+        std::fs::write(&self.path, json_data)
+            .map_err(|e| format!("Failed to save processed video index: {}", e))
+    }
+}
+
+// YouTube URLs are keyed by video ID so watch, short and playlist links all match;
+// anything else by its URL without query string or fragment
+pub fn video_key(url: &str) -> String {
+    if let Some(video_id) = youtube_video_id(url) {
+        return format!("youtube:{}", video_id);
+    }
+
+    let normalized = url.trim()
+        .split(['?', '#'])
+        .next()
+        .unwrap_or("")
+        .trim_end_matches('/')
+        .to_lowercase();
+
+    format!("url:{}", normalized)
+}
+
+pub fn youtube_video_id(url: &str) -> Option<String> {
+    let candidate = if url.contains("youtube.com/") {
+        url.split(['?', '&'])
+            .find_map(|part| part.strip_prefix("v="))
+            .or_else(|| url.split("/shorts/").nth(1))
+    } else if url.contains("youtu.be/") {
+        url.split("youtu.be/").nth(1)
+    } else {
+        None
+    }?;
+
+    let video_id: String = candidate.chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+
+    if video_id.is_empty() { None } else { Some(video_id) }
+}
+
+pub fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open file for hashing: {}", e))?;
+
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)
+            .map_err(|e| format!("Failed to read file for hashing: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_video_key_matches_youtube_url_variants() {
+        let key = video_key("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+        assert_eq!(key, "youtube:dQw4w9WgXcQ");
+        assert_eq!(video_key("https://youtu.be/dQw4w9WgXcQ?t=42"), key);
+        assert_eq!(video_key("https://www.youtube.com/watch?list=PL123&v=dQw4w9WgXcQ&index=3"), key);
+        assert_eq!(video_key("https://www.youtube.com/shorts/dQw4w9WgXcQ"), key);
+
+        assert_eq!(video_key("https://Example.com/talk.mp4?utm_source=x"), "url:https://example.com/talk.mp4");
+    }
+
+    #[test]
+    fn test_index_round_trip() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join(PROCESSED_INDEX_FILE);
+
+        let mut index = ProcessedIndex::load(path.clone()).unwrap();
+        assert!(!index.contains_url("https://youtu.be/dQw4w9WgXcQ"));
+        index.record("https://youtu.be/dQw4w9WgXcQ", Some("abc123".to_string()), "job-1");
+        index.save().unwrap();
+
+        let index = ProcessedIndex::load(path).unwrap();
+        assert!(index.contains_url("https://www.youtube.com/watch?v=dQw4w9WgXcQ"));
+        assert!(index.contains_hash("abc123"));
+        assert!(!index.contains_hash("def456"));
+    }
+}
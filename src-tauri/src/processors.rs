@@ -0,0 +1,94 @@
+use std::sync::{Mutex, MutexGuard};
+use crate::app_settings;
+use crate::capabilities;
+use crate::ffmpeg_processor::{FFmpegProcessor, FfmpegError};
+use crate::speech_recognition::{SpeechError, SpeechRecognizer};
+use crate::youtube_extractor::YouTubeExtractor;
+
+// The processors commands and batch runs share, so tools are probed once instead of on every
+// call. They're probed the first time they're needed, not at startup, so a missing FFmpeg only
+// fails what needs it; a failed probe is tried again next time, in case it was installed since.
+// Callers get a clone, and take FFmpegProcessor::scratch when they write files.
+pub struct Processors {
+    ffmpeg: Mutex<Option<FFmpegProcessor>>,
+    speech: Mutex<Option<SpeechRecognizer>>,
+    youtube: YouTubeExtractor,
+}
+
+impl Default for Processors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Processors {
+    pub fn new() -> Self {
+        Self {
+            ffmpeg: Mutex::new(None),
+            speech: Mutex::new(None),
+            youtube: YouTubeExtractor::new(),
+        }
+    }
+
+    pub fn ffmpeg(&self) -> Result<FFmpegProcessor, FfmpegError> {
+        let mut ffmpeg = lock(&self.ffmpeg);
+        if let Some(processor) = ffmpeg.as_ref() {
+            return Ok(processor.clone());
+        }
+        let mut processor = FFmpegProcessor::new()?;
+        // Test encodes take a moment, another reason to probe only once
+        if app_settings::current().defaults.hardware_encoding {
+            let encoder = capabilities::hardware_encoders(processor.ffmpeg_path()).into_iter().next();
+            if let Some(encoder) = &encoder {
+                tracing::info!("Encoding video with {}", encoder.api);
+            }
+            processor = processor.with_video_encoder(encoder);
+        }
+        *ffmpeg = Some(processor.clone());
+        Ok(processor)
+    }
+
+    pub fn speech(&self) -> Result<SpeechRecognizer, SpeechError> {
+        let mut speech = lock(&self.speech);
+        if let Some(recognizer) = speech.as_ref() {
+            return Ok(recognizer.clone());
+        }
+        let recognizer = SpeechRecognizer::new()?;
+        *speech = Some(recognizer.clone());
+        Ok(recognizer)
+    }
+
+    pub fn youtube(&self) -> &YouTubeExtractor {
+        &self.youtube
+    }
+
+    // After the tool paths in the settings change. Operations already running keep the
+    // processors they have
+    pub fn reset(&self) {
+        *lock(&self.ffmpeg) = None;
+        *lock(&self.speech) = None;
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_a_successful_probe_is_kept() {
+        let processors = Processors::new();
+
+        let probed = processors.ffmpeg().is_ok();
+        assert_eq!(lock(&processors.ffmpeg).is_some(), probed);
+        let probed = processors.speech().is_ok();
+        assert_eq!(lock(&processors.speech).is_some(), probed);
+
+        processors.reset();
+        assert!(lock(&processors.ffmpeg).is_none());
+        assert!(lock(&processors.speech).is_none());
+    }
+}
@@ -0,0 +1,230 @@
+use crate::operations::{OperationProgress, OperationRegistry};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+
+// Events waiting for the slowest client; one further behind skips ahead
+const CHANNEL_CAPACITY: usize = 256;
+const HEARTBEAT: Duration = Duration::from_secs(15);
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+struct Server {
+    port: u16,
+    shutdown: CancellationToken,
+}
+
+// Streams operation progress, batch jobs included, to dashboards and OBS browser sources on
+// this machine, as Server-Sent Events on localhost:
+//   GET /events      the running operations first, then every event the registry emits
+//   GET /operations  the running operations as JSON
+// Each event is the OperationProgress the app gets as operation://progress.
+pub struct ProgressStream {
+    sender: broadcast::Sender<OperationProgress>,
+    server: Mutex<Option<Server>>,
+}
+
+impl Default for ProgressStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressStream {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            server: Mutex::new(None),
+        }
+    }
+
+    // Called for every registry event; without a client listening it's dropped
+    pub fn publish(&self, progress: &OperationProgress) {
+        let _ = self.sender.send(progress.clone());
+    }
+
+    // Starts serving on port, or on any free port for 0, replacing a server already running.
+    // Clients of the old one are disconnected
+    pub async fn start(&self, port: u16, operations: OperationRegistry) -> Result<(), String> {
+        self.stop();
+        let listener = TcpListener::bind(("127.0.0.1", port)).await
+            .map_err(|e| format!("Failed to listen on port {}: {}", port, e))?;
+        let port = listener.local_addr()
+            .map_err(|e| format!("Failed to read progress stream address: {}", e))?
+            .port();
+        let shutdown = CancellationToken::new();
+        tokio::spawn(serve(listener, self.sender.clone(), operations, shutdown.clone()));
+
+        tracing::info!("Streaming progress on http://127.0.0.1:{}/events", port);
+        *self.lock() = Some(Server { port, shutdown });
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if let Some(server) = self.lock().take() {
+            server.shutdown.cancel();
+        }
+    }
+
+    // The event stream's address while it's running
+    pub fn url(&self) -> Option<String> {
+        self.lock().as_ref().map(|server| format!("http://127.0.0.1:{}/events", server.port))
+    }
+
+    // Starts, stops or moves the server to match the settings
+    pub async fn apply(&self, enabled: bool, port: u16, operations: OperationRegistry) -> Result<(), String> {
+        let running_port = self.lock().as_ref().map(|server| server.port);
+        match (enabled, running_port) {
+            (false, _) => {
+                self.stop();
+                Ok(())
+            }
+            (true, Some(running_port)) if running_port == port => Ok(()),
+            (true, _) => self.start(port, operations).await,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Server>> {
+        self.server.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+async fn serve(listener: TcpListener, sender: broadcast::Sender<OperationProgress>, operations: OperationRegistry, shutdown: CancellationToken) {
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::warn!("Failed to accept progress stream client: {}", e);
+                    continue;
+                }
+            },
+            _ = shutdown.cancelled() => return,
+        };
+
+        let receiver = sender.subscribe();
+        let operations = operations.clone();
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            // Clients going away mid-write is how every stream ends, so it's not worth a warning
+            if let Err(e) = handle_client(stream, receiver, operations, shutdown).await {
+                tracing::debug!("Progress stream client disconnected: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_client(
+    stream: TcpStream,
+    mut receiver: broadcast::Receiver<OperationProgress>,
+    operations: OperationRegistry,
+    shutdown: CancellationToken,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    // Only the request line matters, the headers are read past
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+    let mut stream = reader.into_inner().into_inner();
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default().split('?').next().unwrap_or_default();
+    match (method, path) {
+        ("GET", "/events") => {}
+        ("GET", "/operations") => {
+            let body = serde_json::to_string(&operations.list()).unwrap_or_else(|_| "[]".to_string());
+            return respond(&mut stream, "200 OK", "application/json", &body).await;
+        }
+        ("OPTIONS", _) => return respond(&mut stream, "204 No Content", "text/plain", "").await,
+        _ => return respond(&mut stream, "404 Not Found", "text/plain", "Not found").await,
+    }
+
+    // Browser sources and dashboards are usually served from another origin
+    stream.write_all(b"HTTP/1.1 200 OK\r\n\
+        Content-Type: text/event-stream\r\n\
+        Cache-Control: no-cache\r\n\
+        Connection: keep-alive\r\n\
+        Access-Control-Allow-Origin: *\r\n\r\n").await?;
+    for progress in operations.list() {
+        write_event(&mut stream, &progress).await?;
+    }
+
+    let mut heartbeat = tokio::time::interval(HEARTBEAT);
+    loop {
+        tokio::select! {
+            received = receiver.recv() => match received {
+                Ok(progress) => write_event(&mut stream, &progress).await?,
+                // The next event for an operation has its whole state, so skipped ones aren't missed
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Ok(()),
+            },
+            _ = heartbeat.tick() => stream.write_all(b": keep-alive\n\n").await?,
+            _ = shutdown.cancelled() => return Ok(()),
+        }
+    }
+}
+
+async fn write_event(stream: &mut TcpStream, progress: &OperationProgress) -> std::io::Result<()> {
+    let data = serde_json::to_string(progress)
+        .map_err(std::io::Error::other)?;
+    stream.write_all(format!("event: progress\ndata: {}\n\n", data).as_bytes()).await?;
+    stream.flush().await
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::OperationKind;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_streams_progress_events() {
+        let stream = Arc::new(ProgressStream::new());
+        let publisher = stream.clone();
+        let operations = OperationRegistry::new(move |progress| publisher.publish(progress));
+        let running = operations.start(Some("op-1".to_string()), OperationKind::Rendering, "clip").unwrap();
+
+        stream.start(0, operations.clone()).await.unwrap();
+        let url = stream.url().unwrap();
+        let address = url.trim_start_matches("http://").trim_end_matches("/events").to_string();
+
+        let mut client = TcpStream::connect(&address).await.unwrap();
+        client.write_all(b"GET /events HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        let mut lines = BufReader::new(client).lines();
+        let mut data = Vec::new();
+        while data.len() < 2 {
+            let line = lines.next_line().await.unwrap().unwrap();
+            if let Some(json) = line.strip_prefix("data: ") {
+                data.push(serde_json::from_str::<OperationProgress>(json).unwrap());
+                // The snapshot is in, anything after it comes from the registry
+                running.stage("Encoding");
+            }
+        }
+        assert_eq!(data[0].operation_id, "op-1");
+        assert_eq!(data[1].stage, "Encoding");
+
+        stream.stop();
+        assert!(stream.url().is_none());
+    }
+}
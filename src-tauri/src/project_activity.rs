@@ -0,0 +1,152 @@
+use crate::project_manager::{EventType, ProcessingEvent, Project};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+
+pub const ACTIVITY_PAGE_SIZE: usize = 50;
+
+// Every field narrows the results; an empty filter returns everything
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ActivityFilter {
+    #[serde(default)]
+    pub event_types: Vec<EventType>,
+    // Only that video's events, project-wide events are left out
+    pub video_id: Option<String>,
+    // RFC 3339 timestamps or plain dates, both ends inclusive
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActivityEntry {
+    #[serde(flatten)]
+    pub event: ProcessingEvent,
+    // None for project-wide events
+    pub video_id: Option<String>,
+    pub video_title: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActivityPage {
+    // Newest first
+    pub entries: Vec<ActivityEntry>,
+    pub page: usize,
+    pub page_size: usize,
+    pub total: usize,
+}
+
+// Project-wide events and every video's history, merged into one timeline
+pub fn query_activity(project: &Project, filter: &ActivityFilter, page: usize) -> Result<ActivityPage, String> {
+    let from = filter.from.as_deref().map(|value| parse_bound(value, false)).transpose()?;
+    let to = filter.to.as_deref().map(|value| parse_bound(value, true)).transpose()?;
+
+    let project_events = project.activity.iter().map(|event| (event, None));
+    let video_events = project.videos.iter()
+        .flat_map(|video| video.processing_history.iter().map(move |event| (event, Some(video))));
+
+    let mut matching: Vec<(DateTime<Utc>, ActivityEntry)> = project_events
+        .chain(video_events)
+        .filter(|(event, _)| {
+            filter.event_types.is_empty() || filter.event_types.contains(&event.event_type)
+        })
+        .filter(|(_, video)| match &filter.video_id {
+            Some(video_id) => video.is_some_and(|v| &v.id == video_id),
+            None => true,
+        })
+        .filter_map(|(event, video)| {
+            // Events with an unreadable timestamp sort last
+            let timestamp = DateTime::parse_from_rfc3339(&event.timestamp)
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or(DateTime::<Utc>::MIN_UTC);
+            if from.is_some_and(|from| timestamp < from) || to.is_some_and(|to| timestamp > to) {
+                return None;
+            }
+            Some((timestamp, ActivityEntry {
+                event: event.clone(),
+                video_id: video.map(|v| v.id.clone()),
+                video_title: video.map(|v| v.video_info.title.clone()),
+            }))
+        })
+        .collect();
+    matching.sort_by_key(|(timestamp, _)| Reverse(*timestamp));
+
+    let total = matching.len();
+    let entries = matching.into_iter()
+        .skip(page * ACTIVITY_PAGE_SIZE)
+        .take(ACTIVITY_PAGE_SIZE)
+        .map(|(_, entry)| entry)
+        .collect();
+
+    Ok(ActivityPage {
+        entries,
+        page,
+        page_size: ACTIVITY_PAGE_SIZE,
+        total,
+    })
+}
+
+// A plain date covers the whole day, so `to` is the end of it
+fn parse_bound(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}", value))?;
+    let time = if end_of_day {
+        date.and_hms_milli_opt(23, 59, 59, 999)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    time.map(|t| t.and_utc())
+        .ok_or_else(|| format!("Invalid date: {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_manager::ProjectManager;
+    use crate::VideoInfo;
+    use std::collections::HashMap;
+
+    fn video_info(title: &str) -> VideoInfo {
+        VideoInfo {
+            title: title.to_string(),
+            duration: 60.0,
+            url: format!("https://example.com/{}", title),
+            thumbnail: None,
+        }
+    }
+
+    #[test]
+    fn test_query_activity() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let first = manager.add_video_to_project(&project_id, video_info("first"), Vec::new(), None).unwrap();
+        manager.add_video_to_project(&project_id, video_info("second"), Vec::new(), None).unwrap();
+        manager.add_processing_event(&project_id, EventType::ExportCreated, "Exported".to_string(), HashMap::new()).unwrap();
+
+        let project = manager.get_project(&project_id).unwrap().unwrap();
+        assert!(project.videos.iter().all(|v| v.processing_history.len() == 1));
+
+        let all = query_activity(project, &ActivityFilter::default(), 0).unwrap();
+        assert_eq!(all.total, 3);
+        assert!(matches!(all.entries[0].event.event_type, EventType::ExportCreated));
+        assert_eq!(all.entries[0].video_id, None);
+
+        let filter = ActivityFilter {
+            event_types: vec![EventType::VideoAdded],
+            video_id: Some(first.clone()),
+            ..ActivityFilter::default()
+        };
+        let page = query_activity(project, &filter, 0).unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.entries[0].video_id, Some(first));
+
+        let future = ActivityFilter { from: Some("2999-01-01".to_string()), ..ActivityFilter::default() };
+        assert_eq!(query_activity(project, &future, 0).unwrap().total, 0);
+        assert!(query_activity(project, &ActivityFilter { to: Some("yesterday".to_string()), ..ActivityFilter::default() }, 0).is_err());
+        assert!(query_activity(project, &ActivityFilter::default(), 1).unwrap().entries.is_empty());
+    }
+}
@@ -0,0 +1,289 @@
+use crate::project_manager::Project;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const PROJECT_ENTRY: &str = "project.json";
+// Files from the project's own directory keep their layout under this prefix
+const WORKSPACE_PREFIX: &str = "workspace/";
+// Clips, thumbnails and exports written elsewhere (a batch output directory, usually)
+const MEDIA_PREFIX: &str = "media/";
+const COPY_BUFFER_SIZE: usize = 256 * 1024;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ArchiveProgress {
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub current_file: String,
+}
+
+struct ArchiveEntry {
+    source: PathBuf,
+    name: String,
+    size: u64,
+}
+
+// Writes project.json plus every file the project references into a zip. Paths inside the
+// archived project.json point into the archive, import_archive points them back at disk.
+pub fn export_archive(project: &Project, archive_path: &Path, mut on_progress: impl FnMut(&ArchiveProgress)) -> Result<(), String> {
+    let (entries, archived_project) = collect_entries(project);
+
+    let file = File::create(archive_path)
+        .map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut writer = ZipWriter::new(file);
+    // Video is already compressed, deflating it only costs time
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored).large_file(true);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let json_data = serde_json::to_string_pretty(&archived_project)
+        .map_err(|e| format!("Failed to serialize project: {}", e))?;
+    writer.start_file(PROJECT_ENTRY, deflated)
+        .and_then(|_| writer.write_all(json_data.as_bytes()).map_err(Into::into))
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+
+    let mut progress = ArchiveProgress {
+        files_total: entries.len(),
+        bytes_total: entries.iter().map(|entry| entry.size).sum(),
+        ..ArchiveProgress::default()
+    };
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+
+    for entry in &entries {
+        progress.current_file = entry.name.clone();
+        on_progress(&progress);
+
+        let options = if is_compressed_media(&entry.source) { stored } else { deflated };
+        writer.start_file(entry.name.as_str(), options)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+
+        let mut source = File::open(&entry.source)
+            .map_err(|e| format!("Failed to read {}: {}", entry.source.display(), e))?;
+        loop {
+            let read = source.read(&mut buffer)
+                .map_err(|e| format!("Failed to read {}: {}", entry.source.display(), e))?;
+            if read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..read])
+                .map_err(|e| format!("Failed to write archive: {}", e))?;
+            progress.bytes_done += read as u64;
+        }
+
+        progress.files_done += 1;
+    }
+
+    writer.finish()
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+
+    progress.current_file.clear();
+    on_progress(&progress);
+    Ok(())
+}
+
+// Unpacks an archive into project_dir and returns its project with paths pointing there.
+// The caller assigns the project its new ID and saves it.
+pub fn import_archive(archive_path: &Path, project_dir: &Path, mut on_progress: impl FnMut(&ArchiveProgress)) -> Result<Project, String> {
+    let file = File::open(archive_path)
+        .map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+
+    let mut project: Project = {
+        let mut entry = archive.by_name(PROJECT_ENTRY)
+            .map_err(|_| "Archive does not contain a project.json".to_string())?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content)
+            .map_err(|e| format!("Failed to read project data: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse project data: {}", e))?
+    };
+
+    let mut progress = ArchiveProgress {
+        files_total: archive.len().saturating_sub(1),
+        bytes_total: (0..archive.len())
+            .filter_map(|index| {
+                let entry = archive.by_index_raw(index).ok()?;
+                (entry.name() != PROJECT_ENTRY).then(|| entry.size())
+            })
+            .sum(),
+        ..ArchiveProgress::default()
+    };
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)
+            .map_err(|e| format!("Failed to read archive: {}", e))?;
+        if entry.is_dir() || entry.name() == PROJECT_ENTRY {
+            continue;
+        }
+
+        // enclosed_name rejects absolute paths and "..", so nothing lands outside project_dir
+        let Some(target) = entry.enclosed_name().and_then(|name| restore_path(&name.to_string_lossy(), project_dir)) else {
+            continue;
+        };
+        progress.current_file = entry.name().to_string();
+        on_progress(&progress);
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create project directory: {}", e))?;
+        }
+        let mut output = File::create(&target)
+            .map_err(|e| format!("Failed to extract {}: {}", entry.name(), e))?;
+        progress.bytes_done += std::io::copy(&mut entry, &mut output)
+            .map_err(|e| format!("Failed to extract {}: {}", entry.name(), e))?;
+        progress.files_done += 1;
+    }
+
+    for video in &mut project.videos {
+        for file in &mut video.output_files {
+            if let Some(restored) = restore_path(file, project_dir) {
+                *file = restored.to_string_lossy().to_string();
+            }
+        }
+        if let Some(restored) = video.video_info.thumbnail.as_deref().and_then(|thumbnail| restore_path(thumbnail, project_dir)) {
+            video.video_info.thumbnail = Some(restored.to_string_lossy().to_string());
+        }
+    }
+
+    progress.current_file.clear();
+    on_progress(&progress);
+    Ok(project)
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .map(|_| magic == *b"PK\x03\x04")
+        .unwrap_or(false)
+}
+
+// Everything under the project directory, plus each video's files from elsewhere on disk.
+// Returns the entries and a copy of the project with those paths rewritten to archive names.
+fn collect_entries(project: &Project) -> (Vec<ArchiveEntry>, Project) {
+    let mut entries = Vec::new();
+    let mut names: HashMap<PathBuf, String> = HashMap::new();
+
+    for path in walk_files(&project.workspace_path) {
+        let Ok(relative) = path.strip_prefix(&project.workspace_path) else {
+            continue;
+        };
+        // The archive carries its own, rewritten project.json
+        if relative == Path::new(PROJECT_ENTRY) {
+            continue;
+        }
+        let name = format!("{}{}", WORKSPACE_PREFIX, relative.to_string_lossy().replace('\\', "/"));
+        push_entry(&mut entries, &mut names, path, name);
+    }
+
+    let mut archived = project.clone();
+    for video in &mut archived.videos {
+        let video_id = video.id.clone();
+        let mut archive_name = |file: &str, entries: &mut Vec<ArchiveEntry>| -> Option<String> {
+            let path = Path::new(file).canonicalize().ok().filter(|path| path.is_file())?;
+            if let Some(name) = names.get(&path) {
+                return Some(name.clone());
+            }
+            let file_name = path.file_name()?.to_string_lossy().to_string();
+            let name = unique_name(&names, format!("{}{}/{}", MEDIA_PREFIX, video_id, file_name));
+            push_entry(entries, &mut names, path, name.clone());
+            Some(name)
+        };
+
+        for file in &mut video.output_files {
+            if let Some(name) = archive_name(file, &mut entries) {
+                *file = name;
+            }
+        }
+        if let Some(name) = video.video_info.thumbnail.clone().and_then(|thumbnail| archive_name(&thumbnail, &mut entries)) {
+            video.video_info.thumbnail = Some(name);
+        }
+    }
+
+    (entries, archived)
+}
+
+fn push_entry(entries: &mut Vec<ArchiveEntry>, names: &mut HashMap<PathBuf, String>, path: PathBuf, name: String) {
+    let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+    if names.contains_key(&key) {
+        return;
+    }
+    let size = std::fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0);
+    names.insert(key, name.clone());
+    entries.push(ArchiveEntry { source: path, name, size });
+}
+
+// Two videos can both have a "clip_001.mp4", so later ones get a numeric suffix
+pub fn unique_name(names: &HashMap<PathBuf, String>, name: String) -> String {
+    let taken = |candidate: &str| names.values().any(|existing| existing == candidate);
+    if !taken(&name) {
+        return name;
+    }
+
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.ends_with('/') => (stem.to_string(), format!(".{}", extension)),
+        _ => (name.clone(), String::new()),
+    };
+    (2..)
+        .map(|suffix| format!("{}_{}{}", stem, suffix, extension))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or(name)
+}
+
+fn restore_path(name: &str, project_dir: &Path) -> Option<PathBuf> {
+    if let Some(relative) = name.strip_prefix(WORKSPACE_PREFIX) {
+        Some(project_dir.join(relative))
+    } else if name.starts_with(MEDIA_PREFIX) {
+        Some(project_dir.join(name))
+    } else {
+        None
+    }
+}
+
+pub fn walk_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return files;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(walk_files(&path));
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    files
+}
+
+pub fn is_compressed_media(path: &Path) -> bool {
+    let extension = path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    matches!(extension.as_str(), "mp4" | "mov" | "mkv" | "webm" | "mp3" | "m4a" | "jpg" | "jpeg" | "png" | "webp" | "zip")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_name_and_restore_path() {
+        let mut names = HashMap::new();
+        names.insert(PathBuf::from("/a/clip.mp4"), "media/v1/clip.mp4".to_string());
+        assert_eq!(unique_name(&names, "media/v1/clip.mp4".to_string()), "media/v1/clip_2.mp4");
+        assert_eq!(unique_name(&names, "media/v1/other.mp4".to_string()), "media/v1/other.mp4");
+
+        let project_dir = Path::new("/workspace/new-project");
+        assert_eq!(restore_path("workspace/exports/nuggets.json", project_dir), Some(project_dir.join("exports/nuggets.json")));
+        assert_eq!(restore_path("media/v1/clip.mp4", project_dir), Some(project_dir.join("media/v1/clip.mp4")));
+        assert_eq!(restore_path("/somewhere/else.mp4", project_dir), None);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::VideoNugget;
+use crate::ai_analyzer::ContentAnalysis;
+use crate::project_manager::VideoProject;
+use crate::virality_scorer::{Platform, ViralityScorer};
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NuggetRef {
+    pub video_id: String,
+    pub nugget_id: String,
+}
+
+// A named, ordered selection of nuggets from any of the project's videos
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NuggetCollection {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub items: Vec<NuggetRef>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CollectionNugget {
+    pub video_id: String,
+    pub video_title: String,
+    pub nugget: VideoNugget,
+}
+
+// In collection order. Items whose nugget has since been deleted are skipped rather than
+// removed, so undoing the delete brings them back
+pub fn resolve_items(videos: &[VideoProject], items: &[NuggetRef]) -> Vec<CollectionNugget> {
+    items.iter()
+        .filter_map(|item| {
+            let video = videos.iter().find(|v| v.id == item.video_id)?;
+            let nugget = video.nuggets.iter().find(|n| n.id == item.nugget_id)?;
+            Some(CollectionNugget {
+                video_id: video.id.clone(),
+                video_title: video.video_info.title.clone(),
+                nugget: nugget.clone(),
+            })
+        })
+        .collect()
+}
+
+pub fn validate_items(videos: &[VideoProject], items: &[NuggetRef]) -> Result<(), String> {
+    for (index, item) in items.iter().enumerate() {
+        let video = videos.iter()
+            .find(|v| v.id == item.video_id)
+            .ok_or("Video not found in project")?;
+        if !video.nuggets.iter().any(|n| n.id == item.nugget_id) {
+            return Err("Nugget not found".to_string());
+        }
+        if items[..index].contains(item) {
+            return Err("A nugget can only be in a collection once".to_string());
+        }
+    }
+    Ok(())
+}
+
+// Every set field narrows the results; bounds are inclusive
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct NuggetQuery {
+    // Matched case-insensitively against the title, transcript and notes
+    #[serde(default)]
+    pub text: String,
+    // A nugget needs all of them
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub min_sentiment: Option<f64>,
+    pub max_sentiment: Option<f64>,
+    pub min_duration: Option<f64>,
+    pub max_duration: Option<f64>,
+    // Without a platform the nugget's best score on any platform counts
+    pub platform: Option<Platform>,
+    pub min_platform_score: Option<f64>,
+}
+
+impl NuggetQuery {
+    pub fn validate(&self) -> Result<(), String> {
+        let ranges = [
+            ("sentiment", self.min_sentiment, self.max_sentiment),
+            ("duration", self.min_duration, self.max_duration),
+        ];
+        for (name, min, max) in ranges {
+            if let (Some(min), Some(max)) = (min, max) {
+                if min > max {
+                    return Err(format!("The minimum {} is above the maximum", name));
+                }
+            }
+        }
+        if self.min_platform_score.is_some_and(|score| !(0.0..=1.0).contains(&score)) {
+            return Err("Platform score must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+
+    fn matches(&self, nugget: &VideoNugget, analysis: Option<&ContentAnalysis>, scorer: &ViralityScorer) -> bool {
+        let text = self.text.trim().to_lowercase();
+        if !text.is_empty() {
+            let haystack = [Some(nugget.title.as_str()), nugget.transcript.as_deref(), nugget.notes.as_deref()];
+            if !haystack.iter().flatten().any(|field| field.to_lowercase().contains(&text)) {
+                return false;
+            }
+        }
+
+        if !self.tags.iter().all(|tag| nugget.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))) {
+            return false;
+        }
+
+        let duration = nugget.end_time - nugget.start_time;
+        if self.min_duration.is_some_and(|min| duration < min) || self.max_duration.is_some_and(|max| duration > max) {
+            return false;
+        }
+
+        if self.min_sentiment.is_some() || self.max_sentiment.is_some() {
+            // Unanalyzed videos have no sentiment to compare
+            let Some(sentiment) = analysis.map(|analysis| nugget_sentiment(nugget, analysis)) else {
+                return false;
+            };
+            if self.min_sentiment.is_some_and(|min| sentiment < min) || self.max_sentiment.is_some_and(|max| sentiment > max) {
+                return false;
+            }
+        }
+
+        if let Some(min_score) = self.min_platform_score {
+            let platforms = match self.platform {
+                Some(platform) => vec![platform],
+                None => Platform::all().to_vec(),
+            };
+            let best = platforms.into_iter()
+                .map(|platform| scorer.score_nugget(nugget, platform, analysis).overall)
+                .fold(0.0, f64::max);
+            if best < min_score {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+// A saved query; its nuggets are worked out again each time it's opened
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SmartCollection {
+    pub id: String,
+    pub name: String,
+    pub query: NuggetQuery,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// In video and nugget order
+pub fn search_nuggets(videos: &[VideoProject], query: &NuggetQuery) -> Vec<CollectionNugget> {
+    // Without trending topics, so a nugget's score doesn't change from one day to the next
+    let scorer = ViralityScorer::new(Vec::new());
+
+    videos.iter()
+        .flat_map(|video| video.nuggets.iter()
+            .filter(|nugget| query.matches(nugget, video.analysis.as_ref(), &scorer))
+            .map(|nugget| CollectionNugget {
+                video_id: video.id.clone(),
+                video_title: video.video_info.title.clone(),
+                nugget: nugget.clone(),
+            }))
+        .collect()
+}
+
+// The average of the timeline points the nugget overlaps, or the whole video's score when the
+// analysis has no timeline
+pub fn nugget_sentiment(nugget: &VideoNugget, analysis: &ContentAnalysis) -> f64 {
+    let overlapping: Vec<f64> = analysis.sentiment_timeline.iter()
+        .filter(|point| point.start_time < nugget.end_time && point.end_time > nugget.start_time)
+        .map(|point| point.sentiment)
+        .collect();
+    if overlapping.is_empty() {
+        analysis.sentiment_score
+    } else {
+        overlapping.iter().sum::<f64>() / overlapping.len() as f64
+    }
+}
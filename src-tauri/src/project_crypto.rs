@@ -0,0 +1,189 @@
+use crate::atomic_file::write_atomic;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Serialize, Deserialize};
+use std::num::NonZeroU32;
+use std::path::Path;
+
+const ENCRYPTION_FILE: &str = "encryption.json";
+// Encrypted files start with this, anything else is read as plain JSON
+const MAGIC: &[u8] = b"VNENC1";
+const PBKDF2_ITERATIONS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+const VERIFIER: &[u8] = b"video-nugget";
+
+pub const WORKSPACE_LOCKED: &str = "The workspace is encrypted, unlock it with the passphrase first";
+
+// Kept in memory only while the workspace is unlocked
+#[derive(Clone)]
+pub struct DataKey([u8; 32]);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EncryptionStatus {
+    pub enabled: bool,
+    pub unlocked: bool,
+}
+
+// Stored in the workspace next to the projects. The verifier is a known value encrypted with
+// the key, so a wrong passphrase is caught before anything is decrypted with it
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptionConfig {
+    salt: String,
+    iterations: u32,
+    verifier: String,
+}
+
+pub fn is_enabled(workspace_root: &Path) -> bool {
+    workspace_root.join(ENCRYPTION_FILE).exists()
+}
+
+// A new salt each time, so turning encryption off and on again gives a different key
+pub fn create_key(workspace_root: &Path, passphrase: &str) -> Result<DataKey, String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase cannot be empty".to_string());
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    SystemRandom::new().fill(&mut salt)
+        .map_err(|_| "Failed to generate salt".to_string())?;
+    let key = derive_key(passphrase, &salt, PBKDF2_ITERATIONS);
+
+    let config = EncryptionConfig {
+        salt: STANDARD.encode(salt),
+        iterations: PBKDF2_ITERATIONS,
+        verifier: STANDARD.encode(encrypt(&key, VERIFIER)?),
+    };
+    let json_data = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize encryption settings: {}", e))?;
+    write_atomic(&workspace_root.join(ENCRYPTION_FILE), json_data.as_bytes())
+        .map_err(|e| format!("Failed to save encryption settings: {}", e))?;
+
+    Ok(key)
+}
+
+pub fn unlock(workspace_root: &Path, passphrase: &str) -> Result<DataKey, String> {
+    let content = std::fs::read_to_string(workspace_root.join(ENCRYPTION_FILE))
+        .map_err(|e| format!("Failed to read encryption settings: {}", e))?;
+    let config: EncryptionConfig = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse encryption settings: {}", e))?;
+
+    let salt = STANDARD.decode(&config.salt)
+        .map_err(|e| format!("Failed to parse encryption settings: {}", e))?;
+    let verifier = STANDARD.decode(&config.verifier)
+        .map_err(|e| format!("Failed to parse encryption settings: {}", e))?;
+
+    let key = derive_key(passphrase, &salt, config.iterations);
+    match decrypt(&key, &verifier) {
+        Ok(value) if value == VERIFIER => Ok(key),
+        _ => Err("Wrong passphrase".to_string()),
+    }
+}
+
+pub fn remove_config(workspace_root: &Path) -> Result<(), String> {
+    std::fs::remove_file(workspace_root.join(ENCRYPTION_FILE))
+        .map_err(|e| format!("Failed to remove encryption settings: {}", e))
+}
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+// The magic, a random nonce, then the ciphertext with its tag
+pub fn encrypt(key: &DataKey, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new().fill(&mut nonce)
+        .map_err(|_| "Failed to generate nonce".to_string())?;
+
+    let mut in_out = plaintext.to_vec();
+    key.aead_key()
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(MAGIC), &mut in_out)
+        .map_err(|_| "Failed to encrypt data".to_string())?;
+
+    let mut data = Vec::with_capacity(MAGIC.len() + NONCE_LEN + in_out.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&in_out);
+    Ok(data)
+}
+
+pub fn decrypt(key: &DataKey, data: &[u8]) -> Result<Vec<u8>, String> {
+    let body = data.strip_prefix(MAGIC)
+        .filter(|body| body.len() >= NONCE_LEN)
+        .ok_or("Not an encrypted file")?;
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce)
+        .map_err(|_| "Not an encrypted file".to_string())?;
+
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = key.aead_key()
+        .open_in_place(nonce, Aad::from(MAGIC), &mut in_out)
+        .map_err(|_| "Failed to decrypt data, the file is corrupted or was encrypted with another key".to_string())?;
+    Ok(plaintext.to_vec())
+}
+
+// Reads a file written by write_data. Plain files are read as they are, so a workspace keeps
+// working while encryption is being turned on or off
+pub fn read_data(path: &Path, key: Option<&DataKey>) -> Result<String, String> {
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    if !is_encrypted(&data) {
+        return String::from_utf8(data).map_err(|e| e.to_string());
+    }
+
+    let key = key.ok_or(WORKSPACE_LOCKED)?;
+    String::from_utf8(decrypt(key, &data)?).map_err(|e| e.to_string())
+}
+
+// Encrypted when there is a key, plain otherwise
+pub fn seal(contents: &str, key: Option<&DataKey>) -> Result<Vec<u8>, String> {
+    match key {
+        Some(key) => encrypt(key, contents.as_bytes()),
+        None => Ok(contents.as_bytes().to_vec()),
+    }
+}
+
+pub fn write_data(path: &Path, contents: &str, key: Option<&DataKey>) -> Result<(), String> {
+    write_atomic(path, &seal(contents, key)?).map_err(|e| e.to_string())
+}
+
+impl DataKey {
+    fn aead_key(&self) -> LessSafeKey {
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &self.0).expect("AES-256 key is 32 bytes"))
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> DataKey {
+    let iterations = NonZeroU32::new(iterations).unwrap_or(NonZeroU32::MIN);
+    let mut key = [0u8; 32];
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, passphrase.as_bytes(), &mut key);
+    DataKey(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_and_unlock() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        assert!(!is_enabled(temp_dir.path()));
+
+        let key = create_key(temp_dir.path(), "correct horse").unwrap();
+        assert!(is_enabled(temp_dir.path()));
+        assert!(unlock(temp_dir.path(), "battery staple").is_err());
+        let unlocked = unlock(temp_dir.path(), "correct horse").unwrap();
+
+        let file = temp_dir.path().join("project.json");
+        write_data(&file, "{\"name\":\"Board meeting\"}", Some(&key)).unwrap();
+        let raw = std::fs::read(&file).unwrap();
+        assert!(is_encrypted(&raw));
+        assert!(!String::from_utf8_lossy(&raw).contains("Board meeting"));
+
+        assert_eq!(read_data(&file, Some(&unlocked)).unwrap(), "{\"name\":\"Board meeting\"}");
+        assert_eq!(read_data(&file, None).unwrap_err(), WORKSPACE_LOCKED);
+
+        write_data(&file, "{}", None).unwrap();
+        assert_eq!(read_data(&file, Some(&key)).unwrap(), "{}");
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Serialize, Deserialize};
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+const LOCK_FILE: &str = "project.lock";
+// A save takes milliseconds, so a lock this old belongs to a process that hung or crashed
+const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LockOwner {
+    pid: u32,
+    acquired_at: String,
+}
+
+// Advisory only: other instances of the app respect it, nothing else does. Held while a
+// project file is written and removed when dropped
+pub struct ProjectLock {
+    path: PathBuf,
+}
+
+impl ProjectLock {
+    pub fn acquire(project_dir: &Path) -> Result<Self, String> {
+        let path = project_dir.join(LOCK_FILE);
+        let started = Instant::now();
+
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let owner = LockOwner {
+                        pid: std::process::id(),
+                        acquired_at: chrono::Utc::now().to_rfc3339(),
+                    };
+                    // The lock is held either way; an unreadable lock file just has to age out
+                    if let Ok(content) = serde_json::to_string(&owner) {
+                        let _ = file.write_all(content.as_bytes());
+                    }
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    if let Some(stale) = stale_owner(&path) {
+                        remove_stale(&path, &stale);
+                        continue;
+                    }
+                    if started.elapsed() > LOCK_TIMEOUT {
+                        return Err("Project is locked by another instance of the app".to_string());
+                    }
+                    wait(RETRY_INTERVAL);
+                }
+                Err(e) => return Err(format!("Failed to lock project: {}", e)),
+            }
+        }
+    }
+}
+
+impl Drop for ProjectLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// Saves run on tokio workers; a worker waiting for the lock hands its other tasks off first
+fn wait(duration: Duration) {
+    let on_worker = tokio::runtime::Handle::try_current()
+        .is_ok_and(|handle| handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread);
+    if on_worker {
+        tokio::task::block_in_place(|| std::thread::sleep(duration));
+    } else {
+        std::thread::sleep(duration);
+    }
+}
+
+// The stale lock's contents, which tell it apart from a fresh lock taken in its place
+fn stale_owner(lock_path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(lock_path).ok()?;
+    let age = std::fs::metadata(lock_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok());
+    if age.is_some_and(|age| age > STALE_LOCK_AGE) {
+        return Some(content);
+    }
+
+    match serde_json::from_str::<LockOwner>(&content) {
+        Ok(owner) if !is_running(owner.pid) => Some(content),
+        // Either its owner is still writing it or it will age out
+        _ => None,
+    }
+}
+
+// Another instance may have judged the same lock stale, removed it and taken a fresh one by
+// now. The lock is moved aside first and only removed when it's still the stale one
+fn remove_stale(lock_path: &Path, stale: &str) {
+    let aside = lock_path.with_extension(format!("lock.{}", uuid::Uuid::new_v4().simple()));
+    if std::fs::rename(lock_path, &aside).is_err() {
+        return;
+    }
+    if std::fs::read_to_string(&aside).is_ok_and(|content| content == stale) {
+        tracing::info!("Removed stale project lock {}", lock_path.display());
+    } else {
+        // Someone's live lock; the link fails only when yet another lock was taken meanwhile
+        let _ = std::fs::hard_link(&aside, lock_path);
+    }
+    let _ = std::fs::remove_file(&aside);
+}
+
+fn is_running(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), false);
+    system.process(pid).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atomic_file::write_atomic;
+
+    #[test]
+    fn test_lock_and_stale_recovery() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let lock_path = temp_dir.path().join(LOCK_FILE);
+
+        let lock = ProjectLock::acquire(temp_dir.path()).unwrap();
+        assert!(lock_path.exists());
+        drop(lock);
+        assert!(!lock_path.exists());
+
+        // Left behind by a process that no longer exists
+        let owner = LockOwner { pid: u32::MAX - 1, acquired_at: chrono::Utc::now().to_rfc3339() };
+        std::fs::write(&lock_path, serde_json::to_string(&owner).unwrap()).unwrap();
+        let _lock = ProjectLock::acquire(temp_dir.path()).unwrap();
+
+        // Another instance took a fresh lock after this one judged the old one stale
+        let fresh = std::fs::read_to_string(&lock_path).unwrap();
+        remove_stale(&lock_path, &serde_json::to_string(&owner).unwrap());
+        assert_eq!(std::fs::read_to_string(&lock_path).unwrap(), fresh);
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+        let file = temp_dir.path().join("project.json");
+        write_atomic(&file, b"{}").unwrap();
+        write_atomic(&file, b"[]").unwrap();
+        assert_eq!(std::fs::read(&file).unwrap(), b"[]");
+        assert!(!temp_dir.path().join("project.json.tmp").exists());
+    }
+}
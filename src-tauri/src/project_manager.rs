@@ -1,7 +1,20 @@
 use crate::{VideoNugget, VideoInfo};
-use crate::ai_analyzer::ContentAnalysis;
-use crate::batch_processor::BatchJob;
+use crate::ai_analyzer::{ContentAnalysis, PromptTemplate, SeoMetadata};
+use crate::backup_retention::{backups_to_prune, BackupInfo, BackupRetention};
+use crate::batch_processor::BatchResult;
+use crate::nugget_import;
+use crate::processed_index::video_key;
+use crate::project_archive::{self, walk_files};
+use crate::project_crypto::{self, read_data, seal, DataKey, EncryptionStatus};
+use crate::project_collections::{resolve_items, search_nuggets, validate_items, CollectionNugget, NuggetCollection, NuggetQuery, NuggetRef, SmartCollection};
+use crate::atomic_file::write_atomic;
+use crate::workflow::{StepCondition, WorkflowAction};
+use crate::project_lock::ProjectLock;
+use crate::project_storage::storage_used_mb;
+use crate::trash::{Trash, TrashEntry, TrashedItem};
+use crate::project_versions::{diff_projects, VersionDiff, VersionHistory, VersionInfo};
 use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
@@ -19,6 +32,13 @@ pub struct Project {
     pub collaborators: Vec<Collaborator>,
     pub settings: ProjectSettings,
     pub metadata: ProjectMetadata,
+    // Events about the project as a whole; each video keeps its own in processing_history
+    #[serde(default)]
+    pub activity: Vec<ProcessingEvent>,
+    #[serde(default)]
+    pub collections: Vec<NuggetCollection>,
+    #[serde(default)]
+    pub smart_collections: Vec<SmartCollection>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -27,6 +47,11 @@ pub struct VideoProject {
     pub video_info: VideoInfo,
     pub nuggets: Vec<VideoNugget>,
     pub analysis: Option<ContentAnalysis>,
+    #[serde(default)]
+    pub seo: Option<SeoMetadata>,
+    // Clips, thumbnails and exports a batch job wrote for this video
+    #[serde(default)]
+    pub output_files: Vec<String>,
     pub processing_history: Vec<ProcessingEvent>,
     pub custom_tags: Vec<String>,
     pub notes: String,
@@ -45,7 +70,20 @@ pub struct ProcessingEvent {
     pub parameters: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+impl ProcessingEvent {
+    pub fn new(event_type: EventType, details: String, parameters: HashMap<String, serde_json::Value>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            event_type,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            details,
+            user_id: None,
+            parameters,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum EventType {
     VideoAdded,
     NuggetsGenerated,
@@ -55,9 +93,10 @@ pub enum EventType {
     NotesUpdated,
     ConfigurationChanged,
     BatchProcessed,
+    NuggetsEdited,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum VideoStatus {
     Pending,
     Processing,
@@ -107,7 +146,19 @@ pub struct ProjectSettings {
     pub social_media_formats: bool,
     pub backup_enabled: bool,
     pub backup_interval_hours: u32,
+    #[serde(default)]
+    pub backup_retention: BackupRetention,
     pub quality_presets: HashMap<String, QualityPreset>,
+    #[serde(default = "default_prompt_templates")]
+    pub prompt_templates: Vec<PromptTemplate>,
+    #[serde(default)]
+    pub active_prompt_template: Option<String>,
+    #[serde(default)]
+    pub output_language: Option<String>,
+}
+
+fn default_prompt_templates() -> Vec<PromptTemplate> {
+    vec![PromptTemplate::default_analysis()]
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -129,7 +180,7 @@ pub struct ProjectMetadata {
     pub version: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProjectTemplate {
     pub id: String,
     pub name: String,
@@ -137,20 +188,156 @@ pub struct ProjectTemplate {
     pub settings: ProjectSettings,
     pub suggested_tags: Vec<String>,
     pub workflow: Vec<WorkflowStep>,
+    // Built-in templates come from create_default_templates and can't be edited or deleted
+    #[serde(default)]
+    pub built_in: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WorkflowStep {
     pub name: String,
     pub description: String,
     pub automated: bool,
     pub parameters: HashMap<String, serde_json::Value>,
+    // What run_workflow does for the step; steps without one are left to the user
+    #[serde(default)]
+    pub action: Option<WorkflowAction>,
+    #[serde(default)]
+    pub condition: Option<StepCondition>,
+    // Record a failure and go on with the next step instead of stopping the run
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+// What to do with an imported video that the target project already has
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum MergeStrategy {
+    Skip,
+    // The imported copy takes over the existing video's ID
+    Replace,
+    KeepBoth,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub skipped: usize,
+    pub replaced: usize,
+    pub kept_both: usize,
+}
+
+// Another project's copy of a video that is about to be added
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DuplicateVideo {
+    pub project_id: String,
+    pub project_name: String,
+    pub video_id: String,
+    pub title: String,
+    pub nugget_count: usize,
+    pub added_at: String,
+}
+
+// Just enough of project.json to look for duplicates in a project that isn't loaded
+#[derive(Deserialize)]
+struct VideoListing {
+    id: String,
+    name: String,
+    videos: Vec<ListedVideo>,
+}
+
+#[derive(Deserialize)]
+struct ListedVideo {
+    id: String,
+    video_info: VideoInfo,
+    nuggets: Vec<serde::de::IgnoredAny>,
+    created_at: String,
+}
+
+// Fields left as None keep their current value
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct NuggetUpdate {
+    pub title: Option<String>,
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+    pub tags: Option<Vec<String>>,
+    pub notes: Option<String>,
+}
+
+// Destructive changes that undo_last_change can reverse; each holds what it needs to
+// be applied in either direction
+#[derive(Debug, Clone)]
+enum ProjectChange {
+    // The video goes to the trash, with its files when delete_files is set
+    VideoDeleted { project_id: String, index: usize, video: Box<VideoProject>, delete_files: bool },
+    NuggetsDeleted { project_id: String, video_id: String, removed: Vec<(usize, VideoNugget)> },
+    SettingsChanged { project_id: String, before: Box<ProjectSettings>, after: Box<ProjectSettings> },
+}
+
+impl ProjectChange {
+    fn project_id(&self) -> &str {
+        match self {
+            ProjectChange::VideoDeleted { project_id, .. }
+            | ProjectChange::NuggetsDeleted { project_id, .. }
+            | ProjectChange::SettingsChanged { project_id, .. } => project_id,
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            ProjectChange::VideoDeleted { video, .. } => format!("Delete video '{}'", video.video_info.title),
+            ProjectChange::NuggetsDeleted { removed, .. } => format!("Delete {} nuggets", removed.len()),
+            ProjectChange::SettingsChanged { .. } => "Change project settings".to_string(),
+        }
+    }
+}
+
+const MAX_UNDO_STEPS: usize = 50;
+// Projects moved out of the workspace with move_project, by ID
+const PROJECT_LOCATIONS_FILE: &str = "project_locations.json";
+
+// project.json without the videos, which is where nearly all of a big project's size is
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectSummary {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub workspace_path: PathBuf,
+    pub tags: Vec<String>,
+    pub collaborators: Vec<Collaborator>,
+    pub settings: ProjectSettings,
+    pub metadata: ProjectMetadata,
+}
+
+impl From<&Project> for ProjectSummary {
+    fn from(project: &Project) -> Self {
+        Self {
+            id: project.id.clone(),
+            name: project.name.clone(),
+            description: project.description.clone(),
+            created_at: project.created_at.clone(),
+            updated_at: project.updated_at.clone(),
+            workspace_path: project.workspace_path.clone(),
+            tags: project.tags.clone(),
+            collaborators: project.collaborators.clone(),
+            settings: project.settings.clone(),
+            metadata: project.metadata.clone(),
+        }
+    }
 }
 
 pub struct ProjectManager {
     projects: HashMap<String, Project>,
+    // Found by load_projects but not read in full yet; hydrate moves them into projects
+    unloaded: HashMap<String, ProjectSummary>,
     workspace_root: PathBuf,
     templates: Vec<ProjectTemplate>,
+    // In memory only, the version history covers anything older than this session
+    undo_stack: Vec<ProjectChange>,
+    redo_stack: Vec<ProjectChange>,
+    // Set once an encrypted workspace is unlocked, see enable_encryption
+    data_key: Option<DataKey>,
 }
 
 impl ProjectManager {
@@ -160,8 +347,12 @@ impl ProjectManager {
 
         Ok(Self {
             projects: HashMap::new(),
+            unloaded: HashMap::new(),
             workspace_root,
             templates: Self::create_default_templates(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            data_key: None,
         })
     }
 
@@ -176,7 +367,7 @@ impl ProjectManager {
             self.templates.iter()
                 .find(|t| t.id == template_id)
                 .map(|t| t.settings.clone())
-                .unwrap_or_else(|| Self::default_settings())
+                .unwrap_or_else(Self::default_settings)
         } else {
             Self::default_settings()
         };
@@ -216,15 +407,20 @@ impl ProjectManager {
                 last_activity: chrono::Utc::now().to_rfc3339(),
                 version: "1.0.0".to_string(),
             },
+            activity: Vec::new(),
+            collections: Vec::new(),
+            smart_collections: Vec::new(),
         };
 
         self.save_project(&project)?;
         self.projects.insert(project_id.clone(), project);
+        self.record_version(&project_id, "Project created");
         
         Ok(project_id)
     }
 
     pub fn add_video_to_project(&mut self, project_id: &str, video_info: VideoInfo, nuggets: Vec<VideoNugget>, analysis: Option<ContentAnalysis>) -> Result<String, String> {
+        self.hydrate(project_id)?;
         let project = self.projects.get_mut(project_id)
             .ok_or("Project not found")?;
 
@@ -234,6 +430,8 @@ impl ProjectManager {
             video_info: video_info.clone(),
             nuggets: nuggets.clone(),
             analysis,
+            seo: None,
+            output_files: Vec::new(),
             processing_history: vec![ProcessingEvent {
                 id: Uuid::new_v4().to_string(),
                 event_type: EventType::VideoAdded,
@@ -251,50 +449,374 @@ impl ProjectManager {
 
         project.videos.push(video_project);
         project.updated_at = chrono::Utc::now().to_rfc3339();
-        
-        // Update metadata
-        project.metadata.total_videos = project.videos.len();
-        project.metadata.total_nuggets = project.videos.iter().map(|v| v.nuggets.len()).sum();
-        project.metadata.total_duration_seconds = project.videos.iter().map(|v| v.video_info.duration).sum();
-        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+        refresh_metadata(project);
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        self.record_version(project_id, &format!("Added video '{}'", video_info.title));
+        Ok(video_id)
+    }
+
+    // Batch jobs with a project_id call this for every video that finished successfully
+    pub fn add_batch_result(&mut self, project_id: &str, batch_job_id: &str, result: &BatchResult) -> Result<String, String> {
+        let video_info = result.video_info.clone()
+            .ok_or("Batch result has no video info")?;
+        let video_id = self.add_video_to_project(project_id, video_info, result.nuggets.clone(), result.analysis.clone())?;
+        self.add_video_events(project_id, &video_id, result.events.clone())?;
+
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+        let video = project.videos.iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or("Video not found")?;
+
+        let mut parameters = HashMap::new();
+        parameters.insert("batch_job_id".to_string(), serde_json::json!(batch_job_id));
+        parameters.insert("url".to_string(), serde_json::json!(result.url));
+        parameters.insert("processing_time_seconds".to_string(), serde_json::json!(result.processing_time_seconds));
+
+        video.output_files = result.output_files.clone();
+        video.processing_history.push(ProcessingEvent::new(
+            EventType::BatchProcessed,
+            format!("Processed by batch job {}", batch_job_id),
+            parameters,
+        ));
+
+        refresh_metadata(project);
 
+        let project = self.projects.get(project_id)
+            .ok_or("Project not found")?;
         self.save_project(project)?;
         Ok(video_id)
     }
 
-    pub fn get_project(&self, project_id: &str) -> Option<&Project> {
+    pub fn workspace_root(&self) -> &Path {
+        &self.workspace_root
+    }
+
+    pub fn get_project(&mut self, project_id: &str) -> Result<Option<&Project>, String> {
+        self.hydrate(project_id)?;
+        Ok(self.projects.get(project_id))
+    }
+
+    pub fn get_project_mut(&mut self, project_id: &str) -> Result<Option<&mut Project>, String> {
+        self.hydrate(project_id)?;
+        Ok(self.projects.get_mut(project_id))
+    }
+
+    // Settings are part of the summary, so this never has to read a project's videos
+    pub fn project_settings(&self, project_id: &str) -> Option<&ProjectSettings> {
         self.projects.get(project_id)
+            .map(|project| &project.settings)
+            .or_else(|| self.unloaded.get(project_id).map(|summary| &summary.settings))
     }
 
-    pub fn get_project_mut(&mut self, project_id: &str) -> Option<&mut Project> {
-        self.projects.get_mut(project_id)
+    pub fn list_projects(&self) -> Vec<ProjectSummary> {
+        self.projects.values()
+            .map(ProjectSummary::from)
+            .chain(self.unloaded.values().cloned())
+            .collect()
     }
 
-    pub fn list_projects(&self) -> Vec<&Project> {
-        self.projects.values().collect()
+    // Reads the full project.json of a project load_projects only summarized. Unknown IDs are
+    // left for the caller to report as "Project not found"
+    fn hydrate(&mut self, project_id: &str) -> Result<(), String> {
+        let Some(summary) = self.unloaded.get(project_id) else {
+            return Ok(());
+        };
+
+        let content = read_data(&summary.workspace_path.join("project.json"), self.data_key.as_ref())
+            .map_err(|e| format!("Failed to read project file: {}", e))?;
+
+        let mut project: Project = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse project file: {}", e))?;
+        collect_project_events(&mut project);
+
+        self.unloaded.remove(project_id);
+        self.projects.insert(project.id.clone(), project);
+        Ok(())
     }
 
+    // Moves the project folder to the trash, see restore_from_trash
     pub fn delete_project(&mut self, project_id: &str) -> Result<(), String> {
-        let project = self.projects.remove(project_id)
+        self.hydrate(project_id)?;
+        let project = self.projects.get(project_id)
+            .ok_or("Project not found")?;
+        self.trash().trash_project(project)?;
+
+        self.projects.remove(project_id);
+        self.undo_stack.retain(|change| change.project_id() != project_id);
+        self.redo_stack.retain(|change| change.project_id() != project_id);
+
+        let mut locations = self.load_project_locations()?;
+        if locations.remove(project_id).is_some() {
+            self.save_project_locations(&locations)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn list_trash(&self) -> Result<Vec<TrashEntry>, String> {
+        self.trash().list()
+    }
+
+    // A trashed video can only go back into its project once the project itself is back
+    pub fn restore_from_trash(&mut self, entry_id: &str) -> Result<TrashEntry, String> {
+        let trash = self.trash();
+        let entry = trash.list()?.into_iter()
+            .find(|entry| entry.id == entry_id)
+            .ok_or("Trash entry not found")?;
+        if let TrashedItem::Video { project_id, .. } = &entry.item {
+            self.hydrate(project_id)?;
+            if !self.projects.contains_key(project_id) {
+                return Err("The video's project is not available, restore the project first".to_string());
+            }
+        }
+
+        let (entry, video) = trash.restore(entry_id)?;
+        match (&entry.item, video) {
+            (TrashedItem::Project { project_id, original_path, .. }, _) => {
+                let content = read_data(&original_path.join("project.json"), self.data_key.as_ref())
+                    .map_err(|e| format!("Failed to read project file: {}", e))?;
+                let mut project: Project = serde_json::from_str(&content)
+                    .map_err(|e| format!("Failed to parse project file: {}", e))?;
+                collect_project_events(&mut project);
+                // In case the workspace moved while the project was in the trash
+                let stored_path = project.workspace_path.clone();
+                rebase_paths(&mut project, &stored_path, original_path);
+
+                if original_path.parent() != Some(self.workspace_root.as_path()) {
+                    let mut locations = self.load_project_locations()?;
+                    locations.insert(project_id.clone(), original_path.clone());
+                    self.save_project_locations(&locations)?;
+                }
+                self.save_project(&project)?;
+                self.projects.insert(project_id.clone(), project);
+            }
+            (TrashedItem::Video { project_id, index, title, .. }, Some(video)) => {
+                let project = self.projects.get_mut(project_id)
+                    .ok_or("Project not found")?;
+                if !project.videos.iter().any(|v| v.id == video.id) {
+                    let index = (*index).min(project.videos.len());
+                    project.videos.insert(index, video);
+                }
+                project.updated_at = chrono::Utc::now().to_rfc3339();
+                refresh_metadata(project);
+
+                let project = &self.projects[project_id];
+                self.save_project(project)?;
+                self.record_version(project_id, &format!("Restored video '{}' from the trash", title));
+            }
+            _ => {}
+        }
+
+        Ok(entry)
+    }
+
+    // Deletes one entry for good, or the whole trash when entry_id is None; returns how many went
+    pub fn purge_trash(&self, entry_id: Option<&str>) -> Result<usize, String> {
+        let trash = self.trash();
+        match entry_id {
+            Some(entry_id) => trash.purge(entry_id).map(|_| 1),
+            None => {
+                let entries = trash.list()?;
+                for entry in &entries {
+                    trash.purge(&entry.id)?;
+                }
+                Ok(entries.len())
+            }
+        }
+    }
+
+    // Moves the project folder to <destination>/<project id>; moving it back into the workspace
+    // root makes it an ordinary project again
+    pub fn move_project(&mut self, project_id: &str, destination: &Path) -> Result<PathBuf, String> {
+        self.hydrate(project_id)?;
+        let mut locations = self.load_project_locations()?;
+        let project = self.projects.get_mut(project_id)
             .ok_or("Project not found")?;
 
-        // Remove project directory
-        if project.workspace_path.exists() {
-            std::fs::remove_dir_all(&project.workspace_path)
-                .map_err(|e| format!("Failed to remove project directory: {}", e))?;
+        let old_dir = project.workspace_path.clone();
+        let new_dir = destination.join(&project.id);
+        if new_dir == old_dir {
+            return Ok(new_dir);
+        }
+        if new_dir.starts_with(&old_dir) {
+            return Err("Cannot move a project into its own folder".to_string());
+        }
+
+        move_dir(&old_dir, &new_dir)?;
+        rebase_paths(project, &old_dir, &new_dir);
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        // Undo entries still point at the old folder
+        self.undo_stack.retain(|change| change.project_id() != project_id);
+        self.redo_stack.retain(|change| change.project_id() != project_id);
+
+        if destination == self.workspace_root {
+            locations.remove(project_id);
+        } else {
+            locations.insert(project_id.to_string(), new_dir.clone());
+        }
+        self.save_project_locations(&locations)?;
+
+        Ok(new_dir)
+    }
+
+    // Moves everything under the workspace root, including settings and batch jobs. Projects
+    // moved elsewhere with move_project stay put, but their paths into the workspace are updated
+    pub fn move_workspace(&mut self, new_root: &Path) -> Result<(), String> {
+        if new_root == self.workspace_root {
+            return Ok(());
+        }
+        if new_root.starts_with(&self.workspace_root) {
+            return Err("The new workspace cannot be inside the current one".to_string());
+        }
+
+        let mut locations = self.load_project_locations()?;
+        move_dir(&self.workspace_root, new_root)?;
+        let old_root = std::mem::replace(&mut self.workspace_root, new_root.to_path_buf());
+
+        // A project moved to a subfolder of the workspace has moved along with it
+        for location in locations.values_mut() {
+            if let Some(path) = rebase_path(location, &old_root, new_root) {
+                *location = path;
+            }
+        }
+        self.save_project_locations(&locations)?;
+
+        for project in self.projects.values_mut() {
+            rebase_paths(project, &old_root, new_root);
         }
+        for project in self.projects.values() {
+            self.save_project(project)?;
+        }
+
+        // Rewritten on disk without keeping them in memory, like load_projects
+        let unloaded_ids: Vec<String> = self.unloaded.keys().cloned().collect();
+        for project_id in unloaded_ids {
+            let old_dir = &self.unloaded[&project_id].workspace_path;
+            let project_dir = rebase_path(old_dir, &old_root, new_root).unwrap_or(old_dir.clone());
+            let content = read_data(&project_dir.join("project.json"), self.data_key.as_ref())
+                .map_err(|e| format!("Failed to read project file: {}", e))?;
+            let mut project: Project = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse project file: {}", e))?;
+
+            project.workspace_path = project_dir;
+            rebase_paths(&mut project, &old_root, new_root);
+            self.save_project(&project)?;
+            self.unloaded.insert(project_id, ProjectSummary::from(&project));
+        }
+
+        self.trash().rebase(&old_root, new_root)?;
 
+        self.undo_stack.clear();
+        self.redo_stack.clear();
         Ok(())
     }
 
+    // A copy with new IDs throughout, for reusing a project's setup and structure. With
+    // include_media the videos' files are hard-linked where the filesystem allows and copied
+    // otherwise; without it the copy's videos have no output files
+    pub fn clone_project(&mut self, project_id: &str, new_name: String, include_media: bool) -> Result<String, String> {
+        self.hydrate(project_id)?;
+        let source = self.projects.get(project_id)
+            .ok_or("Project not found")?;
+        let source_name = source.name.clone();
+        let source_dir = source.workspace_path.clone();
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut project = source.clone();
+        project.id = Uuid::new_v4().to_string();
+        project.name = new_name;
+        project.workspace_path = self.workspace_root.join(&project.id);
+        project.created_at = now.clone();
+        project.updated_at = now;
+        project.activity.clear();
+
+        std::fs::create_dir_all(&project.workspace_path)
+            .map_err(|e| format!("Failed to create project directory: {}", e))?;
+
+        let mut nugget_ids: HashMap<(String, String), NuggetRef> = HashMap::new();
+        for video in &mut project.videos {
+            let old_video_id = std::mem::replace(&mut video.id, Uuid::new_v4().to_string());
+            for nugget in &mut video.nuggets {
+                let old_nugget_id = std::mem::replace(&mut nugget.id, Uuid::new_v4().to_string());
+                nugget_ids.insert((old_video_id.clone(), old_nugget_id), NuggetRef {
+                    video_id: video.id.clone(),
+                    nugget_id: nugget.id.clone(),
+                });
+            }
+
+            if !include_media {
+                video.output_files.clear();
+                continue;
+            }
+            let mut output_files = Vec::new();
+            for file in video.output_files.iter().filter(|file| Path::new(file).is_file()) {
+                match clone_media_file(Path::new(file), &source_dir, &project.workspace_path, &video.id) {
+                    Ok(cloned) => output_files.push(cloned),
+                    Err(e) => {
+                        let _ = std::fs::remove_dir_all(&project.workspace_path);
+                        return Err(e);
+                    }
+                }
+            }
+            video.output_files = output_files;
+        }
+
+        for collection in &mut project.collections {
+            collection.id = Uuid::new_v4().to_string();
+            collection.items = collection.items.iter()
+                .filter_map(|item| nugget_ids.get(&(item.video_id.clone(), item.nugget_id.clone())).cloned())
+                .collect();
+        }
+        for collection in &mut project.smart_collections {
+            collection.id = Uuid::new_v4().to_string();
+        }
+        refresh_metadata(&mut project);
+
+        if let Err(e) = self.save_project(&project) {
+            let _ = std::fs::remove_dir_all(&project.workspace_path);
+            return Err(e);
+        }
+        let project_id = project.id.clone();
+        self.projects.insert(project_id.clone(), project);
+        self.record_version(&project_id, &format!("Cloned from '{}'", source_name));
+        Ok(project_id)
+    }
+
+    fn load_project_locations(&self) -> Result<HashMap<String, PathBuf>, String> {
+        let locations_file = self.workspace_root.join(PROJECT_LOCATIONS_FILE);
+        if !locations_file.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let content = std::fs::read_to_string(&locations_file)
+            .map_err(|e| format!("Failed to read project locations: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse project locations: {}", e))
+    }
+
+    fn save_project_locations(&self, locations: &HashMap<String, PathBuf>) -> Result<(), String> {
+        let json_data = serde_json::to_string_pretty(locations)
+            .map_err(|e| format!("Failed to serialize project locations: {}", e))?;
+        write_atomic(&self.workspace_root.join(PROJECT_LOCATIONS_FILE), json_data.as_bytes())
+            .map_err(|e| format!("Failed to save project locations: {}", e))
+    }
+
     pub fn update_project_settings(&mut self, project_id: &str, settings: ProjectSettings) -> Result<(), String> {
+        self.hydrate(project_id)?;
         let project = self.projects.get_mut(project_id)
             .ok_or("Project not found")?;
 
-        project.settings = settings;
+        let before = std::mem::replace(&mut project.settings, settings.clone());
         project.updated_at = chrono::Utc::now().to_rfc3339();
         project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
 
+        // Saves the project as well
         self.add_processing_event(
             project_id,
             EventType::ConfigurationChanged,
@@ -302,156 +824,1172 @@ impl ProjectManager {
             HashMap::new(),
         )?;
 
-        self.save_project(project)?;
+        self.push_change(ProjectChange::SettingsChanged {
+            project_id: project_id.to_string(),
+            before: Box::new(before),
+            after: Box::new(settings),
+        });
+        self.record_version(project_id, "Project settings updated");
         Ok(())
     }
 
-    pub fn add_collaborator(&mut self, project_id: &str, collaborator: Collaborator) -> Result<(), String> {
+    // Undo brings the video back, but not the files removed with delete_files
+    pub fn delete_video(&mut self, project_id: &str, video_id: &str, delete_files: bool) -> Result<(), String> {
+        self.hydrate(project_id)?;
         let project = self.projects.get_mut(project_id)
             .ok_or("Project not found")?;
 
-        // Check if collaborator already exists
-        if project.collaborators.iter().any(|c| c.email == collaborator.email) {
-            return Err("Collaborator already exists in this project".to_string());
-        }
+        let index = project.videos.iter()
+            .position(|v| v.id == video_id)
+            .ok_or("Video not found in project")?;
 
-        project.collaborators.push(collaborator);
-        project.updated_at = chrono::Utc::now().to_rfc3339();
-        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+        let change = ProjectChange::VideoDeleted {
+            project_id: project_id.to_string(),
+            index,
+            video: Box::new(project.videos[index].clone()),
+            delete_files,
+        };
 
-        self.save_project(project)?;
+        self.apply_change(&change, false)?;
+        self.push_change(change);
         Ok(())
     }
 
-    pub fn remove_collaborator(&mut self, project_id: &str, collaborator_id: &str) -> Result<(), String> {
-        let project = self.projects.get_mut(project_id)
+    // Returns how many of nugget_ids were found and deleted
+    pub fn delete_nuggets(&mut self, project_id: &str, video_id: &str, nugget_ids: &[String]) -> Result<usize, String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get(project_id)
             .ok_or("Project not found")?;
 
-        let initial_len = project.collaborators.len();
-        project.collaborators.retain(|c| c.id != collaborator_id);
+        let video = project.videos.iter()
+            .find(|v| v.id == video_id)
+            .ok_or("Video not found in project")?;
+        let removed: Vec<(usize, VideoNugget)> = video.nuggets.iter()
+            .enumerate()
+            .filter(|(_, n)| nugget_ids.contains(&n.id))
+            .map(|(index, n)| (index, n.clone()))
+            .collect();
+        if removed.is_empty() {
+            return Ok(0);
+        }
 
-        if project.collaborators.len() == initial_len {
-            return Err("Collaborator not found".to_string());
+        let count = removed.len();
+        let change = ProjectChange::NuggetsDeleted {
+            project_id: project_id.to_string(),
+            video_id: video_id.to_string(),
+            removed,
+        };
+
+        self.apply_change(&change, false)?;
+        self.push_change(change);
+        Ok(count)
+    }
+
+    // Matches by YouTube video ID when the URL has one, so youtu.be and watch links are the same video.
+    // Projects that aren't loaded are scanned from disk without loading them.
+    pub fn find_duplicate_videos(&self, url: &str) -> Result<Vec<DuplicateVideo>, String> {
+        let key = video_key(url);
+        let mut duplicates = Vec::new();
+
+        for project in self.projects.values() {
+            duplicates.extend(project.videos.iter()
+                .filter(|v| video_key(&v.video_info.url) == key)
+                .map(|v| DuplicateVideo {
+                    project_id: project.id.clone(),
+                    project_name: project.name.clone(),
+                    video_id: v.id.clone(),
+                    title: v.video_info.title.clone(),
+                    nugget_count: v.nuggets.len(),
+                    added_at: v.created_at.clone(),
+                }));
         }
 
-        project.updated_at = chrono::Utc::now().to_rfc3339();
-        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+        for summary in self.unloaded.values() {
+            let content = read_data(&summary.workspace_path.join("project.json"), self.data_key.as_ref())
+                .map_err(|e| format!("Failed to read project file: {}", e))?;
+            let listing: VideoListing = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse project file: {}", e))?;
+
+            duplicates.extend(listing.videos.into_iter()
+                .filter(|v| video_key(&v.video_info.url) == key)
+                .map(|v| DuplicateVideo {
+                    project_id: listing.id.clone(),
+                    project_name: listing.name.clone(),
+                    video_id: v.id,
+                    title: v.video_info.title,
+                    nugget_count: v.nuggets.len(),
+                    added_at: v.created_at,
+                }));
+        }
 
-        self.save_project(project)?;
-        Ok(())
+        duplicates.sort_by(|a, b| a.project_name.cmp(&b.project_name).then(a.added_at.cmp(&b.added_at)));
+        Ok(duplicates)
     }
 
-    pub fn add_processing_event(&mut self, project_id: &str, event_type: EventType, details: String, parameters: HashMap<String, serde_json::Value>) -> Result<(), String> {
+    // Copies a processed video from another project instead of processing it again.
+    // Both copies point at the same rendered files.
+    pub fn link_existing_video(&mut self, project_id: &str, source_project_id: &str, video_id: &str) -> Result<String, String> {
+        self.hydrate(source_project_id)?;
+        self.hydrate(project_id)?;
+
+        let source = self.projects.get(source_project_id)
+            .ok_or("Source project not found")?;
+        let mut video = source.videos.iter()
+            .find(|v| v.id == video_id)
+            .ok_or("Video not found in project")?
+            .clone();
+        let source_name = source.name.clone();
+
         let project = self.projects.get_mut(project_id)
             .ok_or("Project not found")?;
+        let key = video_key(&video.video_info.url);
+        if project.videos.iter().any(|v| video_key(&v.video_info.url) == key) {
+            return Err("Video is already in this project".to_string());
+        }
 
-        let event = ProcessingEvent {
+        video.id = Uuid::new_v4().to_string();
+        video.created_at = chrono::Utc::now().to_rfc3339();
+        video.updated_at = chrono::Utc::now().to_rfc3339();
+        video.processing_history.push(ProcessingEvent {
             id: Uuid::new_v4().to_string(),
-            event_type,
+            event_type: EventType::VideoAdded,
             timestamp: chrono::Utc::now().to_rfc3339(),
-            details,
+            details: format!("Linked from project '{}'", source_name),
             user_id: None,
-            parameters,
-        };
+            parameters: HashMap::new(),
+        });
 
-        // Add event to all videos (global project events)
-        for video in &mut project.videos {
-            video.processing_history.push(event.clone());
-        }
+        let new_id = video.id.clone();
+        let reason = format!("Linked video '{}'", video.video_info.title);
+        project.videos.push(video);
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+        refresh_metadata(project);
 
-        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+        let project = &self.projects[project_id];
         self.save_project(project)?;
-        Ok(())
+        self.record_version(project_id, &reason);
+        Ok(new_id)
     }
 
-    pub fn export_project(&self, project_id: &str, export_path: &str, include_files: bool) -> Result<(), String> {
-        let project = self.projects.get(project_id)
+    // Archived videos stay in the project but are out of the way; archived = false restores them
+    pub fn archive_video(&mut self, project_id: &str, video_id: &str, archived: bool, delete_files: bool) -> Result<(), String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
             .ok_or("Project not found")?;
+        let index = project.videos.iter()
+            .position(|v| v.id == video_id)
+            .ok_or("Video not found in project")?;
 
-        let export_data = if include_files {
-            // Create zip archive with all project files
-            self.create_project_archive(project, export_path)?
-        } else {
-            // Export just the project metadata as JSON
-            let json_data = serde_json::to_string_pretty(project)
-                .map_err(|e| format!("Failed to serialize project: {}", e))?;
-            
-            std::fs::write(export_path, json_data)
-                .map_err(|e| format!("Failed to write export file: {}", e))?;
-        };
+        if delete_files {
+            remove_output_files(&project.videos[index].output_files)?;
+            project.videos[index].output_files.clear();
+        }
 
-        Ok(())
-    }
+        let video = &mut project.videos[index];
+        video.status = if archived { VideoStatus::Archived } else { VideoStatus::Completed };
+        video.updated_at = chrono::Utc::now().to_rfc3339();
+        let reason = format!("{} video '{}'", if archived { "Archived" } else { "Restored" }, video.video_info.title);
 
-    fn create_project_archive(&self, project: &Project, archive_path: &str) -> Result<(), String> {
-        // This would create a zip archive containing all project files
-        // For now, just export the JSON
-        let json_data = serde_json::to_string_pretty(project)
-            .map_err(|e| format!("Failed to serialize project: {}", e))?;
-        
-        std::fs::write(archive_path, json_data)
-            .map_err(|e| format!("Failed to write archive: {}", e))?;
-        
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+        refresh_metadata(project);
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        self.record_version(project_id, &reason);
         Ok(())
     }
 
-    pub fn import_project(&mut self, import_path: &str) -> Result<String, String> {
-        let content = std::fs::read_to_string(import_path)
-            .map_err(|e| format!("Failed to read import file: {}", e))?;
-
-        let mut project: Project = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse project data: {}", e))?;
+    pub fn update_nugget(&mut self, project_id: &str, video_id: &str, nugget_id: &str, update: NuggetUpdate) -> Result<(), String> {
+        self.edit_nuggets(project_id, video_id, |nuggets, duration| {
+            let nugget = nuggets.iter_mut()
+                .find(|n| n.id == nugget_id)
+                .ok_or("Nugget not found")?;
 
-        // Generate new ID to avoid conflicts
-        let old_id = project.id.clone();
-        project.id = Uuid::new_v4().to_string();
-        
-        // Update workspace path
-        project.workspace_path = self.workspace_root.join(&project.id);
-        
-        // Create project directory
-        std::fs::create_dir_all(&project.workspace_path)
-            .map_err(|e| format!("Failed to create project directory: {}", e))?;
+            let start_time = update.start_time.unwrap_or(nugget.start_time);
+            let end_time = update.end_time.unwrap_or(nugget.end_time);
+            validate_nugget_times(start_time, end_time, duration)?;
 
-        self.save_project(&project)?;
-        self.projects.insert(project.id.clone(), project.clone());
+            nugget.start_time = start_time;
+            nugget.end_time = end_time;
+            if let Some(title) = update.title {
+                nugget.title = title;
+            }
+            if let Some(tags) = update.tags {
+                nugget.tags = tags;
+            }
+            if let Some(notes) = update.notes {
+                nugget.notes = (!notes.trim().is_empty()).then_some(notes);
+            }
+            Ok(((), format!("Edited nugget '{}'", nugget.title)))
+        })
+    }
 
-        Ok(project.id)
+    // After the nugget's clip went up to YouTube
+    pub fn set_nugget_youtube_video_id(&mut self, project_id: &str, video_id: &str, nugget_id: &str, youtube_video_id: &str) -> Result<(), String> {
+        self.edit_nuggets(project_id, video_id, |nuggets, _| {
+            let nugget = nuggets.iter_mut()
+                .find(|n| n.id == nugget_id)
+                .ok_or("Nugget not found")?;
+            nugget.youtube_video_id = Some(youtube_video_id.to_string());
+            Ok(((), format!("Uploaded nugget '{}' to YouTube as {}", nugget.title, youtube_video_id)))
+        })
     }
 
-    fn save_project(&self, project: &Project) -> Result<(), String> {
-        let project_file = project.workspace_path.join("project.json");
-        let json_data = serde_json::to_string_pretty(project)
-            .map_err(|e| format!("Failed to serialize project: {}", e))?;
+    // The first part keeps the nugget's ID; returns the ID of the new second part
+    pub fn split_nugget(&mut self, project_id: &str, video_id: &str, nugget_id: &str, at: f64) -> Result<String, String> {
+        self.edit_nuggets(project_id, video_id, |nuggets, _| {
+            let index = nuggets.iter()
+                .position(|n| n.id == nugget_id)
+                .ok_or("Nugget not found")?;
+            let first = &mut nuggets[index];
+            if at <= first.start_time || at >= first.end_time {
+                return Err("Split point must be inside the nugget".to_string());
+            }
 
-        std::fs::write(project_file, json_data)
-            .map_err(|e| format!("Failed to save project: {}", e))?;
+            let fraction = (at - first.start_time) / (first.end_time - first.start_time);
+            let (first_transcript, second_transcript) = match first.transcript.as_deref() {
+                Some(transcript) => {
+                    let (head, tail) = split_transcript(transcript, fraction);
+                    (Some(head), Some(tail))
+                }
+                None => (None, None),
+            };
 
-        Ok(())
+            let second = VideoNugget {
+                id: Uuid::new_v4().to_string(),
+                title: format!("{} (part 2)", first.title),
+                start_time: at,
+                end_time: first.end_time,
+                transcript: second_transcript,
+                tags: first.tags.clone(),
+                notes: None,
+                youtube_video_id: None,
+                created_at: chrono::Utc::now().to_rfc3339(),
+            };
+            first.end_time = at;
+            first.transcript = first_transcript;
+
+            let details = format!("Split nugget '{}' at {:.1}s", first.title, at);
+            let second_id = second.id.clone();
+            nuggets.insert(index + 1, second);
+            Ok((second_id, details))
+        })
+    }
+
+    // Merges second_id into first_id, which must come right before it in the video's list
+    pub fn merge_nuggets(&mut self, project_id: &str, video_id: &str, first_id: &str, second_id: &str) -> Result<(), String> {
+        self.edit_nuggets(project_id, video_id, |nuggets, _| {
+            let index = nuggets.iter()
+                .position(|n| n.id == first_id)
+                .ok_or("Nugget not found")?;
+            if nuggets.get(index + 1).map(|n| n.id.as_str()) != Some(second_id) {
+                return Err("Only adjacent nuggets can be merged".to_string());
+            }
+
+            let second = nuggets.remove(index + 1);
+            let first = &mut nuggets[index];
+            first.start_time = first.start_time.min(second.start_time);
+            first.end_time = first.end_time.max(second.end_time);
+            first.transcript = join_text(first.transcript.take(), second.transcript, " ");
+            first.notes = join_text(first.notes.take(), second.notes, "\n");
+            for tag in second.tags {
+                if !first.tags.contains(&tag) {
+                    first.tags.push(tag);
+                }
+            }
+            Ok(((), format!("Merged nugget '{}' into '{}'", second.title, first.title)))
+        })
+    }
+
+    // nugget_ids must list every nugget of the video exactly once, in the new order
+    pub fn reorder_nuggets(&mut self, project_id: &str, video_id: &str, nugget_ids: &[String]) -> Result<(), String> {
+        self.edit_nuggets(project_id, video_id, |nuggets, _| {
+            if nugget_ids.len() != nuggets.len() {
+                return Err("New order must include every nugget of the video".to_string());
+            }
+
+            let mut reordered = Vec::with_capacity(nuggets.len());
+            for id in nugget_ids {
+                let index = nuggets.iter()
+                    .position(|n| &n.id == id)
+                    .ok_or_else(|| format!("Nugget {} not found", id))?;
+                reordered.push(nuggets.remove(index));
+            }
+            *nuggets = reordered;
+            Ok(((), "Reordered nuggets".to_string()))
+        })
+    }
+
+    // content is an SRT/WebVTT file or a chapter list (see nugget_import). The nuggets are added
+    // after the existing ones, or replace them; returns how many were imported
+    pub fn import_nuggets(&mut self, project_id: &str, video_id: &str, content: &str, replace: bool) -> Result<usize, String> {
+        self.edit_nuggets(project_id, video_id, |nuggets, duration| {
+            let imported = nugget_import::parse_nuggets(content, duration)?;
+            let count = imported.len();
+            if replace {
+                *nuggets = imported;
+            } else {
+                nuggets.extend(imported);
+            }
+            Ok((count, format!("Imported {} nuggets", count)))
+        })
+    }
+
+    // Runs edit on the video's nuggets and, if it succeeds, records it in the video's history.
+    // edit gets the video duration and returns its result with a description for the history.
+    fn edit_nuggets<T>(
+        &mut self,
+        project_id: &str,
+        video_id: &str,
+        edit: impl FnOnce(&mut Vec<VideoNugget>, f64) -> Result<(T, String), String>,
+    ) -> Result<T, String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+        let video = project.videos.iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or("Video not found in project")?;
+
+        // Edit a copy so a failed edit leaves the nuggets untouched
+        let mut nuggets = video.nuggets.clone();
+        let (result, details) = edit(&mut nuggets, video.video_info.duration)?;
+        video.nuggets = nuggets;
+        video.processing_history.push(ProcessingEvent {
+            id: Uuid::new_v4().to_string(),
+            event_type: EventType::NuggetsEdited,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            details: details.clone(),
+            user_id: None,
+            parameters: HashMap::new(),
+        });
+        video.updated_at = chrono::Utc::now().to_rfc3339();
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+        refresh_metadata(project);
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        self.record_version(project_id, &details);
+        Ok(result)
+    }
+
+    pub fn create_collection(&mut self, project_id: &str, name: String, description: Option<String>, items: Vec<NuggetRef>) -> Result<String, String> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let collection = NuggetCollection {
+            id: Uuid::new_v4().to_string(),
+            name,
+            description,
+            items,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+
+        self.edit_collections(project_id, |collections, videos| {
+            validate_items(videos, &collection.items)?;
+            let details = format!("Created collection '{}'", collection.name);
+            let collection_id = collection.id.clone();
+            collections.push(collection);
+            Ok((collection_id, details))
+        })
+    }
+
+    // None leaves a field as it is; items replaces the whole list, which is also how it's reordered
+    pub fn update_collection(&mut self, project_id: &str, collection_id: &str, name: Option<String>, description: Option<String>, items: Option<Vec<NuggetRef>>) -> Result<(), String> {
+        self.edit_collections(project_id, |collections, videos| {
+            let collection = collections.iter_mut()
+                .find(|c| c.id == collection_id)
+                .ok_or("Collection not found")?;
+
+            if let Some(items) = items {
+                validate_items(videos, &items)?;
+                collection.items = items;
+            }
+            if let Some(name) = name {
+                collection.name = name;
+            }
+            if let Some(description) = description {
+                collection.description = (!description.trim().is_empty()).then_some(description);
+            }
+            collection.updated_at = chrono::Utc::now().to_rfc3339();
+            Ok(((), format!("Updated collection '{}'", collection.name)))
+        })
+    }
+
+    // Appends the nuggets that aren't in the collection yet
+    pub fn add_to_collection(&mut self, project_id: &str, collection_id: &str, items: Vec<NuggetRef>) -> Result<(), String> {
+        self.edit_collections(project_id, |collections, videos| {
+            let collection = collections.iter_mut()
+                .find(|c| c.id == collection_id)
+                .ok_or("Collection not found")?;
+
+            for item in items {
+                if !collection.items.contains(&item) {
+                    collection.items.push(item);
+                }
+            }
+            validate_items(videos, &collection.items)?;
+            collection.updated_at = chrono::Utc::now().to_rfc3339();
+            Ok(((), format!("Added nuggets to collection '{}'", collection.name)))
+        })
+    }
+
+    pub fn delete_collection(&mut self, project_id: &str, collection_id: &str) -> Result<(), String> {
+        self.edit_collections(project_id, |collections, _| {
+            let index = collections.iter()
+                .position(|c| c.id == collection_id)
+                .ok_or("Collection not found")?;
+            let collection = collections.remove(index);
+            Ok(((), format!("Deleted collection '{}'", collection.name)))
+        })
+    }
+
+    pub fn collection_nuggets(&mut self, project_id: &str, collection_id: &str) -> Result<Vec<CollectionNugget>, String> {
+        let project = self.get_project(project_id)?
+            .ok_or("Project not found")?;
+        let collection = project.collections.iter()
+            .find(|c| c.id == collection_id)
+            .ok_or("Collection not found")?;
+        Ok(resolve_items(&project.videos, &collection.items))
+    }
+
+    // Nuggets from any of the project's videos, in the order of the ids
+    pub fn find_nuggets(&mut self, project_id: &str, nugget_ids: &[String]) -> Result<Vec<VideoNugget>, String> {
+        let project = self.get_project(project_id)?
+            .ok_or("Project not found")?;
+        nugget_ids.iter()
+            .map(|nugget_id| project.videos.iter()
+                .flat_map(|video| video.nuggets.iter())
+                .find(|nugget| &nugget.id == nugget_id)
+                .cloned()
+                .ok_or(format!("Nugget not found: {}", nugget_id)))
+            .collect()
+    }
+
+    // Same shape as edit_nuggets; edit also gets the videos, to check the nugget references
+    fn edit_collections<T>(
+        &mut self,
+        project_id: &str,
+        edit: impl FnOnce(&mut Vec<NuggetCollection>, &[VideoProject]) -> Result<(T, String), String>,
+    ) -> Result<T, String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+
+        let mut collections = project.collections.clone();
+        let (result, details) = edit(&mut collections, &project.videos)?;
+        project.collections = collections;
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        self.record_version(project_id, &details);
+        Ok(result)
+    }
+
+    pub fn create_smart_collection(&mut self, project_id: &str, name: String, query: NuggetQuery) -> Result<String, String> {
+        query.validate()?;
+        let now = chrono::Utc::now().to_rfc3339();
+        let collection = SmartCollection {
+            id: Uuid::new_v4().to_string(),
+            name,
+            query,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+
+        self.edit_smart_collections(project_id, |collections| {
+            let details = format!("Created smart collection '{}'", collection.name);
+            let collection_id = collection.id.clone();
+            collections.push(collection);
+            Ok((collection_id, details))
+        })
+    }
+
+    pub fn update_smart_collection(&mut self, project_id: &str, collection_id: &str, name: Option<String>, query: Option<NuggetQuery>) -> Result<(), String> {
+        if let Some(query) = &query {
+            query.validate()?;
+        }
+
+        self.edit_smart_collections(project_id, |collections| {
+            let collection = collections.iter_mut()
+                .find(|c| c.id == collection_id)
+                .ok_or("Collection not found")?;
+
+            if let Some(name) = name {
+                collection.name = name;
+            }
+            if let Some(query) = query {
+                collection.query = query;
+            }
+            collection.updated_at = chrono::Utc::now().to_rfc3339();
+            Ok(((), format!("Updated smart collection '{}'", collection.name)))
+        })
+    }
+
+    pub fn delete_smart_collection(&mut self, project_id: &str, collection_id: &str) -> Result<(), String> {
+        self.edit_smart_collections(project_id, |collections| {
+            let index = collections.iter()
+                .position(|c| c.id == collection_id)
+                .ok_or("Collection not found")?;
+            let collection = collections.remove(index);
+            Ok(((), format!("Deleted smart collection '{}'", collection.name)))
+        })
+    }
+
+    pub fn smart_collection_nuggets(&mut self, project_id: &str, collection_id: &str) -> Result<Vec<CollectionNugget>, String> {
+        let project = self.get_project(project_id)?
+            .ok_or("Project not found")?;
+        let collection = project.smart_collections.iter()
+            .find(|c| c.id == collection_id)
+            .ok_or("Collection not found")?;
+        Ok(search_nuggets(&project.videos, &collection.query))
+    }
+
+    fn edit_smart_collections<T>(
+        &mut self,
+        project_id: &str,
+        edit: impl FnOnce(&mut Vec<SmartCollection>) -> Result<(T, String), String>,
+    ) -> Result<T, String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+
+        let mut collections = project.smart_collections.clone();
+        let (result, details) = edit(&mut collections)?;
+        project.smart_collections = collections;
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        self.record_version(project_id, &details);
+        Ok(result)
+    }
+
+    // Returns a description of the change that was undone, or None when there is nothing to undo
+    pub fn undo_last_change(&mut self) -> Result<Option<String>, String> {
+        let Some(change) = self.undo_stack.pop() else {
+            return Ok(None);
+        };
+
+        self.apply_change(&change, true)?;
+        let description = change.description();
+        self.redo_stack.push(change);
+        Ok(Some(description))
+    }
+
+    pub fn redo(&mut self) -> Result<Option<String>, String> {
+        let Some(change) = self.redo_stack.pop() else {
+            return Ok(None);
+        };
+
+        self.apply_change(&change, false)?;
+        let description = change.description();
+        self.undo_stack.push(change);
+        Ok(Some(description))
+    }
+
+    fn push_change(&mut self, change: ProjectChange) {
+        self.undo_stack.push(change);
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            self.undo_stack.remove(0);
+        }
+        // A new change makes whatever was undone before it impossible to redo
+        self.redo_stack.clear();
+    }
+
+    // Reverting restores what the change removed; the video or nuggets go back where they were
+    fn apply_change(&mut self, change: &ProjectChange, revert: bool) -> Result<(), String> {
+        let project_id = change.project_id();
+        self.hydrate(project_id)?;
+        let trash = self.trash();
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+
+        match change {
+            ProjectChange::VideoDeleted { index, video, delete_files, .. } => {
+                if revert {
+                    // Undoing the delete takes the video back out of the trash
+                    let trashed = trash.list()?.into_iter().find(|entry| matches!(&entry.item,
+                        TrashedItem::Video { project_id: p, video_id, .. } if p == project_id && *video_id == video.id));
+                    if let Some(entry) = trashed {
+                        trash.restore(&entry.id)?;
+                    }
+                    if !project.videos.iter().any(|v| v.id == video.id) {
+                        let index = (*index).min(project.videos.len());
+                        project.videos.insert(index, video.as_ref().clone());
+                    }
+                } else {
+                    trash.trash_video(project, *index, video, *delete_files)?;
+                    project.videos.retain(|v| v.id != video.id);
+                }
+            }
+            ProjectChange::NuggetsDeleted { video_id, removed, .. } => {
+                let video = project.videos.iter_mut()
+                    .find(|v| &v.id == video_id)
+                    .ok_or("Video not found in project")?;
+                let details = if revert {
+                    for (index, nugget) in removed {
+                        let index = (*index).min(video.nuggets.len());
+                        video.nuggets.insert(index, nugget.clone());
+                    }
+                    format!("Restored {} deleted nuggets", removed.len())
+                } else {
+                    video.nuggets.retain(|n| !removed.iter().any(|(_, r)| r.id == n.id));
+                    format!("Deleted {} nuggets", removed.len())
+                };
+                video.processing_history.push(ProcessingEvent {
+                    id: Uuid::new_v4().to_string(),
+                    event_type: EventType::NuggetsEdited,
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                    details,
+                    user_id: None,
+                    parameters: HashMap::new(),
+                });
+                video.updated_at = chrono::Utc::now().to_rfc3339();
+            }
+            ProjectChange::SettingsChanged { before, after, .. } => {
+                project.settings = if revert { before.as_ref().clone() } else { after.as_ref().clone() };
+            }
+        }
+
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+        refresh_metadata(project);
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+
+        let reason = if revert {
+            format!("Undo: {}", change.description())
+        } else {
+            change.description()
+        };
+        self.record_version(project_id, &reason);
+        Ok(())
+    }
+
+    pub fn get_video(&mut self, project_id: &str, video_id: &str) -> Result<&VideoProject, String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get(project_id)
+            .ok_or("Project not found")?;
+
+        project.videos.iter()
+            .find(|v| v.id == video_id)
+            .ok_or_else(|| "Video not found in project".to_string())
+    }
+
+    pub fn set_video_seo(&mut self, project_id: &str, video_id: &str, seo: SeoMetadata) -> Result<(), String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+
+        let video = project.videos.iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or("Video not found in project")?;
+
+        video.seo = Some(seo);
+        video.updated_at = chrono::Utc::now().to_rfc3339();
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        self.record_version(project_id, "SEO metadata updated");
+        Ok(())
+    }
+
+    pub fn set_video_notes(&mut self, project_id: &str, video_id: &str, notes: String) -> Result<(), String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+
+        let video = project.videos.iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or("Video not found in project")?;
+
+        video.notes = notes;
+        video.processing_history.push(ProcessingEvent {
+            id: Uuid::new_v4().to_string(),
+            event_type: EventType::NotesUpdated,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            details: "Video notes updated".to_string(),
+            user_id: None,
+            parameters: HashMap::new(),
+        });
+        video.updated_at = chrono::Utc::now().to_rfc3339();
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        self.record_version(project_id, "Video notes updated");
+        Ok(())
+    }
+
+    pub fn list_prompt_templates(&self, project_id: &str) -> Result<Vec<PromptTemplate>, String> {
+        let settings = self.project_settings(project_id)
+            .ok_or("Project not found")?;
+
+        Ok(settings.prompt_templates.clone())
+    }
+
+    pub fn active_prompt_template(&self, project_id: &str) -> Result<PromptTemplate, String> {
+        let settings = self.project_settings(project_id)
+            .ok_or("Project not found")?;
+
+        let active_id = settings.active_prompt_template.as_deref()
+            .unwrap_or(PromptTemplate::DEFAULT_ID);
+
+        Ok(settings.prompt_templates.iter()
+            .find(|t| t.id == active_id)
+            .cloned()
+            .unwrap_or_else(PromptTemplate::default_analysis))
+    }
+
+    pub fn save_prompt_template(&mut self, project_id: &str, mut template: PromptTemplate, make_active: bool) -> Result<(), String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+
+        if template.id.trim().is_empty() {
+            template.id = Uuid::new_v4().to_string();
+        }
+
+        let templates = &mut project.settings.prompt_templates;
+        if let Some(existing) = templates.iter_mut().find(|t| t.id == template.id) {
+            // Bump the version so cached analyses made with the old prompt are not reused
+            template.version = existing.version + 1;
+            *existing = template.clone();
+        } else {
+            templates.push(template.clone());
+        }
+
+        if make_active {
+            project.settings.active_prompt_template = Some(template.id);
+        }
+
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        self.record_version(project_id, &format!("Prompt template '{}' saved", template.name));
+        Ok(())
+    }
+
+    pub fn reset_prompt_templates(&mut self, project_id: &str) -> Result<(), String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+
+        project.settings.prompt_templates = default_prompt_templates();
+        project.settings.active_prompt_template = None;
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        self.record_version(project_id, "Prompt templates reset");
+        Ok(())
+    }
+
+    pub fn add_collaborator(&mut self, project_id: &str, collaborator: Collaborator) -> Result<(), String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+
+        // Check if collaborator already exists
+        if project.collaborators.iter().any(|c| c.email == collaborator.email) {
+            return Err("Collaborator already exists in this project".to_string());
+        }
+
+        project.collaborators.push(collaborator);
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        Ok(())
+    }
+
+    pub fn remove_collaborator(&mut self, project_id: &str, collaborator_id: &str) -> Result<(), String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+
+        let initial_len = project.collaborators.len();
+        project.collaborators.retain(|c| c.id != collaborator_id);
+
+        if project.collaborators.len() == initial_len {
+            return Err("Collaborator not found".to_string());
+        }
+
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        Ok(())
+    }
+
+    pub fn add_processing_event(&mut self, project_id: &str, event_type: EventType, details: String, parameters: HashMap<String, serde_json::Value>) -> Result<(), String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+
+        project.activity.push(ProcessingEvent::new(event_type, details, parameters));
+        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        Ok(())
+    }
+
+    // For events the processing pipeline produced for a video: nuggets generated, an analysis
+    // run, an export written
+    pub fn add_video_events(&mut self, project_id: &str, video_id: &str, events: Vec<ProcessingEvent>) -> Result<(), String> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+        let video = project.videos.iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or("Video not found in project")?;
+
+        video.processing_history.extend(events);
+        project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        Ok(())
+    }
+
+    // Clips and exports made for the video outside a batch, like by a workflow run
+    pub fn set_video_output_files(&mut self, project_id: &str, video_id: &str, output_files: Vec<String>) -> Result<(), String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+        let video = project.videos.iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or("Video not found in project")?;
+
+        video.output_files = output_files;
+        refresh_metadata(project);
+
+        let project = &self.projects[project_id];
+        self.save_project(project)?;
+        Ok(())
+    }
+
+    pub fn export_project(&mut self, project_id: &str, export_path: &str, include_files: bool) -> Result<(), String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get(project_id)
+            .ok_or("Project not found")?;
+
+        if include_files {
+            project_archive::export_archive(project, Path::new(export_path), |_| {})?;
+        } else {
+            // Export just the project metadata as JSON
+            let json_data = serde_json::to_string_pretty(project)
+                .map_err(|e| format!("Failed to serialize project: {}", e))?;
+            
+            write_atomic(Path::new(export_path), json_data.as_bytes())
+                .map_err(|e| format!("Failed to write export file: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    // Accepts both a zip archive from export_project(.., true) and a plain project JSON
+    pub fn import_project(&mut self, import_path: &str) -> Result<String, String> {
+        if project_archive::is_archive(Path::new(import_path)) {
+            let (project_id, project_dir) = self.prepare_import();
+            let project = project_archive::import_archive(Path::new(import_path), &project_dir, |_| {})
+                .inspect_err(|_| {
+                    let _ = std::fs::remove_dir_all(&project_dir);
+                })?;
+            return self.finish_import(project_id, project);
+        }
+
+        let content = std::fs::read_to_string(import_path)
+            .map_err(|e| format!("Failed to read import file: {}", e))?;
+
+        let mut project: Project = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse project data: {}", e))?;
+        collect_project_events(&mut project);
+
+        // Generate new ID to avoid conflicts
+        project.id = Uuid::new_v4().to_string();
+        
+        // Update workspace path
+        project.workspace_path = self.workspace_root.join(&project.id);
+        
+        // Create project directory
+        std::fs::create_dir_all(&project.workspace_path)
+            .map_err(|e| format!("Failed to create project directory: {}", e))?;
+
+        self.save_project(&project)?;
+        self.projects.insert(project.id.clone(), project.clone());
+        self.record_version(&project.id, "Project imported");
+
+        Ok(project.id)
+    }
+
+    // Unpacking an archive with its media can take minutes, so the import command does it
+    // between these two calls without holding the manager lock
+    pub fn prepare_import(&self) -> (String, PathBuf) {
+        let project_id = Uuid::new_v4().to_string();
+        let project_dir = self.workspace_root.join(&project_id);
+        (project_id, project_dir)
+    }
+
+    pub fn finish_import(&mut self, project_id: String, mut project: Project) -> Result<String, String> {
+        collect_project_events(&mut project);
+        project.id = project_id;
+        project.workspace_path = self.workspace_root.join(&project.id);
+
+        std::fs::create_dir_all(&project.workspace_path)
+            .map_err(|e| format!("Failed to create project directory: {}", e))?;
+
+        self.save_project(&project)?;
+        self.projects.insert(project.id.clone(), project.clone());
+        self.record_version(&project.id, "Project imported");
+
+        Ok(project.id)
+    }
+
+    // Imports an exported project's videos into an existing project instead of creating a new one.
+    // Duplicates are matched by video URL (any YouTube link form) or by video ID.
+    pub fn merge_import(&mut self, project_id: &str, import_path: &str, strategy: MergeStrategy) -> Result<MergeSummary, String> {
+        self.hydrate(project_id)?;
+        let content = std::fs::read_to_string(import_path)
+            .map_err(|e| format!("Failed to read import file: {}", e))?;
+
+        let mut imported: Project = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse project data: {}", e))?;
+        collect_project_events(&mut imported);
+
+        let project = self.projects.get_mut(project_id)
+            .ok_or("Project not found")?;
+
+        let summary = merge_videos(&mut project.videos, imported.videos, strategy, &imported.name);
+        project.updated_at = chrono::Utc::now().to_rfc3339();
+        refresh_metadata(project);
+
+        let project = self.projects.get(project_id)
+            .ok_or("Project not found")?;
+        self.save_project(project)?;
+        self.record_version(project_id, &format!("Merged videos from '{}'", imported.name));
+        Ok(summary)
+    }
+
+    pub fn list_versions(&mut self, project_id: &str) -> Result<Vec<VersionInfo>, String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get(project_id)
+            .ok_or("Project not found")?;
+
+        self.version_history(&project.workspace_path).list()
+    }
+
+    // Without to_version the diff is against the project as it is now
+    pub fn diff_versions(&mut self, project_id: &str, from_version: &str, to_version: Option<&str>) -> Result<VersionDiff, String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get(project_id)
+            .ok_or("Project not found")?;
+
+        let history = self.version_history(&project.workspace_path);
+        let from = history.load(from_version)?;
+        let diff = match to_version {
+            Some(to_version) => diff_projects(&from, &history.load(to_version)?),
+            None => diff_projects(&from, project),
+        };
+        Ok(diff)
+    }
+
+    pub fn rollback_to_version(&mut self, project_id: &str, version_id: &str) -> Result<(), String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get(project_id)
+            .ok_or("Project not found")?;
+
+        let history = self.version_history(&project.workspace_path);
+        let version = history.list()?
+            .into_iter()
+            .find(|v| v.id == version_id)
+            .ok_or("Version not found")?;
+
+        let mut restored = history.load(version_id)?;
+        collect_project_events(&mut restored);
+        restored.id = project.id.clone();
+        restored.workspace_path = project.workspace_path.clone();
+        restored.updated_at = chrono::Utc::now().to_rfc3339();
+        refresh_metadata(&mut restored);
+
+        self.save_project(&restored)?;
+        self.projects.insert(project_id.to_string(), restored);
+        // The rollback is a version of its own, so it can be rolled back too
+        self.record_version(project_id, &format!("Rolled back to version from {}", version.created_at));
+        Ok(())
+    }
+
+    // The change is already saved by the time this runs, a failed snapshot shouldn't turn it into an error
+    fn record_version(&self, project_id: &str, reason: &str) {
+        let Some(project) = self.projects.get(project_id) else {
+            return;
+        };
+        if let Err(e) = self.version_history(&project.workspace_path).record(project, reason) {
+            tracing::warn!("Failed to record project version: {}", e);
+        }
+    }
+
+    fn save_project(&self, project: &Project) -> Result<(), String> {
+        let project_file = project.workspace_path.join("project.json");
+        let json_data = serde_json::to_string_pretty(project)
+            .map_err(|e| format!("Failed to serialize project: {}", e))?;
+
+        let data = seal(&json_data, self.data_key.as_ref())?;
+        let _lock = ProjectLock::acquire(&project.workspace_path)?;
+        write_atomic(&project_file, &data)
+            .map_err(|e| format!("Failed to save project: {}", e))?;
+
+        Ok(())
     }
 
     pub fn load_projects(&mut self) -> Result<(), String> {
+        // Nothing can be read until unlock_workspace, which loads the projects again
+        if self.encryption_status().enabled && self.data_key.is_none() {
+            return Ok(());
+        }
+
+        let mut project_dirs = Vec::new();
         for entry in std::fs::read_dir(&self.workspace_root)
             .map_err(|e| format!("Failed to read workspace directory: {}", e))? {
             
             let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-            let project_file = entry.path().join("project.json");
+            project_dirs.push(entry.path());
+        }
+        project_dirs.extend(self.load_project_locations()?.into_values());
+
+        for project_dir in project_dirs {
+            let project_file = project_dir.join("project.json");
             
             if project_file.exists() {
-                let content = std::fs::read_to_string(&project_file)
+                let content = read_data(&project_file, self.data_key.as_ref())
                     .map_err(|e| format!("Failed to read project file: {}", e))?;
                 
-                let project: Project = serde_json::from_str(&content)
+                // Videos and nuggets are only read once the project is opened, see hydrate
+                let summary: ProjectSummary = serde_json::from_str(&content)
                     .map_err(|e| format!("Failed to parse project file: {}", e))?;
                 
-                self.projects.insert(project.id.clone(), project);
+                if !self.projects.contains_key(&summary.id) {
+                    self.unloaded.insert(summary.id.clone(), summary);
+                }
             }
         }
+
+        if let Err(e) = self.trash().purge_expired() {
+            tracing::warn!("Failed to purge expired trash: {}", e);
+        }
         
         Ok(())
     }
 
-    fn default_settings() -> ProjectSettings {
+    pub fn encryption_status(&self) -> EncryptionStatus {
+        EncryptionStatus {
+            enabled: project_crypto::is_enabled(&self.workspace_root),
+            unlocked: self.data_key.is_some(),
+        }
+    }
+
+    // Encrypts every project.json, version snapshot and backup, the trashed videos, cached
+    // analyses and batch pipeline transcripts, which covers the transcripts and notes. Exports
+    // stay plain, they're written where the user asked
+    pub fn enable_encryption(&mut self, passphrase: &str) -> Result<(), String> {
+        if self.encryption_status().enabled {
+            return Err("Encryption is already enabled".to_string());
+        }
+
+        let key = project_crypto::create_key(&self.workspace_root, passphrase)?;
+        // Plain files are still read, so stopping halfway leaves nothing unreadable
+        self.data_key = Some(key.clone());
+        self.rewrite_data_files(None, Some(&key))
+    }
+
+    // For the other stores that hold transcripts; None while the workspace is plain or locked
+    pub fn data_key(&self) -> Option<DataKey> {
+        self.data_key.clone()
+    }
+
+    pub fn unlock_workspace(&mut self, passphrase: &str) -> Result<(), String> {
+        if !self.encryption_status().enabled {
+            return Err("Encryption is not enabled".to_string());
+        }
+
+        self.data_key = Some(project_crypto::unlock(&self.workspace_root, passphrase)?);
+        self.load_projects()
+    }
+
+    pub fn disable_encryption(&mut self, passphrase: &str) -> Result<(), String> {
+        if !self.encryption_status().enabled {
+            return Err("Encryption is not enabled".to_string());
+        }
+
+        let key = project_crypto::unlock(&self.workspace_root, passphrase)?;
+        self.rewrite_data_files(Some(&key), None)?;
+        project_crypto::remove_config(&self.workspace_root)?;
+        self.data_key = None;
+        // In case the workspace was still locked
+        self.load_projects()
+    }
+
+    // Re-writes the data of every project, loaded or not and including the trash, from one
+    // key (or none) to another
+    fn rewrite_data_files(&self, from: Option<&DataKey>, to: Option<&DataKey>) -> Result<(), String> {
+        let list_dir = |dir: &Path| -> Vec<PathBuf> {
+            std::fs::read_dir(dir).into_iter().flatten().flatten().map(|entry| entry.path()).collect()
+        };
+
+        let mut project_dirs = list_dir(&self.workspace_root);
+        project_dirs.extend(self.load_project_locations()?.into_values());
+        let mut files = Vec::new();
+        for entry_dir in list_dir(&self.workspace_root.join("trash")) {
+            project_dirs.push(entry_dir.join("project"));
+            files.push(entry_dir.join("video.json"));
+        }
+
+        for project_dir in &project_dirs {
+            files.push(project_dir.join("project.json"));
+            for dir in ["versions", "backups"] {
+                files.extend(list_dir(&project_dir.join(dir)).into_iter().filter(|path| {
+                    path.extension().and_then(|e| e.to_str()) == Some("json")
+                        && path.file_name().and_then(|n| n.to_str()) != Some("index.json")
+                }));
+            }
+        }
+
+        let is_json = |path: &PathBuf| path.extension().and_then(|e| e.to_str()) == Some("json");
+        files.extend(list_dir(&self.workspace_root.join("cache").join("analysis")).into_iter().filter(is_json));
+        // The pipelines' transcripts and nuggets; their downloaded videos stay as they are
+        files.extend(walk_files(&self.workspace_root.join("batch_jobs").join("artifacts")).into_iter().filter(is_json));
+
+        for file in files.into_iter().filter(|file| file.is_file()) {
+            let content = read_data(&file, from)
+                .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+            write_atomic(&file, &seal(&content, to)?)
+                .map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+        }
+        Ok(())
+    }
+
+    fn trash(&self) -> Trash {
+        Trash::new(&self.workspace_root).with_key(self.data_key.clone())
+    }
+
+    fn version_history(&self, project_dir: &Path) -> VersionHistory {
+        VersionHistory::new(project_dir).with_key(self.data_key.clone())
+    }
+
+    pub fn default_settings() -> ProjectSettings {
         let mut quality_presets = HashMap::new();
         
         quality_presets.insert("high".to_string(), QualityPreset {
@@ -480,7 +2018,11 @@ impl ProjectManager {
             social_media_formats: true,
             backup_enabled: true,
             backup_interval_hours: 24,
+            backup_retention: BackupRetention::default(),
             quality_presets,
+            prompt_templates: default_prompt_templates(),
+            active_prompt_template: None,
+            output_language: None,
         }
     }
 
@@ -500,23 +2042,44 @@ impl ProjectManager {
                     social_media_formats: false,
                     backup_enabled: true,
                     backup_interval_hours: 12,
+                    backup_retention: BackupRetention::default(),
                     quality_presets: HashMap::new(),
+                    prompt_templates: default_prompt_templates(),
+                    active_prompt_template: None,
+                    output_language: None,
                 },
                 suggested_tags: vec!["education".to_string(), "tutorial".to_string(), "learning".to_string()],
                 workflow: vec![
+                    WorkflowStep {
+                        name: "Transcribe".to_string(),
+                        description: "Transcribe the video and split it into nuggets".to_string(),
+                        automated: true,
+                        parameters: HashMap::new(),
+                        action: Some(WorkflowAction::Transcribe),
+                        condition: None,
+                        continue_on_error: false,
+                    },
                     WorkflowStep {
                         name: "Extract Key Concepts".to_string(),
                         description: "Identify main educational concepts".to_string(),
                         automated: true,
                         parameters: HashMap::new(),
+                        action: Some(WorkflowAction::Analyze),
+                        condition: Some(StepCondition::SettingEnabled { setting: "ai_analysis_enabled".to_string() }),
+                        // The notes are still worth having without it
+                        continue_on_error: true,
                     },
                     WorkflowStep {
                         name: "Generate Study Notes".to_string(),
                         description: "Create structured notes from content".to_string(),
                         automated: true,
-                        parameters: HashMap::new(),
+                        parameters: HashMap::from([("formats".to_string(), serde_json::json!(["markdown"]))]),
+                        action: Some(WorkflowAction::Export),
+                        condition: None,
+                        continue_on_error: false,
                     },
                 ],
+                built_in: true,
             },
             ProjectTemplate {
                 id: "social_media".to_string(),
@@ -532,23 +2095,52 @@ impl ProjectManager {
                     social_media_formats: true,
                     backup_enabled: true,
                     backup_interval_hours: 6,
+                    backup_retention: BackupRetention::default(),
                     quality_presets: HashMap::new(),
+                    prompt_templates: default_prompt_templates(),
+                    active_prompt_template: None,
+                    output_language: None,
                 },
                 suggested_tags: vec!["viral".to_string(), "social".to_string(), "short".to_string()],
                 workflow: vec![
+                    WorkflowStep {
+                        name: "Transcribe".to_string(),
+                        description: "Transcribe the video and split it into nuggets".to_string(),
+                        automated: true,
+                        parameters: HashMap::new(),
+                        action: Some(WorkflowAction::Transcribe),
+                        condition: None,
+                        continue_on_error: false,
+                    },
                     WorkflowStep {
                         name: "Find Viral Moments".to_string(),
                         description: "Identify engaging clips for social media".to_string(),
                         automated: true,
                         parameters: HashMap::new(),
+                        action: Some(WorkflowAction::DetectHighlights),
+                        condition: None,
+                        continue_on_error: false,
+                    },
+                    WorkflowStep {
+                        name: "Render Clips".to_string(),
+                        description: "Cut the viral moments into vertical clips".to_string(),
+                        automated: true,
+                        parameters: HashMap::new(),
+                        action: Some(WorkflowAction::RenderClips),
+                        condition: Some(StepCondition::MinHighlights { count: 1 }),
+                        continue_on_error: false,
                     },
                     WorkflowStep {
                         name: "Generate Captions".to_string(),
                         description: "Create platform-specific captions".to_string(),
                         automated: true,
-                        parameters: HashMap::new(),
+                        parameters: HashMap::from([("formats".to_string(), serde_json::json!(["srt"])), ("highlights_only".to_string(), serde_json::json!(true))]),
+                        action: Some(WorkflowAction::Export),
+                        condition: Some(StepCondition::MinHighlights { count: 1 }),
+                        continue_on_error: false,
                     },
                 ],
+                built_in: true,
             },
         ]
     }
@@ -557,11 +2149,98 @@ impl ProjectManager {
         &self.templates
     }
 
-    pub fn create_backup(&self, project_id: &str) -> Result<String, String> {
+    // User templates are kept in one file next to the projects, after the built-in ones
+    pub fn load_templates(&mut self) -> Result<(), String> {
+        let templates_file = self.workspace_root.join("project_templates.json");
+        if !templates_file.exists() {
+            return Ok(());
+        }
+
+        let content = std::fs::read_to_string(&templates_file)
+            .map_err(|e| format!("Failed to read project templates: {}", e))?;
+        let user_templates: Vec<ProjectTemplate> = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse project templates: {}", e))?;
+
+        self.templates.retain(|t| t.built_in);
+        self.templates.extend(user_templates.into_iter().map(|mut t| {
+            t.built_in = false;
+            t
+        }));
+        Ok(())
+    }
+
+    // Creates the template when its ID is empty or unknown, otherwise replaces it
+    pub fn save_template(&mut self, mut template: ProjectTemplate) -> Result<String, String> {
+        if template.name.trim().is_empty() {
+            return Err("Template name is required".to_string());
+        }
+        if template.id.trim().is_empty() {
+            template.id = Uuid::new_v4().to_string();
+        }
+        template.built_in = false;
+
+        match self.templates.iter_mut().find(|t| t.id == template.id) {
+            Some(existing) if existing.built_in => return Err("Built-in templates can't be changed".to_string()),
+            Some(existing) => *existing = template.clone(),
+            None => self.templates.push(template.clone()),
+        }
+
+        self.save_templates()?;
+        Ok(template.id)
+    }
+
+    pub fn delete_template(&mut self, template_id: &str) -> Result<(), String> {
+        let index = self.templates.iter()
+            .position(|t| t.id == template_id)
+            .ok_or("Template not found")?;
+        if self.templates[index].built_in {
+            return Err("Built-in templates can't be deleted".to_string());
+        }
+
+        self.templates.remove(index);
+        self.save_templates()
+    }
+
+    pub fn export_template(&self, template_id: &str, export_path: &str) -> Result<(), String> {
+        let template = self.templates.iter()
+            .find(|t| t.id == template_id)
+            .ok_or("Template not found")?;
+
+        let json_data = serde_json::to_string_pretty(template)
+            .map_err(|e| format!("Failed to serialize template: {}", e))?;
+        write_atomic(Path::new(export_path), json_data.as_bytes())
+            .map_err(|e| format!("Failed to write template file: {}", e))
+    }
+
+    // Imported templates always get a new ID, so importing the same file twice gives two copies
+    pub fn import_template(&mut self, import_path: &str) -> Result<String, String> {
+        let content = std::fs::read_to_string(import_path)
+            .map_err(|e| format!("Failed to read template file: {}", e))?;
+        let mut template: ProjectTemplate = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse template file: {}", e))?;
+
+        template.id = String::new();
+        self.save_template(template)
+    }
+
+    fn save_templates(&self) -> Result<(), String> {
+        let user_templates: Vec<&ProjectTemplate> = self.templates.iter()
+            .filter(|t| !t.built_in)
+            .collect();
+        let json_data = serde_json::to_string_pretty(&user_templates)
+            .map_err(|e| format!("Failed to serialize project templates: {}", e))?;
+
+        write_atomic(&self.workspace_root.join("project_templates.json"), json_data.as_bytes())
+            .map_err(|e| format!("Failed to save project templates: {}", e))
+    }
+
+    pub fn create_backup(&mut self, project_id: &str) -> Result<String, String> {
+        self.hydrate(project_id)?;
         let project = self.projects.get(project_id)
             .ok_or("Project not found")?;
 
-        let backup_name = format!("backup_{}_{}.json", project_id, chrono::Utc::now().timestamp());
+        // Milliseconds, so the safety backup taken by restore_from_backup can't overwrite another one
+        let backup_name = format!("backup_{}_{}.json", project_id, chrono::Utc::now().timestamp_millis());
         let backup_path = project.workspace_path.join("backups").join(backup_name);
         
         std::fs::create_dir_all(backup_path.parent().unwrap())
@@ -570,9 +2249,908 @@ impl ProjectManager {
         let json_data = serde_json::to_string_pretty(project)
             .map_err(|e| format!("Failed to serialize project: {}", e))?;
 
-        std::fs::write(&backup_path, json_data)
+        write_atomic(&backup_path, &seal(&json_data, self.data_key.as_ref())?)
             .map_err(|e| format!("Failed to write backup: {}", e))?;
 
+        let backups = self.list_backups(project_id)?;
+        let retention = self.project_settings(project_id)
+            .map(|settings| settings.backup_retention.clone())
+            .unwrap_or_default();
+        for old_backup in backups_to_prune(&backups, &retention) {
+            if let Err(e) = std::fs::remove_file(&old_backup) {
+                tracing::warn!("Failed to remove old backup {}: {}", old_backup.display(), e);
+            }
+        }
+
         Ok(backup_path.to_string_lossy().to_string())
     }
-}
\ No newline at end of file
+
+    // Newest first
+    pub fn list_backups(&mut self, project_id: &str) -> Result<Vec<BackupInfo>, String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get(project_id)
+            .ok_or("Project not found")?;
+
+        let backups_dir = project.workspace_path.join("backups");
+        if !backups_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups = Vec::new();
+        for entry in std::fs::read_dir(&backups_dir)
+            .map_err(|e| format!("Failed to read backup directory: {}", e))? {
+            let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            let metadata = entry.metadata()
+                .map_err(|e| format!("Failed to read backup {}: {}", path.display(), e))?;
+            let created_at = metadata.modified()
+                .map(chrono::DateTime::<chrono::Utc>::from)
+                .unwrap_or_else(|_| chrono::Utc::now());
+            backups.push(BackupInfo {
+                path,
+                created_at,
+                size_bytes: metadata.len(),
+            });
+        }
+
+        backups.sort_by_key(|backup| Reverse(backup.created_at));
+        Ok(backups)
+    }
+
+    // Takes a backup of the current state first and returns its path, so a restore can itself be undone
+    pub fn restore_from_backup(&mut self, project_id: &str, backup_path: &str) -> Result<String, String> {
+        self.hydrate(project_id)?;
+        let project = self.projects.get(project_id)
+            .ok_or("Project not found")?;
+
+        // Only this project's own backups; anything else should go through import_project
+        let backups_dir = project.workspace_path.join("backups").canonicalize()
+            .map_err(|_| "Project has no backups".to_string())?;
+        let backup_path = Path::new(backup_path).canonicalize()
+            .map_err(|e| format!("Failed to read backup: {}", e))?;
+        if !backup_path.starts_with(&backups_dir) {
+            return Err("Not a backup of this project".to_string());
+        }
+
+        let content = read_data(&backup_path, self.data_key.as_ref())
+            .map_err(|e| format!("Failed to read backup: {}", e))?;
+        let mut restored: Project = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse backup: {}", e))?;
+        collect_project_events(&mut restored);
+
+        let safety_backup = self.create_backup(project_id)?;
+
+        let project = self.projects.get(project_id)
+            .ok_or("Project not found")?;
+        restored.id = project.id.clone();
+        restored.workspace_path = project.workspace_path.clone();
+        restored.updated_at = chrono::Utc::now().to_rfc3339();
+        refresh_metadata(&mut restored);
+
+        self.save_project(&restored)?;
+
+        self.projects.insert(project_id.to_string(), restored);
+        // Undo entries refer to the state that was just replaced
+        self.undo_stack.retain(|change| change.project_id() != project_id);
+        self.redo_stack.retain(|change| change.project_id() != project_id);
+
+        let backup_name = backup_path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.record_version(project_id, &format!("Restored from backup {}", backup_name));
+        Ok(safety_backup)
+    }
+}
+
+fn refresh_metadata(project: &mut Project) {
+    project.metadata.total_videos = project.videos.len();
+    project.metadata.total_nuggets = project.videos.iter().map(|v| v.nuggets.len()).sum();
+    project.metadata.total_duration_seconds = project.videos.iter().map(|v| v.video_info.duration).sum();
+    project.metadata.storage_used_mb = storage_used_mb(project);
+    project.metadata.last_activity = chrono::Utc::now().to_rfc3339();
+}
+
+// Older versions copied every project-wide event into each video's history. An event ID found
+// in more than one history is one of those, and is kept once in project.activity instead
+fn collect_project_events(project: &mut Project) {
+    let mut seen_in: HashMap<String, usize> = HashMap::new();
+    for video in &project.videos {
+        for event in &video.processing_history {
+            *seen_in.entry(event.id.clone()).or_default() += 1;
+        }
+    }
+    if seen_in.values().all(|count| *count < 2) {
+        return;
+    }
+
+    for video in &mut project.videos {
+        for event in std::mem::take(&mut video.processing_history) {
+            if seen_in[&event.id] < 2 {
+                video.processing_history.push(event);
+            } else if !project.activity.iter().any(|e| e.id == event.id) {
+                project.activity.push(event);
+            }
+        }
+    }
+}
+
+pub fn rebase_path(path: &Path, from: &Path, to: &Path) -> Option<PathBuf> {
+    let rest = path.strip_prefix(from).ok()?;
+    // Joining an empty path would add a trailing separator
+    if rest.as_os_str().is_empty() {
+        Some(to.to_path_buf())
+    } else {
+        Some(to.join(rest))
+    }
+}
+
+// Paths outside `from` are left alone
+fn rebase_paths(project: &mut Project, from: &Path, to: &Path) {
+    if let Some(path) = rebase_path(&project.workspace_path, from, to) {
+        project.workspace_path = path;
+    }
+    for video in &mut project.videos {
+        for file in video.output_files.iter_mut().chain(video.video_info.thumbnail.iter_mut()) {
+            if let Some(path) = rebase_path(Path::new(file), from, to) {
+                *file = path.to_string_lossy().to_string();
+            }
+        }
+    }
+}
+
+// Files inside the source project keep their relative path, others go under media/<video id>
+fn clone_media_file(file: &Path, source_dir: &Path, target_dir: &Path, video_id: &str) -> Result<String, String> {
+    let target = match file.strip_prefix(source_dir) {
+        Ok(relative) => target_dir.join(relative),
+        Err(_) => target_dir.join("media").join(video_id).join(file.file_name().unwrap_or_default()),
+    };
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    if std::fs::hard_link(file, &target).is_err() {
+        std::fs::copy(file, &target)
+            .map_err(|e| format!("Failed to copy {}: {}", file.display(), e))?;
+    }
+    Ok(target.to_string_lossy().to_string())
+}
+
+// Renames when possible, and copies then deletes when the destination is on another drive
+pub fn move_dir(from: &Path, to: &Path) -> Result<(), String> {
+    if to.exists() {
+        let is_empty = std::fs::read_dir(to)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+        if !is_empty {
+            return Err(format!("{} already exists and is not empty", to.display()));
+        }
+        std::fs::remove_dir(to)
+            .map_err(|e| format!("Failed to replace {}: {}", to.display(), e))?;
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    for file in walk_files(from) {
+        let target = to.join(file.strip_prefix(from).unwrap_or(&file));
+        let copied = target.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::copy(&file, &target));
+        if let Err(e) = copied {
+            let _ = std::fs::remove_dir_all(to);
+            return Err(format!("Failed to copy {}: {}", file.display(), e));
+        }
+    }
+    std::fs::remove_dir_all(from)
+        .map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+}
+
+// Files that are already gone are skipped
+fn remove_output_files(files: &[String]) -> Result<(), String> {
+    for file in files {
+        if !Path::new(file).exists() {
+            continue;
+        }
+        std::fs::remove_file(file)
+            .map_err(|e| format!("Failed to delete {}: {}", file, e))?;
+    }
+    Ok(())
+}
+
+fn validate_nugget_times(start_time: f64, end_time: f64, duration: f64) -> Result<(), String> {
+    if !(start_time >= 0.0 && start_time < end_time) {
+        return Err("Nugget must start before it ends".to_string());
+    }
+    // Duration is 0 when the video info didn't include it
+    if duration > 0.0 && end_time > duration {
+        return Err(format!("Nugget ends after the video ({:.1}s)", duration));
+    }
+    Ok(())
+}
+
+// There are no word timings in a nugget, so the split point is assumed to fall
+// proportionally through the text
+fn split_transcript(transcript: &str, fraction: f64) -> (String, String) {
+    let words: Vec<&str> = transcript.split_whitespace().collect();
+    let split = ((words.len() as f64 * fraction).round() as usize).min(words.len());
+    (words[..split].join(" "), words[split..].join(" "))
+}
+
+fn join_text(first: Option<String>, second: Option<String>, separator: &str) -> Option<String> {
+    match (first, second) {
+        (Some(first), Some(second)) => Some(format!("{}{}{}", first, separator, second)),
+        (first, second) => first.or(second),
+    }
+}
+
+fn merge_videos(videos: &mut Vec<VideoProject>, imported: Vec<VideoProject>, strategy: MergeStrategy, source_name: &str) -> MergeSummary {
+    let mut summary = MergeSummary::default();
+
+    for mut video in imported {
+        let key = video_key(&video.video_info.url);
+        let existing = videos.iter()
+            .position(|v| v.id == video.id || video_key(&v.video_info.url) == key);
+
+        video.processing_history.push(ProcessingEvent {
+            id: Uuid::new_v4().to_string(),
+            event_type: EventType::VideoAdded,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            details: format!("Imported from project '{}'", source_name),
+            user_id: None,
+            parameters: HashMap::new(),
+        });
+
+        match (existing, strategy) {
+            (None, _) => {
+                videos.push(video);
+                summary.added += 1;
+            }
+            (Some(_), MergeStrategy::Skip) => summary.skipped += 1,
+            (Some(index), MergeStrategy::Replace) => {
+                video.id = videos[index].id.clone();
+                videos[index] = video;
+                summary.replaced += 1;
+            }
+            (Some(_), MergeStrategy::KeepBoth) => {
+                video.id = Uuid::new_v4().to_string();
+                videos.push(video);
+                summary.kept_both += 1;
+            }
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(id: &str, url: &str, nugget_count: usize) -> VideoProject {
+        VideoProject {
+            id: id.to_string(),
+            video_info: VideoInfo {
+                title: id.to_string(),
+                duration: 60.0,
+                url: url.to_string(),
+                thumbnail: None,
+            },
+            nuggets: (0..nugget_count).map(|index| VideoNugget {
+                id: format!("{}-{}", id, index),
+                title: format!("Nugget {}", index),
+                start_time: 0.0,
+                end_time: 10.0,
+                transcript: None,
+                tags: Vec::new(),
+                notes: None,
+                youtube_video_id: None,
+                created_at: String::new(),
+            }).collect(),
+            analysis: None,
+            seo: None,
+            output_files: Vec::new(),
+            processing_history: Vec::new(),
+            custom_tags: Vec::new(),
+            notes: String::new(),
+            status: VideoStatus::Completed,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge_videos_strategies() {
+        let existing = vec![video("a", "https://www.youtube.com/watch?v=dQw4w9WgXcQ", 1)];
+        let imported = || vec![
+            video("b", "https://youtu.be/dQw4w9WgXcQ", 3),
+            video("c", "https://example.com/talk.mp4", 2),
+        ];
+
+        let mut videos = existing.clone();
+        let summary = merge_videos(&mut videos, imported(), MergeStrategy::Skip, "Other");
+        assert_eq!(summary, MergeSummary { added: 1, skipped: 1, ..MergeSummary::default() });
+        assert_eq!(videos[0].nuggets.len(), 1);
+
+        let mut videos = existing.clone();
+        let summary = merge_videos(&mut videos, imported(), MergeStrategy::Replace, "Other");
+        assert_eq!(summary.replaced, 1);
+        assert_eq!(videos.len(), 2);
+        assert_eq!(videos[0].id, "a");
+        assert_eq!(videos[0].nuggets.len(), 3);
+
+        let mut videos = existing;
+        let summary = merge_videos(&mut videos, imported(), MergeStrategy::KeepBoth, "Other");
+        assert_eq!(summary.kept_both, 1);
+        assert_eq!(videos.len(), 3);
+        assert_ne!(videos[1].id, "b");
+    }
+
+    #[test]
+    fn test_load_projects_hydrates_on_demand() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 2);
+        manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        manager.load_projects().unwrap();
+        assert!(manager.projects.is_empty());
+
+        let projects = manager.list_projects();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].metadata.total_nuggets, 2);
+
+        let project = manager.get_project(&project_id).unwrap().unwrap();
+        assert_eq!(project.videos.len(), 1);
+        assert!(manager.unloaded.is_empty());
+    }
+
+    #[test]
+    fn test_version_diff_and_rollback() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let first = video("a", "https://youtu.be/dQw4w9WgXcQ", 2);
+        manager.add_video_to_project(&project_id, first.video_info, first.nuggets, None).unwrap();
+
+        let versions = manager.list_versions(&project_id).unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[1].total_nuggets, 2);
+
+        let second = video("b", "https://youtu.be/9bZkp7q19f0", 1);
+        manager.add_video_to_project(&project_id, second.video_info, second.nuggets, None).unwrap();
+        let project = manager.get_project_mut(&project_id).unwrap().unwrap();
+        project.videos[0].nuggets[0].title = "Renamed".to_string();
+
+        let diff = manager.diff_versions(&project_id, &versions[1].id, None).unwrap();
+        assert_eq!(diff.videos_added, vec!["b".to_string()]);
+        assert_eq!(diff.nuggets_added.len(), 1);
+        assert_eq!(diff.nuggets_edited.len(), 1);
+        assert_eq!(diff.nuggets_edited[0].after.title, "Renamed");
+        assert!(diff.nuggets_removed.is_empty());
+
+        manager.rollback_to_version(&project_id, &versions[1].id).unwrap();
+        let project = manager.get_project(&project_id).unwrap().unwrap();
+        assert_eq!(project.videos.len(), 1);
+        assert_eq!(project.videos[0].nuggets[0].title, "Nugget 0");
+        assert_eq!(manager.list_versions(&project_id).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_undo_redo_deletions() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 3);
+        let video_id = manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+
+        let deleted = manager.delete_nuggets(&project_id, &video_id, &["a-0".to_string(), "a-2".to_string()]).unwrap();
+        assert_eq!(deleted, 2);
+        manager.delete_video(&project_id, &video_id, false).unwrap();
+        assert!(manager.get_project(&project_id).unwrap().unwrap().videos.is_empty());
+
+        assert_eq!(manager.undo_last_change().unwrap(), Some("Delete video 'a'".to_string()));
+        assert_eq!(manager.undo_last_change().unwrap(), Some("Delete 2 nuggets".to_string()));
+        assert_eq!(manager.undo_last_change().unwrap(), None);
+        let nugget_ids: Vec<_> = manager.get_video(&project_id, &video_id).unwrap()
+            .nuggets.iter().map(|n| n.id.clone()).collect();
+        assert_eq!(nugget_ids, vec!["a-0", "a-1", "a-2"]);
+
+        assert_eq!(manager.redo().unwrap(), Some("Delete 2 nuggets".to_string()));
+        assert_eq!(manager.get_video(&project_id, &video_id).unwrap().nuggets.len(), 1);
+        assert_eq!(manager.get_project(&project_id).unwrap().unwrap().metadata.total_nuggets, 1);
+
+        // A new change drops the redo stack
+        let mut settings = manager.project_settings(&project_id).unwrap().clone();
+        settings.prompt_templates.clear();
+        manager.update_project_settings(&project_id, settings).unwrap();
+        assert_eq!(manager.redo().unwrap(), None);
+    }
+
+    #[test]
+    fn test_split_merge_and_reorder_nuggets() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let mut sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 1);
+        sample.nuggets[0].transcript = Some("one two three four".to_string());
+        let video_id = manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+
+        assert!(manager.split_nugget(&project_id, &video_id, "a-0", 10.0).is_err());
+        let second_id = manager.split_nugget(&project_id, &video_id, "a-0", 5.0).unwrap();
+        let nuggets = &manager.get_video(&project_id, &video_id).unwrap().nuggets;
+        assert_eq!((nuggets[0].end_time, nuggets[1].start_time), (5.0, 5.0));
+        assert_eq!(nuggets[0].transcript.as_deref(), Some("one two"));
+        assert_eq!(nuggets[1].transcript.as_deref(), Some("three four"));
+
+        manager.reorder_nuggets(&project_id, &video_id, &[second_id.clone(), "a-0".to_string()]).unwrap();
+        assert!(manager.merge_nuggets(&project_id, &video_id, "a-0", &second_id).is_err());
+        manager.merge_nuggets(&project_id, &video_id, &second_id, "a-0").unwrap();
+
+        let video = manager.get_video(&project_id, &video_id).unwrap();
+        assert_eq!(video.nuggets.len(), 1);
+        assert_eq!((video.nuggets[0].start_time, video.nuggets[0].end_time), (0.0, 10.0));
+        assert_eq!(video.processing_history.iter().filter(|e| matches!(e.event_type, EventType::NuggetsEdited)).count(), 3);
+
+        let update = NuggetUpdate { end_time: Some(90.0), ..NuggetUpdate::default() };
+        assert!(manager.update_nugget(&project_id, &video_id, &second_id, update).is_err());
+    }
+
+    #[test]
+    fn test_import_nuggets() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 1);
+        let video_id = manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+
+        assert_eq!(manager.import_nuggets(&project_id, &video_id, "0:00 Intro\n0:40 Main part", false).unwrap(), 2);
+        let video = manager.get_video(&project_id, &video_id).unwrap();
+        assert_eq!(video.nuggets.len(), 3);
+        assert_eq!(video.nuggets[2].title, "Main part");
+        assert_eq!(video.nuggets[2].end_time, video.video_info.duration);
+
+        assert!(manager.import_nuggets(&project_id, &video_id, "nothing to import", true).is_err());
+        assert_eq!(manager.import_nuggets(&project_id, &video_id, "1\n00:00:01,000 --> 00:00:03,000\nHi\n", true).unwrap(), 1);
+        assert_eq!(manager.get_video(&project_id, &video_id).unwrap().nuggets.len(), 1);
+        assert_eq!(manager.get_project(&project_id).unwrap().unwrap().metadata.total_nuggets, 1);
+    }
+
+    #[test]
+    fn test_archive_video_deletes_files() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().join("workspace")).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 1);
+        let video_id = manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+
+        let clip = temp_dir.path().join("clip.mp4");
+        std::fs::write(&clip, vec![0u8; 1024 * 1024]).unwrap();
+        let project = manager.get_project_mut(&project_id).unwrap().unwrap();
+        project.videos[0].output_files = vec![clip.to_string_lossy().to_string()];
+
+        manager.archive_video(&project_id, &video_id, true, true).unwrap();
+        assert!(!clip.exists());
+        let project = manager.get_project(&project_id).unwrap().unwrap();
+        assert!(matches!(project.videos[0].status, VideoStatus::Archived));
+        assert!(project.videos[0].output_files.is_empty());
+        assert!(project.metadata.storage_used_mb < 0.1);
+
+        manager.delete_video(&project_id, &video_id, true).unwrap();
+        assert_eq!(manager.get_project(&project_id).unwrap().unwrap().metadata.total_videos, 0);
+    }
+
+    #[test]
+    fn test_user_templates_persist() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let mut template = manager.get_templates()[0].clone();
+        assert!(manager.save_template(template.clone()).is_err());
+        assert!(manager.delete_template(&template.id).is_err());
+
+        template.id = String::new();
+        template.name = "Podcasts".to_string();
+        let template_id = manager.save_template(template).unwrap();
+        let export_path = temp_dir.path().join("podcasts.json");
+        manager.export_template(&template_id, &export_path.to_string_lossy()).unwrap();
+
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        manager.load_templates().unwrap();
+        assert!(manager.get_templates().iter().any(|t| t.id == template_id && !t.built_in));
+
+        let imported_id = manager.import_template(&export_path.to_string_lossy()).unwrap();
+        assert_ne!(imported_id, template_id);
+        manager.delete_template(&template_id).unwrap();
+        assert_eq!(manager.get_templates().iter().filter(|t| t.name == "Podcasts").count(), 1);
+    }
+
+    #[test]
+    fn test_find_and_link_duplicate_videos() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let talks_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let clips_id = manager.create_project("Clips".to_string(), None, None).unwrap();
+        let sample = video("a", "https://www.youtube.com/watch?v=dQw4w9WgXcQ", 2);
+        let video_id = manager.add_video_to_project(&talks_id, sample.video_info, sample.nuggets, None).unwrap();
+
+        // Talks is only on disk in the reloaded manager
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        manager.load_projects().unwrap();
+        let duplicates = manager.find_duplicate_videos("https://youtu.be/dQw4w9WgXcQ").unwrap();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!((duplicates[0].project_id.as_str(), duplicates[0].nugget_count), (talks_id.as_str(), 2));
+
+        let linked_id = manager.link_existing_video(&clips_id, &talks_id, &video_id).unwrap();
+        assert_ne!(linked_id, video_id);
+        assert_eq!(manager.find_duplicate_videos("https://youtu.be/dQw4w9WgXcQ").unwrap().len(), 2);
+        assert!(manager.link_existing_video(&clips_id, &talks_id, &video_id).is_err());
+    }
+
+    #[test]
+    fn test_restore_from_backup() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let backup_path = manager.create_backup(&project_id).unwrap();
+
+        let sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 2);
+        manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+
+        let outside = temp_dir.path().join("elsewhere.json");
+        std::fs::copy(&backup_path, &outside).unwrap();
+        assert!(manager.restore_from_backup(&project_id, &outside.to_string_lossy()).is_err());
+
+        let safety_backup = manager.restore_from_backup(&project_id, &backup_path).unwrap();
+        assert!(manager.get_project(&project_id).unwrap().unwrap().videos.is_empty());
+        assert_eq!(manager.list_backups(&project_id).unwrap().len(), 2);
+
+        manager.restore_from_backup(&project_id, &safety_backup).unwrap();
+        assert_eq!(manager.get_project(&project_id).unwrap().unwrap().metadata.total_nuggets, 2);
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().join("workspace")).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 1);
+        manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+
+        // A clip rendered outside the project directory and an export inside it
+        let clip = temp_dir.path().join("clip_001.mp4");
+        std::fs::write(&clip, b"clip").unwrap();
+        let project = manager.get_project_mut(&project_id).unwrap().unwrap();
+        project.videos[0].output_files = vec![clip.to_string_lossy().to_string()];
+        std::fs::create_dir_all(project.workspace_path.join("exports")).unwrap();
+        std::fs::write(project.workspace_path.join("exports/nuggets.json"), b"[]").unwrap();
+
+        let archive = temp_dir.path().join("talks.zip");
+        manager.export_project(&project_id, &archive.to_string_lossy(), true).unwrap();
+        let imported_id = manager.import_project(&archive.to_string_lossy()).unwrap();
+        assert_ne!(imported_id, project_id);
+
+        let imported = manager.get_project(&imported_id).unwrap().unwrap();
+        let imported_clip = PathBuf::from(&imported.videos[0].output_files[0]);
+        assert!(imported_clip.starts_with(&imported.workspace_path));
+        assert_eq!(std::fs::read(imported_clip).unwrap(), b"clip");
+        assert!(imported.workspace_path.join("exports/nuggets.json").exists());
+    }
+
+    #[test]
+    fn test_move_project_and_workspace() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().join("workspace")).unwrap();
+        let moved_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let other_id = manager.create_project("Podcasts".to_string(), None, None).unwrap();
+
+        let project = manager.get_project_mut(&other_id).unwrap().unwrap();
+        let clip = project.workspace_path.join("clip.mp4");
+        std::fs::write(&clip, b"clip").unwrap();
+        project.videos.push(video("a", "https://youtu.be/dQw4w9WgXcQ", 1));
+        project.videos[0].output_files = vec![clip.to_string_lossy().to_string()];
+
+        let external = temp_dir.path().join("external");
+        let moved_dir = manager.move_project(&moved_id, &external).unwrap();
+        assert_eq!(moved_dir, external.join(&moved_id));
+        assert!(moved_dir.join("project.json").exists());
+
+        let new_root = temp_dir.path().join("relocated");
+        manager.move_workspace(&new_root).unwrap();
+        assert!(!temp_dir.path().join("workspace").exists());
+        let other = manager.get_project(&other_id).unwrap().unwrap();
+        assert_eq!(other.workspace_path, new_root.join(&other_id));
+        assert_eq!(other.videos[0].output_files[0], new_root.join(&other_id).join("clip.mp4").to_string_lossy());
+
+        // Both projects are found again from the new location
+        let mut reloaded = ProjectManager::new(new_root.clone()).unwrap();
+        reloaded.load_projects().unwrap();
+        assert_eq!(reloaded.list_projects().len(), 2);
+        assert_eq!(reloaded.get_project(&moved_id).unwrap().unwrap().workspace_path, moved_dir);
+    }
+
+    #[test]
+    fn test_collections() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let first = video("a", "https://youtu.be/dQw4w9WgXcQ", 2);
+        let second = video("b", "https://youtu.be/9bZkp7q19f0", 1);
+        let first_id = manager.add_video_to_project(&project_id, first.video_info, first.nuggets, None).unwrap();
+        let second_id = manager.add_video_to_project(&project_id, second.video_info, second.nuggets, None).unwrap();
+        let item = |video_id: &str, nugget_id: &str| NuggetRef { video_id: video_id.to_string(), nugget_id: nugget_id.to_string() };
+
+        let collection_id = manager.create_collection(&project_id, "Best of".to_string(), None, vec![item(&second_id, "b-0"), item(&first_id, "a-1")]).unwrap();
+        assert!(manager.add_to_collection(&project_id, &collection_id, vec![item(&first_id, "missing")]).is_err());
+        assert!(manager.update_collection(&project_id, &collection_id, None, None, Some(vec![item(&first_id, "a-0"), item(&first_id, "a-0")])).is_err());
+
+        manager.add_to_collection(&project_id, &collection_id, vec![item(&first_id, "a-1"), item(&first_id, "a-0")]).unwrap();
+        let titles: Vec<String> = manager.collection_nuggets(&project_id, &collection_id).unwrap()
+            .iter()
+            .map(|n| format!("{}/{}", n.video_title, n.nugget.id))
+            .collect();
+        assert_eq!(titles, vec!["b/b-0", "a/a-1", "a/a-0"]);
+
+        // Deleted nuggets drop out of the collection and come back with undo
+        manager.delete_nuggets(&project_id, &first_id, &["a-1".to_string()]).unwrap();
+        assert_eq!(manager.collection_nuggets(&project_id, &collection_id).unwrap().len(), 2);
+        manager.undo_last_change().unwrap();
+        assert_eq!(manager.collection_nuggets(&project_id, &collection_id).unwrap().len(), 3);
+
+        manager.delete_collection(&project_id, &collection_id).unwrap();
+        assert!(manager.collection_nuggets(&project_id, &collection_id).is_err());
+    }
+
+    #[test]
+    fn test_find_nuggets() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        for sample in [video("a", "https://youtu.be/dQw4w9WgXcQ", 2), video("b", "https://youtu.be/9bZkp7q19f0", 1)] {
+            manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+        }
+
+        let ids = ["b-0".to_string(), "a-1".to_string()];
+        let found: Vec<String> = manager.find_nuggets(&project_id, &ids).unwrap()
+            .into_iter()
+            .map(|nugget| nugget.id)
+            .collect();
+        assert_eq!(found, ids);
+        assert!(manager.find_nuggets(&project_id, &["missing".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_smart_collections() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let mut sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 3);
+        sample.nuggets[0].tags = vec!["Rust".to_string()];
+        sample.nuggets[1].tags = vec!["rust".to_string(), "async".to_string()];
+        sample.nuggets[2].end_time = 50.0;
+        let video_id = manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+
+        let invalid = NuggetQuery { min_duration: Some(20.0), max_duration: Some(10.0), ..NuggetQuery::default() };
+        assert!(manager.create_smart_collection(&project_id, "Broken".to_string(), invalid).is_err());
+
+        let query = NuggetQuery { tags: vec!["RUST".to_string()], max_duration: Some(30.0), ..NuggetQuery::default() };
+        let collection_id = manager.create_smart_collection(&project_id, "Rust clips".to_string(), query).unwrap();
+        let ids = |manager: &mut ProjectManager| -> Vec<String> {
+            manager.smart_collection_nuggets(&project_id, &collection_id).unwrap()
+                .into_iter()
+                .map(|n| n.nugget.id)
+                .collect()
+        };
+        assert_eq!(ids(&mut manager), vec!["a-0", "a-1"]);
+
+        // The collection follows edits to the nuggets
+        let update = NuggetUpdate { tags: Some(vec!["rust".to_string()]), ..NuggetUpdate::default() };
+        manager.update_nugget(&project_id, &video_id, "a-2", update).unwrap();
+        let update = NuggetUpdate { end_time: Some(20.0), ..NuggetUpdate::default() };
+        manager.update_nugget(&project_id, &video_id, "a-2", update).unwrap();
+        assert_eq!(ids(&mut manager), vec!["a-0", "a-1", "a-2"]);
+
+        // No analysis means no sentiment to match
+        let query = NuggetQuery { min_sentiment: Some(0.0), ..NuggetQuery::default() };
+        manager.update_smart_collection(&project_id, &collection_id, None, Some(query)).unwrap();
+        assert!(ids(&mut manager).is_empty());
+    }
+
+    #[test]
+    fn test_clone_project() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().join("workspace")).unwrap();
+        let project_id = manager.create_project("Weekly show".to_string(), None, None).unwrap();
+        let sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 2);
+        let video_id = manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+        let item = NuggetRef { video_id: video_id.clone(), nugget_id: "a-1".to_string() };
+        manager.create_collection(&project_id, "Best of".to_string(), None, vec![item]).unwrap();
+
+        let external_clip = temp_dir.path().join("clip.mp4");
+        std::fs::write(&external_clip, b"clip").unwrap();
+        let project = manager.get_project_mut(&project_id).unwrap().unwrap();
+        let local_clip = project.workspace_path.join("clips/local.mp4");
+        std::fs::create_dir_all(local_clip.parent().unwrap()).unwrap();
+        std::fs::write(&local_clip, b"local").unwrap();
+        project.videos[0].output_files = vec![
+            external_clip.to_string_lossy().to_string(),
+            local_clip.to_string_lossy().to_string(),
+        ];
+
+        let clone_id = manager.clone_project(&project_id, "Next week".to_string(), true).unwrap();
+        let clone = manager.get_project(&clone_id).unwrap().unwrap();
+        assert_eq!(clone.name, "Next week");
+        assert_ne!(clone.videos[0].id, video_id);
+        assert_eq!(clone.videos[0].output_files.len(), 2);
+        assert!(clone.videos[0].output_files.iter().all(|file| Path::new(file).starts_with(&clone.workspace_path)));
+        assert!(clone.workspace_path.join("clips/local.mp4").exists());
+
+        // The collection points at the clone's own nuggets
+        let collection = &clone.collections[0];
+        assert_eq!(collection.items[0].video_id, clone.videos[0].id);
+        assert_eq!(collection.items[0].nugget_id, clone.videos[0].nuggets[1].id);
+
+        let bare_id = manager.clone_project(&project_id, "Template".to_string(), false).unwrap();
+        let bare = manager.get_project(&bare_id).unwrap().unwrap();
+        assert!(bare.videos[0].output_files.is_empty());
+        assert_eq!(bare.metadata.total_nuggets, 2);
+    }
+
+    #[test]
+    fn test_trash_and_restore() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().join("workspace")).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 1);
+        let video_id = manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+
+        let clip = temp_dir.path().join("clip.mp4");
+        std::fs::write(&clip, b"clip").unwrap();
+        let project = manager.get_project_mut(&project_id).unwrap().unwrap();
+        project.videos[0].output_files = vec![clip.to_string_lossy().to_string()];
+        let project_dir = project.workspace_path.clone();
+
+        // Deleting a video with its files moves both to the trash, and undo brings them back
+        manager.delete_video(&project_id, &video_id, true).unwrap();
+        assert!(!clip.exists());
+        assert_eq!(manager.list_trash().unwrap().len(), 1);
+        manager.undo_last_change().unwrap();
+        assert!(clip.exists());
+        assert!(manager.list_trash().unwrap().is_empty());
+
+        manager.delete_video(&project_id, &video_id, true).unwrap();
+        manager.delete_project(&project_id).unwrap();
+        assert!(!project_dir.exists());
+        let trash = manager.list_trash().unwrap();
+        assert_eq!(trash.len(), 2);
+        assert!(matches!(trash[0].item, TrashedItem::Project { .. }));
+
+        // The video can't go back before its project does
+        assert!(manager.restore_from_trash(&trash[1].id).is_err());
+        manager.restore_from_trash(&trash[0].id).unwrap();
+        assert!(project_dir.join("project.json").exists());
+        manager.restore_from_trash(&trash[1].id).unwrap();
+        let project = manager.get_project(&project_id).unwrap().unwrap();
+        assert_eq!(project.videos[0].id, video_id);
+        assert!(clip.exists());
+
+        manager.delete_video(&project_id, &video_id, false).unwrap();
+        assert_eq!(manager.purge_trash(None).unwrap(), 1);
+        assert!(manager.list_trash().unwrap().is_empty());
+        assert!(clip.exists());
+    }
+
+    #[test]
+    fn test_encryption_at_rest() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let workspace = temp_dir.path().join("workspace");
+        let mut manager = ProjectManager::new(workspace.clone()).unwrap();
+        let project_id = manager.create_project("Board meetings".to_string(), None, None).unwrap();
+        let sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 1);
+        manager.add_video_to_project(&project_id, sample.video_info, sample.nuggets, None).unwrap();
+        let backup = manager.create_backup(&project_id).unwrap();
+        let artifacts = crate::pipeline_artifacts::ArtifactStore::new(workspace.join("batch_jobs").join("artifacts").join("pipeline"));
+        let transcript = artifacts.store_transcript("https://youtu.be/dQw4w9WgXcQ", &"the quarterly numbers").unwrap();
+        let cached_analysis = workspace.join("cache").join("analysis").join("key.json");
+        std::fs::create_dir_all(cached_analysis.parent().unwrap()).unwrap();
+        std::fs::write(&cached_analysis, "{}").unwrap();
+
+        manager.enable_encryption("hunter2").unwrap();
+        let project_file = workspace.join(&project_id).join("project.json");
+        for file in [project_file.clone(), PathBuf::from(&backup), transcript.clone(), cached_analysis] {
+            assert!(project_crypto::is_encrypted(&std::fs::read(file).unwrap()));
+        }
+        assert!(manager.enable_encryption("hunter2").is_err());
+
+        // A fresh start can't see the projects until it's unlocked
+        let mut reopened = ProjectManager::new(workspace.clone()).unwrap();
+        reopened.load_projects().unwrap();
+        assert!(reopened.list_projects().is_empty());
+        assert!(reopened.unlock_workspace("wrong").is_err());
+        reopened.unlock_workspace("hunter2").unwrap();
+        assert_eq!(reopened.get_project(&project_id).unwrap().unwrap().videos.len(), 1);
+        let versions = reopened.list_versions(&project_id).unwrap();
+        reopened.diff_versions(&project_id, &versions[0].id, None).unwrap();
+        reopened.restore_from_backup(&project_id, &backup).unwrap();
+
+        reopened.disable_encryption("hunter2").unwrap();
+        assert!(!reopened.encryption_status().enabled);
+        let content = std::fs::read_to_string(&project_file).unwrap();
+        assert!(content.contains("Board meetings"));
+    }
+
+    #[test]
+    fn test_batch_result_records_pipeline_events() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Lectures".to_string(), None, None).unwrap();
+        let sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 2);
+
+        let result = BatchResult {
+            url: sample.video_info.url.clone(),
+            video_info: Some(sample.video_info),
+            nuggets: sample.nuggets,
+            analysis: None,
+            output_files: vec!["out/lecture.csv".to_string()],
+            events: vec![
+                ProcessingEvent::new(EventType::NuggetsGenerated, "Generated 2 nuggets".to_string(), HashMap::new()),
+                crate::file_manager::FileManager::export_event("csv", "out/lecture.csv", 2),
+            ],
+            status: crate::batch_processor::ProcessingStatus::Success,
+            error_message: None,
+            processing_time_seconds: 3.0,
+        };
+        let video_id = manager.add_batch_result(&project_id, "job-1", &result).unwrap();
+
+        let history: Vec<EventType> = manager.get_video(&project_id, &video_id).unwrap()
+            .processing_history.iter()
+            .map(|event| event.event_type.clone())
+            .collect();
+        assert_eq!(history, vec![EventType::VideoAdded, EventType::NuggetsGenerated, EventType::ExportCreated, EventType::BatchProcessed]);
+        let export = &manager.get_video(&project_id, &video_id).unwrap().processing_history[2];
+        assert_eq!(export.parameters["format"], serde_json::json!("csv"));
+        assert_eq!(export.parameters["nugget_count"], serde_json::json!(2));
+    }
+
+    #[test]
+    fn test_batch_result_is_attached_and_saved() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Lectures".to_string(), None, None).unwrap();
+        let sample = video("a", "https://youtu.be/dQw4w9WgXcQ", 3);
+
+        let mut result = BatchResult {
+            url: sample.video_info.url.clone(),
+            video_info: None,
+            nuggets: sample.nuggets,
+            analysis: None,
+            output_files: vec!["out/lecture.json".to_string()],
+            events: Vec::new(),
+            status: crate::batch_processor::ProcessingStatus::Success,
+            error_message: None,
+            processing_time_seconds: 3.0,
+        };
+        assert!(manager.add_batch_result(&project_id, "job-1", &result).is_err());
+
+        result.video_info = Some(sample.video_info);
+        let video_id = manager.add_batch_result(&project_id, "job-1", &result).unwrap();
+
+        let mut reopened = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        reopened.load_projects().unwrap();
+        let video = reopened.get_video(&project_id, &video_id).unwrap();
+        assert_eq!(video.nuggets.len(), 3);
+        assert_eq!(video.output_files, vec!["out/lecture.json"]);
+        assert_eq!(reopened.list_projects()[0].metadata.total_nuggets, 3);
+    }
+}
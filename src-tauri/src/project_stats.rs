@@ -0,0 +1,189 @@
+use crate::project_manager::{Project, VideoProject, VideoStatus};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+// Upper bounds of the nugget length buckets, in seconds; the last bucket is open-ended
+const DURATION_BUCKETS: [f64; 5] = [15.0, 30.0, 60.0, 120.0, 300.0];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectStats {
+    pub total_videos: usize,
+    pub total_nuggets: usize,
+    pub total_duration_seconds: f64,
+    pub total_nugget_seconds: f64,
+    pub total_processing_seconds: f64,
+    pub total_storage_mb: f64,
+    pub videos: Vec<VideoStats>,
+    pub duration_distribution: Vec<DurationBucket>,
+    pub tag_frequencies: Vec<TagCount>,
+    pub sentiment_over_time: Vec<SentimentSample>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoStats {
+    pub video_id: String,
+    pub title: String,
+    pub status: VideoStatus,
+    pub nugget_count: usize,
+    pub duration_seconds: f64,
+    pub nugget_seconds: f64,
+    pub processing_time_seconds: f64,
+    pub storage_mb: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DurationBucket {
+    pub min_seconds: f64,
+    pub max_seconds: Option<f64>,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+// One point per analyzed video, in the order the videos were added
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SentimentSample {
+    pub video_id: String,
+    pub title: String,
+    pub added_at: String,
+    pub sentiment: f64,
+    pub engagement: f64,
+}
+
+pub fn project_stats(project: &Project) -> ProjectStats {
+    let videos: Vec<VideoStats> = project.videos.iter().map(video_stats).collect();
+
+    let mut duration_distribution: Vec<DurationBucket> = DURATION_BUCKETS.iter()
+        .enumerate()
+        .map(|(index, max)| DurationBucket {
+            min_seconds: if index == 0 { 0.0 } else { DURATION_BUCKETS[index - 1] },
+            max_seconds: Some(*max),
+            count: 0,
+        })
+        .collect();
+    duration_distribution.push(DurationBucket {
+        min_seconds: DURATION_BUCKETS[DURATION_BUCKETS.len() - 1],
+        max_seconds: None,
+        count: 0,
+    });
+
+    let mut tag_counts: HashMap<String, usize> = HashMap::new();
+    for nugget in project.videos.iter().flat_map(|v| &v.nuggets) {
+        let duration = (nugget.end_time - nugget.start_time).max(0.0);
+        let bucket = DURATION_BUCKETS.iter()
+            .position(|max| duration < *max)
+            .unwrap_or(DURATION_BUCKETS.len());
+        duration_distribution[bucket].count += 1;
+
+        for tag in &nugget.tags {
+            *tag_counts.entry(tag.to_lowercase()).or_default() += 1;
+        }
+    }
+
+    let mut tag_frequencies: Vec<TagCount> = tag_counts.into_iter()
+        .map(|(tag, count)| TagCount { tag, count })
+        .collect();
+    tag_frequencies.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+
+    let mut sentiment_over_time: Vec<SentimentSample> = project.videos.iter()
+        .filter_map(|video| video.analysis.as_ref().map(|analysis| SentimentSample {
+            video_id: video.id.clone(),
+            title: video.video_info.title.clone(),
+            added_at: video.created_at.clone(),
+            sentiment: analysis.sentiment_score,
+            engagement: analysis.engagement_score,
+        }))
+        .collect();
+    sentiment_over_time.sort_by(|a, b| a.added_at.cmp(&b.added_at));
+
+    ProjectStats {
+        total_videos: videos.len(),
+        total_nuggets: videos.iter().map(|v| v.nugget_count).sum(),
+        total_duration_seconds: videos.iter().map(|v| v.duration_seconds).sum(),
+        total_nugget_seconds: videos.iter().map(|v| v.nugget_seconds).sum(),
+        total_processing_seconds: videos.iter().map(|v| v.processing_time_seconds).sum(),
+        total_storage_mb: videos.iter().map(|v| v.storage_mb).sum(),
+        videos,
+        duration_distribution,
+        tag_frequencies,
+        sentiment_over_time,
+    }
+}
+
+fn video_stats(video: &VideoProject) -> VideoStats {
+    // Batch runs record how long each video took in the BatchProcessed event
+    let processing_time_seconds = video.processing_history.iter()
+        .filter_map(|event| event.parameters.get("processing_time_seconds"))
+        .filter_map(|value| value.as_f64())
+        .sum();
+
+    let storage_bytes: u64 = video.output_files.iter()
+        .filter_map(|file| std::fs::metadata(file).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    VideoStats {
+        video_id: video.id.clone(),
+        title: video.video_info.title.clone(),
+        status: video.status.clone(),
+        nugget_count: video.nuggets.len(),
+        duration_seconds: video.video_info.duration,
+        nugget_seconds: video.nuggets.iter().map(|n| (n.end_time - n.start_time).max(0.0)).sum(),
+        processing_time_seconds,
+        storage_mb: storage_bytes as f64 / (1024.0 * 1024.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_manager::ProjectManager;
+    use crate::{VideoInfo, VideoNugget};
+
+    fn nugget(start_time: f64, end_time: f64, tags: &[&str]) -> VideoNugget {
+        VideoNugget {
+            id: format!("{}-{}", start_time, end_time),
+            title: "Clip".to_string(),
+            start_time,
+            end_time,
+            transcript: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            notes: None,
+            youtube_video_id: None,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_project_stats() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let video_info = VideoInfo {
+            title: "Keynote".to_string(),
+            duration: 600.0,
+            url: "https://youtu.be/dQw4w9WgXcQ".to_string(),
+            thumbnail: None,
+        };
+        let nuggets = vec![
+            nugget(0.0, 10.0, &["Rust", "async"]),
+            nugget(60.0, 100.0, &["rust"]),
+            nugget(200.0, 600.0, &[]),
+        ];
+        manager.add_video_to_project(&project_id, video_info, nuggets, None).unwrap();
+
+        let stats = project_stats(manager.get_project(&project_id).unwrap().unwrap());
+        assert_eq!(stats.total_nuggets, 3);
+        assert_eq!(stats.total_nugget_seconds, 450.0);
+        assert_eq!(stats.videos[0].nugget_count, 3);
+
+        let counts: Vec<usize> = stats.duration_distribution.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![1, 0, 1, 0, 0, 1]);
+        assert_eq!((stats.tag_frequencies[0].tag.as_str(), stats.tag_frequencies[0].count), ("rust", 2));
+        assert!(stats.sentiment_over_time.is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+use crate::project_archive::walk_files;
+use crate::project_manager::Project;
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StorageCategory {
+    Clips,
+    Thumbnails,
+    Transcripts,
+    Exports,
+    Backups,
+    Versions,
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StorageItem {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub category: StorageCategory,
+    // Set for files listed in a video's output_files or thumbnail
+    pub video_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategoryUsage {
+    pub category: StorageCategory,
+    pub bytes: u64,
+    pub files: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StorageBreakdown {
+    pub total_bytes: u64,
+    pub by_category: Vec<CategoryUsage>,
+    // Biggest first
+    pub largest: Vec<StorageItem>,
+}
+
+// Everything under the project directory plus the videos' files stored elsewhere, each counted once
+pub fn storage_breakdown(project: &Project, limit: usize) -> StorageBreakdown {
+    let mut items = project_files(project);
+    let total_bytes = items.iter().map(|item| item.bytes).sum();
+
+    let mut by_category: HashMap<StorageCategory, CategoryUsage> = HashMap::new();
+    for item in &items {
+        let usage = by_category.entry(item.category).or_insert(CategoryUsage {
+            category: item.category,
+            bytes: 0,
+            files: 0,
+        });
+        usage.bytes += item.bytes;
+        usage.files += 1;
+    }
+    let mut by_category: Vec<CategoryUsage> = by_category.into_values().collect();
+    by_category.sort_by_key(|usage| Reverse(usage.bytes));
+
+    items.sort_by_key(|item| Reverse(item.bytes));
+    items.truncate(limit);
+
+    StorageBreakdown {
+        total_bytes,
+        by_category,
+        largest: items,
+    }
+}
+
+pub fn storage_used_mb(project: &Project) -> f64 {
+    let bytes: u64 = project_files(project).iter().map(|item| item.bytes).sum();
+    bytes as f64 / BYTES_PER_MB
+}
+
+fn project_files(project: &Project) -> Vec<StorageItem> {
+    let mut seen = HashSet::new();
+    let mut items = Vec::new();
+
+    // Video files first, so a clip inside the workspace is still attributed to its video
+    for video in &project.videos {
+        let files = video.output_files.iter().chain(video.video_info.thumbnail.iter());
+        for file in files {
+            if let Some(item) = storage_item(Path::new(file), project, Some(&video.id), &mut seen) {
+                items.push(item);
+            }
+        }
+    }
+
+    for path in walk_files(&project.workspace_path) {
+        if let Some(item) = storage_item(&path, project, None, &mut seen) {
+            items.push(item);
+        }
+    }
+
+    items
+}
+
+fn storage_item(path: &Path, project: &Project, video_id: Option<&str>, seen: &mut HashSet<PathBuf>) -> Option<StorageItem> {
+    // Thumbnails are usually URLs, and files may have been deleted since
+    let path = path.canonicalize().ok()?;
+    let metadata = std::fs::metadata(&path).ok().filter(|m| m.is_file())?;
+    if !seen.insert(path.clone()) {
+        return None;
+    }
+
+    Some(StorageItem {
+        category: categorize(&path, &project.workspace_path),
+        bytes: metadata.len(),
+        video_id: video_id.map(str::to_string),
+        path,
+    })
+}
+
+fn categorize(path: &Path, workspace_path: &Path) -> StorageCategory {
+    let in_dir = |name: &str| workspace_path.join(name).canonicalize()
+        .map(|dir| path.starts_with(dir))
+        .unwrap_or(false);
+    if in_dir("backups") {
+        return StorageCategory::Backups;
+    }
+    if in_dir("versions") {
+        return StorageCategory::Versions;
+    }
+    if path.parent() == workspace_path.canonicalize().ok().as_deref() && path.ends_with("project.json") {
+        return StorageCategory::Other;
+    }
+
+    let extension = path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "mp4" | "mov" | "mkv" | "webm" | "avi" | "mp3" | "m4a" | "wav" => StorageCategory::Clips,
+        "jpg" | "jpeg" | "png" | "webp" | "gif" => StorageCategory::Thumbnails,
+        "srt" | "vtt" | "txt" => StorageCategory::Transcripts,
+        "json" | "csv" | "md" | "html" | "zip" => StorageCategory::Exports,
+        _ => StorageCategory::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_manager::ProjectManager;
+    use crate::VideoInfo;
+
+    #[test]
+    fn test_storage_breakdown() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().join("workspace")).unwrap();
+        let project_id = manager.create_project("Talks".to_string(), None, None).unwrap();
+        let video_info = VideoInfo {
+            title: "Keynote".to_string(),
+            duration: 600.0,
+            url: "https://youtu.be/dQw4w9WgXcQ".to_string(),
+            thumbnail: Some("https://i.ytimg.com/vi/dQw4w9WgXcQ/default.jpg".to_string()),
+        };
+        manager.add_video_to_project(&project_id, video_info, Vec::new(), None).unwrap();
+
+        let project = manager.get_project_mut(&project_id).unwrap().unwrap();
+        // Bigger than project.json, so it comes first
+        let clip = project.workspace_path.join("clip.mp4");
+        std::fs::write(&clip, vec![0u8; 64 * 1024]).unwrap();
+        std::fs::write(project.workspace_path.join("notes.txt"), "hello").unwrap();
+        project.videos[0].output_files = vec![clip.to_string_lossy().to_string()];
+        let video_id = project.videos[0].id.clone();
+
+        let breakdown = storage_breakdown(project, 1);
+        assert_eq!(breakdown.largest.len(), 1);
+        assert_eq!(breakdown.largest[0].category, StorageCategory::Clips);
+        assert_eq!(breakdown.largest[0].video_id, Some(video_id));
+
+        // The clip is only counted once even though it is also inside the workspace
+        let clips = breakdown.by_category.iter().find(|c| c.category == StorageCategory::Clips).unwrap();
+        assert_eq!((clips.files, clips.bytes), (1, 64 * 1024));
+        assert!(breakdown.by_category.iter().any(|c| c.category == StorageCategory::Transcripts));
+        assert!(storage_used_mb(project) > 0.0);
+    }
+}
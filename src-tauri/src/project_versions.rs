@@ -0,0 +1,196 @@
+use crate::VideoNugget;
+use crate::atomic_file::write_atomic;
+use crate::project_crypto::{read_data, write_data, DataKey};
+use crate::project_manager::Project;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+// Full snapshots of a few hundred nuggets are small, keeping the last 50 is cheaper than
+// working out deltas
+const MAX_VERSIONS: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VersionInfo {
+    pub id: String,
+    pub created_at: String,
+    pub reason: String,
+    pub total_videos: usize,
+    pub total_nuggets: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NuggetChange {
+    pub video_id: String,
+    pub video_title: String,
+    pub nugget: VideoNugget,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NuggetEdit {
+    pub video_id: String,
+    pub video_title: String,
+    pub before: VideoNugget,
+    pub after: VideoNugget,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct VersionDiff {
+    pub videos_added: Vec<String>,
+    pub videos_removed: Vec<String>,
+    pub nuggets_added: Vec<NuggetChange>,
+    pub nuggets_removed: Vec<NuggetChange>,
+    pub nuggets_edited: Vec<NuggetEdit>,
+    pub settings_changed: bool,
+}
+
+// Snapshots live next to project.json, in versions/<id>.json with an index.json listing them
+pub struct VersionHistory {
+    dir: PathBuf,
+    // Snapshots are encrypted with it, the index only holds counts and reasons
+    key: Option<DataKey>,
+}
+
+impl VersionHistory {
+    pub fn new(project_dir: &Path) -> Self {
+        Self {
+            dir: project_dir.join("versions"),
+            key: None,
+        }
+    }
+
+    pub fn with_key(mut self, key: Option<DataKey>) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn record(&self, project: &Project, reason: &str) -> Result<VersionInfo, String> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create versions directory: {}", e))?;
+
+        let info = VersionInfo {
+            id: Uuid::new_v4().to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            reason: reason.to_string(),
+            total_videos: project.videos.len(),
+            total_nuggets: project.videos.iter().map(|v| v.nuggets.len()).sum(),
+        };
+
+        let json_data = serde_json::to_string(project)
+            .map_err(|e| format!("Failed to serialize project: {}", e))?;
+        write_data(&self.snapshot_path(&info.id), &json_data, self.key.as_ref())
+            .map_err(|e| format!("Failed to write project version: {}", e))?;
+
+        let mut versions = self.list()?;
+        versions.push(info.clone());
+        if versions.len() > MAX_VERSIONS {
+            for old in versions.drain(..versions.len() - MAX_VERSIONS) {
+                let _ = std::fs::remove_file(self.snapshot_path(&old.id));
+            }
+        }
+        self.write_index(&versions)?;
+
+        Ok(info)
+    }
+
+    // Oldest first
+    pub fn list(&self) -> Result<Vec<VersionInfo>, String> {
+        let index_path = self.dir.join("index.json");
+        if !index_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = std::fs::read_to_string(&index_path)
+            .map_err(|e| format!("Failed to read version history: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse version history: {}", e))
+    }
+
+    pub fn load(&self, version_id: &str) -> Result<Project, String> {
+        if !self.list()?.iter().any(|v| v.id == version_id) {
+            return Err("Version not found".to_string());
+        }
+
+        let content = read_data(&self.snapshot_path(version_id), self.key.as_ref())
+            .map_err(|e| format!("Failed to read project version: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse project version: {}", e))
+    }
+
+    fn write_index(&self, versions: &[VersionInfo]) -> Result<(), String> {
+        let json_data = serde_json::to_string_pretty(versions)
+            .map_err(|e| format!("Failed to serialize version history: {}", e))?;
+        write_atomic(&self.dir.join("index.json"), json_data.as_bytes())
+            .map_err(|e| format!("Failed to save version history: {}", e))
+    }
+
+    fn snapshot_path(&self, version_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", version_id))
+    }
+}
+
+// Nuggets are matched by video ID and nugget ID, so re-running analysis on a video shows up
+// as removed and added nuggets rather than edits
+pub fn diff_projects(from: &Project, to: &Project) -> VersionDiff {
+    let mut diff = VersionDiff {
+        settings_changed: serde_json::to_value(&from.settings).ok() != serde_json::to_value(&to.settings).ok(),
+        ..VersionDiff::default()
+    };
+
+    let old_videos: HashMap<&str, _> = from.videos.iter().map(|v| (v.id.as_str(), v)).collect();
+    let new_videos: HashMap<&str, _> = to.videos.iter().map(|v| (v.id.as_str(), v)).collect();
+
+    for video in &from.videos {
+        if !new_videos.contains_key(video.id.as_str()) {
+            diff.videos_removed.push(video.video_info.title.clone());
+            diff.nuggets_removed.extend(video.nuggets.iter().map(|nugget| NuggetChange {
+                video_id: video.id.clone(),
+                video_title: video.video_info.title.clone(),
+                nugget: nugget.clone(),
+            }));
+        }
+    }
+
+    for video in &to.videos {
+        let old_nuggets: HashMap<&str, &VideoNugget> = match old_videos.get(video.id.as_str()) {
+            Some(old) => old.nuggets.iter().map(|n| (n.id.as_str(), n)).collect(),
+            None => {
+                diff.videos_added.push(video.video_info.title.clone());
+                HashMap::new()
+            }
+        };
+        let new_ids: Vec<&str> = video.nuggets.iter().map(|n| n.id.as_str()).collect();
+
+        for nugget in &video.nuggets {
+            match old_nuggets.get(nugget.id.as_str()) {
+                None => diff.nuggets_added.push(NuggetChange {
+                    video_id: video.id.clone(),
+                    video_title: video.video_info.title.clone(),
+                    nugget: nugget.clone(),
+                }),
+                Some(old) if serde_json::to_value(old).ok() != serde_json::to_value(nugget).ok() => {
+                    diff.nuggets_edited.push(NuggetEdit {
+                        video_id: video.id.clone(),
+                        video_title: video.video_info.title.clone(),
+                        before: (*old).clone(),
+                        after: nugget.clone(),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some(old) = old_videos.get(video.id.as_str()) {
+            diff.nuggets_removed.extend(old.nuggets.iter()
+                .filter(|n| !new_ids.contains(&n.id.as_str()))
+                .map(|nugget| NuggetChange {
+                    video_id: video.id.clone(),
+                    video_title: video.video_info.title.clone(),
+                    nugget: nugget.clone(),
+                }));
+        }
+    }
+
+    diff
+}
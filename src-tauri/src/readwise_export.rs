@@ -0,0 +1,240 @@
+use crate::ai_analyzer::ContentAnalysis;
+use crate::ai_config_store::{read_secret, write_secret};
+use crate::anki_export::timestamp_link;
+use crate::project_manager::VideoProject;
+use serde::{Serialize, Deserialize};
+
+const READWISE_TOKEN_ENTRY: &str = "readwise_token";
+const HIGHLIGHTS_URL: &str = "https://readwise.io/api/v2/highlights/";
+const AUTH_URL: &str = "https://readwise.io/api/v2/auth/";
+// Readwise truncates anything longer
+const MAX_TEXT_CHARS: usize = 8191;
+const HIGHLIGHTS_PER_REQUEST: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReadwiseHighlight {
+    pub text: String,
+    pub title: String,
+    pub source_url: String,
+    pub source_type: String,
+    pub category: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    // Seconds into the video, so Readwise orders the highlights by time
+    pub location: u64,
+    pub location_type: String,
+    pub highlight_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlighted_at: Option<String>,
+}
+
+pub fn token_set() -> Result<bool, String> {
+    Ok(read_secret(READWISE_TOKEN_ENTRY)?.is_some())
+}
+
+// Checks the token with Readwise before keeping it in the OS keychain; an empty token removes it
+pub async fn save_token(token: &str) -> Result<(), String> {
+    let token = token.trim();
+    if !token.is_empty() {
+        let response = reqwest::Client::new()
+            .get(AUTH_URL)
+            .header("Authorization", format!("Token {}", token))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Readwise: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Readwise rejected the access token: {}", response.status()));
+        }
+    }
+    write_secret(READWISE_TOKEN_ENTRY, Some(token))
+}
+
+// One highlight per nugget, quoting its transcript (or its title when there is none). The title
+// and notes go in the highlight's note, along with the reason for any AI highlight moment the
+// nugget covers and its tags as Readwise's .tag syntax. The video's AI summary comes first.
+pub fn video_highlights(video: &VideoProject) -> Vec<ReadwiseHighlight> {
+    let url = &video.video_info.url;
+    let highlight = |text: &str, note: Option<String>, start_time: f64, highlighted_at: &str| ReadwiseHighlight {
+        text: truncate(text.trim()),
+        title: video.video_info.title.clone(),
+        source_url: url.clone(),
+        source_type: "video_nugget".to_string(),
+        // Readwise has no video category, podcasts are its other time-based source
+        category: "podcasts".to_string(),
+        note: note.filter(|note| !note.is_empty()),
+        location: start_time.max(0.0).floor() as u64,
+        location_type: "time_offset".to_string(),
+        highlight_url: timestamp_link(url, start_time),
+        highlighted_at: Some(highlighted_at.to_string()).filter(|date| !date.is_empty()),
+    };
+
+    let mut highlights = Vec::new();
+    if let Some(analysis) = video.analysis.as_ref().filter(|a| !a.summary.trim().is_empty()) {
+        highlights.push(highlight(&analysis.summary, Some("Summary".to_string()), 0.0, &video.created_at));
+    }
+    for nugget in &video.nuggets {
+        let transcript = nugget.transcript.as_deref().filter(|t| !t.trim().is_empty());
+        let mut note: Vec<String> = Vec::new();
+        if transcript.is_some() {
+            note.push(nugget.title.clone());
+        }
+        if let Some(notes) = nugget.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+            note.push(notes.trim().to_string());
+        }
+        note.extend(moment_reasons(video.analysis.as_ref(), nugget.start_time, nugget.end_time));
+        let tags: Vec<String> = nugget.tags.iter()
+            .map(|tag| tag.split_whitespace().collect::<Vec<_>>().join("_"))
+            .filter(|tag| !tag.is_empty())
+            .map(|tag| format!(".{}", tag))
+            .collect();
+        if !tags.is_empty() {
+            note.push(tags.join(" "));
+        }
+
+        let text = transcript.unwrap_or(&nugget.title);
+        highlights.push(highlight(text, Some(note.join("\n")), nugget.start_time, &nugget.created_at));
+    }
+    highlights
+}
+
+fn moment_reasons(analysis: Option<&ContentAnalysis>, start_time: f64, end_time: f64) -> Vec<String> {
+    analysis.map(|analysis| analysis.highlight_moments.iter()
+        .filter(|moment| moment.start_time < end_time && moment.end_time > start_time)
+        .map(|moment| format!("{:?}: {}", moment.moment_type, moment.reason.trim()))
+        .collect())
+        .unwrap_or_default()
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_TEXT_CHARS {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(MAX_TEXT_CHARS - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+// Readwise merges highlights it already has, so sending a video again doesn't duplicate them.
+// Returns how many highlights were sent
+pub async fn send_highlights(highlights: &[ReadwiseHighlight]) -> Result<usize, String> {
+    let token = read_secret(READWISE_TOKEN_ENTRY)?
+        .ok_or("No Readwise access token set")?;
+    let client = reqwest::Client::new();
+
+    for batch in highlights.chunks(HIGHLIGHTS_PER_REQUEST) {
+        let response = client.post(HIGHLIGHTS_URL)
+            .header("Authorization", format!("Token {}", token))
+            .json(&serde_json::json!({ "highlights": batch }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Readwise: {}", e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Readwise rejected the highlights ({}): {}", status, body.trim()));
+        }
+    }
+    Ok(highlights.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_analyzer::{HighlightMoment, MomentType};
+    use crate::project_manager::VideoStatus;
+    use crate::{VideoInfo, VideoNugget};
+
+    fn video() -> VideoProject {
+        VideoProject {
+            id: "v1".to_string(),
+            video_info: VideoInfo {
+                title: "Cells".to_string(),
+                duration: 600.0,
+                url: "https://www.youtube.com/watch?v=abc123".to_string(),
+                thumbnail: None,
+            },
+            nuggets: vec![
+                VideoNugget {
+                    id: "n1".to_string(),
+                    title: "Mitochondria".to_string(),
+                    start_time: 65.5,
+                    end_time: 90.0,
+                    transcript: Some(" The mitochondria makes ATP. ".to_string()),
+                    tags: vec!["cell biology".to_string()],
+                    notes: Some("Exam topic".to_string()),
+                    youtube_video_id: None,
+                    created_at: "2024-05-01T10:00:00Z".to_string(),
+                },
+                VideoNugget {
+                    id: "n2".to_string(),
+                    title: "Ribosomes".to_string(),
+                    start_time: 120.0,
+                    end_time: 150.0,
+                    transcript: None,
+                    tags: Vec::new(),
+                    notes: None,
+                    youtube_video_id: None,
+                    created_at: String::new(),
+                },
+            ],
+            analysis: Some(ContentAnalysis {
+                summary: "How cells make energy".to_string(),
+                key_topics: Vec::new(),
+                sentiment_score: 0.0,
+                engagement_score: 0.0,
+                suggested_tags: Vec::new(),
+                highlight_moments: vec![HighlightMoment {
+                    start_time: 80.0,
+                    end_time: 85.0,
+                    reason: "Core definition".to_string(),
+                    confidence: 0.9,
+                    moment_type: MomentType::KeyPoint,
+                }],
+                content_categories: Vec::new(),
+                difficulty_level: "Beginner".to_string(),
+                sentiment_timeline: Vec::new(),
+                warnings: Vec::new(),
+                audience_insights: None,
+            }),
+            seo: None,
+            output_files: Vec::new(),
+            processing_history: Vec::new(),
+            custom_tags: Vec::new(),
+            notes: String::new(),
+            status: VideoStatus::Completed,
+            created_at: "2024-05-01T09:00:00Z".to_string(),
+            updated_at: "2024-05-01T09:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_video_highlights() {
+        let highlights = video_highlights(&video());
+        assert_eq!(highlights.len(), 3);
+        assert_eq!(highlights[0].text, "How cells make energy");
+        assert_eq!(highlights[0].note.as_deref(), Some("Summary"));
+
+        let mitochondria = &highlights[1];
+        assert_eq!(mitochondria.text, "The mitochondria makes ATP.");
+        assert_eq!(mitochondria.note.as_deref(), Some("Mitochondria\nExam topic\nKeyPoint: Core definition\n.cell_biology"));
+        assert_eq!(mitochondria.location, 65);
+        assert_eq!(mitochondria.highlight_url, "https://www.youtube.com/watch?v=abc123&t=65s");
+
+        // Without a transcript the title is the highlight and there's nothing left for the note
+        assert_eq!(highlights[2].text, "Ribosomes");
+        assert_eq!(highlights[2].note, None);
+        assert_eq!(highlights[2].highlighted_at, None);
+
+        let json = serde_json::to_value(&highlights[2]).unwrap();
+        assert!(json.get("note").is_none());
+        assert_eq!(json["location_type"], "time_offset");
+    }
+
+    #[test]
+    fn test_truncate() {
+        let long = "a".repeat(MAX_TEXT_CHARS + 10);
+        assert_eq!(truncate(&long).chars().count(), MAX_TEXT_CHARS);
+        assert_eq!(truncate("short"), "short");
+    }
+}
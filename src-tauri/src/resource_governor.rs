@@ -0,0 +1,187 @@
+use serde::{Serialize, Deserialize};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use sysinfo::System;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+const HEADROOM_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResourcePolicy {
+    pub max_ffmpeg_processes: usize,
+    pub max_transcriptions: usize,
+    // New work waits while the machine is above either threshold, unless nothing of ours is running
+    pub max_cpu_percent: f32,
+    pub min_available_memory_mb: u64,
+}
+
+impl Default for ResourcePolicy {
+    fn default() -> Self {
+        let cpus = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(2);
+
+        Self {
+            max_ffmpeg_processes: (cpus / 2).max(1),
+            // Whisper already uses every core on its own
+            max_transcriptions: 1,
+            max_cpu_percent: 85.0,
+            min_available_memory_mb: 1024,
+        }
+    }
+}
+
+impl ResourcePolicy {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read resource policy: {}", e))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse resource policy: {}", e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json_data = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize resource policy: {}", e))?;
+
+        std::fs::write(path, json_data)
+            .map_err(|e| format!("Failed to save resource policy: {}", e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_ffmpeg_processes == 0 || self.max_transcriptions == 0 {
+            return Err("Process limits must be at least 1".to_string());
+        }
+        if !(1.0..=100.0).contains(&self.max_cpu_percent) {
+            return Err("CPU threshold must be between 1 and 100 percent".to_string());
+        }
+        Ok(())
+    }
+
+    fn has_headroom(&self, cpu_percent: f32, available_memory_mb: u64) -> bool {
+        cpu_percent <= self.max_cpu_percent && available_memory_mb >= self.min_available_memory_mb
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ResourceKind {
+    Ffmpeg,
+    Transcription,
+}
+
+pub struct ResourcePermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+// Shared by every batch job, so two jobs together can't start more FFmpeg or Whisper
+// processes than the policy allows
+pub struct ResourceGovernor {
+    policy: ResourcePolicy,
+    ffmpeg: Arc<Semaphore>,
+    transcription: Arc<Semaphore>,
+    system: Mutex<System>,
+}
+
+impl ResourceGovernor {
+    pub fn new(policy: ResourcePolicy) -> Self {
+        Self {
+            ffmpeg: Arc::new(Semaphore::new(policy.max_ffmpeg_processes.max(1))),
+            transcription: Arc::new(Semaphore::new(policy.max_transcriptions.max(1))),
+            policy,
+            system: Mutex::new(System::new()),
+        }
+    }
+
+    pub fn policy(&self) -> &ResourcePolicy {
+        &self.policy
+    }
+
+    // None when the token is cancelled while waiting
+    pub async fn acquire(&self, kind: ResourceKind, cancel_token: &CancellationToken) -> Option<ResourcePermit> {
+        let semaphore = match kind {
+            ResourceKind::Ffmpeg => self.ffmpeg.clone(),
+            ResourceKind::Transcription => self.transcription.clone(),
+        };
+
+        let permit = tokio::select! {
+            _ = cancel_token.cancelled() => return None,
+            permit = semaphore.acquire_owned() => permit.ok()?,
+        };
+
+        // The thresholds only hold back extra concurrency; with nothing else of ours running a
+        // busy machine would otherwise stall the batch forever
+        while self.others_running() && !self.system_has_headroom() {
+            tokio::select! {
+                _ = cancel_token.cancelled() => return None,
+                _ = tokio::time::sleep(HEADROOM_POLL_INTERVAL) => {}
+            }
+        }
+
+        Some(ResourcePermit { _permit: permit })
+    }
+
+    fn others_running(&self) -> bool {
+        let in_use = (self.policy.max_ffmpeg_processes.max(1) - self.ffmpeg.available_permits())
+            + (self.policy.max_transcriptions.max(1) - self.transcription.available_permits());
+        in_use > 1
+    }
+
+    fn system_has_headroom(&self) -> bool {
+        let mut system = self.system.lock().unwrap();
+        system.refresh_cpu_usage();
+        system.refresh_memory();
+
+        self.policy.has_headroom(system.global_cpu_usage(), system.available_memory() / (1024 * 1024))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_thresholds() {
+        let policy = ResourcePolicy {
+            max_ffmpeg_processes: 2,
+            max_transcriptions: 1,
+            max_cpu_percent: 80.0,
+            min_available_memory_mb: 2048,
+        };
+
+        assert!(policy.has_headroom(50.0, 4096));
+        assert!(!policy.has_headroom(95.0, 4096));
+        assert!(!policy.has_headroom(50.0, 512));
+        assert!(policy.validate().is_ok());
+        assert!(ResourcePolicy { max_transcriptions: 0, ..policy }.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_respects_process_limit() {
+        let governor = ResourceGovernor::new(ResourcePolicy {
+            max_ffmpeg_processes: 1,
+            max_transcriptions: 1,
+            // Never throttles on load, only the process limit applies
+            max_cpu_percent: 100.0,
+            min_available_memory_mb: 0,
+        });
+        let token = CancellationToken::new();
+
+        let first = governor.acquire(ResourceKind::Ffmpeg, &token).await;
+        assert!(first.is_some());
+
+        let second = tokio::time::timeout(Duration::from_millis(20), governor.acquire(ResourceKind::Ffmpeg, &token)).await;
+        assert!(second.is_err());
+
+        let transcription = tokio::time::timeout(Duration::from_millis(20), governor.acquire(ResourceKind::Transcription, &token)).await;
+        assert!(matches!(transcription, Ok(Some(_))));
+
+        token.cancel();
+        assert!(governor.acquire(ResourceKind::Ffmpeg, &token).await.is_none());
+    }
+}
@@ -1,7 +1,13 @@
 use serde::{Serialize, Deserialize};
-use std::process::Command;
 use tempfile::TempDir;
+use tokio::process::Command;
 use std::path::Path;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use crate::ffmpeg_processor::run_cancellable;
+use crate::nugget_import::parse_cues;
+use crate::app_settings;
+use crate::error::{AppError, ErrorCode};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TranscriptSegment {
@@ -21,30 +27,108 @@ pub struct SpeechAnalysis {
     pub average_confidence: f64,
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum SpeechError {
+    #[error("Cancelled")]
+    Cancelled,
+    // Whisper or FFmpeg ran and exited with an error, the message carries its output
+    #[error("{0}")]
+    Failed(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<String> for SpeechError {
+    fn from(message: String) -> Self {
+        SpeechError::Other(message)
+    }
+}
+
+impl From<SpeechError> for String {
+    fn from(error: SpeechError) -> Self {
+        error.to_string()
+    }
+}
+
+impl From<SpeechError> for AppError {
+    fn from(error: SpeechError) -> Self {
+        match error {
+            SpeechError::Cancelled => AppError::cancelled(),
+            SpeechError::Failed(message) => AppError::new(ErrorCode::ProcessFailed, message),
+            SpeechError::Other(message) => AppError::new(ErrorCode::Unknown, message),
+        }
+    }
+}
+
+// run_cancellable reports a cancel as Interrupted
+fn command_error(context: &str, error: std::io::Error) -> SpeechError {
+    match error.kind() {
+        std::io::ErrorKind::Interrupted => SpeechError::Cancelled,
+        _ => SpeechError::Other(format!("{}: {}", context, error)),
+    }
+}
+
+#[derive(Clone)]
 pub struct SpeechRecognizer {
-    temp_dir: TempDir,
+    temp_dir: Arc<TempDir>,
     whisper_path: Option<String>,
+    cancel_token: Option<CancellationToken>,
 }
 
 impl SpeechRecognizer {
-    pub fn new() -> Result<Self, String> {
+    pub fn new() -> Result<Self, SpeechError> {
         let temp_dir = TempDir::new()
             .map_err(|e| format!("Failed to create temp directory: {}", e))?;
         
         let whisper_path = Self::find_whisper();
         
         Ok(Self {
-            temp_dir,
+            temp_dir: Arc::new(temp_dir),
             whisper_path,
+            cancel_token: None,
         })
     }
 
+    // Like FFmpegProcessor::scratch, Whisper's output and audio segments go to a directory
+    // only this copy uses
+    pub fn scratch(&self) -> Result<Self, SpeechError> {
+        let temp_dir = TempDir::new()
+            .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+        Ok(Self {
+            temp_dir: Arc::new(temp_dir),
+            ..self.clone()
+        })
+    }
+
+    // Where this copy writes its files
+    pub fn work_dir(&self) -> &Path {
+        self.temp_dir.path()
+    }
+
+    // None when transcription falls back to the cloud API
+    pub fn whisper_path(&self) -> Option<&str> {
+        self.whisper_path.as_deref()
+    }
+
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    async fn run(&self, command: &mut Command) -> std::io::Result<std::process::Output> {
+        run_cancellable(command, self.cancel_token.as_ref()).await
+    }
+
     fn find_whisper() -> Option<String> {
+        if let Some(path) = app_settings::current().tools.whisper {
+            return Some(path.to_string_lossy().to_string());
+        }
+
         // Check if Whisper is installed
         let whisper_commands = vec!["whisper", "openai-whisper", "whisper-cpp"];
         
         for cmd in whisper_commands {
-            if Command::new(cmd).arg("--version").output().is_ok() {
+            if std::process::Command::new(cmd).arg("--version").output().is_ok() {
                 return Some(cmd.to_string());
             }
         }
@@ -66,21 +150,21 @@ impl SpeechRecognizer {
         None
     }
 
-    pub async fn transcribe_audio(&self, audio_path: &str) -> Result<SpeechAnalysis, String> {
+    pub async fn transcribe_audio(&self, audio_path: &str) -> Result<SpeechAnalysis, SpeechError> {
         if let Some(ref whisper_path) = self.whisper_path {
             self.transcribe_with_whisper(audio_path, whisper_path).await
         } else {
             // Fallback to cloud-based speech recognition
-            self.transcribe_with_cloud_api(audio_path).await
+            self.transcribe_with_cloud_api().await
         }
     }
 
-    async fn transcribe_with_whisper(&self, audio_path: &str, whisper_path: &str) -> Result<SpeechAnalysis, String> {
+    async fn transcribe_with_whisper(&self, audio_path: &str, whisper_path: &str) -> Result<SpeechAnalysis, SpeechError> {
         let output_dir = self.temp_dir.path();
         let output_format = "json";
         
-        let output = Command::new(whisper_path)
-            .args(&[
+        let output = self.run(Command::new(whisper_path)
+            .args([
                 audio_path,
                 "--output_dir", &output_dir.to_string_lossy(),
                 "--output_format", output_format,
@@ -88,18 +172,17 @@ impl SpeechRecognizer {
                 "--language", "auto", // Auto-detect language
                 "--task", "transcribe",
                 "--word_timestamps", "True", // Get word-level timestamps
-            ])
-            .output()
-            .map_err(|e| format!("Failed to execute whisper: {}", e))?;
+            ])).await
+            .map_err(|e| command_error("Failed to execute whisper", e))?;
 
         if !output.status.success() {
-            return Err(format!("Whisper transcription failed: {}", 
-                String::from_utf8_lossy(&output.stderr)));
+            return Err(SpeechError::Failed(format!("Whisper transcription failed: {}", 
+                String::from_utf8_lossy(&output.stderr))));
         }
 
         // Parse Whisper JSON output
         let json_path = output_dir.join(
-            Path::new(audio_path).file_stem().unwrap().to_string_lossy() + ".json"
+            format!("{}.json", Path::new(audio_path).file_stem().unwrap().to_string_lossy())
         );
 
         let json_content = tokio::fs::read_to_string(&json_path).await
@@ -111,7 +194,7 @@ impl SpeechRecognizer {
         Ok(self.convert_whisper_result(whisper_result))
     }
 
-    async fn transcribe_with_cloud_api(&self, audio_path: &str) -> Result<SpeechAnalysis, String> {
+    async fn transcribe_with_cloud_api(&self) -> Result<SpeechAnalysis, SpeechError> {
         // Placeholder for cloud API integration (Google Speech-to-Text, Azure, etc.)
         // For now, return a mock result
         Ok(SpeechAnalysis {
@@ -150,22 +233,24 @@ impl SpeechRecognizer {
             });
         }
 
-        let average_confidence = if segments.len() > 0 {
+        let average_confidence = if !segments.is_empty() {
             total_confidence / segments.len() as f64
         } else {
             0.0
         };
 
+        let total_speech_time = segments.last().map(|s| s.end_time).unwrap_or(0.0);
+
         SpeechAnalysis {
             segments,
             language: whisper_result.language,
-            total_speech_time: segments.last().map(|s| s.end_time).unwrap_or(0.0),
+            total_speech_time,
             word_count,
             average_confidence,
         }
     }
 
-    pub async fn transcribe_segment(&self, audio_path: &str, start_time: f64, end_time: f64) -> Result<String, String> {
+    pub async fn transcribe_segment(&self, audio_path: &str, start_time: f64, end_time: f64) -> Result<String, SpeechError> {
         // Extract specific audio segment first
         let segment_path = self.extract_audio_segment(audio_path, start_time, end_time).await?;
         
@@ -182,13 +267,13 @@ impl SpeechRecognizer {
         Ok(transcript)
     }
 
-    async fn extract_audio_segment(&self, audio_path: &str, start_time: f64, end_time: f64) -> Result<String, String> {
+    async fn extract_audio_segment(&self, audio_path: &str, start_time: f64, end_time: f64) -> Result<String, SpeechError> {
         let output_path = self.temp_dir.path().join("segment.wav");
         let duration = end_time - start_time;
 
         // Use FFmpeg to extract segment
-        let output = Command::new("ffmpeg")
-            .args(&[
+        let output = self.run(Command::new(app_settings::ffmpeg())
+            .args([
                 "-i", audio_path,
                 "-ss", &start_time.to_string(),
                 "-t", &duration.to_string(),
@@ -196,28 +281,26 @@ impl SpeechRecognizer {
                 "-ar", "16000", // 16kHz for better speech recognition
                 "-ac", "1", // Mono
                 &output_path.to_string_lossy(),
-            ])
-            .output()
-            .map_err(|e| format!("Failed to extract audio segment: {}", e))?;
+            ])).await
+            .map_err(|e| command_error("Failed to extract audio segment", e))?;
 
         if output.status.success() {
             Ok(output_path.to_string_lossy().to_string())
         } else {
-            Err(format!("FFmpeg segment extraction failed: {}", 
-                String::from_utf8_lossy(&output.stderr)))
+            Err(SpeechError::Failed(format!("FFmpeg segment extraction failed: {}", 
+                String::from_utf8_lossy(&output.stderr))))
         }
     }
 
-    pub async fn detect_language(&self, audio_path: &str) -> Result<String, String> {
+    pub async fn detect_language(&self, audio_path: &str) -> Result<String, SpeechError> {
         if let Some(ref whisper_path) = self.whisper_path {
-            let output = Command::new(whisper_path)
-                .args(&[
+            let output = self.run(Command::new(whisper_path)
+                .args([
                     audio_path,
                     "--task", "detect_language",
                     "--output_format", "txt",
-                ])
-                .output()
-                .map_err(|e| format!("Failed to detect language: {}", e))?;
+                ])).await
+                .map_err(|e| command_error("Failed to detect language", e))?;
 
             if output.status.success() {
                 let result = String::from_utf8_lossy(&output.stdout);
@@ -230,7 +313,7 @@ impl SpeechRecognizer {
         }
     }
 
-    pub async fn generate_subtitles(&self, analysis: &SpeechAnalysis, format: SubtitleFormat) -> Result<String, String> {
+    pub async fn generate_subtitles(&self, analysis: &SpeechAnalysis, format: SubtitleFormat) -> Result<String, SpeechError> {
         match format {
             SubtitleFormat::SRT => self.generate_srt(analysis),
             SubtitleFormat::VTT => self.generate_vtt(analysis),
@@ -238,7 +321,7 @@ impl SpeechRecognizer {
         }
     }
 
-    fn generate_srt(&self, analysis: &SpeechAnalysis) -> Result<String, String> {
+    fn generate_srt(&self, analysis: &SpeechAnalysis) -> Result<String, SpeechError> {
         let mut srt_content = String::new();
         
         for (index, segment) in analysis.segments.iter().enumerate() {
@@ -257,7 +340,7 @@ impl SpeechRecognizer {
         Ok(srt_content)
     }
 
-    fn generate_vtt(&self, analysis: &SpeechAnalysis) -> Result<String, String> {
+    fn generate_vtt(&self, analysis: &SpeechAnalysis) -> Result<String, SpeechError> {
         let mut vtt_content = String::from("WEBVTT\n\n");
         
         for segment in &analysis.segments {
@@ -275,7 +358,7 @@ impl SpeechRecognizer {
         Ok(vtt_content)
     }
 
-    fn generate_ass(&self, analysis: &SpeechAnalysis) -> Result<String, String> {
+    fn generate_ass(&self, analysis: &SpeechAnalysis) -> Result<String, SpeechError> {
         let mut ass_content = String::from(
             "[Script Info]\nTitle: Generated Subtitles\n\n[V4+ Styles]\nFormat: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\nStyle: Default,Arial,20,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,0,0,0,0,100,100,0,0,1,2,0,2,10,10,10,1\n\n[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n"
         );
@@ -295,7 +378,7 @@ impl SpeechRecognizer {
         Ok(ass_content)
     }
 
-    fn format_timestamp(seconds: f64, with_comma: bool) -> String {
+    pub fn format_timestamp(seconds: f64, with_comma: bool) -> String {
         let hours = (seconds / 3600.0) as u32;
         let minutes = ((seconds % 3600.0) / 60.0) as u32;
         let secs = (seconds % 60.0) as u32;
@@ -344,4 +427,59 @@ pub enum SubtitleFormat {
     SRT,
     VTT,
     ASS,
-}
\ No newline at end of file
+}
+
+// A caption file read as a transcript, in place of Whisper. YouTube's auto-generated captions
+// roll, each cue repeating the line shown before it, so lines the previous cue already had are
+// dropped. Captions carry no confidence; they count as certain
+pub fn captions_analysis(content: &str, language: &str) -> SpeechAnalysis {
+    let mut segments: Vec<TranscriptSegment> = Vec::new();
+    let mut previous_lines: Vec<String> = Vec::new();
+    for cue in parse_cues(content) {
+        let text = cue.lines.iter()
+            .filter(|line| !previous_lines.contains(line))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ");
+        previous_lines = cue.lines;
+        if text.is_empty() {
+            continue;
+        }
+        segments.push(TranscriptSegment {
+            start_time: cue.start_time,
+            end_time: cue.end_time,
+            text,
+            confidence: 1.0,
+            speaker_id: None,
+        });
+    }
+
+    let word_count = segments.iter().map(|segment| segment.text.split_whitespace().count()).sum();
+    SpeechAnalysis {
+        language: language.to_string(),
+        total_speech_time: segments.last().map(|s| s.end_time).unwrap_or(0.0),
+        word_count,
+        average_confidence: if segments.is_empty() { 0.0 } else { 1.0 },
+        segments,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_captions_analysis_drops_rolled_lines() {
+        let vtt = "WEBVTT\nKind: captions\nLanguage: en\n\n\
+            00:00:00.000 --> 00:00:02.000 align:start position:0%\nwelcome<00:00:00.500><c> back</c>\n\n\
+            00:00:02.000 --> 00:00:04.000 align:start position:0%\nwelcome back\ntoday we look at ownership\n\n\
+            00:00:04.000 --> 00:00:04.010\ntoday we look at ownership\n";
+        let analysis = captions_analysis(vtt, "en");
+        assert_eq!(analysis.segments.len(), 2);
+        assert_eq!(analysis.segments[0].text, "welcome back");
+        assert_eq!(analysis.segments[1].text, "today we look at ownership");
+        assert_eq!(analysis.word_count, 7);
+        assert_eq!(analysis.total_speech_time, 4.0);
+        assert_eq!(analysis.language, "en");
+    }
+}
@@ -0,0 +1,251 @@
+use crate::anki_export::{format_time, timestamp_link};
+use crate::atomic_file::{copy_atomic, write_atomic};
+use crate::obsidian_export::{frontmatter, yaml_list, yaml_string};
+use crate::pdf_export::nugget_thumbnails;
+use crate::project_manager::Project;
+use crate::VideoNugget;
+use std::collections::HashSet;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SiteGenerator {
+    Hugo,
+    Jekyll,
+}
+
+impl SiteGenerator {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "hugo" => Ok(SiteGenerator::Hugo),
+            "jekyll" => Ok(SiteGenerator::Jekyll),
+            _ => Err(format!("Unsupported site generator: {}", name)),
+        }
+    }
+
+    // Hugo reads a section's list page from _index.md, Jekyll renders index.md like any page
+    fn index_file(self) -> &'static str {
+        match self {
+            SiteGenerator::Hugo => "_index.md",
+            SiteGenerator::Jekyll => "index.md",
+        }
+    }
+}
+
+struct NuggetPage<'a> {
+    slug: String,
+    video_index: usize,
+    nugget: &'a VideoNugget,
+    thumbnail: bool,
+}
+
+// Meant to be dropped into a site's content folder (content/<section> for Hugo, any folder for
+// Jekyll). Every nugget gets <slug>/index.md with its thumbnail next to it, which both generators
+// publish at <slug>/, so the index and the pages link to each other with relative paths.
+// Tags go in the frontmatter, where Hugo's taxonomies and Jekyll's site.tags pick them up.
+// Returns how many nugget pages were written.
+pub fn write_site(project: &Project, directory: &Path, generator: SiteGenerator) -> Result<usize, String> {
+    let mut used_slugs = HashSet::new();
+    let mut pages = Vec::new();
+    for (video_index, video) in project.videos.iter().enumerate() {
+        let thumbnails = nugget_thumbnails(&video.output_files, video.nuggets.len());
+        for (nugget, thumbnail) in video.nuggets.iter().zip(thumbnails) {
+            let slug = unique_slug(&format!("{} {}", video.video_info.title, nugget.title), &mut used_slugs);
+            let page_dir = directory.join(&slug);
+            std::fs::create_dir_all(&page_dir)
+                .map_err(|e| format!("Failed to create {}: {}", page_dir.display(), e))?;
+            if let Some(thumbnail) = &thumbnail {
+                copy_atomic(thumbnail, &page_dir.join(THUMBNAIL_FILE))
+                    .map_err(|e| format!("Failed to copy {}: {}", thumbnail.display(), e))?;
+            }
+            pages.push(NuggetPage { slug, video_index, nugget, thumbnail: thumbnail.is_some() });
+        }
+    }
+
+    for page in &pages {
+        let markdown = nugget_markdown(project, page, generator);
+        write_page(&directory.join(&page.slug).join("index.md"), &markdown)?;
+    }
+    write_page(&directory.join(generator.index_file()), &index_markdown(project, &pages, generator))?;
+
+    Ok(pages.len())
+}
+
+const THUMBNAIL_FILE: &str = "thumbnail.jpg";
+
+fn index_markdown(project: &Project, pages: &[NuggetPage], generator: SiteGenerator) -> String {
+    let mut fields = vec![
+        ("title", yaml_string(&project.name)),
+        ("date", yaml_string(&project.created_at)),
+        ("tags", yaml_list(&project.tags)),
+    ];
+    if let Some(description) = project.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        fields.push(("description", yaml_string(description.trim())));
+    }
+    fields.extend(generator_fields(generator));
+
+    let mut markdown = frontmatter(&fields);
+    if let Some(description) = project.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        markdown.push_str(&format!("{}\n\n", description.trim_end()));
+    }
+    for (video_index, video) in project.videos.iter().enumerate() {
+        markdown.push_str(&format!("## {}\n\n", video.video_info.title));
+        for page in pages.iter().filter(|page| page.video_index == video_index) {
+            markdown.push_str(&format!(
+                "- [{}]({}/) ({})\n",
+                link_text(&page.nugget.title),
+                page.slug,
+                format_time(page.nugget.start_time),
+            ));
+        }
+        markdown.push('\n');
+    }
+    markdown
+}
+
+fn nugget_markdown(project: &Project, page: &NuggetPage, generator: SiteGenerator) -> String {
+    let video = &project.videos[page.video_index];
+    let nugget = page.nugget;
+    let link = timestamp_link(&video.video_info.url, nugget.start_time);
+
+    let mut fields = vec![
+        ("title", yaml_string(&nugget.title)),
+        ("date", yaml_string(&nugget.created_at)),
+        ("tags", yaml_list(&nugget.tags)),
+        ("video_title", yaml_string(&video.video_info.title)),
+        ("video_url", yaml_string(&video.video_info.url)),
+        ("timestamp_url", yaml_string(&link)),
+        ("start", format!("{}", nugget.start_time)),
+        ("end", format!("{}", nugget.end_time)),
+        ("duration", format!("{}", nugget.end_time - nugget.start_time)),
+        ("project", yaml_string(&project.name)),
+    ];
+    if page.thumbnail {
+        fields.push(("thumbnail", yaml_string(THUMBNAIL_FILE)));
+    }
+    fields.extend(generator_fields(generator));
+
+    let mut markdown = frontmatter(&fields);
+    if page.thumbnail {
+        markdown.push_str(&format!("![{}]({})\n\n", link_text(&nugget.title), THUMBNAIL_FILE));
+    }
+    markdown.push_str(&format!(
+        "From [{}]({}) at [{} - {}]({})\n\n",
+        link_text(&video.video_info.title),
+        video.video_info.url,
+        format_time(nugget.start_time),
+        format_time(nugget.end_time),
+        link,
+    ));
+    if let Some(transcript) = nugget.transcript.as_deref().filter(|t| !t.trim().is_empty()) {
+        markdown.push_str(&format!("## Transcript\n\n{}\n\n", transcript.trim_end()));
+    }
+    if let Some(notes) = nugget.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+        markdown.push_str(&format!("## Notes\n\n{}\n\n", notes.trim_end()));
+    }
+    markdown
+}
+
+// Transcripts are free text, so Jekyll mustn't read {{ or {% in them as Liquid
+fn generator_fields(generator: SiteGenerator) -> Vec<(&'static str, String)> {
+    match generator {
+        SiteGenerator::Hugo => Vec::new(),
+        SiteGenerator::Jekyll => vec![("render_with_liquid", "false".to_string())],
+    }
+}
+
+// Lowercase ASCII words joined with dashes, so the URLs come out the same on every host
+fn unique_slug(title: &str, used: &mut HashSet<String>) -> String {
+    let words: Vec<String> = title.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let base = if words.is_empty() { "nugget".to_string() } else { words.join("-") };
+
+    let mut slug = base.clone();
+    let mut suffix = 2;
+    while !used.insert(slug.clone()) {
+        slug = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    slug
+}
+
+fn link_text(text: &str) -> String {
+    text.replace(['[', ']'], "")
+}
+
+fn write_page(path: &Path, markdown: &str) -> Result<(), String> {
+    write_atomic(path, markdown.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_manager::ProjectManager;
+    use crate::VideoInfo;
+
+    fn nugget(title: &str, start_time: f64) -> VideoNugget {
+        VideoNugget {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: title.to_string(),
+            start_time,
+            end_time: start_time + 30.0,
+            transcript: Some("Use {{ braces }} freely".to_string()),
+            tags: vec!["rust".to_string()],
+            notes: None,
+            youtube_video_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn test_unique_slug() {
+        let mut used = HashSet::new();
+        assert_eq!(unique_slug("Ownership: Part #1", &mut used), "ownership-part-1");
+        assert_eq!(unique_slug("ownership part 1", &mut used), "ownership-part-1-2");
+        assert_eq!(unique_slug("日本語", &mut used), "nugget");
+    }
+
+    #[test]
+    fn test_write_site() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut manager = ProjectManager::new(temp_dir.path().join("workspace")).unwrap();
+        let project_id = manager.create_project("Rust talks".to_string(), None, None).unwrap();
+        let video_info = VideoInfo {
+            title: "Borrowing".to_string(),
+            duration: 600.0,
+            url: "https://www.youtube.com/watch?v=abc123".to_string(),
+            thumbnail: None,
+        };
+        manager.add_video_to_project(&project_id, video_info, vec![nugget("Moves", 65.0), nugget("Lifetimes", 120.0)], None).unwrap();
+        let mut project = manager.get_project(&project_id).unwrap().unwrap().clone();
+
+        let thumbs: Vec<String> = (1..=2)
+            .map(|index| {
+                let thumb = temp_dir.path().join(format!("nugget_{:03}_thumb.jpg", index));
+                std::fs::write(&thumb, b"jpg").unwrap();
+                thumb.to_string_lossy().to_string()
+            })
+            .collect();
+        project.videos[0].output_files = thumbs;
+
+        let hugo = temp_dir.path().join("hugo");
+        assert_eq!(write_site(&project, &hugo, SiteGenerator::Hugo).unwrap(), 2);
+        let index = std::fs::read_to_string(hugo.join("_index.md")).unwrap();
+        assert!(index.contains("## Borrowing\n\n- [Moves](borrowing-moves/) (1:05)\n"));
+        let page = std::fs::read_to_string(hugo.join("borrowing-moves").join("index.md")).unwrap();
+        assert!(page.starts_with("---\ntitle: \"Moves\"\n"));
+        assert!(page.contains("timestamp_url: \"https://www.youtube.com/watch?v=abc123&t=65s\"\n"));
+        assert!(page.contains("thumbnail: \"thumbnail.jpg\"\n"));
+        assert!(!page.contains("render_with_liquid"));
+        assert_eq!(std::fs::read(hugo.join("borrowing-lifetimes").join(THUMBNAIL_FILE)).unwrap(), b"jpg");
+
+        let jekyll = temp_dir.path().join("jekyll");
+        write_site(&project, &jekyll, SiteGenerator::Jekyll).unwrap();
+        assert!(jekyll.join("index.md").exists());
+        assert!(!jekyll.join("_index.md").exists());
+        let page = std::fs::read_to_string(jekyll.join("borrowing-moves").join("index.md")).unwrap();
+        assert!(page.contains("render_with_liquid: false\n"));
+    }
+}
@@ -0,0 +1,452 @@
+use crate::ai_config_store::{read_secret, write_secret};
+use crate::atomic_file::write_atomic;
+use crate::oauth_loopback::{code_verifier, LoopbackRedirect};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use url::Url;
+
+const CLIENT_ENTRY: &str = "tiktok_client";
+const AUTH_URL: &str = "https://www.tiktok.com/v2/auth/authorize/";
+const API_URL: &str = "https://open.tiktokapis.com/v2";
+const SCOPES: &str = "user.info.basic,video.upload,video.publish";
+// The redirect URI registered with the TikTok app has to match this path exactly
+const REDIRECT_PATH: &str = "/callback/";
+const MAX_CAPTION_CHARS: usize = 2200;
+// TikTok takes a clip in one piece up to 64 MB. Bigger ones go in chunks, the last of which holds
+// the remainder
+const MAX_SINGLE_CHUNK_BYTES: u64 = 64 * 1024 * 1024;
+const CHUNK_BYTES: u64 = 10 * 1024 * 1024;
+// Until TikTok has audited the app, everything it posts has to be private
+const DEFAULT_PRIVACY_LEVEL: &str = "SELF_ONLY";
+
+// The app's credentials from the TikTok developer portal. Login Kit for desktop wants the
+// redirect on a fixed loopback port, http://127.0.0.1:<redirect_port>/callback/
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TikTokClient {
+    pub client_key: String,
+    pub client_secret: String,
+    pub redirect_port: u16,
+}
+
+// A connected account. Its tokens are in the keychain, only this is kept in the workspace
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TikTokAccount {
+    pub open_id: String,
+    pub display_name: Option<String>,
+    pub added_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct AccountTokens {
+    access_token: String,
+    expires_at: String,
+    refresh_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PostMode {
+    // Posted straight to the profile
+    Direct,
+    // Sent to the account's TikTok inbox, to be finished and posted from the app
+    #[default]
+    Draft,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct TikTokPost {
+    pub caption: String,
+    #[serde(default)]
+    pub mode: PostMode,
+    // One of the creator's privacy_level_options, SELF_ONLY when not given. Drafts ignore it
+    #[serde(default)]
+    pub privacy_level: Option<String>,
+    #[serde(default)]
+    pub disable_comment: bool,
+}
+
+pub fn set_client(client_key: &str, client_secret: &str, redirect_port: u16) -> Result<(), String> {
+    if client_key.trim().is_empty() || client_secret.trim().is_empty() {
+        return Err("TikTok client key and secret are both needed".to_string());
+    }
+    if redirect_port == 0 {
+        return Err("TikTok needs the redirect port registered with the app".to_string());
+    }
+    let client = TikTokClient {
+        client_key: client_key.trim().to_string(),
+        client_secret: client_secret.trim().to_string(),
+        redirect_port,
+    };
+    let content = serde_json::to_string(&client)
+        .map_err(|e| format!("Failed to serialize TikTok client: {}", e))?;
+    write_secret(CLIENT_ENTRY, Some(&content))
+}
+
+pub fn client_set() -> Result<bool, String> {
+    Ok(read_secret(CLIENT_ENTRY)?.is_some())
+}
+
+fn client() -> Result<TikTokClient, String> {
+    let content = read_secret(CLIENT_ENTRY)?.ok_or("Add the TikTok app's client key first")?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to read TikTok client: {}", e))
+}
+
+fn tokens_entry(open_id: &str) -> String {
+    format!("tiktok_account_{}", open_id)
+}
+
+// The browser signs in to TikTok and comes back to the loopback port, with PKCE
+pub struct PendingAuthorization {
+    pub url: String,
+    client: TikTokClient,
+    redirect: LoopbackRedirect,
+    verifier: String,
+}
+
+pub async fn begin_authorization() -> Result<PendingAuthorization, String> {
+    let client = client()?;
+    let redirect = LoopbackRedirect::bind("TikTok", client.redirect_port, REDIRECT_PATH).await?;
+    let verifier = code_verifier();
+
+    let mut url = Url::parse(AUTH_URL)
+        .map_err(|e| format!("Invalid authorization URL: {}", e))?;
+    url.query_pairs_mut()
+        .append_pair("client_key", &client.client_key)
+        .append_pair("scope", SCOPES)
+        .append_pair("response_type", "code")
+        .append_pair("redirect_uri", &redirect.redirect_uri)
+        .append_pair("state", &redirect.state)
+        .append_pair("code_challenge", &code_challenge(&verifier))
+        .append_pair("code_challenge_method", "S256");
+
+    Ok(PendingAuthorization {
+        url: url.to_string(),
+        client,
+        redirect,
+        verifier,
+    })
+}
+
+// Desktop apps send the SHA-256 hex encoded, unlike the base64url of the PKCE spec
+fn code_challenge(verifier: &str) -> String {
+    format!("{:x}", Sha256::digest(verifier.as_bytes()))
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: i64,
+    refresh_token: String,
+    open_id: String,
+}
+
+async fn request_tokens(form: &[(&str, &str)]) -> Result<TokenResponse, String> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/oauth/token/", API_URL))
+        .form(form)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach TikTok: {}", e))?;
+    let body: serde_json::Value = response.json()
+        .await
+        .map_err(|e| format!("Failed to read TikTok's token response: {}", e))?;
+    if let Some(error) = body["error"].as_str().filter(|error| !error.is_empty()) {
+        return Err(format!("TikTok rejected the token request: {}", body["error_description"].as_str().unwrap_or(error)));
+    }
+    serde_json::from_value(body)
+        .map_err(|e| format!("Failed to read TikTok's token response: {}", e))
+}
+
+fn account_tokens(tokens: TokenResponse) -> AccountTokens {
+    AccountTokens {
+        access_token: tokens.access_token,
+        expires_at: (chrono::Utc::now() + chrono::Duration::seconds(tokens.expires_in)).to_rfc3339(),
+        refresh_token: tokens.refresh_token,
+    }
+}
+
+fn save_tokens(open_id: &str, tokens: &AccountTokens) -> Result<(), String> {
+    let content = serde_json::to_string(tokens)
+        .map_err(|e| format!("Failed to serialize TikTok tokens: {}", e))?;
+    write_secret(&tokens_entry(open_id), Some(&content))
+}
+
+// TikTok's own envelope: error.code is "ok" when the request worked
+fn api_data(body: serde_json::Value, context: &str) -> Result<serde_json::Value, String> {
+    let code = body["error"]["code"].as_str().unwrap_or_default();
+    if code != "ok" {
+        let message = body["error"]["message"].as_str().filter(|message| !message.is_empty()).unwrap_or(code);
+        return Err(format!("{}: {}", context, message));
+    }
+    Ok(body["data"].clone())
+}
+
+// The accounts clips can be posted to, each signed in on its own, kept in the workspace
+pub struct TikTokPublisher {
+    client: reqwest::Client,
+    path: Mutex<PathBuf>,
+    accounts: Mutex<Vec<TikTokAccount>>,
+}
+
+impl TikTokPublisher {
+    pub fn new(path: PathBuf) -> Self {
+        let accounts = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            client: reqwest::Client::new(),
+            path: Mutex::new(path),
+            accounts: Mutex::new(accounts),
+        }
+    }
+
+    // After the workspace moved, with the file in it
+    pub fn relocate(&self, path: PathBuf) {
+        *self.path.lock().unwrap_or_else(|e| e.into_inner()) = path;
+    }
+
+    pub fn list(&self) -> Vec<TikTokAccount> {
+        self.lock().clone()
+    }
+
+    // Waits for the browser to come back and adds the account, or refreshes its tokens when it
+    // was already connected
+    pub async fn connect(&self, authorization: PendingAuthorization) -> Result<TikTokAccount, String> {
+        let code = authorization.redirect.receive_code().await?;
+        let tokens = request_tokens(&[
+            ("client_key", authorization.client.client_key.as_str()),
+            ("client_secret", authorization.client.client_secret.as_str()),
+            ("code", code.as_str()),
+            ("grant_type", "authorization_code"),
+            ("redirect_uri", authorization.redirect.redirect_uri.as_str()),
+            ("code_verifier", authorization.verifier.as_str()),
+        ]).await?;
+        let open_id = tokens.open_id.clone();
+        let tokens = account_tokens(tokens);
+        save_tokens(&open_id, &tokens)?;
+        let display_name = self.display_name(&tokens.access_token).await;
+
+        let mut accounts = self.lock();
+        if let Some(account) = accounts.iter_mut().find(|account| account.open_id == open_id) {
+            account.display_name = display_name.or(account.display_name.take());
+            let account = account.clone();
+            self.save(&accounts)?;
+            return Ok(account);
+        }
+        let account = TikTokAccount {
+            open_id,
+            display_name,
+            added_at: chrono::Utc::now().to_rfc3339(),
+        };
+        accounts.push(account.clone());
+        self.save(&accounts)?;
+        Ok(account)
+    }
+
+    pub fn remove(&self, open_id: &str) -> Result<(), String> {
+        let mut accounts = self.lock();
+        let count = accounts.len();
+        accounts.retain(|account| account.open_id != open_id);
+        if accounts.len() == count {
+            return Err(format!("TikTok account {} isn't connected", open_id));
+        }
+        write_secret(&tokens_entry(open_id), Some(""))?;
+        self.save(&accounts)
+    }
+
+    // Uploads the clip and returns TikTok's publish ID, for post_status. A draft ends up in the
+    // account's inbox; a direct post still takes TikTok a while to process
+    pub async fn post(&self, open_id: &str, clip_path: &str, post: &TikTokPost) -> Result<String, String> {
+        let access_token = self.access_token(open_id).await?;
+        let clip = tokio::fs::read(clip_path)
+            .await
+            .map_err(|e| format!("Failed to read clip: {}", e))?;
+        let chunks = chunk_ranges(clip.len() as u64);
+        let source_info = serde_json::json!({
+            "source": "FILE_UPLOAD",
+            "video_size": clip.len(),
+            "chunk_size": chunks[0].1 - chunks[0].0,
+            "total_chunk_count": chunks.len(),
+        });
+
+        let (url, body) = match post.mode {
+            PostMode::Draft => (format!("{}/post/publish/inbox/video/init/", API_URL), serde_json::json!({ "source_info": source_info })),
+            PostMode::Direct => {
+                let privacy_level = self.checked_privacy_level(&access_token, post.privacy_level.as_deref()).await?;
+                (format!("{}/post/publish/video/init/", API_URL), serde_json::json!({
+                    "post_info": {
+                        "title": post.caption.chars().take(MAX_CAPTION_CHARS).collect::<String>(),
+                        "privacy_level": privacy_level,
+                        "disable_comment": post.disable_comment,
+                    },
+                    "source_info": source_info,
+                }))
+            }
+        };
+        let data = self.api_post(&access_token, &url, &body, "TikTok refused the post").await?;
+        let publish_id = data["publish_id"].as_str().ok_or("TikTok didn't return a publish ID")?.to_string();
+        let upload_url = data["upload_url"].as_str().ok_or("TikTok didn't return an upload URL")?;
+
+        for (start, end) in chunks {
+            let response = self.client
+                .put(upload_url)
+                .header(reqwest::header::CONTENT_TYPE, "video/mp4")
+                .header(reqwest::header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end - 1, clip.len()))
+                .body(clip[start as usize..end as usize].to_vec())
+                .send()
+                .await
+                .map_err(|e| format!("Failed to upload clip to TikTok: {}", e))?;
+            if !response.status().is_success() {
+                return Err(format!("TikTok rejected the upload: {}", response.status()));
+            }
+        }
+        Ok(publish_id)
+    }
+
+    // PROCESSING_UPLOAD, SEND_TO_USER_INBOX, PUBLISH_COMPLETE or FAILED, as TikTok names them
+    pub async fn post_status(&self, open_id: &str, publish_id: &str) -> Result<String, String> {
+        let access_token = self.access_token(open_id).await?;
+        let data = self.api_post(
+            &access_token,
+            &format!("{}/post/publish/status/fetch/", API_URL),
+            &serde_json::json!({ "publish_id": publish_id }),
+            "Failed to get TikTok post status",
+        ).await?;
+        let status = data["status"].as_str().unwrap_or_default().to_string();
+        match data["fail_reason"].as_str().filter(|reason| !reason.is_empty()) {
+            Some(reason) => Ok(format!("{}: {}", status, reason)),
+            None => Ok(status),
+        }
+    }
+
+    // The privacy level asked for, if the creator can use it
+    async fn checked_privacy_level(&self, access_token: &str, privacy_level: Option<&str>) -> Result<String, String> {
+        let creator = self.api_post(
+            access_token,
+            &format!("{}/post/publish/creator_info/query/", API_URL),
+            &serde_json::json!({}),
+            "Failed to get TikTok creator info",
+        ).await?;
+        let privacy_level = privacy_level.unwrap_or(DEFAULT_PRIVACY_LEVEL);
+        let allowed = creator["privacy_level_options"].as_array()
+            .is_some_and(|options| options.iter().any(|option| option.as_str() == Some(privacy_level)));
+        if !allowed {
+            return Err(format!("This TikTok account can't post with privacy level {}", privacy_level));
+        }
+        Ok(privacy_level.to_string())
+    }
+
+    async fn api_post(&self, access_token: &str, url: &str, body: &serde_json::Value, context: &str) -> Result<serde_json::Value, String> {
+        let response = self.client
+            .post(url)
+            .bearer_auth(access_token)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach TikTok: {}", e))?;
+        let body: serde_json::Value = response.json()
+            .await
+            .map_err(|e| format!("Failed to read TikTok's response: {}", e))?;
+        api_data(body, context)
+    }
+
+    async fn display_name(&self, access_token: &str) -> Option<String> {
+        let response = self.client
+            .get(format!("{}/user/info/", API_URL))
+            .query(&[("fields", "open_id,display_name")])
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .ok()?;
+        let data = api_data(response.json().await.ok()?, "").ok()?;
+        data["user"]["display_name"].as_str().map(str::to_string)
+    }
+
+    // Access tokens last a day; the refresh token gets a new one and is itself replaced
+    async fn access_token(&self, open_id: &str) -> Result<String, String> {
+        let content = read_secret(&tokens_entry(open_id))?
+            .ok_or_else(|| format!("TikTok account {} isn't connected", open_id))?;
+        let tokens: AccountTokens = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to read TikTok tokens: {}", e))?;
+        let expires_at = chrono::DateTime::parse_from_rfc3339(&tokens.expires_at)
+            .map_err(|e| format!("Failed to read TikTok tokens: {}", e))?;
+        // A minute's margin so the token doesn't run out mid-upload
+        if expires_at > chrono::Utc::now() + chrono::Duration::minutes(1) {
+            return Ok(tokens.access_token);
+        }
+
+        let client = client()?;
+        let refreshed = request_tokens(&[
+            ("client_key", client.client_key.as_str()),
+            ("client_secret", client.client_secret.as_str()),
+            ("grant_type", "refresh_token"),
+            ("refresh_token", tokens.refresh_token.as_str()),
+        ])
+        .await
+        .map_err(|e| format!("{}; connect the TikTok account again", e))?;
+        let tokens = account_tokens(refreshed);
+        save_tokens(open_id, &tokens)?;
+        Ok(tokens.access_token)
+    }
+
+    fn save(&self, accounts: &[TikTokAccount]) -> Result<(), String> {
+        let path = self.path.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create TikTok accounts directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(accounts)
+            .map_err(|e| format!("Failed to serialize TikTok accounts: {}", e))?;
+        write_atomic(&path, content.as_bytes())
+            .map_err(|e| format!("Failed to write TikTok accounts: {}", e))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<TikTokAccount>> {
+        self.accounts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// Byte ranges, end exclusive, of the chunks a clip of size bytes is uploaded in
+fn chunk_ranges(size: u64) -> Vec<(u64, u64)> {
+    if size <= MAX_SINGLE_CHUNK_BYTES {
+        return vec![(0, size)];
+    }
+    let count = size / CHUNK_BYTES;
+    (0..count)
+        .map(|index| {
+            let start = index * CHUNK_BYTES;
+            let end = if index == count - 1 { size } else { start + CHUNK_BYTES };
+            (start, end)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_ranges() {
+        assert_eq!(chunk_ranges(1000), vec![(0, 1000)]);
+
+        let size = 100 * 1024 * 1024 + 123;
+        let chunks = chunk_ranges(size);
+        assert_eq!(chunks.len(), 10);
+        assert_eq!(chunks[0], (0, CHUNK_BYTES));
+        assert_eq!(chunks[9], (9 * CHUNK_BYTES, size));
+        assert!(chunks.windows(2).all(|pair| pair[0].1 == pair[1].0));
+    }
+
+    #[test]
+    fn test_api_data_reads_the_envelope() {
+        let ok = serde_json::json!({ "data": { "publish_id": "v_pub_1" }, "error": { "code": "ok", "message": "" } });
+        assert_eq!(api_data(ok, "Post").unwrap()["publish_id"], "v_pub_1");
+
+        let failed = serde_json::json!({ "data": {}, "error": { "code": "spam_risk_too_many_posts", "message": "Too many posts today" } });
+        assert_eq!(api_data(failed, "Post").unwrap_err(), "Post: Too many posts today");
+    }
+}
@@ -0,0 +1,287 @@
+use crate::atomic_file::write_atomic;
+use crate::project_archive::walk_files;
+use crate::project_crypto::{read_data, write_data, DataKey};
+use crate::project_manager::{move_dir, rebase_path, Project, VideoProject};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind")]
+pub enum TrashedItem {
+    Project {
+        project_id: String,
+        name: String,
+        original_path: PathBuf,
+    },
+    Video {
+        project_id: String,
+        project_name: String,
+        video_id: String,
+        title: String,
+        // Where the video was in the project's list
+        index: usize,
+        files: Vec<TrashedFile>,
+    },
+}
+
+// An output file moved into the trash along with its video
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashedFile {
+    pub original_path: String,
+    pub trashed_path: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashEntry {
+    pub id: String,
+    pub deleted_at: String,
+    // Purged automatically after this
+    pub expires_at: String,
+    pub size_bytes: u64,
+    #[serde(flatten)]
+    pub item: TrashedItem,
+}
+
+// workspace/trash/<entry id>/ holds a deleted project's folder, or a deleted video's
+// video.json and files; index.json lists the entries
+pub struct Trash {
+    dir: PathBuf,
+    // For the trashed videos' video.json, when the workspace is encrypted
+    key: Option<DataKey>,
+}
+
+impl Trash {
+    pub fn new(workspace_root: &Path) -> Self {
+        Self {
+            dir: workspace_root.join("trash"),
+            key: None,
+        }
+    }
+
+    pub fn with_key(mut self, key: Option<DataKey>) -> Self {
+        self.key = key;
+        self
+    }
+
+    // Newest first
+    pub fn list(&self) -> Result<Vec<TrashEntry>, String> {
+        let index_path = self.dir.join("index.json");
+        if !index_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = std::fs::read_to_string(&index_path)
+            .map_err(|e| format!("Failed to read trash: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse trash: {}", e))
+    }
+
+    pub fn trash_project(&self, project: &Project) -> Result<TrashEntry, String> {
+        let entry_id = Uuid::new_v4().to_string();
+        let size_bytes = walk_files(&project.workspace_path).iter()
+            .filter_map(|file| std::fs::metadata(file).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+
+        if project.workspace_path.exists() {
+            move_dir(&project.workspace_path, &self.project_dir(&entry_id))?;
+        }
+
+        self.add(entry_id, size_bytes, TrashedItem::Project {
+            project_id: project.id.clone(),
+            name: project.name.clone(),
+            original_path: project.workspace_path.clone(),
+        })
+    }
+
+    // With move_files the video's output files go into the trash too, otherwise they stay where they are
+    pub fn trash_video(&self, project: &Project, index: usize, video: &VideoProject, move_files: bool) -> Result<TrashEntry, String> {
+        let entry_id = Uuid::new_v4().to_string();
+        let entry_dir = self.dir.join(&entry_id);
+        std::fs::create_dir_all(entry_dir.join("files"))
+            .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+
+        let json_data = serde_json::to_string_pretty(video)
+            .map_err(|e| format!("Failed to serialize video: {}", e))?;
+        write_data(&entry_dir.join("video.json"), &json_data, self.key.as_ref())
+            .map_err(|e| format!("Failed to write trashed video: {}", e))?;
+
+        let mut files = Vec::new();
+        let mut size_bytes = 0;
+        if move_files {
+            for (position, file) in video.output_files.iter().enumerate() {
+                let path = Path::new(file);
+                let Ok(metadata) = std::fs::metadata(path) else {
+                    continue;
+                };
+                // Prefixed with the position so two files with the same name can't collide
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let trashed_path = entry_dir.join("files").join(format!("{}_{}", position, name));
+                move_file(path, &trashed_path)?;
+                size_bytes += metadata.len();
+                files.push(TrashedFile {
+                    original_path: file.clone(),
+                    trashed_path,
+                });
+            }
+        }
+
+        self.add(entry_id, size_bytes, TrashedItem::Video {
+            project_id: project.id.clone(),
+            project_name: project.name.clone(),
+            video_id: video.id.clone(),
+            title: video.video_info.title.clone(),
+            index,
+            files,
+        })
+    }
+
+    // Takes the entry out of the trash and moves its files back. For a project this is its
+    // folder; for a video it's the output files, and the video itself is returned
+    pub fn restore(&self, entry_id: &str) -> Result<(TrashEntry, Option<VideoProject>), String> {
+        let entry = self.find(entry_id)?;
+        let video = match &entry.item {
+            TrashedItem::Project { original_path, .. } => {
+                if original_path.exists() {
+                    return Err(format!("{} already exists", original_path.display()));
+                }
+                move_dir(&self.project_dir(entry_id), original_path)?;
+                None
+            }
+            TrashedItem::Video { files, .. } => {
+                let content = read_data(&self.dir.join(entry_id).join("video.json"), self.key.as_ref())
+                    .map_err(|e| format!("Failed to read trashed video: {}", e))?;
+                let video: VideoProject = serde_json::from_str(&content)
+                    .map_err(|e| format!("Failed to parse trashed video: {}", e))?;
+                for file in files {
+                    move_file(&file.trashed_path, Path::new(&file.original_path))?;
+                }
+                Some(video)
+            }
+        };
+
+        self.remove(entry_id)?;
+        Ok((entry, video))
+    }
+
+    // After the workspace has moved, for the paths that pointed into it
+    pub fn rebase(&self, from: &Path, to: &Path) -> Result<(), String> {
+        let rebase = |path: &Path| rebase_path(path, from, to).unwrap_or(path.to_path_buf());
+
+        let mut entries = self.list()?;
+        if entries.is_empty() {
+            return Ok(());
+        }
+        for entry in &mut entries {
+            match &mut entry.item {
+                TrashedItem::Project { original_path, .. } => *original_path = rebase(original_path),
+                TrashedItem::Video { files, .. } => {
+                    for file in files.iter_mut() {
+                        file.original_path = rebase(Path::new(&file.original_path)).to_string_lossy().to_string();
+                        file.trashed_path = rebase(&file.trashed_path);
+                    }
+
+                    let video_file = self.dir.join(&entry.id).join("video.json");
+                    let content = read_data(&video_file, self.key.as_ref())
+                        .map_err(|e| format!("Failed to read trashed video: {}", e))?;
+                    let mut video: VideoProject = serde_json::from_str(&content)
+                        .map_err(|e| format!("Failed to parse trashed video: {}", e))?;
+                    for file in &mut video.output_files {
+                        *file = rebase(Path::new(file)).to_string_lossy().to_string();
+                    }
+                    let json_data = serde_json::to_string_pretty(&video)
+                        .map_err(|e| format!("Failed to serialize video: {}", e))?;
+                    write_data(&video_file, &json_data, self.key.as_ref())
+                        .map_err(|e| format!("Failed to write trashed video: {}", e))?;
+                }
+            }
+        }
+        self.write_index(&entries)
+    }
+
+    pub fn purge(&self, entry_id: &str) -> Result<(), String> {
+        self.find(entry_id)?;
+        self.remove(entry_id)
+    }
+
+    pub fn purge_expired(&self) -> Result<usize, String> {
+        let now = Utc::now();
+        let expired: Vec<String> = self.list()?.into_iter()
+            .filter(|entry| DateTime::parse_from_rfc3339(&entry.expires_at).is_ok_and(|expires| expires < now))
+            .map(|entry| entry.id)
+            .collect();
+
+        for entry_id in &expired {
+            self.remove(entry_id)?;
+        }
+        Ok(expired.len())
+    }
+
+    fn add(&self, entry_id: String, size_bytes: u64, item: TrashedItem) -> Result<TrashEntry, String> {
+        let now = Utc::now();
+        let entry = TrashEntry {
+            id: entry_id,
+            deleted_at: now.to_rfc3339(),
+            expires_at: (now + Duration::days(TRASH_RETENTION_DAYS)).to_rfc3339(),
+            size_bytes,
+            item,
+        };
+
+        let mut entries = self.list()?;
+        entries.insert(0, entry.clone());
+        self.write_index(&entries)?;
+        Ok(entry)
+    }
+
+    fn find(&self, entry_id: &str) -> Result<TrashEntry, String> {
+        self.list()?.into_iter()
+            .find(|entry| entry.id == entry_id)
+            .ok_or_else(|| "Trash entry not found".to_string())
+    }
+
+    // Drops the entry and deletes whatever is left of it on disk
+    fn remove(&self, entry_id: &str) -> Result<(), String> {
+        let entry_dir = self.dir.join(entry_id);
+        if entry_dir.exists() {
+            std::fs::remove_dir_all(&entry_dir)
+                .map_err(|e| format!("Failed to delete trash entry: {}", e))?;
+        }
+
+        let mut entries = self.list()?;
+        entries.retain(|entry| entry.id != entry_id);
+        self.write_index(&entries)
+    }
+
+    fn write_index(&self, entries: &[TrashEntry]) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+        let json_data = serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("Failed to serialize trash: {}", e))?;
+        write_atomic(&self.dir.join("index.json"), json_data.as_bytes())
+            .map_err(|e| format!("Failed to save trash: {}", e))
+    }
+
+    fn project_dir(&self, entry_id: &str) -> PathBuf {
+        self.dir.join(entry_id).join("project")
+    }
+}
+
+// Copies and deletes when a rename isn't possible, e.g. from another drive
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    std::fs::copy(from, to)
+        .map_err(|e| format!("Failed to move {}: {}", from.display(), e))?;
+    std::fs::remove_file(from)
+        .map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+}
@@ -1,15 +1,33 @@
 use crate::{VideoNugget, ProcessingResult};
+use crate::app_settings;
+use crate::error::AppError;
+use crate::operations::OperationHandle;
+use crate::processors::Processors;
+use crate::project_manager::{EventType, ProcessingEvent};
+use crate::youtube_extractor::YouTubeExtractor;
 use serde_json;
 use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
 pub struct VideoProcessor {
-    // Add any state needed for video processing
+    youtube_extractor: YouTubeExtractor,
+}
+
+impl Default for VideoProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl VideoProcessor {
     pub fn new() -> Self {
-        Self {}
+        Self::with_extractor(YouTubeExtractor::new())
+    }
+
+    // For callers that already have an extractor to share
+    pub fn with_extractor(youtube_extractor: YouTubeExtractor) -> Self {
+        Self { youtube_extractor }
     }
 
     pub async fn process_video(
@@ -31,8 +49,7 @@ impl VideoProcessor {
             .unwrap_or(true);
 
         // Get video duration first
-        let youtube_extractor = crate::youtube_extractor::YouTubeExtractor::new();
-        let video_info = youtube_extractor.get_video_info(url).await?;
+        let video_info = self.youtube_extractor.get_video_info(url).await?;
 
         // Generate nuggets based on duration and configuration
         let mut nuggets = Vec::new();
@@ -54,6 +71,8 @@ impl VideoProcessor {
                     None
                 },
                 tags: self.generate_tags(&video_info.title),
+                notes: None,
+                youtube_video_id: None,
                 created_at: chrono::Utc::now().to_rfc3339(),
             };
 
@@ -68,10 +87,23 @@ impl VideoProcessor {
             nugget_index += 1;
         }
 
+        let mut parameters = HashMap::new();
+        parameters.insert("url".to_string(), serde_json::json!(url));
+        parameters.insert("nugget_count".to_string(), serde_json::json!(nuggets.len()));
+        parameters.insert("nugget_duration".to_string(), serde_json::json!(nugget_duration));
+        parameters.insert("overlap_duration".to_string(), serde_json::json!(overlap_duration));
+        parameters.insert("extract_transcript".to_string(), serde_json::json!(extract_transcript));
+        let event = ProcessingEvent::new(
+            EventType::NuggetsGenerated,
+            format!("Generated {} nuggets", nuggets.len()),
+            parameters,
+        );
+
         Ok(ProcessingResult {
             success: true,
             message: format!("Successfully processed video into {} nuggets", nuggets.len()),
             nuggets,
+            events: vec![event],
         })
     }
 
@@ -179,6 +211,118 @@ impl VideoProcessor {
     }
 }
 
+// The desktop app's processing, which downloads and transcribes the video rather than
+// estimating parts from its metadata like VideoProcessor does
+pub async fn process_video_with_progress(
+    url: &str,
+    config: HashMap<String, serde_json::Value>,
+    processors: &Processors,
+    operation: &OperationHandle
+) -> Result<ProcessingResult, AppError> {
+    let ffmpeg_processor = processors.ffmpeg()?.scratch()?.with_cancellation(operation.cancel_token());
+    let speech_recognizer = processors.speech()?.scratch()?.with_cancellation(operation.cancel_token());
+    operation.work_dir(ffmpeg_processor.work_dir());
+    operation.work_dir(speech_recognizer.work_dir());
+    
+    // Download video, unless an interrupted run of this operation already did
+    let video_path = match operation.reusable_artifact("video") {
+        Some(video_path) => video_path,
+        None => {
+            operation.stage("Downloading");
+            let quality = app_settings::current().defaults.download_quality;
+            let video_path = ffmpeg_processor.download_video_with_progress(url, &quality, Some(Arc::new(operation.reporter()))).await?;
+            operation.artifact("video", &video_path);
+            video_path
+        }
+    };
+    let video_info = ffmpeg_processor.get_video_info(&video_path).await?;
+    
+    // Extract audio for transcription
+    let audio_path = match operation.reusable_artifact("audio") {
+        Some(audio_path) => audio_path,
+        None => {
+            operation.stage("Extracting audio");
+            let audio_path = ffmpeg_processor.extract_audio(&video_path).await?;
+            operation.artifact("audio", &audio_path);
+            audio_path
+        }
+    };
+    
+    // Get configuration
+    let nugget_duration = config.get("nugget_duration")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(30.0);
+    
+    let overlap_duration = config.get("overlap_duration")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(5.0);
+    
+    let enable_transcript = config.get("enable_transcript")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    
+    // Generate nuggets with transcription
+    let mut nuggets = Vec::new();
+    let mut current_time = 0.0;
+    let mut nugget_index = 1;
+
+    operation.stage("Transcribing");
+    while current_time < video_info.duration {
+        // A cancelled recognizer fails every segment, which would otherwise just leave them empty
+        if operation.cancel_token().is_cancelled() {
+            return Err(AppError::cancelled());
+        }
+        operation.progress(current_time / video_info.duration * 100.0, Some(format!("Part {}", nugget_index)));
+        let end_time = (current_time + nugget_duration).min(video_info.duration);
+        
+        let transcript = if enable_transcript {
+            speech_recognizer.transcribe_segment(&audio_path, current_time, end_time).await.ok()
+        } else {
+            None
+        };
+
+        let nugget = VideoNugget {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: format!("{} - Part {}", video_info.title, nugget_index),
+            start_time: current_time,
+            end_time,
+            transcript,
+            tags: vec!["video-nugget".to_string()],
+            notes: None,
+            youtube_video_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        nuggets.push(nugget);
+        current_time = end_time - overlap_duration;
+        
+        if current_time >= video_info.duration - 1.0 {
+            break;
+        }
+        
+        nugget_index += 1;
+    }
+
+    let mut parameters = HashMap::new();
+    parameters.insert("url".to_string(), serde_json::json!(url));
+    parameters.insert("nugget_count".to_string(), serde_json::json!(nuggets.len()));
+    parameters.insert("nugget_duration".to_string(), serde_json::json!(nugget_duration));
+    parameters.insert("overlap_duration".to_string(), serde_json::json!(overlap_duration));
+    parameters.insert("extract_transcript".to_string(), serde_json::json!(enable_transcript));
+    let event = ProcessingEvent::new(
+        EventType::NuggetsGenerated,
+        format!("Generated {} nuggets", nuggets.len()),
+        parameters,
+    );
+
+    Ok(ProcessingResult {
+        success: true,
+        message: format!("Successfully processed video into {} nuggets", nuggets.len()),
+        nuggets,
+        events: vec![event],
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,7 +332,6 @@ mod tests {
     fn test_new_processor() {
         let _processor = VideoProcessor::new();
         // Just verify it can be created
-        assert!(true);
     }
 
     #[test]
@@ -0,0 +1,293 @@
+use crate::VideoNugget;
+use crate::ai_analyzer::ContentAnalysis;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Platform {
+    TikTok,
+    YouTubeShorts,
+    InstagramReels,
+    LinkedIn,
+}
+
+impl Platform {
+    pub fn all() -> [Platform; 4] {
+        [Platform::TikTok, Platform::YouTubeShorts, Platform::InstagramReels, Platform::LinkedIn]
+    }
+
+    fn profile(&self) -> PlatformProfile {
+        match self {
+            Platform::TikTok => PlatformProfile {
+                ideal_duration: (15.0, 45.0),
+                max_duration: 180.0,
+                hook_weight: 0.5,
+                duration_weight: 0.25,
+                topic_weight: 0.25,
+                affinity_keywords: &["hack", "secret", "funny", "trend", "challenge", "crazy", "wait", "pov"],
+            },
+            Platform::YouTubeShorts => PlatformProfile {
+                ideal_duration: (20.0, 55.0),
+                max_duration: 60.0,
+                hook_weight: 0.4,
+                duration_weight: 0.3,
+                topic_weight: 0.3,
+                affinity_keywords: &["how to", "tutorial", "tip", "review", "explained", "best", "vs"],
+            },
+            Platform::InstagramReels => PlatformProfile {
+                ideal_duration: (15.0, 60.0),
+                max_duration: 90.0,
+                hook_weight: 0.45,
+                duration_weight: 0.25,
+                topic_weight: 0.3,
+                affinity_keywords: &["style", "travel", "food", "behind the scenes", "day in", "tip", "love"],
+            },
+            Platform::LinkedIn => PlatformProfile {
+                ideal_duration: (30.0, 120.0),
+                max_duration: 600.0,
+                hook_weight: 0.3,
+                duration_weight: 0.2,
+                topic_weight: 0.5,
+                affinity_keywords: &["business", "career", "leadership", "team", "lesson", "growth", "strategy", "hiring"],
+            },
+        }
+    }
+}
+
+struct PlatformProfile {
+    ideal_duration: (f64, f64),
+    max_duration: f64,
+    hook_weight: f64,
+    duration_weight: f64,
+    topic_weight: f64,
+    affinity_keywords: &'static [&'static str],
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ViralityScore {
+    pub nugget_id: String,
+    pub platform: Platform,
+    pub overall: f64,
+    pub hook_strength: f64,
+    pub duration_fit: f64,
+    pub topic_trendiness: f64,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlatformRanking {
+    pub platform: Platform,
+    pub recommendations: Vec<ViralityScore>,
+}
+
+const HOOK_WORDS: [&str; 14] = [
+    "you", "secret", "mistake", "never", "always", "why", "how", "stop",
+    "nobody", "everyone", "best", "worst", "truth", "actually",
+];
+
+pub struct ViralityScorer {
+    trending_topics: Vec<String>,
+}
+
+impl ViralityScorer {
+    pub fn new(trending_topics: Vec<String>) -> Self {
+        Self {
+            trending_topics: trending_topics.into_iter().map(|t| t.to_lowercase()).collect(),
+        }
+    }
+
+    pub fn rank_nuggets(&self, nuggets: &[VideoNugget], analysis: Option<&ContentAnalysis>, top_n: usize) -> Vec<PlatformRanking> {
+        Platform::all().iter()
+            .map(|platform| {
+                let mut recommendations: Vec<ViralityScore> = nuggets.iter()
+                    .map(|nugget| self.score_nugget(nugget, *platform, analysis))
+                    .collect();
+
+                recommendations.sort_by(|a, b| b.overall.partial_cmp(&a.overall).unwrap_or(std::cmp::Ordering::Equal));
+                recommendations.truncate(top_n);
+
+                PlatformRanking {
+                    platform: *platform,
+                    recommendations,
+                }
+            })
+            .collect()
+    }
+
+    pub fn score_nugget(&self, nugget: &VideoNugget, platform: Platform, analysis: Option<&ContentAnalysis>) -> ViralityScore {
+        let profile = platform.profile();
+        let mut reasons = Vec::new();
+
+        let text = format!("{} {}", nugget.title, nugget.transcript.as_deref().unwrap_or("")).to_lowercase();
+
+        let hook_strength = Self::hook_strength(nugget.transcript.as_deref().unwrap_or(&nugget.title));
+        if hook_strength >= 0.6 {
+            reasons.push("Strong opening hook".to_string());
+        }
+
+        let duration = nugget.end_time - nugget.start_time;
+        let duration_fit = Self::duration_fit(duration, &profile);
+        if duration > profile.max_duration {
+            reasons.push(format!("Longer than the {:.0}s platform limit", profile.max_duration));
+        } else if duration_fit >= 0.99 {
+            reasons.push("Duration is in the platform sweet spot".to_string());
+        }
+
+        let topic_trendiness = self.topic_trendiness(&text, nugget, &profile, analysis, &mut reasons);
+
+        let overall = (profile.hook_weight * hook_strength
+            + profile.duration_weight * duration_fit
+            + profile.topic_weight * topic_trendiness)
+            .clamp(0.0, 1.0);
+
+        ViralityScore {
+            nugget_id: nugget.id.clone(),
+            platform,
+            overall,
+            hook_strength,
+            duration_fit,
+            topic_trendiness,
+            reasons,
+        }
+    }
+
+    // Only the first few seconds matter for the scroll decision, so look at the opening words
+    fn hook_strength(text: &str) -> f64 {
+        let opening: Vec<String> = text.split_whitespace()
+            .take(15)
+            .map(|w| w.to_lowercase())
+            .collect();
+
+        if opening.is_empty() {
+            return 0.0;
+        }
+
+        let mut score: f64 = 0.2;
+        let opening_text = opening.join(" ");
+
+        if opening_text.contains('?') {
+            score += 0.25;
+        }
+        if opening.iter().any(|w| w.chars().any(|c| c.is_ascii_digit())) {
+            score += 0.15;
+        }
+
+        let hook_word_count = opening.iter()
+            .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+            .filter(|w| HOOK_WORDS.contains(w))
+            .count();
+        score += (hook_word_count as f64 * 0.15).min(0.4);
+
+        score.min(1.0)
+    }
+
+    fn duration_fit(duration: f64, profile: &PlatformProfile) -> f64 {
+        let (ideal_min, ideal_max) = profile.ideal_duration;
+
+        if duration <= 0.0 || duration > profile.max_duration {
+            0.0
+        } else if duration < ideal_min {
+            duration / ideal_min
+        } else if duration <= ideal_max {
+            1.0
+        } else {
+            // Linear decay from the end of the sweet spot down to the hard limit
+            let span = (profile.max_duration - ideal_max).max(1.0);
+            (1.0 - (duration - ideal_max) / span).max(0.0)
+        }
+    }
+
+    fn topic_trendiness(
+        &self,
+        text: &str,
+        nugget: &VideoNugget,
+        profile: &PlatformProfile,
+        analysis: Option<&ContentAnalysis>,
+        reasons: &mut Vec<String>,
+    ) -> f64 {
+        let mut score: f64 = 0.0;
+
+        let trending_hits: Vec<&String> = self.trending_topics.iter()
+            .filter(|topic| text.contains(topic.as_str()) || nugget.tags.iter().any(|t| t.eq_ignore_ascii_case(topic)))
+            .collect();
+        if !trending_hits.is_empty() {
+            score += (trending_hits.len() as f64 * 0.25).min(0.5);
+            reasons.push(format!("Mentions trending topics: {}", trending_hits.iter().map(|t| t.as_str()).collect::<Vec<_>>().join(", ")));
+        }
+
+        if let Some(analysis) = analysis {
+            let key_topic_hits = analysis.key_topics.iter()
+                .filter(|topic| text.contains(&topic.to_lowercase()))
+                .count();
+            score += (key_topic_hits as f64 * 0.1).min(0.3);
+
+            let in_highlight = analysis.highlight_moments.iter()
+                .any(|h| h.start_time < nugget.end_time && h.end_time > nugget.start_time);
+            if in_highlight {
+                score += 0.2;
+                reasons.push("Overlaps a detected highlight moment".to_string());
+            }
+        }
+
+        let affinity_hits = profile.affinity_keywords.iter()
+            .filter(|keyword| text.contains(*keyword))
+            .count();
+        score += (affinity_hits as f64 * 0.1).min(0.3);
+
+        score.min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nugget(id: &str, duration: f64, title: &str, transcript: &str) -> VideoNugget {
+        VideoNugget {
+            id: id.to_string(),
+            title: title.to_string(),
+            start_time: 0.0,
+            end_time: duration,
+            transcript: Some(transcript.to_string()),
+            tags: vec![],
+            notes: None,
+            youtube_video_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn test_hook_strength_rewards_questions_and_hook_words() {
+        let weak = ViralityScorer::hook_strength("today we continue with the agenda");
+        let strong = ViralityScorer::hook_strength("Why does nobody tell you the 3 secret rules?");
+
+        assert!(strong > weak);
+        assert!(strong <= 1.0);
+        assert_eq!(ViralityScorer::hook_strength(""), 0.0);
+    }
+
+    #[test]
+    fn test_duration_fit_respects_platform_limits() {
+        let shorts = Platform::YouTubeShorts.profile();
+
+        assert_eq!(ViralityScorer::duration_fit(30.0, &shorts), 1.0);
+        assert_eq!(ViralityScorer::duration_fit(90.0, &shorts), 0.0);
+        assert!(ViralityScorer::duration_fit(10.0, &shorts) < 1.0);
+        assert!(ViralityScorer::duration_fit(58.0, &shorts) < 1.0);
+    }
+
+    #[test]
+    fn test_rank_nuggets_orders_by_overall_score_per_platform() {
+        let scorer = ViralityScorer::new(vec!["rust".to_string()]);
+        let nuggets = vec![
+            nugget("slow", 300.0, "Part 1", "so anyway, as I was saying earlier"),
+            nugget("punchy", 30.0, "Rust secret", "Why does nobody use this Rust secret?"),
+        ];
+
+        let rankings = scorer.rank_nuggets(&nuggets, None, 1);
+        assert_eq!(rankings.len(), 4);
+
+        let tiktok = rankings.iter().find(|r| r.platform == Platform::TikTok).unwrap();
+        assert_eq!(tiktok.recommendations.len(), 1);
+        assert_eq!(tiktok.recommendations[0].nugget_id, "punchy");
+    }
+}
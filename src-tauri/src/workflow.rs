@@ -0,0 +1,480 @@
+use crate::ai_analyzer::{AIAnalyzer, AIConfig, CircuitBreakers, ContentAnalysis, PromptTemplate};
+use crate::error::AppError;
+use crate::ffmpeg_processor::FFmpegProcessor;
+use crate::file_manager::{write_export, FileManager};
+use crate::operations::OperationHandle;
+use crate::output_template::sanitize_segment;
+use crate::processors::Processors;
+use crate::project_manager::{ProcessingEvent, ProjectSettings, WorkflowStep};
+use crate::speech_recognition::{SpeechAnalysis, TranscriptSegment};
+use crate::virality_scorer::{Platform, ViralityScorer};
+use crate::{app_settings, VideoInfo, VideoNugget};
+use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub const WORKFLOW_STEP_EVENT: &str = "workflow://step";
+
+// What a template step runs. Each works on what the steps before it produced
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum WorkflowAction {
+    // Download, transcribe and cut into nuggets. Parameters: nugget_duration, overlap_duration,
+    // transcript (false for nuggets without text)
+    Transcribe,
+    // AI analysis of the transcript, with the project's prompt template
+    Analyze,
+    // The nuggets most likely to do well on a platform. Parameters: platform, min_score, top_n,
+    // trending_topics
+    DetectHighlights,
+    // A clip per nugget, the highlights only once there are some. Parameters: highlights_only,
+    // social_formats
+    RenderClips,
+    // Parameters: formats, highlights_only
+    Export,
+}
+
+// When a step runs; one whose condition doesn't hold is skipped
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind")]
+pub enum StepCondition {
+    // A project setting by its field name: auto_analyze, auto_transcribe, ai_analysis_enabled,
+    // social_media_formats or backup_enabled
+    SettingEnabled { setting: String },
+    MinNuggets { count: usize },
+    MinHighlights { count: usize },
+    MinDurationSeconds { seconds: f64 },
+    HasTranscript,
+    HasAnalysis,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum StepStatus {
+    Started,
+    Completed,
+    Skipped,
+    Failed,
+}
+
+// Sent as workflow://step when a step starts and when it ends
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkflowStepEvent {
+    pub operation_id: String,
+    pub step_index: usize,
+    pub step_count: usize,
+    pub name: String,
+    pub action: Option<WorkflowAction>,
+    pub status: StepStatus,
+    pub message: Option<String>,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkflowRunResult {
+    pub url: String,
+    pub video_info: Option<VideoInfo>,
+    pub nuggets: Vec<VideoNugget>,
+    pub highlight_ids: Vec<String>,
+    pub analysis: Option<ContentAnalysis>,
+    pub output_files: Vec<String>,
+    // How each step ended
+    pub steps: Vec<WorkflowStepEvent>,
+    #[serde(skip)]
+    pub events: Vec<ProcessingEvent>,
+}
+
+// What the steps so far have produced
+#[derive(Default)]
+struct WorkflowState {
+    video_path: Option<String>,
+    video_info: Option<VideoInfo>,
+    transcript: Option<SpeechAnalysis>,
+    nuggets: Vec<VideoNugget>,
+    analysis: Option<ContentAnalysis>,
+    highlights: Option<Vec<VideoNugget>>,
+    output_files: Vec<String>,
+    events: Vec<ProcessingEvent>,
+}
+
+impl WorkflowState {
+    fn holds(&self, condition: &StepCondition, settings: &ProjectSettings) -> bool {
+        match condition {
+            StepCondition::SettingEnabled { setting } => match setting.as_str() {
+                "auto_analyze" => settings.auto_analyze,
+                "auto_transcribe" => settings.auto_transcribe,
+                "ai_analysis_enabled" => settings.ai_analysis_enabled,
+                "social_media_formats" => settings.social_media_formats,
+                "backup_enabled" => settings.backup_enabled,
+                _ => false,
+            },
+            StepCondition::MinNuggets { count } => self.nuggets.len() >= *count,
+            StepCondition::MinHighlights { count } => self.highlights.as_ref().is_some_and(|highlights| highlights.len() >= *count),
+            StepCondition::MinDurationSeconds { seconds } => self.video_info.as_ref().is_some_and(|info| info.duration >= *seconds),
+            StepCondition::HasTranscript => self.transcript.is_some(),
+            StepCondition::HasAnalysis => self.analysis.is_some(),
+        }
+    }
+
+    // The highlights once a step has detected them, else every nugget
+    fn selected(&self, highlights_only: Option<bool>) -> Vec<VideoNugget> {
+        match (&self.highlights, highlights_only) {
+            (Some(highlights), None | Some(true)) => highlights.clone(),
+            _ => self.nuggets.clone(),
+        }
+    }
+
+    fn title(&self) -> String {
+        self.video_info.as_ref()
+            .map(|info| info.title.clone())
+            .unwrap_or_else(|| "video".to_string())
+    }
+}
+
+// Runs a template's workflow on one video, so a template automates the pipeline instead of
+// only describing it. Steps without an action are the user's to do and are skipped.
+pub struct WorkflowRunner {
+    processors: Arc<Processors>,
+    settings: ProjectSettings,
+    output_directory: PathBuf,
+    ai: Option<(AIConfig, PromptTemplate)>,
+    circuit_breakers: CircuitBreakers,
+}
+
+impl WorkflowRunner {
+    pub fn new(processors: Arc<Processors>, settings: ProjectSettings, output_directory: PathBuf) -> Self {
+        Self {
+            processors,
+            settings,
+            output_directory,
+            ai: None,
+            circuit_breakers: CircuitBreakers::default(),
+        }
+    }
+
+    // Without it Analyze steps fail
+    pub fn with_ai(mut self, config: AIConfig, prompt_template: PromptTemplate) -> Self {
+        self.ai = Some((config, prompt_template));
+        self
+    }
+
+    pub fn with_circuit_breakers(mut self, circuit_breakers: CircuitBreakers) -> Self {
+        self.circuit_breakers = circuit_breakers;
+        self
+    }
+
+    // A failing step stops the run unless it's marked continue_on_error; cancelling the
+    // operation always does
+    pub async fn run<F>(&self, steps: &[WorkflowStep], url: &str, operation: &OperationHandle, on_event: F) -> Result<WorkflowRunResult, AppError>
+    where
+        F: Fn(&WorkflowStepEvent),
+    {
+        let ffmpeg = self.processors.ffmpeg()?.scratch()?.with_cancellation(operation.cancel_token());
+        let mut state = WorkflowState::default();
+        let mut outcomes = Vec::new();
+
+        for (index, step) in steps.iter().enumerate() {
+            let event = |status: StepStatus, message: Option<String>| WorkflowStepEvent {
+                operation_id: operation.id().to_string(),
+                step_index: index,
+                step_count: steps.len(),
+                name: step.name.clone(),
+                action: step.action,
+                status,
+                message,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            };
+
+            let action = match (step.action, &step.condition) {
+                (None, _) => Err("Manual step".to_string()),
+                (Some(_), Some(condition)) if !state.holds(condition, &self.settings) => Err(format!("Condition not met: {:?}", condition)),
+                (Some(action), _) => Ok(action),
+            };
+            let action = match action {
+                Ok(action) => action,
+                Err(reason) => {
+                    let skipped = event(StepStatus::Skipped, Some(reason));
+                    on_event(&skipped);
+                    outcomes.push(skipped);
+                    continue;
+                }
+            };
+
+            on_event(&event(StepStatus::Started, None));
+            operation.report(&step.name, Some(index as f64 / steps.len() as f64 * 100.0), None);
+            let result = self.run_step(action, step, url, &ffmpeg, &mut state, operation).await;
+            if operation.cancel_token().is_cancelled() {
+                return Err(AppError::cancelled());
+            }
+
+            let finished = match &result {
+                Ok(message) => event(StepStatus::Completed, message.clone()),
+                Err(e) => event(StepStatus::Failed, Some(e.message.clone())),
+            };
+            on_event(&finished);
+            outcomes.push(finished);
+            if let Err(e) = result {
+                if !step.continue_on_error {
+                    return Err(AppError::new(e.code, format!("Workflow step '{}' failed: {}", step.name, e.message)));
+                }
+            }
+        }
+
+        Ok(WorkflowRunResult {
+            url: url.to_string(),
+            highlight_ids: state.highlights.iter().flatten().map(|nugget| nugget.id.clone()).collect(),
+            video_info: state.video_info,
+            nuggets: state.nuggets,
+            analysis: state.analysis,
+            output_files: state.output_files,
+            steps: outcomes,
+            events: state.events,
+        })
+    }
+
+    async fn run_step(
+        &self,
+        action: WorkflowAction,
+        step: &WorkflowStep,
+        url: &str,
+        ffmpeg: &FFmpegProcessor,
+        state: &mut WorkflowState,
+        operation: &OperationHandle,
+    ) -> Result<Option<String>, AppError> {
+        match action {
+            WorkflowAction::Transcribe => {
+                let video_path = self.download(url, ffmpeg, state, operation).await?;
+                let duration = state.video_info.as_ref().map(|info| info.duration).unwrap_or_default();
+                let nugget_duration = number(step, "nugget_duration").unwrap_or(self.settings.default_nugget_duration);
+                let overlap = number(step, "overlap_duration").unwrap_or(self.settings.default_overlap);
+
+                let transcript = if flag(step, "transcript").unwrap_or(self.settings.auto_transcribe) {
+                    let audio_path = ffmpeg.extract_audio(&video_path).await?;
+                    let speech_recognizer = self.processors.speech()?.scratch()?.with_cancellation(operation.cancel_token());
+                    Some(speech_recognizer.transcribe_audio(&audio_path).await?)
+                } else {
+                    None
+                };
+                let segments = transcript.as_ref().map(|transcript| transcript.segments.as_slice()).unwrap_or_default();
+                state.nuggets = nuggets_from_transcript(&state.title(), duration, segments, nugget_duration, overlap);
+                state.transcript = transcript;
+                state.highlights = None;
+                Ok(Some(format!("{} nuggets", state.nuggets.len())))
+            }
+            WorkflowAction::Analyze => {
+                let transcript = state.transcript.as_ref()
+                    .ok_or("Analyze needs a transcript, add a Transcribe step before it")?;
+                let (config, prompt_template) = self.ai.clone()
+                    .ok_or("AI analysis isn't configured")?;
+                let duration = state.video_info.as_ref().map(|info| info.duration).unwrap_or_default();
+
+                let ai_analyzer = AIAnalyzer::new(config)
+                    .with_prompt_template(prompt_template)
+                    .with_video_duration(duration)
+                    .with_circuit_breakers(self.circuit_breakers.clone());
+                let analysis = ai_analyzer.analyze_segments(&transcript.segments, &state.title(), None).await?;
+                state.events.push(ai_analyzer.analysis_event(&analysis));
+                let message = format!("{} topics", analysis.key_topics.len());
+                state.analysis = Some(analysis);
+                Ok(Some(message))
+            }
+            WorkflowAction::DetectHighlights => {
+                if state.nuggets.is_empty() {
+                    return Err("There are no nuggets to pick highlights from, add a Transcribe step before it".into());
+                }
+                let platform: Platform = step.parameters.get("platform")
+                    .map(|platform| serde_json::from_value(platform.clone()))
+                    .transpose()
+                    .map_err(|e| format!("Unknown platform: {}", e))?
+                    .unwrap_or(Platform::TikTok);
+                let min_score = number(step, "min_score").unwrap_or(0.5);
+                let top_n = number(step, "top_n").map(|n| n as usize).unwrap_or(5);
+                let trending_topics = strings(step, "trending_topics").unwrap_or_default();
+
+                let scorer = ViralityScorer::new(trending_topics);
+                let mut scored: Vec<(f64, &VideoNugget)> = state.nuggets.iter()
+                    .map(|nugget| (scorer.score_nugget(nugget, platform, state.analysis.as_ref()).overall, nugget))
+                    .filter(|(score, _)| *score >= min_score)
+                    .collect();
+                scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+                let highlights: Vec<VideoNugget> = scored.into_iter()
+                    .take(top_n)
+                    .map(|(_, nugget)| nugget.clone())
+                    .collect();
+                let message = format!("{} of {} nuggets", highlights.len(), state.nuggets.len());
+                state.highlights = Some(highlights);
+                Ok(Some(message))
+            }
+            WorkflowAction::RenderClips => {
+                let video_path = self.download(url, ffmpeg, state, operation).await?;
+                let nuggets = state.selected(flag(step, "highlights_only"));
+                let social_formats = flag(step, "social_formats").unwrap_or(self.settings.social_media_formats);
+                let clips_dir = self.output_directory.join("clips");
+                std::fs::create_dir_all(&clips_dir)
+                    .map_err(|e| format!("Failed to create clips directory: {}", e))?;
+
+                let title = sanitize_segment(&state.title());
+                for (position, nugget) in nuggets.iter().enumerate() {
+                    operation.progress(position as f64 / nuggets.len() as f64 * 100.0, Some(format!("Clip {} of {}", position + 1, nuggets.len())));
+                    let stem = clips_dir.join(format!("{}_{:02}", title, position + 1)).to_string_lossy().to_string();
+                    for clip in ffmpeg.create_video_clips_at(&video_path, std::slice::from_ref(nugget), &[stem]).await? {
+                        if social_formats {
+                            let formats = ffmpeg.create_social_media_formats(&clip.output_path).await?;
+                            state.output_files.extend([formats.tiktok, formats.instagram, formats.youtube_short]);
+                        }
+                        state.output_files.push(clip.output_path);
+                        state.output_files.extend(clip.thumbnail_path);
+                    }
+                }
+                Ok(Some(format!("{} clips", nuggets.len())))
+            }
+            WorkflowAction::Export => {
+                let formats = strings(step, "formats").unwrap_or_else(|| self.settings.export_formats.clone());
+                let nuggets = state.selected(Some(flag(step, "highlights_only").unwrap_or(false)));
+                std::fs::create_dir_all(&self.output_directory)
+                    .map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+                let stem = self.output_directory.join(sanitize_segment(&state.title()));
+                for format in &formats {
+                    let path = format!("{}.{}", stem.to_string_lossy(), export_extension(format));
+                    let formatting = app_settings::current().defaults.export_formatting;
+                    write_export(nuggets.clone(), format, &path, None, None, None, formatting).await?;
+                    state.events.push(FileManager::export_event(format, &path, nuggets.len()));
+                    state.output_files.push(path);
+                }
+                Ok(Some(format!("{} files", formats.len())))
+            }
+        }
+    }
+
+    // Downloads the video the first time a step needs it; later steps use the same file
+    async fn download(&self, url: &str, ffmpeg: &FFmpegProcessor, state: &mut WorkflowState, operation: &OperationHandle) -> Result<String, AppError> {
+        if let Some(video_path) = &state.video_path {
+            return Ok(video_path.clone());
+        }
+        let quality = app_settings::current().defaults.download_quality;
+        let video_path = ffmpeg.download_video_with_progress(url, &quality, Some(Arc::new(operation.reporter()))).await?;
+        let mut video_info = ffmpeg.get_video_info(&video_path).await?;
+        video_info.url = url.to_string();
+        if let Some(source) = ffmpeg.get_source_metadata(url).await {
+            video_info.title = source.title;
+        }
+        state.video_info = Some(video_info);
+        state.video_path = Some(video_path.clone());
+        Ok(video_path)
+    }
+}
+
+// Nuggets of nugget_duration seconds, each starting overlap seconds before the last one ended,
+// with the transcript segments that fall in them
+fn nuggets_from_transcript(title: &str, duration: f64, segments: &[TranscriptSegment], nugget_duration: f64, overlap: f64) -> Vec<VideoNugget> {
+    let mut nuggets = Vec::new();
+    // An overlap as long as the nugget would never get anywhere
+    let advance = (nugget_duration - overlap).max(1.0);
+    let mut start_time = 0.0;
+
+    while start_time < duration {
+        let end_time = (start_time + nugget_duration).min(duration);
+        let text = segments.iter()
+            .filter(|segment| segment.end_time > start_time && segment.start_time < end_time)
+            .map(|segment| segment.text.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        nuggets.push(VideoNugget {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: format!("{} - Part {}", title, nuggets.len() + 1),
+            start_time,
+            end_time,
+            transcript: (!text.is_empty()).then_some(text),
+            tags: vec!["video-nugget".to_string()],
+            notes: None,
+            youtube_video_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        });
+        if end_time >= duration - 1.0 {
+            break;
+        }
+        start_time += advance;
+    }
+    nuggets
+}
+
+fn export_extension(format: &str) -> &str {
+    match format {
+        "markdown" => "md",
+        "anki" => "apkg",
+        other => other,
+    }
+}
+
+fn number(step: &WorkflowStep, name: &str) -> Option<f64> {
+    step.parameters.get(name).and_then(|value| value.as_f64())
+}
+
+fn flag(step: &WorkflowStep, name: &str) -> Option<bool> {
+    step.parameters.get(name).and_then(|value| value.as_bool())
+}
+
+fn strings(step: &WorkflowStep, name: &str) -> Option<Vec<String>> {
+    step.parameters.get(name)
+        .and_then(|value| value.as_array())
+        .map(|values| values.iter().filter_map(|value| value.as_str().map(str::to_string)).collect())
+}
+
+// Where a run writes when the caller doesn't say: the project's folder, else the workspace's
+pub fn default_output_directory(workspace_root: &Path, project_path: Option<&Path>) -> PathBuf {
+    project_path
+        .map(|path| path.join("workflow"))
+        .unwrap_or_else(|| workspace_root.join("workflow"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_manager::ProjectManager;
+
+    fn segment(start_time: f64, end_time: f64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_time,
+            end_time,
+            text: text.to_string(),
+            confidence: 1.0,
+            speaker_id: None,
+        }
+    }
+
+    #[test]
+    fn test_nuggets_from_transcript() {
+        let segments = [segment(0.0, 8.0, "Hello there."), segment(8.0, 14.0, "Second part."), segment(25.0, 30.0, "The end.")];
+        let nuggets = nuggets_from_transcript("Talk", 30.0, &segments, 12.0, 2.0);
+
+        let spans: Vec<(f64, f64)> = nuggets.iter().map(|n| (n.start_time, n.end_time)).collect();
+        assert_eq!(spans, vec![(0.0, 12.0), (10.0, 22.0), (20.0, 30.0)]);
+        assert_eq!(nuggets[0].transcript.as_deref(), Some("Hello there. Second part."));
+        assert_eq!(nuggets[1].transcript.as_deref(), Some("Second part."));
+        assert_eq!(nuggets[2].title, "Talk - Part 3");
+    }
+
+    #[test]
+    fn test_step_conditions() {
+        let mut settings = ProjectManager::default_settings();
+        settings.social_media_formats = false;
+        let mut state = WorkflowState {
+            nuggets: nuggets_from_transcript("Talk", 60.0, &[], 20.0, 0.0),
+            ..Default::default()
+        };
+
+        assert!(state.holds(&StepCondition::MinNuggets { count: 3 }, &settings));
+        assert!(!state.holds(&StepCondition::MinHighlights { count: 1 }, &settings));
+        assert!(!state.holds(&StepCondition::SettingEnabled { setting: "social_media_formats".to_string() }, &settings));
+        assert!(!state.holds(&StepCondition::HasTranscript, &settings));
+        assert_eq!(state.selected(None).len(), 3);
+
+        state.highlights = Some(vec![state.nuggets[1].clone()]);
+        assert!(state.holds(&StepCondition::MinHighlights { count: 1 }, &settings));
+        assert_eq!(state.selected(None)[0].id, state.nuggets[1].id);
+        assert_eq!(state.selected(Some(false)).len(), 3);
+
+        let condition: StepCondition = serde_json::from_str(r#"{"kind":"MinNuggets","count":2}"#).unwrap();
+        assert_eq!(condition, StepCondition::MinNuggets { count: 2 });
+    }
+}
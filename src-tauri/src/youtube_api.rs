@@ -1,7 +1,17 @@
-use crate::{VideoInfo, youtube_extractor::{VideoChapter, VideoSearchResult}};
+use crate::{VideoInfo, youtube_extractor::VideoSearchResult};
 use reqwest;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+use url::Url;
+use crate::error::{http_error_code, status_error_code, AppError, ErrorCode};
+use crate::youtube_cache::{self, ResponseCache};
+use crate::youtube_quota::{ApiEndpoint, ApiQuota};
+
+// A region's trending chart is read from the cache for this long before YouTube is asked again
+const TRENDING_MAX_AGE_MINUTES: i64 = 60;
+// As far down as YouTube's chart goes
+pub const MAX_TRENDING_VIDEOS: usize = 200;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct YouTubeApiResponse<T> {
@@ -122,10 +132,122 @@ struct CaptionSnippet {
     status: String,
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum YouTubeApiError {
+    #[error("{0}")]
+    MissingApiKey(&'static str),
+    #[error("YouTube rejected the API key")]
+    InvalidApiKey,
+    // The key works, but not from here or not for the Data API
+    #[error("YouTube API key is restricted: {0}")]
+    KeyRestricted(String),
+    #[error("YouTube API quota exceeded")]
+    QuotaExceeded,
+    #[error("YouTube API request failed ({status}): {message}")]
+    Rejected { status: reqwest::StatusCode, message: String },
+    #[error("{0}: {1}")]
+    Http(&'static str, reqwest::Error),
+    #[error("{0}")]
+    NotFound(&'static str),
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<String> for YouTubeApiError {
+    fn from(message: String) -> Self {
+        YouTubeApiError::Other(message)
+    }
+}
+
+impl From<YouTubeApiError> for String {
+    fn from(error: YouTubeApiError) -> Self {
+        error.to_string()
+    }
+}
+
+impl From<YouTubeApiError> for AppError {
+    fn from(error: YouTubeApiError) -> Self {
+        let message = error.to_string();
+        match error {
+            YouTubeApiError::MissingApiKey(_) | YouTubeApiError::InvalidApiKey => AppError::new(ErrorCode::Unauthorized, message)
+                .with_hint("Add a valid YouTube Data API key in Settings"),
+            YouTubeApiError::KeyRestricted(_) => AppError::new(ErrorCode::Unauthorized, message)
+                .with_hint("Check the key's restrictions and that the YouTube Data API is enabled in the Google Cloud console"),
+            YouTubeApiError::QuotaExceeded => AppError::new(ErrorCode::QuotaExceeded, message)
+                .with_hint("The daily quota resets at midnight Pacific time"),
+            YouTubeApiError::Rejected { status, .. } => AppError::new(status_error_code(status), message),
+            YouTubeApiError::Http(_, e) => AppError::new(http_error_code(&e), message),
+            YouTubeApiError::NotFound(_) => AppError::new(ErrorCode::NotFound, message),
+            YouTubeApiError::InvalidInput(_) => AppError::new(ErrorCode::InvalidInput, message),
+            YouTubeApiError::Other(_) => AppError::new(ErrorCode::Unknown, message),
+        }
+    }
+}
+
+// Passes successful responses through; YouTube's error body says why the others failed
+pub(crate) async fn check_response(response: reqwest::Response) -> Result<reqwest::Response, YouTubeApiError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    Err(api_error(status, &body))
+}
+
+// Older responses give the reason in errors, newer ones only name key problems in details
+fn api_error(status: reqwest::StatusCode, body: &serde_json::Value) -> YouTubeApiError {
+    let error = &body["error"];
+    let message = error["message"].as_str().unwrap_or_default().to_string();
+    let reason = error["errors"][0]["reason"].as_str().unwrap_or_default();
+    let detail = error["details"].as_array()
+        .into_iter()
+        .flatten()
+        .find_map(|detail| detail["reason"].as_str())
+        .unwrap_or_default();
+    match (reason, detail) {
+        ("quotaExceeded" | "dailyLimitExceeded" | "rateLimitExceeded", _) => YouTubeApiError::QuotaExceeded,
+        ("keyInvalid" | "keyExpired", _) | (_, "API_KEY_INVALID" | "API_KEY_EXPIRED") => YouTubeApiError::InvalidApiKey,
+        // Referrer, IP, app or API restrictions on the key, or the Data API not enabled for its project
+        ("ipRefererBlocked" | "accessNotConfigured", _) | (_, "SERVICE_DISABLED") => YouTubeApiError::KeyRestricted(message),
+        (_, detail) if detail.starts_with("API_KEY_") && detail.ends_with("_BLOCKED") => YouTubeApiError::KeyRestricted(message),
+        _ => YouTubeApiError::Rejected { status, message },
+    }
+}
+
+// What validate_api_key found out about the key
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ApiKeyStatus {
+    Valid,
+    Missing,
+    Invalid,
+    Restricted { message: String },
+    QuotaExhausted,
+    // YouTube couldn't be reached or answered with something else, so the key may still be fine
+    Unknown { message: String },
+}
+
+impl ApiKeyStatus {
+    fn from_check(result: Result<(), YouTubeApiError>) -> Self {
+        match result {
+            Ok(()) => ApiKeyStatus::Valid,
+            Err(YouTubeApiError::MissingApiKey(_)) => ApiKeyStatus::Missing,
+            Err(YouTubeApiError::InvalidApiKey) => ApiKeyStatus::Invalid,
+            Err(YouTubeApiError::KeyRestricted(message)) => ApiKeyStatus::Restricted { message },
+            Err(YouTubeApiError::QuotaExceeded) => ApiKeyStatus::QuotaExhausted,
+            Err(e) => ApiKeyStatus::Unknown { message: e.to_string() },
+        }
+    }
+}
+
 pub struct YouTubeAPI {
     client: reqwest::Client,
     api_key: Option<String>,
     base_url: String,
+    quota: Option<Arc<ApiQuota>>,
+    cache: Option<ResponseCache>,
 }
 
 impl YouTubeAPI {
@@ -134,10 +256,42 @@ impl YouTubeAPI {
             client: reqwest::Client::new(),
             api_key,
             base_url: "https://www.googleapis.com/youtube/v3".to_string(),
+            quota: None,
+            cache: None,
         }
     }
 
-    pub async fn get_video_info(&self, video_id: &str) -> Result<VideoInfo, String> {
+    // Every request is counted against the day's quota, and refused once it wouldn't fit
+    pub fn with_quota(mut self, quota: Arc<ApiQuota>) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    // The cheapest call that needs a valid key, one quota unit. It goes out even when the app's
+    // own tally says the quota is gone, so YouTube is the one to say whether it is
+    pub async fn check_api_key(&self) -> Result<(), YouTubeApiError> {
+        let api_key = self.api_key
+            .as_ref()
+            .ok_or(YouTubeApiError::MissingApiKey("YouTube API key not provided"))?;
+        let url = self.api_url("i18nLanguages", &[("part", "snippet"), ("hl", "en")], api_key)?;
+
+        let response = self.dispatch(ApiEndpoint::I18nLanguages, self.client.get(url), "Failed to reach YouTube").await?;
+        check_response(response).await?;
+        Ok(())
+    }
+
+    // check_api_key for the settings form, which shows a bad key rather than failing on it
+    pub async fn validate_api_key(&self) -> ApiKeyStatus {
+        ApiKeyStatus::from_check(self.check_api_key().await)
+    }
+
+    pub async fn get_video_info(&self, video_id: &str) -> Result<VideoInfo, YouTubeApiError> {
+        let video_id = checked_id("video ID", video_id)?;
         if let Some(ref api_key) = self.api_key {
             self.get_video_info_with_api(video_id, api_key).await
         } else {
@@ -145,25 +299,9 @@ impl YouTubeAPI {
         }
     }
 
-    async fn get_video_info_with_api(&self, video_id: &str, api_key: &str) -> Result<VideoInfo, String> {
-        let url = format!(
-            "{}/videos?part=snippet,contentDetails,statistics&id={}&key={}",
-            self.base_url, video_id, api_key
-        );
-
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to fetch video info: {}", e))?;
-
-        if !response.status().is_success() {
-            return Err(format!("API request failed with status: {}", response.status()));
-        }
-
-        let api_response: YouTubeApiResponse<YouTubeVideo> = response
-            .json()
-            .await
+    async fn get_video_info_with_api(&self, video_id: &str, api_key: &str) -> Result<VideoInfo, YouTubeApiError> {
+        let body = self.get_cached_json(ApiEndpoint::Videos, &self.video_url(video_id, api_key)?, "Failed to fetch video info").await?;
+        let api_response: YouTubeApiResponse<YouTubeVideo> = serde_json::from_value(body)
             .map_err(|e| format!("Failed to parse API response: {}", e))?;
 
         if let Some(video) = api_response.items.first() {
@@ -185,11 +323,44 @@ impl YouTubeAPI {
                 thumbnail,
             })
         } else {
-            Err("Video not found".to_string())
+            Err(YouTubeApiError::NotFound("Video not found"))
         }
     }
 
-    async fn get_video_info_fallback(&self, video_id: &str) -> Result<VideoInfo, String> {
+    // get_video_info and get_video_category share the request, and so its cache entry
+    fn video_url(&self, video_id: &str, api_key: &str) -> Result<Url, YouTubeApiError> {
+        self.api_url("videos", &[("part", "snippet,contentDetails,statistics"), ("id", checked_id("video ID", video_id)?)], api_key)
+    }
+
+    // The name of the video's category, "Education" rather than its categoryId "27", as
+    // YouTube lists it for region_code
+    pub async fn get_video_category(&self, video_id: &str, region_code: &str) -> Result<Option<String>, YouTubeApiError> {
+        let api_key = self.api_key
+            .as_ref()
+            .ok_or(YouTubeApiError::MissingApiKey("API key required for video categories"))?;
+
+        let body = self.get_cached_json(ApiEndpoint::Videos, &self.video_url(video_id, api_key)?, "Failed to fetch video info").await?;
+        let Some(category_id) = body["items"][0]["snippet"]["categoryId"].as_str() else {
+            return Ok(None);
+        };
+        let mut categories = self.get_video_categories(region_code).await?;
+        Ok(categories.remove(category_id))
+    }
+
+    // Category names by id for a region. The list hardly ever changes, so it's cached like
+    // video info, one entry per region
+    pub async fn get_video_categories(&self, region_code: &str) -> Result<HashMap<String, String>, YouTubeApiError> {
+        let api_key = self.api_key
+            .as_ref()
+            .ok_or(YouTubeApiError::MissingApiKey("API key required for video categories"))?;
+
+        let region_code = checked_region(region_code)?;
+        let url = self.api_url("videoCategories", &[("part", "snippet"), ("regionCode", &region_code), ("hl", "en")], api_key)?;
+        let body = self.get_cached_json(ApiEndpoint::VideoCategories, &url, "Failed to get video categories").await?;
+        Ok(parse_categories(&body))
+    }
+
+    async fn get_video_info_fallback(&self, video_id: &str) -> Result<VideoInfo, YouTubeApiError> {
         // Fallback method without API - scrape from YouTube page
         let url = format!("https://www.youtube.com/watch?v={}", video_id);
         
@@ -197,12 +368,12 @@ impl YouTubeAPI {
             .get(&url)
             .send()
             .await
-            .map_err(|e| format!("Failed to fetch video page: {}", e))?;
+            .map_err(|e| YouTubeApiError::Http("Failed to fetch video page", e))?;
 
         let html = response
             .text()
             .await
-            .map_err(|e| format!("Failed to get page content: {}", e))?;
+            .map_err(|e| YouTubeApiError::Http("Failed to get page content", e))?;
 
         // Extract title from HTML
         let title = self.extract_title_from_html(&html)?;
@@ -218,7 +389,7 @@ impl YouTubeAPI {
         })
     }
 
-    fn extract_title_from_html(&self, html: &str) -> Result<String, String> {
+    fn extract_title_from_html(&self, html: &str) -> Result<String, YouTubeApiError> {
         use regex::Regex;
         
         let title_regex = Regex::new(r#"<title>([^<]+) - YouTube</title>"#)
@@ -243,7 +414,7 @@ impl YouTubeAPI {
         }
     }
 
-    fn parse_youtube_duration(duration: &str) -> Result<f64, String> {
+    fn parse_youtube_duration(duration: &str) -> Result<f64, YouTubeApiError> {
         use regex::Regex;
         
         // YouTube duration format: PT#H#M#S or PT#M#S or PT#S
@@ -257,64 +428,61 @@ impl YouTubeAPI {
 
             Ok(hours * 3600.0 + minutes * 60.0 + seconds)
         } else {
-            Err("Invalid duration format".to_string())
+            Err("Invalid duration format".to_string().into())
         }
     }
 
-    pub async fn get_video_transcript(&self, video_id: &str) -> Result<String, String> {
+    // The captions in language, or else the first track machine-translated to it; without a
+    // language, English or the first track there is
+    pub async fn get_video_transcript(&self, video_id: &str, language: Option<&str>) -> Result<String, YouTubeApiError> {
+        let video_id = checked_id("video ID", video_id)?;
         if let Some(ref api_key) = self.api_key {
-            self.get_transcript_with_api(video_id, api_key).await
+            self.get_transcript_with_api(video_id, language, api_key).await
         } else {
             self.get_transcript_fallback(video_id).await
         }
     }
 
-    async fn get_transcript_with_api(&self, video_id: &str, api_key: &str) -> Result<String, String> {
+    async fn get_transcript_with_api(&self, video_id: &str, language: Option<&str>, api_key: &str) -> Result<String, YouTubeApiError> {
         // First, get list of caption tracks
-        let captions_url = format!(
-            "{}/captions?part=snippet&videoId={}&key={}",
-            self.base_url, video_id, api_key
-        );
+        let captions_url = self.api_url("captions", &[("part", "snippet"), ("videoId", video_id)], api_key)?;
 
-        let response = self.client
-            .get(&captions_url)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to fetch captions list: {}", e))?;
-
-        let captions_response: YouTubeApiResponse<CaptionTrack> = response
-            .json()
-            .await
+        let body = self.get_cached_json(ApiEndpoint::Captions, &captions_url, "Failed to fetch captions list").await?;
+        let captions_response: YouTubeApiResponse<CaptionTrack> = serde_json::from_value(body)
             .map_err(|e| format!("Failed to parse captions response: {}", e))?;
 
-        // Find English captions
-        let caption_track = captions_response.items
+        // Tracks in the language, the creator's ahead of speech recognition's
+        let wanted = language.unwrap_or("en");
+        let mut in_language: Vec<&CaptionTrack> = captions_response.items
             .iter()
-            .find(|track| track.snippet.language == "en")
-            .or_else(|| captions_response.items.first())
-            .ok_or("No captions available")?;
+            .filter(|track| track.snippet.language.eq_ignore_ascii_case(wanted))
+            .collect();
+        in_language.sort_by_key(|track| track.snippet.track_kind.eq_ignore_ascii_case("asr"));
+        let first = captions_response.items.first().ok_or(YouTubeApiError::NotFound("No captions available"))?;
+        let (caption_track, translate_to) = match in_language.first() {
+            Some(track) => (*track, None),
+            None => (first, language),
+        };
 
         // Download caption content
-        let caption_url = format!(
-            "{}/captions/{}?key={}",
-            self.base_url, caption_track.id, api_key
-        );
+        let mut params = Vec::new();
+        if let Some(language) = translate_to {
+            params.push(("tlang", checked_id("caption language", language)?));
+        }
+        let caption_url = self.api_url(&format!("captions/{}", checked_id("caption track ID", &caption_track.id)?), &params, api_key)?;
 
-        let caption_response = self.client
-            .get(&caption_url)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to download captions: {}", e))?;
+        let caption_response = self.send(ApiEndpoint::CaptionDownload, self.client.get(caption_url), "Failed to download captions").await?;
+        let caption_response = check_response(caption_response).await?;
 
         let transcript = caption_response
             .text()
             .await
-            .map_err(|e| format!("Failed to get caption text: {}", e))?;
+            .map_err(|e| YouTubeApiError::Http("Failed to get caption text", e))?;
 
         Ok(self.clean_transcript(&transcript))
     }
 
-    async fn get_transcript_fallback(&self, video_id: &str) -> Result<String, String> {
+    async fn get_transcript_fallback(&self, video_id: &str) -> Result<String, YouTubeApiError> {
         // Fallback: try to get auto-generated captions or use external service
         // This is a placeholder - you would need to implement actual transcript extraction
         Ok(format!("Transcript for video {} (fallback method)", video_id))
@@ -332,133 +500,800 @@ impl YouTubeAPI {
         whitespace_regex.replace_all(&cleaned, " ").trim().to_string()
     }
 
-    pub async fn search_videos(&self, query: &str, max_results: u32) -> Result<Vec<VideoSearchResult>, String> {
+    // One page of results; pass next_page_token back as page_token for the page after it
+    pub async fn search_videos(&self, query: &str, filters: &SearchFilters, max_results: u32, page_token: Option<&str>) -> Result<SearchPage, YouTubeApiError> {
         let api_key = self.api_key
             .as_ref()
-            .ok_or("API key required for search functionality")?;
-
-        let url = format!(
-            "{}/search?part=snippet&type=video&q={}&maxResults={}&key={}",
-            self.base_url,
-            urlencoding::encode(query),
-            max_results,
-            api_key
-        );
+            .ok_or(YouTubeApiError::MissingApiKey("API key required for search functionality"))?;
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to search videos: {}", e))?;
+        if query.trim().is_empty() {
+            return Err(YouTubeApiError::InvalidInput("Search query is empty".to_string()));
+        }
+        let mut url = self.api_url("search", &[
+            ("part", "snippet"),
+            ("type", "video"),
+            ("q", query.trim()),
+            ("maxResults", &max_results.to_string()),
+        ], api_key)?;
+        url.query_pairs_mut().extend_pairs(filters.query_params()?);
+        self.search_page(ApiEndpoint::Search, url, page_token, api_key, "Failed to search videos").await
+    }
 
-        let search_response: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse search response: {}", e))?;
-
-        let mut results = Vec::new();
-
-        if let Some(items) = search_response.get("items").and_then(|i| i.as_array()) {
-            for item in items {
-                if let Some(snippet) = item.get("snippet") {
-                    let video_id = item.get("id")
-                        .and_then(|id| id.get("videoId"))
-                        .and_then(|vid| vid.as_str())
-                        .unwrap_or("")
-                        .to_string();
-
-                    let title = snippet.get("title")
-                        .and_then(|t| t.as_str())
-                        .unwrap_or("")
-                        .to_string();
-
-                    let channel = snippet.get("channelTitle")
-                        .and_then(|c| c.as_str())
-                        .unwrap_or("")
-                        .to_string();
-
-                    let thumbnail = snippet.get("thumbnails")
-                        .and_then(|t| t.get("high"))
-                        .and_then(|h| h.get("url"))
-                        .and_then(|u| u.as_str())
-                        .unwrap_or("")
-                        .to_string();
-
-                    results.push(VideoSearchResult {
-                        video_id,
-                        title,
-                        channel,
-                        duration: 0.0, // Would need separate API call to get duration
-                        thumbnail,
-                    });
-                }
-            }
+    // Newest first
+    pub async fn get_channel_videos(&self, channel_id: &str, max_results: u32, page_token: Option<&str>) -> Result<SearchPage, YouTubeApiError> {
+        let api_key = self.api_key
+            .as_ref()
+            .ok_or(YouTubeApiError::MissingApiKey("API key required for channel video listing"))?;
+
+        let url = self.api_url("search", &[
+            ("part", "snippet"),
+            ("type", "video"),
+            ("channelId", checked_id("channel ID", channel_id)?),
+            ("maxResults", &max_results.to_string()),
+            ("order", "date"),
+        ], api_key)?;
+        self.search_page(ApiEndpoint::Search, url, page_token, api_key, "Failed to get channel videos").await
+    }
+
+    // search.list has no durations, so they come from a videos.list call for the whole page
+    async fn search_page(&self, endpoint: ApiEndpoint, url: Url, page_token: Option<&str>, api_key: &str, context: &'static str) -> Result<SearchPage, YouTubeApiError> {
+        let body = self.get_json(endpoint, &with_page_token(url, page_token)?, context).await?;
+        let mut page = parse_search_results(&body);
+        if page.results.is_empty() {
+            return Ok(page);
         }
 
-        Ok(results)
+        let ids: Vec<&str> = page.results.iter().map(|result| result.video_id.as_str()).collect();
+        let url = self.api_url("videos", &[
+            ("part", "contentDetails"),
+            ("id", &ids.join(",")),
+            ("maxResults", &ids.len().to_string()),
+        ], api_key)?;
+        let details = self.get_cached_json(ApiEndpoint::Videos, &url, "Failed to get video durations").await?;
+        let durations = parse_durations(&details);
+        for result in &mut page.results {
+            if let Some(duration) = durations.get(&result.video_id) {
+                result.duration = *duration;
+            }
+        }
+        Ok(page)
     }
 
-    pub async fn get_channel_videos(&self, channel_id: &str, max_results: u32) -> Result<Vec<VideoSearchResult>, String> {
+    // The region's most popular videos, all of them or one category's (an ID from
+    // get_video_categories), with durations; cached for an hour
+    pub async fn get_trending_videos(&self, region_code: &str, category_id: Option<&str>, max_results: u32, page_token: Option<&str>) -> Result<SearchPage, YouTubeApiError> {
         let api_key = self.api_key
             .as_ref()
-            .ok_or("API key required for channel video listing")?;
-
-        let url = format!(
-            "{}/search?part=snippet&type=video&channelId={}&maxResults={}&order=date&key={}",
-            self.base_url,
-            channel_id,
-            max_results,
-            api_key
-        );
+            .ok_or(YouTubeApiError::MissingApiKey("API key required for trending videos"))?;
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to get channel videos: {}", e))?;
+        let region_code = checked_region(region_code)?;
+        let max_results = max_results.to_string();
+        let mut params = vec![
+            ("part", "snippet,contentDetails"),
+            ("chart", "mostPopular"),
+            ("regionCode", region_code.as_str()),
+            ("maxResults", max_results.as_str()),
+        ];
+        if let Some(category_id) = category_id {
+            params.push(("videoCategoryId", checked_id("category ID", category_id)?));
+        }
+        let url = with_page_token(self.api_url("videos", &params, api_key)?, page_token)?;
+        let max_age = chrono::Duration::minutes(TRENDING_MAX_AGE_MINUTES);
+        let body = self.get_cached_json_within(ApiEndpoint::Videos, &url, "Failed to get trending videos", max_age).await?;
+        Ok(parse_video_results(&body))
+    }
 
-        // Similar processing to search_videos
-        self.parse_search_results(response).await
+    // The top count trending videos, a page of 50 to a quota unit
+    pub async fn get_top_trending(&self, region_code: &str, category_id: Option<&str>, count: usize) -> Result<Vec<VideoSearchResult>, YouTubeApiError> {
+        let count = count.min(MAX_TRENDING_VIDEOS);
+        let mut videos: Vec<VideoSearchResult> = Vec::new();
+        let mut page_token: Option<String> = None;
+        while videos.len() < count {
+            let page = self.get_trending_videos(region_code, category_id, 50, page_token.as_deref()).await?;
+            videos.extend(page.results);
+            page_token = page.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+        videos.truncate(count);
+        Ok(videos)
     }
 
-    async fn parse_search_results(&self, response: reqwest::Response) -> Result<Vec<VideoSearchResult>, String> {
-        // Implementation similar to search_videos
-        // This is a helper method to avoid code duplication
-        Ok(vec![]) // Placeholder
+    // Top-level comments, most relevant first as YouTube ranks them, up to max_results. Replies
+    // aren't included, only their count
+    pub async fn get_video_comments(&self, video_id: &str, max_results: u32) -> Result<Vec<VideoComment>, YouTubeApiError> {
+        let api_key = self.api_key
+            .as_ref()
+            .ok_or(YouTubeApiError::MissingApiKey("API key required for comments"))?;
+
+        let video_id = checked_id("video ID", video_id)?;
+        let mut comments = Vec::new();
+        let mut page_token: Option<String> = None;
+        while comments.len() < max_results as usize {
+            let page_size = (max_results as usize - comments.len()).min(100);
+            let url = self.api_url("commentThreads", &[
+                ("part", "snippet"),
+                ("videoId", video_id),
+                ("order", "relevance"),
+                ("textFormat", "plainText"),
+                ("maxResults", &page_size.to_string()),
+            ], api_key)?;
+            let body = self.get_json(ApiEndpoint::CommentThreads, &with_page_token(url, page_token.as_deref())?, "Failed to get comments").await?;
+            comments.extend(parse_comments(&body));
+            page_token = body["nextPageToken"].as_str().map(str::to_string);
+            if page_token.is_none() {
+                break;
+            }
+        }
+        comments.truncate(max_results as usize);
+        Ok(comments)
     }
 
-    pub async fn get_trending_videos(&self, region_code: &str, max_results: u32) -> Result<Vec<VideoSearchResult>, String> {
+    // Every video in the playlist in playlist order, 50 to a quota unit. Private and deleted
+    // videos are left out, they can't be processed
+    pub async fn get_playlist_items(&self, playlist_id: &str) -> Result<Vec<PlaylistItem>, YouTubeApiError> {
         let api_key = self.api_key
             .as_ref()
-            .ok_or("API key required for trending videos")?;
-
-        let url = format!(
-            "{}/videos?part=snippet,contentDetails&chart=mostPopular&regionCode={}&maxResults={}&key={}",
-            self.base_url,
-            region_code,
-            max_results,
-            api_key
-        );
+            .ok_or(YouTubeApiError::MissingApiKey("API key required for playlists"))?;
 
-        let response = self.client
-            .get(&url)
+        let url = self.playlist_url(playlist_id, api_key)?;
+        let mut items = Vec::new();
+        let mut page_token: Option<String> = None;
+        for _ in 0..MAX_PLAYLIST_PAGES {
+            let body = self.get_json(ApiEndpoint::PlaylistItems, &with_page_token(url.clone(), page_token.as_deref())?, "Failed to list playlist").await?;
+            items.extend(parse_playlist_items(&body));
+            page_token = body["nextPageToken"].as_str().map(str::to_string);
+            if page_token.is_none() {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    // A channel's uploads published after published_after, from the first page of its uploads
+    // playlist, newest first. search.list can filter by publishedAfter too but costs 100 units a
+    // call, this costs one; when the ETag from the last check still matches, nothing was
+    // uploaded and nothing is downloaded
+    pub async fn get_new_uploads(&self, uploads_playlist_id: &str, etag: Option<&str>, published_after: Option<&str>) -> Result<UploadsCheck, YouTubeApiError> {
+        let api_key = self.api_key
+            .as_ref()
+            .ok_or(YouTubeApiError::MissingApiKey("API key required for channel monitoring"))?;
+
+        let mut request = self.client.get(self.playlist_url(uploads_playlist_id, api_key)?);
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let context = "Failed to check for new uploads";
+        let response = self.send(ApiEndpoint::PlaylistItems, request, context).await?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(UploadsCheck::Unchanged);
+        }
+
+        let response = check_response(response).await?;
+        let etag = response_etag(&response);
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| YouTubeApiError::Http(context, e))?;
+        Ok(UploadsCheck::Changed {
+            etag,
+            uploads: uploads_after(parse_playlist_items(&body), published_after),
+        })
+    }
+
+    fn playlist_url(&self, playlist_id: &str, api_key: &str) -> Result<Url, YouTubeApiError> {
+        self.api_url("playlistItems", &[
+            ("part", "snippet,contentDetails,status"),
+            ("playlistId", checked_id("playlist ID", playlist_id)?),
+            ("maxResults", "50"),
+        ], api_key)
+    }
+
+    // Parameters are percent-encoded as UTF-8, so queries in any language arrive intact
+    fn api_url(&self, resource: &str, params: &[(&str, &str)], api_key: &str) -> Result<Url, YouTubeApiError> {
+        let mut url = Url::parse(&format!("{}/{}", self.base_url, resource))
+            .map_err(|e| YouTubeApiError::Other(format!("Invalid YouTube API URL: {}", e)))?;
+        url.query_pairs_mut()
+            .extend_pairs(params)
+            .append_pair("key", api_key);
+        Ok(url)
+    }
+
+    async fn get_json(&self, endpoint: ApiEndpoint, url: &Url, context: &'static str) -> Result<serde_json::Value, YouTubeApiError> {
+        let response = self.send(endpoint, self.client.get(url.clone()), context).await?;
+        let response = check_response(response).await?;
+        response
+            .json()
+            .await
+            .map_err(|e| YouTubeApiError::Http(context, e))
+    }
+
+    // For responses that rarely change. A cached one from today is used without asking YouTube;
+    // an older one is revalidated with its ETag, which still costs quota but not the download
+    async fn get_cached_json(&self, endpoint: ApiEndpoint, url: &Url, context: &'static str) -> Result<serde_json::Value, YouTubeApiError> {
+        self.get_cached_json_within(endpoint, url, context, chrono::Duration::hours(youtube_cache::MAX_AGE_HOURS)).await
+    }
+
+    async fn get_cached_json_within(&self, endpoint: ApiEndpoint, url: &Url, context: &'static str, max_age: chrono::Duration) -> Result<serde_json::Value, YouTubeApiError> {
+        let Some(cache) = &self.cache else {
+            return self.get_json(endpoint, url, context).await;
+        };
+        let cached = cache.get(url.as_str());
+        if let Some(cached) = cached.as_ref().filter(|cached| cached.is_fresh_within(max_age)) {
+            return Ok(cached.body.clone());
+        }
+
+        let mut request = self.client.get(url.clone());
+        if let Some(etag) = cached.as_ref().and_then(|cached| cached.etag.as_deref()) {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let response = self.send(endpoint, request, context).await?;
+        if let (reqwest::StatusCode::NOT_MODIFIED, Some(cached)) = (response.status(), cached) {
+            cache.put(url.as_str(), cached.etag, &cached.body);
+            return Ok(cached.body);
+        }
+
+        let response = check_response(response).await?;
+        let etag = response_etag(&response);
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| YouTubeApiError::Http(context, e))?;
+        cache.put(url.as_str(), etag, &body);
+        Ok(body)
+    }
+
+    async fn send(&self, endpoint: ApiEndpoint, request: reqwest::RequestBuilder, context: &'static str) -> Result<reqwest::Response, YouTubeApiError> {
+        if let Some(quota) = &self.quota {
+            if !quota.allows(endpoint) {
+                return Err(YouTubeApiError::QuotaExceeded);
+            }
+        }
+        self.dispatch(endpoint, request, context).await
+    }
+
+    async fn dispatch(&self, endpoint: ApiEndpoint, request: reqwest::RequestBuilder, context: &'static str) -> Result<reqwest::Response, YouTubeApiError> {
+        let response = request
             .send()
             .await
-            .map_err(|e| format!("Failed to get trending videos: {}", e))?;
+            .map_err(|e| YouTubeApiError::Http(context, e))?;
+        if let Some(quota) = &self.quota {
+            quota.record(endpoint);
+        }
+        Ok(response)
+    }
+}
+
+fn response_etag(response: &reqwest::Response) -> Option<String> {
+    response.headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(str::to_string)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DurationFilter {
+    // Under 4 minutes
+    Short,
+    // 4 to 20 minutes
+    Medium,
+    // Over 20 minutes
+    Long,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionFilter {
+    // Has captions the uploader added, auto-generated ones don't count
+    Captioned,
+    Uncaptioned,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LicenseFilter {
+    CreativeCommons,
+    // The standard YouTube license
+    Standard,
+}
 
-        // Parse trending videos response
-        Ok(vec![]) // Placeholder
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchOrder {
+    #[default]
+    Relevance,
+    Date,
+    ViewCount,
+    Rating,
+    Title,
+}
+
+// Narrows search_videos; every filter left out matches everything
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SearchFilters {
+    pub duration: Option<DurationFilter>,
+    // RFC 3339 or YYYY-MM-DD, which means midnight UTC
+    pub published_after: Option<String>,
+    pub published_before: Option<String>,
+    pub captions: Option<CaptionFilter>,
+    pub license: Option<LicenseFilter>,
+    pub order: SearchOrder,
+}
+
+impl SearchFilters {
+    // search.list parameters, not yet encoded
+    fn query_params(&self) -> Result<Vec<(&'static str, String)>, YouTubeApiError> {
+        let mut params = Vec::new();
+        if let Some(duration) = self.duration {
+            let value = match duration {
+                DurationFilter::Short => "short",
+                DurationFilter::Medium => "medium",
+                DurationFilter::Long => "long",
+            };
+            params.push(("videoDuration", value.to_string()));
+        }
+        let published_after = self.published_after.as_deref().map(search_date).transpose()?;
+        let published_before = self.published_before.as_deref().map(search_date).transpose()?;
+        if let (Some(after), Some(before)) = (&published_after, &published_before) {
+            if after >= before {
+                return Err(YouTubeApiError::InvalidInput("Upload date range is empty".to_string()));
+            }
+        }
+        if let Some(after) = published_after {
+            params.push(("publishedAfter", after.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)));
+        }
+        if let Some(before) = published_before {
+            params.push(("publishedBefore", before.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)));
+        }
+        if let Some(captions) = self.captions {
+            let value = match captions {
+                CaptionFilter::Captioned => "closedCaption",
+                CaptionFilter::Uncaptioned => "none",
+            };
+            params.push(("videoCaption", value.to_string()));
+        }
+        if let Some(license) = self.license {
+            let value = match license {
+                LicenseFilter::CreativeCommons => "creativeCommon",
+                LicenseFilter::Standard => "youtube",
+            };
+            params.push(("videoLicense", value.to_string()));
+        }
+        let order = match self.order {
+            SearchOrder::Relevance => "relevance",
+            SearchOrder::Date => "date",
+            SearchOrder::ViewCount => "viewCount",
+            SearchOrder::Rating => "rating",
+            SearchOrder::Title => "title",
+        };
+        params.push(("order", order.to_string()));
+        Ok(params)
+    }
+}
+
+fn search_date(date: &str) -> Result<chrono::DateTime<chrono::Utc>, YouTubeApiError> {
+    let date = date.trim();
+    chrono::DateTime::parse_from_rfc3339(date)
+        .map(|date| date.with_timezone(&chrono::Utc))
+        .or_else(|_| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(|day| day.and_time(chrono::NaiveTime::MIN).and_utc()))
+        .map_err(|_| YouTubeApiError::InvalidInput(format!("Invalid upload date: {}", date)))
+}
+
+// A page of search, channel or trending results
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchPage {
+    pub results: Vec<VideoSearchResult>,
+    // None on the last page
+    pub next_page_token: Option<String>,
+    // YouTube's estimate, often far from exact for searches
+    pub total_results: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoComment {
+    pub author: String,
+    pub text: String,
+    pub like_count: u64,
+    pub reply_count: u64,
+    pub published_at: String,
+}
+
+fn parse_comments(body: &serde_json::Value) -> Vec<VideoComment> {
+    body["items"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let snippet = &item["snippet"]["topLevelComment"]["snippet"];
+            let text = snippet["textDisplay"].as_str().or_else(|| snippet["textOriginal"].as_str())?;
+            Some(VideoComment {
+                author: snippet["authorDisplayName"].as_str().unwrap_or_default().to_string(),
+                text: text.to_string(),
+                like_count: snippet["likeCount"].as_u64().unwrap_or(0),
+                reply_count: item["snippet"]["totalReplyCount"].as_u64().unwrap_or(0),
+                published_at: snippet["publishedAt"].as_str().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+// YouTube stops listing a playlist at 20,000 videos anyway
+const MAX_PLAYLIST_PAGES: usize = 400;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaylistItem {
+    pub video_id: String,
+    pub title: String,
+    // From 0, in playlist order
+    pub position: u32,
+    // When the video was published, not when it was added to the playlist
+    pub published_at: Option<String>,
+    pub channel: Option<String>,
+}
+
+impl PlaylistItem {
+    pub fn url(&self) -> String {
+        format!("https://www.youtube.com/watch?v={}", self.video_id)
     }
 }
 
-// Add URL encoding dependency
-mod urlencoding {
-    pub fn encode(input: &str) -> String {
-        input.chars()
-            .map(|c| match c {
-                'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '_' | '.' | '~' => c.to_string(),
-                _ => format!("%{:02X}", c as u8),
+fn parse_playlist_items(body: &serde_json::Value) -> Vec<PlaylistItem> {
+    body["items"].as_array()
+        .into_iter()
+        .flatten()
+        // Deleted videos have an unspecified privacy status
+        .filter(|item| matches!(item["status"]["privacyStatus"].as_str(), Some("public" | "unlisted") | None))
+        .filter_map(|item| {
+            let snippet = &item["snippet"];
+            let video_id = item["contentDetails"]["videoId"].as_str()
+                .or_else(|| snippet["resourceId"]["videoId"].as_str())?;
+            Some(PlaylistItem {
+                video_id: video_id.to_string(),
+                title: snippet["title"].as_str().unwrap_or_default().to_string(),
+                position: snippet["position"].as_u64().unwrap_or(0) as u32,
+                published_at: item["contentDetails"]["videoPublishedAt"].as_str().map(str::to_string),
+                channel: snippet["videoOwnerChannelTitle"].as_str().map(str::to_string),
             })
-            .collect()
+        })
+        .collect()
+}
+
+pub enum UploadsCheck {
+    // The ETag still matched
+    Unchanged,
+    // Something in the playlist changed, though not necessarily a new upload
+    Changed { etag: Option<String>, uploads: Vec<PlaylistItem> },
+}
+
+// Everything when there's no cutoff; uploads without a publish time, like premieres that haven't
+// started, are left for a later check
+fn uploads_after(items: Vec<PlaylistItem>, published_after: Option<&str>) -> Vec<PlaylistItem> {
+    let Some(cutoff) = published_after.and_then(|cutoff| chrono::DateTime::parse_from_rfc3339(cutoff).ok()) else {
+        return items;
+    };
+    items.into_iter()
+        .filter(|item| item.published_at.as_deref()
+            .and_then(|published_at| chrono::DateTime::parse_from_rfc3339(published_at).ok())
+            .is_some_and(|published_at| published_at > cutoff))
+        .collect()
+}
+
+fn parse_categories(body: &serde_json::Value) -> HashMap<String, String> {
+    body["items"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| Some((item["id"].as_str()?.to_string(), item["snippet"]["title"].as_str()?.to_string())))
+        .collect()
+}
+
+fn with_page_token(mut url: Url, page_token: Option<&str>) -> Result<Url, YouTubeApiError> {
+    if let Some(token) = page_token.filter(|token| !token.is_empty()) {
+        url.query_pairs_mut().append_pair("pageToken", checked_id("page token", token)?);
+    }
+    Ok(url)
+}
+
+// Video, channel, playlist and caption IDs and page tokens are all letters, digits, - and _;
+// anything else can't match and would only spend quota
+fn checked_id<'a>(kind: &str, id: &'a str) -> Result<&'a str, YouTubeApiError> {
+    let id = id.trim();
+    if id.is_empty() || id.len() > 256 || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(YouTubeApiError::InvalidInput(format!("Invalid {}: {}", kind, id)));
+    }
+    Ok(id)
+}
+
+// ISO 3166-1 alpha-2, "US"
+fn checked_region(region_code: &str) -> Result<String, YouTubeApiError> {
+    let region_code = region_code.trim();
+    if region_code.len() != 2 || !region_code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(YouTubeApiError::InvalidInput(format!("Invalid region code: {}", region_code)));
     }
-}
\ No newline at end of file
+    Ok(region_code.to_ascii_uppercase())
+}
+
+fn page_of(body: &serde_json::Value, results: Vec<VideoSearchResult>) -> SearchPage {
+    SearchPage {
+        results,
+        next_page_token: body["nextPageToken"].as_str().map(str::to_string),
+        total_results: body["pageInfo"]["totalResults"].as_u64(),
+    }
+}
+
+// search.list items, with the video id under id.videoId and no duration yet
+fn parse_search_results(body: &serde_json::Value) -> SearchPage {
+    let results = body["items"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let video_id = item["id"]["videoId"].as_str()?;
+            Some(search_result(video_id, &item["snippet"], 0.0))
+        })
+        .collect();
+    page_of(body, results)
+}
+
+// videos.list items, with the video id as id and the duration in contentDetails
+fn parse_video_results(body: &serde_json::Value) -> SearchPage {
+    let results = body["items"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let video_id = item["id"].as_str()?;
+            let duration = video_duration(item).unwrap_or(0.0);
+            Some(search_result(video_id, &item["snippet"], duration))
+        })
+        .collect();
+    page_of(body, results)
+}
+
+fn parse_durations(body: &serde_json::Value) -> HashMap<String, f64> {
+    body["items"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| Some((item["id"].as_str()?.to_string(), video_duration(item)?)))
+        .collect()
+}
+
+fn video_duration(item: &serde_json::Value) -> Option<f64> {
+    let duration = item["contentDetails"]["duration"].as_str()?;
+    YouTubeAPI::parse_youtube_duration(duration).ok()
+}
+
+fn search_result(video_id: &str, snippet: &serde_json::Value, duration: f64) -> VideoSearchResult {
+    let thumbnails = &snippet["thumbnails"];
+    let thumbnail = ["high", "medium", "default"].iter()
+        .find_map(|size| thumbnails[size]["url"].as_str())
+        .unwrap_or_default();
+
+    VideoSearchResult {
+        video_id: video_id.to_string(),
+        title: snippet["title"].as_str().unwrap_or_default().to_string(),
+        channel: snippet["channelTitle"].as_str().unwrap_or_default().to_string(),
+        duration,
+        thumbnail: thumbnail.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_search_and_video_results() {
+        let search = serde_json::json!({
+            "nextPageToken": "CAUQAA",
+            "pageInfo": { "totalResults": 1000000, "resultsPerPage": 2 },
+            "items": [
+                {
+                    "id": { "kind": "youtube#video", "videoId": "abc" },
+                    "snippet": {
+                        "title": "Rust in 100 Seconds",
+                        "channelTitle": "Fireship",
+                        "thumbnails": { "default": { "url": "https://i.ytimg.com/vi/abc/default.jpg" } }
+                    }
+                },
+                { "id": { "kind": "youtube#channel", "channelId": "UC1" }, "snippet": { "title": "A channel" } }
+            ]
+        });
+        let page = parse_search_results(&search);
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.results[0].channel, "Fireship");
+        assert_eq!(page.results[0].thumbnail, "https://i.ytimg.com/vi/abc/default.jpg");
+        assert_eq!(page.next_page_token.as_deref(), Some("CAUQAA"));
+        assert_eq!(page.total_results, Some(1000000));
+
+        let videos = serde_json::json!({
+            "items": [{
+                "id": "abc",
+                "snippet": { "title": "Rust in 100 Seconds", "channelTitle": "Fireship", "thumbnails": {} },
+                "contentDetails": { "duration": "PT1H2M5S" }
+            }]
+        });
+        assert_eq!(parse_durations(&videos).get("abc"), Some(&3725.0));
+        let trending = parse_video_results(&videos);
+        assert_eq!(trending.results[0].duration, 3725.0);
+        assert!(trending.next_page_token.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_trending_is_cached_per_region_for_an_hour() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(cache_dir.path().to_path_buf());
+        let mut api = YouTubeAPI::new(Some("test-key".to_string())).with_cache(cache.clone());
+        // Nothing listens here, so only a cached chart can come back
+        api.base_url = "http://127.0.0.1:1".to_string();
+
+        let us_url = api.api_url("videos", &[
+            ("part", "snippet,contentDetails"),
+            ("chart", "mostPopular"),
+            ("regionCode", "US"),
+            ("maxResults", "50"),
+        ], "test-key").unwrap();
+        cache.put(us_url.as_str(), None, &serde_json::json!({
+            "items": [{ "id": "abc", "snippet": { "title": "Trending", "thumbnails": {} }, "contentDetails": { "duration": "PT5M" } }]
+        }));
+
+        let page = api.get_trending_videos("us", None, 50, None).await.unwrap();
+        assert_eq!(page.results[0].duration, 300.0);
+        assert!(api.get_trending_videos("DE", None, 50, None).await.is_err());
+
+        let two_hours_old = youtube_cache::CachedResponse {
+            etag: None,
+            fetched_at: (chrono::Utc::now() - chrono::Duration::hours(2)).to_rfc3339(),
+            body: serde_json::Value::Null,
+        };
+        assert!(!two_hours_old.is_fresh_within(chrono::Duration::minutes(TRENDING_MAX_AGE_MINUTES)));
+        assert!(two_hours_old.is_fresh());
+    }
+
+    #[test]
+    fn test_api_url_encodes_and_checks_parameters() {
+        let api = YouTubeAPI::new(Some("test-key".to_string()));
+        let url = api.api_url("search", &[("q", "ラーメン & co"), ("type", "video")], "test-key").unwrap();
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert_eq!(params.get("q").map(String::as_str), Some("ラーメン & co"));
+        assert_eq!(params.get("key").map(String::as_str), Some("test-key"));
+        assert!(url.as_str().starts_with("https://www.googleapis.com/youtube/v3/search?"));
+
+        let url = with_page_token(url, Some("CAUQAA")).unwrap();
+        assert!(url.as_str().contains("pageToken=CAUQAA"));
+        assert!(with_page_token(url, Some("CAUQAA&key=other")).is_err());
+        assert!(checked_id("video ID", "dQw4w9WgXcQ").is_ok());
+        assert!(checked_id("video ID", "../videos").is_err());
+        assert_eq!(checked_region("gb").unwrap(), "GB");
+        assert!(checked_region("GBR").is_err());
+    }
+
+    #[test]
+    fn test_parse_playlist_items() {
+        let body = serde_json::json!({
+            "items": [
+                {
+                    "snippet": { "title": "Part 1", "position": 0, "videoOwnerChannelTitle": "Channel", "resourceId": { "videoId": "abc" } },
+                    "contentDetails": { "videoId": "abc", "videoPublishedAt": "2025-05-01T10:00:00Z" },
+                    "status": { "privacyStatus": "public" }
+                },
+                {
+                    "snippet": { "title": "Deleted video", "position": 1, "resourceId": { "videoId": "gone" } },
+                    "contentDetails": { "videoId": "gone" },
+                    "status": { "privacyStatus": "privacyStatusUnspecified" }
+                },
+                {
+                    "snippet": { "title": "Part 3", "position": 2, "resourceId": { "videoId": "def" } },
+                    "contentDetails": { "videoId": "def" },
+                    "status": { "privacyStatus": "unlisted" }
+                }
+            ]
+        });
+        let items = parse_playlist_items(&body);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].published_at.as_deref(), Some("2025-05-01T10:00:00Z"));
+        assert_eq!(items[0].url(), "https://www.youtube.com/watch?v=abc");
+        assert_eq!(items[1].position, 2);
+        assert!(items[1].published_at.is_none());
+
+        assert_eq!(uploads_after(items.clone(), Some("2025-04-30T00:00:00Z")).len(), 1);
+        assert!(uploads_after(items.clone(), Some("2025-05-01T10:00:00Z")).is_empty());
+        assert_eq!(uploads_after(items, None).len(), 2);
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let body = serde_json::json!({
+            "items": [{
+                "snippet": {
+                    "totalReplyCount": 3,
+                    "topLevelComment": {
+                        "snippet": {
+                            "authorDisplayName": "@viewer",
+                            "textDisplay": "Could you do a video on async traits?",
+                            "likeCount": 42,
+                            "publishedAt": "2026-01-02T03:04:05Z"
+                        }
+                    }
+                }
+            }]
+        });
+        let comments = parse_comments(&body);
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].like_count, 42);
+        assert_eq!(comments[0].reply_count, 3);
+        assert_eq!(comments[0].author, "@viewer");
+    }
+
+    #[test]
+    fn test_search_filter_params() {
+        let filters = SearchFilters {
+            duration: Some(DurationFilter::Medium),
+            published_after: Some("2025-01-01".to_string()),
+            captions: Some(CaptionFilter::Captioned),
+            license: Some(LicenseFilter::CreativeCommons),
+            order: SearchOrder::Date,
+            ..Default::default()
+        };
+        let params = filters.query_params().unwrap();
+        assert!(params.contains(&("videoDuration", "medium".to_string())));
+        assert!(params.contains(&("publishedAfter", "2025-01-01T00:00:00Z".to_string())));
+        assert!(params.contains(&("videoCaption", "closedCaption".to_string())));
+        assert!(params.contains(&("videoLicense", "creativeCommon".to_string())));
+        assert!(params.contains(&("order", "date".to_string())));
+        assert_eq!(SearchFilters::default().query_params().unwrap(), vec![("order", "relevance".to_string())]);
+
+        let empty_range = SearchFilters {
+            published_after: Some("2025-02-01T00:00:00Z".to_string()),
+            published_before: Some("2025-01-01".to_string()),
+            ..Default::default()
+        };
+        assert!(empty_range.query_params().is_err());
+        let bad_date = SearchFilters { published_before: Some("last week".to_string()), ..Default::default() };
+        assert!(bad_date.query_params().is_err());
+    }
+
+    #[test]
+    fn test_parse_categories() {
+        let body = serde_json::json!({
+            "items": [
+                { "id": "27", "snippet": { "title": "Education", "assignable": true } },
+                { "id": "28", "snippet": { "title": "Science & Technology", "assignable": true } },
+                { "id": "99" }
+            ]
+        });
+        let categories = parse_categories(&body);
+        assert_eq!(categories.len(), 2);
+        assert_eq!(categories.get("27").map(String::as_str), Some("Education"));
+    }
+
+    #[test]
+    fn test_api_error_tells_key_problems_apart() {
+        let quota = serde_json::json!({ "error": { "message": "Quota exceeded", "errors": [{ "reason": "quotaExceeded" }] } });
+        assert!(matches!(api_error(reqwest::StatusCode::FORBIDDEN, &quota), YouTubeApiError::QuotaExceeded));
+
+        let invalid = serde_json::json!({ "error": {
+            "message": "API key not valid. Please pass a valid API key.",
+            "errors": [{ "reason": "badRequest" }],
+            "details": [{ "@type": "type.googleapis.com/google.rpc.ErrorInfo", "reason": "API_KEY_INVALID" }]
+        } });
+        assert!(matches!(api_error(reqwest::StatusCode::BAD_REQUEST, &invalid), YouTubeApiError::InvalidApiKey));
+
+        let referrer = serde_json::json!({ "error": {
+            "message": "Requests from referer <empty> are blocked.",
+            "errors": [{ "reason": "forbidden" }],
+            "details": [{ "reason": "API_KEY_HTTP_REFERRER_BLOCKED" }]
+        } });
+        let error = api_error(reqwest::StatusCode::FORBIDDEN, &referrer);
+        assert_eq!(
+            ApiKeyStatus::from_check(Err(error)),
+            ApiKeyStatus::Restricted { message: "Requests from referer <empty> are blocked.".to_string() }
+        );
+
+        let other = serde_json::json!({ "error": { "message": "Backend error", "errors": [{ "reason": "backendError" }] } });
+        assert!(matches!(
+            ApiKeyStatus::from_check(Err(api_error(reqwest::StatusCode::SERVICE_UNAVAILABLE, &other))),
+            ApiKeyStatus::Unknown { .. }
+        ));
+    }
+}
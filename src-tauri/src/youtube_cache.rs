@@ -0,0 +1,128 @@
+use crate::atomic_file::write_atomic;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+// Within a day a cached response is used as is; after that it's revalidated with its ETag
+pub const MAX_AGE_HOURS: i64 = 24;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CachedResponse {
+    pub etag: Option<String>,
+    pub fetched_at: String,
+    pub body: serde_json::Value,
+}
+
+impl CachedResponse {
+    pub fn is_fresh(&self) -> bool {
+        self.is_fresh_within(chrono::Duration::hours(MAX_AGE_HOURS))
+    }
+
+    pub fn is_fresh_within(&self, max_age: chrono::Duration) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&self.fetched_at)
+            .is_ok_and(|fetched_at| chrono::Utc::now().signed_duration_since(fetched_at) < max_age)
+    }
+}
+
+// Data API responses that rarely change, video metadata and caption lists, one file per request
+// URL. Search results change too often to cache; trending charts are kept for an hour
+#[derive(Clone)]
+pub struct ResponseCache {
+    // Shared by the clones, so a relocate moves them all
+    dir: Arc<Mutex<PathBuf>>,
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir: Arc::new(Mutex::new(dir)) }
+    }
+
+    // After the workspace moved, with the cache in it
+    pub fn relocate(&self, dir: PathBuf) {
+        *self.dir.lock().unwrap_or_else(|e| e.into_inner()) = dir;
+    }
+
+    pub fn get(&self, url: &str) -> Option<CachedResponse> {
+        let content = std::fs::read_to_string(self.entry_path(url)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    // A failed write only means the next request goes to YouTube
+    pub fn put(&self, url: &str, etag: Option<String>, body: &serde_json::Value) {
+        let entry = CachedResponse {
+            etag,
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            body: body.clone(),
+        };
+        if let Err(e) = self.save(url, &entry) {
+            tracing::warn!("{}", e);
+        }
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        let dir = self.dir();
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)
+                .map_err(|e| format!("Failed to clear YouTube cache: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn save(&self, url: &str, entry: &CachedResponse) -> Result<(), String> {
+        std::fs::create_dir_all(self.dir())
+            .map_err(|e| format!("Failed to create YouTube cache directory: {}", e))?;
+        let content = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize cached response: {}", e))?;
+        write_atomic(&self.entry_path(url), content.as_bytes())
+            .map_err(|e| format!("Failed to write YouTube cache: {}", e))
+    }
+
+    // The API key is left out, so changing it doesn't empty the cache
+    fn entry_path(&self, url: &str) -> PathBuf {
+        let request = url.split(['?', '&'])
+            .filter(|part| !part.starts_with("key="))
+            .collect::<Vec<_>>()
+            .join("&");
+        self.dir().join(format!("{:x}.json", Sha256::digest(request.as_bytes())))
+    }
+
+    fn dir(&self) -> PathBuf {
+        self.dir.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_ignores_api_key() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let cache = ResponseCache::new(dir.path().to_path_buf());
+        let body = serde_json::json!({ "items": [{ "id": "abc" }] });
+
+        cache.put("https://www.googleapis.com/youtube/v3/videos?part=snippet&id=abc&key=old", Some("\"etag-1\"".to_string()), &body);
+        let cached = cache.get("https://www.googleapis.com/youtube/v3/videos?part=snippet&id=abc&key=new").unwrap();
+        assert_eq!(cached.etag.as_deref(), Some("\"etag-1\""));
+        assert_eq!(cached.body, body);
+        assert!(cached.is_fresh());
+        assert!(!cached.is_fresh_within(chrono::Duration::zero()));
+        assert!(cache.get("https://www.googleapis.com/youtube/v3/videos?part=snippet&id=xyz&key=new").is_none());
+
+        cache.clear().unwrap();
+        assert!(cache.get("https://www.googleapis.com/youtube/v3/videos?part=snippet&id=abc&key=new").is_none());
+    }
+
+    #[test]
+    fn test_clones_follow_a_relocate() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let cache = ResponseCache::new(dir.path().join("old"));
+        let batch_copy = cache.clone();
+
+        cache.relocate(dir.path().join("new"));
+        batch_copy.put("https://www.googleapis.com/youtube/v3/videos?id=abc", None, &serde_json::json!({}));
+        assert!(!dir.path().join("old").exists());
+        assert!(cache.get("https://www.googleapis.com/youtube/v3/videos?id=abc").is_some());
+    }
+}
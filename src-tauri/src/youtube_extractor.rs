@@ -1,16 +1,11 @@
-use crate::VideoInfo;
-use reqwest;
-use serde_json;
+use crate::{VideoInfo, VideoNugget};
 
-pub struct YouTubeExtractor {
-    client: reqwest::Client,
-}
+#[derive(Clone, Default)]
+pub struct YouTubeExtractor;
 
 impl YouTubeExtractor {
     pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-        }
+        Self
     }
 
     pub async fn get_video_info(&self, url: &str) -> Result<VideoInfo, String> {
@@ -54,31 +49,31 @@ impl YouTubeExtractor {
         Ok(format!("Transcript for video ID: {}", video_id))
     }
 
-    pub async fn download_video(&self, url: &str, quality: &str, output_path: &str) -> Result<String, String> {
+    pub async fn download_video(&self, _url: &str, quality: &str, output_path: &str) -> Result<String, String> {
         // TODO: Implement video download functionality
         // This would use yt-dlp or similar tools
         Ok(format!("Video downloaded to: {} (quality: {})", output_path, quality))
     }
 
-    pub async fn get_video_chapters(&self, video_id: &str) -> Result<Vec<VideoChapter>, String> {
+    pub async fn get_video_chapters(&self, _video_id: &str) -> Result<Vec<VideoChapter>, String> {
         // TODO: Implement chapter extraction
         Ok(vec![])
     }
 
-    pub async fn search_videos(&self, query: &str, max_results: u32) -> Result<Vec<VideoSearchResult>, String> {
+    pub async fn search_videos(&self, _query: &str, _max_results: u32) -> Result<Vec<VideoSearchResult>, String> {
         // TODO: Implement video search functionality
         Ok(vec![])
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct VideoChapter {
     pub title: String,
     pub start_time: f64,
     pub end_time: f64,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct VideoSearchResult {
     pub video_id: String,
     pub title: String,
@@ -87,6 +82,24 @@ pub struct VideoSearchResult {
     pub thumbnail: String,
 }
 
+// One nugget per chapter, for processing a video the way its uploader divided it
+pub fn chapter_nuggets(chapters: &[VideoChapter]) -> Vec<VideoNugget> {
+    chapters.iter()
+        .filter(|chapter| chapter.end_time > chapter.start_time)
+        .map(|chapter| VideoNugget {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: chapter.title.clone(),
+            start_time: chapter.start_time,
+            end_time: chapter.end_time,
+            transcript: None,
+            tags: vec!["chapter".to_string()],
+            notes: None,
+            youtube_video_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,7 +108,6 @@ mod tests {
     fn test_new_extractor() {
         let _extractor = YouTubeExtractor::new();
         // Just verify it can be created
-        assert!(true);
     }
 
     #[test]
@@ -198,9 +210,9 @@ mod tests {
         let url = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
         let result = extractor.download_video(url, "720p", "/tmp/video.mp4").await;
         
-        assert!(result.is_ok());
-        assert!(result.unwrap().contains("/tmp/video.mp4"));
-        assert!(result.unwrap().contains("720p"));
+        let path = result.unwrap();
+        assert!(path.contains("/tmp/video.mp4"));
+        assert!(path.contains("720p"));
     }
 
     #[tokio::test]
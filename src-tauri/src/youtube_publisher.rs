@@ -0,0 +1,420 @@
+use crate::ai_config_store::{read_secret, write_secret};
+use crate::anki_export::timestamp_link;
+use crate::oauth_loopback::{code_verifier, LoopbackRedirect};
+use crate::youtube_api::{check_response, YouTubeApiError};
+use crate::youtube_quota::{ApiEndpoint, ApiQuota};
+use crate::VideoNugget;
+use base64::Engine;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use url::Url;
+
+const OAUTH_CLIENT_ENTRY: &str = "youtube_oauth_client";
+const REFRESH_TOKEN_ENTRY: &str = "youtube_refresh_token";
+const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const UPLOAD_URL: &str = "https://www.googleapis.com/upload/youtube/v3/videos";
+// Uploading only; the app never manages the channel
+const UPLOAD_SCOPE: &str = "https://www.googleapis.com/auth/youtube.upload";
+// YouTube's limits on what an upload can say about itself
+const MAX_TITLE_CHARS: usize = 100;
+const MAX_DESCRIPTION_CHARS: usize = 5000;
+const MAX_TAGS_CHARS: usize = 500;
+// Vertical clips up to this long are shown as Shorts
+const MAX_SHORT_SECONDS: f64 = 180.0;
+
+// A Desktop app client from the Google Cloud console. Google doesn't treat an installed app's
+// secret as secret, but it's kept in the keychain with the rest
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OAuthClient {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PublisherStatus {
+    pub client_set: bool,
+    // Signed in, with a refresh token in the keychain
+    pub authorized: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyStatus {
+    #[default]
+    Private,
+    Unlisted,
+    Public,
+}
+
+// What the upload goes up with. nugget_metadata drafts it; the UI can change any of it first
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UploadMetadata {
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub privacy: PrivacyStatus,
+    // RFC 3339. A scheduled video stays private until then, whatever privacy says
+    #[serde(default)]
+    pub publish_at: Option<String>,
+    #[serde(default)]
+    pub category_id: Option<String>,
+}
+
+pub fn set_oauth_client(client_id: &str, client_secret: &str) -> Result<(), String> {
+    if client_id.trim().is_empty() || client_secret.trim().is_empty() {
+        return Err("OAuth client ID and secret are both needed".to_string());
+    }
+    let client = OAuthClient {
+        client_id: client_id.trim().to_string(),
+        client_secret: client_secret.trim().to_string(),
+    };
+    let content = serde_json::to_string(&client)
+        .map_err(|e| format!("Failed to serialize OAuth client: {}", e))?;
+    write_secret(OAUTH_CLIENT_ENTRY, Some(&content))?;
+    // A token from another client won't refresh with this one
+    write_secret(REFRESH_TOKEN_ENTRY, Some(""))
+}
+
+pub fn status() -> Result<PublisherStatus, String> {
+    Ok(PublisherStatus {
+        client_set: oauth_client()?.is_some(),
+        authorized: read_secret(REFRESH_TOKEN_ENTRY)?.is_some(),
+    })
+}
+
+pub fn sign_out() -> Result<(), String> {
+    write_secret(REFRESH_TOKEN_ENTRY, Some(""))
+}
+
+fn oauth_client() -> Result<Option<OAuthClient>, String> {
+    read_secret(OAUTH_CLIENT_ENTRY)?
+        .map(|content| serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to read OAuth client: {}", e)))
+        .transpose()
+}
+
+// Google's flow for desktop apps: the browser signs in and is sent back to a port on loopback,
+// with PKCE so a code caught on the way is no use to anyone else
+pub struct PendingAuthorization {
+    pub url: String,
+    client: OAuthClient,
+    redirect: LoopbackRedirect,
+    verifier: String,
+}
+
+pub async fn begin_authorization() -> Result<PendingAuthorization, String> {
+    let client = oauth_client()?.ok_or("Add an OAuth client for YouTube uploads first")?;
+    let redirect = LoopbackRedirect::bind("YouTube", 0, "/").await?;
+    let verifier = code_verifier();
+
+    let mut url = Url::parse(AUTH_URL)
+        .map_err(|e| format!("Invalid authorization URL: {}", e))?;
+    url.query_pairs_mut()
+        .append_pair("client_id", &client.client_id)
+        .append_pair("redirect_uri", &redirect.redirect_uri)
+        .append_pair("response_type", "code")
+        .append_pair("scope", UPLOAD_SCOPE)
+        .append_pair("state", &redirect.state)
+        .append_pair("code_challenge", &code_challenge(&verifier))
+        .append_pair("code_challenge_method", "S256")
+        // Without these Google only hands out a refresh token the first time
+        .append_pair("access_type", "offline")
+        .append_pair("prompt", "consent");
+
+    Ok(PendingAuthorization {
+        url: url.to_string(),
+        client,
+        redirect,
+        verifier,
+    })
+}
+
+impl PendingAuthorization {
+    // Waits for the browser to come back, then keeps the refresh token in the keychain
+    pub async fn finish(self) -> Result<(), String> {
+        let code = self.redirect.receive_code().await?;
+
+        let response = reqwest::Client::new()
+            .post(TOKEN_URL)
+            .form(&[
+                ("code", code.as_str()),
+                ("client_id", self.client.client_id.as_str()),
+                ("client_secret", self.client.client_secret.as_str()),
+                ("redirect_uri", self.redirect.redirect_uri.as_str()),
+                ("grant_type", "authorization_code"),
+                ("code_verifier", self.verifier.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Google: {}", e))?;
+        let tokens = token_response(response).await?;
+        let refresh_token = tokens.refresh_token.ok_or("Google didn't return a refresh token")?;
+        write_secret(REFRESH_TOKEN_ENTRY, Some(&refresh_token))
+    }
+}
+
+fn code_challenge(verifier: &str) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+async fn token_response(response: reqwest::Response) -> Result<TokenResponse, String> {
+    let status = response.status();
+    let body: serde_json::Value = response.json()
+        .await
+        .map_err(|e| format!("Failed to read Google's token response: {}", e))?;
+    if !status.is_success() {
+        let error = body["error"].as_str().unwrap_or_default();
+        if error == "invalid_grant" {
+            // Revoked, expired or from another client; only signing in again helps
+            write_secret(REFRESH_TOKEN_ENTRY, Some(""))?;
+            return Err("YouTube sign-in has expired, sign in again".to_string());
+        }
+        return Err(format!("Google rejected the token request ({}): {}", status, body["error_description"].as_str().unwrap_or(error)));
+    }
+    serde_json::from_value(body)
+        .map_err(|e| format!("Failed to read Google's token response: {}", e))
+}
+
+// Uploads to the channel of whoever signed in
+pub struct YouTubePublisher {
+    client: reqwest::Client,
+    oauth: OAuthClient,
+    refresh_token: String,
+    quota: Option<Arc<ApiQuota>>,
+}
+
+impl YouTubePublisher {
+    pub fn from_keychain() -> Result<Self, String> {
+        let oauth = oauth_client()?.ok_or("Add an OAuth client for YouTube uploads first")?;
+        let refresh_token = read_secret(REFRESH_TOKEN_ENTRY)?.ok_or("Sign in to YouTube first")?;
+        Ok(Self {
+            client: reqwest::Client::new(),
+            oauth,
+            refresh_token,
+            quota: None,
+        })
+    }
+
+    pub fn with_quota(mut self, quota: Arc<ApiQuota>) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    // A resumable upload, started and finished in one go; returns the new video's ID. The clip is
+    // read into memory, which is fine for nuggets but not for a whole film
+    pub async fn upload(&self, clip_path: &str, metadata: &UploadMetadata) -> Result<String, YouTubeApiError> {
+        let body = upload_body(metadata)?;
+        if let Some(quota) = &self.quota {
+            if !quota.allows(ApiEndpoint::VideosInsert) {
+                return Err(YouTubeApiError::QuotaExceeded);
+            }
+        }
+        let clip = tokio::fs::read(clip_path)
+            .await
+            .map_err(|e| YouTubeApiError::Other(format!("Failed to read clip: {}", e)))?;
+        let access_token = self.access_token().await?;
+
+        let response = self.client
+            .post(UPLOAD_URL)
+            .query(&[("uploadType", "resumable"), ("part", "snippet,status")])
+            .bearer_auth(&access_token)
+            .header("X-Upload-Content-Type", "video/*")
+            .header("X-Upload-Content-Length", clip.len())
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| YouTubeApiError::Http("Failed to start YouTube upload", e))?;
+        if let Some(quota) = &self.quota {
+            quota.record(ApiEndpoint::VideosInsert);
+        }
+        let response = check_response(response).await?;
+        let session_url = response.headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .ok_or_else(|| YouTubeApiError::Other("YouTube didn't return an upload URL".to_string()))?
+            .to_string();
+
+        let response = self.client
+            .put(session_url)
+            .bearer_auth(&access_token)
+            .header(reqwest::header::CONTENT_TYPE, "video/*")
+            .body(clip)
+            .send()
+            .await
+            .map_err(|e| YouTubeApiError::Http("Failed to upload clip to YouTube", e))?;
+        let video: serde_json::Value = check_response(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| YouTubeApiError::Http("Failed to read YouTube upload response", e))?;
+        video["id"].as_str()
+            .map(str::to_string)
+            .ok_or_else(|| YouTubeApiError::Other("YouTube didn't return the uploaded video's ID".to_string()))
+    }
+
+    async fn access_token(&self) -> Result<String, YouTubeApiError> {
+        let response = self.client
+            .post(TOKEN_URL)
+            .form(&[
+                ("client_id", self.oauth.client_id.as_str()),
+                ("client_secret", self.oauth.client_secret.as_str()),
+                ("refresh_token", self.refresh_token.as_str()),
+                ("grant_type", "refresh_token"),
+            ])
+            .send()
+            .await
+            .map_err(|e| YouTubeApiError::Http("Failed to reach Google", e))?;
+        Ok(token_response(response).await?.access_token)
+    }
+}
+
+fn upload_body(metadata: &UploadMetadata) -> Result<serde_json::Value, YouTubeApiError> {
+    if metadata.title.trim().is_empty() {
+        return Err(YouTubeApiError::InvalidInput("The upload needs a title".to_string()));
+    }
+    let mut status = serde_json::json!({
+        "privacyStatus": match metadata.privacy {
+            PrivacyStatus::Private => "private",
+            PrivacyStatus::Unlisted => "unlisted",
+            PrivacyStatus::Public => "public",
+        },
+    });
+    if let Some(publish_at) = &metadata.publish_at {
+        let time = chrono::DateTime::parse_from_rfc3339(publish_at)
+            .map_err(|e| YouTubeApiError::InvalidInput(format!("Invalid publish time {}: {}", publish_at, e)))?;
+        if time <= chrono::Utc::now() {
+            return Err(YouTubeApiError::InvalidInput("The publish time has to be in the future".to_string()));
+        }
+        // YouTube only schedules private videos
+        status["privacyStatus"] = "private".into();
+        status["publishAt"] = time.with_timezone(&chrono::Utc).to_rfc3339_opts(chrono::SecondsFormat::Secs, true).into();
+    }
+
+    let mut snippet = serde_json::json!({
+        "title": clean_text(&metadata.title, MAX_TITLE_CHARS),
+        "description": clean_text(&metadata.description, MAX_DESCRIPTION_CHARS),
+        "tags": fitting_tags(&metadata.tags),
+    });
+    if let Some(category_id) = &metadata.category_id {
+        snippet["categoryId"] = category_id.as_str().into();
+    }
+    Ok(serde_json::json!({ "snippet": snippet, "status": status }))
+}
+
+// A draft for the nugget's upload: its title, the AI's YouTube caption for the video, a link
+// back to the moment in the full video and the nugget's and analysis' tags
+pub fn nugget_metadata(nugget: &VideoNugget, video_url: &str, caption: Option<&str>, suggested_tags: &[String]) -> UploadMetadata {
+    let mut description = caption
+        .map(str::to_string)
+        .or_else(|| nugget.transcript.clone())
+        .unwrap_or_default();
+    description = format!("{}\n\nFull video: {}", description.trim(), timestamp_link(video_url, nugget.start_time));
+    if nugget.end_time - nugget.start_time <= MAX_SHORT_SECONDS {
+        description.push_str("\n\n#Shorts");
+    }
+
+    let mut tags: Vec<String> = Vec::new();
+    for tag in nugget.tags.iter().chain(suggested_tags) {
+        let tag = tag.trim().trim_start_matches('#');
+        if !tag.is_empty() && !tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+
+    UploadMetadata {
+        title: clean_text(&nugget.title, MAX_TITLE_CHARS),
+        description: description.trim().to_string(),
+        tags: fitting_tags(&tags),
+        privacy: PrivacyStatus::Private,
+        publish_at: None,
+        category_id: None,
+    }
+}
+
+// YouTube refuses titles and descriptions with angle brackets, and anything over its limits
+fn clean_text(text: &str, max_chars: usize) -> String {
+    text.chars()
+        .filter(|c| *c != '<' && *c != '>')
+        .take(max_chars)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+// As many tags as fit in YouTube's 500 characters, where a tag with a space counts its quotes
+fn fitting_tags(tags: &[String]) -> Vec<String> {
+    let mut used = 0;
+    let mut fitting = Vec::new();
+    for tag in tags {
+        let tag = clean_text(tag, MAX_TAGS_CHARS);
+        if tag.is_empty() {
+            continue;
+        }
+        let length = tag.chars().count() + if tag.contains(' ') { 2 } else { 0 };
+        let separator = if fitting.is_empty() { 0 } else { 1 };
+        if used + separator + length > MAX_TAGS_CHARS {
+            break;
+        }
+        used += separator + length;
+        fitting.push(tag);
+    }
+    fitting
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nugget_metadata_and_upload_body() {
+        let nugget = VideoNugget {
+            id: "n1".to_string(),
+            title: "Why <caching> matters".to_string(),
+            start_time: 95.0,
+            end_time: 140.0,
+            transcript: Some("Caching saves work.".to_string()),
+            tags: vec!["caching".to_string(), "Performance".to_string()],
+            notes: None,
+            youtube_video_id: None,
+            created_at: String::new(),
+        };
+        let suggested = vec!["#performance".to_string(), "web dev".to_string()];
+        let metadata = nugget_metadata(&nugget, "https://www.youtube.com/watch?v=abc", Some("AI caption"), &suggested);
+        assert_eq!(metadata.title, "Why caching matters");
+        assert_eq!(metadata.tags, vec!["caching", "Performance", "web dev"]);
+        assert!(metadata.description.starts_with("AI caption\n\nFull video: https://www.youtube.com/watch?v=abc&t=95s"));
+        assert!(metadata.description.ends_with("#Shorts"));
+
+        let scheduled = UploadMetadata {
+            privacy: PrivacyStatus::Public,
+            publish_at: Some("2999-01-01T09:00:00+01:00".to_string()),
+            ..metadata.clone()
+        };
+        let body = upload_body(&scheduled).unwrap();
+        assert_eq!(body["status"]["privacyStatus"], "private");
+        assert_eq!(body["status"]["publishAt"], "2999-01-01T08:00:00Z");
+        assert!(body["snippet"].get("categoryId").is_none());
+
+        let past = UploadMetadata { publish_at: Some("2020-01-01T00:00:00Z".to_string()), ..metadata };
+        assert!(matches!(upload_body(&past), Err(YouTubeApiError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_fitting_tags_counts_quotes() {
+        let tags: Vec<String> = (0..100).map(|i| format!("tag number {}", i)).collect();
+        let fitting = fitting_tags(&tags);
+        let used: usize = fitting.iter().map(|tag| tag.len() + 2).sum::<usize>() + fitting.len() - 1;
+        assert!(used <= MAX_TAGS_CHARS);
+        assert!(fitting.len() < tags.len());
+    }
+}
@@ -0,0 +1,260 @@
+use crate::app_settings;
+use crate::atomic_file::write_atomic;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+pub const QUOTA_WARNING_EVENT: &str = "youtube://quota-warning";
+
+// Once this share of the day's quota is gone the UI is warned, once a day
+const WARNING_SHARE: f64 = 0.8;
+// Days of usage kept in the ledger
+const HISTORY_DAYS: usize = 7;
+
+// The Data API endpoints the app calls, each with what Google charges for a request
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ApiEndpoint {
+    Search,
+    Videos,
+    Captions,
+    CaptionDownload,
+    CommentThreads,
+    PlaylistItems,
+    VideoCategories,
+    // An upload, by far the dearest call
+    VideosInsert,
+    I18nLanguages,
+}
+
+impl ApiEndpoint {
+    pub fn name(self) -> &'static str {
+        match self {
+            ApiEndpoint::Search => "search.list",
+            ApiEndpoint::Videos => "videos.list",
+            ApiEndpoint::Captions => "captions.list",
+            ApiEndpoint::CaptionDownload => "captions.download",
+            ApiEndpoint::CommentThreads => "commentThreads.list",
+            ApiEndpoint::PlaylistItems => "playlistItems.list",
+            ApiEndpoint::VideoCategories => "videoCategories.list",
+            ApiEndpoint::VideosInsert => "videos.insert",
+            ApiEndpoint::I18nLanguages => "i18nLanguages.list",
+        }
+    }
+
+    pub fn cost(self) -> u64 {
+        match self {
+            ApiEndpoint::Search => 100,
+            ApiEndpoint::Videos => 1,
+            ApiEndpoint::Captions => 50,
+            ApiEndpoint::CaptionDownload => 200,
+            ApiEndpoint::CommentThreads => 1,
+            ApiEndpoint::PlaylistItems => 1,
+            ApiEndpoint::VideoCategories => 1,
+            ApiEndpoint::VideosInsert => 1600,
+            ApiEndpoint::I18nLanguages => 1,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuotaUsage {
+    // The quota day, which starts at midnight Pacific time
+    pub day: String,
+    pub used: u64,
+    pub daily_limit: u64,
+    pub remaining: u64,
+    // Units by endpoint name, "search.list" and so on
+    pub by_endpoint: BTreeMap<String, u64>,
+    pub near_limit: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct QuotaLedger {
+    #[serde(default)]
+    days: BTreeMap<String, BTreeMap<String, u64>>,
+    // The day the warning last went out
+    #[serde(default)]
+    warned_on: Option<String>,
+}
+
+type WarningCallback = Box<dyn Fn(&QuotaUsage) + Send + Sync>;
+
+// Units the YouTube API key spent, by day and endpoint, kept in the workspace. Google only
+// counts per project and doesn't say how much is left, so this is the app's own tally: calls
+// made with the same key from elsewhere aren't in it
+pub struct ApiQuota {
+    path: Mutex<PathBuf>,
+    daily_limit: Option<u64>,
+    ledger: Mutex<QuotaLedger>,
+    on_warning: Option<WarningCallback>,
+}
+
+impl ApiQuota {
+    pub fn new(path: PathBuf) -> Self {
+        let ledger = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: Mutex::new(path),
+            daily_limit: None,
+            ledger: Mutex::new(ledger),
+            on_warning: None,
+        }
+    }
+
+    // Instead of the limit in settings
+    pub fn with_daily_limit(mut self, daily_limit: u64) -> Self {
+        self.daily_limit = Some(daily_limit);
+        self
+    }
+
+    // Called once a day, with the first request that takes usage past WARNING_SHARE
+    pub fn with_warning(mut self, on_warning: impl Fn(&QuotaUsage) + Send + Sync + 'static) -> Self {
+        self.on_warning = Some(Box::new(on_warning));
+        self
+    }
+
+    // After the workspace moved, with the ledger in it
+    pub fn relocate(&self, path: PathBuf) {
+        *self.path.lock().unwrap_or_else(|e| e.into_inner()) = path;
+    }
+
+    pub fn usage(&self) -> QuotaUsage {
+        let day = quota_day(Utc::now());
+        self.usage_on(&self.lock(), &day)
+    }
+
+    // Whether a request to endpoint still fits in today's quota
+    pub fn allows(&self, endpoint: ApiEndpoint) -> bool {
+        self.usage().remaining >= endpoint.cost()
+    }
+
+    // Counts a request YouTube answered, whatever the answer; failed requests are charged too
+    pub fn record(&self, endpoint: ApiEndpoint) {
+        let day = quota_day(Utc::now());
+        let mut ledger = self.lock();
+        *ledger.days.entry(day.clone()).or_default()
+            .entry(endpoint.name().to_string())
+            .or_default() += endpoint.cost();
+        while ledger.days.len() > HISTORY_DAYS {
+            ledger.days.pop_first();
+        }
+
+        let usage = self.usage_on(&ledger, &day);
+        let warn = usage.near_limit && ledger.warned_on.as_deref() != Some(day.as_str());
+        if warn {
+            ledger.warned_on = Some(day);
+        }
+        if let Err(e) = self.save(&ledger) {
+            tracing::warn!("{}", e);
+        }
+        drop(ledger);
+
+        if warn {
+            tracing::warn!("YouTube API quota at {} of {} units", usage.used, usage.daily_limit);
+            if let Some(on_warning) = &self.on_warning {
+                on_warning(&usage);
+            }
+        }
+    }
+
+    fn usage_on(&self, ledger: &QuotaLedger, day: &str) -> QuotaUsage {
+        let daily_limit = self.daily_limit.unwrap_or_else(|| app_settings::current().defaults.youtube_daily_quota);
+        let by_endpoint = ledger.days.get(day).cloned().unwrap_or_default();
+        let used = by_endpoint.values().sum();
+        QuotaUsage {
+            day: day.to_string(),
+            used,
+            daily_limit,
+            remaining: daily_limit.saturating_sub(used),
+            by_endpoint,
+            near_limit: used as f64 >= daily_limit as f64 * WARNING_SHARE,
+        }
+    }
+
+    fn save(&self, ledger: &QuotaLedger) -> Result<(), String> {
+        let path = self.path.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create quota ledger directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(ledger)
+            .map_err(|e| format!("Failed to serialize quota ledger: {}", e))?;
+        write_atomic(&path, content.as_bytes())
+            .map_err(|e| format!("Failed to write quota ledger: {}", e))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QuotaLedger> {
+        self.ledger.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// The quota resets at midnight Pacific time, PDT from the second Sunday of March to the first
+// Sunday of November
+fn quota_day(now: DateTime<Utc>) -> String {
+    let daylight_start = us_transition(now.year(), 3, 2, 10);
+    let daylight_end = us_transition(now.year(), 11, 1, 9);
+    let offset = if daylight_start.is_some_and(|start| now >= start) && daylight_end.is_some_and(|end| now < end) {
+        Duration::hours(-7)
+    } else {
+        Duration::hours(-8)
+    };
+    (now + offset).format("%Y-%m-%d").to_string()
+}
+
+// 2am local time on the nth Sunday of month, as UTC
+fn us_transition(year: i32, month: u32, nth_sunday: u8, utc_hour: u32) -> Option<DateTime<Utc>> {
+    NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, nth_sunday)?
+        .and_hms_opt(utc_hour, 0, 0)
+        .map(|time| time.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_records_usage_and_warns_once() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("youtube_quota.json");
+        let warnings = Arc::new(AtomicUsize::new(0));
+        let counter = warnings.clone();
+        let quota = ApiQuota::new(path.clone())
+            .with_daily_limit(300)
+            .with_warning(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+
+        quota.record(ApiEndpoint::Search);
+        quota.record(ApiEndpoint::Videos);
+        assert!(!quota.usage().near_limit);
+        quota.record(ApiEndpoint::Search);
+        quota.record(ApiEndpoint::Videos);
+        let usage = quota.usage();
+        assert_eq!(usage.used, 202);
+        assert_eq!(usage.by_endpoint.get("search.list"), Some(&200));
+        assert!(!quota.allows(ApiEndpoint::Search));
+        assert!(quota.allows(ApiEndpoint::Videos));
+
+        quota.record(ApiEndpoint::Captions);
+        assert!(quota.usage().near_limit);
+        quota.record(ApiEndpoint::Videos);
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+
+        let reloaded = ApiQuota::new(path).with_daily_limit(300);
+        assert_eq!(reloaded.usage().used, 253);
+    }
+
+    #[test]
+    fn test_quota_day_follows_pacific_time() {
+        let winter = DateTime::parse_from_rfc3339("2026-01-15T07:30:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(quota_day(winter), "2026-01-14");
+        let summer = DateTime::parse_from_rfc3339("2026-07-15T07:30:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(quota_day(summer), "2026-07-15");
+    }
+}
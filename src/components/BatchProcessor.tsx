@@ -9,7 +9,7 @@ import { Progress } from '@/components/ui/progress';
 import { Badge } from '@/components/ui/badge';
 import { Tabs, TabsContent, TabsList, TabsTrigger } from '@/components/ui/tabs';
 import { useToast } from '@/hooks/use-toast';
-import { useBatchProcessor, BatchJob, BatchConfig, BatchStatus } from '@/lib/tauri-api-enhanced';
+import { useBatchProcessor, TauriError, BatchJob, BatchConfig, BatchStatus } from '@/lib/tauri-api-enhanced';
 import { Play, Pause, Square, Trash2, Plus, RefreshCw } from 'lucide-react';
 
 export const BatchProcessor: React.FC = () => {
@@ -83,7 +83,7 @@ export const BatchProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to create batch job: ${error}`,
+        description: `Failed to create batch job: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     }
@@ -99,7 +99,7 @@ export const BatchProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to start job: ${error}`,
+        description: `Failed to start job: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     }
@@ -115,7 +115,7 @@ export const BatchProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to cancel job: ${error}`,
+        description: `Failed to cancel job: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     }
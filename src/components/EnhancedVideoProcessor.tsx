@@ -18,7 +18,8 @@ import {
   ContentAnalysis,
   SpeechAnalysis,
   useVideoProcessor,
-  useProjectManager
+  useProjectManager,
+  TauriError
 } from '@/lib/tauri-api-enhanced';
 import { Play, Download, Share, MessageSquare, Brain, Video, FileText, Waveform, Sparkles } from 'lucide-react';
 
@@ -74,7 +75,7 @@ export const EnhancedVideoProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to get video info: ${error}`,
+        description: `Failed to get video info: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     } finally {
@@ -126,7 +127,7 @@ export const EnhancedVideoProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to process video: ${error}`,
+        description: `Failed to process video: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     }
@@ -143,7 +144,7 @@ export const EnhancedVideoProcessor: React.FC = () => {
     }
 
     try {
-      await addVideoToProject(selectedProject, videoInfo, nuggets, analysis || undefined);
+      await addVideoToProject(selectedProject, videoInfo, nuggets, analysis || undefined, result?.events);
       toast({
         title: "Success",
         description: "Video added to project successfully",
@@ -151,7 +152,7 @@ export const EnhancedVideoProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to add video to project: ${error}`,
+        description: `Failed to add video to project: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     }
@@ -177,7 +178,7 @@ export const EnhancedVideoProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to export: ${error}`,
+        description: `Failed to export: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     }
@@ -214,7 +215,7 @@ export const EnhancedVideoProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to generate subtitles: ${error}`,
+        description: `Failed to generate subtitles: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     }
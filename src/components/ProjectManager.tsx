@@ -8,7 +8,7 @@ import { Badge } from '@/components/ui/badge';
 import { Tabs, TabsContent, TabsList, TabsTrigger } from '@/components/ui/tabs';
 import { Dialog, DialogContent, DialogHeader, DialogTitle, DialogTrigger } from '@/components/ui/dialog';
 import { useToast } from '@/hooks/use-toast';
-import { useProjectManager, Project, VideoProject, ProjectSettings } from '@/lib/tauri-api-enhanced';
+import { useProjectManager, TauriError, Project, VideoProject, ProjectSettings } from '@/lib/tauri-api-enhanced';
 import { Plus, Folder, Video, Settings, Users, Download, Upload, Trash2 } from 'lucide-react';
 
 export const ProjectManager: React.FC = () => {
@@ -57,7 +57,7 @@ export const ProjectManager: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to create project: ${error}`,
+        description: `Failed to create project: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     }
@@ -73,7 +73,7 @@ export const ProjectManager: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to load project: ${error}`,
+        description: `Failed to load project: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     }
@@ -6,7 +6,7 @@ import { Label } from '@/components/ui/label';
 import { Slider } from '@/components/ui/slider';
 import { Switch } from '@/components/ui/switch';
 import { useToast } from '@/hooks/use-toast';
-import { TauriAPI, VideoNugget, VideoInfo, ProcessingConfig } from '@/lib/tauri-api';
+import { TauriAPI, TauriError, VideoNugget, VideoInfo, ProcessingConfig } from '@/lib/tauri-api';
 
 export const VideoProcessor: React.FC = () => {
   const [url, setUrl] = useState('');
@@ -45,7 +45,7 @@ export const VideoProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to get video info: ${error}`,
+        description: `Failed to get video info: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     } finally {
@@ -78,7 +78,7 @@ export const VideoProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to process video: ${error}`,
+        description: `Failed to process video: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     } finally {
@@ -107,7 +107,7 @@ export const VideoProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to save nuggets: ${error}`,
+        description: `Failed to save nuggets: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     }
@@ -133,7 +133,7 @@ export const VideoProcessor: React.FC = () => {
     } catch (error) {
       toast({
         title: "Error",
-        description: `Failed to export: ${error}`,
+        description: `Failed to export: ${TauriError.fromUnknown(error).message}`,
         variant: "destructive",
       });
     }
@@ -9,6 +9,9 @@ export interface VideoNugget {
   end_time: number;
   transcript?: string;
   tags: string[];
+  notes?: string;
+  // Set once the nugget has been uploaded with publishNuggetToYouTube
+  youtube_video_id?: string | null;
   created_at: string;
 }
 
@@ -16,6 +19,8 @@ export interface ProcessingResult {
   success: boolean;
   message: string;
   nuggets: VideoNugget[];
+  // Pass to addVideoToProject so the video's history shows how it was processed
+  events: ProcessingEvent[];
 }
 
 export interface VideoInfo {
@@ -34,6 +39,89 @@ export interface ProcessingConfig {
   enable_social_formats?: boolean;
 }
 
+export interface VideoChapter {
+  title: string;
+  start_time: number;
+  end_time: number;
+}
+
+export type CaptionKind = 'uploaded' | 'auto_generated' | 'auto_translated';
+
+export interface CaptionTrack {
+  language: string;
+  name: string;
+  kind: CaptionKind;
+}
+
+// A track from listCaptionTracks, to use instead of running Whisper
+export interface CaptionSelection {
+  language: string;
+  kind: CaptionKind;
+}
+
+export interface VideoSearchResult {
+  video_id: string;
+  title: string;
+  channel: string;
+  duration: number;
+  thumbnail: string;
+}
+
+// Every filter left out matches everything. Durations: short is under 4 minutes, medium 4 to 20,
+// long over 20. Dates are RFC 3339 or YYYY-MM-DD; captions only counts uploader-added ones
+export interface SearchFilters {
+  duration?: 'short' | 'medium' | 'long';
+  published_after?: string;
+  published_before?: string;
+  captions?: 'captioned' | 'uncaptioned';
+  license?: 'creative_commons' | 'standard';
+  order?: 'relevance' | 'date' | 'view_count' | 'rating' | 'title';
+}
+
+// Pass next_page_token back as pageToken for the following page; it's missing on the last one
+export interface SearchPage {
+  results: VideoSearchResult[];
+  next_page_token?: string;
+  total_results?: number;
+}
+
+// Private and deleted videos are left out, so positions can skip numbers
+export interface PlaylistItem {
+  video_id: string;
+  title: string;
+  position: number;
+  published_at?: string;
+  channel?: string;
+}
+
+export interface WatchedChannel {
+  channel_id: string;
+  uploads_playlist_id: string;
+  etag?: string;
+  last_published_at?: string;
+  added_at: string;
+  last_checked_at?: string;
+}
+
+// Payload of the 'youtube://new-uploads' event, sent when a watched channel has uploaded;
+// channels are checked every 30 minutes
+export interface NewUploads {
+  channel_id: string;
+  uploads: PlaylistItem[];
+}
+
+// Today's YouTube Data API usage as the app counted it; also the payload of the
+// 'youtube://quota-warning' event, sent once a day when usage passes 80% of the limit
+export interface QuotaUsage {
+  // The quota day, which starts at midnight Pacific time
+  day: string;
+  used: number;
+  daily_limit: number;
+  remaining: number;
+  by_endpoint: Record<string, number>;
+  near_limit: boolean;
+}
+
 // Advanced processing types
 export interface SpeechAnalysis {
   segments: TranscriptSegment[];
@@ -60,6 +148,27 @@ export interface ContentAnalysis {
   highlight_moments: HighlightMoment[];
   content_categories: string[];
   difficulty_level: string;
+  // From the video's comments, when analyzeContent was given its YouTube URL
+  audience_insights?: AudienceInsights | null;
+}
+
+export interface AudienceRequest {
+  text: string;
+  mentions: number;
+}
+
+export interface AudienceInsights {
+  questions: AudienceRequest[];
+  requested_topics: AudienceRequest[];
+  comments_analyzed: number;
+}
+
+export interface VideoComment {
+  author: string;
+  text: string;
+  like_count: number;
+  reply_count: number;
+  published_at: string;
 }
 
 export interface HighlightMoment {
@@ -81,6 +190,13 @@ export enum MomentType {
   Controversy = 'Controversy',
 }
 
+// A question and answer for an Anki deck, taken from one of the nuggets
+export interface Flashcard {
+  question: string;
+  answer: string;
+  nugget_id?: string;
+}
+
 export interface SocialMediaFormats {
   tiktok: string;
   instagram: string;
@@ -111,6 +227,227 @@ export interface BatchConfig {
   concurrent_jobs: number;
   retry_failed: boolean;
   max_retries: number;
+  output_template?: string;
+  skip_duplicates?: boolean;
+  project_id?: string;
+  disk_space_policy?: 'Refuse' | 'Warn' | 'Skip';
+  url_overrides?: Record<string, VideoOverride>;
+  stage?: 'Transcribe' | 'Analyze' | 'RenderClips';
+  download_pacing?: DownloadPacing;
+  csv_options?: CsvOptions;
+  formatting?: ExportFormatting;
+  // Which of each video's nuggets go into the exports; clips are still cut for all of them
+  export_filter?: ExportFilter;
+}
+
+export interface CsvOptions {
+  // A single ASCII character
+  delimiter: string;
+  // Lets Excel detect UTF-8
+  include_bom: boolean;
+}
+
+// Timecode is HH:MM:SS:FF at fps; dates other than Rfc3339 are in local time
+export type TimeFormat = 'Seconds' | 'Clock' | 'ClockMillis' | 'Timecode';
+export type DateFormat = 'Rfc3339' | 'Date' | 'DateTime' | 'Locale';
+
+export interface ExportFormatting {
+  time_format?: TimeFormat;
+  date_format?: DateFormat;
+  fps?: number;
+  // A BCP 47 tag such as "en-US", used by the Locale date format
+  locale?: string;
+}
+
+// Every set field narrows the export; best then keeps the top nuggets by engagement and
+// confidence, which need the video's AI analysis. Nuggets keep their order.
+export type ApiKeyProvider = 'OpenAI' | 'Claude' | 'Gemini' | 'YouTube';
+
+// Unknown when YouTube couldn't be reached, so the key may still be fine
+export type ApiKeyStatus =
+  | { status: 'valid' }
+  | { status: 'missing' }
+  | { status: 'invalid' }
+  | { status: 'restricted'; message: string }
+  | { status: 'quota_exhausted' }
+  | { status: 'unknown'; message: string };
+
+export interface YouTubePublisherStatus {
+  client_set: boolean;
+  authorized: boolean;
+}
+
+export type PrivacyStatus = 'private' | 'unlisted' | 'public';
+
+export interface TikTokAccount {
+  open_id: string;
+  display_name?: string | null;
+  added_at: string;
+}
+
+// A draft goes to the account's TikTok inbox to be finished in the app; privacy_level is one of
+// the creator's options and defaults to SELF_ONLY
+export interface TikTokPost {
+  caption: string;
+  mode?: 'direct' | 'draft';
+  privacy_level?: string | null;
+  disable_comment?: boolean;
+}
+
+// publish_at is RFC 3339; a scheduled upload stays private until then
+export interface UploadMetadata {
+  title: string;
+  description: string;
+  tags: string[];
+  privacy?: PrivacyStatus;
+  publish_at?: string | null;
+  category_id?: string | null;
+}
+
+// Unset tool paths are looked up on the PATH
+export interface ToolPaths {
+  ffmpeg?: string | null;
+  yt_dlp?: string | null;
+  whisper?: string | null;
+}
+
+export interface DeepLinkRequest {
+  url: string;
+  project_id?: string | null;
+  received_at: string;
+}
+
+export interface AppSettings {
+  // Only move_workspace changes it
+  workspace_path?: string | null;
+  verify_writes: boolean;
+  tools: ToolPaths;
+  defaults: {
+    download_quality: 'best' | 'worst' | '720p' | '480p';
+    export_formatting?: ExportFormatting | null;
+    hardware_encoding: boolean;
+    // Units a day the YouTube API key may spend
+    youtube_daily_quota: number;
+    // Two-letter country code for YouTube category names, e.g. 'US'
+    youtube_region: string;
+  };
+  // Server-Sent Events of operation progress on localhost, for dashboards and OBS overlays
+  progress_stream: {
+    enabled: boolean;
+    port: number;
+  };
+}
+
+export interface ExportFilter {
+  // A nugget needs at least one of them
+  include_tags?: string[];
+  exclude_tags?: string[];
+  // Seconds into the video
+  from_time?: number;
+  to_time?: number;
+  min_engagement?: number;
+  min_confidence?: number;
+  statuses?: VideoStatus[];
+  best?: number;
+}
+
+export type BundleFormat = 'Zip' | 'TarGz';
+
+export type ErrorCode =
+  | 'ToolMissing'
+  | 'Network'
+  | 'Timeout'
+  | 'QuotaExceeded'
+  | 'RateLimited'
+  | 'Unauthorized'
+  | 'NotFound'
+  | 'InvalidInput'
+  | 'InvalidResponse'
+  | 'Cancelled'
+  | 'ProcessFailed'
+  | 'Unknown';
+
+// What every command rejects with. TauriError.fromUnknown turns it into an Error
+export interface AppError {
+  code: ErrorCode;
+  message: string;
+  hint?: string | null;
+}
+
+export type LogLevel = 'Error' | 'Warn' | 'Info' | 'Debug' | 'Trace';
+
+export type OperationKind = 'Transcription' | 'Analysis' | 'Rendering' | 'Batch' | 'Workflow' | 'Download' | 'Upload';
+export type OperationState = 'Running' | 'Completed' | 'Failed' | 'Cancelled';
+
+// Payload of the 'operation://progress' event. percent and eta_seconds are for the current
+// stage and missing while it can't tell how far along it is
+export interface OperationProgress {
+  operation_id: string;
+  kind: OperationKind;
+  label: string;
+  state: OperationState;
+  stage: string;
+  percent?: number | null;
+  message?: string | null;
+  eta_seconds?: number | null;
+  started_at: string;
+}
+
+export interface HardwareEncoder {
+  name: string;
+  api: string;
+}
+
+export type WhisperAcceleration = 'Cuda' | 'Metal' | 'Cpu';
+
+export interface CapabilityReport {
+  ffmpeg_path?: string | null;
+  hardware_encoders: HardwareEncoder[];
+  whisper?: { path: string; acceleration: WhisperAcceleration } | null;
+  cpu_cores: number;
+  physical_cpu_cores?: number | null;
+  total_memory_mb: number;
+  workspace_free_bytes?: number | null;
+  temp_free_bytes?: number | null;
+  recommended: {
+    video_encoder: string;
+    max_ffmpeg_processes: number;
+    max_transcriptions: number;
+    download_quality: 'best' | 'worst' | '720p' | '480p';
+  };
+}
+
+export type ResumableCommand = 'ProcessVideo' | 'ExtractTranscript' | 'Batch';
+
+// An operation a crash cut short. To resume it, call its command again with params and this
+// operation_id (processVideoAdvanced or extractTranscript), or startBatchJob with params.job_id
+export interface InterruptedOperation {
+  operation_id: string;
+  kind: OperationKind;
+  label: string;
+  command: ResumableCommand;
+  params: Record<string, any>;
+  stage: string;
+  artifacts: Record<string, string>;
+  work_dirs: string[];
+  started_at: string;
+  updated_at: string;
+}
+
+// KeepFolders keeps paths below the folder the files share; ByType sorts them into
+// clips/, thumbnails/, subtitles/, exports/ and other/
+export type BundleLayout = 'Flat' | 'KeepFolders' | 'ByType';
+
+export interface DownloadPacing {
+  delay_seconds: number;
+  jitter_seconds: number;
+  max_per_host: number;
+}
+
+export interface VideoOverride {
+  nugget_duration?: number;
+  skip_transcription?: boolean;
+  quality?: 'best' | 'worst' | '720p' | '480p';
 }
 
 export enum BatchStatus {
@@ -130,6 +467,23 @@ export interface BatchProgress {
   percentage: number;
   eta_minutes?: number;
   start_time?: number;
+  active_videos?: VideoProgress[];
+}
+
+export type VideoStage =
+  | { Downloading: { percent?: number } }
+  | 'WaitingForResources'
+  | 'FindingNuggets'
+  | { RenderingClip: { current: number; total: number } }
+  | 'Transcribing'
+  | 'Analyzing'
+  | 'Exporting'
+  | { Retrying: { attempt: number } };
+
+export interface VideoProgress {
+  url: string;
+  index: number;
+  stage: VideoStage;
 }
 
 export interface BatchResult {
@@ -138,6 +492,8 @@ export interface BatchResult {
   nuggets: VideoNugget[];
   analysis?: ContentAnalysis;
   output_files: string[];
+  // Copied into the video's processing history when the job adds it to a project
+  events: ProcessingEvent[];
   status: ProcessingStatus;
   error_message?: string;
   processing_time_seconds: number;
@@ -163,6 +519,224 @@ export interface Project {
   collaborators: Collaborator[];
   settings: ProjectSettings;
   metadata: ProjectMetadata;
+  // Project-wide events; per-video events stay in each video's processing_history
+  activity: ProcessingEvent[];
+  collections: NuggetCollection[];
+  smart_collections: SmartCollection[];
+}
+
+// Projects as listed, without their videos; getProject loads the full project
+export type ProjectSummary = Omit<Project, 'videos' | 'activity' | 'collections' | 'smart_collections'>;
+
+export interface TrashedFile {
+  original_path: string;
+  trashed_path: string;
+}
+
+export type TrashedItem =
+  | { kind: 'Project'; project_id: string; name: string; original_path: string }
+  | {
+      kind: 'Video';
+      project_id: string;
+      project_name: string;
+      video_id: string;
+      title: string;
+      index: number;
+      files: TrashedFile[];
+    };
+
+export type TrashEntry = TrashedItem & {
+  id: string;
+  deleted_at: string;
+  // Purged automatically after this
+  expires_at: string;
+  size_bytes: number;
+};
+
+export interface EncryptionStatus {
+  enabled: boolean;
+  // Projects can only be listed and opened once unlocked
+  unlocked: boolean;
+}
+
+export interface NuggetRef {
+  video_id: string;
+  nugget_id: string;
+}
+
+export interface NuggetCollection {
+  id: string;
+  name: string;
+  description?: string;
+  items: NuggetRef[];
+  created_at: string;
+  updated_at: string;
+}
+
+export type Platform = 'TikTok' | 'YouTubeShorts' | 'InstagramReels' | 'LinkedIn';
+
+// Every set field narrows the results; bounds are inclusive
+export interface NuggetQuery {
+  text?: string;
+  // A nugget needs all of them
+  tags?: string[];
+  min_sentiment?: number;
+  max_sentiment?: number;
+  min_duration?: number;
+  max_duration?: number;
+  // Without a platform the best score on any platform counts
+  platform?: Platform;
+  min_platform_score?: number;
+}
+
+export interface SmartCollection {
+  id: string;
+  name: string;
+  query: NuggetQuery;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface CollectionNugget {
+  video_id: string;
+  video_title: string;
+  nugget: VideoNugget;
+}
+
+export interface ActivityFilter {
+  event_types?: EventType[];
+  video_id?: string;
+  // RFC 3339 timestamps or YYYY-MM-DD dates, both inclusive
+  from?: string;
+  to?: string;
+}
+
+export interface ActivityEntry extends ProcessingEvent {
+  video_id?: string;
+  video_title?: string;
+}
+
+export interface ActivityPage {
+  entries: ActivityEntry[];
+  page: number;
+  page_size: number;
+  total: number;
+}
+
+export type MergeStrategy = 'Skip' | 'Replace' | 'KeepBoth';
+
+export interface VideoStats {
+  video_id: string;
+  title: string;
+  status: VideoStatus;
+  nugget_count: number;
+  duration_seconds: number;
+  nugget_seconds: number;
+  processing_time_seconds: number;
+  storage_mb: number;
+}
+
+export interface DurationBucket {
+  min_seconds: number;
+  max_seconds?: number;
+  count: number;
+}
+
+export type StorageCategory = 'Clips' | 'Thumbnails' | 'Transcripts' | 'Exports' | 'Backups' | 'Versions' | 'Other';
+
+export interface StorageItem {
+  path: string;
+  bytes: number;
+  category: StorageCategory;
+  video_id?: string;
+}
+
+export interface StorageBreakdown {
+  total_bytes: number;
+  by_category: { category: StorageCategory; bytes: number; files: number }[];
+  largest: StorageItem[];
+}
+
+export interface ProjectStats {
+  total_videos: number;
+  total_nuggets: number;
+  total_duration_seconds: number;
+  total_nugget_seconds: number;
+  total_processing_seconds: number;
+  total_storage_mb: number;
+  videos: VideoStats[];
+  duration_distribution: DurationBucket[];
+  tag_frequencies: { tag: string; count: number }[];
+  sentiment_over_time: {
+    video_id: string;
+    title: string;
+    added_at: string;
+    sentiment: number;
+    engagement: number;
+  }[];
+}
+
+export interface DuplicateVideo {
+  project_id: string;
+  project_name: string;
+  video_id: string;
+  title: string;
+  nugget_count: number;
+  added_at: string;
+}
+
+export interface NuggetUpdate {
+  title?: string;
+  start_time?: number;
+  end_time?: number;
+  tags?: string[];
+  notes?: string;
+}
+
+export interface MergeSummary {
+  added: number;
+  skipped: number;
+  replaced: number;
+  kept_both: number;
+}
+
+export interface VersionInfo {
+  id: string;
+  created_at: string;
+  reason: string;
+  total_videos: number;
+  total_nuggets: number;
+}
+
+export interface NuggetChange {
+  video_id: string;
+  video_title: string;
+  nugget: VideoNugget;
+}
+
+export interface NuggetEdit {
+  video_id: string;
+  video_title: string;
+  before: VideoNugget;
+  after: VideoNugget;
+}
+
+export interface VersionDiff {
+  videos_added: string[];
+  videos_removed: string[];
+  nuggets_added: NuggetChange[];
+  nuggets_removed: NuggetChange[];
+  nuggets_edited: NuggetEdit[];
+  settings_changed: boolean;
+}
+
+// Payload of the "project-archive-progress" event during archive export and import
+export interface ArchiveProgress {
+  files_done: number;
+  files_total: number;
+  bytes_done: number;
+  bytes_total: number;
+  current_file: string;
 }
 
 export interface VideoProject {
@@ -170,6 +744,7 @@ export interface VideoProject {
   video_info: VideoInfo;
   nuggets: VideoNugget[];
   analysis?: ContentAnalysis;
+  output_files?: string[];
   processing_history: ProcessingEvent[];
   custom_tags: string[];
   notes: string;
@@ -196,6 +771,7 @@ export enum EventType {
   NotesUpdated = 'NotesUpdated',
   ConfigurationChanged = 'ConfigurationChanged',
   BatchProcessed = 'BatchProcessed',
+  NuggetsEdited = 'NuggetsEdited',
 }
 
 export enum VideoStatus {
@@ -243,9 +819,78 @@ export interface ProjectSettings {
   social_media_formats: boolean;
   backup_enabled: boolean;
   backup_interval_hours: number;
+  backup_retention?: BackupRetention;
   quality_presets: Record<string, QualityPreset>;
 }
 
+// A backup is kept if any rule keeps it; all zeros keeps every backup
+export interface BackupRetention {
+  keep_last: number;
+  keep_daily: number;
+  keep_weekly: number;
+}
+
+export interface BackupInfo {
+  path: string;
+  created_at: string;
+  size_bytes: number;
+}
+
+export type WorkflowAction = 'Transcribe' | 'Analyze' | 'DetectHighlights' | 'RenderClips' | 'Export';
+
+export type StepCondition =
+  | { kind: 'SettingEnabled'; setting: string }
+  | { kind: 'MinNuggets'; count: number }
+  | { kind: 'MinHighlights'; count: number }
+  | { kind: 'MinDurationSeconds'; seconds: number }
+  | { kind: 'HasTranscript' }
+  | { kind: 'HasAnalysis' };
+
+export interface WorkflowStep {
+  name: string;
+  description: string;
+  automated: boolean;
+  parameters: Record<string, any>;
+  // Steps without an action are left to the user
+  action?: WorkflowAction | null;
+  condition?: StepCondition | null;
+  continue_on_error?: boolean;
+}
+
+export type StepStatus = 'Started' | 'Completed' | 'Skipped' | 'Failed';
+
+// Payload of the 'workflow://step' event, sent when a step starts and when it ends
+export interface WorkflowStepEvent {
+  operation_id: string;
+  step_index: number;
+  step_count: number;
+  name: string;
+  action?: WorkflowAction | null;
+  status: StepStatus;
+  message?: string | null;
+  timestamp: string;
+}
+
+export interface WorkflowRunResult {
+  url: string;
+  video_info?: VideoInfo | null;
+  nuggets: VideoNugget[];
+  highlight_ids: string[];
+  analysis?: ContentAnalysis | null;
+  output_files: string[];
+  steps: WorkflowStepEvent[];
+}
+
+export interface ProjectTemplate {
+  id: string;
+  name: string;
+  description: string;
+  settings: ProjectSettings;
+  suggested_tags: string[];
+  workflow: WorkflowStep[];
+  built_in?: boolean;
+}
+
 export interface QualityPreset {
   name: string;
   video_quality: string;
@@ -277,11 +922,13 @@ export class TauriAPI {
     return await invoke('process_video', { url, config });
   }
 
+  // Pick an operationId to follow the 'operation://progress' events before the call returns
   static async processVideoAdvanced(
     url: string,
-    config: ProcessingConfig = {}
+    config: ProcessingConfig = {},
+    operationId?: string
   ): Promise<ProcessingResult> {
-    return await invoke('process_video_advanced', { url, config });
+    return await invoke('process_video_advanced', { url, config, operation_id: operationId ?? null });
   }
 
   // File management
@@ -292,29 +939,227 @@ export class TauriAPI {
     return await invoke('save_nuggets', { nuggets, filepath });
   }
 
+  // The file is watched afterwards; outside edits emit 'nugget-file-changed' with its path
   static async loadNuggets(filepath: string): Promise<VideoNugget[]> {
     return await invoke('load_nuggets', { filepath });
   }
 
+  // Resolves to the number of nuggets copied
+  static async copyExportToClipboard(
+    projectId: string,
+    nuggetIds: string[],
+    format: 'markdown' | 'text' | 'csv',
+    formatting?: ExportFormatting,
+    filter?: ExportFilter
+  ): Promise<number> {
+    return await invoke('copy_export_to_clipboard', {
+      project_id: projectId,
+      nugget_ids: nuggetIds,
+      format,
+      formatting: formatting ?? null,
+      filter: filter ?? null
+    });
+  }
+
+  static async unwatchNuggetFile(filepath: string): Promise<void> {
+    return await invoke('unwatch_nugget_file', { filepath });
+  }
+
+  // jsonl (or ndjson) writes a nugget per line plus a .schema.json next to it. srt and vtt write
+  // the nuggets themselves as cues, to load as markers in an editor.
+  // notes (markdown) is only used by the markdown format. With a project the export is recorded in
+  // its activity, or in the video's history when videoId is given too
   static async exportNuggets(
     nuggets: VideoNugget[],
-    format: 'json' | 'csv' | 'markdown',
-    filepath: string
+    format: 'json' | 'jsonl' | 'ndjson' | 'csv' | 'markdown' | 'anki' | 'pdf' | 'html' | 'srt' | 'vtt',
+    filepath: string,
+    notes?: string,
+    projectId?: string,
+    videoId?: string,
+    csvOptions?: CsvOptions,
+    formatting?: ExportFormatting,
+    filter?: ExportFilter
   ): Promise<string> {
-    return await invoke('export_nuggets', { nuggets, format, filepath });
+    return await invoke('export_nuggets', {
+      nuggets,
+      format,
+      filepath,
+      notes: notes ?? null,
+      project_id: projectId ?? null,
+      video_id: videoId ?? null,
+      csv_options: csvOptions ?? null,
+      formatting: formatting ?? null,
+      filter: filter ?? null
+    });
   }
 
   // Advanced processing features
-  static async extractTranscript(url: string): Promise<SpeechAnalysis> {
-    return await invoke('extract_transcript', { url });
+  // With captions the transcript comes from that caption track and Whisper isn't run
+  static async extractTranscript(url: string, operationId?: string, captions?: CaptionSelection): Promise<SpeechAnalysis> {
+    return await invoke('extract_transcript', { url, operation_id: operationId ?? null, captions: captions ?? null });
+  }
+
+  // Progress and cancellation go through the operation like any other; quality defaults to the one in settings
+  static async downloadVideo(url: string, outputPath: string, quality?: string, operationId?: string): Promise<string> {
+    return await invoke('download_video', {
+      url,
+      output_path: outputPath,
+      quality: quality ?? null,
+      operation_id: operationId ?? null,
+    });
+  }
+
+  static async getVideoChapters(url: string): Promise<VideoChapter[]> {
+    return await invoke('get_video_chapters', { url });
+  }
+
+  // One nugget per chapter; fails with NotFound for a video without chapters
+  static async getChapterNuggets(url: string): Promise<VideoNugget[]> {
+    return await invoke('get_chapter_nuggets', { url });
+  }
+
+  // Takes a video ID or a watch URL
+  static async listCaptionTracks(videoId: string): Promise<CaptionTrack[]> {
+    return await invoke('list_caption_tracks', { video_id: videoId });
+  }
+
+  // Search, channel and trending need a YouTube API key, see setApiKey('YouTube', ...)
+  static async searchYouTubeVideos(
+    query: string,
+    maxResults?: number,
+    pageToken?: string,
+    filters?: SearchFilters
+  ): Promise<SearchPage> {
+    return await invoke('search_youtube_videos', {
+      query,
+      filters: filters ?? null,
+      max_results: maxResults ?? null,
+      page_token: pageToken ?? null,
+    });
+  }
+
+  static async getYouTubeChannelVideos(channelId: string, maxResults?: number, pageToken?: string): Promise<SearchPage> {
+    return await invoke('get_youtube_channel_videos', {
+      channel_id: channelId,
+      max_results: maxResults ?? null,
+      page_token: pageToken ?? null,
+    });
+  }
+
+  // The region defaults to youtube_region in settings; categoryId is a key from getYouTubeCategories
+  static async getTrendingYouTubeVideos(
+    regionCode?: string,
+    maxResults?: number,
+    pageToken?: string,
+    categoryId?: string
+  ): Promise<SearchPage> {
+    return await invoke('get_trending_youtube_videos', {
+      region_code: regionCode ?? null,
+      category_id: categoryId ?? null,
+      max_results: maxResults ?? null,
+      page_token: pageToken ?? null,
+    });
+  }
+
+  // Category names by ID
+  static async getYouTubeCategories(regionCode?: string): Promise<Record<string, string>> {
+    return await invoke('get_youtube_categories', { region_code: regionCode ?? null });
+  }
+
+  // Every video of the playlist, however many pages that takes
+  static async getPlaylistItems(playlistId: string): Promise<PlaylistItem[]> {
+    return await invoke('get_playlist_items', { playlist_id: playlistId });
   }
 
+  static async watchChannel(channelUrl: string): Promise<WatchedChannel> {
+    return await invoke('watch_channel', { channel_url: channelUrl });
+  }
+
+  static async unwatchChannel(channelId: string): Promise<void> {
+    return await invoke('unwatch_channel', { channel_id: channelId });
+  }
+
+  static async listWatchedChannels(): Promise<WatchedChannel[]> {
+    return await invoke('list_watched_channels');
+  }
+
+  static async checkWatchedChannels(): Promise<NewUploads[]> {
+    return await invoke('check_watched_channels');
+  }
+
+  static async getApiQuotaUsage(): Promise<QuotaUsage> {
+    return await invoke('get_api_quota_usage');
+  }
+
+  // Takes a second or two, it test-encodes with every hardware encoder FFmpeg has
+  static async detectCapabilities(): Promise<CapabilityReport> {
+    return await invoke('detect_capabilities');
+  }
+
+  static async listOperations(): Promise<OperationProgress[]> {
+    return await invoke('list_operations');
+  }
+
+  // The operation reports itself Cancelled once it has stopped
+  static async cancelOperation(operationId: string): Promise<void> {
+    return await invoke('cancel_operation', { operation_id: operationId });
+  }
+
+  // Oldest first; worth checking on startup
+  static async listInterruptedOperations(): Promise<InterruptedOperation[]> {
+    return await invoke('list_interrupted_operations');
+  }
+
+  // Deletes what the operation left behind. Not for batch jobs, which stay paused instead
+  static async discardInterruptedOperation(operationId: string): Promise<void> {
+    return await invoke('discard_interrupted_operation', { operation_id: operationId });
+  }
+
+  // video-nugget://process links opened since the last call. Call it on startup and on every
+  // 'deep-link-received' event, and confirm each one before processing it
+  static async takePendingDeepLinks(): Promise<DeepLinkRequest[]> {
+    return await invoke('take_pending_deep_links');
+  }
+
+  // The /events URL while the progress stream is on
+  static async getProgressStreamUrl(): Promise<string | null> {
+    return await invoke('get_progress_stream_url');
+  }
+
+  // Newest lines of the app's log files, oldest first; at most 5000
+  static async getRecentLogs(maxLines?: number): Promise<string[]> {
+    return await invoke('get_recent_logs', { max_lines: maxLines ?? null });
+  }
+
+  // Back to Info when the app restarts
+  static async setLogLevel(level: LogLevel): Promise<void> {
+    return await invoke('set_log_level', { level });
+  }
+
+  // Re-analyzing a video already in a project records the run in its history
   static async analyzeContent(
     transcript: string,
     title: string,
-    description?: string
+    description?: string,
+    projectId?: string,
+    videoId?: string,
+    operationId?: string,
+    sourceUrl?: string
   ): Promise<ContentAnalysis> {
-    return await invoke('analyze_content', { transcript, title, description });
+    return await invoke('analyze_content', {
+      transcript,
+      title,
+      description,
+      project_id: projectId ?? null,
+      video_id: videoId ?? null,
+      operation_id: operationId ?? null,
+      source_url: sourceUrl ?? null
+    });
+  }
+
+  // videoId is YouTube's id for the video; needs a YouTube API key
+  static async getVideoComments(videoId: string, maxResults?: number): Promise<VideoComment[]> {
+    return await invoke('get_video_comments', { video_id: videoId, max_results: maxResults ?? null });
   }
 
   static async generateSubtitles(
@@ -327,8 +1172,8 @@ export class TauriAPI {
     });
   }
 
-  static async createSocialFormats(videoPath: string): Promise<SocialMediaFormats> {
-    return await invoke('create_social_formats', { video_path: videoPath });
+  static async createSocialFormats(videoPath: string, operationId?: string): Promise<SocialMediaFormats> {
+    return await invoke('create_social_formats', { video_path: videoPath, operation_id: operationId ?? null });
   }
 
   // Batch processing
@@ -340,6 +1185,27 @@ export class TauriAPI {
     return await invoke('create_batch_job', { name, urls, config });
   }
 
+  static async createBatchFromPlaylist(playlistUrl: string, name: string, config: BatchConfig): Promise<string> {
+    return await invoke('create_batch_from_playlist', { playlist_url: playlistUrl, name, config });
+  }
+
+  // The top count (25 by default, 200 at most) trending videos; name defaults to the category and region
+  static async createBatchFromTrending(
+    config: BatchConfig,
+    categoryId?: string,
+    regionCode?: string,
+    count?: number,
+    name?: string
+  ): Promise<string> {
+    return await invoke('create_batch_from_trending', {
+      region_code: regionCode ?? null,
+      category_id: categoryId ?? null,
+      count: count ?? null,
+      name: name ?? null,
+      config,
+    });
+  }
+
   static async startBatchJob(jobId: string): Promise<void> {
     return await invoke('start_batch_job', { job_id: jobId });
   }
@@ -356,6 +1222,25 @@ export class TauriAPI {
     return await invoke('list_batch_jobs');
   }
 
+  // Resolves to the number of files in the bundle
+  static async bundleBatchOutput(
+    jobId: string,
+    bundlePath: string,
+    format: BundleFormat,
+    layout: BundleLayout
+  ): Promise<number> {
+    return await invoke('bundle_batch_output', { job_id: jobId, bundle_path: bundlePath, format, layout });
+  }
+
+  static async bundleExportFiles(
+    files: string[],
+    bundlePath: string,
+    format: BundleFormat,
+    layout: BundleLayout
+  ): Promise<number> {
+    return await invoke('bundle_export_files', { files, bundle_path: bundlePath, format, layout });
+  }
+
   // Project management
   static async createProject(
     name: string,
@@ -365,17 +1250,57 @@ export class TauriAPI {
     return await invoke('create_project', { name, description, template_id: templateId });
   }
 
+  static async getTemplates(): Promise<ProjectTemplate[]> {
+    return await invoke('get_templates');
+  }
+
+  // Leave id empty to create a new template; resolves to the template's ID
+  static async saveProjectTemplate(template: ProjectTemplate): Promise<string> {
+    return await invoke('save_project_template', { template });
+  }
+
+  static async deleteProjectTemplate(templateId: string): Promise<void> {
+    return await invoke('delete_project_template', { template_id: templateId });
+  }
+
+  static async exportProjectTemplate(templateId: string, exportPath: string): Promise<void> {
+    return await invoke('export_project_template', { template_id: templateId, export_path: exportPath });
+  }
+
+  static async importProjectTemplate(importPath: string): Promise<string> {
+    return await invoke('import_project_template', { import_path: importPath });
+  }
+
+  // With a projectId the video is added to that project and its settings are used
+  static async runWorkflow(
+    templateId: string,
+    url: string,
+    projectId?: string,
+    outputDirectory?: string,
+    operationId?: string
+  ): Promise<WorkflowRunResult> {
+    return await invoke('run_workflow', {
+      template_id: templateId,
+      url,
+      project_id: projectId ?? null,
+      output_directory: outputDirectory ?? null,
+      operation_id: operationId ?? null
+    });
+  }
+
   static async addVideoToProject(
     projectId: string,
     videoInfo: VideoInfo,
     nuggets: VideoNugget[],
-    analysis?: ContentAnalysis
+    analysis?: ContentAnalysis,
+    events?: ProcessingEvent[]
   ): Promise<string> {
     return await invoke('add_video_to_project', { 
       project_id: projectId, 
       video_info: videoInfo, 
       nuggets, 
-      analysis 
+      analysis,
+      events: events ?? null
     });
   }
 
@@ -383,7 +1308,7 @@ export class TauriAPI {
     return await invoke('get_project', { project_id: projectId });
   }
 
-  static async listProjects(): Promise<Project[]> {
+  static async listProjects(): Promise<ProjectSummary[]> {
     return await invoke('list_projects');
   }
 
@@ -397,10 +1322,49 @@ export class TauriAPI {
     });
   }
 
+  // Moves the project to the trash; restoreFromTrash brings it back within the retention window
   static async deleteProject(projectId: string): Promise<void> {
     return await invoke('delete_project', { project_id: projectId });
   }
 
+  static async listTrash(): Promise<TrashEntry[]> {
+    return await invoke('list_trash');
+  }
+
+  // A trashed video needs its project restored first
+  static async restoreFromTrash(entryId: string): Promise<TrashEntry> {
+    return await invoke('restore_from_trash', { entry_id: entryId });
+  }
+
+  // Deletes for good; without an entry ID the whole trash is emptied. Returns how many entries went
+  static async purgeTrash(entryId?: string): Promise<number> {
+    return await invoke('purge_trash', { entry_id: entryId });
+  }
+
+  static async getEncryptionStatus(): Promise<EncryptionStatus> {
+    return await invoke('get_encryption_status');
+  }
+
+  // Encrypts project data, versions, backups and trashed videos with a key derived from the passphrase.
+  // There is no way back in without it
+  static async enableEncryption(passphrase: string): Promise<void> {
+    return await invoke('enable_encryption', { passphrase });
+  }
+
+  static async unlockWorkspace(passphrase: string): Promise<void> {
+    return await invoke('unlock_workspace', { passphrase });
+  }
+
+  static async disableEncryption(passphrase: string): Promise<void> {
+    return await invoke('disable_encryption', { passphrase });
+  }
+
+  // New IDs throughout; media is hard-linked or copied into the new project when included
+  static async cloneProject(projectId: string, newName: string, includeMedia: boolean): Promise<string> {
+    return await invoke('clone_project', { project_id: projectId, new_name: newName, include_media: includeMedia });
+  }
+
+  // Also writes <name>.manifest.json next to the export with its size and SHA-256
   static async exportProject(
     projectId: string,
     exportPath: string,
@@ -417,6 +1381,542 @@ export class TauriAPI {
     return await invoke('import_project', { import_path: importPath });
   }
 
+  static async mergeImportProject(
+    projectId: string,
+    importPath: string,
+    strategy: MergeStrategy
+  ): Promise<MergeSummary> {
+    return await invoke('merge_import_project', {
+      project_id: projectId,
+      import_path: importPath,
+      strategy
+    });
+  }
+
+  static async getVideoNotes(projectId: string, videoId: string): Promise<string> {
+    return await invoke('get_video_notes', { project_id: projectId, video_id: videoId });
+  }
+
+  static async setVideoNotes(projectId: string, videoId: string, notes: string): Promise<void> {
+    return await invoke('set_video_notes', { project_id: projectId, video_id: videoId, notes });
+  }
+
+  static async getNuggetNotes(
+    projectId: string,
+    videoId: string,
+    nuggetId: string
+  ): Promise<string | null> {
+    return await invoke('get_nugget_notes', {
+      project_id: projectId,
+      video_id: videoId,
+      nugget_id: nuggetId
+    });
+  }
+
+  // An empty string clears the notes
+  static async setNuggetNotes(
+    projectId: string,
+    videoId: string,
+    nuggetId: string,
+    notes: string
+  ): Promise<void> {
+    return await invoke('set_nugget_notes', {
+      project_id: projectId,
+      video_id: videoId,
+      nugget_id: nuggetId,
+      notes
+    });
+  }
+
+  static async exportVideoMarkdown(
+    projectId: string,
+    videoId: string,
+    filepath: string,
+    formatting?: ExportFormatting,
+    filter?: ExportFilter
+  ): Promise<string> {
+    return await invoke('export_video_markdown', {
+      project_id: projectId,
+      video_id: videoId,
+      filepath,
+      formatting: formatting ?? null,
+      filter: filter ?? null
+    });
+  }
+
+  static async suggestFlashcards(nuggets: VideoNugget[]): Promise<Flashcard[]> {
+    return await invoke('suggest_flashcards', { nuggets });
+  }
+
+  // An .apkg deck with the video's nuggets and the given flashcards
+  static async exportVideoAnki(
+    projectId: string,
+    videoId: string,
+    filepath: string,
+    flashcards?: Flashcard[],
+    filter?: ExportFilter
+  ): Promise<string> {
+    return await invoke('export_video_anki', {
+      project_id: projectId,
+      video_id: videoId,
+      filepath,
+      flashcards: flashcards ?? null,
+      filter: filter ?? null
+    });
+  }
+
+  // Without videoId the page covers every video in the project
+  static async exportProjectHtml(projectId: string, filepath: string, videoId?: string, filter?: ExportFilter): Promise<string> {
+    return await invoke('export_project_html', { project_id: projectId, video_id: videoId ?? null, filepath, filter: filter ?? null });
+  }
+
+  static async exportVideoPdf(
+    projectId: string,
+    videoId: string,
+    filepath: string,
+    formatting?: ExportFormatting,
+    filter?: ExportFilter
+  ): Promise<string> {
+    return await invoke('export_video_pdf', {
+      project_id: projectId,
+      video_id: videoId,
+      filepath,
+      formatting: formatting ?? null,
+      filter: filter ?? null
+    });
+  }
+
+  // Writes the project, its videos and nuggets as linked notes into the vault folder
+  static async exportProjectObsidian(projectId: string, directory: string, filter?: ExportFilter): Promise<string> {
+    return await invoke('export_project_obsidian', { project_id: projectId, directory, filter: filter ?? null });
+  }
+
+  // Keys live in the OS keychain and are never sent back, only whether one is set
+  static async getApiKeySet(provider: ApiKeyProvider): Promise<boolean> {
+    return await invoke('get_api_key_set', { provider });
+  }
+
+  static async setApiKey(provider: ApiKeyProvider, key: string): Promise<void> {
+    return await invoke('set_api_key', { provider, key });
+  }
+
+  static async clearApiKey(provider: ApiKeyProvider): Promise<void> {
+    return await invoke('clear_api_key', { provider });
+  }
+
+  // Tests key, or the stored key when none is given; rejects with the provider's error
+  static async testApiKey(provider: ApiKeyProvider, key?: string): Promise<void> {
+    return await invoke('test_api_key', { provider, key: key ?? null });
+  }
+
+  // Costs one quota unit; resolves with the key's status rather than rejecting on a bad key
+  static async validateYouTubeApiKey(key?: string): Promise<ApiKeyStatus> {
+    return await invoke('validate_youtube_api_key', { key: key ?? null });
+  }
+
+  static async getReadwiseTokenSet(): Promise<boolean> {
+    return await invoke('get_readwise_token_set');
+  }
+
+  // Checked with Readwise, then kept in the OS keychain; an empty token removes it
+  static async setReadwiseToken(token: string): Promise<void> {
+    return await invoke('set_readwise_token', { token });
+  }
+
+  // Sends the video's AI summary and a highlight per nugget; resolves to how many were sent
+  static async exportVideoReadwise(projectId: string, videoId: string, filter?: ExportFilter): Promise<number> {
+    return await invoke('export_video_readwise', { project_id: projectId, video_id: videoId, filter: filter ?? null });
+  }
+
+  static async getYouTubePublisherStatus(): Promise<YouTubePublisherStatus> {
+    return await invoke('get_youtube_publisher_status');
+  }
+
+  // A Desktop app OAuth client from the Google Cloud console, with the YouTube Data API enabled
+  static async setYouTubeOAuthClient(clientId: string, clientSecret: string): Promise<void> {
+    return await invoke('set_youtube_oauth_client', { client_id: clientId, client_secret: clientSecret });
+  }
+
+  // Opens Google's sign-in in the browser; resolves once the user has signed in
+  static async connectYouTubeAccount(): Promise<void> {
+    return await invoke('connect_youtube_account');
+  }
+
+  static async disconnectYouTubeAccount(): Promise<void> {
+    return await invoke('disconnect_youtube_account');
+  }
+
+  static async draftYouTubeUpload(projectId: string, videoId: string, nuggetId: string): Promise<UploadMetadata> {
+    return await invoke('draft_youtube_upload', { project_id: projectId, video_id: videoId, nugget_id: nuggetId });
+  }
+
+  // Resolves with the new YouTube video ID, which is also kept on the nugget. Costs 1600 quota units
+  static async publishNuggetToYouTube(
+    projectId: string,
+    videoId: string,
+    nuggetId: string,
+    clipPath: string,
+    metadata?: UploadMetadata,
+    operationId?: string
+  ): Promise<string> {
+    return await invoke('publish_nugget_to_youtube', {
+      project_id: projectId,
+      video_id: videoId,
+      nugget_id: nuggetId,
+      clip_path: clipPath,
+      metadata: metadata ?? null,
+      operation_id: operationId ?? null,
+    });
+  }
+
+  static async getTikTokClientSet(): Promise<boolean> {
+    return await invoke('get_tiktok_client_set');
+  }
+
+  // redirectPort is the port of the redirect URI registered with the TikTok app, http://127.0.0.1:<port>/callback/
+  static async setTikTokClient(clientKey: string, clientSecret: string, redirectPort: number): Promise<void> {
+    return await invoke('set_tiktok_client', { client_key: clientKey, client_secret: clientSecret, redirect_port: redirectPort });
+  }
+
+  static async listTikTokAccounts(): Promise<TikTokAccount[]> {
+    return await invoke('list_tiktok_accounts');
+  }
+
+  // Opens TikTok's sign-in in the browser; resolves with the account once the user has signed in
+  static async connectTikTokAccount(): Promise<TikTokAccount> {
+    return await invoke('connect_tiktok_account');
+  }
+
+  static async removeTikTokAccount(openId: string): Promise<void> {
+    return await invoke('remove_tiktok_account', { open_id: openId });
+  }
+
+  static async draftTikTokCaption(projectId: string, videoId: string): Promise<string> {
+    return await invoke('draft_tiktok_caption', { project_id: projectId, video_id: videoId });
+  }
+
+  // Without a post, or with an empty caption, the drafted caption is used and the clip goes up as a draft.
+  // Resolves with the publish ID for getTikTokPostStatus
+  static async publishToTikTok(
+    openId: string,
+    projectId: string,
+    videoId: string,
+    clipPath: string,
+    post?: TikTokPost,
+    operationId?: string
+  ): Promise<string> {
+    return await invoke('publish_to_tiktok', {
+      open_id: openId,
+      project_id: projectId,
+      video_id: videoId,
+      clip_path: clipPath,
+      post: post ?? null,
+      operation_id: operationId ?? null,
+    });
+  }
+
+  static async getTikTokPostStatus(openId: string, publishId: string): Promise<string> {
+    return await invoke('get_tiktok_post_status', { open_id: openId, publish_id: publishId });
+  }
+
+  // directory is the site's content folder, e.g. content/nuggets for Hugo
+  static async exportProjectStaticSite(
+    projectId: string,
+    generator: 'hugo' | 'jekyll',
+    directory: string,
+    filter?: ExportFilter
+  ): Promise<string> {
+    return await invoke('export_project_static_site', { project_id: projectId, generator, directory, filter: filter ?? null });
+  }
+
+  static async getProjectStats(projectId: string): Promise<ProjectStats> {
+    return await invoke('get_project_stats', { project_id: projectId });
+  }
+
+  // Newest first; pages start at 0
+  static async getActivity(projectId: string, filter?: ActivityFilter, page?: number): Promise<ActivityPage> {
+    return await invoke('get_activity', { project_id: projectId, filter, page });
+  }
+
+  static async getCollections(projectId: string): Promise<NuggetCollection[]> {
+    return await invoke('get_collections', { project_id: projectId });
+  }
+
+  static async createCollection(projectId: string, name: string, description?: string, items?: NuggetRef[]): Promise<string> {
+    return await invoke('create_collection', { project_id: projectId, name, description, items });
+  }
+
+  // Omitted fields are left as they are; items replaces the whole ordered list
+  static async updateCollection(
+    projectId: string,
+    collectionId: string,
+    changes: { name?: string; description?: string; items?: NuggetRef[] }
+  ): Promise<void> {
+    return await invoke('update_collection', { project_id: projectId, collection_id: collectionId, ...changes });
+  }
+
+  static async addToCollection(projectId: string, collectionId: string, items: NuggetRef[]): Promise<void> {
+    return await invoke('add_to_collection', { project_id: projectId, collection_id: collectionId, items });
+  }
+
+  static async deleteCollection(projectId: string, collectionId: string): Promise<void> {
+    return await invoke('delete_collection', { project_id: projectId, collection_id: collectionId });
+  }
+
+  // In collection order; nuggets deleted since they were added are left out
+  static async getCollectionNuggets(projectId: string, collectionId: string): Promise<CollectionNugget[]> {
+    return await invoke('get_collection_nuggets', { project_id: projectId, collection_id: collectionId });
+  }
+
+  static async searchProjectNuggets(projectId: string, query: NuggetQuery): Promise<CollectionNugget[]> {
+    return await invoke('search_project_nuggets', { project_id: projectId, query });
+  }
+
+  static async getSmartCollections(projectId: string): Promise<SmartCollection[]> {
+    return await invoke('get_smart_collections', { project_id: projectId });
+  }
+
+  static async createSmartCollection(projectId: string, name: string, query: NuggetQuery): Promise<string> {
+    return await invoke('create_smart_collection', { project_id: projectId, name, query });
+  }
+
+  static async updateSmartCollection(projectId: string, collectionId: string, changes: { name?: string; query?: NuggetQuery }): Promise<void> {
+    return await invoke('update_smart_collection', { project_id: projectId, collection_id: collectionId, ...changes });
+  }
+
+  static async deleteSmartCollection(projectId: string, collectionId: string): Promise<void> {
+    return await invoke('delete_smart_collection', { project_id: projectId, collection_id: collectionId });
+  }
+
+  // Runs the saved query against the project as it is now
+  static async getSmartCollectionNuggets(projectId: string, collectionId: string): Promise<CollectionNugget[]> {
+    return await invoke('get_smart_collection_nuggets', { project_id: projectId, collection_id: collectionId });
+  }
+
+  static async exportCollection(
+    projectId: string,
+    collectionId: string,
+    format: 'json' | 'csv' | 'markdown',
+    filepath: string,
+    formatting?: ExportFormatting,
+    filter?: ExportFilter
+  ): Promise<string> {
+    return await invoke('export_collection', {
+      project_id: projectId,
+      collection_id: collectionId,
+      format,
+      filepath,
+      formatting: formatting ?? null,
+      filter: filter ?? null
+    });
+  }
+
+  // The biggest files come first, for picking what to clean up
+  static async getStorageBreakdown(projectId: string, limit?: number): Promise<StorageBreakdown> {
+    return await invoke('get_storage_breakdown', { project_id: projectId, limit });
+  }
+
+  static async getWorkspacePath(): Promise<string> {
+    return await invoke('get_workspace_path');
+  }
+
+  // Moves every project, setting and batch job; fails while a batch job is running
+  static async moveWorkspace(newPath: string): Promise<void> {
+    return await invoke('move_workspace', { new_path: newPath });
+  }
+
+  static async getVerifyWrites(): Promise<boolean> {
+    return await invoke('get_verify_writes');
+  }
+
+  // Reads every saved file back to check it, at the cost of slower saves
+  static async setVerifyWrites(enabled: boolean): Promise<void> {
+    return await invoke('set_verify_writes', { enabled });
+  }
+
+  static async getSettings(): Promise<AppSettings> {
+    return await invoke('get_settings');
+  }
+
+  // Tool paths have to exist; returns the settings as saved
+  static async updateSettings(settings: AppSettings): Promise<AppSettings> {
+    return await invoke('update_settings', { settings });
+  }
+
+  // Returns the project's new folder, <destination>/<project id>
+  static async moveProject(projectId: string, destination: string): Promise<string> {
+    return await invoke('move_project', { project_id: projectId, destination });
+  }
+
+  // Check before processing a URL; a match can be linked instead of processed again
+  static async findDuplicateVideos(url: string): Promise<DuplicateVideo[]> {
+    return await invoke('find_duplicate_videos', { url });
+  }
+
+  static async linkExistingVideo(
+    projectId: string,
+    sourceProjectId: string,
+    videoId: string
+  ): Promise<string> {
+    return await invoke('link_existing_video', {
+      project_id: projectId,
+      source_project_id: sourceProjectId,
+      video_id: videoId
+    });
+  }
+
+  // The video goes to the trash; deleteFiles moves its rendered clips and exports there too
+  static async removeVideoFromProject(
+    projectId: string,
+    videoId: string,
+    deleteFiles: boolean = false
+  ): Promise<void> {
+    return await invoke('remove_video_from_project', {
+      project_id: projectId,
+      video_id: videoId,
+      delete_files: deleteFiles
+    });
+  }
+
+  static async archiveVideo(
+    projectId: string,
+    videoId: string,
+    archived: boolean = true,
+    deleteFiles: boolean = false
+  ): Promise<void> {
+    return await invoke('archive_video', {
+      project_id: projectId,
+      video_id: videoId,
+      archived,
+      delete_files: deleteFiles
+    });
+  }
+
+  static async updateNugget(
+    projectId: string,
+    videoId: string,
+    nuggetId: string,
+    update: NuggetUpdate
+  ): Promise<void> {
+    return await invoke('update_nugget', {
+      project_id: projectId,
+      video_id: videoId,
+      nugget_id: nuggetId,
+      update
+    });
+  }
+
+  // Resolves to the ID of the new second part
+  static async splitNugget(
+    projectId: string,
+    videoId: string,
+    nuggetId: string,
+    at: number
+  ): Promise<string> {
+    return await invoke('split_nugget', {
+      project_id: projectId,
+      video_id: videoId,
+      nugget_id: nuggetId,
+      at
+    });
+  }
+
+  static async mergeNuggets(
+    projectId: string,
+    videoId: string,
+    firstId: string,
+    secondId: string
+  ): Promise<void> {
+    return await invoke('merge_nuggets', {
+      project_id: projectId,
+      video_id: videoId,
+      first_id: firstId,
+      second_id: secondId
+    });
+  }
+
+  static async reorderNuggets(
+    projectId: string,
+    videoId: string,
+    nuggetIds: string[]
+  ): Promise<void> {
+    return await invoke('reorder_nuggets', {
+      project_id: projectId,
+      video_id: videoId,
+      nugget_ids: nuggetIds
+    });
+  }
+
+  // An SRT/VTT file or a chapter list ("0:00 Intro" per line); returns how many nuggets were added
+  static async importNuggets(
+    projectId: string,
+    videoId: string,
+    filepath: string,
+    replace?: boolean
+  ): Promise<number> {
+    return await invoke('import_nuggets', {
+      project_id: projectId,
+      video_id: videoId,
+      filepath,
+      replace: replace ?? null
+    });
+  }
+
+  static async deleteProjectNuggets(
+    projectId: string,
+    videoId: string,
+    nuggetIds: string[]
+  ): Promise<number> {
+    return await invoke('delete_project_nuggets', {
+      project_id: projectId,
+      video_id: videoId,
+      nugget_ids: nuggetIds
+    });
+  }
+
+  // Both resolve to a description of the change, or null when there was nothing to undo/redo
+  static async undoLastChange(): Promise<string | null> {
+    return await invoke('undo_last_change');
+  }
+
+  static async redo(): Promise<string | null> {
+    return await invoke('redo');
+  }
+
+  static async createProjectBackup(projectId: string): Promise<string> {
+    return await invoke('create_project_backup', { project_id: projectId });
+  }
+
+  static async listProjectBackups(projectId: string): Promise<BackupInfo[]> {
+    return await invoke('list_project_backups', { project_id: projectId });
+  }
+
+  // Resolves to the path of the backup taken of the state being replaced
+  static async restoreFromBackup(projectId: string, backupPath: string): Promise<string> {
+    return await invoke('restore_from_backup', { project_id: projectId, backup_path: backupPath });
+  }
+
+  static async listProjectVersions(projectId: string): Promise<VersionInfo[]> {
+    return await invoke('list_project_versions', { project_id: projectId });
+  }
+
+  // Leave toVersion out to compare against the current project
+  static async diffProjectVersions(
+    projectId: string,
+    fromVersion: string,
+    toVersion?: string
+  ): Promise<VersionDiff> {
+    return await invoke('diff_project_versions', {
+      project_id: projectId,
+      from_version: fromVersion,
+      to_version: toVersion ?? null
+    });
+  }
+
+  static async rollbackProject(projectId: string, versionId: string): Promise<void> {
+    return await invoke('rollback_project', { project_id: projectId, version_id: versionId });
+  }
+
   // Utility functions
   static async getAppVersion(): Promise<string> {
     return await invoke('get_app_version');
@@ -425,23 +1925,42 @@ export class TauriAPI {
   static async openFile(filepath: string): Promise<void> {
     return await invoke('open_file', { filepath });
   }
+
+  // Shows the file selected in Finder or Explorer
+  static async revealInFileManager(path: string): Promise<void> {
+    return await invoke('reveal_in_file_manager', { path });
+  }
 }
 
 // Error handling utility
 export class TauriError extends Error {
-  constructor(message: string, public readonly original?: unknown) {
+  constructor(
+    message: string,
+    public readonly original?: unknown,
+    public readonly code: ErrorCode = 'Unknown',
+    public readonly hint?: string | null
+  ) {
     super(message);
     this.name = 'TauriError';
   }
 
   static fromUnknown(error: unknown): TauriError {
+    if (error instanceof TauriError) {
+      return error;
+    }
     if (error instanceof Error) {
       return new TauriError(error.message, error);
     }
+    if (isAppError(error)) {
+      return new TauriError(error.message, error, error.code, error.hint);
+    }
     return new TauriError(String(error), error);
   }
 }
 
+const isAppError = (error: unknown): error is AppError =>
+  typeof error === 'object' && error !== null && 'code' in error && 'message' in error;
+
 // Safe invoke wrapper
 export const safeTauriInvoke = async <T>(
   command: string,
@@ -496,7 +2015,7 @@ export const useVideoProcessor = () => {
       setResult(result);
       return result;
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
+      const errorMessage = TauriError.fromUnknown(err).message;
       setError(errorMessage);
       throw err;
     } finally {
@@ -543,7 +2062,7 @@ export const useBatchProcessor = () => {
 };
 
 export const useProjectManager = () => {
-  const [projects, setProjects] = useState<Project[]>([]);
+  const [projects, setProjects] = useState<ProjectSummary[]>([]);
   const [currentProject, setCurrentProject] = useState<Project | null>(null);
   const [loading, setLoading] = useState(false);
 
@@ -575,9 +2094,10 @@ export const useProjectManager = () => {
     projectId: string,
     videoInfo: VideoInfo,
     nuggets: VideoNugget[],
-    analysis?: ContentAnalysis
+    analysis?: ContentAnalysis,
+    events?: ProcessingEvent[]
   ) => {
-    const videoId = await TauriAPI.addVideoToProject(projectId, videoInfo, nuggets, analysis);
+    const videoId = await TauriAPI.addVideoToProject(projectId, videoInfo, nuggets, analysis, events);
     await refreshProjects();
     if (currentProject?.id === projectId) {
       await loadProject(projectId);
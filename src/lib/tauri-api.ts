@@ -9,6 +9,9 @@ export interface VideoNugget {
   end_time: number;
   transcript?: string;
   tags: string[];
+  notes?: string;
+  // Set once the nugget has been uploaded with publishNuggetToYouTube
+  youtube_video_id?: string | null;
   created_at: string;
 }
 
@@ -104,6 +107,9 @@ export class TauriError extends Error {
     if (error instanceof Error) {
       return new TauriError(error.message, error);
     }
+    if (typeof error === 'object' && error !== null && 'message' in error) {
+      return new TauriError(String(error.message), error);
+    }
     return new TauriError(String(error), error);
   }
 }